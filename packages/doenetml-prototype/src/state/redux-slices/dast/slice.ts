@@ -113,9 +113,8 @@ const dastSlice = createSlice({
                     // @ts-ignore
                     Object.assign(elm.data.props, update.changedState);
                 }
-                // XXX: Check the types to see if this is even possible
-                if ((update as any).changedAttributes) {
-                    console.warn("Updating attributes is not yet implemented");
+                if (update.changedAttributes) {
+                    Object.assign(elm.attributes, update.changedAttributes);
                 }
                 if (update.newChildren) {
                     elm.children = update.newChildren;
@@ -342,7 +342,13 @@ export class RefResolutionDependency extends Dependency {
             );
         } catch (e) {
             // console.log("resolve error", e);
-            if (e === "NonUniqueReferent" || e === "NoReferent") {
+            // Resolution errors from the Rust core are objects tagged with their `kind`.
+            const errorKind = (e as { kind?: unknown } | null | undefined)
+                ?.kind;
+            if (
+                errorKind === "NonUniqueReferent" ||
+                errorKind === "NoReferent"
+            ) {
                 const referenceText = getDoenetMLStringForReference();
 
                 // TODO: these message match the messages from `format_error_message` of `ref_resolve.ts`.
@@ -354,7 +360,7 @@ export class RefResolutionDependency extends Dependency {
                         // Spread rather than a ternary on the value: the
                         // code has to sit next to `code:` as a literal, or
                         // `lint:i18n` reads it as a code nothing raises.
-                        ...(e === "NonUniqueReferent"
+                        ...(errorKind === "NonUniqueReferent"
                             ? { code: "doenet-w0105" as const }
                             : { code: "doenet-w0104" as const }),
                         args: { reference: `$${referenceText}` },
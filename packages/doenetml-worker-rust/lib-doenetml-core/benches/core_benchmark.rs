@@ -105,7 +105,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     let dast_root = dast_root_no_position(THOUSAND_CHAIN);
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
-    core.to_flat_dast();
+    core.to_flat_dast().unwrap();
     c.bench_function("dispatch_action with thousand chain", |b| {
         b.iter(|| {
             core.dispatch_action(Action {
@@ -148,7 +148,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     let dast_root = dast_root_no_position(REVERSE_THOUSAND_CHAIN);
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
-    core.to_flat_dast();
+    core.to_flat_dast().unwrap();
     c.bench_function("dispatch_action with reverse thousand chain", |b| {
         b.iter(|| {
             core.dispatch_action(Action {
@@ -200,8 +200,18 @@ impl Component {
     }
 
     /// Create a new `_Error` component instance.
-    pub fn new_error(message: String, common: ComponentCommonData) -> Self {
-        let variant = ComponentEnum::_Error(_Error { message });
+    ///
+    /// If the error came from resolving a reference, `path_part_position` is the position of the part of the path
+    /// that failed to resolve.
+    pub fn new_error(
+        message: String,
+        path_part_position: Option<DastPosition>,
+        common: ComponentCommonData,
+    ) -> Self {
+        let variant = ComponentEnum::_Error(_Error {
+            message,
+            path_part_position,
+        });
         Self { common, variant }
     }
 
@@ -6,6 +6,7 @@ pub use super::_external::_External;
 pub use super::_ref::_Ref;
 pub use super::doenet::_fragment::_Fragment;
 pub use super::doenet::boolean::Boolean;
+pub use super::doenet::call_action::CallAction;
 pub use super::doenet::division::Division;
 pub use super::doenet::document::Document;
 pub use super::doenet::graph::Graph;
@@ -18,8 +19,12 @@ pub use super::doenet::point::Point;
 pub use super::doenet::text::Text;
 pub use super::doenet::text_input::TextInput;
 pub use super::doenet::title::Title;
+pub use super::doenet::trigger_set::TriggerSet;
 pub use super::doenet::ul::Ul;
+pub use super::doenet::update_value::UpdateValue;
 pub use super::doenet::xref::Xref;
+#[cfg(feature = "testing")]
+pub use super::testing::_test_timer::_TestTimer;
 
 /// A enum that can contain a component of any possible component type.
 ///
@@ -52,8 +57,13 @@ pub enum ComponentEnum {
     Li(Li),
     Graph(Graph),
     Point(Point),
+    UpdateValue(UpdateValue),
+    CallAction(CallAction),
+    TriggerSet(TriggerSet),
     _Error(_Error),
     _External(_External),
     _Fragment(_Fragment),
     _Ref(_Ref),
+    #[cfg(feature = "testing")]
+    _TestTimer(_TestTimer),
 }
@@ -0,0 +1,142 @@
+use crate::components::prelude::*;
+use crate::general_prop::{BooleanProp, IndependentProp, StringProp};
+use crate::props::UpdaterObject;
+
+/// The `<callAction>` component renders a button that, when clicked,
+/// calls the action `actionName` of the component given by its `target` attribute.
+#[component(name = CallAction)]
+mod component {
+
+    use super::*;
+    use crate::general_prop::ComponentRefProp;
+
+    enum Props {
+        /// The component whose action is called.
+        #[prop(value_type = PropValueType::ComponentRef)]
+        Target,
+
+        /// The name of the action to call, for example, `updateValue`.
+        #[prop(value_type = PropValueType::String)]
+        ActionName,
+
+        /// The label of the button, taken from the children of the `<callAction>`.
+        #[prop(value_type = PropValueType::String, for_render)]
+        Label,
+
+        /// Whether `label` should be rendered as LaTeX.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        LabelHasLatex,
+
+        /// The action the renderer sends when the button is clicked.
+        #[prop(value_type = PropValueType::String, for_render)]
+        ClickAction,
+
+        /// Whether the button can be clicked.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        Disabled,
+
+        /// Whether the `<callAction>` should be hidden.
+        #[prop(value_type = PropValueType::Boolean, profile = PropProfile::Hidden)]
+        Hidden,
+    }
+
+    enum Attributes {
+        /// Whether the `<callAction>` should be hidden.
+        #[attribute(prop = BooleanProp, default = false)]
+        Hide,
+        /// Whether the `<callAction>` should be disabled.
+        #[attribute(prop = BooleanProp, default = false)]
+        Disabled,
+        /// The component whose action is called.
+        #[attribute(prop = ComponentRefProp, default = None, preserve_refs)]
+        Target,
+        /// The name of the action to call. The action must not take any arguments.
+        #[attribute(prop = StringProp, default = String::new())]
+        ActionName,
+    }
+
+    enum Actions {
+        CallAction,
+    }
+}
+
+pub use component::CallAction;
+pub use component::CallActionActions;
+pub use component::CallActionAttributes;
+pub use component::CallActionProps;
+use component::attrs;
+use component::props;
+
+impl PropGetUpdater for CallActionProps {
+    fn get_updater(&self) -> UpdaterObject {
+        match self {
+            CallActionProps::Target => {
+                as_updater_object::<_, props::types::Target>(attrs::Target::get_prop_updater())
+            }
+            CallActionProps::ActionName => as_updater_object::<_, props::types::ActionName>(
+                attrs::ActionName::get_prop_updater(),
+            ),
+            CallActionProps::Label => as_updater_object::<_, props::types::Label>(
+                StringProp::new_from_children("call action"),
+            ),
+            CallActionProps::LabelHasLatex => as_updater_object::<_, props::types::LabelHasLatex>(
+                IndependentProp::new_frozen(false),
+            ),
+            CallActionProps::ClickAction => as_updater_object::<_, props::types::ClickAction>(
+                IndependentProp::new_frozen(prop_type::String::new("callAction".to_string())),
+            ),
+            CallActionProps::Disabled => {
+                as_updater_object::<_, props::types::Disabled>(attrs::Disabled::get_prop_updater())
+            }
+            CallActionProps::Hidden => {
+                as_updater_object::<_, props::types::Hidden>(attrs::Hide::get_prop_updater())
+            }
+        }
+    }
+}
+
+impl ComponentOnAction for CallAction {
+    fn on_action(
+        &self,
+        action: ActionsEnum,
+        query_prop: ActionQueryProp,
+    ) -> Result<Vec<UpdateFromAction>, String> {
+        // The type of `action` should have already been verified, so an
+        // error here is a programming logic error, not an API error.
+        let action: CallActionActions = action.try_into()?;
+
+        match action {
+            CallActionActions::CallAction => {
+                let disabled = query_prop
+                    .get_local_prop(CallActionProps::Disabled.local_idx())
+                    .map_err(|err| err.to_string())?;
+                if disabled.value == PropValue::Boolean(true) {
+                    return Ok(vec![]);
+                }
+                // Without a target, there is no action to call.
+                let Some(target_idx) = query_prop
+                    .get_local_component_ref(CallActionProps::Target.local_idx())
+                    .map_err(|err| err.to_string())?
+                else {
+                    return Ok(vec![]);
+                };
+
+                let action_name = query_prop
+                    .get_local_prop(CallActionProps::ActionName.local_idx())
+                    .map_err(|err| err.to_string())?;
+                let action_name = match &action_name.value {
+                    PropValue::String(action_name) => action_name.as_str(),
+                    _ => unreachable!("`actionName` is a string prop"),
+                };
+                let target_type = query_prop
+                    .get_component_type(target_idx)
+                    .map_err(|err| err.to_string())?;
+
+                Ok(vec![UpdateFromAction::TriggerAction {
+                    component_idx: target_idx,
+                    action: ActionsEnum::new_without_args(&target_type, action_name)?,
+                }])
+            }
+        }
+    }
+}
@@ -97,19 +97,19 @@ impl ComponentOnAction for Graph {
 
         match action {
             GraphActions::ChangeBoundingBox(ActionBody { args }) => Ok(vec![
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: GraphProps::XMin.local_idx(),
                     requested_value: PropValue::Number(args.x_min),
                 },
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: GraphProps::XMax.local_idx(),
                     requested_value: PropValue::Number(args.x_max),
                 },
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: GraphProps::YMin.local_idx(),
                     requested_value: PropValue::Number(args.y_min),
                 },
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: GraphProps::YMax.local_idx(),
                     requested_value: PropValue::Number(args.y_max),
                 },
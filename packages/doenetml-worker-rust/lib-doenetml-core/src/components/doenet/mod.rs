@@ -2,6 +2,7 @@
 
 pub mod _fragment;
 pub mod boolean;
+pub mod call_action;
 pub mod division;
 pub mod document;
 pub mod graph;
@@ -14,5 +15,7 @@ pub mod point;
 pub mod text;
 pub mod text_input;
 pub mod title;
+pub mod trigger_set;
 pub mod ul;
+pub mod update_value;
 pub mod xref;
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use crate::components::prelude::*;
-use crate::general_prop::{BooleanProp, LatexProp, MathProp};
+use crate::general_prop::{BooleanProp, LatexProp, MathProp, PropAlias};
 use crate::props::UpdaterObject;
 
 #[component(name = Point)]
@@ -21,7 +21,7 @@ mod component {
         Y,
         #[prop(value_type = PropValueType::Math,
             profile = PropProfile::Math,
-            is_public)]
+            is_public, is_array)]
         Coords,
         #[prop(value_type = PropValueType::String,
             profile = PropProfile::String,
@@ -97,11 +97,11 @@ impl ComponentOnAction for Point {
 
         match action {
             PointActions::Move(ActionBody { args }) => Ok(vec![
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: PointProps::X.local_idx(),
                     requested_value: PropValue::Math(Rc::new(args.x.into())),
                 },
-                UpdateFromAction {
+                UpdateFromAction::Prop {
                     local_prop_idx: PointProps::Y.local_idx(),
                     requested_value: PropValue::Math(Rc::new(args.y.into())),
                 },
@@ -193,6 +193,16 @@ mod custom_props {
                     }
                 }
             }
+
+            fn element_updater(&self, index: usize) -> Option<UpdaterObject> {
+                // Each coordinate depends only on its own prop, so that changing `x`
+                // does not recalculate references to `$P.coords[2]`, and vice versa.
+                match index {
+                    0 => Some(PropAlias::<prop_type::Math>::new(PointProps::X.local_idx()).into()),
+                    1 => Some(PropAlias::<prop_type::Math>::new(PointProps::Y.local_idx()).into()),
+                    _ => None,
+                }
+            }
         }
     }
 }
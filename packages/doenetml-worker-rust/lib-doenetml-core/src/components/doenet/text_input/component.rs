@@ -68,8 +68,14 @@ pub(super) mod component {
         ///
         /// If `disabled`, then a user cannot interact with the text input,
         /// and the input box should display as disabled (e.g., grayed out)
+        ///
+        /// It is `true` if the `disabled` attribute is set or if the document is read-only.
         #[prop(value_type = PropValueType::Boolean)]
         Disabled,
+
+        /// The value of the `disabled` attribute.
+        #[prop(value_type = PropValueType::Boolean)]
+        DisabledAttribute,
     }
 
     enum Attributes {
@@ -106,7 +112,7 @@ pub use component::TextInputProps;
 use component::attrs;
 pub(super) use component::props;
 
-use super::custom_props::{ImmediateValueProp, ValueProp};
+use super::custom_props::{DisabledProp, ImmediateValueProp, ValueProp};
 
 impl PropGetUpdater for TextInputProps {
     fn get_updater(&self) -> UpdaterObject {
@@ -132,8 +138,12 @@ impl PropGetUpdater for TextInputProps {
                 as_updater_object::<_, props::types::Hidden>(attrs::Hide::get_prop_updater())
             }
             TextInputProps::Disabled => {
-                as_updater_object::<_, props::types::Disabled>(attrs::Disabled::get_prop_updater())
+                as_updater_object::<_, props::types::Disabled>(DisabledProp::new())
             }
+            TextInputProps::DisabledAttribute => as_updater_object::<
+                _,
+                props::types::DisabledAttribute,
+            >(attrs::Disabled::get_prop_updater()),
         }
     }
 }
@@ -150,16 +160,18 @@ impl ComponentOnAction for TextInput {
 
         match action {
             TextInputActions::UpdateImmediateValue(ActionBody { args }) => {
-                Ok(vec![UpdateFromAction {
+                Ok(vec![UpdateFromAction::Prop {
                     local_prop_idx: TextInputProps::ImmediateValue.local_idx(),
                     requested_value: args.text.into(),
                 }])
             }
 
             TextInputActions::UpdateValue => {
-                let new_val = query_prop.get_local_prop(TextInputProps::ImmediateValue.local_idx());
+                let new_val = query_prop
+                    .get_local_prop(TextInputProps::ImmediateValue.local_idx())
+                    .map_err(|err| err.to_string())?;
 
-                Ok(vec![UpdateFromAction {
+                Ok(vec![UpdateFromAction::Prop {
                     local_prop_idx: TextInputProps::Value.local_idx(),
                     requested_value: new_val.value,
                 }])
@@ -0,0 +1,62 @@
+use super::super::*;
+use crate::{
+    components::{doenet::text_input::TextInputProps, prelude::*},
+    document_flags::DocumentFlag,
+};
+
+/// The `disabled` prop of a `<textInput>`, which is `true` if the `disabled` attribute is set
+/// or if the document is read-only.
+#[derive(Debug, Default)]
+pub struct DisabledProp {}
+
+impl DisabledProp {
+    pub fn new() -> Self {
+        DisabledProp {}
+    }
+}
+
+/// Structure to hold data generated from the data queries
+#[derive(TryFromDataQueryResults, IntoDataQueryResults)]
+#[data_query(query_trait = DataQueries)]
+#[derive(TestDataQueryTypes)]
+#[owning_component(TextInput)]
+struct RequiredData {
+    /// The value of the `disabled` attribute of this `text_input`.
+    disabled_attribute: PropView<prop_type::Boolean>,
+
+    /// The `read_only` flag of the document.
+    read_only: PropView<prop_type::Boolean>,
+}
+
+impl DataQueries for RequiredData {
+    fn disabled_attribute_query() -> DataQuery {
+        DataQuery::Prop {
+            source: PropSource::Me,
+            prop_specifier: TextInputProps::DisabledAttribute.local_idx().into(),
+        }
+    }
+
+    fn read_only_query() -> DataQuery {
+        DataQuery::DocumentFlag(DocumentFlag::ReadOnly)
+    }
+}
+
+impl PropUpdater for DisabledProp {
+    type PropType = props::types::Disabled;
+
+    fn data_queries(&self) -> Vec<DataQuery> {
+        RequiredData::to_data_queries()
+    }
+
+    fn calculate(&self, data: DataQueryResults) -> PropCalcResult<Self::PropType> {
+        let required_data = RequiredData::try_from_data_query_results(data).unwrap();
+
+        if required_data.read_only.value {
+            PropCalcResult::Calculated(true)
+        } else if required_data.disabled_attribute.came_from_default {
+            PropCalcResult::FromDefault(required_data.disabled_attribute.value)
+        } else {
+            PropCalcResult::Calculated(required_data.disabled_attribute.value)
+        }
+    }
+}
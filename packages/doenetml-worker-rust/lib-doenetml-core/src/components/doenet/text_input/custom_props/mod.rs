@@ -1,5 +1,7 @@
+mod disabled;
 mod immediate_value;
 mod value;
 
+pub use disabled::*;
 pub use immediate_value::*;
 pub use value::*;
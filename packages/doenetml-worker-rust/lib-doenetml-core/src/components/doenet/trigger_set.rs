@@ -0,0 +1,140 @@
+use crate::components::prelude::*;
+use crate::general_prop::{BooleanProp, ComponentRefsProp, IndependentProp, StringProp};
+use crate::props::{ContentFilter, Op, UpdaterObject};
+
+use super::call_action::CallActionActions;
+use super::update_value::UpdateValueActions;
+
+/// The `<triggerSet>` component renders a button that, when clicked,
+/// runs the actions of all of its `<updateValue>` and `<callAction>` children at once.
+#[component(name = TriggerSet)]
+mod component {
+
+    use super::*;
+
+    enum Props {
+        /// The `<updateValue>` and `<callAction>` children whose actions are run.
+        #[prop(value_type = PropValueType::ComponentRefs)]
+        Triggers,
+
+        /// The label of the button.
+        #[prop(value_type = PropValueType::String, for_render)]
+        Label,
+
+        /// Whether `label` should be rendered as LaTeX.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        LabelHasLatex,
+
+        /// The action the renderer sends when the button is clicked.
+        #[prop(value_type = PropValueType::String, for_render)]
+        ClickAction,
+
+        /// Whether the button can be clicked.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        Disabled,
+
+        /// Whether the `<triggerSet>` should be hidden.
+        #[prop(value_type = PropValueType::Boolean, profile = PropProfile::Hidden)]
+        Hidden,
+    }
+
+    enum Attributes {
+        /// Whether the `<triggerSet>` should be hidden.
+        #[attribute(prop = BooleanProp, default = false)]
+        Hide,
+        /// Whether the `<triggerSet>` should be disabled.
+        #[attribute(prop = BooleanProp, default = false)]
+        Disabled,
+        /// The label of the button.
+        #[attribute(prop = StringProp, default = String::from("update"))]
+        Label,
+    }
+
+    enum Actions {
+        TriggerActions,
+    }
+}
+
+pub use component::TriggerSet;
+pub use component::TriggerSetActions;
+pub use component::TriggerSetAttributes;
+pub use component::TriggerSetProps;
+use component::attrs;
+use component::props;
+
+impl PropGetUpdater for TriggerSetProps {
+    fn get_updater(&self) -> UpdaterObject {
+        match self {
+            TriggerSetProps::Triggers => as_updater_object::<_, props::types::Triggers>(
+                ComponentRefsProp::new_from_children_matching(Op::Or(
+                    ContentFilter::IsType("updateValue"),
+                    ContentFilter::IsType("callAction"),
+                )),
+            ),
+            TriggerSetProps::Label => {
+                as_updater_object::<_, props::types::Label>(attrs::Label::get_prop_updater())
+            }
+            TriggerSetProps::LabelHasLatex => as_updater_object::<_, props::types::LabelHasLatex>(
+                IndependentProp::new_frozen(false),
+            ),
+            TriggerSetProps::ClickAction => as_updater_object::<_, props::types::ClickAction>(
+                IndependentProp::new_frozen(prop_type::String::new("triggerActions".to_string())),
+            ),
+            TriggerSetProps::Disabled => {
+                as_updater_object::<_, props::types::Disabled>(attrs::Disabled::get_prop_updater())
+            }
+            TriggerSetProps::Hidden => {
+                as_updater_object::<_, props::types::Hidden>(attrs::Hide::get_prop_updater())
+            }
+        }
+    }
+}
+
+impl ComponentOnAction for TriggerSet {
+    fn on_action(
+        &self,
+        action: ActionsEnum,
+        query_prop: ActionQueryProp,
+    ) -> Result<Vec<UpdateFromAction>, String> {
+        // The type of `action` should have already been verified, so an
+        // error here is a programming logic error, not an API error.
+        let action: TriggerSetActions = action.try_into()?;
+
+        match action {
+            TriggerSetActions::TriggerActions => {
+                let disabled = query_prop
+                    .get_local_prop(TriggerSetProps::Disabled.local_idx())
+                    .map_err(|err| err.to_string())?;
+                if disabled.value == PropValue::Boolean(true) {
+                    return Ok(vec![]);
+                }
+
+                let triggers = query_prop
+                    .get_local_prop(TriggerSetProps::Triggers.local_idx())
+                    .map_err(|err| err.to_string())?;
+                let PropValue::ComponentRefs(triggers) = triggers.value else {
+                    unreachable!("`triggers` is a component refs prop")
+                };
+
+                triggers
+                    .iter()
+                    .map(|&component_idx| {
+                        let component_type = query_prop
+                            .get_component_type(component_idx)
+                            .map_err(|err| err.to_string())?;
+                        let action = match component_type.as_str() {
+                            "updateValue" => {
+                                ActionsEnum::UpdateValue(UpdateValueActions::UpdateValue)
+                            }
+                            _ => ActionsEnum::CallAction(CallActionActions::CallAction),
+                        };
+                        Ok(UpdateFromAction::TriggerAction {
+                            component_idx,
+                            action,
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::components::prelude::*;
+use crate::general_prop::{BooleanProp, IndependentProp, MathProp, NumberProp, StringProp};
+use crate::props::UpdaterObject;
+use crate::state::types::math_expr::MathExpr;
+
+/// The `<updateValue>` component renders a button that, when clicked,
+/// changes the value of the component given by its `target` attribute to `newValue`.
+#[component(name = UpdateValue)]
+mod component {
+
+    use super::*;
+    use crate::general_prop::ComponentRefProp;
+
+    enum Props {
+        /// The component whose value is changed.
+        #[prop(value_type = PropValueType::ComponentRef)]
+        Target,
+
+        /// The type of the value to change: `math`, `number`, `text` or `boolean`.
+        #[prop(value_type = PropValueType::String)]
+        Type,
+
+        /// The new value, when `type` is `math`.
+        #[prop(value_type = PropValueType::Math)]
+        NewValueMath,
+
+        /// The new value, when `type` is `number`.
+        #[prop(value_type = PropValueType::Number)]
+        NewValueNumber,
+
+        /// The new value, when `type` is `text`.
+        #[prop(value_type = PropValueType::String)]
+        NewValueText,
+
+        /// The new value, when `type` is `boolean`.
+        #[prop(value_type = PropValueType::Boolean)]
+        NewValueBoolean,
+
+        /// The label of the button, taken from the children of the `<updateValue>`.
+        #[prop(value_type = PropValueType::String, for_render)]
+        Label,
+
+        /// Whether `label` should be rendered as LaTeX.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        LabelHasLatex,
+
+        /// The action the renderer sends when the button is clicked.
+        #[prop(value_type = PropValueType::String, for_render)]
+        ClickAction,
+
+        /// Whether the button can be clicked.
+        #[prop(value_type = PropValueType::Boolean, for_render)]
+        Disabled,
+
+        /// Whether the `<updateValue>` should be hidden.
+        #[prop(value_type = PropValueType::Boolean, profile = PropProfile::Hidden)]
+        Hidden,
+    }
+
+    enum Attributes {
+        /// Whether the `<updateValue>` should be hidden.
+        #[attribute(prop = BooleanProp, default = false)]
+        Hide,
+        /// Whether the `<updateValue>` should be disabled.
+        #[attribute(prop = BooleanProp, default = false)]
+        Disabled,
+        /// The component whose value is changed.
+        #[attribute(prop = ComponentRefProp, default = None, preserve_refs)]
+        Target,
+        /// The type of the value to change: `math` (the default), `number`, `text` or `boolean`.
+        #[attribute(prop = StringProp, default = String::from("math"))]
+        Type,
+        /// The new value of the target.
+        #[attribute(prop = MathProp, default = MathExpr::default())]
+        NewValue,
+    }
+
+    enum Actions {
+        UpdateValue,
+    }
+}
+
+pub use component::UpdateValue;
+pub use component::UpdateValueActions;
+pub use component::UpdateValueAttributes;
+pub use component::UpdateValueProps;
+use component::attrs;
+use component::props;
+
+impl PropGetUpdater for UpdateValueProps {
+    fn get_updater(&self) -> UpdaterObject {
+        match self {
+            UpdateValueProps::Target => {
+                as_updater_object::<_, props::types::Target>(attrs::Target::get_prop_updater())
+            }
+            UpdateValueProps::Type => {
+                as_updater_object::<_, props::types::Type>(attrs::Type::get_prop_updater())
+            }
+            UpdateValueProps::NewValueMath => as_updater_object::<_, props::types::NewValueMath>(
+                attrs::NewValue::get_prop_updater(),
+            ),
+            UpdateValueProps::NewValueNumber => {
+                as_updater_object::<_, props::types::NewValueNumber>(
+                    NumberProp::new_from_attribute("newValue", prop_type::Number::NAN),
+                )
+            }
+            UpdateValueProps::NewValueText => as_updater_object::<_, props::types::NewValueText>(
+                StringProp::new_from_attribute("newValue", ""),
+            ),
+            UpdateValueProps::NewValueBoolean => {
+                as_updater_object::<_, props::types::NewValueBoolean>(
+                    BooleanProp::new_from_attribute("newValue", false),
+                )
+            }
+            UpdateValueProps::Label => as_updater_object::<_, props::types::Label>(
+                StringProp::new_from_children("update value"),
+            ),
+            UpdateValueProps::LabelHasLatex => as_updater_object::<_, props::types::LabelHasLatex>(
+                IndependentProp::new_frozen(false),
+            ),
+            UpdateValueProps::ClickAction => as_updater_object::<_, props::types::ClickAction>(
+                IndependentProp::new_frozen(prop_type::String::new("updateValue".to_string())),
+            ),
+            UpdateValueProps::Disabled => {
+                as_updater_object::<_, props::types::Disabled>(attrs::Disabled::get_prop_updater())
+            }
+            UpdateValueProps::Hidden => {
+                as_updater_object::<_, props::types::Hidden>(attrs::Hide::get_prop_updater())
+            }
+        }
+    }
+}
+
+impl ComponentOnAction for UpdateValue {
+    fn on_action(
+        &self,
+        action: ActionsEnum,
+        query_prop: ActionQueryProp,
+    ) -> Result<Vec<UpdateFromAction>, String> {
+        // The type of `action` should have already been verified, so an
+        // error here is a programming logic error, not an API error.
+        let action: UpdateValueActions = action.try_into()?;
+
+        match action {
+            UpdateValueActions::UpdateValue => {
+                let get_local_prop = |prop: UpdateValueProps| {
+                    query_prop
+                        .get_local_prop(prop.local_idx())
+                        .map(|prop| prop.value)
+                        .map_err(|err| err.to_string())
+                };
+
+                if get_local_prop(UpdateValueProps::Disabled)? == PropValue::Boolean(true) {
+                    return Ok(vec![]);
+                }
+                // Without a target, there is nothing to update.
+                let Some(target_idx) = query_prop
+                    .get_local_component_ref(UpdateValueProps::Target.local_idx())
+                    .map_err(|err| err.to_string())?
+                else {
+                    return Ok(vec![]);
+                };
+
+                let value_type = get_local_prop(UpdateValueProps::Type)?;
+                let value_type = match &value_type {
+                    PropValue::String(value_type) => value_type.to_lowercase(),
+                    _ => unreachable!("`type` is a string prop"),
+                };
+                let (profile, new_value_prop) = match value_type.as_str() {
+                    "math" => (PropProfile::Math, UpdateValueProps::NewValueMath),
+                    "number" => (PropProfile::Number, UpdateValueProps::NewValueNumber),
+                    "text" => (PropProfile::String, UpdateValueProps::NewValueText),
+                    "boolean" => (PropProfile::Boolean, UpdateValueProps::NewValueBoolean),
+                    _ => {
+                        return Err(format!(
+                            "Invalid type `{value_type}` of `<updateValue>`. Expected one of `math`, `number`, `text` or `boolean`"
+                        ));
+                    }
+                };
+
+                let Some(local_prop_idx) = query_prop
+                    .get_component_prop_by_profile(target_idx, &[profile])
+                    .map_err(|err| err.to_string())?
+                else {
+                    return Err(format!(
+                        "The target of `<updateValue>` has no value of type `{value_type}`"
+                    ));
+                };
+
+                Ok(vec![UpdateFromAction::ComponentProp {
+                    component_idx: target_idx,
+                    local_prop_idx,
+                    requested_value: get_local_prop(new_value_prop)?,
+                }])
+            }
+        }
+    }
+}
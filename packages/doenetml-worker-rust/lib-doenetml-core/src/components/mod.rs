@@ -19,6 +19,8 @@ pub mod component_enum;
 pub mod doenet;
 pub mod prelude;
 pub mod special;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod traits;
 pub mod types;
 
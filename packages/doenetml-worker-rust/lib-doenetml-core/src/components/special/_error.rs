@@ -9,6 +9,8 @@ use crate::{
 #[derive(Debug, Default, Clone)]
 pub struct _Error {
     pub message: String,
+    /// For an error resolving a reference, the position of the part of the path that failed to resolve.
+    pub path_part_position: Option<DastPosition>,
 }
 
 impl _Error {
@@ -81,6 +83,9 @@ impl ComponentVariantProps for _Error {
     fn get_prop_is_public(&self, _local_prop_idx: LocalPropIdx) -> bool {
         panic!("No props on _Error")
     }
+    fn get_prop_is_array(&self, _local_prop_idx: LocalPropIdx) -> bool {
+        panic!("No props on _Error")
+    }
     fn get_prop_names(&self) -> &'static [&'static str] {
         &[]
     }
@@ -22,6 +22,7 @@ impl _External {
         in_text: false,
     }];
     const PROP_IS_PUBLICS: &'static [bool] = &[false];
+    const PROP_IS_ARRAYS: &'static [bool] = &[false];
     const PROP_VALUE_TYPES: &'static [PropValueType] = &[PropValueType::ContentRefs];
     const DEFAULT_PROP: Option<LocalPropIdx> = None;
 }
@@ -118,6 +119,9 @@ impl ComponentVariantProps for _External {
     fn get_prop_is_public(&self, local_prop_idx: LocalPropIdx) -> bool {
         _External::PROP_IS_PUBLICS[local_prop_idx.as_usize()]
     }
+    fn get_prop_is_array(&self, local_prop_idx: LocalPropIdx) -> bool {
+        _External::PROP_IS_ARRAYS[local_prop_idx.as_usize()]
+    }
     fn get_prop_value_type(&self, local_prop_idx: LocalPropIdx) -> PropValueType {
         _External::PROP_VALUE_TYPES[local_prop_idx.as_usize()]
     }
@@ -88,6 +88,9 @@ impl ComponentVariantProps for _Ref {
     fn get_prop_is_public(&self, _local_prop_idx: LocalPropIdx) -> bool {
         false
     }
+    fn get_prop_is_array(&self, _local_prop_idx: LocalPropIdx) -> bool {
+        false
+    }
     fn get_prop_names(&self) -> &'static [&'static str] {
         &["referent"]
     }
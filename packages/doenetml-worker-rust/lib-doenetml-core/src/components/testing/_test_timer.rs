@@ -0,0 +1,100 @@
+use crate::components::prelude::*;
+use crate::general_prop::{IndependentProp, NumberProp};
+use crate::props::UpdaterObject;
+
+/// A component that counts the ticks of a timer that it schedules itself (see [`UpdateFromAction::ScheduleAction`]).
+/// After the `start` action, it sends itself a `tick` action `delay` milliseconds later,
+/// and each `tick` schedules the next one, until the `stop` action.
+#[component(name = _TestTimer)]
+mod component {
+
+    use super::*;
+
+    enum Props {
+        /// The number of ticks so far.
+        #[prop(value_type = PropValueType::Integer, is_public)]
+        Count,
+
+        /// The time from one tick to the next, in milliseconds.
+        #[prop(value_type = PropValueType::Number)]
+        Delay,
+    }
+
+    enum Attributes {
+        /// The time from one tick to the next, in milliseconds.
+        #[attribute(prop = NumberProp, default = 10.0)]
+        Delay,
+    }
+
+    enum Actions {
+        Start,
+        Tick,
+        Stop,
+    }
+}
+
+pub use component::_TestTimer;
+pub use component::_TestTimerActions;
+pub use component::_TestTimerAttributes;
+pub use component::_TestTimerProps;
+use component::attrs;
+use component::props;
+
+impl PropGetUpdater for _TestTimerProps {
+    fn get_updater(&self) -> UpdaterObject {
+        match self {
+            _TestTimerProps::Count => {
+                as_updater_object::<_, props::types::Count>(IndependentProp::new(0))
+            }
+            _TestTimerProps::Delay => {
+                as_updater_object::<_, props::types::Delay>(attrs::Delay::get_prop_updater())
+            }
+        }
+    }
+}
+
+impl ComponentOnAction for _TestTimer {
+    fn on_action(
+        &self,
+        action: ActionsEnum,
+        query_prop: ActionQueryProp,
+    ) -> Result<Vec<UpdateFromAction>, String> {
+        let action: _TestTimerActions = action.try_into()?;
+
+        let get_local_prop = |prop: _TestTimerProps| {
+            query_prop
+                .get_local_prop(prop.local_idx())
+                .map(|prop| prop.value)
+                .map_err(|err| err.to_string())
+        };
+        let schedule_tick = || {
+            let delay = match get_local_prop(_TestTimerProps::Delay)? {
+                PropValue::Number(delay) => delay,
+                _ => unreachable!("`delay` is a number prop"),
+            };
+            Ok::<_, String>(UpdateFromAction::ScheduleAction {
+                action: ActionsEnum::_TestTimer(_TestTimerActions::Tick),
+                delay,
+                interval: None,
+            })
+        };
+
+        match action {
+            _TestTimerActions::Start => Ok(vec![schedule_tick()?]),
+            _TestTimerActions::Tick => {
+                let count = match get_local_prop(_TestTimerProps::Count)? {
+                    PropValue::Integer(count) => count,
+                    _ => unreachable!("`count` is an integer prop"),
+                };
+                Ok(vec![
+                    UpdateFromAction::Prop {
+                        local_prop_idx: _TestTimerProps::Count.local_idx(),
+                        requested_value: PropValue::Integer(count + 1),
+                    },
+                    schedule_tick()?,
+                ])
+            }
+            _TestTimerActions::Stop => Ok(vec![UpdateFromAction::CancelScheduledActions]),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! Components that are only available when compiled with the `testing` feature.
+//! They exercise features of `Core` that no authored component uses yet.
+
+pub mod _test_timer;
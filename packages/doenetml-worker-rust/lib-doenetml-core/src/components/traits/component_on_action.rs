@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use crate::components::{
     ComponentEnum,
     doenet::{
-        graph::GraphActions, point::PointActions, text::TextActions, text_input::TextInputActions,
+        call_action::CallActionActions, graph::GraphActions, point::PointActions,
+        text::TextActions, text_input::TextInputActions, trigger_set::TriggerSetActions,
+        update_value::UpdateValueActions,
     },
     types::{ActionQueryProp, UpdateFromAction},
 };
@@ -14,16 +16,39 @@ use super::{ComponentActions, ComponentNode};
 
 /// An enum listing the actions that are available for each component type.
 /// A deserialized version of this action will be sent to the component.
+///
+/// The `component` tag is the component type as it is written in DoenetML, e.g., `textInput`.
 #[derive(Debug, Deserialize, Serialize, derive_more::TryInto)]
-#[serde(tag = "component")]
+#[serde(tag = "component", rename_all = "camelCase")]
 #[cfg_attr(feature = "web", derive(tsify_next::Tsify))]
-#[cfg_attr(feature = "web", serde(rename_all = "camelCase"))]
 #[cfg_attr(feature = "web", tsify(from_wasm_abi))]
 pub enum ActionsEnum {
     Text(TextActions),
     TextInput(TextInputActions),
     Point(PointActions),
     Graph(GraphActions),
+    UpdateValue(UpdateValueActions),
+    CallAction(CallActionActions),
+    TriggerSet(TriggerSetActions),
+    #[cfg(feature = "testing")]
+    #[serde(rename = "_testTimer")]
+    _TestTimer(crate::components::testing::_test_timer::_TestTimerActions),
+}
+
+impl ActionsEnum {
+    /// Create the action `action_name` of a component of type `component_type`,
+    /// for example, the `updateValue` action of a `textInput`.
+    ///
+    /// Only actions that take no arguments can be created this way.
+    pub fn new_without_args(component_type: &str, action_name: &str) -> Result<Self, String> {
+        serde_json::from_value(serde_json::json!({
+            "component": component_type,
+            "actionName": action_name,
+        }))
+        .map_err(|_| {
+            format!("`{action_name}` is not an action without arguments of a `<{component_type}>`")
+        })
+    }
 }
 
 /// The `ComponentOnAction` trait allows a component to handle actions sent to the component.
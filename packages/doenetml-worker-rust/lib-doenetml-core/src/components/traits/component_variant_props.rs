@@ -53,6 +53,10 @@ pub trait ComponentVariantProps {
     fn get_prop_is_public(&self, local_prop_idx: LocalPropIdx) -> bool {
         unimplemented!()
     }
+    /// Get whether the prop is an array prop, whose elements can be referenced individually.
+    fn get_prop_is_array(&self, local_prop_idx: LocalPropIdx) -> bool {
+        unimplemented!()
+    }
     /// Get the `PropValueType` of the prop.
     fn get_prop_value_type(&self, local_prop_idx: LocalPropIdx) -> PropValueType {
         unimplemented!()
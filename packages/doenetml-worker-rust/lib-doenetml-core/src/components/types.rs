@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    DocumentModel, core::props::PropValue, graph_node::GraphNode, props::cache::PropWithMeta,
+    DocumentModel,
+    core::props::PropValue,
+    dast::ref_resolve::PathIndex,
+    error::CoreError,
+    graph_node::GraphNode,
+    props::{PropProfile, cache::PropWithMeta},
 };
 
-use super::ActionsEnum;
+use super::{ActionsEnum, ComponentNode};
 
 /// The local index of a prop relative to the component to which it belongs.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::From, derive_more::Into)]
@@ -21,7 +26,7 @@ impl LocalPropIdx {
 }
 
 /// The index of a PropDefinition in `DocumentStructure.prop_definitions`
-#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::From, derive_more::Into)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, derive_more::From, derive_more::Into)]
 pub struct PropDefinitionIdx(usize);
 
 impl PropDefinitionIdx {
@@ -40,15 +45,28 @@ impl From<PropDefinitionIdx> for GraphNode {
     }
 }
 
-impl<T: IntoGraphNode> From<T> for PropDefinitionIdx {
-    fn from(node: T) -> Self {
-        match node.into() {
-            GraphNode::Prop(idx) => idx.into(),
-            node => panic!("Expected GraphNode::Component, not {node:?}"),
+impl TryFrom<GraphNode> for PropDefinitionIdx {
+    type Error = CoreError;
+
+    fn try_from(node: GraphNode) -> Result<Self, Self::Error> {
+        match node {
+            GraphNode::Prop(idx) => Ok(idx.into()),
+            found => Err(CoreError::UnexpectedGraphNode {
+                expected: "Prop",
+                found,
+            }),
         }
     }
 }
 
+impl TryFrom<&GraphNode> for PropDefinitionIdx {
+    type Error = CoreError;
+
+    fn try_from(node: &GraphNode) -> Result<Self, Self::Error> {
+        (*node).try_into()
+    }
+}
+
 /// The index of the component in `DocumentStructure.components`
 #[derive(
     Debug,
@@ -62,6 +80,8 @@ impl<T: IntoGraphNode> From<T> for PropDefinitionIdx {
     Deserialize,
     Default,
     Hash,
+    PartialOrd,
+    Ord,
 )]
 #[serde(transparent)]
 #[cfg_attr(feature = "web", derive(tsify_next::Tsify))]
@@ -86,20 +106,28 @@ impl From<ComponentIdx> for GraphNode {
     }
 }
 
-// Helper trait
-pub trait IntoGraphNode: Into<GraphNode> + Copy {}
-impl IntoGraphNode for GraphNode {}
-impl IntoGraphNode for &GraphNode {}
+impl TryFrom<GraphNode> for ComponentIdx {
+    type Error = CoreError;
 
-impl<T: IntoGraphNode> From<T> for ComponentIdx {
-    fn from(node: T) -> Self {
-        match node.into() {
-            GraphNode::Component(idx) => idx.into(),
-            node => panic!("Expected GraphNode::Component, not {node:?}"),
+    fn try_from(node: GraphNode) -> Result<Self, Self::Error> {
+        match node {
+            GraphNode::Component(idx) => Ok(idx.into()),
+            found => Err(CoreError::UnexpectedGraphNode {
+                expected: "Component",
+                found,
+            }),
         }
     }
 }
 
+impl TryFrom<&GraphNode> for ComponentIdx {
+    type Error = CoreError;
+
+    fn try_from(node: &GraphNode) -> Result<Self, Self::Error> {
+        (*node).try_into()
+    }
+}
+
 /// The index of the component in `Core.components`
 #[derive(
     Debug,
@@ -136,23 +164,36 @@ impl From<StringIdx> for GraphNode {
     }
 }
 
-impl<T: IntoGraphNode> From<T> for StringIdx {
-    fn from(node: T) -> Self {
-        match node.into() {
-            GraphNode::String(idx) => idx.into(),
-            node => panic!("Expected GraphNode::Component, not {node:?}"),
+impl TryFrom<GraphNode> for StringIdx {
+    type Error = CoreError;
+
+    fn try_from(node: GraphNode) -> Result<Self, Self::Error> {
+        match node {
+            GraphNode::String(idx) => Ok(idx.into()),
+            found => Err(CoreError::UnexpectedGraphNode {
+                expected: "String",
+                found,
+            }),
         }
     }
 }
 
+impl TryFrom<&GraphNode> for StringIdx {
+    type Error = CoreError;
+
+    fn try_from(node: &GraphNode) -> Result<Self, Self::Error> {
+        (*node).try_into()
+    }
+}
+
 /// Information of the source that a component is extending, which is currently
 /// either another component or a prop.
 #[derive(Debug, Clone)]
 pub enum Extending {
     /// The component is extending another entire component, given by the component index
     Component(ComponentIdx),
-    // TODO: what about array props?
     /// The component is extending the prop of another component
+    /// (or a single element of an array prop)
     Prop(ExtendingPropSource),
 }
 
@@ -171,6 +212,11 @@ pub struct ExtendingPropSource {
     /// the expansion behavior is slightly different. We keep track of whether or not this `extend`
     /// is from a direct ref or not.
     pub from_direct_ref: bool,
+
+    /// If set, `prop_pointer` is an array prop and only the element (or range of elements) selected
+    /// by this index is being extended. For example, `$P.coords[2]` extends the second element of `coords`,
+    /// `$P.coords[-1]` its last element and `$P.coords[1:2]` its first two elements.
+    pub array_index: Option<PathIndex>,
 }
 
 /// Pointer to a component's prop
@@ -203,11 +249,43 @@ pub struct Action {
     pub action: ActionsEnum,
 }
 
-/// A requested update to a prop of a component coming from an action
+/// A requested update coming from an action of a component
 #[derive(Debug)]
-pub struct UpdateFromAction {
-    pub local_prop_idx: LocalPropIdx,
-    pub requested_value: PropValue,
+pub enum UpdateFromAction {
+    /// Change the prop `local_prop_idx` of the component to `requested_value`.
+    Prop {
+        local_prop_idx: LocalPropIdx,
+        requested_value: PropValue,
+    },
+    /// Change the prop `local_prop_idx` of another component, `component_idx`, to `requested_value`.
+    ///
+    /// The other component is typically found through a `ComponentRef` prop
+    /// (see [`ActionQueryProp::get_local_component_ref`]).
+    ComponentProp {
+        component_idx: ComponentIdx,
+        local_prop_idx: LocalPropIdx,
+        requested_value: PropValue,
+    },
+    /// Run `action` on the component `component_idx` as part of this action.
+    ///
+    /// The updates requested by the triggered action are made together with the updates of this action,
+    /// and are calculated from the state of the document before any of them are made.
+    /// A component cannot be triggered by an action that it triggered itself, directly or through other components.
+    TriggerAction {
+        component_idx: ComponentIdx,
+        action: ActionsEnum,
+    },
+    /// Send `action` to the component once `delay` milliseconds have passed on the document's clock
+    /// and then every `interval` milliseconds, if `interval` is specified.
+    ///
+    /// The document's clock is advanced by the host (see `Core::advance_time` and `Core::tick`).
+    ScheduleAction {
+        action: ActionsEnum,
+        delay: f64,
+        interval: Option<f64>,
+    },
+    /// Cancel all the actions that the component has scheduled and that have not yet been sent.
+    CancelScheduledActions,
 }
 
 /// An object that can be used to get the value of any prop of a component.
@@ -229,7 +307,7 @@ impl<'a> ActionQueryProp<'a> {
     ///
     /// **Note**: the `changed` meta data indicates whether or not this prop has changed
     /// since *any* action of this component called `get_local_prop()`.
-    pub fn get_local_prop(&self, local_prop_idx: LocalPropIdx) -> PropWithMeta {
+    pub fn get_local_prop(&self, local_prop_idx: LocalPropIdx) -> Result<PropWithMeta, CoreError> {
         let prop_pointer = PropPointer {
             component_idx: self.component_idx,
             local_prop_idx,
@@ -240,6 +318,61 @@ impl<'a> ActionQueryProp<'a> {
 
         self.document_model.get_prop(prop_node, origin)
     }
+
+    /// Get the PropWithMeta of prop with local_prop_idx of the component `component_idx`.
+    ///
+    /// **Note**: the `changed` meta data indicates whether or not this prop has changed
+    /// since *any* action of this component queried it.
+    pub fn get_component_prop(
+        &self,
+        component_idx: ComponentIdx,
+        local_prop_idx: LocalPropIdx,
+    ) -> Result<PropWithMeta, CoreError> {
+        let prop_pointer = PropPointer {
+            component_idx,
+            local_prop_idx,
+        };
+        self.document_model.check_prop_pointer(prop_pointer)?;
+        let prop_node = self.document_model.prop_pointer_to_prop_node(prop_pointer);
+
+        let origin = GraphNode::Component(self.component_idx.as_usize());
+
+        self.document_model.get_prop(prop_node, origin)
+    }
+
+    /// Get the component referred to by the `ComponentRef` prop with local_prop_idx of this component, if any.
+    pub fn get_local_component_ref(
+        &self,
+        local_prop_idx: LocalPropIdx,
+    ) -> Result<Option<ComponentIdx>, CoreError> {
+        match self.get_local_prop(local_prop_idx)?.value {
+            PropValue::ComponentRef(component_ref) => Ok(component_ref.map(|c| c.0)),
+            value => Err(CoreError::InvalidAction(format!(
+                "Expected a component reference, found {value:?}"
+            ))),
+        }
+    }
+
+    /// Get the type of the component `component_idx`, for example, `textInput`.
+    pub fn get_component_type(&self, component_idx: ComponentIdx) -> Result<String, CoreError> {
+        Ok(self
+            .document_model
+            .get_component(component_idx)?
+            .get_component_type()
+            .to_string())
+    }
+
+    /// Get the first prop of the component `component_idx` that matches a profile in `profiles`.
+    pub fn get_component_prop_by_profile(
+        &self,
+        component_idx: ComponentIdx,
+        profiles: &[PropProfile],
+    ) -> Result<Option<LocalPropIdx>, CoreError> {
+        Ok(self
+            .document_model
+            .get_component(component_idx)?
+            .get_prop_by_profile(profiles))
+    }
 }
 
 /// The `camelCase` name of an attribute.
@@ -0,0 +1,154 @@
+//! # Action history
+//!
+//! Records the changes that each dispatched action made to `State` and `String` nodes
+//! so that they can be undone and redone.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    components::{prelude::FlatDastElementUpdate, types::ComponentIdx},
+    core::document_model::NodeValue,
+    error::CoreError,
+    graph_node::GraphNodeLookup,
+};
+
+use super::{Core, action_recording::RecordedEvent};
+
+/// The number of actions that are remembered by default.
+pub const DEFAULT_ACTION_HISTORY_LEN: usize = 100;
+
+/// The changes made by a single action.
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    /// The values of the changed `State` and `String` nodes before the action,
+    /// including whether the states had their default values.
+    previous_values: GraphNodeLookup<NodeValue>,
+    /// The values of the changed `State` and `String` nodes after the action.
+    new_values: GraphNodeLookup<NodeValue>,
+}
+
+/// A bounded undo/redo history of the changes made by actions.
+///
+/// When more than `max_len` actions have been recorded, the oldest actions are forgotten.
+/// Recording a new action clears the redo history.
+#[derive(Debug)]
+pub struct ActionHistory {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    max_len: usize,
+}
+
+impl ActionHistory {
+    pub fn new(max_len: usize) -> Self {
+        ActionHistory {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            max_len,
+        }
+    }
+
+    /// Record that an action changed nodes from `previous_values` to `new_values`.
+    pub fn record(
+        &mut self,
+        previous_values: GraphNodeLookup<NodeValue>,
+        new_values: GraphNodeLookup<NodeValue>,
+    ) {
+        if self.max_len == 0 {
+            return;
+        }
+        self.redo_stack.clear();
+        if self.undo_stack.len() == self.max_len {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(HistoryEntry {
+            previous_values,
+            new_values,
+        });
+    }
+
+    /// Remove the most recent action from the undo history and return the values
+    /// that restore the nodes to their state before that action.
+    pub fn undo(&mut self) -> Option<GraphNodeLookup<NodeValue>> {
+        let entry = self.undo_stack.pop_back()?;
+        let values = entry.previous_values.clone();
+        self.redo_stack.push(entry);
+        Some(values)
+    }
+
+    /// Remove the most recently undone action from the redo history and return the values
+    /// that reapply that action.
+    pub fn redo(&mut self) -> Option<GraphNodeLookup<NodeValue>> {
+        let entry = self.redo_stack.pop()?;
+        let values = entry.new_values.clone();
+        self.undo_stack.push_back(entry);
+        Some(values)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Change the maximum number of remembered actions, forgetting the oldest actions if necessary.
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = max_len;
+        while self.undo_stack.len() > max_len {
+            self.undo_stack.pop_front();
+        }
+        // The bottom of the redo stack holds the actions furthest from the current state.
+        let excess_redo = self.redo_stack.len().saturating_sub(max_len);
+        self.redo_stack.drain(..excess_redo);
+    }
+
+    /// Forget all recorded actions.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl Default for ActionHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTION_HISTORY_LEN)
+    }
+}
+
+impl Core {
+    /// Undo the changes made by the most recent action and return any changes to the output flat dast.
+    /// A state that had its default value before the action has its default value again.
+    ///
+    /// If there is no action to undo, no changes are made. A read-only document cannot be changed by undoing.
+    pub fn undo(&mut self) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        if self.document_model.get_flags().read_only {
+            return Err(CoreError::ReadOnly);
+        }
+        let flat_dast_updates = match self.action_history.undo() {
+            Some(values) => self.restore_values(values)?,
+            None => HashMap::new(),
+        };
+        self.record_event(RecordedEvent::Undo)?;
+        Ok(flat_dast_updates)
+    }
+
+    /// Reapply the changes of the most recently undone action and return any changes to the output flat dast.
+    ///
+    /// If there is no action to redo, no changes are made. A read-only document cannot be changed by redoing.
+    pub fn redo(&mut self) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        if self.document_model.get_flags().read_only {
+            return Err(CoreError::ReadOnly);
+        }
+        let flat_dast_updates = match self.action_history.redo() {
+            Some(values) => self.restore_values(values)?,
+            None => HashMap::new(),
+        };
+        self.record_event(RecordedEvent::Redo)?;
+        Ok(flat_dast_updates)
+    }
+}
+
+#[cfg(test)]
+#[path = "action_history.test.rs"]
+mod test;
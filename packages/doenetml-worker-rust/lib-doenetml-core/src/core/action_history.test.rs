@@ -0,0 +1,71 @@
+use super::*;
+use crate::{graph::directed_graph::Taggable, graph_node::GraphNode, props::PropValue};
+
+/// A lookup that sets `GraphNode::State(0)` to `value`.
+fn state_value(value: i64) -> GraphNodeLookup<NodeValue> {
+    let mut lookup = GraphNodeLookup::new();
+    lookup.set_tag(
+        GraphNode::State(0),
+        NodeValue {
+            value: PropValue::Integer(value),
+            came_from_default: false,
+        },
+    );
+    lookup
+}
+
+fn get_state_value(lookup: Option<GraphNodeLookup<NodeValue>>) -> Option<PropValue> {
+    lookup.and_then(|lookup| {
+        lookup
+            .get_tag(&GraphNode::State(0))
+            .map(|node_value| node_value.value.clone())
+    })
+}
+
+#[test]
+fn undo_and_redo_return_previous_and_new_values() {
+    let mut history = ActionHistory::default();
+    assert!(!history.can_undo());
+    assert!(!history.can_redo());
+
+    history.record(state_value(0), state_value(1));
+    history.record(state_value(1), state_value(2));
+
+    assert_eq!(get_state_value(history.undo()), Some(PropValue::Integer(1)));
+    assert_eq!(get_state_value(history.undo()), Some(PropValue::Integer(0)));
+    assert!(history.undo().is_none());
+
+    assert_eq!(get_state_value(history.redo()), Some(PropValue::Integer(1)));
+    assert_eq!(get_state_value(history.redo()), Some(PropValue::Integer(2)));
+    assert!(history.redo().is_none());
+}
+
+#[test]
+fn recording_clears_redo_history() {
+    let mut history = ActionHistory::default();
+
+    history.record(state_value(0), state_value(1));
+    history.undo();
+    assert!(history.can_redo());
+
+    history.record(state_value(0), state_value(5));
+    assert!(!history.can_redo());
+    assert_eq!(get_state_value(history.undo()), Some(PropValue::Integer(0)));
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut history = ActionHistory::new(2);
+
+    history.record(state_value(0), state_value(1));
+    history.record(state_value(1), state_value(2));
+    history.record(state_value(2), state_value(3));
+
+    assert_eq!(get_state_value(history.undo()), Some(PropValue::Integer(2)));
+    assert_eq!(get_state_value(history.undo()), Some(PropValue::Integer(1)));
+    assert!(history.undo().is_none());
+
+    history.set_max_len(1);
+    assert_eq!(get_state_value(history.redo()), Some(PropValue::Integer(2)));
+    assert!(history.redo().is_none());
+}
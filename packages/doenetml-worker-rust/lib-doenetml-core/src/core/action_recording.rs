@@ -0,0 +1,338 @@
+//! # Action recording
+//!
+//! An `ActionRecording` captures a session with a document: the source and flags that `Core` was initialized with,
+//! followed by each action (or batch of actions, undo or redo) that changed the document, in the order they were dispatched.
+//! Each entry stores when it happened and a hash of the rendered flat dast right after it was applied.
+//!
+//! A recording can be serialized, e.g., to study how students interact with a document or to attach to a bug report.
+//! [`Core::replay_action_recording`] re-initializes `Core` from the recorded source and dispatches the recorded
+//! actions again, checking after each step that the document renders exactly as it did when it was recorded.
+//!
+//! Recording is opt-in; it is started with [`Core::start_action_recording`]. Besides actions, batches of actions,
+//! undo and redo, every other call that changes the document is recorded: scheduling actions, advancing the document's clock,
+//! changing the flags, editing the source, replacing generated children and applying a state snapshot.
+//! Initializing `Core` from a new source with [`Core::init_from_dast_root`] ends the recording.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::types::{Action, ComponentIdx},
+    dast::{DastElementContent, DastRoot, FlatDastRoot},
+    error::CoreError,
+};
+
+use super::{
+    Core,
+    document_flags::DocumentFlags,
+    scheduler::ScheduledActionId,
+    state_snapshot::{StateSnapshot, StateSnapshotError},
+};
+
+/// The version of the recording format produced by [`Core::start_action_recording`].
+/// Recordings with a different version are rejected by [`Core::replay_action_recording`].
+pub const ACTION_RECORDING_VERSION: u32 = 1;
+
+/// A recorded session with a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi, from_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct ActionRecording {
+    /// The version of the recording format.
+    pub version: u32,
+    /// The source that `Core` was initialized from.
+    pub dast_root: DastRoot,
+    /// The flags of the document when the recording started.
+    pub flags: DocumentFlags,
+    /// The hash of the flat dast when the recording started (see [`hash_flat_dast`]).
+    pub initial_hash: String,
+    /// The recorded events, in the order they occurred.
+    pub entries: Vec<RecordedEntry>,
+}
+
+/// A single event of an [`ActionRecording`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedEntry {
+    /// The number of milliseconds between the start of the recording and the event.
+    pub timestamp: f64,
+    pub event: RecordedEvent,
+    /// The hash of the flat dast after the event (see [`hash_flat_dast`]).
+    pub flat_dast_hash: String,
+}
+
+/// An event that changed the document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RecordedEvent {
+    /// An action dispatched with [`Core::dispatch_action`], serialized as it is sent by the host.
+    Action {
+        #[cfg_attr(feature = "web", tsify(type = "unknown"))]
+        action: serde_json::Value,
+    },
+    /// A batch of actions dispatched with [`Core::dispatch_actions`].
+    Batch {
+        #[cfg_attr(feature = "web", tsify(type = "unknown[]"))]
+        actions: Vec<serde_json::Value>,
+    },
+    /// A call to [`Core::undo`].
+    Undo,
+    /// A call to [`Core::redo`].
+    Redo,
+    /// A call to [`Core::advance_time`] (or [`Core::tick`]) that advanced the document's clock by `ms` milliseconds.
+    AdvanceTime { ms: f64 },
+    /// An action scheduled with [`Core::schedule_action`].
+    ScheduleAction {
+        #[cfg_attr(feature = "web", tsify(type = "unknown"))]
+        action: serde_json::Value,
+        delay: f64,
+        interval: Option<f64>,
+    },
+    /// A call to [`Core::cancel_scheduled_action`] that cancelled the action `id`.
+    CancelScheduledAction { id: ScheduledActionId },
+    /// A call to [`Core::set_flags`].
+    SetFlags { flags: DocumentFlags },
+    /// A call to [`Core::update_from_dast_root`] (or [`Core::update_dast_subtree`]) with the edited source `dast_root`.
+    UpdateSource {
+        #[cfg_attr(feature = "web", tsify(type = "unknown"))]
+        dast_root: serde_json::Value,
+    },
+    /// A call to [`Core::replace_generated_children`] that replaced the children generated for `parent` with `content`.
+    ReplaceGeneratedChildren {
+        parent: ComponentIdx,
+        #[cfg_attr(feature = "web", tsify(type = "unknown[]"))]
+        content: Vec<serde_json::Value>,
+    },
+    /// A call to [`Core::apply_state_snapshot`].
+    ApplyStateSnapshot { snapshot: StateSnapshot },
+}
+
+/// The reasons that [`Core::replay_action_recording`] may fail.
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Unsupported action recording version {found}; expected version {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    /// Step `0` is the document before any event; step `n` is the document after the `n`th event.
+    #[error(
+        "The document differs from the recording at step {step}: expected hash {expected}, found {found}"
+    )]
+    HashMismatch {
+        step: usize,
+        expected: String,
+        found: String,
+    },
+    /// A recorded action, source or state snapshot could not be read.
+    #[error("Cannot read the action of step {step}: {message}")]
+    InvalidAction { step: usize, message: String },
+    /// Dispatching the event of step `step` failed.
+    #[error("Step {step} of the recording failed: {err}")]
+    StepFailed { step: usize, err: CoreError },
+    #[error(transparent)]
+    Core(#[from] CoreError),
+}
+
+/// A recording in progress.
+#[derive(Debug)]
+pub(super) struct ActionRecorder {
+    recording: ActionRecording,
+    start: instant::Instant,
+}
+
+/// Compute a hash of `flat_dast` that does not depend on the order in which attributes are stored,
+/// so that the same document always has the same hash.
+///
+/// The hash is the 64-bit FNV-1a hash of the JSON serialization of `flat_dast` with its keys sorted, as a hex string.
+pub fn hash_flat_dast(flat_dast: &FlatDastRoot) -> String {
+    // `serde_json::Value` stores the fields of objects sorted by key.
+    let json = serde_json::to_value(flat_dast)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let hash = json.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+impl Core {
+    /// Start recording the actions dispatched to the document, discarding any recording in progress.
+    ///
+    /// The recording starts from the source that `Core` was initialized from, so it should be started
+    /// before any action is dispatched. Re-initializing `Core` ends the recording.
+    pub fn start_action_recording(&mut self) -> Result<(), CoreError> {
+        let dast_root = self
+            .dast_root
+            .clone()
+            .ok_or(CoreError::NotInitialized("start recording actions"))?;
+        let initial_hash = hash_flat_dast(&self.to_flat_dast()?);
+
+        self.action_recorder = Some(ActionRecorder {
+            recording: ActionRecording {
+                version: ACTION_RECORDING_VERSION,
+                dast_root,
+                flags: self.get_flags().clone(),
+                initial_hash,
+                entries: Vec::new(),
+            },
+            start: instant::Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Stop recording actions and return the recording, if one was in progress.
+    pub fn stop_action_recording(&mut self) -> Option<ActionRecording> {
+        self.action_recorder
+            .take()
+            .map(|recorder| recorder.recording)
+    }
+
+    /// The recording in progress, if any.
+    pub fn get_action_recording(&self) -> Option<&ActionRecording> {
+        self.action_recorder
+            .as_ref()
+            .map(|recorder| &recorder.recording)
+    }
+
+    /// Whether actions are being recorded.
+    pub(super) fn is_recording_actions(&self) -> bool {
+        self.action_recorder.is_some()
+    }
+
+    /// Serialize `action` for a recording.
+    pub(super) fn serialize_action_for_recording(
+        action: &Action,
+    ) -> Result<serde_json::Value, CoreError> {
+        Self::serialize_for_recording(action, "action")
+    }
+
+    /// Serialize `value`, the `description` passed to a call that changed the document, for a recording.
+    pub(super) fn serialize_for_recording(
+        value: &impl Serialize,
+        description: &str,
+    ) -> Result<serde_json::Value, CoreError> {
+        serde_json::to_value(value)
+            .map_err(|err| CoreError::InvalidAction(format!("Cannot record {description}: {err}")))
+    }
+
+    /// Add `event` to the recording in progress, along with the hash of the document after it.
+    /// Nothing is done if actions are not being recorded.
+    pub(super) fn record_event(&mut self, event: RecordedEvent) -> Result<(), CoreError> {
+        if !self.is_recording_actions() {
+            return Ok(());
+        }
+        let flat_dast_hash = hash_flat_dast(&self.to_flat_dast()?);
+
+        if let Some(recorder) = &mut self.action_recorder {
+            recorder.recording.entries.push(RecordedEntry {
+                timestamp: recorder.start.elapsed().as_secs_f64() * 1000.0,
+                event,
+                flat_dast_hash,
+            });
+        }
+        Ok(())
+    }
+
+    /// Re-initialize `Core` from the source and flags of `recording` and dispatch its events again,
+    /// checking that the document renders as it did when it was recorded before the first event and after each event.
+    ///
+    /// On error, `Core` is left in the state of the step that failed.
+    pub fn replay_action_recording(
+        &mut self,
+        recording: &ActionRecording,
+    ) -> Result<(), ReplayError> {
+        if recording.version != ACTION_RECORDING_VERSION {
+            return Err(ReplayError::UnsupportedVersion {
+                found: recording.version,
+                expected: ACTION_RECORDING_VERSION,
+            });
+        }
+
+        self.set_flags(recording.flags.clone())?;
+        self.init_from_dast_root(&recording.dast_root);
+        self.check_replay_hash(0, &recording.initial_hash)?;
+
+        for (idx, entry) in recording.entries.iter().enumerate() {
+            let step = idx + 1;
+            let invalid_action = |message: String| ReplayError::InvalidAction { step, message };
+            let deserialize_action = |action: &serde_json::Value| {
+                serde_json::from_value::<Action>(action.clone())
+                    .map_err(|err| invalid_action(err.to_string()))
+            };
+
+            let result = match &entry.event {
+                RecordedEvent::Action { action } => self
+                    .dispatch_action(deserialize_action(action)?)
+                    .map(|_| ()),
+                RecordedEvent::Batch { actions } => self
+                    .dispatch_actions(
+                        actions
+                            .iter()
+                            .map(deserialize_action)
+                            .collect::<Result<_, _>>()?,
+                    )
+                    .map(|_| ()),
+                RecordedEvent::Undo => self.undo().map(|_| ()),
+                RecordedEvent::Redo => self.redo().map(|_| ()),
+                RecordedEvent::AdvanceTime { ms } => self.advance_time(*ms).map(|_| ()),
+                RecordedEvent::ScheduleAction {
+                    action,
+                    delay,
+                    interval,
+                } => self
+                    .schedule_action(deserialize_action(action)?, *delay, *interval)
+                    .map(|_| ()),
+                RecordedEvent::CancelScheduledAction { id } => {
+                    self.cancel_scheduled_action(*id).map(|_| ())
+                }
+                RecordedEvent::SetFlags { flags } => self.set_flags(flags.clone()).map(|_| ()),
+                RecordedEvent::UpdateSource { dast_root } => {
+                    let dast_root = serde_json::from_value::<DastRoot>(dast_root.clone())
+                        .map_err(|err| invalid_action(err.to_string()))?;
+                    self.update_from_dast_root(dast_root).map(|_| ())
+                }
+                RecordedEvent::ReplaceGeneratedChildren { parent, content } => {
+                    let content = content
+                        .iter()
+                        .map(|node| serde_json::from_value::<DastElementContent>(node.clone()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| invalid_action(err.to_string()))?;
+                    self.replace_generated_children(*parent, &content)
+                        .map(|_| ())
+                }
+                RecordedEvent::ApplyStateSnapshot { snapshot } => {
+                    match self.apply_state_snapshot(snapshot) {
+                        Ok(_) => Ok(()),
+                        Err(StateSnapshotError::Core(err)) => Err(err),
+                        Err(err @ StateSnapshotError::UnsupportedVersion { .. }) => {
+                            return Err(invalid_action(err.to_string()));
+                        }
+                    }
+                }
+            };
+            result.map_err(|err| ReplayError::StepFailed { step, err })?;
+
+            self.check_replay_hash(step, &entry.flat_dast_hash)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the hash of the current flat dast is `expected`.
+    fn check_replay_hash(&mut self, step: usize, expected: &str) -> Result<(), ReplayError> {
+        let found = hash_flat_dast(&self.to_flat_dast()?);
+        if found != expected {
+            return Err(ReplayError::HashMismatch {
+                step,
+                expected: expected.to_string(),
+                found,
+            });
+        }
+        Ok(())
+    }
+}
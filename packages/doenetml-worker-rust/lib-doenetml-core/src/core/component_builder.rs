@@ -1,6 +1,10 @@
 //! Build the `structure_graph` and initialize `components`.
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    rc::Rc,
+};
 
 use anyhow::anyhow;
 use typed_index_collections::TiVec;
@@ -8,23 +12,30 @@ use typed_index_collections::TiVec;
 use crate::{
     components::{
         _Ref, Component, ComponentAttributes, ComponentCommon, ComponentCommonData, ComponentEnum,
-        ComponentNode, ComponentProps,
+        ComponentNode, ComponentProps, ComponentVariantProps,
         prelude::{
             ComponentIdx, Extending, ExtendingPropSource, FlatAttribute, KeyValueIgnoreCase,
             UntaggedContent,
         },
-        types::{PropDefinitionIdx, PropPointer},
+        types::{AttributeName, LocalPropIdx, PropDefinitionIdx, PropPointer},
     },
     dast::{
-        flat_dast::{Index, NormalizedNode, NormalizedRoot, Source},
-        ref_resolve::RefResolution,
+        DastWarning, Position,
+        flat_dast::{
+            FlatElement, FlatIndex, Index, NormalizedNode, NormalizedRoot, Source, SourceDoc,
+        },
+        ref_resolve::{PathIndex, RefResolution, index_text},
     },
     graph::directed_graph::{DirectedGraph, Taggable},
 };
 
 use super::{
     graph_node::{GraphNode, GraphNodeLookup},
-    props::{PropDefinition, StringCache},
+    props::{
+        ArrayElementProp, ArrayType, DataQuery, DataQueryResult, DataQueryResults,
+        ForRenderOutputs, PropDefinition, PropDefinitionMeta, PropValue, StringCache,
+        cache::PropWithMeta,
+    },
 };
 
 /// Initialize `structure_graph` and `components` based on a provided `normalized_root`.
@@ -50,6 +61,26 @@ pub struct ComponentBuilder {
     /// Stores whether a particular virtual node was created to house the children coming from another component
     /// because it was `extend`ing another component.
     pub children_came_from_extending_marker: GraphNodeLookup<bool>,
+    /// The element props that have been added to components, keyed by the array prop and the (zero-based) index
+    /// of the element, so that each element referenced in the document gets only one prop.
+    pub array_element_props: HashMap<(PropDefinitionIdx, PathIndex), LocalPropIdx>,
+    /// The virtual node that houses the children generated for a component after the document was initialized
+    /// (see [`ComponentBuilder::replace_generated_children`]). It is the last child of the component's children virtual node.
+    pub generated_children_virtual_nodes: HashMap<ComponentIdx, GraphNode>,
+    /// The location in the source of each attribute that was given to a component and recognized by it,
+    /// so that warnings about the value of an attribute can point to the attribute.
+    pub attribute_positions: HashMap<(ComponentIdx, AttributeName), AttributePosition>,
+    /// Warnings about each component found while building it, e.g., unrecognized attributes.
+    pub component_warnings: BTreeMap<ComponentIdx, Vec<DastWarning>>,
+    /// Warnings about names that are never referenced.
+    pub unused_name_warnings: Vec<DastWarning>,
+}
+
+/// The location of an attribute in the source.
+#[derive(Debug, Clone, Default)]
+pub struct AttributePosition {
+    pub position: Option<Position>,
+    pub source_doc: Option<SourceDoc>,
 }
 
 impl Default for ComponentBuilder {
@@ -68,6 +99,11 @@ impl ComponentBuilder {
             props: TiVec::new(),
             virtual_node_count: 0,
             children_came_from_extending_marker: GraphNodeLookup::new(),
+            array_element_props: HashMap::new(),
+            generated_children_virtual_nodes: HashMap::new(),
+            attribute_positions: HashMap::new(),
+            component_warnings: BTreeMap::new(),
+            unused_name_warnings: Vec::new(),
         }
     }
 
@@ -95,11 +131,54 @@ impl ComponentBuilder {
     /// This function relies upon the fact that `dast.nodes` will be the same length as `self.components`
     /// and exactly mirror it's structure (i.e., `dast.nodes[i].idx == self.components[i].idx`).
     fn init_from_normalized_root(&mut self, dast: &NormalizedRoot) {
-        self.init_normalized_root_without_extending(dast);
+        self.add_components(&dast.nodes);
+        self.set_unused_name_warnings(dast);
+    }
+
+    /// Create components for `nodes` and link them to the components they extend.
+    ///
+    /// The components are appended to `self.components`, so `nodes[i].idx` must be `self.components.len() + i`.
+    /// The nodes may extend components that were created before, but not the other way around.
+    fn add_components(&mut self, nodes: &[NormalizedNode]) {
+        let offset = self.components.len();
+        self.add_components_without_extending(nodes);
+        let end = offset + nodes.len();
+
+        // Components that (indirectly) extend themselves can never be linked to their referents,
+        // so they are turned into errors before any linking is done.
+        for idx in offset..end {
+            if let Some(mut cycle) = self.find_extending_cycle(nodes, offset, idx) {
+                // Start the message at the earliest element, so that every element in the cycle gets the same message.
+                let start = (0..cycle.len())
+                    .min_by_key(|i| cycle[*i])
+                    .unwrap_or_default();
+                cycle.rotate_left(start);
+
+                let elm = match &nodes[idx - offset] {
+                    NormalizedNode::Element(elm) => elm,
+                    _ => unreachable!("Only elements can extend"),
+                };
+                let mut labels = cycle
+                    .iter()
+                    .map(|cycle_idx| get_element_label(&nodes[*cycle_idx - offset]))
+                    .collect::<Vec<_>>();
+                labels.push(labels[0].clone());
+                self.components[ComponentIdx::new(idx)] = Component::new_error(
+                    format!("Circular dependency: {}", labels.join(" -> ")),
+                    None,
+                    ComponentCommonData {
+                        idx: ComponentIdx::new(idx),
+                        parent: elm.parent.map(ComponentIdx::from),
+                        position: elm.position.clone(),
+                        unrecognized_attributes: HashMap::new(),
+                    },
+                );
+            }
+        }
 
-        for idx in 0..self.components.len() {
+        for idx in offset..end {
             let component_idx = ComponentIdx::new(idx);
-            let elm = match &dast.nodes[idx] {
+            let elm = match &nodes[idx - offset] {
                 NormalizedNode::Element(elm) => elm,
                 _ => continue,
             };
@@ -108,9 +187,13 @@ impl ComponentBuilder {
             }
 
             let component = &self.components[component_idx];
-            if matches!(component.variant, ComponentEnum::_Ref(_)) {
+            if matches!(
+                component.variant,
+                ComponentEnum::_Ref(_) | ComponentEnum::_Error(_)
+            ) {
                 // The `_Ref` component is special, keeping a pointer to its referent
                 // but never getting connected to it.
+                // An `_Error` component was part of an `extend` cycle and is not connected either.
                 continue;
             }
             let ref_source = elm.extending.clone().unwrap();
@@ -123,6 +206,7 @@ impl ComponentBuilder {
                             self.add_component_extending_structure(component_idx, referent_idx);
                         }
                         Extending::Prop(prop_source) => {
+                            let prop_source = self.resolve_array_element(prop_source);
                             self.add_prop_extending_structure(component_idx, prop_source);
 
                             // Check if we are extending from prop where
@@ -154,6 +238,7 @@ impl ComponentBuilder {
                 Err(err) => {
                     self.components[component_idx] = Component::new_error(
                         format!("Error while extending: {err}"),
+                        None,
                         ComponentCommonData {
                             idx: component_idx,
                             parent: elm.parent.map(ComponentIdx::from),
@@ -166,6 +251,129 @@ impl ComponentBuilder {
         }
     }
 
+    /// Replace the children that were generated for `parent` with `children`, creating components for `nodes`,
+    /// the normalized nodes of the new children and their descendants.
+    ///
+    /// The new components are appended to `self.components`, so `nodes[i].idx` must be `self.components.len() + i`.
+    /// The generated children come after the children `parent` was created with.
+    ///
+    /// Returns the nodes of the previously generated children.
+    pub fn replace_generated_children(
+        &mut self,
+        parent: ComponentIdx,
+        children: &[UntaggedContent],
+        nodes: &[NormalizedNode],
+    ) -> Vec<GraphNode> {
+        self.add_components(nodes);
+
+        let generated_children_virtual_node =
+            match self.generated_children_virtual_nodes.get(&parent) {
+                Some(node) => *node,
+                None => {
+                    let node = self.new_virtual_node();
+                    let children_virtual_node = self
+                        .structure_graph
+                        .get_component_children_virtual_node(parent);
+                    self.structure_graph.add_edge(children_virtual_node, node);
+                    self.generated_children_virtual_nodes.insert(parent, node);
+                    node
+                }
+            };
+        let old_children = self
+            .structure_graph
+            .get_children(generated_children_virtual_node);
+        self.structure_graph
+            .remove_outgoing_edges(generated_children_virtual_node);
+        self.add_content_to_structure_graph(generated_children_virtual_node, children);
+
+        old_children
+    }
+
+    /// Replace the children of `parent` that came from the source in the range `old_range` with `children`,
+    /// creating components for `nodes`, the normalized nodes of the new children and their descendants.
+    /// `num_source_children` is the number of children `parent` had in the source before the replacement.
+    ///
+    /// The new components are appended to `self.components`, so `nodes[i].idx` must be `self.components.len() + i`.
+    /// The children of `parent` that did not come from the source (e.g., those from extending another component)
+    /// are left in place.
+    ///
+    /// Returns the nodes of the replaced children.
+    pub fn replace_source_children(
+        &mut self,
+        parent: ComponentIdx,
+        num_source_children: usize,
+        old_range: Range<usize>,
+        children: &[UntaggedContent],
+        nodes: &[NormalizedNode],
+    ) -> Vec<GraphNode> {
+        self.add_components(nodes);
+
+        let children_virtual_node = self
+            .structure_graph
+            .get_component_children_virtual_node(parent);
+        let old_children = self.structure_graph.get_children(children_virtual_node);
+        // The children that came from the source are preceded by the children from extending
+        // and followed by the virtual node of the generated children, if there is one.
+        let num_generated =
+            usize::from(self.generated_children_virtual_nodes.contains_key(&parent));
+        let offset = old_children.len() - num_generated - num_source_children;
+        let start = offset + old_range.start;
+        let end = offset + old_range.end;
+
+        self.structure_graph
+            .remove_outgoing_edges(children_virtual_node);
+        for node in old_children[..start].iter() {
+            self.structure_graph.add_edge(children_virtual_node, node);
+        }
+        self.add_content_to_structure_graph(children_virtual_node, children);
+        for node in old_children[end..].iter() {
+            self.structure_graph.add_edge(children_virtual_node, node);
+        }
+
+        old_children[start..end].to_vec()
+    }
+
+    /// If following the `extending` of the element at `idx` (and the `extending` of its referent, etc.)
+    /// leads back to `idx`, return the indices of the elements in that cycle, starting with `idx`.
+    ///
+    /// `nodes[i]` is the node of the element with index `offset + i`. Elements with smaller indices were linked earlier,
+    /// so they cannot lead back to `idx`.
+    fn find_extending_cycle(
+        &self,
+        nodes: &[NormalizedNode],
+        offset: usize,
+        idx: usize,
+    ) -> Option<Vec<usize>> {
+        let mut cycle = vec![idx];
+        let mut current = idx;
+        loop {
+            if matches!(
+                self.components[ComponentIdx::new(current)].variant,
+                ComponentEnum::_Ref(_)
+            ) {
+                return None;
+            }
+            current = match &nodes[current - offset] {
+                NormalizedNode::Element(FlatElement {
+                    extending: Some(source),
+                    ..
+                }) => source.idx(),
+                _ => return None,
+            };
+            if current < offset {
+                return None;
+            }
+            if current == idx {
+                return Some(cycle);
+            }
+            if cycle.contains(&current) {
+                // We are stuck in a cycle that does not include `idx`.
+                return None;
+            }
+            cycle.push(current);
+        }
+    }
+
     /// DoenetML coerces the type of `extending` to allow users to be sloppy with types.
     ///
     /// The default behavior is to ignore the fact that the type changed,
@@ -222,22 +430,32 @@ impl ComponentBuilder {
             if unresolved_path.len() != 1 {
                 return Err(anyhow!("Nested props not implemented yet"));
             }
-            if !unresolved_path[0].index.is_empty() {
-                return Err(anyhow!("Path indices not yet supported"));
-            }
+            let array_index = parse_array_index(&unresolved_path[0].index)?;
             let referenced_prop_name = &unresolved_path[0].name;
 
             // Look to see if there is a public prop with a matching name on `referent`
             return match referent
                 .get_public_local_prop_index_from_name_case_insensitive(referenced_prop_name)
             {
-                Some(referent_prop_idx) => Ok(Extending::Prop(ExtendingPropSource {
-                    prop_pointer: PropPointer {
-                        component_idx: referent.get_idx(),
-                        local_prop_idx: referent_prop_idx,
-                    },
-                    from_direct_ref,
-                })),
+                Some(referent_prop_idx) => {
+                    if array_index.is_some()
+                        && !referent.variant.get_prop_is_array(referent_prop_idx)
+                    {
+                        return Err(anyhow!(
+                            "prop {} of component {} is not an array and cannot be indexed",
+                            referenced_prop_name,
+                            referent.get_component_type()
+                        ));
+                    }
+                    Ok(Extending::Prop(ExtendingPropSource {
+                        prop_pointer: PropPointer {
+                            component_idx: referent.get_idx(),
+                            local_prop_idx: referent_prop_idx,
+                        },
+                        from_direct_ref,
+                        array_index,
+                    }))
+                }
                 None => Err(anyhow!(
                     "prop {} not found on component {}",
                     referenced_prop_name,
@@ -265,6 +483,7 @@ impl ComponentBuilder {
                         local_prop_idx: default_prop,
                     },
                     from_direct_ref,
+                    array_index: None,
                 })),
                 None => Err(anyhow!(
                     "Cannot extend {} via default prop because a default prop was not defined.",
@@ -293,7 +512,8 @@ impl ComponentBuilder {
             let prop_idx = self
                 .structure_graph
                 .get_component_props(prop_pointer.component_idx)[prop_pointer.local_prop_idx]
-                .prop_idx();
+                .prop_idx()
+                .expect("The props of a component are always `GraphNode::Prop`s");
             let new_component_type = self.props[prop_idx].preferred_component_type();
 
             let new_child = Component::from_tag_name(
@@ -312,18 +532,23 @@ impl ComponentBuilder {
         }
     }
 
-    /// Creates all `components` but sets all their `extending` fields to `None`.
+    /// Creates the components of `nodes` but sets all their `extending` fields to `None`.
     /// This is an intermediate step that needs to be done before resolving references in `extending`.
-    fn init_normalized_root_without_extending(&mut self, normalized_root: &NormalizedRoot) {
+    ///
+    /// The components are appended to `self.components`, so `nodes[i].idx` must be `self.components.len() + i`.
+    fn add_components_without_extending(&mut self, nodes: &[NormalizedNode]) {
+        let offset = self.components.len();
+
         // Keep track of special expanding behavior for components with `extend`.
-        self.component_preserve_refs =
-            TiVec::from_iter(std::iter::repeat_n(false, normalized_root.nodes.len()));
+        // (Components created while building, e.g., implicit children, have no entry, so the vec is resized.)
+        self.component_preserve_refs.resize(offset, false);
+        self.component_preserve_refs
+            .extend(std::iter::repeat_n(false, nodes.len()));
 
         // We are going to create components possibly out of order. We will track which components are created
         // and which are in the process of being created.
-        let mut components: Vec<Option<Component>> = std::iter::repeat_with(|| None)
-            .take(normalized_root.nodes.len())
-            .collect();
+        let mut components: Vec<Option<Component>> =
+            std::iter::repeat_with(|| None).take(nodes.len()).collect();
 
         // Creating the nodes lowest-index first. This
         //  1. is assumed by the algorithm which specializes the `extend` expansion/resolution, and
@@ -342,27 +567,29 @@ impl ComponentBuilder {
                 continue;
             }
 
-            let node = &normalized_root.nodes[idx];
-            match self.create_component(node, &components) {
+            let node = &nodes[idx];
+            match self.create_component(node, offset, &components) {
                 Ok(component) => {
                     components[idx] = Some(component);
                 }
                 Err(dependency_idx) => {
                     // If we have a dependency that needs to be created first, then we need to queue this node again.
                     queue.push(idx);
-                    queue.push(dependency_idx);
+                    queue.push(dependency_idx - offset);
                 }
             }
         }
 
         // Every component should now be created, so this unwrap should be safe.
-        self.components = components.into_iter().map(|c| c.unwrap()).collect();
+        self.components
+            .extend(components.into_iter().map(|c| c.unwrap()));
     }
 
     /// Create a component from `node`.
     ///  - `node` - The node to create a component from.
-    ///  - `components` - An array of already created components. When there is an `extending` field, the algorithm for deciding
-    ///    what component to create is complicated and depends on other existing components.
+    ///  - `offset` - The index of the component of `components[0]`.
+    ///  - `components` - An array of the components being created that are already created. When there is an `extending` field,
+    ///    the algorithm for deciding what component to create is complicated and depends on other existing components.
     ///
     /// Returns:
     ///  - `Ok(component)` - The component created from `node`.
@@ -370,6 +597,7 @@ impl ComponentBuilder {
     fn create_component(
         &mut self,
         node: &NormalizedNode,
+        offset: usize,
         components: &[Option<Component>],
     ) -> Result<Component, Index> {
         let component = match node {
@@ -460,23 +688,61 @@ impl ComponentBuilder {
                         // rather than   `<textInput name="i" /><textInput extend="$i.value" />`
                         #[allow(clippy::unnecessary_unwrap)]
                         let path = ref_resolution.unresolved_path.as_ref().unwrap();
-                        if path.len() == 1 && path[0].index.is_empty() {
+                        if path.len() == 1 {
                             let path_part = &path[0];
-                            let referent = components[ref_resolution.node_idx].as_ref();
+                            let referent = match ref_resolution.node_idx.checked_sub(offset) {
+                                Some(i) => components[i].as_ref(),
+                                None => Some(
+                                    &self.components[ComponentIdx::from(ref_resolution.node_idx)],
+                                ),
+                            };
                             if referent.is_none() {
                                 // We need information from this component, so it must be created first.
                                 return Err(ref_resolution.node_idx);
                             }
                             let referent = referent.unwrap();
+                            let referent_idx = referent.get_idx();
                             let referent_local_prop_idx = referent
                                 .get_public_local_prop_index_from_name_case_insensitive(
                                     &path_part.name,
                                 );
-                            if let Some(referent_local_prop_idx) = referent_local_prop_idx {
-                                let prop_idx =
-                                    self.structure_graph.get_component_props(referent.get_idx())
-                                        [referent_local_prop_idx]
-                                        .prop_idx();
+                            let referent_prop_is_array =
+                                referent_local_prop_idx.is_some_and(|local_prop_idx| {
+                                    referent.variant.get_prop_is_array(local_prop_idx)
+                                });
+                            // If the path has an index, we become a component for the indexed element of the array prop,
+                            // e.g., `<point name="P" />$P.coords[1]` should become a `<math>` rather than a `<point>`.
+                            // Invalid indices are skipped here; they result in an error when `extending` is determined.
+                            let prop_pointer = match (
+                                referent_local_prop_idx,
+                                parse_array_index(&path_part.index),
+                            ) {
+                                (Some(local_prop_idx), Ok(None)) => Some(PropPointer {
+                                    component_idx: referent_idx,
+                                    local_prop_idx,
+                                }),
+                                (Some(local_prop_idx), Ok(Some(index)))
+                                    if referent_prop_is_array =>
+                                {
+                                    Some(self.get_array_element_prop(
+                                        PropPointer {
+                                            component_idx: referent_idx,
+                                            local_prop_idx,
+                                        },
+                                        index,
+                                    ))
+                                }
+                                _ => None,
+                            };
+                            if let Some(prop_pointer) = prop_pointer {
+                                let prop_idx = self
+                                    .structure_graph
+                                    .get_component_props(prop_pointer.component_idx)
+                                    [prop_pointer.local_prop_idx]
+                                    .prop_idx()
+                                    .expect(
+                                        "The props of a component are always `GraphNode::Prop`s",
+                                    );
 
                                 let new_component_type =
                                     self.props[prop_idx].preferred_component_type();
@@ -497,11 +763,14 @@ impl ComponentBuilder {
                     &elm.attributes,
                 );
                 component.set_unrecognized_attributes(unrecognized_attributes);
+                self.add_unrecognized_attribute_warnings(&component);
+                self.add_attribute_value_warnings(&component, &elm.attributes);
 
                 component
             }
             NormalizedNode::Error(e) => Component::new_error(
                 e.message.clone(),
+                e.path_part_position.clone(),
                 ComponentCommonData {
                     idx: e.idx.into(),
                     parent: e.parent.map(ComponentIdx::from),
@@ -560,6 +829,13 @@ impl ComponentBuilder {
                 unused_attributes
                     .remove_ignore_case(attr_name)
                     .map_or_else(Vec::new, |v| {
+                        self.attribute_positions.insert(
+                            (component.get_idx(), attr_name),
+                            AttributePosition {
+                                position: v.position.clone(),
+                                source_doc: v.source_doc,
+                            },
+                        );
                         if v.children.is_empty() {
                             // if an attribute was supplied by given no content,
                             // then make it the same as giving it a empty string
@@ -591,6 +867,178 @@ impl ComponentBuilder {
         unused_attributes
     }
 
+    /// Add a warning for each attribute of `component` that it does not recognize.
+    ///
+    /// The `name` attribute is recognized by every component, and `_External` components accept arbitrary attributes.
+    /// The `class` and `data-*` attributes are passed through to the renderer of any component
+    /// (see [`is_pass_through_attribute`]), and the attributes added when merging an external document
+    /// are ignored (see [`is_source_attribute`]).
+    fn add_unrecognized_attribute_warnings(&mut self, component: &Component) {
+        if matches!(component.variant, ComponentEnum::_External(_)) {
+            return;
+        }
+        let mut attributes = component
+            .get_unrecognized_attributes()
+            .values()
+            .filter(|attr| {
+                !attr.name.eq_ignore_ascii_case("name")
+                    && !is_pass_through_attribute(&attr.name)
+                    && !is_source_attribute(&attr.name)
+            })
+            .collect::<Vec<_>>();
+        attributes.sort_by_key(|attr| {
+            (
+                attr.position
+                    .as_ref()
+                    .and_then(|position| position.start.offset),
+                attr.name.clone(),
+            )
+        });
+        for attr in attributes {
+            self.component_warnings
+                .entry(component.get_idx())
+                .or_default()
+                .push(DastWarning {
+                    message: format!(
+                        "Invalid attribute \"{}\" for a component of type <{}>.",
+                        attr.name,
+                        component.get_component_type()
+                    ),
+                    position: attr.position.clone(),
+                    source_doc: attr.source_doc,
+                });
+        }
+    }
+
+    /// Add a warning for each problem that the props of `component` report about the values of its attributes,
+    /// e.g., an invalid enum value (see [`PropUpdaterUntyped::warnings`](super::props::PropUpdaterUntyped::warnings)).
+    ///
+    /// Only attributes given as plain text are checked, since the value of an attribute
+    /// that contains references is not known until its props are calculated.
+    fn add_attribute_value_warnings(
+        &mut self,
+        component: &Component,
+        attributes: &[FlatAttribute],
+    ) {
+        let component_idx = component.get_idx();
+        let mut warnings: Vec<DastWarning> = Vec::new();
+
+        for prop_node in self.structure_graph.get_component_props(component_idx) {
+            let prop_idx = prop_node
+                .prop_idx()
+                .expect("The props of a component are always `GraphNode::Prop`s");
+            let updater = &self.props[prop_idx].updater;
+
+            let mut checked_attribute = None;
+            let data = DataQueryResults::from_vec(
+                updater
+                    .data_queries()
+                    .into_iter()
+                    .map(|query| {
+                        let value = match query {
+                            DataQuery::Attribute { attribute_name, .. } => {
+                                get_text_attribute_value(attributes, attribute_name).inspect(|_| {
+                                    checked_attribute.get_or_insert(attribute_name);
+                                })
+                            }
+                            _ => None,
+                        };
+                        DataQueryResult {
+                            values: value
+                                .into_iter()
+                                .map(|value| PropWithMeta {
+                                    value: PropValue::String(value.into()),
+                                    came_from_default: false,
+                                    changed: true,
+                                    origin: None,
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect(),
+            );
+            let Some(attribute_name) = checked_attribute else {
+                continue;
+            };
+
+            let attribute_position = self
+                .attribute_positions
+                .get(&(component_idx, attribute_name))
+                .cloned()
+                .unwrap_or_default();
+            for message in updater.warnings(&data) {
+                // Several props may be based on the same attribute
+                if warnings.iter().any(|warning| warning.message == message) {
+                    continue;
+                }
+                warnings.push(DastWarning {
+                    message,
+                    position: attribute_position.position.clone(),
+                    source_doc: attribute_position.source_doc,
+                });
+            }
+        }
+
+        if !warnings.is_empty() {
+            self.component_warnings
+                .entry(component_idx)
+                .or_default()
+                .extend(warnings);
+        }
+    }
+
+    /// Replace the warnings about names that are never referenced with a warning for each `name` attribute in `dast`
+    /// that is never referenced.
+    ///
+    /// A name counts as referenced if the element or one of its descendants is referenced,
+    /// since a reference such as `$a.b` is resolved to the element named `b` inside `a`.
+    pub fn set_unused_name_warnings(&mut self, dast: &NormalizedRoot) {
+        self.unused_name_warnings.clear();
+        let mut referenced = vec![false; dast.nodes.len()];
+        for node in &dast.nodes {
+            let NormalizedNode::Element(FlatElement {
+                extending: Some(source),
+                ..
+            }) = node
+            else {
+                continue;
+            };
+            // A referent that is not in `dast` (e.g., a generated child) has no `name` attribute to check.
+            let mut idx = Some(source.idx());
+            while let Some(referenced_idx) = idx {
+                match referenced.get_mut(referenced_idx) {
+                    Some(referenced) if !*referenced => *referenced = true,
+                    _ => break,
+                }
+                idx = dast.nodes[referenced_idx].parent();
+            }
+        }
+
+        for node in &dast.nodes {
+            let NormalizedNode::Element(elm) = node else {
+                continue;
+            };
+            if referenced[elm.idx] {
+                continue;
+            }
+            let Some(name_attr) = elm
+                .attributes
+                .iter()
+                .find(|attr| attr.name.eq_ignore_ascii_case("name"))
+            else {
+                continue;
+            };
+            self.unused_name_warnings.push(DastWarning {
+                message: format!(
+                    "The name \"{}\" is never referenced.",
+                    get_element_label(node)
+                ),
+                position: name_attr.position.clone(),
+                source_doc: name_attr.source_doc,
+            });
+        }
+    }
+
     /// Add every node in `content` as a child node of `parent` in `structure_graph`.
     /// If `content` contains string children, they are added to `self.strings`.
     fn add_content_to_structure_graph(&mut self, parent: GraphNode, content: &[UntaggedContent]) {
@@ -700,12 +1148,107 @@ impl ComponentBuilder {
             let component_props = self.structure_graph.get_component_props(component_idx);
             let referent_props = self.structure_graph.get_component_props(referent_idx);
 
-            for (comp_prop, ref_prop) in component_props.iter().zip(referent_props.iter()) {
+            // Only the props declared by the component type are linked. Any additional props
+            // are elements of array props, which may differ between `component` and `referent`.
+            let num_props = self.components[component_idx].variant.get_num_props();
+            for (comp_prop, ref_prop) in component_props
+                .iter()
+                .zip(referent_props.iter())
+                .take(num_props)
+            {
                 self.structure_graph.add_edge(comp_prop, ref_prop);
             }
         }
     }
 
+    /// If `prop_source` refers to an element of an array prop,
+    /// return a `prop_source` that refers to the prop for that element instead.
+    fn resolve_array_element(&mut self, prop_source: ExtendingPropSource) -> ExtendingPropSource {
+        match prop_source.array_index {
+            Some(index) => ExtendingPropSource {
+                prop_pointer: self.get_array_element_prop(prop_source.prop_pointer, index),
+                array_index: None,
+                ..prop_source
+            },
+            None => prop_source,
+        }
+    }
+
+    /// Get a pointer to the prop for the element (or range of elements) at `index` of the array prop `array_prop`.
+    ///
+    /// The element prop is a prop of the component owning `array_prop` with a local index beyond
+    /// the props declared by the component. It is created the first time it is requested.
+    fn get_array_element_prop(&mut self, array_prop: PropPointer, index: PathIndex) -> PropPointer {
+        let component_idx = array_prop.component_idx;
+        let array_prop_idx = self.structure_graph.get_component_props(component_idx)
+            [array_prop.local_prop_idx]
+            .prop_idx()
+            .expect("The props of a component are always `GraphNode::Prop`s");
+
+        if let Some(local_prop_idx) = self.array_element_props.get(&(array_prop_idx, index)) {
+            return PropPointer {
+                component_idx,
+                local_prop_idx: *local_prop_idx,
+            };
+        }
+
+        let array_prop_definition = &self.props[array_prop_idx];
+        // The `#[prop]` macro checks that props marked `is_array` have an array type.
+        let array_type = ArrayType::from_value_type(array_prop_definition.variant)
+            .expect("Array props always have an array type");
+        let element_type = array_type.indexed_type(index);
+        // Only an element counted from the start of the array has a fixed position,
+        // so only those can be calculated by the array prop's own element updater.
+        let element_updater = match index {
+            PathIndex::Single(idx) if idx > 0 => array_prop_definition
+                .updater
+                .element_updater(idx as usize - 1),
+            _ => None,
+        };
+        let updater = element_updater.unwrap_or_else(|| {
+            Rc::new(ArrayElementProp::new(
+                array_prop.local_prop_idx,
+                index,
+                array_type,
+            ))
+        });
+
+        let local_prop_idx = LocalPropIdx::new(
+            self.structure_graph
+                .get_component_props(component_idx)
+                .len(),
+        );
+        let prop_pointer = PropPointer {
+            component_idx,
+            local_prop_idx,
+        };
+        let element_prop = PropDefinition {
+            meta: PropDefinitionMeta {
+                name: array_prop_definition.meta.name,
+                prop_pointer,
+                profile: None,
+                for_render: ForRenderOutputs::default(),
+                public: false,
+            },
+            updater,
+            variant: element_type,
+        };
+
+        let prop_graph_node = GraphNode::Prop(self.props.len());
+        self.props.push(element_prop);
+        let props_virtual_node = self
+            .structure_graph
+            .get_nth_child(component_idx.as_graph_node(), 2)
+            .expect("A component node should always have props in the structure graph");
+        self.structure_graph
+            .add_edge(props_virtual_node, prop_graph_node);
+
+        self.array_element_props
+            .insert((array_prop_idx, index), local_prop_idx);
+
+        prop_pointer
+    }
+
     /// Add to `structure_graph` the relationships from a component extending the prop of another component,
     /// such as when, in `<textInput name="i"/>$i.value`,
     /// the reference `$i.value` becomes a `<text>` that is extending the value prop of the `<textInput>`.
@@ -766,3 +1309,78 @@ impl ComponentBuilder {
         }
     }
 }
+
+/// Convert the index of a path part, as in `$P.coords[2]`, `$P.coords[-1]` or `$P.coords[1:2]`, into a `PathIndex`.
+/// Returns `Ok(None)` if the path part has no index.
+pub(crate) fn parse_array_index(index: &[FlatIndex]) -> Result<Option<PathIndex>, anyhow::Error> {
+    match index {
+        [] => Ok(None),
+        [index] => match PathIndex::from_flat_index(index) {
+            Some(Some(path_index)) => Ok(Some(path_index)),
+            Some(None) => Err(anyhow!(
+                "Invalid array index `{}`; indices must be nonzero integers or ranges of them",
+                index_text(index)
+            )),
+            None => Err(anyhow!(
+                "Array indices that contain references are not yet supported"
+            )),
+        },
+        _ => Err(anyhow!("Nested array indices not implemented yet")),
+    }
+}
+
+/// The value of the attribute `attribute_name` in `attributes` if it is given as plain text,
+/// e.g., `"chapter"` for `type="chapter"`, or `""` if it is given without a value.
+/// Returns `None` if the attribute is not given or contains references.
+fn get_text_attribute_value(attributes: &[FlatAttribute], attribute_name: &str) -> Option<String> {
+    let attribute = attributes
+        .iter()
+        .find(|attr| attr.name.eq_ignore_ascii_case(attribute_name))?;
+    attribute
+        .children
+        .iter()
+        .map(|child| match child {
+            UntaggedContent::Text(text) => Some(text.as_str()),
+            UntaggedContent::Ref(_) => None,
+        })
+        .collect()
+}
+
+/// Whether an unrecognized attribute named `name` is meant for the renderer,
+/// e.g., to style the element (`class`) or to store data on it (`data-*`).
+fn is_pass_through_attribute(name: &str) -> bool {
+    name.eq_ignore_ascii_case("class")
+        || name
+            .get(..5)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("data-"))
+}
+
+/// Whether `name` is the name of an attribute added when an external document is merged into an element,
+/// i.e., `source:sequence` or `source-n:name` (see [`FlatRoot::merge_external_documents`]).
+///
+/// [`FlatRoot::merge_external_documents`]: crate::dast::flat_dast::FlatRoot::merge_external_documents
+fn is_source_attribute(name: &str) -> bool {
+    name == "source:sequence" || (name.starts_with("source-") && name.ends_with(":name"))
+}
+
+/// A label for the element of `node` for use in messages: the value of its `name` attribute
+/// or, if it has no name, its tag (e.g., `<text>`).
+fn get_element_label(node: &NormalizedNode) -> String {
+    match node {
+        NormalizedNode::Element(elm) => elm
+            .attributes
+            .iter()
+            .find(|attr| attr.name.eq_ignore_ascii_case("name"))
+            .map(|attr| {
+                attr.children
+                    .iter()
+                    .filter_map(|child| match child {
+                        UntaggedContent::Text(t) => Some(t.as_str()),
+                        _ => None,
+                    })
+                    .collect::<String>()
+            })
+            .unwrap_or_else(|| format!("<{}>", elm.name)),
+        NormalizedNode::Error(_) => "<_error>".to_string(),
+    }
+}
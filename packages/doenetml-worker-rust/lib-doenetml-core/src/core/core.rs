@@ -1,18 +1,98 @@
 //! A version of `Core` based on `DirectedGraph`
 
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
 use crate::dast::{
-    DastRoot, FlatDastRoot,
+    DastRoot, DastWarning, FlatDastRoot,
+    document_loader::{CachingDocumentLoader, DocumentLoader},
     flat_dast::{
-        FlatFragment, FlatNode, FlatPathPart, FlatRoot, Index, NormalizedRoot, UntaggedContent,
+        ErrorType, FlatError, FlatFragment, FlatNode, FlatPathPart, FlatRoot, Index,
+        NormalizedNode, NormalizedRoot, UntaggedContent,
     },
     ref_expand::Expander,
-    ref_resolve::{IndexResolution, RefResolution, ResolutionError, Resolver},
+    ref_resolve::{IndexResolution, RefResolution, Resolver},
 };
 
 use super::{
-    component_builder::ComponentBuilder, document_model::DocumentModel,
+    action_history::ActionHistory,
+    action_recording::ActionRecorder,
+    component_builder::ComponentBuilder,
+    document_model::DocumentModel,
     document_renderer::DocumentRenderer,
+    error::CoreError,
+    generated_children::GeneratedChildren,
+    graph_node::GraphNode,
+    prop_subscriptions::{PropSubscriptions, SubscriptionId},
+    scheduler::Scheduler,
+    state_snapshot::StateSnapshotError,
 };
+use crate::components::ComponentCommon;
+use crate::components::types::ComponentIdx;
+use crate::graph::directed_graph::Taggable;
+
+/// What was dropped when the components were created again by [`Core::rebuild_from_normalized_root`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RebuildReport {
+    /// The subscriptions to props of components that were replaced.
+    pub removed_subscriptions: Vec<SubscriptionId>,
+    /// The number of scheduled actions of components that were replaced.
+    pub num_cancelled_actions: usize,
+    /// The components whose generated children were removed.
+    pub removed_generated_children: Vec<ComponentIdx>,
+    /// Whether there were actions to undo or redo.
+    pub cleared_history: bool,
+}
+
+impl RebuildReport {
+    fn merge(&mut self, other: RebuildReport) {
+        self.removed_subscriptions
+            .extend(other.removed_subscriptions);
+        self.num_cancelled_actions += other.num_cancelled_actions;
+        self.removed_generated_children
+            .extend(other.removed_generated_children);
+        self.cleared_history |= other.cleared_history;
+    }
+
+    /// A warning describing what was dropped, if anything.
+    fn to_warning(&self) -> Option<DastWarning> {
+        let mut dropped = Vec::new();
+        if !self.removed_subscriptions.is_empty() {
+            dropped.push(format!(
+                "{} prop subscription(s)",
+                self.removed_subscriptions.len()
+            ));
+        }
+        if self.num_cancelled_actions > 0 {
+            dropped.push(format!(
+                "{} scheduled action(s)",
+                self.num_cancelled_actions
+            ));
+        }
+        if !self.removed_generated_children.is_empty() {
+            dropped.push(format!(
+                "the generated children of {} component(s)",
+                self.removed_generated_children.len()
+            ));
+        }
+        if self.cleared_history {
+            dropped.push("the undo history".to_string());
+        }
+        match dropped.is_empty() {
+            true => None,
+            false => Some(DastWarning {
+                message: format!(
+                    "Replacing a circular dependency with errors dropped {}",
+                    dropped.join(", ")
+                ),
+                position: None,
+                source_doc: None,
+            }),
+        }
+    }
+}
 
 /// Core stores all hydrated components, keeps track of caching data, and tracks dependencies.
 /// It is also in charge of marking nodes as dirty when they need to be recalculated and calling
@@ -22,6 +102,24 @@ pub struct Core {
     pub document_model: DocumentModel,
     pub document_renderer: DocumentRenderer,
     pub resolver: Option<Resolver>,
+    /// The changes made by recent actions, so that they can be undone and redone.
+    pub action_history: ActionHistory,
+    /// The recording of the actions dispatched since [`Core::start_action_recording`] was called, if any.
+    pub(super) action_recorder: Option<ActionRecorder>,
+    /// The props whose changes are reported to the host after each action.
+    pub(super) prop_subscriptions: PropSubscriptions,
+    /// The document's clock and the actions scheduled to be sent when it advances.
+    pub(super) scheduler: Scheduler,
+    /// The DAST and normalized root that `Core` was initialized from,
+    /// kept so that an edit of the source can be applied incrementally.
+    pub(super) dast_root: Option<DastRoot>,
+    pub(super) normalized_root: Option<NormalizedRoot>,
+    /// The children generated for components by [`Core::replace_generated_children`].
+    pub(super) generated_children: HashMap<ComponentIdx, GeneratedChildren>,
+    /// Looks up the external documents referred to by `extend="doenet:..."` attributes, if the host supplied one.
+    pub(super) document_loader: Option<Box<dyn DocumentLoader>>,
+    /// The external documents loaded by `document_loader`, by URI, so that each is loaded only once.
+    pub(super) external_documents: RefCell<HashMap<String, DastRoot>>,
 }
 
 impl Default for Core {
@@ -36,30 +134,70 @@ impl Core {
             document_model: DocumentModel::new_with_root_data_query(),
             document_renderer: DocumentRenderer::new(),
             resolver: None,
+            action_history: ActionHistory::default(),
+            action_recorder: None,
+            prop_subscriptions: PropSubscriptions::default(),
+            scheduler: Scheduler::default(),
+            dast_root: None,
+            normalized_root: None,
+            generated_children: HashMap::new(),
+            document_loader: None,
+            external_documents: RefCell::new(HashMap::new()),
         }
     }
 
-    /// Create a `NormalizedRoot` from `dast_root`, which involves creating a `FlatDast`
-    /// and expanding all references to elements (or errors).
+    /// Set the loader used to look up the external documents referred to by `extend` or `copy` attributes
+    /// beginning with `doenet:`. Without a loader, such attributes are reported as invalid.
+    ///
+    /// The loader should be set before calling [`Core::init_from_dast_root`]. It is kept when core is re-initialized,
+    /// along with the documents it loaded, so that each document is loaded only once per loader.
+    /// A document that has changed since it was loaded is not loaded again
+    /// until [`Core::clear_external_documents`] is called or the loader is replaced.
+    pub fn set_document_loader(&mut self, document_loader: impl DocumentLoader + 'static) {
+        self.document_loader = Some(Box::new(document_loader));
+        self.clear_external_documents();
+    }
+
+    /// Forget the external documents loaded so far, so that the next call to [`Core::init_from_dast_root`]
+    /// loads them again from the document loader, picking up any changes.
+    pub fn clear_external_documents(&mut self) {
+        self.external_documents.get_mut().clear();
+    }
+
+    /// Create a `NormalizedRoot` from `dast_root`, which involves creating a `FlatDast`,
+    /// merging any external documents and expanding all references to elements (or errors).
     /// Sets the `resolver` so that it can be reused if needed.
     /// Returns the `NormalizedRoot`
     pub fn normalized_root_from_dast_root(&mut self, dast_root: &DastRoot) -> NormalizedRoot {
+        let (normalized_root, resolver) = self.normalize_dast_root(dast_root);
+        self.resolver = Some(resolver);
+        normalized_root
+    }
+
+    /// Create a `NormalizedRoot` from `dast_root` along with the `Resolver` for its references.
+    pub(super) fn normalize_dast_root(&self, dast_root: &DastRoot) -> (NormalizedRoot, Resolver) {
         let mut flat_root = FlatRoot::from_dast(dast_root);
+        if let Some(document_loader) = &self.document_loader {
+            flat_root.merge_external_documents(&CachingDocumentLoader {
+                loader: document_loader.as_ref(),
+                cache: &self.external_documents,
+            });
+        }
         let mut resolver = Expander::expand(&mut flat_root);
         flat_root.compactify(Some(&mut resolver));
-        self.resolver = Some(resolver);
-        flat_root.into_normalized_root()
+        (flat_root.into_normalized_root(), resolver)
     }
 
     pub fn add_nodes_to_resolver(
         &mut self,
         flat_fragment: &FlatFragment,
         index_resolution: IndexResolution,
-    ) {
+    ) -> Result<(), CoreError> {
         self.resolver
             .as_mut()
-            .expect("Cannot add nodes to resolver before it is created")
+            .ok_or(CoreError::NotInitialized("add nodes to resolver"))?
             .add_nodes(flat_fragment, index_resolution);
+        Ok(())
     }
 
     /// Replace the index resolutions of the parent of `index_resolution` with `components`,
@@ -68,25 +206,47 @@ impl Core {
         &mut self,
         components: &[UntaggedContent],
         index_resolution: IndexResolution,
-    ) {
+    ) -> Result<(), CoreError> {
         self.resolver
             .as_mut()
-            .expect("Cannot replace index resolutions in resolver before it is created")
+            .ok_or(CoreError::NotInitialized(
+                "replace index resolutions in resolver",
+            ))?
             .replace_index_resolutions(components, index_resolution);
+        Ok(())
     }
 
-    pub fn delete_nodes_from_resolver(&mut self, nodes: &[FlatNode]) {
+    pub fn delete_nodes_from_resolver(&mut self, nodes: &[FlatNode]) -> Result<(), CoreError> {
         self.resolver
             .as_mut()
-            .expect("Cannot delete noes from resolver before it is created")
+            .ok_or(CoreError::NotInitialized("delete nodes from resolver"))?
             .delete_nodes(nodes);
+        Ok(())
     }
 
-    pub fn calculate_root_names(&self) -> Vec<Option<String>> {
-        self.resolver
+    /// Stop rendering the components that were removed from the document model
+    /// (including those created while building, e.g., implicit children),
+    /// and drop their scheduled actions and subscriptions.
+    pub(super) fn drop_removed_components(&mut self) {
+        for component_idx in (0..self.document_model.get_num_components()).map(ComponentIdx::new) {
+            if self.document_model.is_removed(component_idx) {
+                self.document_renderer
+                    .in_render_tree
+                    .set_tag(component_idx.as_graph_node(), false);
+                self.scheduler.cancel_component(component_idx);
+                for origin in self.prop_subscriptions.remove_component(component_idx) {
+                    self.document_model.remove_null_query_node(origin);
+                }
+            }
+        }
+    }
+
+    pub fn calculate_root_names(&self) -> Result<Vec<Option<String>>, CoreError> {
+        Ok(self
+            .resolver
             .as_ref()
-            .expect("Cannot calculate root names from resolver before it is created")
-            .calculate_root_names()
+            .ok_or(CoreError::NotInitialized("calculate root names"))?
+            .calculate_root_names())
     }
 
     pub fn resolve_path<T: AsRef<[FlatPathPart]>>(
@@ -94,11 +254,12 @@ impl Core {
         path: T,
         origin: Index,
         skip_parent_search: bool,
-    ) -> Result<RefResolution, ResolutionError> {
-        self.resolver
+    ) -> Result<RefResolution, CoreError> {
+        Ok(self
+            .resolver
             .as_ref()
-            .expect("Cannot resolve path before resolver is created")
-            .resolve(path, origin, skip_parent_search)
+            .ok_or(CoreError::NotInitialized("resolve path"))?
+            .resolve(path, origin, skip_parent_search)?)
     }
 
     /// Initialize `structure_graph`, `state_graph`, and other data
@@ -109,21 +270,276 @@ impl Core {
     ///
     /// A [`Resolver`] is saved to `core. It can be used to look up a `ComponentIdx` by name (useful for testing).
     /// One can also add or delete nodes from it.
+    ///
+    /// External documents loaded by a previous initialization are reused rather than loaded again
+    /// (see [`Core::clear_external_documents`]).
     pub fn init_from_dast_root(&mut self, dast_root: &DastRoot) {
         // If we are initializing, we need to make sure that pre-existing data doesn't mess things up.
-        // The easiest way is to recreate ourself.
+        // The easiest way is to recreate ourself, keeping only the flags and document loader set by the host
+        // (along with the documents it loaded).
         // TODO: think about whether we can update existing structures.
+        let flags = self.get_flags().clone();
+        let document_loader = self.document_loader.take();
+        let external_documents = self.external_documents.take();
         *self = Self::new();
+        // No props have been calculated yet, so no props are marked stale.
+        let _ = self.document_model.set_flags(flags);
+        self.document_loader = document_loader;
+        self.external_documents = RefCell::new(external_documents);
 
         let normalized_flat_root = self.normalized_root_from_dast_root(dast_root);
 
+        // Components whose props depend on themselves are replaced with errors
+        // once the cycle is found while rendering (see [`Core::to_flat_dast`]).
         let component_builder = ComponentBuilder::from_normalized_root(&normalized_flat_root);
         self.document_model.init_from_builder(component_builder);
+
+        self.dast_root = Some(dast_root.clone());
+        self.normalized_root = Some(normalized_flat_root);
+    }
+
+    /// Replace the elements whose components are part of `cycle`, a circular dependency among props,
+    /// with errors whose message names the components in the cycle, and rebuild the document from them.
+    /// The state of the other components is kept.
+    ///
+    /// Returns the circular dependency as an error if no element could be replaced.
+    fn replace_cycle_with_errors(
+        &mut self,
+        cycle: &[GraphNode],
+    ) -> Result<RebuildReport, CoreError> {
+        let Some(normalized_root) = self.normalized_root.as_mut() else {
+            return Err(CoreError::CircularDependency(cycle.to_vec()));
+        };
+        let document_model = &self.document_model;
+        let root_names = match &self.resolver {
+            Some(resolver) => resolver.calculate_root_names(),
+            None => Vec::new(),
+        };
+
+        // Components created while building (e.g., the implicit child of `<text extend="$i.value" />`)
+        // have no element of their own, so they are attributed to their nearest ancestor that does.
+        let mut component_indices = cycle
+            .iter()
+            .filter_map(|prop_node| {
+                let mut component_idx = document_model
+                    .get_prop_pointer(*prop_node)
+                    .ok()?
+                    .component_idx;
+                while !matches!(
+                    normalized_root.nodes.get(usize::from(component_idx)),
+                    Some(NormalizedNode::Element(_))
+                ) {
+                    component_idx = document_model
+                        .get_component(component_idx)
+                        .ok()?
+                        .get_parent()?;
+                }
+                Some(usize::from(component_idx))
+            })
+            .collect::<Vec<_>>();
+        component_indices.dedup();
+        if component_indices.len() > 1 && component_indices.first() == component_indices.last() {
+            component_indices.pop();
+        }
+        if component_indices.is_empty() {
+            return Err(CoreError::CircularDependency(cycle.to_vec()));
+        }
+
+        // Start the cycle at the earliest component so that the message doesn't depend on
+        // which prop happened to be resolved first.
+        let start = (0..component_indices.len())
+            .min_by_key(|i| component_indices[*i])
+            .unwrap_or_default();
+        component_indices.rotate_left(start);
+
+        // Unnamed components (e.g., the `<text>` created from `$b`) are labeled by their nearest named ancestor.
+        let mut labels = component_indices
+            .iter()
+            .map(|idx| {
+                let mut node_idx = Some(*idx);
+                while let Some(i) = node_idx {
+                    if let Some(Some(name)) = root_names.get(i) {
+                        return name.clone();
+                    }
+                    node_idx = match &normalized_root.nodes[i] {
+                        NormalizedNode::Element(elm) => elm.parent,
+                        NormalizedNode::Error(err) => err.parent,
+                    };
+                }
+                match &normalized_root.nodes[*idx] {
+                    NormalizedNode::Element(elm) => format!("<{}>", elm.name),
+                    NormalizedNode::Error(_) => "<_error>".to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+        labels.dedup();
+        if labels.len() > 1 && labels.first() == labels.last() {
+            labels.pop();
+        }
+        labels.push(labels[0].clone());
+        let message = format!("Circular dependency: {}", labels.join(" -> "));
+
+        let mut replaced = false;
+        for idx in component_indices {
+            if let NormalizedNode::Element(elm) = &normalized_root.nodes[idx] {
+                normalized_root.nodes[idx] = NormalizedNode::Error(FlatError {
+                    idx,
+                    parent: elm.parent,
+                    message: message.clone(),
+                    error_type: ErrorType::Error,
+                    code: None,
+                    args: None,
+                    unresolved_path: None,
+                    position: elm.position.clone(),
+                    path_part_position: None,
+                    source_doc: elm.source_doc,
+                });
+                replaced = true;
+            }
+        }
+
+        if !replaced {
+            return Err(CoreError::CircularDependency(cycle.to_vec()));
+        }
+        self.rebuild_from_normalized_root()
     }
 
-    pub fn to_flat_dast(&mut self) -> FlatDastRoot {
-        self.document_renderer
-            .render_flat_dast(&self.document_model)
+    /// Create the components again from the normalized root, e.g., after some of its elements were replaced with errors,
+    /// keeping the interactive state of the components as in [`Core::reinit_from_dast_root_preserving_state`].
+    ///
+    /// The prop subscriptions and scheduled actions of components that still have the same type are kept;
+    /// those of the other components are dropped. The generated children (see [`Core::replace_generated_children`])
+    /// are removed, as they are not part of the normalized root, and the action history is cleared,
+    /// as it refers to the nodes of the previous dependency graph. What was dropped is returned.
+    fn rebuild_from_normalized_root(&mut self) -> Result<RebuildReport, CoreError> {
+        let snapshot = self.get_state_snapshot()?;
+        let removed_generated_children = self.clear_generated_children()?;
+        let component_types = self
+            .document_model
+            .get_component_indices()
+            .map(|component_idx| {
+                Ok((
+                    component_idx,
+                    self.document_model.get_component_type(component_idx)?,
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, CoreError>>()?;
+        let cleared_history = self.action_history.can_undo() || self.action_history.can_redo();
+        let prop_subscriptions = std::mem::take(&mut self.prop_subscriptions);
+        let mut scheduler = std::mem::take(&mut self.scheduler);
+        let mut action_history = std::mem::take(&mut self.action_history);
+        action_history.clear();
+
+        let flags = self.get_flags().clone();
+        let document_loader = self.document_loader.take();
+        let external_documents = self.external_documents.take();
+        // The document is rebuilt from the same source, so the recording in progress continues.
+        let action_recorder = self.action_recorder.take();
+        let resolver = self.resolver.take();
+        let dast_root = self.dast_root.take();
+        let normalized_root = self.normalized_root.take();
+        *self = Self::new();
+        self.document_model.set_flags(flags)?;
+        self.document_loader = document_loader;
+        self.external_documents = RefCell::new(external_documents);
+        self.action_recorder = action_recorder;
+        self.resolver = resolver;
+        self.dast_root = dast_root;
+
+        if let Some(normalized_root) = &normalized_root {
+            self.document_model
+                .init_from_builder(ComponentBuilder::from_normalized_root(normalized_root));
+        }
+        self.normalized_root = normalized_root;
+
+        let replaced = component_types
+            .into_iter()
+            .filter(|(component_idx, component_type)| {
+                self.document_model
+                    .get_component_type(*component_idx)
+                    .ok()
+                    .as_ref()
+                    != Some(component_type)
+            })
+            .map(|(component_idx, _)| component_idx)
+            .collect::<HashSet<_>>();
+        let num_cancelled_actions = replaced
+            .iter()
+            .map(|component_idx| scheduler.cancel_component(*component_idx))
+            .sum();
+        self.scheduler = scheduler;
+        self.action_history = action_history;
+        let removed_subscriptions = self.restore_prop_subscriptions(prop_subscriptions, &replaced);
+
+        match self.restore_state_snapshot(&snapshot) {
+            Ok(_) => {}
+            Err(StateSnapshotError::Core(err)) => return Err(err),
+            Err(StateSnapshotError::UnsupportedVersion { .. }) => {
+                unreachable!("A snapshot created by `Core` should have a supported version")
+            }
+        }
+        Ok(RebuildReport {
+            removed_subscriptions,
+            num_cancelled_actions,
+            removed_generated_children,
+            cleared_history,
+        })
+    }
+
+    /// Initialize from `dast_root` as in [`Core::init_from_dast_root`], but keep the values of the `State` nodes
+    /// of components that still have the same name (or path, if unnamed) and type after re-initialization.
+    ///
+    /// This is intended for when an author edits the source of a document that has been interacted with,
+    /// so that, for example, the points that were dragged or the text that was typed is not lost.
+    /// Changes made by actions to `String` nodes are not kept, since they may conflict with the new source.
+    /// The action history is cleared.
+    ///
+    /// If the state of the previous document cannot be read, an error is returned and the document is not changed.
+    pub fn reinit_from_dast_root_preserving_state(
+        &mut self,
+        dast_root: &DastRoot,
+    ) -> Result<(), CoreError> {
+        let mut snapshot = self.get_state_snapshot()?;
+        snapshot.strings.clear();
+
+        self.init_from_dast_root(dast_root);
+
+        match self.restore_state_snapshot(&snapshot) {
+            Ok(_) => {}
+            Err(StateSnapshotError::Core(err)) => return Err(err),
+            Err(StateSnapshotError::UnsupportedVersion { .. }) => {
+                unreachable!("A snapshot created by `Core` should have a supported version")
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the document as a flat dast.
+    ///
+    /// Circular dependencies are found when the props involved are resolved. The components in such a cycle
+    /// are replaced with errors (see [`Core::replace_cycle_with_errors`]) and the document is rendered again.
+    /// Each round replaces at least one component, so this terminates.
+    ///
+    /// Replacing a cycle creates the components again (see [`Core::rebuild_from_normalized_root`]).
+    /// If that dropped any subscriptions, scheduled actions, generated children or action history,
+    /// a warning saying so is added to the returned flat dast.
+    pub fn to_flat_dast(&mut self) -> Result<FlatDastRoot, CoreError> {
+        let mut report = RebuildReport::default();
+        loop {
+            match self
+                .document_renderer
+                .render_flat_dast(&self.document_model)
+            {
+                Err(CoreError::CircularDependency(cycle)) => {
+                    report.merge(self.replace_cycle_with_errors(&cycle)?)
+                }
+                Ok(mut flat_dast) => {
+                    flat_dast.warnings.extend(report.to_warning());
+                    return Ok(flat_dast);
+                }
+                Err(err) => return Err(err),
+            }
+        }
     }
 
     pub fn _run_test(&mut self, test_name: &str) {
@@ -146,7 +562,10 @@ pub mod testing_features {
     use crate::{graph_node::GraphNode, props::cache::PropWithMeta};
 
     impl Core {
-        pub fn get_prop_for_render_untracked(&mut self, prop_node: GraphNode) -> PropWithMeta {
+        pub fn get_prop_for_render_untracked(
+            &mut self,
+            prop_node: GraphNode,
+        ) -> Result<PropWithMeta, CoreError> {
             self.document_renderer
                 .get_prop_for_render_untracked(prop_node, &self.document_model)
         }
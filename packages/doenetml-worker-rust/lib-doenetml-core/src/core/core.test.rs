@@ -14,12 +14,14 @@ fn test_can_get_annotated_children() {
     core.init_from_dast_root(&dast_root);
     let c = core
         .document_model
-        .get_component_content_children(ComponentIdx::from(2));
+        .get_component_content_children(ComponentIdx::from(2))
+        .unwrap();
     assert_eq!(c, vec![GraphNode::String(0), GraphNode::String(1)]);
 
     let c = core
         .document_model
-        .get_component_content_children_annotated(ComponentIdx::from(2));
+        .get_component_content_children_annotated(ComponentIdx::from(2))
+        .unwrap();
     assert_eq!(
         c,
         vec![
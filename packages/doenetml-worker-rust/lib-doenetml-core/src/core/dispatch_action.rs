@@ -1,12 +1,79 @@
 use std::collections::HashMap;
 
-use crate::components::{
-    ComponentOnAction,
-    prelude::{ComponentIdx, FlatDastElementUpdate},
-    types::{Action, ActionQueryProp},
+use serde::Serialize;
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::{
+        ComponentOnAction,
+        prelude::{ComponentIdx, FlatDastElementUpdate},
+        types::{Action, ActionQueryProp, PropPointer, UpdateFromAction},
+    },
+    error::CoreError,
+    graph::directed_graph::Taggable,
+    graph_node::GraphNodeLookup,
+    props::PropValue,
+};
+
+use super::{
+    action_recording::RecordedEvent,
+    core::Core,
+    document_model::{ComponentChanges, NodeValue, RequestedPropUpdate},
+    prop_subscriptions::SubscribedPropChange,
+    scheduler::ScheduleRequest,
 };
 
-use super::core::Core;
+/// The result of dispatching an action.
+#[derive(Debug, Clone, Default)]
+pub struct ActionResult {
+    /// Changes to the output flat dast.
+    pub flat_dast_updates: HashMap<ComponentIdx, FlatDastElementUpdate>,
+    /// The outcome of each prop update requested by the action(s), in the order they were requested.
+    pub prop_outcomes: Vec<PropUpdateOutcome>,
+    /// The subscribed props whose values changed (see [`Core::subscribe_to_prop`]).
+    pub subscribed_prop_changes: Vec<SubscribedPropChange>,
+}
+
+/// The changes requested by an action, calculated before any of them are made.
+pub(super) struct ChangesFromAction {
+    /// The requested values of `State` and `String` nodes.
+    pub changes_to_make: GraphNodeLookup<PropValue>,
+    /// The prop updates that the action requested.
+    pub requested_updates: Vec<RequestedPropUpdate>,
+    /// The actions that the component requested be scheduled or cancelled.
+    pub schedule_requests: Vec<ScheduleRequest>,
+}
+
+/// Whether a prop update requested by an action was carried out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub enum PropUpdateStatus {
+    /// The requested value was propagated and the prop now has the requested value.
+    Accepted,
+    /// Some, but not all, of the changes needed to give the prop its requested value could be made,
+    /// or the changes were made but the prop ended up with a different value than requested.
+    PartiallyApplied,
+    /// The prop (or a prop it depends on) belongs to a component that is `fixed`.
+    RejectedFixed,
+    /// The prop (or a prop it depends on) could not be inverted to produce the requested value.
+    RejectedByInvert,
+}
+
+/// The outcome of a prop update requested by an action.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct PropUpdateOutcome {
+    pub component_idx: ComponentIdx,
+    pub prop_name: String,
+    pub status: PropUpdateStatus,
+    /// The value the action requested for the prop.
+    pub requested_value: PropValue,
+    /// The value of the prop after the changes from the action were applied.
+    pub final_value: PropValue,
+}
 
 impl Core {
     /// Run the action specified by the `action` json and return any changes to the output flat dast.
@@ -20,11 +87,208 @@ impl Core {
     /// - `args`: an object containing data that will be interpreted by the action implementation.
     ///   The values of each field must be quantities that can be converted into `PropValue`
     ///   or a vector of `PropValue`.
-    pub fn dispatch_action(
+    ///
+    /// Along with the changes to the flat dast, the result reports whether each requested prop update
+    /// was accepted, partially applied, or rejected, and which subscribed props changed.
+    ///
+    /// An error is returned if the action does not refer to a component of the document,
+    /// if the component could not process the action,
+    /// or if the action would change the document and the document is read-only.
+    pub fn dispatch_action(&mut self, action: Action) -> Result<ActionResult, CoreError> {
+        let recorded_action = match self.is_recording_actions() {
+            true => Some(Self::serialize_action_for_recording(&action)?),
+            false => None,
+        };
+
+        let ChangesFromAction {
+            changes_to_make,
+            requested_updates,
+            schedule_requests,
+        } = self.calculate_changes_from_action(action)?;
+
+        let flat_dast_updates = self.commit_changes(changes_to_make)?;
+        self.apply_schedule_requests(schedule_requests);
+
+        let result = ActionResult {
+            flat_dast_updates,
+            prop_outcomes: self.get_prop_outcomes(requested_updates)?,
+            subscribed_prop_changes: self.get_subscribed_prop_changes()?,
+        };
+
+        if let Some(action) = recorded_action {
+            self.record_event(RecordedEvent::Action { action })?;
+        }
+        Ok(result)
+    }
+
+    /// Run several actions as a single transaction and return the merged changes to the output flat dast.
+    ///
+    /// The actions are run in order, and each action sees the changes made by the actions before it.
+    /// If any action fails or any of the updates requested by an action cannot be made in full,
+    /// the changes made by the batch are rolled back and an error is returned. Rolling back restores
+    /// the previous values of the changed nodes, including whether states had their default values.
+    /// Otherwise, the changes are rendered at once and can be undone as a single step.
+    ///
+    /// The changes of each action are made before the next action is run, rather than calculating the changes
+    /// of all actions first and making them in a single pass. An action calculates its changes from the
+    /// current values of props (e.g., `updateValue` of a text input commits its immediate value), so changes
+    /// calculated up front would ignore the earlier actions of the batch. The changes are not rendered
+    /// until the whole batch has succeeded, so the renderer still sees a single set of updates.
+    pub fn dispatch_actions(&mut self, actions: Vec<Action>) -> Result<ActionResult, CoreError> {
+        let recorded_actions = match self.is_recording_actions() {
+            true => Some(
+                actions
+                    .iter()
+                    .map(Self::serialize_action_for_recording)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            false => None,
+        };
+
+        // The values of the changed nodes from before the batch, so that the batch can be rolled back or undone.
+        let mut previous_values = GraphNodeLookup::new();
+        let mut new_values = GraphNodeLookup::new();
+        let mut component_changes = ComponentChanges::default();
+        let mut all_requested_updates = Vec::new();
+        let mut all_schedule_requests = Vec::new();
+
+        for (action_idx, action) in actions.into_iter().enumerate() {
+            let changes = self
+                .apply_batched_action(action, &mut previous_values)
+                .map_err(|err| match err {
+                    CoreError::ReadOnly => err,
+                    _ => CoreError::InvalidAction(format!(
+                        "Action {action_idx} of batch failed: {err}"
+                    )),
+                });
+            let ChangesFromAction {
+                changes_to_make: changes,
+                requested_updates,
+                schedule_requests,
+            } = match changes {
+                Ok(changes) => changes,
+                Err(err) => {
+                    self.document_model.restore_values(previous_values)?;
+                    return Err(err);
+                }
+            };
+
+            for (node, value) in NodeValue::from_changes(&changes).iter() {
+                new_values.set_tag(node, value.clone());
+            }
+            component_changes.extend(self.document_model.execute_changes(changes)?);
+            all_requested_updates.extend(requested_updates);
+            all_schedule_requests.extend(schedule_requests);
+        }
+
+        if new_values.keys().next().is_some() {
+            self.action_history.record(previous_values, new_values);
+        }
+        self.apply_schedule_requests(all_schedule_requests);
+
+        let result = ActionResult {
+            flat_dast_updates: self.render_component_changes(component_changes)?,
+            prop_outcomes: self.get_prop_outcomes(all_requested_updates)?,
+            subscribed_prop_changes: self.get_subscribed_prop_changes()?,
+        };
+
+        if let Some(actions) = recorded_actions {
+            self.record_event(RecordedEvent::Batch { actions })?;
+        }
+        Ok(result)
+    }
+
+    /// Calculate the changes requested by `action`, one action of a batch, from the current state of the document.
+    ///
+    /// The current values of the nodes to be changed are added to `previous_values`, unless already present.
+    /// An error is returned if any of the updates requested by the action cannot be made in full.
+    fn apply_batched_action(
+        &mut self,
+        action: Action,
+        previous_values: &mut GraphNodeLookup<NodeValue>,
+    ) -> Result<ChangesFromAction, CoreError> {
+        let changes_from_action = self.calculate_changes_from_action(action)?;
+
+        if let Some(failed_update) = changes_from_action
+            .requested_updates
+            .iter()
+            .find(|requested_update| requested_update.status() != PropUpdateStatus::Accepted)
+        {
+            let prop_pointer = self
+                .document_model
+                .get_prop_pointer(failed_update.prop_node)?;
+            return Err(CoreError::InvalidAction(format!(
+                "the update of prop `{}` of component {} was not accepted ({:?})",
+                self.document_model.get_prop_name(prop_pointer)?,
+                prop_pointer.component_idx.as_usize(),
+                failed_update.status()
+            )));
+        }
+
+        for (node, value) in self
+            .document_model
+            .get_current_values(&changes_from_action.changes_to_make)?
+            .iter()
+        {
+            if previous_values.get_tag(&node).is_none() {
+                previous_values.set_tag(node, value.clone());
+            }
+        }
+        Ok(changes_from_action)
+    }
+
+    /// Calculate the new values of `State` and `String` nodes requested by `action` without applying them.
+    ///
+    /// Returns the requested values along with the prop updates that the action requested
+    /// and the actions that it requested be scheduled or cancelled.
+    /// The updates of actions triggered by `action` on other components are included.
+    pub(super) fn calculate_changes_from_action(
         &mut self,
         action: Action,
-    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, String> {
+    ) -> Result<ChangesFromAction, CoreError> {
+        let mut prop_updates = Vec::new();
+        let mut schedule_requests = Vec::new();
+        self.collect_updates_from_action(
+            action,
+            &mut Vec::new(),
+            &mut prop_updates,
+            &mut schedule_requests,
+        )?;
+
+        // Actions that do not request any changes (if any) are still allowed in a read-only document.
+        if !(prop_updates.is_empty() && schedule_requests.is_empty())
+            && self.document_model.get_flags().read_only
+        {
+            return Err(CoreError::ReadOnly);
+        }
+
+        let (changes_to_make, requested_updates) = self
+            .document_model
+            .calculate_changes_from_action_updates(prop_updates)?;
+
+        Ok(ChangesFromAction {
+            changes_to_make,
+            requested_updates,
+            schedule_requests,
+        })
+    }
+
+    /// Run `action` on its component and add the prop updates and scheduling requests it returns
+    /// to `prop_updates` and `schedule_requests`, running any actions it triggers on other components in turn.
+    ///
+    /// `chain` holds the components whose actions triggered `action`. An error is returned
+    /// if `action` would trigger an action of one of them, as the actions would trigger each other forever.
+    fn collect_updates_from_action(
+        &self,
+        action: Action,
+        chain: &mut Vec<ComponentIdx>,
+        prop_updates: &mut Vec<(PropPointer, PropValue)>,
+        schedule_requests: &mut Vec<ScheduleRequest>,
+    ) -> Result<(), CoreError> {
         let component_idx = action.component_idx;
+        if chain.contains(&component_idx) {
+            return Err(CoreError::ActionLoop(component_idx.as_usize()));
+        }
 
         // We allow actions to resolve and get the value of any prop from the component.
         let query_prop = ActionQueryProp::new(component_idx, &self.document_model);
@@ -33,17 +297,162 @@ impl Core {
         // of component props with requested new values
         let updates_from_action = self
             .document_model
-            .get_component(component_idx)
-            .on_action(action.action, query_prop)?;
+            .get_component(component_idx)?
+            .on_action(action.action, query_prop)
+            .map_err(CoreError::InvalidAction)?;
 
-        let changes_to_make = self
-            .document_model
-            .calculate_changes_from_action_updates(updates_from_action, component_idx);
+        chain.push(component_idx);
+        for update in updates_from_action {
+            match update {
+                UpdateFromAction::Prop {
+                    local_prop_idx,
+                    requested_value,
+                } => prop_updates.push((
+                    PropPointer {
+                        component_idx,
+                        local_prop_idx,
+                    },
+                    requested_value,
+                )),
+                UpdateFromAction::ComponentProp {
+                    component_idx: target_idx,
+                    local_prop_idx,
+                    requested_value,
+                } => {
+                    let prop_pointer = PropPointer {
+                        component_idx: target_idx,
+                        local_prop_idx,
+                    };
+                    self.document_model.check_prop_pointer(prop_pointer)?;
+                    prop_updates.push((prop_pointer, requested_value));
+                }
+                UpdateFromAction::TriggerAction {
+                    component_idx: target_idx,
+                    action,
+                } => self.collect_updates_from_action(
+                    Action {
+                        component_idx: target_idx,
+                        action,
+                    },
+                    chain,
+                    prop_updates,
+                    schedule_requests,
+                )?,
+                UpdateFromAction::ScheduleAction {
+                    action,
+                    delay,
+                    interval,
+                } => schedule_requests.push(ScheduleRequest::schedule(
+                    component_idx,
+                    action,
+                    delay,
+                    interval,
+                )?),
+                UpdateFromAction::CancelScheduledActions => {
+                    schedule_requests.push(ScheduleRequest::Cancel { component_idx })
+                }
+            }
+        }
+        chain.pop();
+        Ok(())
+    }
+
+    /// Determine the outcome of each requested prop update once the changes have been applied.
+    ///
+    /// A request whose changes were all made is only `Accepted` if the prop ended up with the requested value.
+    pub(super) fn get_prop_outcomes(
+        &self,
+        requested_updates: Vec<RequestedPropUpdate>,
+    ) -> Result<Vec<PropUpdateOutcome>, CoreError> {
+        requested_updates
+            .into_iter()
+            .map(|requested_update| {
+                let prop_node = requested_update.prop_node;
+                let prop_pointer = self.document_model.get_prop_pointer(prop_node)?;
+                let final_value = self
+                    .document_model
+                    .get_prop_untracked(prop_node, prop_node)?
+                    .value;
+
+                let status = match requested_update.status() {
+                    PropUpdateStatus::Accepted
+                        if final_value != requested_update.requested_value =>
+                    {
+                        PropUpdateStatus::PartiallyApplied
+                    }
+                    status => status,
+                };
+
+                Ok(PropUpdateOutcome {
+                    component_idx: prop_pointer.component_idx,
+                    prop_name: self.document_model.get_prop_name(prop_pointer)?.to_string(),
+                    status,
+                    requested_value: requested_update.requested_value,
+                    final_value,
+                })
+            })
+            .collect()
+    }
+
+    /// Apply the changes requested by actions and record them so that they can be undone.
+    ///
+    /// Returns any changes to the output flat dast.
+    fn commit_changes(
+        &mut self,
+        changes_to_make: GraphNodeLookup<PropValue>,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        // Record the values being replaced so that the action can be undone.
+        let previous_values = self.document_model.get_current_values(&changes_to_make)?;
+        if changes_to_make.keys().next().is_some() {
+            self.action_history
+                .record(previous_values, NodeValue::from_changes(&changes_to_make));
+        }
+
+        self.apply_changes(changes_to_make)
+    }
+
+    /// Change all the `State` and `String` nodes in `changes_to_make` to their requested values
+    /// and return any changes to the output flat dast.
+    pub(crate) fn apply_changes(
+        &mut self,
+        changes_to_make: GraphNodeLookup<PropValue>,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        let changes = self.document_model.execute_changes(changes_to_make)?;
+        self.render_component_changes(changes)
+    }
 
-        let changed_components = self.document_model.execute_changes(changes_to_make);
+    /// Restore the `State` and `String` nodes in `values` to the given values, including whether
+    /// the values of the states came from their defaults, and return any changes to the output flat dast.
+    pub(crate) fn restore_values(
+        &mut self,
+        values: GraphNodeLookup<NodeValue>,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        let changes = self.document_model.restore_values(values)?;
+        self.render_component_changes(changes)
+    }
 
-        Ok(self
+    /// Return the changes to the output flat dast of the components in `changes`,
+    /// along with the components that own a data query that now matches different children.
+    pub(super) fn render_component_changes(
+        &mut self,
+        changes: ComponentChanges,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        let mut flat_dast_updates = self
             .document_renderer
-            .get_flat_dast_updates(changed_components, &self.document_model))
+            .get_flat_dast_updates(changes.components_with_changed_props, &self.document_model)?;
+
+        // Calculating the props may have found data queries whose matches changed.
+        let mut components_with_changed_children = changes.components_with_changed_children;
+        for component_idx in self.document_model.take_components_with_changed_matches()? {
+            if !components_with_changed_children.contains(&component_idx) {
+                components_with_changed_children.push(component_idx);
+            }
+        }
+        self.document_renderer.add_flat_dast_children_updates(
+            &mut flat_dast_updates,
+            components_with_changed_children,
+            &self.document_model,
+        )?;
+        Ok(flat_dast_updates)
     }
 }
@@ -0,0 +1,149 @@
+//! # Document flags
+//!
+//! Flags are set by the host application (e.g., through `PublicDoenetMLCore::set_flags`)
+//! and change how a document behaves, for example, whether it can be interacted with
+//! or whether the correctness of answers is shown.
+//!
+//! Components access the flags through [`DataQuery::DocumentFlag`](crate::props::DataQuery::DocumentFlag).
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use std::collections::HashMap;
+
+use crate::{
+    components::types::ComponentIdx, dast::FlatDastElementUpdate, error::CoreError,
+    props::PropValue,
+};
+
+use super::{Core, action_recording::RecordedEvent};
+
+/// The flags of a document. The names and defaults match the `DoenetMLFlags` of the JavaScript worker.
+/// Flags that are missing when deserializing are given their default value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi, from_wasm_abi))]
+#[serde(rename_all = "camelCase", default)]
+pub struct DocumentFlags {
+    /// Whether to show whether answers are correct.
+    pub show_correctness: bool,
+    /// If `true`, the document cannot be changed by actions.
+    pub read_only: bool,
+    /// How solutions are displayed.
+    pub solution_display_mode: SolutionDisplayMode,
+    /// Whether to show feedback on submitted answers.
+    pub show_feedback: bool,
+    /// Whether to show hints.
+    pub show_hints: bool,
+    /// Whether a previously saved state may be loaded.
+    pub allow_load_state: bool,
+    /// Whether the state of the document may be saved.
+    pub allow_save_state: bool,
+    /// Whether to save the state of renderers along with the state of the document.
+    pub save_renderer_state: bool,
+    /// Whether the state of the document may be saved locally (e.g., in the browser).
+    pub allow_local_state: bool,
+    /// Whether events (e.g., submitted answers) may be saved.
+    pub allow_save_events: bool,
+    /// Whether to send messages to the parent window.
+    pub message_parent: bool,
+    /// Whether to submit answers automatically when they change.
+    pub auto_submit: bool,
+}
+
+impl Default for DocumentFlags {
+    fn default() -> Self {
+        DocumentFlags {
+            show_correctness: true,
+            read_only: false,
+            solution_display_mode: SolutionDisplayMode::Button,
+            show_feedback: true,
+            show_hints: true,
+            allow_load_state: true,
+            allow_save_state: true,
+            save_renderer_state: false,
+            allow_local_state: false,
+            allow_save_events: true,
+            message_parent: false,
+            auto_submit: false,
+        }
+    }
+}
+
+/// How solutions are displayed.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, strum_macros::IntoStaticStr,
+)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum SolutionDisplayMode {
+    /// Solutions are shown when a button is pressed.
+    #[default]
+    Button,
+    /// Solutions are shown when a button is pressed, but only after permission is granted.
+    ButtonRequirePermission,
+    /// Solutions are always displayed.
+    Displayed,
+    /// Solutions are never displayed.
+    None,
+}
+
+/// A flag of [`DocumentFlags`] that a component can query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFlag {
+    ShowCorrectness,
+    ReadOnly,
+    /// The value is the `camelCase` name of the [`SolutionDisplayMode`], e.g., `"buttonRequirePermission"`.
+    SolutionDisplayMode,
+    ShowFeedback,
+    ShowHints,
+    AllowSaveState,
+    AutoSubmit,
+}
+
+impl DocumentFlags {
+    /// Parse flags from the JSON used by the JavaScript worker.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// The value of `flag` as a `PropValue`.
+    /// The value is a `Boolean` except for `SolutionDisplayMode`, which is a `String`.
+    pub fn get(&self, flag: DocumentFlag) -> PropValue {
+        match flag {
+            DocumentFlag::ShowCorrectness => PropValue::Boolean(self.show_correctness),
+            DocumentFlag::ReadOnly => PropValue::Boolean(self.read_only),
+            DocumentFlag::SolutionDisplayMode => {
+                let mode: &'static str = self.solution_display_mode.into();
+                PropValue::String(mode.to_string().into())
+            }
+            DocumentFlag::ShowFeedback => PropValue::Boolean(self.show_feedback),
+            DocumentFlag::ShowHints => PropValue::Boolean(self.show_hints),
+            DocumentFlag::AllowSaveState => PropValue::Boolean(self.allow_save_state),
+            DocumentFlag::AutoSubmit => PropValue::Boolean(self.auto_submit),
+        }
+    }
+}
+
+impl Core {
+    /// Set the flags of the document and return any changes to the output flat dast.
+    ///
+    /// Props that depend on a flag are recalculated with the new flags.
+    /// The flags are kept when core is re-initialized.
+    pub fn set_flags(
+        &mut self,
+        flags: DocumentFlags,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
+        let changes = self.document_model.set_flags(flags.clone())?;
+        let flat_dast_updates = self.render_component_changes(changes)?;
+        self.record_event(RecordedEvent::SetFlags { flags })?;
+        Ok(flat_dast_updates)
+    }
+
+    /// The flags of the document.
+    pub fn get_flags(&self) -> &DocumentFlags {
+        self.document_model.get_flags()
+    }
+}
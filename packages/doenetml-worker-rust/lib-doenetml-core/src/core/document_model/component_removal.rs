@@ -0,0 +1,125 @@
+//! Remove components from the document after it was initialized, e.g., when the source of the document is edited.
+
+use crate::{
+    components::types::ComponentIdx,
+    graph::directed_graph::Taggable,
+    graph_node::{GraphNode, GraphNodeLookup},
+    props::DataQuery,
+};
+
+use super::DocumentModel;
+
+impl DocumentModel {
+    /// Remove `components` from the document along with their props, states, and the data queries made by their props.
+    /// `extra_nodes` are other nodes of the structure graph to remove, e.g., strings that were children of a component
+    /// whose children were replaced.
+    ///
+    /// Returns the data queries of the remaining props that depended on a removed node.
+    /// They must be relinked with `relink_data_query` once the structure of the document is complete again.
+    pub fn remove_components(
+        &self,
+        components: &[ComponentIdx],
+        extra_nodes: &[GraphNode],
+    ) -> Vec<GraphNode> {
+        let removed_structure_nodes = self
+            .document_structure
+            .borrow_mut()
+            .remove_components(components, extra_nodes);
+
+        let mut dependency_graph = self.dependency_graph.borrow_mut();
+
+        // Virtual nodes of the structure graph are not part of the dependency graph
+        // (the dependency graph has virtual nodes of its own), so they are skipped.
+        let mut removed_nodes = removed_structure_nodes
+            .into_iter()
+            .filter(|node| {
+                !matches!(node, GraphNode::Virtual(_)) && dependency_graph.contains_node(node)
+            })
+            .collect::<Vec<_>>();
+
+        // The queries made by removed props, and the virtual nodes they created, are removed with the props.
+        let mut removed_queries = Vec::new();
+        for prop_node in removed_nodes
+            .iter()
+            .filter(|node| matches!(node, GraphNode::Prop(_)))
+        {
+            for query_node in dependency_graph.get_children(prop_node) {
+                if matches!(query_node, GraphNode::Query(_)) {
+                    removed_queries.push(query_node);
+                }
+            }
+        }
+        for query_node in removed_queries.iter() {
+            for child in dependency_graph.get_children(query_node) {
+                // `Virtual(0)` is the shared null node.
+                if matches!(child, GraphNode::Virtual(idx) if idx != 0) {
+                    removed_nodes.push(child);
+                }
+            }
+        }
+        removed_nodes.extend(removed_queries.iter().copied());
+
+        let mut is_removed = GraphNodeLookup::new();
+        for node in removed_nodes.iter() {
+            is_removed.set_tag(*node, true);
+        }
+
+        // The queries that remain but depended on a removed node, possibly through one of their virtual nodes.
+        let mut dangling_queries = Vec::new();
+        for node in removed_nodes.iter() {
+            for parent in dependency_graph.get_parents(node) {
+                if is_removed.get_tag(&parent).is_some() {
+                    continue;
+                }
+                let query_nodes = match parent {
+                    GraphNode::Query(_) => vec![parent],
+                    GraphNode::Virtual(_) => dependency_graph.get_parents(parent),
+                    _ => Vec::new(),
+                };
+                for query_node in query_nodes {
+                    if is_removed.get_tag(&query_node).is_none()
+                        && !dangling_queries.contains(&query_node)
+                    {
+                        dangling_queries.push(query_node);
+                    }
+                }
+            }
+        }
+
+        for node in removed_nodes.iter() {
+            dependency_graph.remove_node(node);
+        }
+        drop(dependency_graph);
+
+        let document_structure = self.document_structure.borrow();
+        let mut queries = self.queries.borrow_mut();
+        let mut dynamic_query_matches = self.dynamic_query_matches.borrow_mut();
+        for node in removed_nodes {
+            match node {
+                GraphNode::Prop(_) => self.prop_cache.remove_prop(node),
+                GraphNode::State(_) => self.states.remove_state(node),
+                GraphNode::Query(idx) => {
+                    queries[idx] = DataQuery::Null;
+                    self.prop_cache.remove_origin(node);
+                    self.states.remove_origin(node);
+                    document_structure.remove_string_origin(node);
+                    dynamic_query_matches.remove_tag(&node);
+                    self.set_children_query_container(node, None);
+                }
+                _ => {}
+            }
+        }
+
+        // The queries of remaining props whose container was removed no longer have a container.
+        // If they are dangling, they are recorded with their new container when they are relinked.
+        let mut children_queries = self.children_queries.borrow_mut();
+        let mut children_query_containers = self.children_query_containers.borrow_mut();
+        for component_idx in components {
+            for query_node in children_queries.remove(component_idx).unwrap_or_default() {
+                children_query_containers.remove_tag(&query_node);
+            }
+        }
+
+        dangling_queries
+    }
+}
@@ -200,7 +200,9 @@ impl Core {
                     mermaid.push_str(&format!(
                         "{}{{{{\"&lt;{}><sub>id={}</sub>\"}}}}\n",
                         graph_node.to_mermaid_id(),
-                        self.document_model.get_component_type(graph_node),
+                        self.document_model
+                            .get_component_type(graph_node)
+                            .unwrap_or_default(),
                         idx
                     ));
                 }
@@ -230,7 +232,7 @@ impl Core {
             .iter()
             .filter(|n| matches!(n, GraphNode::Component(_)) && print_labels)
         {
-            let component_idx = ComponentIdx::from(component_node);
+            let component_idx = ComponentIdx::new(component_node.idx());
             // Children
             if let Some(children_virtual_node) = graph.get_nth_child(component_node, 0) {
                 mermaid.push_str(&format!(
@@ -248,7 +250,9 @@ impl Core {
             }
 
             let document_structure = self.document_model.document_structure.borrow();
-            let component = document_structure.get_component(component_idx);
+            let Ok(component) = document_structure.get_component(component_idx) else {
+                continue;
+            };
             // Label the individual attributes
             for (i, attr_virtual_node) in graph
                 .get_component_attributes(component_node)
@@ -9,14 +9,35 @@ use crate::{
     props::{FilterData, PickPropSource, PropSource, PropSpecifier, cache::PropStatus},
 };
 
-use crate::{graph_node::GraphNode, props::PropValue};
+use crate::{
+    error::CoreError, graph::directed_graph::Taggable, graph_node::GraphNode, props::PropValue,
+};
 
 use super::DocumentModel;
 use super::dependency_creation_helpers::*;
 
+/// The reasons that [`DocumentModel::add_data_query`] may fail to add a data query.
+#[derive(Debug)]
+pub(super) enum AddDataQueryError {
+    /// `node` is a prop node that needs to be resolved before the query can be added.
+    Unresolved(GraphNode),
+    /// The query could not be added because the document is malformed.
+    Core(CoreError),
+}
+
+impl From<CoreError> for AddDataQueryError {
+    fn from(err: CoreError) -> Self {
+        AddDataQueryError::Core(err)
+    }
+}
+
 impl DocumentModel {
     /// Create any state nodes that are required for the given `DataQuery`.
-    fn _create_state_for_query(&self, prop_node: GraphNode, query: &DataQuery) {
+    fn _create_state_for_query(
+        &self,
+        prop_node: GraphNode,
+        query: &DataQuery,
+    ) -> Result<(), CoreError> {
         match query {
             DataQuery::State => {
                 // Every prop can have exactly one piece of state. In the case of a prop extending another prop,
@@ -28,7 +49,7 @@ impl DocumentModel {
                 match leaf_node {
                     GraphNode::State(_) => {}
                     GraphNode::Prop(_) => {
-                        let prop_updater = self.get_prop_updater(leaf_node);
+                        let prop_updater = self.get_prop_updater(leaf_node)?;
 
                         // TODO: when we load saved data from a data base, then set value and came_from_default
                         // from the data.
@@ -42,10 +63,10 @@ impl DocumentModel {
                             .add_edge(leaf_node, state_node);
                     }
                     _ => {
-                        unreachable!(
-                            "Found a non-prop/non-state node as a leaf node of a prop. Found {:?}.",
-                            leaf_node
-                        )
+                        return Err(CoreError::UnexpectedGraphNode {
+                            expected: "Prop",
+                            found: leaf_node,
+                        });
                     }
                 }
             }
@@ -53,29 +74,36 @@ impl DocumentModel {
                 // No new state to create
             }
         }
+        Ok(())
     }
 
     /// Returns the virtual node used to represent null,
     /// i.e., the lack of a node in that spot in the dependency graph.
     ///
-    /// Since we initialize `self.virtual_node_count` to `1`,
+    /// Since we initialize `self.virtual_node_count` to `2`,
     /// we can use node `0` to represent null.
     #[inline(always)]
     fn get_null_node() -> GraphNode {
         GraphNode::Virtual(0)
     }
 
+    /// Returns the virtual node that all `DataQuery::DocumentFlag` queries depend on,
+    /// so that the props using a flag are marked stale when the flags change.
+    #[inline(always)]
+    pub(super) fn get_flags_node() -> GraphNode {
+        GraphNode::Virtual(1)
+    }
+
     /// Creates all necessary dependencies for a `DataQuery`.
     /// Returns:
     ///  - `Ok(vec)` where `vec` is a vector of all graph nodes directly linked to the data query.
-    ///  - `Err(node)` where `node` is the prop node that that needs to be resolved before this query can be added.
+    ///  - `Err(AddDataQueryError::Unresolved(node))` where `node` is the prop node that that needs to be resolved before this query can be added.
+    ///  - `Err(AddDataQueryError::Core(err))` if the query refers to parts of the document that do not exist.
     pub(super) fn add_data_query(
         &self,
         prop_node: GraphNode,
         query: DataQuery,
-    ) -> Result<Vec<GraphNode>, GraphNode> {
-        let prop_pointer = self.get_prop_pointer(prop_node);
-
+    ) -> Result<Vec<GraphNode>, AddDataQueryError> {
         // We may not always need `resolved_component_idx`, but if we do, it may
         // require additional dependencies to be resolved first. Thus, we resolve it here
         // _before_ modifying the dependency graph in any way.
@@ -95,10 +123,11 @@ impl DocumentModel {
             | DataQuery::Attribute { .. }
             | DataQuery::State
             | DataQuery::SelfRef
+            | DataQuery::DocumentFlag(_)
             | DataQuery::Null => None,
         };
 
-        self._create_state_for_query(prop_node, &query);
+        self._create_state_for_query(prop_node, &query)?;
 
         //
         // Create appropriate dependencies on the dependency graph.
@@ -108,6 +137,111 @@ impl DocumentModel {
             .borrow_mut()
             .add_edge(prop_node, query_node);
 
+        self.link_data_query(prop_node, query_node, query, resolved_component_idx)
+    }
+
+    /// Remove the dependencies of `query_node` and create them again from the current structure of the document,
+    /// e.g., after the children of a component the query depends on were replaced.
+    ///
+    /// Returns a vector of all graph nodes now directly linked to the data query.
+    /// Props among them that were not resolved yet are resolved.
+    pub(super) fn relink_data_query(
+        &self,
+        query_node: GraphNode,
+    ) -> Result<Vec<GraphNode>, CoreError> {
+        let prop_node = self.get_nearest_prop_ancestor_of_query(query_node).ok_or(
+            CoreError::UnexpectedGraphNode {
+                expected: "Query owned by a prop",
+                found: query_node,
+            },
+        )?;
+        let query = self.queries.borrow()[query_node.idx()].clone();
+        let resolved_component_idx = match &query {
+            DataQuery::Prop { source, .. }
+            | DataQuery::ContentRefs {
+                container: source, ..
+            }
+            | DataQuery::AnnotatedContentRefs {
+                container: source, ..
+            } => self.resolve_prop_source_resolving_deps(source, prop_node)?,
+            _ => None,
+        };
+
+        self.dependency_graph
+            .borrow_mut()
+            .remove_outgoing_edges(query_node);
+
+        let linked_nodes = self
+            .link_data_query(prop_node, query_node, query, resolved_component_idx)
+            .map_err(|err| match err {
+                AddDataQueryError::Core(err) => err,
+                AddDataQueryError::Unresolved(node) => {
+                    unreachable!("The source of the query was already resolved, found {node:?}")
+                }
+            })?;
+        for node in linked_nodes
+            .iter()
+            .filter(|node| matches!(node, GraphNode::Prop(_)))
+        {
+            self.resolve_prop(*node)?;
+        }
+        Ok(linked_nodes)
+    }
+
+    /// Record `container` as the component whose content children determine the dependencies of `query_node`,
+    /// replacing any container recorded before. If `container` is `None`, the query is no longer recorded.
+    pub(super) fn set_children_query_container(
+        &self,
+        query_node: GraphNode,
+        container: Option<ComponentIdx>,
+    ) {
+        let mut children_query_containers = self.children_query_containers.borrow_mut();
+        let mut children_queries = self.children_queries.borrow_mut();
+        if let Some(old_container) = children_query_containers.get_tag(&query_node).copied() {
+            if Some(old_container) == container {
+                return;
+            }
+            if let Some(query_nodes) = children_queries.get_mut(&old_container) {
+                query_nodes.retain(|node| *node != query_node);
+            }
+            children_query_containers.remove_tag(&query_node);
+        }
+        if let Some(container) = container {
+            children_query_containers.set_tag(query_node, container);
+            children_queries
+                .entry(container)
+                .or_default()
+                .push(query_node);
+        }
+    }
+
+    /// Create the dependencies of `query_node`, the node of `query` made by the prop `prop_node`.
+    /// `resolved_component_idx` is the component that the source of the query resolved to, if any.
+    ///
+    /// Returns a vector of all graph nodes directly linked to the data query.
+    fn link_data_query(
+        &self,
+        prop_node: GraphNode,
+        query_node: GraphNode,
+        query: DataQuery,
+        resolved_component_idx: Option<ComponentIdx>,
+    ) -> Result<Vec<GraphNode>, AddDataQueryError> {
+        let prop_pointer = self.get_prop_pointer(prop_node)?;
+
+        // Record the component whose content children determine the dependencies of the query,
+        // so that the query can be linked again when those children change.
+        let children_query_container = match &query {
+            DataQuery::ContentRefs { .. } | DataQuery::AnnotatedContentRefs { .. } => {
+                resolved_component_idx
+            }
+            DataQuery::PickProp {
+                source: PickPropSource::Children,
+                ..
+            } => Some(prop_pointer.component_idx),
+            _ => None,
+        };
+        self.set_children_query_container(query_node, children_query_container);
+
         // Accumulate the props linked to the data query
         // to pass on to the caller
         let mut linked_nodes = Vec::new();
@@ -115,12 +249,8 @@ impl DocumentModel {
         let mut fn_add_edges = |edges_to_add: Vec<(GraphNode, GraphNode)>| {
             let mut dependency_graph = self.dependency_graph.borrow_mut();
             for (from, to) in edges_to_add {
-                // Small sanity check to make sure we don't produce  self loop.
-                // `prop_node` should not ask for a query that results in itself.
-                assert_ne!(
-                    prop_node, to,
-                    "Self-loop detected; DataQuery requested a prop that is the same as the origin prop."
-                );
+                // If `prop_node` asks for a query that results in itself, we get a self loop.
+                // It is reported as a cycle by `resolve_prop` when `to` is resolved.
                 dependency_graph.add_edge(from, to);
                 linked_nodes.push(to);
             }
@@ -148,6 +278,12 @@ impl DocumentModel {
                 // to anything.
             }
 
+            DataQuery::DocumentFlag(_) => {
+                // Flag queries are computed on-the-fly. They are linked to the flags node
+                // so that they are marked stale when the flags are changed.
+                fn_add_edges(vec![(query_node, Self::get_flags_node())]);
+            }
+
             // Depend on a prop (of yourself or another component)
             DataQuery::Prop {
                 source,
@@ -202,7 +338,7 @@ impl DocumentModel {
                     prop_pointer,
                     query_node,
                     &self.document_structure.borrow(),
-                );
+                )?;
                 fn_add_edges(edges_to_add);
             }
 
@@ -224,23 +360,24 @@ impl DocumentModel {
                     PickPropSource::Children => {
                         let document_structure = self.document_structure.borrow();
                         let container_nodes = document_structure
-                            .get_component_content_children(prop_pointer.component_idx);
+                            .get_component_content_children(prop_pointer.component_idx)?;
                         container_nodes
                     }
 
                     PickPropSource::Attribute { attribute_name } => {
                         let document_structure = self.document_structure.borrow();
-                        let attr_node = document_structure
+                        let Some(attr_node) = document_structure
                             .get_attr_node(prop_pointer.component_idx, attribute_name)
-                            .unwrap_or_else(|| {
-                                panic!(
-                                    "Cannot find attribute `{}` on component `{}`",
-                                    attribute_name,
-                                    document_structure
-                                        .get_component(prop_pointer.component_idx)
-                                        .get_component_type()
-                                )
-                            });
+                        else {
+                            return Err(CoreError::AttributeNotFound {
+                                attribute_name: attribute_name.to_string(),
+                                component_type: document_structure
+                                    .get_component(prop_pointer.component_idx)?
+                                    .get_component_type()
+                                    .to_string(),
+                            }
+                            .into());
+                        };
 
                         let container_nodes = document_structure
                             .get_attribute_content_children(attr_node)
@@ -263,9 +400,13 @@ impl DocumentModel {
                         // pick the prop off each node, if it exists,
                         // and potentially create an edge to that node
                         let document_structure = self.document_structure.borrow();
-                        let mut edges = container_nodes
+                        let picked_props = container_nodes
                             .into_iter()
-                            .flat_map(|node| pick_prop(node, &match_profiles, &document_structure))
+                            .map(|node| pick_prop(node, &match_profiles, &document_structure))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let mut edges = picked_props
+                            .into_iter()
+                            .flatten()
                             .map(|node| (query_node, node));
 
                         match source {
@@ -286,14 +427,17 @@ impl DocumentModel {
                         let document_structure = self.document_structure.borrow();
                         let props1 = container_nodes
                             .iter()
-                            .map(|&node| pick_prop(node, &match_profiles1, &document_structure));
+                            .map(|&node| pick_prop(node, &match_profiles1, &document_structure))
+                            .collect::<Result<Vec<_>, _>>()?;
                         let props2 = container_nodes
                             .iter()
-                            .map(|&node| pick_prop(node, &match_profiles2, &document_structure));
+                            .map(|&node| pick_prop(node, &match_profiles2, &document_structure))
+                            .collect::<Result<Vec<_>, _>>()?;
 
                         // create an iterator for the ingredients for the an edge to a virtual node
                         // and then edges from that virtual node to both prop nodes
                         let mut matching_props = props1
+                            .into_iter()
                             .zip(props2)
                             .filter_map(|(p1, p2)| match (p1, p2) {
                                 (Some(prop1), Some(prop2)) => Some((prop1, prop2)),
@@ -357,11 +501,13 @@ impl DocumentModel {
                 };
 
                 let mut edges_to_add = Vec::new();
+                // Whether the filter depends on values that can change, so that the content it matches can change.
+                let mut is_dynamic = false;
 
                 let content_children = self
                     .document_structure
                     .borrow()
-                    .get_component_content_children(component_idx);
+                    .get_component_content_children(component_idx)?;
 
                 for node in content_children {
                     // If the component was generated from syntax like `$sec.title`,
@@ -376,6 +522,7 @@ impl DocumentModel {
                         });
                         // deps consists of everything that the filter could possibly depend on.
                         // We need to link each dep to the query node.
+                        is_dynamic |= !deps.is_empty();
                         for dep in deps {
                             edges_to_add.push((query_node, dep));
                         }
@@ -383,6 +530,7 @@ impl DocumentModel {
                 }
 
                 fn_add_edges(edges_to_add);
+                self.register_dynamic_query(query_node, is_dynamic);
             }
         }
         Ok(linked_nodes)
@@ -392,22 +540,21 @@ impl DocumentModel {
     /// Returns:
     ///  - `Ok(Some(component_idx))` if the `PropSource` was successfully resolved.
     ///  - `Ok(None)` if the `PropSource` could not be resolved, and could never be resolved. I.e. it failed for an unrecoverable reason like a malformed reference.
-    ///  - `Err(node)` if the `PropSource` could not be resolved due to a dependency that needs to be resolved first.
+    ///  - `Err(AddDataQueryError::Unresolved(node))` if the `PropSource` could not be resolved due to a dependency that needs to be resolved first.
     fn resolve_prop_source(
         &self,
         prop_source: &PropSource,
         prop_node: GraphNode,
-    ) -> Result<Option<ComponentIdx>, GraphNode> {
-        let prop_pointer = self.get_prop_pointer(prop_node);
+    ) -> Result<Option<ComponentIdx>, AddDataQueryError> {
+        let prop_pointer = self.get_prop_pointer(prop_node)?;
 
         Ok(match prop_source {
             PropSource::Me => Some(prop_pointer.component_idx),
-            PropSource::Parent => Some(
-                self.document_structure
-                    .borrow()
-                    .get_true_component_parent(prop_pointer.component_idx)
-                    .unwrap(),
-            ),
+            // The document root has no parent, so the source can never be resolved.
+            PropSource::Parent => self
+                .document_structure
+                .borrow()
+                .get_true_component_parent(prop_pointer.component_idx),
             PropSource::ByIdx(component_idx) => Some(*component_idx),
             PropSource::StaticComponentRef(local_prop_idx) => {
                 // This is the prop that contains the ref.
@@ -420,12 +567,12 @@ impl DocumentModel {
                 // in order to first resolve this prop.
                 let status = self.prop_cache.get_prop_status(prop_node);
                 if matches!(status, PropStatus::Unresolved | PropStatus::Resolving) {
-                    return Err(prop_node);
+                    return Err(AddDataQueryError::Unresolved(prop_node));
                 }
 
                 // Since the prop is resolved, getting its value should not encounter any problems.
                 // Use `GraphNode::Query(0)` for origin since it doesn't matter with untracked
-                let prop = self.get_prop_untracked(prop_node, GraphNode::Query(0));
+                let prop = self.get_prop_untracked(prop_node, GraphNode::Query(0))?;
                 let component_ref = match prop.value {
                     PropValue::ComponentRef(Some(component_ref)) => component_ref,
                     PropValue::ComponentRef(None) => {
@@ -444,6 +591,22 @@ impl DocumentModel {
         })
     }
 
+    /// Resolve `prop_source` to a component index as in [`DocumentModel::resolve_prop_source`],
+    /// first resolving any props that are needed to do so.
+    fn resolve_prop_source_resolving_deps(
+        &self,
+        prop_source: &PropSource,
+        prop_node: GraphNode,
+    ) -> Result<Option<ComponentIdx>, CoreError> {
+        loop {
+            match self.resolve_prop_source(prop_source, prop_node) {
+                Ok(component_idx) => return Ok(component_idx),
+                Err(AddDataQueryError::Unresolved(node)) => self.resolve_prop(node)?,
+                Err(AddDataQueryError::Core(err)) => return Err(err),
+            }
+        }
+    }
+
     /// Create a new `GraphNode::State` and add it to the `structure_graph`.
     fn add_state_node(
         &self,
@@ -458,7 +621,7 @@ impl DocumentModel {
 
     /// Creates a `GraphNode::Query` node and saves information about the query to `self.queries`.
     /// The `GraphNode::Query` node is added to the `dependency_graph`.
-    fn add_query_node(&self, _origin_node: GraphNode, query: DataQuery) -> GraphNode {
+    pub(super) fn add_query_node(&self, _origin_node: GraphNode, query: DataQuery) -> GraphNode {
         let idx = self.queries.borrow().len();
         self.queries.borrow_mut().push(query);
         let new_node = GraphNode::Query(idx);
@@ -4,6 +4,7 @@ use crate::{
         types::{AttributeName, ComponentIdx, PropPointer},
     },
     core::document_structure::DocumentStructure,
+    error::CoreError,
     graph_node::GraphNode,
     props::PropProfile,
 };
@@ -17,28 +18,28 @@ pub fn process_data_query_attribute(
     prop_pointer: PropPointer,
     query_node: GraphNode,
     document_structure: &DocumentStructure,
-) -> Vec<(GraphNode, GraphNode)> {
+) -> Result<Vec<(GraphNode, GraphNode)>, CoreError> {
     let mut ret = Vec::new();
 
     // Find the requested attribute.
-    let attr_node = document_structure
-        .get_attr_node(prop_pointer.component_idx, attribute_name)
-        .unwrap_or_else(|| {
-            panic!(
-                "Cannot find attribute `{}` on component `{}`",
-                attribute_name,
-                document_structure
-                    .get_component(prop_pointer.component_idx)
-                    .get_component_type()
-            )
+    let Some(attr_node) =
+        document_structure.get_attr_node(prop_pointer.component_idx, attribute_name)
+    else {
+        return Err(CoreError::AttributeNotFound {
+            attribute_name: attribute_name.to_string(),
+            component_type: document_structure
+                .get_component(prop_pointer.component_idx)?
+                .get_component_type()
+                .to_string(),
         });
+    };
 
     for node in document_structure.get_attribute_content_children(attr_node) {
         match node {
             GraphNode::Component(_) => {
                 // Check the component. We want to link to the first prop that matches one of the profiles.
                 let matching_prop = document_structure
-                    .get_component_prop_by_profile(ComponentIdx::from(node), &match_profiles)
+                    .get_component_prop_by_profile(ComponentIdx::try_from(node)?, &match_profiles)
                     .map(|prop_pointer| prop_pointer.into_prop_node(document_structure));
 
                 if let Some(matching_prop) = matching_prop {
@@ -64,5 +65,5 @@ pub fn process_data_query_attribute(
         }
     }
 
-    ret
+    Ok(ret)
 }
@@ -3,24 +3,30 @@
 //! This module contains information about the structure of the document as well as the state of the document.
 //! It can be queried for information about the document and its components.
 
-use std::cell::{Cell, Ref, RefCell};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+};
 
 use crate::{
     component_builder::ComponentBuilder,
     components::{
-        Component, ComponentNode, ComponentProps,
+        Component, ComponentNode, ComponentProps, ComponentVariantProps,
         types::{ComponentIdx, LocalPropIdx, PropPointer},
     },
     dast::ElementRefAnnotation,
-    graph_node::{DependencyGraph, GraphNode},
+    document_flags::DocumentFlags,
+    error::CoreError,
+    graph_node::{DependencyGraph, GraphNode, GraphNodeLookup},
     props::{
-        DataQuery, DataQueryResults, PropDefinition, PropProfile, RenderContext, StateCache,
-        UpdaterObject,
+        DataQuery, DataQueryResults, PropCalcResult, PropDefinition, PropProfile, PropValue,
+        RenderContext, StateCache, UpdaterObject,
         cache::{PropCache, PropStatus, PropWithMeta},
     },
 };
 
 use super::super::document_structure::DocumentStructure;
+use super::prop_updates::ComponentChanges;
 
 #[derive(Debug)]
 pub struct DocumentModel {
@@ -42,6 +48,20 @@ pub struct DocumentModel {
     // XXX: Revisit if we still need this.
     #[allow(unused)]
     pub(super) virtual_node_count: Cell<usize>,
+    /// The flags of the document, which are returned by `DataQuery::DocumentFlag`.
+    pub(super) flags: DocumentFlags,
+    /// The data queries whose matches depend on the values of props, along with
+    /// the content they matched the last time they were executed (`None` if they have not been executed).
+    pub(super) dynamic_query_matches: RefCell<GraphNodeLookup<Option<Vec<GraphNode>>>>,
+    /// The data queries in `dynamic_query_matches` that depend on props that went stale, so that they must be matched again.
+    pub(super) stale_dynamic_queries: RefCell<Vec<GraphNode>>,
+    /// The data queries in `dynamic_query_matches` that matched different content the last time they were executed.
+    pub(super) queries_with_changed_matches: RefCell<Vec<GraphNode>>,
+    /// The data queries whose dependencies are determined by the content children of a component, keyed by that component,
+    /// so that they can be linked again when its children change.
+    pub(super) children_queries: RefCell<HashMap<ComponentIdx, Vec<GraphNode>>>,
+    /// The component whose content children determine the dependencies of each query in `children_queries`.
+    pub(super) children_query_containers: RefCell<GraphNodeLookup<ComponentIdx>>,
 }
 
 impl DocumentModel {
@@ -53,10 +73,17 @@ impl DocumentModel {
             states: StateCache::new(),
             queries: RefCell::new(Vec::new()),
             prop_cache: PropCache::new(),
-            // Start with a count of 1, as the virtual node with index 0
+            // Start with a count of 2, as the virtual node with index 0
             // will be used to represent null,
-            // i.e., the lack of a node in that spot in the dependency graph.
-            virtual_node_count: Cell::new(1),
+            // i.e., the lack of a node in that spot in the dependency graph,
+            // and the virtual node with index 1 is depended on by flag queries.
+            virtual_node_count: Cell::new(2),
+            flags: DocumentFlags::default(),
+            dynamic_query_matches: RefCell::new(GraphNodeLookup::new()),
+            stale_dynamic_queries: RefCell::new(Vec::new()),
+            queries_with_changed_matches: RefCell::new(Vec::new()),
+            children_queries: RefCell::new(HashMap::new()),
+            children_query_containers: RefCell::new(GraphNodeLookup::new()),
         }
     }
 
@@ -76,6 +103,45 @@ impl DocumentModel {
             .init_from_builder(builder);
     }
 
+    /// Set the flags of the document and mark the props that depend on the flags as stale.
+    ///
+    /// Return the components that have had one of their `for_render` props newly marked as stale.
+    pub fn set_flags(&mut self, flags: DocumentFlags) -> Result<ComponentChanges, CoreError> {
+        if flags == self.flags {
+            return Ok(ComponentChanges::default());
+        }
+        self.flags = flags;
+
+        let flags_node = Self::get_flags_node();
+        if !self.dependency_graph.borrow().contains_node(flags_node) {
+            // No prop has used a flag yet
+            return Ok(ComponentChanges::default());
+        }
+        self.mark_dependents_stale(&[flags_node])
+    }
+
+    /// The flags of the document.
+    pub fn get_flags(&self) -> &DocumentFlags {
+        &self.flags
+    }
+
+    /// Add a `DataQuery::Null` that no prop depends on. It can be used as the `origin` when getting props
+    /// to track whether they changed independently of rendering (e.g., for subscriptions to props).
+    pub fn add_null_query_node(&self) -> GraphNode {
+        self.add_query_node(GraphNode::Query(0), DataQuery::Null)
+    }
+
+    /// Remove a node added by [`DocumentModel::add_null_query_node`], along with its edges
+    /// and the change tracking of the props, states and strings it was the `origin` of.
+    pub fn remove_null_query_node(&self, query_node: GraphNode) {
+        self.dependency_graph.borrow_mut().remove_node(query_node);
+        self.prop_cache.remove_origin(query_node);
+        self.states.remove_origin(query_node);
+        self.document_structure
+            .borrow()
+            .remove_string_origin(query_node);
+    }
+
     pub fn get_dependency_graph(&'_ self) -> Ref<'_, DependencyGraph> {
         self.dependency_graph.borrow()
     }
@@ -84,40 +150,44 @@ impl DocumentModel {
     /// this function will resolve the prop, calculate all its dependencies, and then
     /// return the result of `PropUpdater::calculate` applied to those dependencies.
     /// Track that the prop has been viewed for rendering so that a second call will report it being unchanged.
-    pub fn get_prop(&self, prop_node: GraphNode, origin: GraphNode) -> PropWithMeta {
-        self.resolve_prop(prop_node);
-
-        self.prop_cache.get_prop(prop_node, origin, || {
-            let required_data = DataQueryResults::from_vec(
-                self.get_data_query_nodes_for_prop(prop_node)
-                    .into_iter()
-                    .map(|query_node| self._execute_data_query_with_resolved_deps(query_node))
-                    .collect(),
-            );
+    pub fn get_prop(
+        &self,
+        prop_node: GraphNode,
+        origin: GraphNode,
+    ) -> Result<PropWithMeta, CoreError> {
+        self.resolve_prop(prop_node)?;
 
-            let prop = &self.get_prop_definition(prop_node.prop_idx());
-            prop.updater.calculate_untyped(required_data)
-        })
+        self.prop_cache
+            .try_get_prop(prop_node, origin, || self.calculate_prop(prop_node))
     }
 
     /// Get the value of a prop for rendering. If the prop is stale or not resolved,
     /// this function will resolve the prop, calculate all its dependencies, and then
     /// return the result of `PropUpdater::calculate` applied to those dependencies.
     /// Do not track that the prop has been viewed for rendering so that its change state is unaltered.
-    pub fn get_prop_untracked(&self, prop_node: GraphNode, origin: GraphNode) -> PropWithMeta {
-        self.resolve_prop(prop_node);
+    pub fn get_prop_untracked(
+        &self,
+        prop_node: GraphNode,
+        origin: GraphNode,
+    ) -> Result<PropWithMeta, CoreError> {
+        self.resolve_prop(prop_node)?;
 
-        self.prop_cache.get_prop_untracked(prop_node, origin, || {
-            let required_data = DataQueryResults::from_vec(
-                self.get_data_query_nodes_for_prop(prop_node)
-                    .into_iter()
-                    .map(|query_node| self._execute_data_query_with_resolved_deps(query_node))
-                    .collect(),
-            );
+        self.prop_cache
+            .try_get_prop_untracked(prop_node, origin, || self.calculate_prop(prop_node))
+    }
 
-            let prop_definition = self.get_prop_definition(prop_node);
-            prop_definition.updater.calculate_untyped(required_data)
-        })
+    /// Calculate the value of a **resolved** prop by calculating all its dependencies
+    /// and then applying `PropUpdater::calculate` to those dependencies.
+    fn calculate_prop(&self, prop_node: GraphNode) -> Result<PropCalcResult<PropValue>, CoreError> {
+        let required_data = DataQueryResults::from_vec(
+            self.get_data_query_nodes_for_prop(prop_node)
+                .into_iter()
+                .map(|query_node| self._execute_data_query_with_resolved_deps(query_node))
+                .collect::<Result<_, _>>()?,
+        );
+
+        let prop_definition = self.get_prop_definition(prop_node)?;
+        Ok(prop_definition.updater.calculate_untyped(required_data))
     }
 
     /// Get the value of a prop without checking its status. This function assumes the value
@@ -139,40 +209,57 @@ impl DocumentModel {
 
     /// Get a `PropDefinition` corresponding to `pointer`. The type of `pointer` can be `GraphNode`
     /// or `PropDefinitionIdx`.
-    pub fn get_prop_definition<T: Into<GraphNode>>(&self, pointer: T) -> PropDefinition {
+    pub fn get_prop_definition<T: Into<GraphNode>>(
+        &self,
+        pointer: T,
+    ) -> Result<PropDefinition, CoreError> {
         let document_structure = self.document_structure.borrow();
-        (*document_structure.get_prop_definition(pointer)).clone()
+        document_structure.get_prop_definition(pointer).cloned()
     }
 
     /// Get the prop `UpdaterObject` corresponding to `pointer`.
     /// The type of `pointer` can be `GraphNode` or `PropDefinitionIdx`.
-    pub fn get_prop_updater<T: Into<GraphNode>>(&self, pointer: T) -> UpdaterObject {
+    pub fn get_prop_updater<T: Into<GraphNode>>(
+        &self,
+        pointer: T,
+    ) -> Result<UpdaterObject, CoreError> {
         let document_structure = self.document_structure.borrow();
-        document_structure
-            .get_prop_definition(pointer)
+        Ok(document_structure
+            .get_prop_definition(pointer)?
             .updater
-            .clone()
+            .clone())
     }
 
     /// Get the requested component type
-    pub fn get_component_type<T: Into<GraphNode>>(&self, pointer: T) -> String {
+    pub fn get_component_type<T: Into<GraphNode>>(&self, pointer: T) -> Result<String, CoreError> {
         let document_structure = self.document_structure.borrow();
-        document_structure
-            .get_component(pointer)
+        Ok(document_structure
+            .get_component(pointer)?
             .get_component_type()
-            .to_string()
+            .to_string())
     }
 
-    /// An iterator that iterates over the indices of every component.
+    /// An iterator that iterates over the indices of every component that was not removed.
     pub fn get_component_indices(&self) -> impl Iterator<Item = ComponentIdx> {
         self.document_structure.borrow().get_component_indices()
     }
 
+    /// The number of components that have been created, including those that were removed.
+    /// A new component is given this number as its index.
+    pub fn get_num_components(&self) -> usize {
+        self.document_structure.borrow()._get_num_components()
+    }
+
+    /// Returns `true` if the component was removed from the document.
+    pub fn is_removed(&self, component_idx: ComponentIdx) -> bool {
+        self.document_structure.borrow().is_removed(component_idx)
+    }
+
     /// Get prop pointers to all `for_render` props of a component.
     pub fn get_for_render_prop_pointers(
         &self,
         component_idx: ComponentIdx,
-    ) -> impl Iterator<Item = PropPointer> {
+    ) -> Result<impl Iterator<Item = PropPointer>, CoreError> {
         let local_prop_indices = {
             let document_structure = self.document_structure.borrow();
 
@@ -183,8 +270,7 @@ impl DocumentModel {
                 .any(|ancestor_idx| {
                     document_structure
                         .get_component(ancestor_idx)
-                        .get_component_type()
-                        == "graph"
+                        .is_ok_and(|ancestor| ancestor.get_component_type() == "graph")
                 });
 
             let render_context = match in_graph {
@@ -193,17 +279,17 @@ impl DocumentModel {
             };
 
             let iterator = document_structure
-                .get_component(component_idx)
+                .get_component(component_idx)?
                 .get_for_render_local_prop_indices(render_context);
             // Note: collect into a vector so that stop borrowing from document_structure.components
             iterator.collect::<Vec<_>>()
         };
-        local_prop_indices
+        Ok(local_prop_indices
             .into_iter()
             .map(move |local_prop_idx| PropPointer {
                 component_idx,
                 local_prop_idx,
-            })
+            }))
     }
 
     /// Convert a `PropPointer` into a `GraphNode::Prop`
@@ -213,20 +299,20 @@ impl DocumentModel {
     }
 
     /// Get the name of a prop
-    pub fn get_prop_name(&self, prop_pointer: PropPointer) -> &'static str {
+    pub fn get_prop_name(&self, prop_pointer: PropPointer) -> Result<&'static str, CoreError> {
         let document_structure = self.document_structure.borrow();
         let prop_node = prop_pointer.into_prop_node(&document_structure);
-        let prop_name = document_structure.get_prop_definition(prop_node).meta.name;
-        prop_name
+        let prop_name = document_structure.get_prop_definition(prop_node)?.meta.name;
+        Ok(prop_name)
     }
 
     /// Get a `PropPointer` for the prop.
-    pub fn get_prop_pointer(&self, prop_node: GraphNode) -> PropPointer {
+    pub fn get_prop_pointer(&self, prop_node: GraphNode) -> Result<PropPointer, CoreError> {
         let document_structure = self.document_structure.borrow();
-        document_structure
-            .get_prop_definition(prop_node)
+        Ok(document_structure
+            .get_prop_definition(prop_node)?
             .meta
-            .prop_pointer
+            .prop_pointer)
     }
 
     /// Get the string associated with a `GraphNode::String`
@@ -247,20 +333,32 @@ impl DocumentModel {
     pub fn get_provided_profiles(
         &self,
         component_idx: ComponentIdx,
-    ) -> Vec<(PropProfile, LocalPropIdx)> {
+    ) -> Result<Vec<(PropProfile, LocalPropIdx)>, CoreError> {
         let document_structure = self.document_structure.borrow();
-        document_structure
-            .get_component(component_idx)
-            .provided_profiles()
+        Ok(document_structure
+            .get_component(component_idx)?
+            .provided_profiles())
     }
 
     /// Get a clone of the component at the given index. Since a clone is returned, the
     /// caller can take ownership of the parts of the returned component.
-    pub fn get_component(&self, component_idx: ComponentIdx) -> Component {
+    pub fn get_component(&self, component_idx: ComponentIdx) -> Result<Component, CoreError> {
         self.document_structure
             .borrow()
             .get_component(component_idx)
-            .clone()
+            .cloned()
+    }
+
+    /// Check that `prop_pointer` refers to a prop of a component of the document.
+    pub fn check_prop_pointer(&self, prop_pointer: PropPointer) -> Result<(), CoreError> {
+        let component = self.get_component(prop_pointer.component_idx)?;
+        if prop_pointer.local_prop_idx.as_usize() >= component.variant.get_num_props() {
+            return Err(CoreError::LocalPropNotFound {
+                component_idx: prop_pointer.component_idx.as_usize(),
+                local_prop_idx: prop_pointer.local_prop_idx.as_usize(),
+            });
+        }
+        Ok(())
     }
 
     /// Get the first prop that matches a profile in `profiles` for a given component.
@@ -278,7 +376,7 @@ impl DocumentModel {
     pub fn get_component_content_children<T: Into<ComponentIdx>>(
         &self,
         component_idx: T,
-    ) -> Vec<GraphNode> {
+    ) -> Result<Vec<GraphNode>, CoreError> {
         let component_idx: ComponentIdx = component_idx.into();
         let document_structure = self.document_structure.borrow();
         document_structure.get_component_content_children(component_idx)
@@ -289,7 +387,7 @@ impl DocumentModel {
     pub fn get_component_content_children_annotated<T: Into<ComponentIdx>>(
         &self,
         component_idx: T,
-    ) -> Vec<(GraphNode, ElementRefAnnotation)> {
+    ) -> Result<Vec<(GraphNode, ElementRefAnnotation)>, CoreError> {
         let component_idx: ComponentIdx = component_idx.into();
         let document_structure = self.document_structure.borrow();
         document_structure.get_component_content_children_annotated(component_idx)
@@ -330,6 +330,7 @@ fn do_not_transmute_with_extend_attribute() {
     assert_eq!(
         document_structure
             .get_component(GraphNode::Component(2 as usize))
+            .unwrap()
             .get_component_type(),
         "textInput"
     );
@@ -0,0 +1,120 @@
+//! Data queries whose matches depend on the values of props, e.g., a `DataQuery::ContentRefs`
+//! whose filter excludes hidden children with `ContentFilter::HasPropMatchingProfileAndCondition`.
+//!
+//! The content matched by such a query is recorded each time the query is executed while calculating a prop.
+//! If different content matches than the previous time, the query is recorded as having changed matches.
+//! [`DocumentModel::take_components_with_changed_matches`] calculates the props that own the queries
+//! that went stale, links the queries with changed matches again, and reports the components that own them
+//! as having changed children, so that their new children can be rendered.
+//!
+//! `DataQuery::PickProp` and `DataQuery::Attribute` match props by their profiles, which do not change,
+//! so they are never matched again.
+
+use crate::{
+    components::prelude::ComponentIdx, error::CoreError, graph::directed_graph::Taggable,
+    graph_node::GraphNode,
+};
+
+use super::DocumentModel;
+
+impl DocumentModel {
+    /// Record whether the matches of `query_node` depend on the values of props.
+    /// The matches of a query that is already registered are kept, so that a relinked query
+    /// is still compared against the content it matched before.
+    pub(super) fn register_dynamic_query(&self, query_node: GraphNode, is_dynamic: bool) {
+        let mut dynamic_query_matches = self.dynamic_query_matches.borrow_mut();
+        if !is_dynamic {
+            dynamic_query_matches.remove_tag(&query_node);
+        } else if dynamic_query_matches.get_tag(&query_node).is_none() {
+            dynamic_query_matches.set_tag(query_node, None);
+        }
+    }
+
+    /// Record `matches`, the content that `query_node` matched when it was executed.
+    /// Nothing is recorded for queries whose matches do not depend on the values of props.
+    ///
+    /// If the query was executed before and matched different content, it is recorded as having changed matches.
+    pub(super) fn record_query_matches(&self, query_node: GraphNode, matches: Vec<GraphNode>) {
+        let mut dynamic_query_matches = self.dynamic_query_matches.borrow_mut();
+        let changed = match dynamic_query_matches.get_tag(&query_node) {
+            None => return,
+            Some(None) => false,
+            Some(Some(previous_matches)) => *previous_matches != matches,
+        };
+        dynamic_query_matches.set_tag(query_node, Some(matches));
+
+        let mut queries_with_changed_matches = self.queries_with_changed_matches.borrow_mut();
+        if changed && !queries_with_changed_matches.contains(&query_node) {
+            queries_with_changed_matches.push(query_node);
+        }
+    }
+
+    /// Record that `query_nodes`, queries whose matches depend on the values of props,
+    /// depend on props that went stale, so they must be matched again.
+    /// The queries that have not been executed yet have nothing to compare against, so they are skipped.
+    pub(super) fn record_stale_dynamic_queries(&self, query_nodes: &[GraphNode]) {
+        let dynamic_query_matches = self.dynamic_query_matches.borrow();
+        let mut stale_dynamic_queries = self.stale_dynamic_queries.borrow_mut();
+        for query_node in query_nodes {
+            if matches!(dynamic_query_matches.get_tag(query_node), Some(Some(_)))
+                && !stale_dynamic_queries.contains(query_node)
+            {
+                stale_dynamic_queries.push(*query_node);
+            }
+        }
+    }
+
+    /// Match the queries that went stale again by calculating the props that own them,
+    /// and link the queries whose matches changed to the current dependencies of their filters.
+    ///
+    /// Return the components that own a query whose matches changed since this was last called.
+    pub fn take_components_with_changed_matches(&self) -> Result<Vec<ComponentIdx>, CoreError> {
+        let stale_dynamic_queries = std::mem::take(&mut *self.stale_dynamic_queries.borrow_mut());
+        for query_node in stale_dynamic_queries {
+            // Queries that were removed since they went stale are no longer registered.
+            if self
+                .dynamic_query_matches
+                .borrow()
+                .get_tag(&query_node)
+                .is_none()
+            {
+                continue;
+            }
+            let prop_node = self.get_query_owner_node(query_node)?;
+            self.get_prop_untracked(prop_node, query_node)?;
+        }
+
+        let queries_with_changed_matches =
+            std::mem::take(&mut *self.queries_with_changed_matches.borrow_mut());
+        let mut components_with_changed_matches = Vec::new();
+        for query_node in queries_with_changed_matches {
+            if self
+                .dynamic_query_matches
+                .borrow()
+                .get_tag(&query_node)
+                .is_none()
+            {
+                continue;
+            }
+            self.relink_data_query(query_node)?;
+
+            let prop_node = self.get_query_owner_node(query_node)?;
+            let component_idx = self.get_prop_pointer(prop_node)?.component_idx;
+            if !components_with_changed_matches.contains(&component_idx) {
+                components_with_changed_matches.push(component_idx);
+            }
+        }
+        components_with_changed_matches.sort_by_key(|component_idx| component_idx.as_usize());
+
+        Ok(components_with_changed_matches)
+    }
+
+    /// The prop that owns `query_node`.
+    fn get_query_owner_node(&self, query_node: GraphNode) -> Result<GraphNode, CoreError> {
+        self.get_nearest_prop_ancestor_of_query(query_node)
+            .ok_or(CoreError::UnexpectedGraphNode {
+                expected: "Query owned by a prop",
+                found: query_node,
+            })
+    }
+}
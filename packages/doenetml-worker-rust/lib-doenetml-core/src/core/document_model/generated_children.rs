@@ -0,0 +1,133 @@
+//! Replace the children that are generated for a component after the document was initialized,
+//! e.g., when the inputs of a composite such as `<repeat>` change.
+
+use crate::{
+    components::{prelude::UntaggedContent, types::ComponentIdx},
+    dast::flat_dast::NormalizedNode,
+    error::CoreError,
+    graph_node::GraphNode,
+};
+
+use super::{DocumentModel, prop_updates::ComponentChanges};
+
+impl DocumentModel {
+    /// Replace the children that were generated for `parent` with `children`, creating components for `nodes`,
+    /// the normalized nodes of the new children and their descendants.
+    /// The index of each node must be one more than the index of the previous node,
+    /// starting with the number of components in the document.
+    /// `removed_components` are the components of the previously generated children and their descendants, which are removed.
+    ///
+    /// The data queries that depend on the content children of the affected components or on the removed components
+    /// are linked again, and the props that depend on them are marked stale.
+    ///
+    /// The components with changed children are the component given the children
+    /// and any components that get their children by extending it.
+    pub fn replace_generated_children(
+        &self,
+        parent: ComponentIdx,
+        removed_components: &[ComponentIdx],
+        children: &[UntaggedContent],
+        nodes: &[NormalizedNode],
+    ) -> Result<ComponentChanges, CoreError> {
+        let replaced_nodes = {
+            let mut document_structure = self.document_structure.borrow_mut();
+            // Check that `parent` exists before taking the structure apart.
+            document_structure.get_component(parent)?;
+            let mut builder = document_structure.take_into_builder();
+            let replaced_nodes = builder.replace_generated_children(parent, children, nodes);
+            document_structure.init_from_builder(builder);
+            replaced_nodes
+        };
+
+        // The replaced strings are removed with the components.
+        let replaced_strings = replaced_nodes
+            .into_iter()
+            .filter(|node| matches!(node, GraphNode::String(_)))
+            .collect::<Vec<_>>();
+        let dangling_queries = self.remove_components(removed_components, &replaced_strings);
+
+        let Some(generated_children_virtual_node) = self
+            .document_structure
+            .borrow()
+            .get_generated_children_virtual_node(parent)
+        else {
+            return Ok(ComponentChanges::default());
+        };
+        let components_with_changed_children =
+            self.get_components_with_content_child(generated_children_virtual_node);
+
+        self.relink_children_queries(components_with_changed_children, dangling_queries)
+    }
+
+    /// Link the data queries that depend on the content children of `components_with_changed_children`
+    /// to their new children, along with `query_nodes`, and mark the props that depend on them stale.
+    ///
+    /// The components with changed children are reported in the returned changes.
+    pub(super) fn relink_children_queries(
+        &self,
+        components_with_changed_children: Vec<ComponentIdx>,
+        query_nodes: Vec<GraphNode>,
+    ) -> Result<ComponentChanges, CoreError> {
+        let mut relinked_query_nodes = query_nodes;
+        {
+            let children_queries = self.children_queries.borrow();
+            for component_idx in components_with_changed_children.iter() {
+                for query_node in children_queries.get(component_idx).into_iter().flatten() {
+                    if !relinked_query_nodes.contains(query_node) {
+                        relinked_query_nodes.push(*query_node);
+                    }
+                }
+            }
+        }
+        for query_node in relinked_query_nodes.iter() {
+            self.relink_data_query(*query_node)?;
+        }
+
+        let mut changes = self.mark_dependents_stale(&relinked_query_nodes)?;
+        for component_idx in components_with_changed_children {
+            if !changes
+                .components_with_changed_children
+                .contains(&component_idx)
+            {
+                changes.components_with_changed_children.push(component_idx);
+            }
+        }
+        changes
+            .components_with_changed_children
+            .sort_by_key(|component_idx| component_idx.as_usize());
+
+        Ok(changes)
+    }
+
+    /// The components that have `node`, a child of a children virtual node, among their content children:
+    /// the component of that children virtual node and any components that (indirectly) extend it.
+    pub(super) fn get_components_with_content_child(&self, node: GraphNode) -> Vec<ComponentIdx> {
+        let document_structure = self.document_structure.borrow();
+        let structure_graph = document_structure.get_structure_graph();
+
+        // Content children are found by walking down virtual nodes from the children virtual node of a component,
+        // so we walk up virtual nodes to find the components.
+        let mut components = Vec::new();
+        let mut visited = vec![node];
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            for parent_node in structure_graph.get_parents(node) {
+                match parent_node {
+                    GraphNode::Virtual(_) if !visited.contains(&parent_node) => {
+                        visited.push(parent_node);
+                        stack.push(parent_node);
+                    }
+                    GraphNode::Component(idx) => {
+                        let component_idx = ComponentIdx::new(idx);
+                        if !components.contains(&component_idx) {
+                            components.push(component_idx);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        components.sort_by_key(|component_idx| component_idx.as_usize());
+        components
+    }
+}
@@ -3,14 +3,22 @@
 //! This module contains information about the structure of the document as well as the state of the document.
 //! It can be queried for information about the document and its components.
 
+mod component_removal;
 mod dependency_creation;
 mod dependency_creation_helpers;
 #[allow(clippy::module_inception)]
 mod document_model;
+mod dynamic_queries;
+mod generated_children;
 mod prop_calculation;
 mod prop_updates;
+mod source_children;
+mod state_access;
+mod warnings;
 
 pub use document_model::*;
+pub use prop_updates::{ComponentChanges, RequestedPropUpdate};
+pub use state_access::NodeValue;
 
 #[cfg(any(feature = "testing", test, not(feature = "web")))]
 mod debug;
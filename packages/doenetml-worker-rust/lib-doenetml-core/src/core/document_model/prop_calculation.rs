@@ -2,6 +2,7 @@ use std::rc::Rc;
 
 use crate::{
     dast::ElementRefAnnotation,
+    error::CoreError,
     props::{DataQuery, DataQueryResults, FilterData, PropSource, PropValue},
     state::types::content_refs::{ContentRef, ContentRefs},
 };
@@ -15,6 +16,7 @@ use super::{
         },
     },
     DocumentModel,
+    dependency_creation::AddDataQueryError,
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -76,10 +78,13 @@ impl DocumentModel {
     /// They need to be resolved to be used.
     ///
     /// We resolve the prop by adding its data query to the dependency graph.
-    pub fn resolve_prop(&self, prop_node: GraphNode) {
+    ///
+    /// Returns an error if the prop (indirectly) depends on itself, which is found when
+    /// a prop that is still being resolved is reached again.
+    pub fn resolve_prop(&self, prop_node: GraphNode) -> Result<(), CoreError> {
         // Short-circuit if the prop is already resolved
         if self.prop_cache.get_prop_status(prop_node) != PropStatus::Unresolved {
-            return;
+            return Ok(());
         }
 
         // Stack to keep track of props that need to be resolved
@@ -101,13 +106,24 @@ impl DocumentModel {
                     continue;
                 }
                 PropStatus::Resolving => {
-                    if matches!(processing_state.status, QueriesStatus::Processed) {
-                        // If prop is in resolving state, that means we've gotten back to this prop
-                        // after all its dependencies have been resolved; it's now resolved.
-                        self.prop_cache
-                            .set_prop_status(prop_node, PropStatus::Resolved);
-                        resolve_stack.pop();
-                        continue;
+                    match processing_state.status {
+                        QueriesStatus::Processed => {
+                            // If prop is in resolving state, that means we've gotten back to this prop
+                            // after all its dependencies have been resolved; it's now resolved.
+                            self.prop_cache
+                                .set_prop_status(prop_node, PropStatus::Resolved);
+                            resolve_stack.pop();
+                            continue;
+                        }
+                        QueriesStatus::Unprocessed => {
+                            // A prop is only `Resolving` while it is being processed lower on the stack,
+                            // so a fresh entry for it means that it (indirectly) depends on itself.
+                            return Err(CoreError::CircularDependency(get_cycle_from_stack(
+                                &resolve_stack[..processing_state_idx],
+                                prop_node,
+                            )));
+                        }
+                        _ => {}
                     }
                 }
                 PropStatus::Unresolved => {
@@ -119,7 +135,7 @@ impl DocumentModel {
             self.prop_cache
                 .set_prop_status(prop_node, PropStatus::Resolving);
 
-            let prop = self.get_prop_definition(prop_node);
+            let prop = self.get_prop_definition(prop_node)?;
             let data_queries = prop.updater.data_queries();
             let num_data_queries = data_queries.len();
             match processing_state.status {
@@ -139,11 +155,12 @@ impl DocumentModel {
                                     .filter(is_prop_node)
                                     .map(NodeProcessingState::new),
                             ),
-                            Err(node) => {
+                            Err(AddDataQueryError::Unresolved(node)) => {
                                 // `node` is a node that needs to be resolved before we can finish resolving `prop_node`
                                 tmp_unresolved_queries.push(i);
                                 tmp_unresolved_query_nodes.push(node);
                             }
+                            Err(AddDataQueryError::Core(err)) => return Err(err),
                         }
                     }
                     if tmp_unresolved_queries.is_empty() {
@@ -188,11 +205,12 @@ impl DocumentModel {
                                         .map(NodeProcessingState::new),
                                 )
                             }
-                            Err(node) => {
+                            Err(AddDataQueryError::Unresolved(node)) => {
                                 // `node` is a node that needs to be resolved before we can finish resolving `prop_node`
                                 tmp_unresolved_queries.push(i);
                                 tmp_unresolved_query_nodes.push(node);
                             }
+                            Err(AddDataQueryError::Core(err)) => return Err(err),
                         }
                     }
                     if tmp_unresolved_queries.is_empty() {
@@ -214,10 +232,15 @@ impl DocumentModel {
             }
             resolve_stack[processing_state_idx] = processing_state;
         }
+
+        Ok(())
     }
 
     /// Gets the `DataQueryResult` associated with the given data query node.
-    pub fn execute_data_query(&mut self, query_node: GraphNode) -> DataQueryResult {
+    pub fn execute_data_query(
+        &mut self,
+        query_node: GraphNode,
+    ) -> Result<DataQueryResult, CoreError> {
         for node in self
             .dependency_graph
             .borrow()
@@ -225,7 +248,7 @@ impl DocumentModel {
             .into_iter()
         {
             match node {
-                GraphNode::Prop(_) => self.resolve_prop(node),
+                GraphNode::Prop(_) => self.resolve_prop(node)?,
                 GraphNode::Virtual(_) => {
                     // if we have a virtual node, we resolve its prop node children
                     // TODO: will we need to go yet another level of virtual nodes?
@@ -236,7 +259,7 @@ impl DocumentModel {
                         .into_iter()
                     {
                         match child_node {
-                            GraphNode::Prop(_) => self.resolve_prop(child_node),
+                            GraphNode::Prop(_) => self.resolve_prop(child_node)?,
                             _ => {}
                         }
                     }
@@ -251,7 +274,10 @@ impl DocumentModel {
     /// compute any dependencies required to call the prop's `calculate` function.
     ///
     /// If `prop_node` is not resolved, this function will panic.
-    pub fn _execute_data_query_with_resolved_deps(&self, query_node: GraphNode) -> DataQueryResult {
+    pub fn _execute_data_query_with_resolved_deps(
+        &self,
+        query_node: GraphNode,
+    ) -> Result<DataQueryResult, CoreError> {
         let skip_fn = |prop_node: &GraphNode| {
             if matches!(prop_node, GraphNode::Prop(_)) {
                 self.prop_cache.get_prop_status(prop_node) == PropStatus::Fresh
@@ -287,10 +313,10 @@ impl DocumentModel {
                             .map(|dependency_query_node| {
                                 self._execute_data_query_with_fresh_deps(dependency_query_node)
                             })
-                            .collect(),
+                            .collect::<Result<_, _>>()?,
                     );
 
-                    let prop_definition = self.get_prop_definition(node);
+                    let prop_definition = self.get_prop_definition(node)?;
                     self.prop_cache.set_prop(
                         node,
                         prop_definition.updater.calculate_untyped(required_data),
@@ -310,7 +336,10 @@ impl DocumentModel {
     /// Executes a data query assuming all props for the data query are already fresh.
     ///
     /// Will panic if any required prop is not fresh.
-    fn _execute_data_query_with_fresh_deps(&self, query_node: GraphNode) -> DataQueryResult {
+    fn _execute_data_query_with_fresh_deps(
+        &self,
+        query_node: GraphNode,
+    ) -> Result<DataQueryResult, CoreError> {
         let query = &self.queries.borrow()[query_node.idx()];
 
         // Get the prop pointer to the prop that owns the current query.
@@ -323,38 +352,48 @@ impl DocumentModel {
         match query {
             DataQuery::ContentRefs { container, filter }
             | DataQuery::AnnotatedContentRefs { container, filter } => {
-                // Resolve a `PropSource` to a component index.
+                // Resolve a `PropSource` to a component index, if the source exists.
                 let resolve_prop_source = |prop_source: &PropSource| match prop_source {
-                    PropSource::Me => get_prop_pointer().component_idx,
-                    PropSource::Parent => self
-                        .document_structure
-                        .borrow()
-                        .get_true_component_parent(get_prop_pointer().component_idx)
-                        .unwrap(),
-                    PropSource::ByIdx(component_idx) => *component_idx,
+                    PropSource::Me => get_prop_pointer().map(|pointer| Some(pointer.component_idx)),
+                    PropSource::Parent => {
+                        let component_idx = get_prop_pointer()?.component_idx;
+                        Ok(self
+                            .document_structure
+                            .borrow()
+                            .get_true_component_parent(component_idx))
+                    }
+                    PropSource::ByIdx(component_idx) => Ok(Some(*component_idx)),
                     PropSource::StaticComponentRef(_) => {
                         panic!("Cannot combine `StaticComponentRef` with `ComponentRefs` query")
                     }
                 };
 
                 // Get the correct "root" for the query.
-                let component_idx = resolve_prop_source(container);
+                let component_idx = resolve_prop_source(container)?;
 
                 match query {
                     DataQuery::ContentRefs { .. } => {
-                        let content_children = self.get_component_content_children(component_idx);
+                        // A source that cannot be resolved (e.g., the parent of the document root) has no content.
+                        let content_children = match component_idx {
+                            Some(component_idx) => {
+                                self.get_component_content_children(component_idx)?
+                            }
+                            None => Vec::new(),
+                        };
 
                         let mut content_refs: Vec<ContentRef> = Vec::new();
+                        let mut matches = Vec::new();
                         for node in content_children {
                             if filter.apply_test(&FilterData {
                                 node,
                                 origin: query_node,
                                 document_model: self,
                             }) {
+                                matches.push(node);
                                 match node {
                                     GraphNode::Component(_) => {
                                         content_refs.push(ContentRef::Component(
-                                            node.component_idx().into(),
+                                            node.component_idx()?.into(),
                                         ));
                                     }
                                     GraphNode::String(_) => {
@@ -377,35 +416,43 @@ impl DocumentModel {
                             }
                         }
 
-                        DataQueryResult {
+                        self.record_query_matches(query_node, matches);
+
+                        Ok(DataQueryResult {
                             values: vec![PropWithMeta {
                                 value: PropValue::ContentRefs(Rc::new(content_refs.into())),
                                 came_from_default: false,
                                 changed: true,
                                 origin: Some(query_node),
                             }],
-                        }
+                        })
                     }
                     // `AnnotatedComponentRefs` are very similar to `ComponentRefs`, but they also include
                     // information about whether the content is original or comes from extending some other element.
                     DataQuery::AnnotatedContentRefs { .. } => {
-                        let content_children =
-                            self.get_component_content_children_annotated(component_idx);
+                        let content_children = match component_idx {
+                            Some(component_idx) => {
+                                self.get_component_content_children_annotated(component_idx)?
+                            }
+                            None => Vec::new(),
+                        };
 
                         let mut content_refs_and_annotations: Vec<(
                             ContentRef,
                             ElementRefAnnotation,
                         )> = Vec::new();
+                        let mut matches = Vec::new();
                         for (node, annotation) in content_children {
                             if filter.apply_test(&FilterData {
                                 node,
                                 origin: query_node,
                                 document_model: self,
                             }) {
+                                matches.push(node);
                                 match node {
                                     GraphNode::Component(_) => {
                                         content_refs_and_annotations.push((
-                                            ContentRef::Component(node.component_idx().into()),
+                                            ContentRef::Component(node.component_idx()?.into()),
                                             annotation,
                                         ));
                                     }
@@ -437,7 +484,9 @@ impl DocumentModel {
                             }
                         }
 
-                        DataQueryResult {
+                        self.record_query_matches(query_node, matches);
+
+                        Ok(DataQueryResult {
                             values: vec![PropWithMeta {
                                 value: PropValue::AnnotatedContentRefs(Rc::new(
                                     content_refs_and_annotations.into(),
@@ -446,24 +495,32 @@ impl DocumentModel {
                                 changed: true,
                                 origin: Some(query_node),
                             }],
-                        }
+                        })
                     }
                     _ => unreachable!(),
                 }
             }
             DataQuery::SelfRef => {
                 // This query is computed on the fly. We need to figure out who asked for this query.
-                let prop_pointer = get_prop_pointer();
+                let prop_pointer = get_prop_pointer()?;
 
-                DataQueryResult {
+                Ok(DataQueryResult {
                     values: vec![PropWithMeta {
                         value: PropValue::ComponentRef(Some(prop_pointer.component_idx.into())),
                         came_from_default: false,
                         changed: true,
                         origin: None,
                     }],
-                }
+                })
             }
+            DataQuery::DocumentFlag(flag) => Ok(DataQueryResult {
+                values: vec![PropWithMeta {
+                    value: self.flags.get(*flag),
+                    came_from_default: false,
+                    changed: true,
+                    origin: None,
+                }],
+            }),
             _ => {
                 //
                 // default behavior
@@ -548,7 +605,7 @@ impl DocumentModel {
                     })
                     .collect::<Vec<_>>();
 
-                DataQueryResult { values }
+                Ok(DataQueryResult { values })
             }
         }
     }
@@ -568,28 +625,51 @@ impl DocumentModel {
     pub fn _get_data_query_results_assuming_fresh_deps(
         &self,
         prop_node: GraphNode,
-    ) -> DataQueryResults {
-        DataQueryResults::from_vec(
+    ) -> Result<DataQueryResults, CoreError> {
+        Ok(DataQueryResults::from_vec(
             self.get_data_query_nodes_for_prop(prop_node)
                 .into_iter()
                 .map(|dependency_query_node| {
                     self._execute_data_query_with_fresh_deps(dependency_query_node)
                 })
-                .collect(),
-        )
+                .collect::<Result<_, _>>()?,
+        ))
     }
 
     /// Get the data needed to calculate the value of `prop_node.`
-    pub fn get_data_query_results(&mut self, prop_node: GraphNode) -> DataQueryResults {
-        DataQueryResults::from_vec(
+    pub fn get_data_query_results(
+        &mut self,
+        prop_node: GraphNode,
+    ) -> Result<DataQueryResults, CoreError> {
+        Ok(DataQueryResults::from_vec(
             self.get_data_query_nodes_for_prop(prop_node)
                 .into_iter()
                 .map(|dependency_query_node| self.execute_data_query(dependency_query_node))
-                .collect(),
-        )
+                .collect::<Result<_, _>>()?,
+        ))
     }
 }
 
 fn is_prop_node(node: &GraphNode) -> bool {
     matches!(node, GraphNode::Prop(_))
 }
+
+/// Get the props that form a cycle ending at `prop_node`, which was encountered again while being resolved.
+///
+/// The props on `resolve_stack` that have started processing are exactly the chain of props
+/// that led to `prop_node`, so the cycle consists of those props from the first occurrence of `prop_node` onwards.
+fn get_cycle_from_stack(
+    resolve_stack: &[NodeProcessingState],
+    prop_node: GraphNode,
+) -> Vec<GraphNode> {
+    let chain = resolve_stack
+        .iter()
+        .filter(|state| !matches!(state.status, QueriesStatus::Unprocessed))
+        .map(|state| state.prop_node)
+        .collect::<Vec<_>>();
+    let start = chain
+        .iter()
+        .position(|node| *node == prop_node)
+        .unwrap_or_default();
+    chain[start..].to_vec()
+}
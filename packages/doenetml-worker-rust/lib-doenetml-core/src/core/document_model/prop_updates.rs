@@ -2,18 +2,19 @@ use itertools::Itertools;
 
 use crate::{
     DocumentModel,
-    components::{
-        prelude::ComponentIdx,
-        types::{PropPointer, UpdateFromAction},
-    },
+    components::{prelude::ComponentIdx, types::PropPointer},
+    dispatch_action::PropUpdateStatus,
+    error::CoreError,
     graph::directed_graph::Taggable,
     graph_node::{GraphNode, GraphNodeLookup},
     props::{PropProfile, PropValue, cache::PropStatus},
 };
 
+use super::NodeValue;
+
 impl DocumentModel {
     /// Calculate new values of `State` or `String` nodes that are intended to achieve the values
-    /// of the props specified in `updates_from_action`.
+    /// of the props specified in `prop_updates`.
     ///
     /// The values are calculated using the `invert()` functions supplied by prop dependencies,
     /// recursing until a `State` or a `String` node is reached.
@@ -23,31 +24,37 @@ impl DocumentModel {
     /// Even when all updates succeed, the final values of the props may not match their requested values
     /// due to constraints of the system.
     ///
-    /// Return a `GraphNodeLookup` that will records the requested values of the `State` and `String` nodes.
+    /// Return a `GraphNodeLookup` that will records the requested values of the `State` and `String` nodes
+    /// along with a [`RequestedPropUpdate`] for each prop in `prop_updates` describing
+    /// whether its requested value could be propagated.
     pub fn calculate_changes_from_action_updates(
         &mut self,
-        updates_from_action: Vec<UpdateFromAction>,
-        component_idx: ComponentIdx,
-    ) -> GraphNodeLookup<PropValue> {
+        prop_updates: Vec<(PropPointer, PropValue)>,
+    ) -> Result<(GraphNodeLookup<PropValue>, Vec<RequestedPropUpdate>), CoreError> {
         let mut requested_value_lookup = GraphNodeLookup::new();
+        // For each node that has a requested value, the indices (in `props_to_update`)
+        // of the props whose requests caused it.
+        let mut requested_by: GraphNodeLookup<Vec<usize>> = GraphNodeLookup::new();
+        let mut requested_updates: Vec<RequestedPropUpdate> = Vec::new();
 
-        let props_to_update = updates_from_action
+        let props_to_update = prop_updates
             .into_iter()
-            .map(
-                |UpdateFromAction {
-                     local_prop_idx,
-                     requested_value,
-                 }| {
-                    let prop_node = self.prop_pointer_to_prop_node(PropPointer {
-                        component_idx,
-                        local_prop_idx,
-                    });
+            .enumerate()
+            .map(|(update_idx, (prop_pointer, requested_value))| {
+                let prop_node = self.prop_pointer_to_prop_node(prop_pointer);
 
-                    requested_value_lookup.set_tag(prop_node, requested_value);
+                requested_value_lookup.set_tag(prop_node, requested_value.clone());
+                add_requester(&mut requested_by, prop_node, &[update_idx]);
+                requested_updates.push(RequestedPropUpdate {
+                    prop_node,
+                    requested_value,
+                    reached_state: false,
+                    failure: None,
+                    failed_directly: false,
+                });
 
-                    prop_node
-                },
-            )
+                prop_node
+            })
             .collect::<Vec<_>>();
 
         // Call get_data_query_results on each original prop that is not fresh
@@ -56,9 +63,9 @@ impl DocumentModel {
             let status = self.get_prop_status(*prop_node);
             if status != PropStatus::Fresh {
                 if status == PropStatus::Unresolved {
-                    self.resolve_prop(*prop_node);
+                    self.resolve_prop(*prop_node)?;
                 }
-                self.get_data_query_results(*prop_node);
+                self.get_data_query_results(*prop_node)?;
             }
         }
 
@@ -80,26 +87,28 @@ impl DocumentModel {
                 continue;
             }
             let requested_value = requested_value.unwrap();
+            let requesters = requested_by.get_tag(&node).cloned().unwrap_or_default();
 
             match node {
                 GraphNode::Prop(_) => (),
-                GraphNode::State(_) => {
-                    // We've recursed all the way down to a `State` node, so record its requested value
+                GraphNode::State(_) | GraphNode::String(_) => {
+                    // We've recursed all the way down to a `State` or `String` node, so record its requested value
                     changes_to_make.set_tag(node, requested_value);
+                    for &update_idx in &requesters {
+                        requested_updates[update_idx].reached_state = true;
+                    }
                     continue;
                 }
-                GraphNode::String(_) => {
-                    // We've recursed all the way down to a `String` node, so record its requested value
-                    changes_to_make.set_tag(node, requested_value);
-                    continue;
+                _ => {
+                    return Err(CoreError::UnexpectedGraphNode {
+                        expected: "Prop",
+                        found: node,
+                    });
                 }
-                _ => panic!(
-                    "Should request value only of a prop, state or string node. Received {node:?}"
-                ),
             }
 
             let prop_node = node;
-            let prop_pointer = self.get_prop_pointer(prop_node);
+            let prop_pointer = self.get_prop_pointer(prop_node)?;
 
             // Check if the component has a `PropProfile::Fixed` with a value of true.
             // If so, then make the invert fail without even needing to call it
@@ -109,19 +118,25 @@ impl DocumentModel {
                 .get_component_prop_by_profile(prop_pointer.component_idx, &[PropProfile::Fixed]);
             if let Some(fixed_prop_pointer) = fixed_option {
                 let fixed_prop_node = self.prop_pointer_to_prop_node(fixed_prop_pointer);
-                let fixed_value = self.get_prop_untracked(fixed_prop_node, prop_node).value;
+                let fixed_value = self.get_prop_untracked(fixed_prop_node, prop_node)?.value;
                 let fixed: bool = fixed_value
                     .try_into()
                     .expect("fixed prop profile should be boolean");
                 if fixed {
                     // component was fixed, so skip invert (i.e., make it fail)
+                    record_failure(
+                        &mut requested_updates,
+                        &requesters,
+                        prop_node,
+                        PropUpdateStatus::RejectedFixed,
+                    );
                     continue;
                 }
             }
 
-            let prop_updater = self.get_prop_updater(prop_node);
+            let prop_updater = self.get_prop_updater(prop_node)?;
 
-            let required_data = self._get_data_query_results_assuming_fresh_deps(prop_node);
+            let required_data = self._get_data_query_results_assuming_fresh_deps(prop_node)?;
 
             // if node is one of the original nodes specified by the action,
             // then we have a direct change from action
@@ -136,6 +151,12 @@ impl DocumentModel {
             // If we were unable to invert prop, then simply stop trying to update that part of the graph
             // and carry on if there are other paths to update.
             if invert_result.is_err() {
+                record_failure(
+                    &mut requested_updates,
+                    &requesters,
+                    prop_node,
+                    PropUpdateStatus::RejectedByInvert,
+                );
                 continue;
             }
 
@@ -146,34 +167,95 @@ impl DocumentModel {
             for data_query_result in invert_result.vec {
                 for prop in data_query_result.values {
                     if prop.changed {
-                        requested_value_lookup.set_tag(prop.origin.unwrap(), prop.value);
+                        let origin = prop.origin.unwrap();
+                        requested_value_lookup.set_tag(origin, prop.value);
+                        add_requester(&mut requested_by, origin, &requesters);
                     }
                 }
             }
         }
 
-        changes_to_make
+        Ok((changes_to_make, requested_updates))
     }
 
     /// Change all the `State` and `String` nodes in `changes_to_make` to their requested values.
     /// Mark all dependencies of those nodes as stale.
     ///
-    /// Return the components that have had one of their `for_render` props newly marked as stale.
+    /// Return the components that have had one of their `for_render` props newly marked as stale
+    /// and the components whose children changed as a result.
+    ///
+    /// If any of the changes is to a node other than a `State` or `String` node, or would set a `String` node
+    /// to a value that is not a string, an error is returned and no changes are made.
     pub fn execute_changes(
         &self,
         changes_to_make: GraphNodeLookup<PropValue>,
-    ) -> Vec<ComponentIdx> {
-        for (node, val) in changes_to_make.iter() {
+    ) -> Result<ComponentChanges, CoreError> {
+        self.restore_values(NodeValue::from_changes(&changes_to_make))
+    }
+
+    /// Change all the `State` and `String` nodes in `values` to the given values, restoring whether
+    /// the value of each state came from its default, as in [`DocumentModel::execute_changes`].
+    ///
+    /// Used to restore the values returned by [`DocumentModel::get_current_values`], e.g., when undoing an action,
+    /// so that a state that had its default value again counts as having its default.
+    pub fn restore_values(
+        &self,
+        values: GraphNodeLookup<NodeValue>,
+    ) -> Result<ComponentChanges, CoreError> {
+        let mut new_strings = Vec::new();
+        for (node, NodeValue { value: val, .. }) in values.iter() {
             match node {
-                GraphNode::State(_) => self.states.set_state(node, val.clone()),
-                GraphNode::String(_) => self
-                    .document_structure
-                    .borrow()
-                    .set_string(node, val.clone().try_into().unwrap()),
-                _ => panic!("Can set the value of only State and String nodes, found {node:?}"),
+                GraphNode::State(_) => {}
+                GraphNode::String(_) => {
+                    let s: String =
+                        val.clone()
+                            .try_into()
+                            .map_err(|_| CoreError::InvalidValue {
+                                node,
+                                value: format!("{val:?}"),
+                            })?;
+                    new_strings.push((node, s));
+                }
+                _ => {
+                    return Err(CoreError::UnexpectedGraphNode {
+                        expected: "State",
+                        found: node,
+                    });
+                }
+            }
+        }
+
+        for (node, val) in values.iter() {
+            if matches!(node, GraphNode::State(_)) {
+                self.states
+                    .restore_state(node, val.value.clone(), val.came_from_default);
             }
         }
+        for (node, s) in new_strings {
+            self.document_structure.borrow().set_string(node, s);
+        }
+
+        // A node that is not in the dependency graph has not been used to calculate any props.
+        let nodes_changed = {
+            let dependency_graph = self.dependency_graph.borrow();
+            values
+                .keys()
+                .filter(|node| dependency_graph.contains_node(node))
+                .collect_vec()
+        };
 
+        self.mark_dependents_stale(&nodes_changed)
+    }
+
+    /// Mark all props that depend on `nodes` as stale.
+    /// Data queries that depend on `nodes` and whose matches depend on the values of props are recorded
+    /// so that they are matched again by [`DocumentModel::take_components_with_changed_matches`].
+    ///
+    /// Return the components that have had one of their `for_render` props newly marked as stale.
+    pub(super) fn mark_dependents_stale(
+        &self,
+        nodes: &[GraphNode],
+    ) -> Result<ComponentChanges, CoreError> {
         let skip_fn = |node: &GraphNode| {
             if matches!(node, GraphNode::Prop(_)) {
                 self.prop_cache.get_prop_status(node) != PropStatus::Fresh
@@ -182,38 +264,140 @@ impl DocumentModel {
             }
         };
 
-        let nodes_changed = changes_to_make.keys().collect_vec();
         let mut changed_components =
             vec![false; self.document_structure.borrow()._get_num_components()];
+        let mut stale_queries = Vec::new();
 
-        // mark all prop nodes that depend on nodes_changed as stale
-        for node in self
-            .dependency_graph
-            .borrow()
-            .ancestors_reverse_topological_multiroot_with_skip(&nodes_changed, skip_fn)
+        // mark all prop nodes that depend on `nodes` as stale
         {
-            if matches!(node, GraphNode::Prop(_)) {
-                self.prop_cache.set_prop_status(node, PropStatus::Stale);
+            let dependency_graph = self.dependency_graph.borrow();
+            for &node in
+                dependency_graph.ancestors_reverse_topological_multiroot_with_skip(nodes, skip_fn)
+            {
+                match node {
+                    GraphNode::Prop(_) => {
+                        self.prop_cache.set_prop_status(node, PropStatus::Stale);
 
-                // if prop is marked for render, add to components_with_changed_for_render_prop
-                let prop_meta = &self.get_prop_definition(node).meta;
-                if prop_meta.for_render.in_graph || prop_meta.for_render.in_text {
-                    let component_idx = prop_meta.prop_pointer.component_idx;
-                    changed_components[component_idx.as_usize()] = true;
+                        // if prop is marked for render, add to components_with_changed_for_render_prop
+                        let prop_meta = &self.get_prop_definition(node)?.meta;
+                        if prop_meta.for_render.in_graph || prop_meta.for_render.in_text {
+                            let component_idx = prop_meta.prop_pointer.component_idx;
+                            changed_components[component_idx.as_usize()] = true;
+                        }
+                    }
+                    GraphNode::Query(_) => stale_queries.push(node),
+                    _ => {}
                 }
             }
         }
 
-        changed_components
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, changed)| {
-                if changed {
-                    Some(ComponentIdx::new(idx))
-                } else {
-                    None
-                }
-            })
-            .collect()
+        self.record_stale_dynamic_queries(&stale_queries);
+
+        Ok(ComponentChanges {
+            components_with_changed_children: Vec::new(),
+            components_with_changed_props: changed_components
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, changed)| {
+                    if changed {
+                        Some(ComponentIdx::new(idx))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+/// The components affected by a change to the document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentChanges {
+    /// The components whose content children changed, or whose data queries now match different children.
+    pub components_with_changed_children: Vec<ComponentIdx>,
+    /// The components that have had one of their `for_render` props newly marked as stale.
+    pub components_with_changed_props: Vec<ComponentIdx>,
+}
+
+impl ComponentChanges {
+    /// Add the components affected by `other`, keeping each component once.
+    pub fn extend(&mut self, other: ComponentChanges) {
+        for (components, other_components) in [
+            (
+                &mut self.components_with_changed_children,
+                other.components_with_changed_children,
+            ),
+            (
+                &mut self.components_with_changed_props,
+                other.components_with_changed_props,
+            ),
+        ] {
+            components.extend(other_components);
+            *components = components
+                .iter()
+                .copied()
+                .unique()
+                .sorted_by_key(|component_idx| component_idx.as_usize())
+                .collect();
+        }
+    }
+}
+
+/// A prop whose value was requested to change by an action, along with information
+/// about whether the requested value could be propagated to `State` or `String` nodes.
+#[derive(Debug, Clone)]
+pub struct RequestedPropUpdate {
+    pub prop_node: GraphNode,
+    pub requested_value: PropValue,
+    /// `true` if the request caused a change to at least one `State` or `String` node.
+    reached_state: bool,
+    /// The first reason that part of the request could not be propagated.
+    failure: Option<PropUpdateStatus>,
+    /// `true` if the requested prop itself (rather than one of its dependencies) could not be updated.
+    failed_directly: bool,
+}
+
+impl RequestedPropUpdate {
+    /// The outcome of the request, not taking into account whether the final value of the prop
+    /// matches the requested value.
+    pub fn status(&self) -> PropUpdateStatus {
+        match (self.failure, self.reached_state) {
+            (Some(failure), _) if self.failed_directly => failure,
+            (Some(failure), false) => failure,
+            (Some(_), true) => PropUpdateStatus::PartiallyApplied,
+            (None, true) => PropUpdateStatus::Accepted,
+            (None, false) => PropUpdateStatus::RejectedByInvert,
+        }
+    }
+}
+
+/// Record that the updates in `requesters` caused a request for the value of `node`.
+fn add_requester(
+    requested_by: &mut GraphNodeLookup<Vec<usize>>,
+    node: GraphNode,
+    requesters: &[usize],
+) {
+    let mut all_requesters = requested_by.get_tag(&node).cloned().unwrap_or_default();
+    for &update_idx in requesters {
+        if !all_requesters.contains(&update_idx) {
+            all_requesters.push(update_idx);
+        }
+    }
+    requested_by.set_tag(node, all_requesters);
+}
+
+/// Record that the request to change `prop_node` failed for the updates in `requesters`.
+fn record_failure(
+    requested_updates: &mut [RequestedPropUpdate],
+    requesters: &[usize],
+    prop_node: GraphNode,
+    failure: PropUpdateStatus,
+) {
+    for &update_idx in requesters {
+        let requested_update = &mut requested_updates[update_idx];
+        if requested_update.prop_node == prop_node {
+            requested_update.failed_directly = true;
+        }
+        requested_update.failure.get_or_insert(failure);
     }
 }
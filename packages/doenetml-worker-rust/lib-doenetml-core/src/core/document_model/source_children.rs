@@ -0,0 +1,72 @@
+//! Replace the children that a component got from the source after the document was initialized,
+//! e.g., when the author edits the source of the document.
+
+use std::ops::Range;
+
+use crate::{
+    components::{prelude::UntaggedContent, types::ComponentIdx},
+    dast::flat_dast::NormalizedRoot,
+    error::CoreError,
+    graph_node::GraphNode,
+};
+
+use super::{DocumentModel, prop_updates::ComponentChanges};
+
+impl DocumentModel {
+    /// Replace the children of `parent` in the range `old_range` of the children it got from the source with `children`.
+    /// `num_source_children` is the number of children `parent` got from the source before the replacement,
+    /// and `removed_components` are the components of the replaced children and their descendants, which are removed.
+    ///
+    /// `normalized_root` is the document after the replacement. Components are created for its nodes starting with
+    /// [`DocumentModel::get_num_components`], which are the nodes of the new children and their descendants,
+    /// and the warnings about unused names are recomputed from it.
+    ///
+    /// The data queries that depend on the content children of the affected components or on the removed components
+    /// are linked again, and the props that depend on them are marked stale.
+    ///
+    /// The components with changed children are `parent` and any components that get their children by extending it.
+    pub fn replace_source_children(
+        &self,
+        parent: ComponentIdx,
+        num_source_children: usize,
+        old_range: Range<usize>,
+        removed_components: &[ComponentIdx],
+        children: &[UntaggedContent],
+        normalized_root: &NormalizedRoot,
+    ) -> Result<ComponentChanges, CoreError> {
+        let (replaced_nodes, children_virtual_node) = {
+            let mut document_structure = self.document_structure.borrow_mut();
+            // Check that `parent` exists before taking the structure apart.
+            document_structure.get_component(parent)?;
+            let mut builder = document_structure.take_into_builder();
+            let nodes = &normalized_root.nodes[builder.components.len()..];
+            let replaced_nodes = builder.replace_source_children(
+                parent,
+                num_source_children,
+                old_range,
+                children,
+                nodes,
+            );
+            builder.set_unused_name_warnings(normalized_root);
+            document_structure.init_from_builder(builder);
+            let children_virtual_node = document_structure
+                .get_structure_graph()
+                .get_component_children_virtual_node(parent);
+            (replaced_nodes, children_virtual_node)
+        };
+
+        // The replaced strings are removed with the components.
+        let replaced_strings = replaced_nodes
+            .into_iter()
+            .filter(|node| matches!(node, GraphNode::String(_)))
+            .collect::<Vec<_>>();
+        let dangling_queries = self.remove_components(removed_components, &replaced_strings);
+
+        // The children virtual node of `parent` is the first child of the intermediate virtual node
+        // of any component extending `parent`, so walking up from it finds `parent` and those components.
+        let components_with_changed_children =
+            self.get_components_with_content_child(children_virtual_node);
+
+        self.relink_children_queries(components_with_changed_children, dangling_queries)
+    }
+}
@@ -0,0 +1,289 @@
+//! Functions for reading and locating the `State` and `String` nodes that hold the interactive state of a document.
+
+use crate::{
+    DocumentModel,
+    components::{
+        ComponentAttributes, ComponentEnum, ComponentProps,
+        types::{ComponentIdx, PropPointer},
+    },
+    error::CoreError,
+    graph::directed_graph::Taggable,
+    graph_node::{GraphNode, GraphNodeLookup},
+    props::PropValue,
+    state_snapshot::StringLocation,
+};
+
+/// The value of a `State` or `String` node, along with whether a state's value came from its default.
+/// Used to restore nodes to an earlier value, e.g., when an action is undone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeValue {
+    pub value: PropValue,
+    /// Whether the value of a `State` node came from its default. Always `false` for a `String` node.
+    pub came_from_default: bool,
+}
+
+impl NodeValue {
+    /// The values of `changes`, as set by an action rather than coming from a default.
+    pub fn from_changes(changes: &GraphNodeLookup<PropValue>) -> GraphNodeLookup<NodeValue> {
+        let mut values = GraphNodeLookup::new();
+        for (node, value) in changes.iter() {
+            values.set_tag(
+                node,
+                NodeValue {
+                    value: value.clone(),
+                    came_from_default: false,
+                },
+            );
+        }
+        values
+    }
+}
+
+impl DocumentModel {
+    /// Returns the _true_ parent of a component coming from the structure of the DAST.
+    pub fn get_true_component_parent(&self, component_idx: ComponentIdx) -> Option<ComponentIdx> {
+        self.document_structure
+            .borrow()
+            .get_true_component_parent(component_idx)
+    }
+
+    /// Get the current values of the `State` and `String` nodes that are keys of `nodes`,
+    /// along with whether the values of the states came from their defaults.
+    ///
+    /// The values are retrieved without affecting change tracking.
+    pub fn get_current_values<T>(
+        &self,
+        nodes: &GraphNodeLookup<T>,
+    ) -> Result<GraphNodeLookup<NodeValue>, CoreError> {
+        let mut values = GraphNodeLookup::new();
+        for node in nodes.keys() {
+            let value = match node {
+                GraphNode::State(_) => {
+                    let state = self.states.get_state_untracked(node, node)?;
+                    NodeValue {
+                        value: state.value,
+                        came_from_default: state.came_from_default,
+                    }
+                }
+                GraphNode::String(_) => NodeValue {
+                    value: PropValue::String(self.get_string_value(node).into()),
+                    came_from_default: false,
+                },
+                _ => {
+                    return Err(CoreError::UnexpectedGraphNode {
+                        expected: "State",
+                        found: node,
+                    });
+                }
+            };
+            values.set_tag(node, value);
+        }
+        Ok(values)
+    }
+
+    /// Get the values of all `State` nodes that did not come from a default value,
+    /// along with the prop that the state belongs to.
+    ///
+    /// The value is retrieved without affecting the change tracking of the state.
+    pub fn get_non_default_states(&self) -> Result<Vec<(PropPointer, PropValue)>, CoreError> {
+        let mut states = Vec::new();
+        for state_node in self.states.get_state_nodes() {
+            let state = self.states.get_state_untracked(state_node, state_node)?;
+            if state.came_from_default {
+                continue;
+            }
+            // A state node is attached in the structure graph as the unique child of the leaf prop that owns it.
+            let prop_node = self
+                .document_structure
+                .borrow()
+                .get_structure_graph()
+                .get_unique_parent(state_node)
+                .ok_or(CoreError::StateWithoutProp(state_node))?;
+            states.push((self.get_prop_pointer(prop_node)?, state.value));
+        }
+        Ok(states)
+    }
+
+    /// Get all `String` nodes that have been changed since the document was initialized.
+    ///
+    /// Only strings that are direct children of a component (or of one of its attributes) are returned,
+    /// so a string that a component acquired by extending another component is reported only once.
+    /// Each string is returned with its component, location, and index among the children of that location.
+    pub fn get_modified_strings(
+        &self,
+    ) -> Result<Vec<(ComponentIdx, StringLocation, usize, String)>, CoreError> {
+        let document_structure = self.document_structure.borrow();
+        let structure_graph = document_structure.get_structure_graph();
+
+        let mut modified_strings = Vec::new();
+
+        for component_idx in document_structure.get_component_indices() {
+            let component = document_structure.get_component(component_idx)?;
+            // Error components are not added to the structure graph
+            if matches!(component.variant, ComponentEnum::_Error(_)) {
+                continue;
+            }
+
+            let children_node = structure_graph.get_component_children_virtual_node(component_idx);
+            let locations = std::iter::once((StringLocation::Children, children_node)).chain(
+                component
+                    .get_attribute_names()
+                    .into_iter()
+                    .zip(structure_graph.get_component_attributes(component_idx))
+                    .map(|(attr_name, attr_node)| {
+                        (StringLocation::Attribute(attr_name.to_string()), attr_node)
+                    }),
+            );
+
+            for (location, parent_node) in locations {
+                for (child_idx, child) in structure_graph
+                    .get_children(parent_node)
+                    .into_iter()
+                    .enumerate()
+                {
+                    if matches!(child, GraphNode::String(_))
+                        && document_structure.is_string_modified(child)
+                    {
+                        modified_strings.push((
+                            component_idx,
+                            location.clone(),
+                            child_idx,
+                            document_structure.get_string_value(child),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(modified_strings)
+    }
+
+    /// Get the `State` node holding the state of the prop named `prop_name` of the given component.
+    /// The prop is resolved, which creates its state node if the prop has state.
+    ///
+    /// Returns `Ok(None)` if there is no such prop or if the prop does not have state.
+    pub fn get_state_node_by_prop_name(
+        &self,
+        component_idx: ComponentIdx,
+        prop_name: &str,
+    ) -> Result<Option<GraphNode>, CoreError> {
+        let Some(local_prop_idx) = self
+            .document_structure
+            .borrow()
+            .get_component(component_idx)?
+            .get_local_prop_index_from_name(prop_name)
+        else {
+            return Ok(None);
+        };
+        let prop_node = self.prop_pointer_to_prop_node(PropPointer {
+            component_idx,
+            local_prop_idx,
+        });
+
+        self.resolve_prop(prop_node)?;
+
+        let leaf_node = self.document_structure.borrow().get_prop_leaf(prop_node);
+        Ok(matches!(leaf_node, GraphNode::State(_)).then_some(leaf_node))
+    }
+
+    /// Get the `String` node that is child number `child_idx` of `location` in the given component.
+    ///
+    /// Returns `None` if there is no such location or if the child is not a `String` node.
+    pub fn get_string_node(
+        &self,
+        component_idx: ComponentIdx,
+        location: &StringLocation,
+        child_idx: usize,
+    ) -> Option<GraphNode> {
+        let document_structure = self.document_structure.borrow();
+        let component = document_structure.get_component(component_idx).ok()?;
+        if matches!(component.variant, ComponentEnum::_Error(_)) {
+            return None;
+        }
+        let structure_graph = document_structure.get_structure_graph();
+
+        let parent_node = match location {
+            StringLocation::Children => {
+                structure_graph.get_component_children_virtual_node(component_idx)
+            }
+            StringLocation::Attribute(attr_name) => {
+                let local_attr_idx = component
+                    .get_attribute_names()
+                    .iter()
+                    .position(|&n| n == attr_name)?;
+                structure_graph.get_component_attributes(component_idx)[local_attr_idx]
+            }
+        };
+
+        structure_graph
+            .get_nth_child(parent_node, child_idx)
+            .filter(|child| matches!(child, GraphNode::String(_)))
+    }
+
+    /// Get the `String` node corresponding to child number `child_idx` of `location` as given in the source,
+    /// where `location` had `num_source_children` children in the source.
+    ///
+    /// Unlike [`DocumentModel::get_string_node`], `child_idx` does not count children that were prepended
+    /// to the component's children from extending another component.
+    ///
+    /// Returns `None` if there is no such location or if the child is not a `String` node.
+    pub fn get_source_string_node(
+        &self,
+        component_idx: ComponentIdx,
+        location: &StringLocation,
+        child_idx: usize,
+        num_source_children: usize,
+    ) -> Option<GraphNode> {
+        let num_children = {
+            let document_structure = self.document_structure.borrow();
+            let component = document_structure.get_component(component_idx).ok()?;
+            if matches!(component.variant, ComponentEnum::_Error(_)) {
+                return None;
+            }
+            let structure_graph = document_structure.get_structure_graph();
+            match location {
+                // The virtual node of the generated children, if any, follows the children from the source.
+                StringLocation::Children => {
+                    structure_graph
+                        .get_children(
+                            structure_graph.get_component_children_virtual_node(component_idx),
+                        )
+                        .len()
+                        - usize::from(
+                            document_structure
+                                .get_generated_children_virtual_node(component_idx)
+                                .is_some(),
+                        )
+                }
+                StringLocation::Attribute(_) => num_source_children,
+            }
+        };
+        // Any extra children were prepended, so the children from the source are the last children.
+        let num_prepended = num_children.checked_sub(num_source_children)?;
+
+        self.get_string_node(component_idx, location, num_prepended + child_idx)
+    }
+
+    /// Get the components that have `string_node` as one of their children, either directly
+    /// or because they extend a component that has `string_node` as a child.
+    pub fn get_components_with_string_child(&self, string_node: GraphNode) -> Vec<ComponentIdx> {
+        let document_structure = self.document_structure.borrow();
+        let structure_graph = document_structure.get_structure_graph();
+
+        let mut components = Vec::new();
+        let mut to_visit = structure_graph.get_parents(string_node);
+        while let Some(node) = to_visit.pop() {
+            match node {
+                GraphNode::Component(idx) => {
+                    let component_idx = ComponentIdx::new(idx);
+                    if !components.contains(&component_idx) {
+                        components.push(component_idx);
+                    }
+                }
+                GraphNode::Virtual(_) => to_visit.extend(structure_graph.get_parents(node)),
+                _ => {}
+            }
+        }
+        components
+    }
+}
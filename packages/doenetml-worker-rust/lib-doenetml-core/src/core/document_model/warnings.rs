@@ -0,0 +1,11 @@
+//! Functions for collecting the warnings about a document that are reported to its author,
+//! e.g., unrecognized attributes or attribute values that could not be parsed.
+
+use crate::{DocumentModel, dast::DastWarning};
+
+impl DocumentModel {
+    /// Get the warnings found while building the document's components.
+    pub fn get_warnings(&self) -> Vec<DastWarning> {
+        self.document_structure.borrow().get_warnings()
+    }
+}
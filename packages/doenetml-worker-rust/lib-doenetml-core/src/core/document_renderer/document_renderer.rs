@@ -1,4 +1,10 @@
-use crate::graph_node::{GraphNode, GraphNodeLookup};
+use std::collections::HashMap;
+
+use crate::{
+    components::types::ComponentIdx,
+    dast::DastAttribute,
+    graph_node::{GraphNode, GraphNodeLookup},
+};
 
 /// The `DocumentRenderer` is responsible for rendering the document tree into a flat DAST and
 /// delivering any rendered updates need as props change.
@@ -12,6 +18,10 @@ pub struct DocumentRenderer {
     // This graph node is used to figure out if any props have changed between renders.
     // It is a single fixed node and should always be related to the first entry of `self.queries`.
     pub(super) for_render_query_node: GraphNode,
+    /// The last rendered value of each attribute that is passed through to the renderer and contains refs,
+    /// keyed by component. The text of such an attribute changes with the referents,
+    /// so it is compared with the last rendered value when producing flat dast updates.
+    pub(super) rendered_ref_attributes: HashMap<ComponentIdx, HashMap<String, DastAttribute>>,
 }
 
 impl Default for DocumentRenderer {
@@ -25,6 +35,7 @@ impl DocumentRenderer {
         DocumentRenderer {
             in_render_tree: GraphNodeLookup::new(),
             for_render_query_node: GraphNode::Query(0), // the DataQuery::Null added in queries, above
+            rendered_ref_attributes: HashMap::new(),
         }
     }
 }
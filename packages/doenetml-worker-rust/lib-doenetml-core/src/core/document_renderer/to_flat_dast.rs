@@ -8,6 +8,7 @@ use crate::{
         types::PropPointer,
     },
     core::document_model::DocumentModel,
+    core::error::CoreError,
     dast::{
         DastAttribute, DastText, DastTextRefElementContent, ElementRefAnnotation,
         FlatDastElementUpdate, FlatDastRoot, ForRenderPropValue, ForRenderPropValueOrContent,
@@ -32,18 +33,44 @@ impl DocumentRenderer {
     /// and dast elements refer to their children via its *ComponentIdx* in that vector.
     ///
     /// Include warnings as a separate vector (errors are embedded in the tree as elements).
-    pub fn render_flat_dast(&mut self, document_model: &DocumentModel) -> FlatDastRoot {
-        self.mark_component_in_render_tree(ComponentIdx::new(0), document_model);
-        let elements = document_model
-            .get_component_indices()
-            .map(|comp_idx| self.component_to_flat_dast(comp_idx, document_model))
-            .collect();
-
-        FlatDastRoot {
+    pub fn render_flat_dast(
+        &mut self,
+        document_model: &DocumentModel,
+    ) -> Result<FlatDastRoot, CoreError> {
+        self.mark_component_in_render_tree(ComponentIdx::new(0), document_model)?;
+        // Elements are found by their index, so removed components are given empty placeholders.
+        let elements = (0..document_model.get_num_components())
+            .map(ComponentIdx::new)
+            .map(|comp_idx| {
+                if document_model.is_removed(comp_idx) {
+                    Ok(Self::removed_component_placeholder(comp_idx))
+                } else {
+                    self.component_to_flat_dast(comp_idx, document_model)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(FlatDastRoot {
             children: vec![FlatDastElementContent::new_original_element(0)],
             elements,
-            warnings: vec![],
+            warnings: document_model.get_warnings(),
             position: None,
+        })
+    }
+
+    /// The element rendered in place of a component that was removed from the document.
+    fn removed_component_placeholder(component_idx: ComponentIdx) -> FlatDastElement {
+        FlatDastElement {
+            name: "_error".to_string(),
+            attributes: HashMap::new(),
+            children: Vec::new(),
+            data: ElementData {
+                id: component_idx.as_usize(),
+                message: Some(String::new()),
+                ..Default::default()
+            },
+            position: None,
+            source_doc: None,
         }
     }
 
@@ -51,21 +78,22 @@ impl DocumentRenderer {
         &mut self,
         component_idx: ComponentIdx,
         document_model: &DocumentModel,
-    ) {
+    ) -> Result<(), CoreError> {
         let component_node = component_idx.as_graph_node();
         if let Some(true) = self.in_render_tree.get_tag(&component_node) {
-            return;
+            return Ok(());
         }
         self.in_render_tree.set_tag(component_node, true);
 
-        for (child_node, _) in self.get_rendered_child_nodes(component_idx, document_model) {
+        for (child_node, _) in self.get_rendered_child_nodes(component_idx, document_model)? {
             if let GraphNode::Component(_) = child_node {
-                self.mark_component_in_render_tree(child_node.into(), document_model);
+                self.mark_component_in_render_tree(child_node.try_into()?, document_model)?;
             }
         }
-        for child_node in self.get_children_from_for_render_props(component_idx, document_model) {
-            self.mark_component_in_render_tree(child_node.into(), document_model);
+        for child_node in self.get_children_from_for_render_props(component_idx, document_model)? {
+            self.mark_component_in_render_tree(child_node.try_into()?, document_model)?;
         }
+        Ok(())
     }
 
     /// Convert a component to a `FlatDastElement`.
@@ -73,10 +101,24 @@ impl DocumentRenderer {
         &mut self,
         component_idx: ComponentIdx,
         document_model: &DocumentModel,
-    ) -> FlatDastElement {
-        let child_nodes = self.get_rendered_child_nodes(component_idx, document_model);
+    ) -> Result<FlatDastElement, CoreError> {
+        let children = self.get_flat_dast_children(component_idx, document_model)?;
 
-        let children = child_nodes
+        Ok(FlatDastElement {
+            children,
+            ..self.component_to_flat_dast_no_children(component_idx, document_model)?
+        })
+    }
+
+    /// Get the rendered children of a component as `FlatDastElementContent`.
+    fn get_flat_dast_children(
+        &mut self,
+        component_idx: ComponentIdx,
+        document_model: &DocumentModel,
+    ) -> Result<Vec<FlatDastElementContent>, CoreError> {
+        let child_nodes = self.get_rendered_child_nodes(component_idx, document_model)?;
+
+        Ok(child_nodes
             .into_iter()
             .filter_map(|(child, annotation)| match child {
                 GraphNode::Component(idx) => {
@@ -87,12 +129,7 @@ impl DocumentRenderer {
                 )),
                 _ => None,
             })
-            .collect::<Vec<_>>();
-
-        FlatDastElement {
-            children,
-            ..self.component_to_flat_dast_no_children(component_idx, document_model)
-        }
+            .collect())
     }
 
     /// Get the vector of graph nodes corresponding to the rendered children of `component_idx`.
@@ -101,36 +138,34 @@ impl DocumentRenderer {
         &mut self,
         component_idx: ComponentIdx,
         document_model: &DocumentModel,
-    ) -> Vec<(GraphNode, ElementRefAnnotation)> {
-        let profs = document_model.get_provided_profiles(component_idx);
-        profs
-            .into_iter()
-            .find_map(|(profile, local_prop_idx)| {
-                if profile == PropProfile::RenderedChildren {
-                    let prop_pointer = PropPointer {
-                        component_idx,
-                        local_prop_idx,
-                    };
-                    let rendered_children_value =
-                        &self.get_prop_for_render(prop_pointer, document_model).value;
-                    match rendered_children_value {
-                        PropValue::AnnotatedContentRefs(content_refs) => {
-                            Some((**content_refs).clone())
-                        }
-                        _ => unreachable!(
-                            "RenderedChildren prop must return AnnotatedContentRefs, found {:?}",
-                            rendered_children_value
-                        ),
-                    }
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_default()
+    ) -> Result<Vec<(GraphNode, ElementRefAnnotation)>, CoreError> {
+        let profs = document_model.get_provided_profiles(component_idx)?;
+        let Some(local_prop_idx) = profs.into_iter().find_map(|(profile, local_prop_idx)| {
+            (profile == PropProfile::RenderedChildren).then_some(local_prop_idx)
+        }) else {
+            return Ok(Vec::new());
+        };
+
+        let prop_pointer = PropPointer {
+            component_idx,
+            local_prop_idx,
+        };
+        let rendered_children_value = self
+            .get_prop_for_render(prop_pointer, document_model)?
+            .value;
+        let content_refs = match rendered_children_value {
+            PropValue::AnnotatedContentRefs(content_refs) => (*content_refs).clone(),
+            _ => unreachable!(
+                "RenderedChildren prop must return AnnotatedContentRefs, found {:?}",
+                rendered_children_value
+            ),
+        };
+
+        Ok(content_refs
             .into_vec()
             .into_iter()
             .map(|(content_ref, annotation)| (content_ref.into(), annotation))
-            .collect()
+            .collect())
     }
 
     /// Get any nodes referenced in a `for_render` prop (e.g., because the prop returns `PropType::ComponentRefs` or similar).
@@ -138,38 +173,34 @@ impl DocumentRenderer {
         &mut self,
         component_idx: ComponentIdx,
         document_model: &DocumentModel,
-    ) -> Vec<GraphNode> {
-        document_model
-            .get_for_render_prop_pointers(component_idx)
-            .flat_map(|prop_pointer| {
-                if !self.prop_may_contain_children(prop_pointer, document_model) {
-                    return vec![];
-                }
-                let prop = self.get_prop_for_render(prop_pointer, document_model);
-                match prop.value {
-                    PropValue::ComponentRefs(refs) => {
-                        refs.iter().map(|c| c.as_graph_node()).collect()
-                    }
-                    PropValue::ComponentRef(c) => {
-                        c.into_iter().map(|c| c.as_graph_node()).collect()
+    ) -> Result<Vec<GraphNode>, CoreError> {
+        let mut children = Vec::new();
+        for prop_pointer in document_model.get_for_render_prop_pointers(component_idx)? {
+            if !self.prop_may_contain_children(prop_pointer, document_model)? {
+                continue;
+            }
+            let prop = self.get_prop_for_render(prop_pointer, document_model)?;
+            let prop_children: Vec<GraphNode> = match prop.value {
+                PropValue::ComponentRefs(refs) => refs.iter().map(|c| c.as_graph_node()).collect(),
+                PropValue::ComponentRef(c) => c.into_iter().map(|c| c.as_graph_node()).collect(),
+                PropValue::ContentRef(c) => match c {
+                    ContentRef::Component(c) => {
+                        vec![c.as_graph_node()]
                     }
-                    PropValue::ContentRef(c) => match c {
-                        ContentRef::Component(c) => {
-                            vec![c.as_graph_node()]
-                        }
-                        ContentRef::String(_) => vec![],
-                    },
-                    PropValue::ContentRefs(refs) => refs
-                        .iter()
-                        .flat_map(|c| match c {
-                            ContentRef::Component(c) => Some(c.as_graph_node()),
-                            ContentRef::String(_) => None,
-                        })
-                        .collect(),
-                    _ => vec![],
-                }
-            })
-            .collect()
+                    ContentRef::String(_) => vec![],
+                },
+                PropValue::ContentRefs(refs) => refs
+                    .iter()
+                    .flat_map(|c| match c {
+                        ContentRef::Component(c) => Some(c.as_graph_node()),
+                        ContentRef::String(_) => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            children.extend(prop_children);
+        }
+        Ok(children)
     }
 
     /// Returns whether a prop may contain references to components.
@@ -177,16 +208,16 @@ impl DocumentRenderer {
         &self,
         prop_pointer: PropPointer,
         document_model: &DocumentModel,
-    ) -> bool {
+    ) -> Result<bool, CoreError> {
         let prop = document_model
-            .get_prop_definition(document_model.prop_pointer_to_prop_node(prop_pointer));
-        matches!(
+            .get_prop_definition(document_model.prop_pointer_to_prop_node(prop_pointer))?;
+        Ok(matches!(
             prop.variant,
             PropValueType::ComponentRef
                 | PropValueType::ComponentRefs
                 | PropValueType::ContentRef
                 | PropValueType::ContentRefs
-        )
+        ))
     }
 
     /// Convert a component to a `FlatDastElement` without its children. This is can be used
@@ -195,67 +226,38 @@ impl DocumentRenderer {
         &mut self,
         component_idx: ComponentIdx,
         document_model: &DocumentModel,
-    ) -> FlatDastElement {
+    ) -> Result<FlatDastElement, CoreError> {
         // For efficiency, calculate rendered props only if component_idx is in the render tree
-        let rendered_props = self
+        let in_render_tree = self
             .in_render_tree
             .get_tag(&component_idx.as_graph_node())
             .copied()
-            .and_then(|in_tree| {
-                in_tree.then(|| {
-                    self.get_rendered_props(
-                        component_idx,
-                        SerializeCondition::Always,
-                        document_model,
-                    )
-                })
-            });
-
-        let component = document_model.get_component(component_idx);
-        let message = if let ComponentEnum::_Error(error) = &component.variant {
-            Some(error.message.clone())
+            .unwrap_or(false);
+        let rendered_props = if in_render_tree {
+            Some(self.get_rendered_props(
+                component_idx,
+                SerializeCondition::Always,
+                document_model,
+            )?)
         } else {
             None
         };
 
-        // Only the unrecognized attributes remain ont he actual element. Convert them to a flat dast.
-        let attributes = component.get_unrecognized_attributes();
-        let attributes = attributes
-            .iter()
-            .map(|(key, flat_attr)| {
-                (
-                    key.to_string(),
-                    DastAttribute {
-                        name: flat_attr.name.clone(),
-                        children: flat_attr
-                            .children
-                            .iter()
-                            .filter_map(|c| match c {
-                                UntaggedContent::Text(s) => {
-                                    Some(DastTextRefElementContent::Text(DastText {
-                                        value: s.clone(),
-                                        data: None,
-                                        position: None,
-                                        source_doc: None,
-                                    }))
-                                }
-                                // Refs have been expanded during processing to actual components. Since XML is not allowed
-                                // in a FlatDast attribute, we ignore them.
-                                // TODO: should we put in the value of the ref here?
-                                UntaggedContent::Ref(_) => None,
-                            })
-                            .collect(),
-                        position: flat_attr.position.clone(),
-                        source_doc: flat_attr.source_doc,
-                    },
-                )
-            })
-            .collect::<HashMap<_, _>>();
+        let component = document_model.get_component(component_idx)?;
+        let (message, path_part_position) = if let ComponentEnum::_Error(error) = &component.variant
+        {
+            (
+                Some(error.message.clone()),
+                error.path_part_position.clone(),
+            )
+        } else {
+            (None, None)
+        };
 
-        FlatDastElement {
+        let attributes = self.get_rendered_attributes(component_idx, document_model)?;
+
+        Ok(FlatDastElement {
             name: component.get_component_type().to_string(),
-            // TODO: We should return some version of component.get_unrecognized_attributes()
-            // However, those attributes might not be expandable if they contain an expanded refs.
             attributes,
             children: Vec::new(),
             data: ElementData {
@@ -269,10 +271,117 @@ impl DocumentRenderer {
                 ),
                 props: rendered_props,
                 message,
+                path_part_position,
             },
             position: component.common.position,
             source_doc: None,
+        })
+    }
+
+    /// Get the attributes of `component_idx` that are passed through to the renderer.
+    ///
+    /// Only the unrecognized attributes remain on the actual element. They are passed through to the renderer
+    /// (e.g., `class` or `data-*` attributes), with their content evaluated to text.
+    /// The values of the attributes that contain refs are remembered, so that changes to them
+    /// are included in the flat dast updates (see [`DocumentRenderer::get_flat_dast_updates`]).
+    fn get_rendered_attributes(
+        &mut self,
+        component_idx: ComponentIdx,
+        document_model: &DocumentModel,
+    ) -> Result<HashMap<String, DastAttribute>, CoreError> {
+        let component = document_model.get_component(component_idx)?;
+        let mut attributes = HashMap::new();
+        let mut ref_attributes = HashMap::new();
+        for (key, flat_attr) in component.get_unrecognized_attributes() {
+            let children = if flat_attr.children.is_empty() {
+                Vec::new()
+            } else {
+                vec![DastTextRefElementContent::Text(DastText {
+                    value: self.attribute_content_to_text(&flat_attr.children, document_model)?,
+                    data: None,
+                    position: None,
+                    source_doc: None,
+                })]
+            };
+            let attribute = DastAttribute {
+                name: flat_attr.name.clone(),
+                children,
+                position: flat_attr.position.clone(),
+                source_doc: flat_attr.source_doc,
+            };
+            if flat_attr
+                .children
+                .iter()
+                .any(|child| matches!(child, UntaggedContent::Ref(_)))
+            {
+                ref_attributes.insert(key.to_string(), attribute.clone());
+            }
+            attributes.insert(key.to_string(), attribute);
         }
+
+        if ref_attributes.is_empty() {
+            self.rendered_ref_attributes.remove(&component_idx);
+        } else {
+            self.rendered_ref_attributes
+                .insert(component_idx, ref_attributes);
+        }
+        Ok(attributes)
+    }
+
+    /// Get the attributes containing refs whose values changed since `component_idx` was last rendered.
+    fn get_changed_ref_attributes(
+        &mut self,
+        component_idx: ComponentIdx,
+        document_model: &DocumentModel,
+    ) -> Result<HashMap<String, DastAttribute>, CoreError> {
+        let previous = self
+            .rendered_ref_attributes
+            .get(&component_idx)
+            .cloned()
+            .unwrap_or_default();
+        let attributes = self.get_rendered_attributes(component_idx, document_model)?;
+        Ok(attributes
+            .into_iter()
+            .filter(|(key, attribute)| {
+                previous
+                    .get(key)
+                    .is_some_and(|previous| attribute_text(previous) != attribute_text(attribute))
+            })
+            .collect())
+    }
+
+    /// Evaluate the content of an attribute to text.
+    ///
+    /// Refs in attributes have been expanded to components during processing. Since XML is not allowed
+    /// in a flat dast attribute, a ref is replaced by the text value of the component
+    /// (i.e., the value of its prop matching the `String` profile).
+    /// Refs to components without such a prop contribute no text.
+    fn attribute_content_to_text(
+        &mut self,
+        content: &[UntaggedContent],
+        document_model: &DocumentModel,
+    ) -> Result<String, CoreError> {
+        let mut text = String::new();
+        for child in content {
+            match child {
+                UntaggedContent::Text(s) => text.push_str(s),
+                UntaggedContent::Ref(idx) => {
+                    let Some(prop_pointer) =
+                        document_model.get_component_prop_by_profile(*idx, &[PropProfile::String])
+                    else {
+                        continue;
+                    };
+                    let prop = self.get_prop_for_render_untracked(
+                        document_model.prop_pointer_to_prop_node(prop_pointer),
+                        document_model,
+                    )?;
+                    if let PropValue::String(s) = prop.value {
+                        text.push_str(&s);
+                    }
+                }
+            }
+        }
+        Ok(text)
     }
 
     /// Calculate the values of the `for_render` props of `component_idx`.
@@ -285,25 +394,26 @@ impl DocumentRenderer {
         component_idx: ComponentIdx,
         serialize_condition: SerializeCondition,
         document_model: &DocumentModel,
-    ) -> ForRenderProps {
-        let rendered_prop_pointers = document_model.get_for_render_prop_pointers(component_idx);
-
-        let rendered_prop_value_vec = rendered_prop_pointers
-            .filter_map(|prop_pointer| {
-                let prop = self.get_prop_for_render(prop_pointer, document_model);
-                let should_serialize =
-                    prop.changed || matches!(serialize_condition, SerializeCondition::Always);
-                if should_serialize {
-                    let prop_value = prop.value;
-                    let prop_name = document_model.get_prop_name(prop_pointer);
-                    Some(self.prepare_prop_value_for_render(prop_name, prop_value, document_model))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+    ) -> Result<ForRenderProps, CoreError> {
+        let rendered_prop_pointers = document_model.get_for_render_prop_pointers(component_idx)?;
 
-        ForRenderProps(rendered_prop_value_vec)
+        let mut rendered_prop_value_vec = Vec::new();
+        for prop_pointer in rendered_prop_pointers {
+            let prop = self.get_prop_for_render(prop_pointer, document_model)?;
+            let should_serialize =
+                prop.changed || matches!(serialize_condition, SerializeCondition::Always);
+            if should_serialize {
+                let prop_value = prop.value;
+                let prop_name = document_model.get_prop_name(prop_pointer)?;
+                rendered_prop_value_vec.push(self.prepare_prop_value_for_render(
+                    prop_name,
+                    prop_value,
+                    document_model,
+                ));
+            }
+        }
+
+        Ok(ForRenderProps(rendered_prop_value_vec))
     }
 
     /// Turns a `PropValue` into a `ForRenderPropValueOrContent` that is ready for serialization.
@@ -351,12 +461,14 @@ impl DocumentRenderer {
     }
 
     /// Output updates for any elements with changed for_render props
+    /// and for any elements whose attributes containing refs changed.
     pub fn get_flat_dast_updates(
         &mut self,
         changed_components: Vec<ComponentIdx>,
         document_model: &DocumentModel,
-    ) -> HashMap<ComponentIdx, FlatDastElementUpdate> {
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, CoreError> {
         let mut flat_dast_updates: HashMap<ComponentIdx, FlatDastElementUpdate> = HashMap::new();
+        self.add_changed_ref_attributes(&mut flat_dast_updates, document_model)?;
 
         for component_idx in changed_components {
             let component_node = component_idx.as_graph_node();
@@ -365,21 +477,80 @@ impl DocumentRenderer {
                     component_idx,
                     SerializeCondition::IfChanged,
                     document_model,
-                );
+                )?;
 
                 if !rendered_props.is_empty() {
-                    flat_dast_updates.insert(
-                        component_idx,
-                        FlatDastElementUpdate {
-                            new_children: None,
-                            changed_state: Some(rendered_props),
-                        },
-                    );
+                    flat_dast_updates
+                        .entry(component_idx)
+                        .or_default()
+                        .changed_state = Some(rendered_props);
                 }
             }
         }
 
-        flat_dast_updates
+        Ok(flat_dast_updates)
+    }
+
+    /// Add the attributes containing refs whose values changed to `flat_dast_updates`.
+    ///
+    /// Components that are not being rendered are skipped, and components that were removed are forgotten.
+    fn add_changed_ref_attributes(
+        &mut self,
+        flat_dast_updates: &mut HashMap<ComponentIdx, FlatDastElementUpdate>,
+        document_model: &DocumentModel,
+    ) -> Result<(), CoreError> {
+        self.rendered_ref_attributes
+            .retain(|component_idx, _| !document_model.is_removed(*component_idx));
+        let components = self
+            .rendered_ref_attributes
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for component_idx in components {
+            if self.in_render_tree.get_tag(&component_idx.as_graph_node()) != Some(&true) {
+                continue;
+            }
+            let changed_attributes =
+                self.get_changed_ref_attributes(component_idx, document_model)?;
+            if !changed_attributes.is_empty() {
+                flat_dast_updates
+                    .entry(component_idx)
+                    .or_default()
+                    .changed_attributes = Some(changed_attributes);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the rendered children of each component of `components` to `flat_dast_updates`,
+    /// for use when the children of the components may have changed.
+    ///
+    /// Components that are not being rendered are skipped.
+    pub fn add_flat_dast_children_updates(
+        &mut self,
+        flat_dast_updates: &mut HashMap<ComponentIdx, FlatDastElementUpdate>,
+        components: Vec<ComponentIdx>,
+        document_model: &DocumentModel,
+    ) -> Result<(), CoreError> {
+        for component_idx in components {
+            let component_node = component_idx.as_graph_node();
+            if let Some(true) = self.in_render_tree.get_tag(&component_node) {
+                // New children are rendered as well.
+                for (child_node, _) in
+                    self.get_rendered_child_nodes(component_idx, document_model)?
+                {
+                    if let GraphNode::Component(_) = child_node {
+                        self.mark_component_in_render_tree(child_node.try_into()?, document_model)?;
+                    }
+                }
+                let children = self.get_flat_dast_children(component_idx, document_model)?;
+                flat_dast_updates
+                    .entry(component_idx)
+                    .or_default()
+                    .new_children = Some(children);
+            }
+        }
+        Ok(())
     }
 
     /// Get the value of a prop for rendering. If the prop is stale or not resolved,
@@ -390,7 +561,7 @@ impl DocumentRenderer {
         &mut self,
         prop_pointer: PropPointer,
         document_model: &DocumentModel,
-    ) -> PropWithMeta {
+    ) -> Result<PropWithMeta, CoreError> {
         document_model.get_prop(
             document_model.prop_pointer_to_prop_node(prop_pointer),
             self.for_render_query_node,
@@ -405,11 +576,19 @@ impl DocumentRenderer {
         &mut self,
         prop_node: GraphNode,
         document_model: &DocumentModel,
-    ) -> PropWithMeta {
+    ) -> Result<PropWithMeta, CoreError> {
         document_model.get_prop_untracked(prop_node, self.for_render_query_node)
     }
 }
 
+/// The text of an attribute rendered by [`DocumentRenderer::get_rendered_attributes`].
+fn attribute_text(attribute: &DastAttribute) -> Option<&str> {
+    match attribute.children.first() {
+        Some(DastTextRefElementContent::Text(text)) => Some(&text.value),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 #[path = "to_flat_dast.test.rs"]
 mod test;
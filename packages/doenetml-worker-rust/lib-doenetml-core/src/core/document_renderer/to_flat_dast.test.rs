@@ -24,7 +24,8 @@ fn test_can_make_flat_dast_for_component() {
     //let flat_dast = core.component_to_flat_dast2(&core.components[0]);
     let flat_dast = core
         .document_renderer
-        .component_to_flat_dast(0.into(), &core.document_model);
+        .component_to_flat_dast(0.into(), &core.document_model)
+        .unwrap();
     dbg!(flat_dast);
 }
 
@@ -60,13 +61,14 @@ fn test_components_referenced_in_for_render_props_are_marked_as_in_render_tree()
 
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
-    core.to_flat_dast();
+    core.to_flat_dast().unwrap();
 
     let title_idx = ComponentIdx::from(2);
     let title_node = GraphNode::Component(title_idx.as_usize());
     assert_eq!(
         core.document_model
             .get_component(title_idx)
+            .unwrap()
             .get_component_type(),
         "title"
     );
@@ -89,7 +91,7 @@ fn test_extending_nodes_get_marked_as_duplicates() {
 
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
-    let flat_root = core.to_flat_dast();
+    let flat_root = core.to_flat_dast().unwrap();
 
     let p1_idx = ComponentIdx::from(1);
     let p2_idx = ComponentIdx::from(3);
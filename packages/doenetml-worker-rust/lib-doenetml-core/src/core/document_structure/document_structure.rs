@@ -3,17 +3,22 @@
 //! This module holds information about the structure of the document: the components, props,
 //! and the (structural) relations to each other.
 
-use std::borrow;
+use std::{
+    borrow,
+    collections::{BTreeMap, HashMap, HashSet},
+};
 
 use typed_index_collections::TiVec;
 
 use crate::{
-    component_builder::ComponentBuilder,
+    component_builder::{AttributePosition, ComponentBuilder},
     components::{
-        Component, ComponentAttributes, ComponentCommon,
-        types::{ComponentIdx, PropDefinitionIdx, PropPointer},
+        Component, ComponentAttributes, ComponentCommon, ComponentCommonData,
+        types::{AttributeName, ComponentIdx, LocalPropIdx, PropDefinitionIdx, PropPointer},
     },
-    dast::ElementRefAnnotation,
+    dast::{DastWarning, ElementRefAnnotation, ref_resolve::PathIndex},
+    error::CoreError,
+    graph::directed_graph::Taggable,
     graph_node::{GraphNode, GraphNodeLookup, StructureGraph},
     props::{PropDefinition, PropProfile, StringCache, cache::PropWithMeta},
 };
@@ -41,6 +46,19 @@ pub struct DocumentStructure {
     /// Stores whether a particular virtual node was created to house the children coming from another component
     /// because it was `extend`ing another component.
     pub children_came_from_extending_marker: GraphNodeLookup<bool>,
+    /// The element props that have been added to components (see `ComponentBuilder`).
+    array_element_props: HashMap<(PropDefinitionIdx, PathIndex), LocalPropIdx>,
+    /// The virtual node that houses the children generated for a component after the document was initialized.
+    generated_children_virtual_nodes: HashMap<ComponentIdx, GraphNode>,
+    /// The location in the source of each attribute that was given to a component and recognized by it.
+    attribute_positions: HashMap<(ComponentIdx, AttributeName), AttributePosition>,
+    /// Warnings about each component found while building it, e.g., unrecognized attributes.
+    component_warnings: BTreeMap<ComponentIdx, Vec<DastWarning>>,
+    /// Warnings about names that are never referenced.
+    unused_name_warnings: Vec<DastWarning>,
+    /// The components that were removed from the document (see [`DocumentStructure::remove_components`]).
+    /// Their indices are not reused.
+    removed_components: HashSet<ComponentIdx>,
 }
 
 impl DocumentStructure {
@@ -53,9 +71,16 @@ impl DocumentStructure {
             virtual_node_count: 0,
             prop_definitions: TiVec::new(),
             children_came_from_extending_marker: GraphNodeLookup::new(),
+            array_element_props: HashMap::new(),
+            generated_children_virtual_nodes: HashMap::new(),
+            attribute_positions: HashMap::new(),
+            component_warnings: BTreeMap::new(),
+            unused_name_warnings: Vec::new(),
+            removed_components: HashSet::new(),
         }
     }
 
+    /// The number of components that have been created, including those that were removed.
     pub fn _get_num_components(&self) -> usize {
         self.components.len()
     }
@@ -68,6 +93,165 @@ impl DocumentStructure {
         self.virtual_node_count = builder.virtual_node_count;
         self.prop_definitions = builder.props;
         self.children_came_from_extending_marker = builder.children_came_from_extending_marker;
+        self.array_element_props = builder.array_element_props;
+        self.generated_children_virtual_nodes = builder.generated_children_virtual_nodes;
+        self.attribute_positions = builder.attribute_positions;
+        self.component_warnings = builder.component_warnings;
+        self.unused_name_warnings = builder.unused_name_warnings;
+    }
+
+    /// Move the values of Self into a `ComponentBuilder` so that more components can be added.
+    /// Self is left empty until it is initialized again with [`DocumentStructure::init_from_builder`].
+    pub fn take_into_builder(&mut self) -> ComponentBuilder {
+        let mut builder = ComponentBuilder::new();
+        builder.structure_graph = std::mem::take(&mut self.structure_graph);
+        builder.components = std::mem::take(&mut self.components);
+        builder.strings = std::mem::take(&mut self.strings);
+        builder.virtual_node_count = self.virtual_node_count;
+        builder.props = std::mem::take(&mut self.prop_definitions);
+        builder.children_came_from_extending_marker =
+            std::mem::take(&mut self.children_came_from_extending_marker);
+        builder.array_element_props = std::mem::take(&mut self.array_element_props);
+        builder.generated_children_virtual_nodes =
+            std::mem::take(&mut self.generated_children_virtual_nodes);
+        builder.attribute_positions = std::mem::take(&mut self.attribute_positions);
+        builder.component_warnings = std::mem::take(&mut self.component_warnings);
+        builder.unused_name_warnings = std::mem::take(&mut self.unused_name_warnings);
+        builder
+    }
+
+    /// The virtual node that houses the children generated for `component_idx`,
+    /// or `None` if no children were generated for it.
+    pub fn get_generated_children_virtual_node(
+        &self,
+        component_idx: ComponentIdx,
+    ) -> Option<GraphNode> {
+        self.generated_children_virtual_nodes
+            .get(&component_idx)
+            .copied()
+    }
+
+    /// The warnings found while building the components, in the order of the components,
+    /// followed by the warnings about names that are never referenced.
+    pub fn get_warnings(&self) -> Vec<DastWarning> {
+        self.component_warnings
+            .values()
+            .flatten()
+            .chain(self.unused_name_warnings.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// The location in the source of the attribute `attribute_name` of `component_idx`,
+    /// or `None` if the attribute was not given to the component.
+    pub fn get_attribute_position(
+        &self,
+        component_idx: ComponentIdx,
+        attribute_name: AttributeName,
+    ) -> Option<&AttributePosition> {
+        self.attribute_positions
+            .get(&(component_idx, attribute_name))
+    }
+
+    /// Remove `components` from the document, along with the nodes of the structure graph that belong only to them
+    /// or to the `extra_nodes` (e.g., strings that were children of a component that is not removed).
+    /// A node belongs only to the removed nodes if each of its parents does.
+    ///
+    /// The removed components are replaced by placeholders so that the indices of the other components do not change,
+    /// and they are no longer found by [`DocumentStructure::get_component`] or [`DocumentStructure::get_component_indices`].
+    ///
+    /// Returns the nodes that were removed from the structure graph.
+    pub fn remove_components(
+        &mut self,
+        components: &[ComponentIdx],
+        extra_nodes: &[GraphNode],
+    ) -> Vec<GraphNode> {
+        let seeds = components
+            .iter()
+            .map(|component_idx| component_idx.as_graph_node())
+            .chain(extra_nodes.iter().copied())
+            .filter(|node| self.structure_graph.contains_node(node))
+            .collect::<Vec<_>>();
+        let mut is_seed = GraphNodeLookup::new();
+        for node in seeds.iter() {
+            is_seed.set_tag(*node, true);
+        }
+
+        // Every node reachable from the seeds could belong to them...
+        let mut is_removed = is_seed.clone();
+        let mut stack = seeds;
+        while let Some(node) = stack.pop() {
+            for child in self.structure_graph.get_children(node) {
+                if is_removed.get_tag(&child).is_none() {
+                    is_removed.set_tag(child, true);
+                    stack.push(child);
+                }
+            }
+        }
+        // ...unless it can also be reached from a node that is kept, e.g., the children of a component
+        // that a removed component extends.
+        loop {
+            let kept = is_removed
+                .keys()
+                .filter(|node| {
+                    is_seed.get_tag(node).is_none()
+                        && self
+                            .structure_graph
+                            .get_parents(node)
+                            .iter()
+                            .any(|parent| is_removed.get_tag(parent).is_none())
+                })
+                .collect::<Vec<_>>();
+            if kept.is_empty() {
+                break;
+            }
+            for node in kept.iter() {
+                is_removed.remove_tag(node);
+            }
+        }
+
+        let removed = is_removed.keys().collect::<Vec<_>>();
+        for node in removed.iter() {
+            self.structure_graph.remove_node(node);
+            self.children_came_from_extending_marker.remove_tag(node);
+            if matches!(node, GraphNode::String(_)) {
+                self.strings.remove_string(node);
+            }
+        }
+        self.array_element_props.retain(|(array_prop_idx, _), _| {
+            is_removed
+                .get_tag(&GraphNode::from(*array_prop_idx))
+                .is_none()
+        });
+
+        // Components created while building (e.g., implicit children) are removed along with the components they belong to.
+        let removed_components = components
+            .iter()
+            .copied()
+            .chain(removed.iter().filter_map(|node| match node {
+                GraphNode::Component(idx) => Some(ComponentIdx::new(*idx)),
+                _ => None,
+            }))
+            .collect::<Vec<_>>();
+        for component_idx in removed_components {
+            self.components[component_idx] = Component::new_error(
+                String::new(),
+                None,
+                ComponentCommonData {
+                    idx: component_idx,
+                    parent: None,
+                    position: None,
+                    unrecognized_attributes: HashMap::new(),
+                },
+            );
+            self.removed_components.insert(component_idx);
+            self.generated_children_virtual_nodes.remove(&component_idx);
+            self.component_warnings.remove(&component_idx);
+        }
+        self.attribute_positions
+            .retain(|(component_idx, _), _| !self.removed_components.contains(component_idx));
+
+        removed
     }
 
     /// Add an edge to the structure graph.
@@ -126,9 +310,18 @@ impl DocumentStructure {
 
     /// Get a `PropDefinition` corresponding to `pointer`. The type of `pointer` can be `GraphNode`
     /// or `PropDefinitionIdx`.
-    pub fn get_prop_definition<T: Into<GraphNode>>(&self, pointer: T) -> &PropDefinition {
-        let prop_idx: PropDefinitionIdx = pointer.into().into();
-        &self.prop_definitions[prop_idx]
+    pub fn get_prop_definition<T: Into<GraphNode>>(
+        &self,
+        pointer: T,
+    ) -> Result<&PropDefinition, CoreError> {
+        let prop_node: GraphNode = pointer.into();
+        let prop_idx = PropDefinitionIdx::try_from(prop_node)?;
+        self.prop_definitions
+            .get(prop_idx)
+            .ok_or(CoreError::UnexpectedGraphNode {
+                expected: "Prop",
+                found: prop_node,
+            })
     }
 
     /// Returns the _true_ parent of a component coming from the structure of the DAST.
@@ -155,15 +348,28 @@ impl DocumentStructure {
     }
 
     /// Get the requested component
-    pub fn get_component<T: Into<GraphNode>>(&self, pointer: T) -> &Component {
-        let component_idx: ComponentIdx = pointer.into().into();
-        &self.components[component_idx]
+    pub fn get_component<T: Into<GraphNode>>(&self, pointer: T) -> Result<&Component, CoreError> {
+        let component_idx = ComponentIdx::try_from(pointer.into())?;
+        if self.removed_components.contains(&component_idx) {
+            return Err(CoreError::ComponentNotFound(component_idx.as_usize()));
+        }
+        self.components
+            .get(component_idx)
+            .ok_or(CoreError::ComponentNotFound(component_idx.as_usize()))
+    }
+
+    /// Returns `true` if the component was removed from the document.
+    pub fn is_removed(&self, component_idx: ComponentIdx) -> bool {
+        self.removed_components.contains(&component_idx)
     }
 
     /// Returns a vector of all the _content_ children of a component. That is,
     /// any virtual nodes that are listed in the children are expanded down to their content.
-    pub fn get_component_content_children<T: Into<GraphNode>>(&self, pointer: T) -> Vec<GraphNode> {
-        let component_idx: ComponentIdx = pointer.into().into();
+    pub fn get_component_content_children<T: Into<GraphNode>>(
+        &self,
+        pointer: T,
+    ) -> Result<Vec<GraphNode>, CoreError> {
+        let component_idx = self.get_component(pointer)?.get_idx();
         let children_virtual_node = self
             .structure_graph
             .get_component_children_virtual_node(component_idx);
@@ -174,7 +380,7 @@ impl DocumentStructure {
             .structure_graph
             .get_content_children(children_virtual_node)
             .collect::<Vec<_>>();
-        content_children
+        Ok(content_children)
     }
 
     /// Returns a vector of all the _content_ children of a component. That is,
@@ -184,8 +390,8 @@ impl DocumentStructure {
     pub fn get_component_content_children_annotated<T: Into<GraphNode>>(
         &self,
         pointer: T,
-    ) -> Vec<(GraphNode, ElementRefAnnotation)> {
-        let component_idx: ComponentIdx = pointer.into().into();
+    ) -> Result<Vec<(GraphNode, ElementRefAnnotation)>, CoreError> {
+        let component_idx = self.get_component(pointer)?.get_idx();
         let children_virtual_node = self
             .structure_graph
             .get_component_children_virtual_node(component_idx);
@@ -206,7 +412,7 @@ impl DocumentStructure {
                 }
             })
             .collect::<Vec<_>>();
-        content_children
+        Ok(content_children)
     }
 
     pub fn get_attribute_content_children<T: Into<GraphNode>>(
@@ -231,9 +437,13 @@ impl DocumentStructure {
             })
     }
 
-    /// Returns an iterator over all component indices
+    /// Returns an iterator over the indices of all components that have not been removed
     pub fn get_component_indices(&self) -> impl Iterator<Item = ComponentIdx> + use<> {
-        (0..self.components.len()).map(ComponentIdx::new)
+        (0..self.components.len())
+            .map(ComponentIdx::new)
+            .filter(|component_idx| !self.removed_components.contains(component_idx))
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     /// Get the value of a string node. `origin` affects the metadata returned,
@@ -252,6 +462,16 @@ impl DocumentStructure {
         self.strings.get_string_value(string_node)
     }
 
+    /// Returns `true` if the value of a string node has been changed since the document was initialized.
+    pub fn is_string_modified<A: borrow::Borrow<GraphNode>>(&self, string_node: A) -> bool {
+        self.strings.is_modified(string_node)
+    }
+
+    /// Stop tracking whether string nodes have changed since they were last requested by `origin`.
+    pub fn remove_string_origin<A: borrow::Borrow<GraphNode>>(&self, origin: A) {
+        self.strings.remove_origin(origin)
+    }
+
     /// Set the value of a string node.
     /// The store tracks and reports if the value has changed since the last time it was queried.
     pub fn set_string<A: borrow::Borrow<GraphNode>>(&self, string_node: A, s: String) {
@@ -0,0 +1,76 @@
+//! Errors that `Core` reports instead of aborting when it is given a malformed document or a bad action.
+
+use thiserror::Error;
+
+use super::graph_node::GraphNode;
+use crate::dast::ref_resolve::ResolutionError;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CoreError {
+    /// A `GraphNode` of one kind was found where a different kind was expected.
+    /// This indicates an inconsistency in the structure or dependency graph.
+    #[error("Expected a `GraphNode::{expected}`, found `{found:?}`")]
+    UnexpectedGraphNode {
+        expected: &'static str,
+        found: GraphNode,
+    },
+    /// An operation that needs the document was attempted before `Core` was initialized from one.
+    #[error("Cannot {0} before core is initialized")]
+    NotInitialized(&'static str),
+    /// An index that does not refer to a component of the document.
+    #[error("There is no component with index {0}")]
+    ComponentNotFound(usize),
+    /// A data query asked for an attribute that the component does not have.
+    #[error("Cannot find attribute `{attribute_name}` on component `{component_type}`")]
+    AttributeNotFound {
+        attribute_name: String,
+        component_type: String,
+    },
+    /// A reference to a prop that the component does not have (or that is not public).
+    #[error("Cannot find public prop `{prop_name}` on component `{component_type}`")]
+    PropNotFound {
+        prop_name: String,
+        component_type: String,
+    },
+    /// A `PropPointer` whose local index is beyond the props of the component.
+    #[error("Component {component_idx} has no prop with local index {local_prop_idx}")]
+    LocalPropNotFound {
+        component_idx: usize,
+        local_prop_idx: usize,
+    },
+    /// A reference path that resolves to a component but cannot be mapped onto one of its props.
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    /// A value that cannot be stored in the given `State` or `String` node.
+    #[error("Cannot set the value of `{node:?}` to {value}")]
+    InvalidValue { node: GraphNode, value: String },
+    /// Props that depend on themselves, so their values cannot be calculated.
+    #[error("Circular dependency among props {0:?}")]
+    CircularDependency(Vec<GraphNode>),
+    /// A reference that could not be resolved.
+    #[error(transparent)]
+    Resolution(#[from] ResolutionError),
+    /// An action that would change a document whose `read_only` flag is set.
+    #[error("Cannot change a read-only document")]
+    ReadOnly,
+    /// A negative, infinite or NaN number of milliseconds used to advance the document's clock or schedule an action.
+    #[error("Invalid time of {0} milliseconds")]
+    InvalidTime(f64),
+    /// More actions were due in one advance of the document's clock than can be sent at once.
+    #[error("More than {0} scheduled actions were due in one advance of the clock")]
+    TooManyScheduledActions(usize),
+    /// An action that triggers an action of the same component, directly or through the actions of other components.
+    #[error("The action of component {0} triggers itself")]
+    ActionLoop(usize),
+    /// A `State` node whose value was read before it was set.
+    /// This indicates an inconsistency in the dependency graph.
+    #[error("The state `{0:?}` has not been set")]
+    StateNotSet(GraphNode),
+    /// A `State` node that is not attached to the prop that owns it.
+    /// This indicates an inconsistency in the structure graph.
+    #[error("The state `{0:?}` does not belong to a prop")]
+    StateWithoutProp(GraphNode),
+    /// An action that a component could not process.
+    #[error("{0}")]
+    InvalidAction(String),
+}
@@ -2,7 +2,8 @@ use std::rc::Rc;
 
 use crate::{
     components::prelude::*,
-    props::ContentFilter,
+    graph_node::GraphNode,
+    props::{ApplyTest, ContentFilter, FilterData},
     state::types::{component_refs::ComponentRefs, content_refs::ContentRef},
 };
 
@@ -24,6 +25,20 @@ impl ComponentRefsProp {
         }
     }
 
+    /// Creates a ComponentRefs prop that returns all children that match `filter`,
+    /// which is a [`ContentFilter`] or a composition of [`ContentFilter`]s.
+    pub fn new_from_children_matching<F>(filter: F) -> Self
+    where
+        F: for<'a> ApplyTest<FilterData<'a>, GraphNode> + 'static,
+    {
+        ComponentRefsProp {
+            data_query: DataQuery::ContentRefs {
+                container: PropSource::Me,
+                filter: Rc::new(filter),
+            },
+        }
+    }
+
     /// Creates a ComponentRefs prop that returns all children with component_type
     pub fn new_from_all_matching_siblings(profile: PropProfile) -> Self {
         ComponentRefsProp {
@@ -2,8 +2,10 @@ use std::{marker::PhantomData, rc::Rc};
 
 use crate::{components::prelude::*, props::UpdaterObject};
 
+use super::util::concatenate_query_strings;
+
 /// A prop that represents a value from an enum. The enum must implement `TryFrom<&str>`.
-/// If the `TryFrom` fails, the default value of the enum is used.
+/// If the `TryFrom` fails, the default value of the enum is used and a warning is reported.
 #[derive(Debug)]
 pub struct EnumProp<T: Default + Clone> {
     /// The data query that indicates how the dependencies of this prop will be created.
@@ -73,4 +75,18 @@ where
             }
         }
     }
+
+    fn warnings(&self, data: &DataQueryResults) -> Vec<String> {
+        let DataQuery::Attribute { attribute_name, .. } = &self.data_query else {
+            return Vec::new();
+        };
+        match concatenate_query_strings(data, 0) {
+            Some(value) if !value.is_empty() && T::try_from(value.as_str()).is_err() => {
+                vec![format!(
+                    "Invalid value \"{value}\" for attribute \"{attribute_name}\"; the default value is used instead."
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
 }
@@ -2,7 +2,10 @@ use itertools::Itertools;
 
 use crate::components::prelude::*;
 
+use super::util::concatenate_query_strings;
+
 /// A prop that computes an integer from a string value.
+/// If the string cannot be parsed as a number, the default value is used and a warning is reported.
 #[derive(Debug)]
 pub struct StringToIntegerProp {
     /// The data query that indicates how the dependencies of this prop will be created.
@@ -70,4 +73,20 @@ impl PropUpdater for StringToIntegerProp {
             }
         }
     }
+
+    fn warnings(&self, data: &DataQueryResults) -> Vec<String> {
+        let DataQuery::Attribute { attribute_name, .. } = &self.data_query else {
+            return Vec::new();
+        };
+        match concatenate_query_strings(data, 0) {
+            // An empty value is not an error, e.g., an attribute without a value (`<ol start>`)
+            Some(value) if !value.is_empty() && value.parse::<f64>().is_err() => {
+                vec![format!(
+                    "Could not parse \"{value}\" as a number for attribute \"{attribute_name}\"; the default value {} is used instead.",
+                    self.default
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use crate::props::{DataQueryResults, PropValue};
+
 /// Convert string to boolean
 ///
 /// The word "true" (case-insensitive) becomes `true`.
@@ -16,6 +18,25 @@ pub fn string_attr_to_boolean(s: &str) -> bool {
     s.eq_ignore_ascii_case("true") || (s.is_empty())
 }
 
+/// Concatenate the string values returned by the data query with index `query_idx` in `data`,
+/// e.g., the string children of an attribute.
+///
+/// Returns `None` if the query returned no values (e.g., the attribute was not specified),
+/// if its values came from a default, or if any value is not a string.
+pub fn concatenate_query_strings(data: &DataQueryResults, query_idx: usize) -> Option<String> {
+    let values = &data.vec.get(query_idx)?.values;
+    if values.is_empty() || values[0].came_from_default {
+        return None;
+    }
+    values
+        .iter()
+        .map(|prop| match &prop.value {
+            PropValue::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
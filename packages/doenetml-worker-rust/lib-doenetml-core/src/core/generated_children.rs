@@ -0,0 +1,210 @@
+//! # Generated children
+//!
+//! Composites such as `<repeat>`, `<select>` and `<conditionalContent>` create or destroy their children
+//! when their inputs change. [`Core::replace_generated_children`] replaces the children generated for a component
+//! after `Core` was initialized, updating the resolver, the document structure, the dependency graph
+//! and the rendered flat dast to match.
+//!
+//! The components of replaced children are removed from the document, so they are no longer found through their names
+//! or rendered. New components are given new indices; the indices of removed components are not reused.
+
+use std::{collections::HashMap, ops::Range};
+
+use serde::Serialize;
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::prelude::{ComponentIdx, FlatDastElement, FlatDastElementUpdate},
+    dast::{
+        DastElementContent, DastRoot,
+        flat_dast::{FlatElement, FlatFragment, FlatNode, NormalizedNode},
+        ref_expand::Expander,
+        ref_resolve::{IndexResolution, RefResolution},
+    },
+    error::CoreError,
+};
+
+use super::{Core, action_recording::RecordedEvent};
+
+/// The children generated for a component by [`Core::replace_generated_children`].
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedChildren {
+    /// The nodes added to the resolver for the children.
+    nodes: Vec<FlatNode>,
+    /// The indices of the components created for the children and their descendants.
+    components: Range<usize>,
+}
+
+impl GeneratedChildren {
+    /// Whether any of the children extends a referent whose resolution satisfies `predicate`.
+    pub(super) fn any_extending(&self, predicate: impl Fn(&RefResolution) -> bool) -> bool {
+        self.nodes.iter().any(|node| match node {
+            FlatNode::Element(FlatElement {
+                extending: Some(source),
+                ..
+            }) => predicate(source.get_resolution()),
+            _ => false,
+        })
+    }
+}
+
+/// The changes to the output of `Core` resulting from replacing the generated children of a component.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedChildrenUpdate {
+    /// The flat dast elements of the components that were created for the new children.
+    pub new_elements: Vec<FlatDastElement>,
+    /// The updates to the elements that were already rendered.
+    /// The elements whose children changed have `new_children` set.
+    pub flat_dast_updates: HashMap<ComponentIdx, FlatDastElementUpdate>,
+}
+
+impl Core {
+    /// Replace the children generated for the component `parent` with `content`.
+    /// The generated children come after the children given to `parent` in the source.
+    ///
+    /// Refs in `content` are resolved relative to `parent`,
+    /// and the generated children replace the index resolutions of `parent`, so that, e.g., `$parent[1]`
+    /// refers to the first generated child.
+    pub fn replace_generated_children(
+        &mut self,
+        parent: ComponentIdx,
+        content: &[DastElementContent],
+    ) -> Result<GeneratedChildrenUpdate, CoreError> {
+        if self.resolver.is_none() {
+            return Err(CoreError::NotInitialized("replace generated children"));
+        }
+        self.document_model.get_component(parent)?;
+        let event = match self.is_recording_actions() {
+            true => Some(RecordedEvent::ReplaceGeneratedChildren {
+                parent,
+                content: content
+                    .iter()
+                    .map(|node| Self::serialize_for_recording(node, "generated children"))
+                    .collect::<Result<_, _>>()?,
+            }),
+            false => None,
+        };
+
+        let removed_components = self.remove_generated_children(parent)?;
+
+        let first_new_idx = self.document_model.get_num_components();
+        let mut fragment = FlatFragment::from_dast_with_id_shift(
+            &DastRoot {
+                children: content.to_vec(),
+                position: None,
+                sources: Vec::new(),
+            },
+            first_new_idx,
+            Some(parent.as_usize()),
+        );
+
+        self.add_nodes_to_resolver(&fragment, IndexResolution::None)?;
+        Expander::expand_fragment(&mut fragment, self.resolver.as_ref().unwrap(), |idx| {
+            self.document_model
+                .get_component_type(ComponentIdx::new(idx))
+                .ok()
+        });
+        self.replace_index_resolutions_in_resolver(
+            &fragment.children,
+            IndexResolution::ReplaceAll {
+                parent: parent.as_usize(),
+            },
+        )?;
+
+        let nodes = fragment
+            .nodes
+            .iter()
+            .map(NormalizedNode::from_flat_node)
+            .collect::<Vec<_>>();
+        let changes = self.document_model.replace_generated_children(
+            parent,
+            &removed_components,
+            &fragment.children,
+            &nodes,
+        )?;
+        self.drop_removed_components();
+        let end_idx = self.document_model.get_num_components();
+        self.generated_children.insert(
+            parent,
+            GeneratedChildren {
+                nodes: fragment.nodes,
+                components: first_new_idx..end_idx,
+            },
+        );
+
+        let flat_dast_updates = self.render_component_changes(changes)?;
+
+        let new_elements = (first_new_idx..end_idx)
+            .map(|idx| {
+                self.document_renderer
+                    .component_to_flat_dast(ComponentIdx::new(idx), &self.document_model)
+            })
+            .collect::<Result<_, _>>()?;
+
+        if let Some(event) = event {
+            self.record_event(event)?;
+        }
+
+        Ok(GeneratedChildrenUpdate {
+            new_elements,
+            flat_dast_updates,
+        })
+    }
+
+    /// Remove the nodes of all the generated children from the resolver, e.g., before the components are created again
+    /// from the normalized root, which doesn't include them. The index resolutions of their parents are left empty.
+    ///
+    /// Returns the components whose generated children were removed, not counting those that were themselves generated.
+    pub(super) fn clear_generated_children(&mut self) -> Result<Vec<ComponentIdx>, CoreError> {
+        let mut parents = Vec::new();
+        // Children are given larger indices than the component they were generated for,
+        // so the parent with the smallest index was not generated for another parent.
+        while let Some(parent) = self.generated_children.keys().min().copied() {
+            self.remove_generated_children(parent)?;
+            self.replace_index_resolutions_in_resolver(
+                &[],
+                IndexResolution::ReplaceAll {
+                    parent: parent.as_usize(),
+                },
+            )?;
+            parents.push(parent);
+        }
+        Ok(parents)
+    }
+
+    /// Remove the nodes of the children previously generated for `parent`, and of the children generated for them,
+    /// from the resolver.
+    ///
+    /// Returns the components of those children, which are to be removed from the document model.
+    fn remove_generated_children(
+        &mut self,
+        parent: ComponentIdx,
+    ) -> Result<Vec<ComponentIdx>, CoreError> {
+        let Some(generated_children) = self.generated_children.remove(&parent) else {
+            return Ok(Vec::new());
+        };
+
+        let mut removed_components = generated_children
+            .components
+            .clone()
+            .map(ComponentIdx::new)
+            .collect::<Vec<_>>();
+
+        let nested_parents = self
+            .generated_children
+            .keys()
+            .filter(|idx| generated_children.components.contains(&idx.as_usize()))
+            .copied()
+            .collect::<Vec<_>>();
+        for nested_parent in nested_parents {
+            removed_components.extend(self.remove_generated_children(nested_parent)?);
+        }
+
+        self.delete_nodes_from_resolver(&generated_children.nodes)?;
+        Ok(removed_components)
+    }
+}
@@ -1,6 +1,7 @@
 pub use super::graph_node_lookup::*;
 use crate::{
-    components::types::{LocalPropIdx, PropDefinitionIdx},
+    components::types::{ComponentIdx, LocalPropIdx, PropDefinitionIdx},
+    error::CoreError,
     graph::directed_graph::DirectedGraph,
 };
 
@@ -36,21 +37,15 @@ impl GraphNode {
         }
     }
     /// Get the wrapped `index` value. This is the same as `idx()`
-    /// except it will panic if `self` is not `GraphNode::Prop`.
-    pub fn prop_idx(&self) -> PropDefinitionIdx {
-        match self {
-            GraphNode::Prop(idx) => (*idx).into(),
-            _ => panic!("`prop_idx` expected a GraphNode::Prop"),
-        }
+    /// except it will return an error if `self` is not `GraphNode::Prop`.
+    pub fn prop_idx(&self) -> Result<PropDefinitionIdx, CoreError> {
+        self.try_into()
     }
 
     /// Get the wrapped `index` value. This is the same as `idx()`
-    /// except it will panic if `self` is not `GraphNode::Component`.
-    pub fn component_idx(&self) -> usize {
-        match self {
-            GraphNode::Component(idx) => *idx,
-            _ => panic!("`component_idx` expected a GraphNode::Component"),
-        }
+    /// except it will return an error if `self` is not `GraphNode::Component`.
+    pub fn component_idx(&self) -> Result<usize, CoreError> {
+        ComponentIdx::try_from(self).map(ComponentIdx::as_usize)
     }
 }
 
@@ -126,6 +126,16 @@ impl<T> Taggable<GraphNode, T> for GraphNodeLookup<T> {
             }
         }
     }
+    fn remove_tag(&mut self, node: &GraphNode) -> Option<T> {
+        match node {
+            GraphNode::Component(idx) => self.components.get_mut(*idx).and_then(|x| x.take()),
+            GraphNode::String(idx) => self.strings.get_mut(*idx).and_then(|x| x.take()),
+            GraphNode::Prop(idx) => self.props.get_mut(*idx).and_then(|x| x.take()),
+            GraphNode::State(idx) => self.states.get_mut(*idx).and_then(|x| x.take()),
+            GraphNode::Query(idx) => self.queries.get_mut(*idx).and_then(|x| x.take()),
+            GraphNode::Virtual(idx) => self.virtuals.get_mut(*idx).and_then(|x| x.take()),
+        }
+    }
 }
 
 /// Data structure to allow for lookup up of values keyed by `(GraphNode, GraphNode)`.
@@ -165,6 +175,20 @@ impl<T> DoubleNodeLookup<T> {
             self.second.push(lookup);
         }
     }
+
+    /// Remove all values whose key has `first` as its first entry.
+    pub fn remove_first(&mut self, first: &GraphNode) {
+        if let Some(idx) = self.first.remove_tag(first) {
+            self.second[idx] = GraphNodeLookup::new();
+        }
+    }
+
+    /// Remove all values whose key has `second` as its second entry.
+    pub fn remove_second(&mut self, second: &GraphNode) {
+        for lookup in self.second.iter_mut() {
+            lookup.remove_tag(second);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -193,4 +217,27 @@ mod test {
         assert_eq!(lookup.get(&key4), None);
         assert_eq!(lookup.get(&key5), None);
     }
+
+    #[test]
+    fn double_node_lookup_removal() {
+        let mut lookup = DoubleNodeLookup::new();
+
+        let key1 = (GraphNode::Prop(0), GraphNode::Query(0));
+        let key2 = (GraphNode::Prop(0), GraphNode::Query(1));
+        let key3 = (GraphNode::Prop(1), GraphNode::Query(1));
+        lookup.insert(key1, 1);
+        lookup.insert(key2, 2);
+        lookup.insert(key3, 3);
+
+        lookup.remove_second(&GraphNode::Query(1));
+        assert_eq!(lookup.get(&key1), Some(&1));
+        assert_eq!(lookup.get(&key2), None);
+        assert_eq!(lookup.get(&key3), None);
+
+        lookup.remove_first(&GraphNode::Prop(0));
+        assert_eq!(lookup.get(&key1), None);
+
+        lookup.insert(key1, 4);
+        assert_eq!(lookup.get(&key1), Some(&4));
+    }
 }
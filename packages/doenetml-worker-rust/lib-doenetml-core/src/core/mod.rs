@@ -4,15 +4,25 @@
 //! props as appropriate and delivers updates to the UI when values change. It is responsible for maintaining (and
 //! updating) relationships between _DoenetML_ components and their props.
 
+pub mod action_history;
+pub mod action_recording;
 pub mod component_builder;
 pub mod dispatch_action;
+pub mod document_flags;
 mod document_model;
 mod document_renderer;
 mod document_structure;
+pub mod error;
+pub mod generated_children;
 pub mod graph_node;
 mod graph_node_lookup;
 pub mod math_via_wasm;
+pub mod prop_by_path;
+pub mod prop_subscriptions;
 pub mod props;
+pub mod scheduler;
+pub mod source_update;
+pub mod state_snapshot;
 
 pub use document_model::DocumentModel;
 
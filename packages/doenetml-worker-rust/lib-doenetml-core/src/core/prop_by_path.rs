@@ -0,0 +1,130 @@
+//! # Props by path
+//!
+//! Look up the value of a public prop from a reference path such as `$answer1.creditAchieved` or `$P.coords[2]`,
+//! so that code outside of core can read values without rendering the document.
+
+use crate::{
+    components::{
+        ComponentNode, ComponentProps, ComponentVariantProps,
+        types::{ComponentIdx, PropPointer},
+    },
+    dast::{
+        flat_dast::{FlatPathPart, Index},
+        ref_resolve::PathIndex,
+    },
+    error::CoreError,
+    graph_node::GraphNode,
+    props::{ArrayType, PropValue},
+};
+
+use super::{Core, DocumentModel, component_builder::parse_array_index};
+
+impl Core {
+    /// Get the value of the public prop referred to by `path`, resolved relative to the node `origin`.
+    ///
+    /// The path is resolved to a component by the `Resolver`. The remaining part of the path must be the name
+    /// of a public prop of that component (matched case-insensitively), optionally followed by an index
+    /// if the prop is an array prop, e.g. `[2]`, `[-1]` or `[1:2]`. If nothing remains of the path, the component's default prop is used.
+    ///
+    /// The value is calculated if needed, but is not marked as viewed for rendering.
+    pub fn get_prop_by_path<T: AsRef<[FlatPathPart]>>(
+        &mut self,
+        path: T,
+        origin: Index,
+    ) -> Result<PropValue, CoreError> {
+        let (prop_pointer, array_index) = self.resolve_prop_path(path, origin)?;
+
+        let prop_node = self.document_model.prop_pointer_to_prop_node(prop_pointer);
+        let value = self
+            .document_renderer
+            .get_prop_for_render_untracked(prop_node, &self.document_model)?
+            .value;
+
+        match array_index {
+            Some(index) => {
+                get_array_element_or_default(&self.document_model, prop_node, &value, index)
+            }
+            None => Ok(value),
+        }
+    }
+
+    /// Map `path`, resolved relative to the node `origin`, onto a public prop as described in [`Core::get_prop_by_path`].
+    ///
+    /// Returns a pointer to the prop along with the index of the element(s) referred to, if the path has an index.
+    pub(crate) fn resolve_prop_path<T: AsRef<[FlatPathPart]>>(
+        &self,
+        path: T,
+        origin: Index,
+    ) -> Result<(PropPointer, Option<PathIndex>), CoreError> {
+        let ref_resolution = self.resolve_path(path, origin, false)?;
+        let component_idx = ComponentIdx::new(ref_resolution.node_idx);
+        let component = self.document_model.get_component(component_idx)?;
+
+        let (local_prop_idx, array_index) = match ref_resolution.unresolved_path.as_deref() {
+            None | Some([]) => {
+                let local_prop_idx = component.get_default_prop_local_index().ok_or_else(|| {
+                    CoreError::InvalidPath(format!(
+                        "component {} does not have a default prop",
+                        component.get_component_type()
+                    ))
+                })?;
+                (local_prop_idx, None)
+            }
+            Some([path_part]) => {
+                let local_prop_idx = component
+                    .get_public_local_prop_index_from_name_case_insensitive(&path_part.name)
+                    .ok_or_else(|| CoreError::PropNotFound {
+                        prop_name: path_part.name.clone(),
+                        component_type: component.get_component_type().to_string(),
+                    })?;
+                let array_index = parse_array_index(&path_part.index)
+                    .map_err(|err| CoreError::InvalidPath(err.to_string()))?;
+                if array_index.is_some() && !component.variant.get_prop_is_array(local_prop_idx) {
+                    return Err(CoreError::InvalidPath(format!(
+                        "prop {} of component {} is not an array and cannot be indexed",
+                        path_part.name,
+                        component.get_component_type()
+                    )));
+                }
+                (local_prop_idx, array_index)
+            }
+            Some(_) => {
+                return Err(CoreError::InvalidPath(
+                    "Nested props not implemented yet".to_string(),
+                ));
+            }
+        };
+
+        Ok((
+            PropPointer {
+                component_idx,
+                local_prop_idx,
+            },
+            array_index,
+        ))
+    }
+}
+
+/// Get the element or range of elements selected by `index` from `value`, the value of the array prop `prop_node`.
+/// As for an element prop, an index that is out of range gives the default value of an element (or an empty array).
+pub(crate) fn get_array_element_or_default(
+    document_model: &DocumentModel,
+    prop_node: GraphNode,
+    value: &PropValue,
+    index: PathIndex,
+) -> Result<PropValue, CoreError> {
+    match value.get_indexed_array_value(index) {
+        Some(element) => Ok(element),
+        None => {
+            let prop_definition = document_model.get_prop_definition(prop_node)?;
+            let array_type =
+                ArrayType::from_value_type(prop_definition.variant).ok_or_else(|| {
+                    CoreError::InvalidPath(format!(
+                        "prop {} is not an array and cannot be indexed",
+                        prop_definition.meta.name
+                    ))
+                })?;
+            Ok(array_type.default_indexed_value(index))
+        }
+    }
+}
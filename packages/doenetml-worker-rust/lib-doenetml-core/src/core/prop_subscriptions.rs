@@ -0,0 +1,237 @@
+//! # Prop subscriptions
+//!
+//! Allow code outside of core to register interest in particular props (e.g., a score or the value of an input)
+//! and be told when their values change, without diffing the flat dast.
+//!
+//! Each subscription gets its own `DataQuery::Null` node that is used as the `origin` when getting the prop,
+//! so the prop cache tracks whether the prop changed since the subscription was last checked
+//! independently of rendering and of other subscriptions.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::types::{ComponentIdx, PropPointer},
+    dast::{
+        flat_dast::{FlatPathPart, Index},
+        ref_resolve::PathIndex,
+    },
+    error::CoreError,
+    graph_node::GraphNode,
+    props::PropValue,
+};
+
+use super::{Core, prop_by_path::get_array_element_or_default};
+
+/// Identifies a subscription created by [`Core::subscribe_to_prop`] or [`Core::subscribe_to_prop_by_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SubscriptionId(usize);
+
+/// A prop whose value was requested to be reported when it changes.
+#[derive(Debug, Clone)]
+struct PropSubscription {
+    prop_pointer: PropPointer,
+    /// If set, only the element(s) of the array prop selected by this index are reported.
+    array_index: Option<PathIndex>,
+    /// The `GraphNode::Query` used as the `origin` when getting the prop.
+    origin: GraphNode,
+    /// The value when the subscription was created or the value last reported.
+    value: PropValue,
+}
+
+/// The subscriptions to props registered with `Core`.
+#[derive(Debug, Default)]
+pub struct PropSubscriptions {
+    subscriptions: BTreeMap<SubscriptionId, PropSubscription>,
+    next_id: usize,
+}
+
+/// A change in the value of a subscribed prop.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribedPropChange {
+    pub subscription_id: SubscriptionId,
+    pub component_idx: ComponentIdx,
+    pub prop_name: String,
+    /// The new value of the prop (or of the subscribed element of an array prop).
+    pub value: PropValue,
+}
+
+impl PropSubscriptions {
+    /// Remove the subscriptions to the props of `component_idx`, e.g., because the component was removed.
+    ///
+    /// Returns the `origin` nodes of the removed subscriptions, which are to be removed from the dependency graph.
+    pub(super) fn remove_component(&mut self, component_idx: ComponentIdx) -> Vec<GraphNode> {
+        let mut origins = Vec::new();
+        self.subscriptions.retain(|_, subscription| {
+            let keep = subscription.prop_pointer.component_idx != component_idx;
+            if !keep {
+                origins.push(subscription.origin);
+            }
+            keep
+        });
+        origins
+    }
+}
+
+impl Core {
+    /// Keep the subscriptions in `prop_subscriptions` after the components were created again
+    /// (see [`Core::rebuild_from_normalized_root`]), giving each an `origin` node in the new dependency graph.
+    /// The subscriptions to the props of the components in `replaced` are removed.
+    ///
+    /// The value last reported is kept, so a change caused by creating the components again is reported as usual.
+    ///
+    /// Returns the removed subscriptions.
+    pub(super) fn restore_prop_subscriptions(
+        &mut self,
+        prop_subscriptions: PropSubscriptions,
+        replaced: &HashSet<ComponentIdx>,
+    ) -> Vec<SubscriptionId> {
+        let mut removed = Vec::new();
+        self.prop_subscriptions.next_id = prop_subscriptions.next_id;
+        for (subscription_id, mut subscription) in prop_subscriptions.subscriptions {
+            if replaced.contains(&subscription.prop_pointer.component_idx) {
+                removed.push(subscription_id);
+                continue;
+            }
+            subscription.origin = self.document_model.add_null_query_node();
+            self.prop_subscriptions
+                .subscriptions
+                .insert(subscription_id, subscription);
+        }
+        removed
+    }
+}
+
+impl Core {
+    /// Subscribe to changes in the value of the prop `prop_pointer`.
+    /// Changes are reported by [`Core::get_subscribed_prop_changes`],
+    /// which is called after each dispatched action.
+    ///
+    /// Subscriptions are removed when core is initialized from a new source.
+    pub fn subscribe_to_prop(
+        &mut self,
+        prop_pointer: PropPointer,
+    ) -> Result<SubscriptionId, CoreError> {
+        self.document_model.check_prop_pointer(prop_pointer)?;
+        self.add_prop_subscription(prop_pointer, None)
+    }
+
+    /// Subscribe to changes in the value of the public prop referred to by `path`, resolved relative to the node `origin`
+    /// (see [`Core::get_prop_by_path`] for how the path is mapped onto a prop).
+    /// Changes are reported by [`Core::get_subscribed_prop_changes`],
+    /// which is called after each dispatched action.
+    ///
+    /// Subscriptions are removed when core is initialized from a new source.
+    pub fn subscribe_to_prop_by_path<T: AsRef<[FlatPathPart]>>(
+        &mut self,
+        path: T,
+        origin: Index,
+    ) -> Result<SubscriptionId, CoreError> {
+        let (prop_pointer, array_index) = self.resolve_prop_path(path, origin)?;
+        self.add_prop_subscription(prop_pointer, array_index)
+    }
+
+    /// Remove the subscription `subscription_id`. Returns `false` if there was no such subscription.
+    pub fn unsubscribe_from_prop(&mut self, subscription_id: SubscriptionId) -> bool {
+        match self
+            .prop_subscriptions
+            .subscriptions
+            .remove(&subscription_id)
+        {
+            Some(subscription) => {
+                self.document_model
+                    .remove_null_query_node(subscription.origin);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the subscribed props whose values changed since the subscription was created
+    /// or since the change was last reported, in the order the subscriptions were created.
+    ///
+    /// The values of subscribed props are calculated if needed.
+    pub fn get_subscribed_prop_changes(&mut self) -> Result<Vec<SubscribedPropChange>, CoreError> {
+        let mut changes = Vec::new();
+
+        for (subscription_id, subscription) in self.prop_subscriptions.subscriptions.iter_mut() {
+            let prop_node = self
+                .document_model
+                .prop_pointer_to_prop_node(subscription.prop_pointer);
+            let prop = self
+                .document_model
+                .get_prop(prop_node, subscription.origin)?;
+
+            // A prop is marked as changed whenever it is recalculated,
+            // so we also check that it ended up with a different value.
+            if !prop.changed {
+                continue;
+            }
+            let value = match subscription.array_index {
+                Some(index) => get_array_element_or_default(
+                    &self.document_model,
+                    prop_node,
+                    &prop.value,
+                    index,
+                )?,
+                None => prop.value,
+            };
+            if value == subscription.value {
+                continue;
+            }
+
+            subscription.value = value.clone();
+            changes.push(SubscribedPropChange {
+                subscription_id: *subscription_id,
+                component_idx: subscription.prop_pointer.component_idx,
+                prop_name: self
+                    .document_model
+                    .get_prop_name(subscription.prop_pointer)?
+                    .to_string(),
+                value,
+            });
+        }
+
+        Ok(changes)
+    }
+
+    /// Add a subscription to `prop_pointer`, recording its current value so that only later changes are reported.
+    fn add_prop_subscription(
+        &mut self,
+        prop_pointer: PropPointer,
+        array_index: Option<PathIndex>,
+    ) -> Result<SubscriptionId, CoreError> {
+        let origin = self.document_model.add_null_query_node();
+        let prop_node = self.document_model.prop_pointer_to_prop_node(prop_pointer);
+        let value = self.document_model.get_prop(prop_node, origin)?.value;
+        let value = match array_index {
+            Some(index) => {
+                get_array_element_or_default(&self.document_model, prop_node, &value, index)?
+            }
+            None => value,
+        };
+
+        let subscriptions = &mut self.prop_subscriptions;
+        let subscription_id = SubscriptionId(subscriptions.next_id);
+        subscriptions.next_id += 1;
+        subscriptions.subscriptions.insert(
+            subscription_id,
+            PropSubscription {
+                prop_pointer,
+                array_index,
+                origin,
+                value,
+            },
+        );
+
+        Ok(subscription_id)
+    }
+}
@@ -0,0 +1,359 @@
+//! Array props are props whose value is an array of elements that can be referenced individually.
+//! For example, `$P.coords[2]` refers to the second coordinate of the `coords` prop of the point `P`.
+//! Elements can also be counted from the end of the array, e.g. `$P.coords[-1]`,
+//! and a range of elements can be referenced, e.g. `$P.coords[1:2]`.
+//!
+//! A prop is made into an array prop by marking it `is_array` in the `#[prop(...)]` annotation of its component.
+//! When an element of an array prop is referenced, a prop for that element is added to the component
+//! that owns the array prop. The element prop is created from the array prop's
+//! [`PropUpdaterUntyped::element_updater`], falling back to an [`ArrayElementProp`] that depends on the entire array.
+
+use std::{ops::Range, rc::Rc};
+
+use crate::{
+    components::types::LocalPropIdx,
+    dast::ref_resolve::PathIndex,
+    state::types::{
+        component_refs::{ComponentRef, ComponentRefs},
+        content_refs::{AnnotatedContentRefs, ContentRefs},
+        math_expr::MathExpr,
+    },
+};
+
+use super::{
+    DataQuery, DataQueryResults, InvertError, PropCalcResult, PropSource, PropUpdaterUntyped,
+    PropValue, PropValueType,
+};
+
+/// The types of the values of array props.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayType {
+    Math,
+    ComponentRefs,
+    ContentRefs,
+    AnnotatedContentRefs,
+}
+
+impl ArrayType {
+    /// The array type of props whose value is of type `value_type`,
+    /// or `None` if props of type `value_type` cannot be array props.
+    pub const fn from_value_type(value_type: PropValueType) -> Option<Self> {
+        match value_type {
+            PropValueType::Math => Some(ArrayType::Math),
+            PropValueType::ComponentRefs => Some(ArrayType::ComponentRefs),
+            PropValueType::ContentRefs => Some(ArrayType::ContentRefs),
+            PropValueType::AnnotatedContentRefs => Some(ArrayType::AnnotatedContentRefs),
+            _ => None,
+        }
+    }
+
+    /// The type of the value of the array.
+    pub const fn value_type(self) -> PropValueType {
+        match self {
+            ArrayType::Math => PropValueType::Math,
+            ArrayType::ComponentRefs => PropValueType::ComponentRefs,
+            ArrayType::ContentRefs => PropValueType::ContentRefs,
+            ArrayType::AnnotatedContentRefs => PropValueType::AnnotatedContentRefs,
+        }
+    }
+
+    /// The type of the elements of the array.
+    ///
+    /// Elements of arrays of content refs are themselves `ContentRefs`/`AnnotatedContentRefs`
+    /// (with a single entry) so that an element that is out of range can be represented by an empty value.
+    pub const fn element_type(self) -> PropValueType {
+        match self {
+            ArrayType::Math => PropValueType::Math,
+            ArrayType::ComponentRefs => PropValueType::ComponentRef,
+            ArrayType::ContentRefs => PropValueType::ContentRefs,
+            ArrayType::AnnotatedContentRefs => PropValueType::AnnotatedContentRefs,
+        }
+    }
+
+    /// The type of the value selected by `index` from the array:
+    /// the element type for a single index and the type of the array itself for a range of indices.
+    pub fn indexed_type(self, index: PathIndex) -> PropValueType {
+        match index {
+            PathIndex::Single(_) => self.element_type(),
+            PathIndex::Range { .. } => self.value_type(),
+        }
+    }
+
+    /// The value selected by `index` from the array when `index` is out of range:
+    /// the default element for a single index and an empty array for a range of indices.
+    pub fn default_indexed_value(self, index: PathIndex) -> PropValue {
+        match index {
+            PathIndex::Single(_) => self.default_element(),
+            PathIndex::Range { .. } => match self {
+                ArrayType::Math => PropValue::Math(Rc::new(MathExpr::default())),
+                ArrayType::ComponentRefs => {
+                    PropValue::ComponentRefs(Rc::new(ComponentRefs(Vec::new())))
+                }
+                ArrayType::ContentRefs => PropValue::ContentRefs(Rc::new(ContentRefs::new())),
+                ArrayType::AnnotatedContentRefs => {
+                    PropValue::AnnotatedContentRefs(Rc::new(AnnotatedContentRefs::new()))
+                }
+            },
+        }
+    }
+
+    /// The value of an element of the array whose index is out of range.
+    pub fn default_element(self) -> PropValue {
+        match self {
+            ArrayType::Math => PropValue::Math(Rc::new(MathExpr::default())),
+            ArrayType::ComponentRefs => PropValue::ComponentRef(None),
+            ArrayType::ContentRefs => PropValue::ContentRefs(Rc::new(ContentRefs::new())),
+            ArrayType::AnnotatedContentRefs => {
+                PropValue::AnnotatedContentRefs(Rc::new(AnnotatedContentRefs::new()))
+            }
+        }
+    }
+}
+
+/// Returns the type of the elements of an array prop whose value is of type `value_type`,
+/// or `None` if props of type `value_type` cannot be array props (see [`ArrayType::element_type`]).
+///
+/// This function must be `const` so that it can be used for compile-time type checking.
+pub const fn array_element_type(value_type: PropValueType) -> Option<PropValueType> {
+    match ArrayType::from_value_type(value_type) {
+        Some(array_type) => Some(array_type.element_type()),
+        None => None,
+    }
+}
+
+impl PropValue {
+    /// The number of elements of this value, viewed as an array.
+    ///
+    /// Returns `None` if the value's type cannot be an array.
+    /// A math expression that is not a vector is treated as an array with a single element.
+    pub fn array_len(&self) -> Option<usize> {
+        match self {
+            PropValue::Math(math) => Some(math.to_vector_components().map_or(1, |c| c.len())),
+            PropValue::ComponentRefs(refs) => Some(refs.0.len()),
+            PropValue::ContentRefs(refs) => Some(refs.as_slice().len()),
+            PropValue::AnnotatedContentRefs(refs) => Some(refs.as_slice().len()),
+            _ => None,
+        }
+    }
+
+    /// Get the element or range of elements selected by `index` from this value, viewed as an array.
+    /// A range of elements is an array of the same type as this value.
+    ///
+    /// Returns `None` if `index` is out of range or the value's type cannot be an array.
+    pub fn get_indexed_array_value(&self, index: PathIndex) -> Option<PropValue> {
+        let positions = index.positions(self.array_len()?)?;
+        match index {
+            PathIndex::Single(_) => self.get_array_element(positions.start),
+            PathIndex::Range { .. } => self.get_array_elements(positions),
+        }
+    }
+
+    /// Create a copy of this value, viewed as an array, with the element or range of elements
+    /// selected by `index` replaced by `value`. A range of elements must be replaced by an array
+    /// with the same number of elements.
+    ///
+    /// Returns `None` if `index` is out of range or `value` is not a valid replacement.
+    pub fn with_indexed_array_value(
+        &self,
+        index: PathIndex,
+        value: PropValue,
+    ) -> Option<PropValue> {
+        let positions = index.positions(self.array_len()?)?;
+        match index {
+            PathIndex::Single(_) => self.with_array_element(positions.start, value),
+            PathIndex::Range { .. } => {
+                if value.array_len()? != positions.len() {
+                    return None;
+                }
+                positions
+                    .enumerate()
+                    .try_fold(self.clone(), |array, (offset, position)| {
+                        array.with_array_element(position, value.get_array_element(offset)?)
+                    })
+            }
+        }
+    }
+
+    /// Get the elements at the (zero-based) positions `range` of this value, viewed as an array,
+    /// as an array of the same type.
+    fn get_array_elements(&self, range: Range<usize>) -> Option<PropValue> {
+        match self {
+            PropValue::Math(math) => {
+                let components = math
+                    .to_vector_components()
+                    .unwrap_or_else(|_| vec![(**math).clone()]);
+                Some(PropValue::Math(Rc::new(MathExpr::new_vector(
+                    components.get(range)?,
+                ))))
+            }
+            PropValue::ComponentRefs(refs) => Some(PropValue::ComponentRefs(Rc::new(
+                ComponentRefs(refs.0.get(range)?.to_vec()),
+            ))),
+            PropValue::ContentRefs(refs) => Some(PropValue::ContentRefs(Rc::new(
+                refs.as_slice().get(range)?.to_vec().into(),
+            ))),
+            PropValue::AnnotatedContentRefs(refs) => {
+                Some(PropValue::AnnotatedContentRefs(Rc::new(
+                    AnnotatedContentRefs::from_vec(refs.as_slice().get(range)?.to_vec()),
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the element at (zero-based) `index` of this value, viewed as an array.
+    ///
+    /// Returns `None` if `index` is out of range or the value's type cannot be an array.
+    /// A math expression that is not a vector is treated as an array with a single element.
+    pub fn get_array_element(&self, index: usize) -> Option<PropValue> {
+        match self {
+            PropValue::Math(math) => {
+                let mut components = math
+                    .to_vector_components()
+                    .unwrap_or_else(|_| vec![(**math).clone()]);
+                (index < components.len())
+                    .then(|| PropValue::Math(Rc::new(components.swap_remove(index))))
+            }
+            PropValue::ComponentRefs(refs) => refs
+                .0
+                .get(index)
+                .map(|component_idx| PropValue::ComponentRef(Some(ComponentRef(*component_idx)))),
+            PropValue::ContentRefs(refs) => refs
+                .as_slice()
+                .get(index)
+                .map(|content_ref| PropValue::ContentRefs(Rc::new(vec![*content_ref].into()))),
+            PropValue::AnnotatedContentRefs(refs) => refs.as_slice().get(index).map(|entry| {
+                PropValue::AnnotatedContentRefs(Rc::new(AnnotatedContentRefs::from_vec(vec![
+                    *entry,
+                ])))
+            }),
+            _ => None,
+        }
+    }
+
+    /// Create a copy of this value, viewed as an array, with the element at (zero-based) `index`
+    /// replaced by `element`.
+    ///
+    /// Returns `None` if `index` is out of range or `element` is not a valid element of this array.
+    pub fn with_array_element(&self, index: usize, element: PropValue) -> Option<PropValue> {
+        match (self, element) {
+            (PropValue::Math(math), PropValue::Math(element)) => {
+                match math.to_vector_components() {
+                    Ok(mut components) => {
+                        *components.get_mut(index)? = (*element).clone();
+                        Some(PropValue::Math(Rc::new(MathExpr::new_vector(&components))))
+                    }
+                    Err(_) => (index == 0).then_some(PropValue::Math(element)),
+                }
+            }
+            (PropValue::ComponentRefs(refs), PropValue::ComponentRef(Some(element))) => {
+                let mut refs = (**refs).clone();
+                *refs.0.get_mut(index)? = element.0;
+                Some(PropValue::ComponentRefs(Rc::new(refs)))
+            }
+            (PropValue::ContentRefs(refs), PropValue::ContentRefs(element)) => {
+                let [element] = element.as_slice() else {
+                    return None;
+                };
+                let mut refs = refs.as_slice().to_vec();
+                *refs.get_mut(index)? = *element;
+                Some(PropValue::ContentRefs(Rc::new(refs.into())))
+            }
+            (PropValue::AnnotatedContentRefs(refs), PropValue::AnnotatedContentRefs(element)) => {
+                let [element] = element.as_slice() else {
+                    return None;
+                };
+                let mut refs = refs.as_slice().to_vec();
+                *refs.get_mut(index)? = *element;
+                Some(PropValue::AnnotatedContentRefs(Rc::new(
+                    AnnotatedContentRefs::from_vec(refs),
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A prop whose value is a single element, or a range of elements, of an array prop of the same component.
+///
+/// The prop depends on the entire array. When inverting, it requests that the array
+/// be changed to a copy with the selected elements replaced.
+///
+/// Constructor:
+/// - `new(array_local_prop_idx, index, array_type)`: create a prop for the element(s)
+///   selected by `index` from the array prop with index `array_local_prop_idx`,
+///   whose value is an array of type `array_type`.
+#[derive(Debug)]
+pub struct ArrayElementProp {
+    array_local_prop_idx: LocalPropIdx,
+    index: PathIndex,
+    array_type: ArrayType,
+}
+
+impl ArrayElementProp {
+    pub fn new(
+        array_local_prop_idx: LocalPropIdx,
+        index: PathIndex,
+        array_type: ArrayType,
+    ) -> Self {
+        ArrayElementProp {
+            array_local_prop_idx,
+            index,
+            array_type,
+        }
+    }
+}
+
+impl PropUpdaterUntyped for ArrayElementProp {
+    fn default(&self) -> PropValue {
+        self.array_type.default_indexed_value(self.index)
+    }
+
+    fn data_queries(&self) -> Vec<DataQuery> {
+        vec![DataQuery::Prop {
+            source: PropSource::Me,
+            prop_specifier: self.array_local_prop_idx.into(),
+        }]
+    }
+
+    fn calculate_untyped(&self, data: DataQueryResults) -> PropCalcResult<PropValue> {
+        let array = &data.vec[0].values[0];
+        if !array.changed {
+            return PropCalcResult::NoChange;
+        }
+
+        let element = array
+            .value
+            .get_indexed_array_value(self.index)
+            .unwrap_or_else(|| self.default());
+
+        // Propagate `came_from_default` from the array.
+        if array.came_from_default {
+            PropCalcResult::FromDefault(element)
+        } else {
+            PropCalcResult::Calculated(element)
+        }
+    }
+
+    fn invert_untyped(
+        &self,
+        data: DataQueryResults,
+        requested_value: PropValue,
+        _is_direct_change_from_action: bool,
+    ) -> Result<DataQueryResults, InvertError> {
+        let mut desired = data.with_reset_meta();
+
+        let array = &mut desired.vec[0].values[0];
+        array.value = array
+            .value
+            .with_indexed_array_value(self.index, requested_value)
+            .ok_or(InvertError::CouldNotUpdate)?;
+        array.changed = true;
+
+        Ok(desired)
+    }
+}
+
+#[cfg(test)]
+#[path = "array_prop.test.rs"]
+mod test;
@@ -0,0 +1,207 @@
+use crate::{
+    components::types::ComponentIdx,
+    props::{DataQueryResult, cache::PropWithMeta},
+    state::types::math_expr::JsMathExpr,
+};
+
+use super::*;
+
+fn math_vector(s: &str) -> PropValue {
+    PropValue::Math(Rc::new(MathExpr {
+        math_object: JsMathExpr(s.to_string()),
+    }))
+}
+
+fn array_data(value: PropValue, came_from_default: bool) -> DataQueryResults {
+    DataQueryResults::from_vec(vec![DataQueryResult {
+        values: vec![PropWithMeta {
+            value,
+            came_from_default,
+            changed: true,
+            origin: None,
+        }],
+    }])
+}
+
+#[test]
+fn can_get_elements_of_arrays() {
+    let coords = math_vector("[\"vector\",3,-1]");
+    assert_eq!(
+        coords.get_array_element(0),
+        Some(PropValue::from(MathExpr::from(3.0)))
+    );
+    assert_eq!(
+        coords.get_array_element(1),
+        Some(PropValue::from(MathExpr::from(-1.0)))
+    );
+    assert_eq!(coords.get_array_element(2), None);
+
+    // A scalar is an array with a single element
+    let scalar = PropValue::from(MathExpr::from(5.0));
+    assert_eq!(scalar.get_array_element(0), Some(scalar.clone()));
+    assert_eq!(scalar.get_array_element(1), None);
+
+    let refs =
+        PropValue::ComponentRefs(Rc::new(crate::state::types::component_refs::ComponentRefs(
+            vec![ComponentIdx::new(4), ComponentIdx::new(7)],
+        )));
+    assert_eq!(
+        refs.get_array_element(1),
+        Some(PropValue::ComponentRef(Some(ComponentRef(
+            ComponentIdx::new(7)
+        ))))
+    );
+
+    // Props of other types are not arrays
+    assert_eq!(PropValue::Boolean(true).get_array_element(0), None);
+}
+
+#[test]
+fn can_replace_elements_of_arrays() {
+    let coords = math_vector("[\"vector\",3,-1]");
+    assert_eq!(
+        coords.with_array_element(1, MathExpr::from(2.0).into()),
+        Some(math_vector("[\"vector\",3,2]"))
+    );
+    assert_eq!(
+        coords.with_array_element(2, MathExpr::from(2.0).into()),
+        None
+    );
+    assert_eq!(coords.with_array_element(0, PropValue::Boolean(true)), None);
+}
+
+#[test]
+fn array_element_prop_depends_on_array() {
+    let array_local_prop_idx = LocalPropIdx::new(3);
+    let prop = ArrayElementProp::new(array_local_prop_idx, PathIndex::Single(2), ArrayType::Math);
+
+    match &prop.data_queries()[..] {
+        [
+            DataQuery::Prop {
+                source: PropSource::Me,
+                prop_specifier: super::super::PropSpecifier::LocalIdx(idx),
+            },
+        ] => assert_eq!(*idx, array_local_prop_idx),
+        _ => panic!("Incorrect query"),
+    }
+}
+
+#[test]
+fn array_element_prop_calculates_element() {
+    let prop = ArrayElementProp::new(LocalPropIdx::new(0), PathIndex::Single(2), ArrayType::Math);
+
+    let data = array_data(math_vector("[\"vector\",3,-1]"), false);
+    match prop.calculate_untyped(data) {
+        PropCalcResult::Calculated(value) => assert_eq!(value, MathExpr::from(-1.0).into()),
+        _ => panic!("Incorrect result"),
+    }
+
+    let data = array_data(math_vector("[\"vector\",0,0]"), true);
+    match prop.calculate_untyped(data) {
+        PropCalcResult::FromDefault(value) => assert_eq!(value, MathExpr::from(0.0).into()),
+        _ => panic!("Incorrect result"),
+    }
+
+    // Out of range elements get the default value
+    let prop = ArrayElementProp::new(LocalPropIdx::new(0), PathIndex::Single(6), ArrayType::Math);
+    let data = array_data(math_vector("[\"vector\",3,-1]"), false);
+    match prop.calculate_untyped(data) {
+        PropCalcResult::Calculated(value) => assert_eq!(value, MathExpr::default().into()),
+        _ => panic!("Incorrect result"),
+    }
+}
+
+#[test]
+fn array_element_prop_inverts_by_changing_element() {
+    let prop = ArrayElementProp::new(LocalPropIdx::new(0), PathIndex::Single(1), ArrayType::Math);
+
+    let data = array_data(math_vector("[\"vector\",3,-1]"), false);
+    let desired = prop
+        .invert_untyped(data, MathExpr::from(8.0).into(), true)
+        .unwrap();
+    let array = &desired.vec[0].values[0];
+    assert_eq!(array.value, math_vector("[\"vector\",8,-1]"));
+    assert!(array.changed);
+
+    let prop = ArrayElementProp::new(LocalPropIdx::new(0), PathIndex::Single(3), ArrayType::Math);
+    let data = array_data(math_vector("[\"vector\",3,-1]"), false);
+    assert!(
+        prop.invert_untyped(data, MathExpr::from(8.0).into(), true)
+            .is_err()
+    );
+}
+
+#[test]
+fn can_index_arrays_from_the_end_and_by_ranges() {
+    let refs = PropValue::ComponentRefs(Rc::new(ComponentRefs(vec![
+        ComponentIdx::new(4),
+        ComponentIdx::new(7),
+        ComponentIdx::new(9),
+    ])));
+    assert_eq!(refs.array_len(), Some(3));
+    assert_eq!(
+        refs.get_indexed_array_value(PathIndex::Single(-1)),
+        Some(PropValue::ComponentRef(Some(ComponentRef(
+            ComponentIdx::new(9)
+        ))))
+    );
+    assert_eq!(
+        refs.get_indexed_array_value(PathIndex::Range {
+            start: Some(2),
+            end: None
+        }),
+        Some(PropValue::ComponentRefs(Rc::new(ComponentRefs(vec![
+            ComponentIdx::new(7),
+            ComponentIdx::new(9),
+        ]))))
+    );
+    assert_eq!(refs.get_indexed_array_value(PathIndex::Single(-4)), None);
+
+    // A range is replaced by an array with the same number of elements
+    let replacement = PropValue::ComponentRefs(Rc::new(ComponentRefs(vec![
+        ComponentIdx::new(1),
+        ComponentIdx::new(2),
+    ])));
+    let range = PathIndex::Range {
+        start: Some(1),
+        end: Some(-2),
+    };
+    assert_eq!(
+        refs.with_indexed_array_value(range, replacement.clone()),
+        Some(PropValue::ComponentRefs(Rc::new(ComponentRefs(vec![
+            ComponentIdx::new(1),
+            ComponentIdx::new(2),
+            ComponentIdx::new(9),
+        ]))))
+    );
+    assert_eq!(
+        refs.with_indexed_array_value(
+            PathIndex::Range {
+                start: None,
+                end: None
+            },
+            replacement
+        ),
+        None
+    );
+}
+
+#[test]
+fn out_of_range_ranges_are_empty_arrays() {
+    let range = PathIndex::Range {
+        start: Some(3),
+        end: Some(4),
+    };
+    let prop = ArrayElementProp::new(LocalPropIdx::new(0), range, ArrayType::ComponentRefs);
+    let refs = PropValue::ComponentRefs(Rc::new(ComponentRefs(vec![ComponentIdx::new(4)])));
+
+    match prop.calculate_untyped(array_data(refs, false)) {
+        PropCalcResult::Calculated(value) => {
+            assert_eq!(
+                value,
+                PropValue::ComponentRefs(Rc::new(ComponentRefs(Vec::new())))
+            )
+        }
+        _ => panic!("Expected a calculated value"),
+    }
+}
@@ -1,6 +1,6 @@
 //! Allow for the caching of props (and state props and strings).
 
-use std::{borrow, cell::RefCell};
+use std::{borrow, cell::RefCell, convert::Infallible};
 
 use crate::{
     components::prelude::{GraphNode, PropCalcResult, PropValue},
@@ -97,20 +97,21 @@ impl CachedProp {
 
     /// Get the value of the prop. If the cached value is not fresh,
     /// the `calculate` function will be called to get the value.
+    /// If `calculate` fails, its error is returned and the cached value is unchanged.
     ///
     /// This function uses interior mutability to cache computed values.
-    pub fn get_value<CalculateFn: FnOnce() -> PropCalcResult<PropValue>>(
+    pub fn get_value<E, CalculateFn: FnOnce() -> Result<PropCalcResult<PropValue>, E>>(
         &self,
         calculate: CalculateFn,
-    ) -> PropValue {
+    ) -> Result<PropValue, E> {
         match self.get_status() {
-            PropStatus::Fresh => self.get_cached_value().expect(
+            PropStatus::Fresh => Ok(self.get_cached_value().expect(
                 "Prop is marked as Fresh but no value is cached. This state should be unreachable",
-            ),
+            )),
             PropStatus::Stale | PropStatus::Resolved => {
-                let result = calculate();
+                let result = calculate()?;
                 self.set_value(result);
-                self.get_cached_value().unwrap()
+                Ok(self.get_cached_value().unwrap())
             }
             PropStatus::Unresolved => {
                 panic!("Cannot get value of prop with Unresolved status")
@@ -204,6 +205,15 @@ impl PropCache {
             })
     }
 
+    /// Get the change counter of a prop, i.e., the number of times its value has been set.
+    /// Returns `0` if no value has ever been cached for the prop.
+    pub fn get_change_counter<A: borrow::Borrow<GraphNode>>(&self, prop_node: A) -> u32 {
+        self.store
+            .borrow()
+            .get_tag(prop_node.borrow())
+            .map_or(0, |cached_prop| cached_prop.get_change_counter())
+    }
+
     /// Set the status of a prop.
     pub fn set_prop_status<A: borrow::Borrow<GraphNode>>(&self, prop_node: A, status: PropStatus) {
         let prop_node = prop_node.borrow();
@@ -240,6 +250,23 @@ impl PropCache {
         origin: B,
         calculate: CalculateFn,
     ) -> PropWithMeta {
+        let Ok::<_, Infallible>(prop) = self._get_prop(prop_node, origin, || Ok(calculate()), true);
+        prop
+    }
+
+    /// Get the value of a prop as in [`PropCache::get_prop`], but with a `calculate` function that may fail.
+    /// If `calculate` fails, its error is returned and the cached value is unchanged.
+    pub fn try_get_prop<
+        E,
+        CalculateFn: FnOnce() -> Result<PropCalcResult<PropValue>, E>,
+        A: borrow::Borrow<GraphNode>,
+        B: borrow::Borrow<GraphNode>,
+    >(
+        &self,
+        prop_node: A,
+        origin: B,
+        calculate: CalculateFn,
+    ) -> Result<PropWithMeta, E> {
         self._get_prop(prop_node, origin, calculate, true)
     }
 
@@ -257,6 +284,24 @@ impl PropCache {
         origin: B,
         calculate: CalculateFn,
     ) -> PropWithMeta {
+        let Ok::<_, Infallible>(prop) =
+            self._get_prop(prop_node, origin, || Ok(calculate()), false);
+        prop
+    }
+
+    /// Get the value of a prop as in [`PropCache::get_prop_untracked`], but with a `calculate` function that may fail.
+    /// If `calculate` fails, its error is returned and the cached value is unchanged.
+    pub fn try_get_prop_untracked<
+        E,
+        CalculateFn: FnOnce() -> Result<PropCalcResult<PropValue>, E>,
+        A: borrow::Borrow<GraphNode>,
+        B: borrow::Borrow<GraphNode>,
+    >(
+        &self,
+        prop_node: A,
+        origin: B,
+        calculate: CalculateFn,
+    ) -> Result<PropWithMeta, E> {
         self._get_prop(prop_node, origin, calculate, false)
     }
 
@@ -270,12 +315,13 @@ impl PropCache {
         prop_node: A,
         origin: B,
     ) -> PropWithMeta {
-        self._get_prop(
+        let Ok::<_, Infallible>(prop) = self._get_prop(
             prop_node,
             origin,
             || panic!("Call to `get_prop_unchecked` on a prop that isn't `Fresh`"),
             true,
-        )
+        );
+        prop
     }
 
     /// Get the cached value of a prop. An error is thrown if the prop is not `Fresh`.
@@ -292,17 +338,19 @@ impl PropCache {
         prop_node: A,
         origin: B,
     ) -> PropWithMeta {
-        self._get_prop(
+        let Ok::<_, Infallible>(prop) = self._get_prop(
             prop_node,
             origin,
             || panic!("Call to `get_prop_unchecked_untracked` on a prop that isn't `Fresh`"),
             false,
-        )
+        );
+        prop
     }
 
     /// Internal version `get_prop`. Can optionally update the change tracker or not when retrieving the prop.
     fn _get_prop<
-        CalculateFn: FnOnce() -> PropCalcResult<PropValue>,
+        E,
+        CalculateFn: FnOnce() -> Result<PropCalcResult<PropValue>, E>,
         A: borrow::Borrow<GraphNode>,
         B: borrow::Borrow<GraphNode>,
     >(
@@ -311,7 +359,7 @@ impl PropCache {
         origin: B,
         calculate: CalculateFn,
         update_change_tracker: bool,
-    ) -> PropWithMeta {
+    ) -> Result<PropWithMeta, E> {
         let prop_node = prop_node.borrow();
         let origin: GraphNode = *origin.borrow();
         let change_tracker_key = (*prop_node, origin);
@@ -329,7 +377,7 @@ impl PropCache {
         let store = self.store.borrow();
 
         let cached_prop = store.get_tag(prop_node).unwrap();
-        let value = cached_prop.get_value(calculate);
+        let value = cached_prop.get_value(calculate)?;
         let came_from_default = cached_prop.get_came_from_default();
 
         // Every time a value is changed, `change_counter` is incremented. We use
@@ -344,12 +392,12 @@ impl PropCache {
             change_tracker.insert(change_tracker_key, change_counter);
         }
 
-        PropWithMeta {
+        Ok(PropWithMeta {
             value,
             came_from_default,
             changed,
             origin: Some(*prop_node),
-        }
+        })
     }
 
     /// Set the value of a prop. `origin` is the `GraphNode::DataQuery` that requested the prop.
@@ -366,6 +414,25 @@ impl PropCache {
         let cached_prop = store.get_tag(prop_node).unwrap();
         cached_prop.set_value(result);
     }
+
+    /// Remove the cached value of a prop, along with the record of which queries have seen it.
+    pub fn remove_prop<A: borrow::Borrow<GraphNode>>(&self, prop_node: A) {
+        let prop_node = prop_node.borrow();
+        self.store.borrow_mut().remove_tag(prop_node);
+        self.change_tracker.borrow_mut().remove_first(prop_node);
+    }
+
+    /// Forget which prop values have been seen by the query `origin`.
+    pub fn remove_origin<A: borrow::Borrow<GraphNode>>(&self, origin: A) {
+        self.change_tracker
+            .borrow_mut()
+            .remove_second(origin.borrow());
+    }
+
+    /// The nodes of all props that have a cached value.
+    pub fn prop_nodes(&self) -> Vec<GraphNode> {
+        self.store.borrow().keys().collect()
+    }
 }
 
 impl Default for PropCache {
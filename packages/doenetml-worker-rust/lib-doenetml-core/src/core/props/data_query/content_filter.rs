@@ -43,7 +43,7 @@ impl ApplyTest<FilterData<'_>, GraphNode> for ContentFilter {
             ContentFilter::IsType(type_name) => match node {
                 GraphNode::Component(_) => {
                     let component_type = document_model.get_component_type(node);
-                    component_type.eq(type_name)
+                    component_type.is_ok_and(|component_type| component_type.eq(type_name))
                 }
                 _ => false,
             },
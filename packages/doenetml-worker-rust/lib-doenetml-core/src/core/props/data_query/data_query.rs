@@ -5,6 +5,7 @@ use crate::{
         prelude::{ComponentIdx, LocalPropIdx},
         types::AttributeName,
     },
+    document_flags::DocumentFlag,
     graph_node::GraphNode,
 };
 
@@ -135,6 +136,9 @@ pub enum DataQuery {
     /// ```
     /// This query will return all children of the querying component that are `<section>` components
     /// and have a prop matching the `PropProfile::Hidden` profile.
+    ///
+    /// If the filter depends on the values of props (e.g., through `ContentFilter::HasPropMatchingProfileAndCondition`),
+    /// the children are matched again whenever those props change, and the dependencies of the query are updated.
     ContentRefs {
         /// Children of this component will be searched
         container: PropSource,
@@ -167,6 +171,9 @@ pub enum DataQuery {
     },
 
     /// Query multiple components and pick a prop from each.
+    ///
+    /// Props are picked by their profiles, which do not change, so the picked props are determined
+    /// once, when the query is added.
     PickProp {
         /// Where to find the component's whose props will be searched.
         source: PickPropSource,
@@ -175,7 +182,8 @@ pub enum DataQuery {
         prop_specifier: PropSpecifier,
     },
 
-    /// Query for all children of an attribute that match the prescribed `PropProfile`
+    /// Query for all children of an attribute that match the prescribed `PropProfile`.
+    /// As with [`DataQuery::PickProp`], the children are matched once, when the query is added.
     Attribute {
         /// The name of the attribute whose children will be matched.
         attribute_name: AttributeName,
@@ -191,6 +199,10 @@ pub enum DataQuery {
     /// Query for a reference to "self", the component making the query.
     SelfRef,
 
+    /// Query for a flag of the document, as set by the host application (see [`DocumentFlags`](crate::document_flags::DocumentFlags)).
+    /// Results in a `prop_type::Boolean`, or a `prop_type::String` for `DocumentFlag::SolutionDisplayMode`.
+    DocumentFlag(DocumentFlag),
+
     #[default]
     /// A data query that cannot be resolved. This is used as a dependency of other data queries.
     Null,
@@ -1,5 +1,6 @@
 use crate::{
     components::ComponentVariantPropTypes,
+    document_flags::DocumentFlag,
     props::{PropProfile, prop_profile_to_type},
 };
 
@@ -38,6 +39,10 @@ impl DataQuery {
             }
             DataQuery::State => Err(()),
             DataQuery::SelfRef => Ok(vec![PropValueType::ComponentRef]),
+            DataQuery::DocumentFlag(DocumentFlag::SolutionDisplayMode) => {
+                Ok(vec![PropValueType::String])
+            }
+            DataQuery::DocumentFlag(_) => Ok(vec![PropValueType::Boolean]),
             DataQuery::Null => Err(()),
         }
     }
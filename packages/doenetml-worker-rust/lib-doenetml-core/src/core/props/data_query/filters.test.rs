@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use super::super::*;
 use super::*;
-use crate::{Core, test_utils::*};
+use crate::{Core, components::types::ComponentIdx, test_utils::*};
 
 #[test]
 fn can_apply_test_on_an_op() {
@@ -85,7 +85,7 @@ fn can_filter_by_component_or_string() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
     let document_model = &core.document_model;
-    let content_children = document_model.get_component_content_children(0);
+    let content_children = document_model.get_component_content_children(0).unwrap();
 
     // Filter for single element
     let filter = ContentFilter::IsComponent.bind(query_node, document_model);
@@ -120,7 +120,7 @@ fn can_filter_by_profile() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
     let document_model = &core.document_model;
-    let content_children = document_model.get_component_content_children(0);
+    let content_children = document_model.get_component_content_children(0).unwrap();
 
     let filter = ContentFilter::IsType("division").bind(query_node, document_model);
     let section_node = content_children
@@ -161,9 +161,9 @@ fn can_filter_by_profile_and_condition() {
     core.init_from_dast_root(&dast_root);
     // We need to call `to_flat_dast` so that the `value` prop of our `<text>` components
     // gets resolved. Otherwise we cannot `apply_test` without failing on an unresolved prop.
-    core.to_flat_dast();
+    core.to_flat_dast().unwrap();
     let document_model = &core.document_model;
-    let content_children = document_model.get_component_content_children(0);
+    let content_children = document_model.get_component_content_children(0).unwrap();
 
     let str_me = PropValue::String(Rc::new("me".to_string()));
     let str_you = PropValue::String(Rc::new("you".to_string()));
@@ -207,7 +207,7 @@ fn can_accumulate_deps_for_simple_filter() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
     let document_model = &core.document_model;
-    let content_children = document_model.get_component_content_children(0);
+    let content_children = document_model.get_component_content_children(0).unwrap();
 
     let str_me = PropValue::String(Rc::new("me".to_string()));
 
@@ -234,8 +234,10 @@ fn can_accumulate_deps_for_simple_filter() {
                 // Never any deps for a non-component node
                 return vec![];
             }
-            let prop =
-                document_model.get_component_prop_by_profile(&n.into(), &[PropProfile::String]);
+            let prop = document_model.get_component_prop_by_profile(
+                ComponentIdx::try_from(n).unwrap(),
+                &[PropProfile::String],
+            );
             vec![document_model.prop_pointer_to_prop_node(prop.unwrap())]
         })
         .collect::<Vec<_>>();
@@ -251,7 +253,7 @@ fn can_accumulate_deps_for_compound_filter() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
     let document_model = &core.document_model;
-    let content_children = document_model.get_component_content_children(0);
+    let content_children = document_model.get_component_content_children(0).unwrap();
 
     let str_me = PropValue::String(Rc::new("me".to_string()));
 
@@ -285,10 +287,14 @@ fn can_accumulate_deps_for_compound_filter() {
                 // Never any deps for a non-component node
                 return vec![];
             }
-            let prop1 =
-                document_model.get_component_prop_by_profile(&n.into(), &[PropProfile::Hidden]);
-            let prop2 =
-                document_model.get_component_prop_by_profile(&n.into(), &[PropProfile::String]);
+            let prop1 = document_model.get_component_prop_by_profile(
+                ComponentIdx::try_from(n).unwrap(),
+                &[PropProfile::Hidden],
+            );
+            let prop2 = document_model.get_component_prop_by_profile(
+                ComponentIdx::try_from(n).unwrap(),
+                &[PropProfile::String],
+            );
             vec![
                 document_model.prop_pointer_to_prop_node(prop1.unwrap()),
                 document_model.prop_pointer_to_prop_node(prop2.unwrap()),
@@ -1,5 +1,6 @@
 //! Objects related to `Prop`s
 
+mod array_prop;
 pub mod cache;
 mod data_query;
 mod prop;
@@ -11,6 +12,7 @@ mod state_cache;
 mod string_cache;
 pub mod traits;
 
+pub use array_prop::*;
 pub use data_query::*;
 pub use prop::*;
 pub use prop_profile::*;
@@ -56,6 +56,16 @@ pub trait PropUpdaterUntyped: std::fmt::Debug {
     /// Results of this function will be cached, so local caching is not needed.
     fn calculate_untyped(&self, data: DataQueryResults) -> PropCalcResult<PropValue>;
 
+    /// Return messages warning the author about problems with `data`, e.g., an attribute value that could not be parsed.
+    ///
+    /// The warnings are checked when the component is created. `data` has a result for each of the `data_queries`
+    /// of this prop: an `Attribute` query whose attribute was given as plain text in the source has that text
+    /// as its value, and all other queries have no values.
+    /// The warnings are reported in `FlatDastRoot.warnings` at the position of the attribute.
+    fn warnings(&self, _data: &DataQueryResults) -> Vec<String> {
+        Vec::new()
+    }
+
     /// All props know how to calculate their value given their dependencies.
     /// Sometimes a prop is requested to take on a particular value. If the
     /// prop has dependencies, these dependencies must change in order for the
@@ -80,6 +90,18 @@ pub trait PropUpdaterUntyped: std::fmt::Debug {
     ) -> Result<DataQueryResults, InvertError> {
         Err(InvertError::NotImplemented)
     }
+
+    /// If this prop is an array prop, return an updater for the element at (zero-based) `index`.
+    /// The element will be a prop of the same component as this prop,
+    /// and its type must be the `array_element_type` of this prop's type.
+    ///
+    /// If `None` is returned, an [`ArrayElementProp`](super::ArrayElementProp), which depends on
+    /// the entire array, is used. Return a custom updater to give each element its own dependencies
+    /// (so that an element is not recalculated when a different element changes).
+    #[allow(unused)]
+    fn element_updater(&self, index: usize) -> Option<UpdaterObject> {
+        None
+    }
 }
 
 /// A typed version of [`PropUpdaterUntyped`]. This trait is what most component's props
@@ -110,6 +132,16 @@ pub trait PropUpdater {
     /// Results of this function will be cached, so local caching is not needed.
     fn calculate(&self, data: DataQueryResults) -> PropCalcResult<Self::PropType>;
 
+    /// Return messages warning the author about problems with `data`, e.g., an attribute value that could not be parsed.
+    ///
+    /// The warnings are checked when the component is created. `data` has a result for each of the `data_queries`
+    /// of this prop: an `Attribute` query whose attribute was given as plain text in the source has that text
+    /// as its value, and all other queries have no values.
+    /// The warnings are reported in `FlatDastRoot.warnings` at the position of the attribute.
+    fn warnings(&self, _data: &DataQueryResults) -> Vec<String> {
+        Vec::new()
+    }
+
     /// All props know how to calculate their value given their dependencies.
     /// Sometimes a prop is requested to take on a particular value. If the
     /// prop has dependencies, these dependencies must change in order for the
@@ -134,6 +166,18 @@ pub trait PropUpdater {
     ) -> Result<DataQueryResults, InvertError> {
         Err(InvertError::NotImplemented)
     }
+
+    /// If this prop is an array prop, return an updater for the element at (zero-based) `index`.
+    /// The element will be a prop of the same component as this prop,
+    /// and its type must be the `array_element_type` of this prop's type.
+    ///
+    /// If `None` is returned, an [`ArrayElementProp`](super::ArrayElementProp), which depends on
+    /// the entire array, is used. Return a custom updater to give each element its own dependencies
+    /// (so that an element is not recalculated when a different element changes).
+    #[allow(unused)]
+    fn element_updater(&self, index: usize) -> Option<UpdaterObject> {
+        None
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////////////
@@ -173,6 +217,9 @@ const _: () = {
                 PropCalcResult::NoChange
             }
         }
+        fn warnings(&self, data: &DataQueryResults) -> Vec<String> {
+            Self::warnings(self, data)
+        }
         fn invert_untyped(
             &self,
             data: DataQueryResults,
@@ -186,6 +233,9 @@ const _: () = {
                 is_direct_change_from_action,
             )
         }
+        fn element_updater(&self, index: usize) -> Option<UpdaterObject> {
+            <T as PropUpdater>::element_updater(self, index)
+        }
     }
 
     /// Anonymous trait to implement `PropUpdaterUntyped` for types that implement `PropUpdater`.
@@ -194,12 +244,14 @@ const _: () = {
         fn default(&self) -> PropValue;
         fn data_queries(&self) -> Vec<DataQuery>;
         fn calculate_untyped(&self, data: DataQueryResults) -> PropCalcResult<PropValue>;
+        fn warnings(&self, data: &DataQueryResults) -> Vec<String>;
         fn invert_untyped(
             &self,
             data: DataQueryResults,
             requested_value: PropValue,
             is_direct_change_from_action: bool,
         ) -> Result<DataQueryResults, InvertError>;
+        fn element_updater(&self, index: usize) -> Option<UpdaterObject>;
     }
 
     /// The generic implementation for `PropUpdaterUntyped`.
@@ -219,6 +271,9 @@ const _: () = {
         fn data_queries(&self) -> Vec<DataQuery> {
             <Self as _PropUpdaterUntyped<<T as PropUpdater>::PropType>>::data_queries(self)
         }
+        fn warnings(&self, data: &DataQueryResults) -> Vec<String> {
+            <Self as _PropUpdaterUntyped<<T as PropUpdater>::PropType>>::warnings(self, data)
+        }
         fn invert_untyped(
             &self,
             data: DataQueryResults,
@@ -232,6 +287,11 @@ const _: () = {
                 is_direct_change_from_action,
             )
         }
+        fn element_updater(&self, index: usize) -> Option<UpdaterObject> {
+            <Self as _PropUpdaterUntyped<<T as PropUpdater>::PropType>>::element_updater(
+                self, index,
+            )
+        }
     }
 };
 
@@ -41,13 +41,11 @@ pub enum PropValue {
     Boolean(prop_type::Boolean),
     #[serde(with = "rc_serde")]
     Math(prop_type::Math),
-    // TODO: when create array props, convert this to use the general array mechanism
-    // Created a vector type for now.
+    // Can be an array prop, whose elements are `ComponentRef`s (see `array_element_type`).
     #[serde(with = "rc_serde")]
     ComponentRefs(prop_type::ComponentRefs),
     ComponentRef(prop_type::ComponentRef),
-    // TODO: when create array props, convert this to use the general array mechanism
-    // Created a vector type for now.
+    // Can be an array prop, whose elements are single-entry `AnnotatedContentRefs` (see `array_element_type`).
     #[serde(with = "rc_serde")]
     AnnotatedContentRefs(prop_type::AnnotatedContentRefs),
     #[serde(with = "rc_serde")]
@@ -2,11 +2,11 @@
 
 use std::{borrow, cell::Cell};
 
-use crate::core::graph_node::GraphNode;
+use crate::core::{error::CoreError, graph_node::GraphNode};
 
 use super::{
     PropCalcResult, PropValue,
-    cache::{PropCache, PropWithMeta},
+    cache::{PropCache, PropStatus, PropWithMeta},
 };
 
 /// Storage for state props that facilitates setting, retrieval, and tracking of changes
@@ -57,6 +57,41 @@ impl StateCache {
         })
     }
 
+    /// Get the value of a state prop without updating the change tracker.
+    /// The change state will be the same as the last time the state was queried from `origin`.
+    ///
+    /// Returns an error if the state prop hasn't been set.
+    pub fn get_state_untracked<A: borrow::Borrow<GraphNode>, B: borrow::Borrow<GraphNode>>(
+        &self,
+        state_node: A,
+        origin: B,
+    ) -> Result<PropWithMeta, CoreError> {
+        let state_node = state_node.borrow();
+        // A state prop is `Fresh` from the time it is set.
+        match self.prop_cache.get_prop_status(state_node) {
+            PropStatus::Fresh => Ok(self
+                .prop_cache
+                .get_prop_unchecked_untracked(state_node, origin)),
+            _ => Err(CoreError::StateNotSet(*state_node)),
+        }
+    }
+
+    /// The nodes of all state items that have been created and not removed.
+    pub fn get_state_nodes(&self) -> Vec<GraphNode> {
+        self.prop_cache.prop_nodes()
+    }
+
+    /// Remove a state item, e.g., because the component it belongs to was removed.
+    /// Its index is not reused.
+    pub fn remove_state<A: borrow::Borrow<GraphNode>>(&self, state_node: A) {
+        self.prop_cache.remove_prop(state_node);
+    }
+
+    /// Forget which state values have been seen by the query `origin`.
+    pub fn remove_origin<A: borrow::Borrow<GraphNode>>(&self, origin: A) {
+        self.prop_cache.remove_origin(origin);
+    }
+
     /// Set the value of a state prop. `origin` is the `GraphNode::DataQuery` that requested the state prop.
     /// The store tracks and reports if the value has changed since the last time it was queried.
     pub fn set_state<A: borrow::Borrow<GraphNode>>(&self, state_node: A, value: PropValue) {
@@ -64,6 +99,24 @@ impl StateCache {
         self.prop_cache
             .set_prop(state_node, PropCalcResult::Calculated(value));
     }
+
+    /// Set the value of a state prop, marking whether it `came_from_default`,
+    /// e.g., to restore a state to the value it had before an action.
+    /// The store tracks and reports if the value has changed since the last time it was queried.
+    pub fn restore_state<A: borrow::Borrow<GraphNode>>(
+        &self,
+        state_node: A,
+        value: PropValue,
+        came_from_default: bool,
+    ) {
+        let state_node = state_node.borrow();
+        let value = if came_from_default {
+            PropCalcResult::FromDefault(value)
+        } else {
+            PropCalcResult::Calculated(value)
+        };
+        self.prop_cache.set_prop(state_node, value);
+    }
 }
 
 impl Default for StateCache {
@@ -79,3 +79,37 @@ fn test_state_props_came_from_default_becomes_false() {
     assert_eq!(val.value, PropValue::Integer(0));
     assert_eq!(val.came_from_default, false);
 }
+
+#[test]
+fn removed_state_props_are_not_listed() {
+    let cache = StateCache::new();
+
+    let idx1 = cache.add_state(PropValue::Integer(1), true);
+    let idx2 = cache.add_state(PropValue::Integer(2), true);
+    cache.remove_state(GraphNode::State(idx1));
+
+    assert_eq!(cache.get_state_nodes(), vec![GraphNode::State(idx2)]);
+
+    // Indices of removed state props are not reused
+    let idx3 = cache.add_state(PropValue::Integer(3), true);
+    assert_eq!(idx3, idx2 + 1);
+}
+
+#[test]
+fn untracked_state_that_was_never_set_is_an_error() {
+    let cache = StateCache::new();
+
+    let idx = cache.add_state(PropValue::Integer(1), true);
+    let unset_node = GraphNode::State(idx + 1);
+
+    assert_eq!(
+        cache.get_state_untracked(unset_node, unset_node),
+        Err(CoreError::StateNotSet(unset_node))
+    );
+    assert_eq!(
+        cache
+            .get_state_untracked(GraphNode::State(idx), GraphNode::State(idx))
+            .map(|state| state.value),
+        Ok(PropValue::Integer(1))
+    );
+}
@@ -72,6 +72,12 @@ impl StringCache {
         }
     }
 
+    /// Returns `true` if the string has been changed (e.g., by an action) since it was added.
+    pub fn is_modified<A: borrow::Borrow<GraphNode>>(&self, string_node: A) -> bool {
+        // `add_string` sets the value once, so any later change increments the counter past `1`.
+        self.prop_cache.get_change_counter(string_node) > 1
+    }
+
     /// Set the value of a string prop.
     /// The store tracks and reports if the value has changed since the last time it was queried.
     pub fn set_string<A: borrow::Borrow<GraphNode>>(&self, string_node: A, s: String) {
@@ -79,6 +85,17 @@ impl StringCache {
         self.prop_cache
             .set_prop(string_node, PropCalcResult::Calculated(s.into()));
     }
+
+    /// Remove a string prop, e.g., because the text it came from was removed from the source.
+    /// Its index is not reused.
+    pub fn remove_string<A: borrow::Borrow<GraphNode>>(&self, string_node: A) {
+        self.prop_cache.remove_prop(string_node);
+    }
+
+    /// Forget which string values have been seen by the query `origin`.
+    pub fn remove_origin<A: borrow::Borrow<GraphNode>>(&self, origin: A) {
+        self.prop_cache.remove_origin(origin);
+    }
 }
 
 impl Default for StringCache {
@@ -0,0 +1,404 @@
+//! # Scheduler
+//!
+//! Gives the document a notion of time, so that components (e.g., animations) can change the document
+//! without the user interacting with it.
+//!
+//! The document has its own clock, which only moves when the host advances it with [`Core::advance_time`]
+//! (by a given number of milliseconds) or [`Core::tick`] (by the wall-clock time since the previous tick).
+//! While the clock advances, the actions that were scheduled to happen in that time are sent to their components,
+//! in the order of the times they were scheduled for.
+//!
+//! Actions are scheduled by components, by returning [`UpdateFromAction::ScheduleAction`] from an action,
+//! or by the host with [`Core::schedule_action`]. Because the clock is only advanced by the host,
+//! the same calls produce the same document, which makes time-driven behavior testable and replayable.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::{
+        ActionsEnum,
+        types::{Action, ComponentIdx},
+    },
+    error::CoreError,
+};
+
+use super::{
+    Core,
+    action_recording::RecordedEvent,
+    dispatch_action::{ActionResult, PropUpdateOutcome},
+    document_model::ComponentChanges,
+};
+
+/// The most scheduled actions that one call to [`Core::advance_time`] sends,
+/// so that actions that keep scheduling each other cannot stall the host.
+pub const MAX_ACTIONS_PER_ADVANCE: usize = 10_000;
+
+/// Identifies an action scheduled with [`Core::schedule_action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct ScheduledActionId(usize);
+
+/// An action waiting for the document's clock to reach `time`.
+#[derive(Debug, Clone)]
+struct ScheduledAction {
+    component_idx: ComponentIdx,
+    /// The action, serialized so that a repeating action can be sent more than once.
+    action: serde_json::Value,
+    /// The time on the document's clock when the action is sent.
+    time: f64,
+    /// If set, the action is sent again every `interval` milliseconds.
+    interval: Option<f64>,
+}
+
+/// The clock of the document and the actions waiting to be sent.
+#[derive(Debug)]
+pub struct Scheduler {
+    /// The time on the document's clock, in milliseconds since `Core` was initialized.
+    time: f64,
+    scheduled_actions: BTreeMap<ScheduledActionId, ScheduledAction>,
+    next_id: usize,
+    /// The wall-clock time when [`Core::tick`] was last called or, before that, when the scheduler was created.
+    last_tick: instant::Instant,
+}
+
+/// A request to schedule or cancel actions, made by a component while processing an action.
+/// It is carried out only if the changes of the action are applied.
+#[derive(Debug)]
+pub(super) enum ScheduleRequest {
+    Schedule {
+        component_idx: ComponentIdx,
+        action: serde_json::Value,
+        delay: f64,
+        interval: Option<f64>,
+    },
+    Cancel {
+        component_idx: ComponentIdx,
+    },
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler {
+            time: 0.0,
+            scheduled_actions: BTreeMap::new(),
+            next_id: 0,
+            last_tick: instant::Instant::now(),
+        }
+    }
+}
+
+impl Scheduler {
+    /// The time on the document's clock, in milliseconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Schedule the serialized `action` of `component_idx` to be sent after `delay` milliseconds,
+    /// and then every `interval` milliseconds if `interval` is set.
+    fn schedule(
+        &mut self,
+        component_idx: ComponentIdx,
+        action: serde_json::Value,
+        delay: f64,
+        interval: Option<f64>,
+    ) -> ScheduledActionId {
+        let id = ScheduledActionId(self.next_id);
+        self.next_id += 1;
+        self.scheduled_actions.insert(
+            id,
+            ScheduledAction {
+                component_idx,
+                action,
+                time: self.time + delay,
+                interval,
+            },
+        );
+        id
+    }
+
+    /// Cancel the scheduled action `id`. Returns `false` if there was no such action.
+    fn cancel(&mut self, id: ScheduledActionId) -> bool {
+        self.scheduled_actions.remove(&id).is_some()
+    }
+
+    /// Cancel all the scheduled actions of `component_idx`. Returns the number of actions cancelled.
+    pub(super) fn cancel_component(&mut self, component_idx: ComponentIdx) -> usize {
+        let num_scheduled = self.scheduled_actions.len();
+        self.scheduled_actions
+            .retain(|_, scheduled_action| scheduled_action.component_idx != component_idx);
+        num_scheduled - self.scheduled_actions.len()
+    }
+
+    /// The time of the next scheduled action, if any.
+    pub fn next_time(&self) -> Option<f64> {
+        self.scheduled_actions
+            .values()
+            .map(|scheduled_action| scheduled_action.time)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Remove the earliest action scheduled for no later than `end_time`, moving the clock to the time it was scheduled for.
+    /// Actions scheduled for the same time are returned in the order they were scheduled.
+    /// A repeating action is scheduled again rather than removed.
+    ///
+    /// Returns the component and the serialized action, or `None` if no action is due by `end_time`.
+    fn pop_due(&mut self, end_time: f64) -> Option<(ComponentIdx, serde_json::Value)> {
+        let (id, scheduled_action) = self
+            .scheduled_actions
+            .iter_mut()
+            .filter(|(_, scheduled_action)| scheduled_action.time <= end_time)
+            .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time))?;
+
+        self.time = self.time.max(scheduled_action.time);
+        let due = (
+            scheduled_action.component_idx,
+            scheduled_action.action.clone(),
+        );
+        match scheduled_action.interval {
+            Some(interval) => scheduled_action.time += interval,
+            None => {
+                let id = *id;
+                self.scheduled_actions.remove(&id);
+            }
+        }
+        Some(due)
+    }
+}
+
+/// Check that `delay` and `interval` can be used to schedule an action.
+///
+/// The delay must be a non-negative number of milliseconds and the interval, if any, a positive number,
+/// so that advancing the clock by a finite amount sends a finite number of actions.
+fn check_schedule(delay: f64, interval: Option<f64>) -> Result<(), CoreError> {
+    if !(delay.is_finite() && delay >= 0.0) {
+        return Err(CoreError::InvalidTime(delay));
+    }
+    match interval {
+        Some(interval) if !(interval.is_finite() && interval > 0.0) => {
+            Err(CoreError::InvalidTime(interval))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl ScheduleRequest {
+    /// Create a request to send `action` to `component_idx` after `delay` milliseconds
+    /// (and then every `interval` milliseconds), checking that the times are valid.
+    ///
+    /// Unlike the host, a component must use a positive `delay`, since an action that schedules itself
+    /// with no delay would be sent again and again without the clock advancing.
+    pub(super) fn schedule(
+        component_idx: ComponentIdx,
+        action: ActionsEnum,
+        delay: f64,
+        interval: Option<f64>,
+    ) -> Result<Self, CoreError> {
+        check_schedule(delay, interval)?;
+        if delay == 0.0 {
+            return Err(CoreError::InvalidTime(delay));
+        }
+        let action = serialize_action(Action {
+            component_idx,
+            action,
+        })?;
+        Ok(ScheduleRequest::Schedule {
+            component_idx,
+            action,
+            delay,
+            interval,
+        })
+    }
+}
+
+fn serialize_action(action: Action) -> Result<serde_json::Value, CoreError> {
+    serde_json::to_value(action)
+        .map_err(|err| CoreError::InvalidAction(format!("Cannot schedule action: {err}")))
+}
+
+impl Core {
+    /// The time on the document's clock, in milliseconds since `Core` was initialized.
+    pub fn get_time(&self) -> f64 {
+        self.scheduler.time()
+    }
+
+    /// The time on the document's clock when the next scheduled action will be sent, if any action is scheduled.
+    ///
+    /// A host can use this to know when it next needs to advance the clock.
+    pub fn get_next_scheduled_time(&self) -> Option<f64> {
+        self.scheduler.next_time()
+    }
+
+    /// Schedule `action` to be sent after the document's clock advances by `delay` milliseconds
+    /// and then, if `interval` is set, every `interval` milliseconds until it is cancelled.
+    ///
+    /// An error is returned if the action does not refer to a component of the document,
+    /// if `delay` is negative, or if `interval` is not positive.
+    ///
+    /// Scheduled actions are removed when core is initialized from a new source.
+    pub fn schedule_action(
+        &mut self,
+        action: Action,
+        delay: f64,
+        interval: Option<f64>,
+    ) -> Result<ScheduledActionId, CoreError> {
+        check_schedule(delay, interval)?;
+        let component_idx = action.component_idx;
+        self.document_model.get_component(component_idx)?;
+        let action = serialize_action(action)?;
+
+        let id = self
+            .scheduler
+            .schedule(component_idx, action.clone(), delay, interval);
+        self.record_event(RecordedEvent::ScheduleAction {
+            action,
+            delay,
+            interval,
+        })?;
+        Ok(id)
+    }
+
+    /// Cancel the action `id` scheduled by [`Core::schedule_action`].
+    /// Returns `false` if the action was not scheduled or has already been sent (and does not repeat).
+    pub fn cancel_scheduled_action(&mut self, id: ScheduledActionId) -> Result<bool, CoreError> {
+        let cancelled = self.scheduler.cancel(id);
+        if cancelled {
+            self.record_event(RecordedEvent::CancelScheduledAction { id })?;
+        }
+        Ok(cancelled)
+    }
+
+    /// Carry out the requests to schedule or cancel actions made by the components of a dispatched action.
+    pub(super) fn apply_schedule_requests(&mut self, schedule_requests: Vec<ScheduleRequest>) {
+        for request in schedule_requests {
+            match request {
+                ScheduleRequest::Schedule {
+                    component_idx,
+                    action,
+                    delay,
+                    interval,
+                } => {
+                    self.scheduler
+                        .schedule(component_idx, action, delay, interval);
+                }
+                ScheduleRequest::Cancel { component_idx } => {
+                    self.scheduler.cancel_component(component_idx);
+                }
+            }
+        }
+    }
+
+    /// Advance the document's clock by `ms` milliseconds, sending each action scheduled in that time
+    /// to its component once the clock reaches the time it was scheduled for.
+    ///
+    /// Returns the combined changes to the output flat dast, the outcomes of the prop updates requested by the actions
+    /// and the subscribed props that changed.
+    ///
+    /// Changes made by scheduled actions are not added to the undo history.
+    ///
+    /// If a scheduled action fails, the clock stops at the time of that action and the error is returned.
+    /// The changes of the actions sent before it have been made and rendered,
+    /// so the host should re-render the document from [`Core::to_flat_dast`].
+    ///
+    /// At most [`MAX_ACTIONS_PER_ADVANCE`] actions are sent. If more are due, the clock stops at the time of the last
+    /// action sent and [`CoreError::TooManyScheduledActions`] is returned as for a failed action;
+    /// the host can advance the clock again to send the rest.
+    pub fn advance_time(&mut self, ms: f64) -> Result<ActionResult, CoreError> {
+        if !(ms.is_finite() && ms >= 0.0) {
+            return Err(CoreError::InvalidTime(ms));
+        }
+        let start_time = self.scheduler.time;
+        let end_time = start_time + ms;
+
+        // Every action must see the changes made by the previous ones, so the changes are made one action at a time,
+        // but the flat dast is only rendered once all of them are made.
+        let mut component_changes = ComponentChanges::default();
+        let mut prop_outcomes = Vec::new();
+        let mut result = Ok(());
+        let mut num_sent = 0;
+
+        while let Some((component_idx, action)) = self.scheduler.pop_due(end_time) {
+            match self.send_scheduled_action(component_idx, action) {
+                Ok((changes, outcomes)) => {
+                    component_changes.extend(changes);
+                    prop_outcomes.extend(outcomes);
+                }
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+            num_sent += 1;
+            if num_sent == MAX_ACTIONS_PER_ADVANCE
+                && self
+                    .scheduler
+                    .next_time()
+                    .is_some_and(|time| time <= end_time)
+            {
+                result = Err(CoreError::TooManyScheduledActions(MAX_ACTIONS_PER_ADVANCE));
+                break;
+            }
+        }
+        if result.is_ok() {
+            self.scheduler.time = end_time;
+        }
+        self.record_event(RecordedEvent::AdvanceTime {
+            ms: self.scheduler.time - start_time,
+        })?;
+        if let Err(err) = result {
+            // The changes of the actions sent before the failed one were made, so they are rendered
+            // to keep the rendered flat dast in step with the document.
+            self.render_component_changes(component_changes)?;
+            return Err(err);
+        }
+
+        Ok(ActionResult {
+            flat_dast_updates: self.render_component_changes(component_changes)?,
+            prop_outcomes,
+            subscribed_prop_changes: self.get_subscribed_prop_changes()?,
+        })
+    }
+
+    /// Advance the document's clock by the wall-clock time that has passed since `tick` was last called
+    /// (or since `Core` was initialized), as with [`Core::advance_time`].
+    ///
+    /// A host can call this on every animation frame.
+    pub fn tick(&mut self) -> Result<ActionResult, CoreError> {
+        let now = instant::Instant::now();
+        let elapsed = now.duration_since(self.scheduler.last_tick);
+        self.scheduler.last_tick = now;
+        self.advance_time(elapsed.as_secs_f64() * 1000.0)
+    }
+
+    /// Send the serialized `action` scheduled by `component_idx` and make its changes to the document.
+    fn send_scheduled_action(
+        &mut self,
+        component_idx: ComponentIdx,
+        action: serde_json::Value,
+    ) -> Result<(ComponentChanges, Vec<PropUpdateOutcome>), CoreError> {
+        let action: Action = serde_json::from_value(action).map_err(|err| {
+            CoreError::InvalidAction(format!(
+                "Cannot read action scheduled by component {}: {err}",
+                component_idx.as_usize()
+            ))
+        })?;
+
+        let changes_from_action = self.calculate_changes_from_action(action)?;
+        let component_changes = self
+            .document_model
+            .execute_changes(changes_from_action.changes_to_make)?;
+        self.apply_schedule_requests(changes_from_action.schedule_requests);
+        let prop_outcomes = self.get_prop_outcomes(changes_from_action.requested_updates)?;
+
+        Ok((component_changes, prop_outcomes))
+    }
+}
+
+#[cfg(test)]
+#[path = "scheduler.test.rs"]
+mod test;
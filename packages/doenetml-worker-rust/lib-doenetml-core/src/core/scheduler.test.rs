@@ -0,0 +1,61 @@
+use super::*;
+
+fn action(name: &str) -> serde_json::Value {
+    serde_json::Value::String(name.to_string())
+}
+
+/// Pop all the actions due by `end_time`, returning their names.
+fn pop_all_due(scheduler: &mut Scheduler, end_time: f64) -> Vec<String> {
+    std::iter::from_fn(|| scheduler.pop_due(end_time))
+        .map(|(_, action)| action.as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn actions_are_sent_in_order_of_time_then_of_scheduling() {
+    let mut scheduler = Scheduler::default();
+    let component_idx = ComponentIdx::new(0);
+    scheduler.schedule(component_idx, action("c"), 20.0, None);
+    scheduler.schedule(component_idx, action("a"), 10.0, None);
+    scheduler.schedule(component_idx, action("b"), 10.0, None);
+    assert_eq!(scheduler.next_time(), Some(10.0));
+
+    assert_eq!(pop_all_due(&mut scheduler, 15.0), vec!["a", "b"]);
+    assert_eq!(scheduler.time(), 10.0);
+    assert_eq!(pop_all_due(&mut scheduler, 20.0), vec!["c"]);
+    assert_eq!(scheduler.time(), 20.0);
+    assert_eq!(scheduler.next_time(), None);
+}
+
+#[test]
+fn repeating_actions_are_sent_until_cancelled() {
+    let mut scheduler = Scheduler::default();
+    let id = scheduler.schedule(ComponentIdx::new(0), action("a"), 5.0, Some(10.0));
+    scheduler.schedule(ComponentIdx::new(1), action("b"), 20.0, None);
+
+    assert_eq!(pop_all_due(&mut scheduler, 30.0), vec!["a", "a", "b", "a"]);
+    assert_eq!(scheduler.next_time(), Some(35.0));
+
+    assert!(scheduler.cancel(id));
+    assert!(!scheduler.cancel(id));
+    assert_eq!(scheduler.next_time(), None);
+}
+
+#[test]
+fn cancelling_a_component_only_removes_its_actions() {
+    let mut scheduler = Scheduler::default();
+    scheduler.schedule(ComponentIdx::new(0), action("a"), 5.0, Some(10.0));
+    scheduler.schedule(ComponentIdx::new(1), action("b"), 10.0, None);
+    scheduler.cancel_component(ComponentIdx::new(0));
+
+    assert_eq!(pop_all_due(&mut scheduler, 30.0), vec!["b"]);
+}
+
+#[test]
+fn invalid_times_cannot_be_scheduled() {
+    assert!(check_schedule(0.0, None).is_ok());
+    assert!(check_schedule(-1.0, None).is_err());
+    assert!(check_schedule(f64::NAN, None).is_err());
+    assert!(check_schedule(0.0, Some(0.0)).is_err());
+    assert!(check_schedule(0.0, Some(f64::INFINITY)).is_err());
+}
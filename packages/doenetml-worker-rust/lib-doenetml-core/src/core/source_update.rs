@@ -0,0 +1,709 @@
+//! # Source updates
+//!
+//! Apply an edit of the DoenetML source to an initialized `Core`.
+//!
+//! If the edit changed only text (e.g., the author is typing the contents of a paragraph or the value of an attribute),
+//! only the affected `String` nodes are changed, so only the props that depend on them are recalculated
+//! and only the flat dast elements that changed are returned.
+//!
+//! Otherwise, the children of the element containing every change are patched: the components of the replaced children
+//! are removed, components are created for the new children, and the refs to and from the new children are resolved.
+//! Only the changed flat dast elements and the elements of the new components are returned.
+//!
+//! If the edit could change what a ref outside the replaced children resolves to,
+//! `Core` is re-initialized from the edited source, preserving the interactive state of components
+//! that still match (see [`Core::reinit_from_dast_root_preserving_state`]), and the whole flat dast is returned.
+
+use std::{collections::HashMap, ops::Range};
+
+use serde::Serialize;
+use thiserror::Error;
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::{
+        ComponentAttributes,
+        prelude::{ComponentIdx, FlatDastElement, FlatDastElementUpdate},
+    },
+    dast::{
+        DastElement, DastElementContent, DastRoot, DastTextRefElementContent, FlatDastRoot,
+        dast_diff::DastChange,
+        flat_dast::{
+            ErrorType, FlatAttribute, FlatFragment, FlatNode, Index, NormalizedNode,
+            NormalizedRoot, UntaggedContent,
+        },
+        ref_expand::Expander,
+        ref_resolve::{CHILDREN_ARE_IMPLICIT_INDEX_RESOLUTIONS, IndexResolution, RefResolution},
+    },
+    error::CoreError,
+    graph::directed_graph::Taggable,
+    graph_node::GraphNodeLookup,
+    props::PropValue,
+    state_snapshot::StringLocation,
+};
+
+use super::{Core, action_recording::RecordedEvent};
+
+/// The changes to the output of `Core` resulting from an edit of the source.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi))]
+#[serde(tag = "type", content = "value")]
+pub enum SourceUpdate {
+    /// The edit was applied to the affected components, so only the changed elements of the flat dast are returned.
+    Incremental(IncrementalUpdate),
+    /// `Core` was re-initialized, so the document must be re-rendered from this flat dast.
+    Full(FlatDastRoot),
+}
+
+/// The changes to the rendered flat dast resulting from an edit that was applied incrementally.
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct IncrementalUpdate {
+    /// The flat dast elements of the components that were created for new children.
+    pub new_elements: Vec<FlatDastElement>,
+    /// The updates to the elements that were already rendered.
+    /// The elements whose children changed have `new_children` set.
+    pub flat_dast_updates: HashMap<ComponentIdx, FlatDastElementUpdate>,
+}
+
+#[derive(Debug, Error)]
+pub enum SourceUpdateError {
+    #[error("Cannot update the source before core is initialized")]
+    NotInitialized,
+    #[error("The path {0:?} does not point to a node of the source")]
+    InvalidPath(Vec<usize>),
+    #[error(transparent)]
+    Core(#[from] CoreError),
+}
+
+/// The replacement of the children in `old_range` of the element at `path` of the source
+/// by the children in `new_range` of the same element of the edited source.
+#[derive(Debug)]
+struct ChildrenReplacement {
+    path: Vec<usize>,
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+}
+
+impl Core {
+    /// Replace the node of the source DAST at `path` with `content` and update `Core` to match.
+    ///
+    /// The first entry of `path` is the index of a child of the DAST root, and each following entry
+    /// is the index of a child of the element found so far.
+    pub fn update_dast_subtree(
+        &mut self,
+        path: &[usize],
+        content: DastElementContent,
+    ) -> Result<SourceUpdate, SourceUpdateError> {
+        let mut dast_root = self
+            .dast_root
+            .clone()
+            .ok_or(SourceUpdateError::NotInitialized)?;
+        dast_root
+            .replace_content(path, content)
+            .ok_or_else(|| SourceUpdateError::InvalidPath(path.to_vec()))?;
+
+        Ok(self.update_from_dast_root(dast_root)?)
+    }
+
+    /// Update `Core` to match the edited source `dast_root`.
+    ///
+    /// If `Core` has not been initialized, it is initialized from `dast_root`.
+    /// If actions are being recorded, the edit is recorded, even if `Core` had to be re-initialized to apply it.
+    pub fn update_from_dast_root(
+        &mut self,
+        dast_root: DastRoot,
+    ) -> Result<SourceUpdate, CoreError> {
+        let event = match self.is_recording_actions() {
+            true => Some(RecordedEvent::UpdateSource {
+                dast_root: Self::serialize_for_recording(&dast_root, "source")?,
+            }),
+            false => None,
+        };
+
+        // Re-initializing `Core` would end the recording, so the recorder is set aside while the edit is applied.
+        let action_recorder = self.action_recorder.take();
+        let result = self.apply_source_update(dast_root);
+        self.action_recorder = action_recorder;
+        let update = result?;
+
+        if let Some(event) = event {
+            self.record_event(event)?;
+        }
+        Ok(update)
+    }
+
+    /// Apply the edited source `dast_root` as in [`Core::update_from_dast_root`], without recording it.
+    fn apply_source_update(&mut self, dast_root: DastRoot) -> Result<SourceUpdate, CoreError> {
+        let (Some(old_dast_root), Some(_)) = (&self.dast_root, &self.normalized_root) else {
+            self.init_from_dast_root(&dast_root);
+            return Ok(SourceUpdate::Full(self.to_flat_dast()?));
+        };
+
+        let changes = old_dast_root.changes_to(&dast_root);
+
+        if let Some((changes_to_make, components_with_changed_children)) =
+            self.get_string_changes(&changes)
+        {
+            self.set_normalized_texts(&changes);
+            self.dast_root = Some(dast_root);
+
+            // The recorded actions may have changed the strings that were just replaced,
+            // so undoing them could revert the edit.
+            self.action_history.clear();
+
+            let mut flat_dast_updates = self.apply_changes(changes_to_make)?;
+            self.document_renderer.add_flat_dast_children_updates(
+                &mut flat_dast_updates,
+                components_with_changed_children,
+                &self.document_model,
+            )?;
+
+            return Ok(SourceUpdate::Incremental(IncrementalUpdate {
+                new_elements: Vec::new(),
+                flat_dast_updates,
+            }));
+        }
+
+        if let Some(replacement) = merge_changes(&changes) {
+            match self.replace_children(&replacement, &dast_root) {
+                Ok(Some(update)) => {
+                    self.dast_root = Some(dast_root);
+                    return Ok(SourceUpdate::Incremental(update));
+                }
+                Ok(None) => {}
+                // The new children depend on themselves. The components in the cycle are replaced with errors
+                // when the document is rendered from scratch.
+                Err(CoreError::CircularDependency(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        // A ref outside the edited children could be affected, so we need to start over.
+        self.reinit_from_dast_root_preserving_state(&dast_root)?;
+        Ok(SourceUpdate::Full(self.to_flat_dast()?))
+    }
+
+    /// Find the `String` nodes changed by `changes` and the new values they should be given,
+    /// along with the components that have one of those `String` nodes as a child.
+    ///
+    /// Returns `None` if some change is not a change of text that corresponds to a `String` node,
+    /// e.g., if it is the value of an attribute that the component does not recognize.
+    fn get_string_changes(
+        &self,
+        changes: &[DastChange],
+    ) -> Option<(GraphNodeLookup<PropValue>, Vec<ComponentIdx>)> {
+        let dast_root = self.dast_root.as_ref()?;
+        let normalized_root = self.normalized_root.as_ref()?;
+
+        let mut changes_to_make = GraphNodeLookup::new();
+        let mut components_with_changed_children = Vec::new();
+
+        for change in changes {
+            let DastChange::Text {
+                path,
+                attribute,
+                child_idx,
+                value,
+            } = change
+            else {
+                return None;
+            };
+            let node_idx = find_normalized_element(dast_root, normalized_root, path)?;
+            let component_idx = ComponentIdx::new(node_idx);
+            let NormalizedNode::Element(elm) = &normalized_root.nodes[node_idx] else {
+                return None;
+            };
+
+            let (location, num_source_children) = match attribute {
+                None => (StringLocation::Children, elm.children.len()),
+                Some(attr_name) => {
+                    // Attribute names are case-insensitive, so find the name used by the component.
+                    let component = self.document_model.get_component(component_idx).ok()?;
+                    let component_attr_name = component
+                        .get_attribute_names()
+                        .into_iter()
+                        .find(|name| name.eq_ignore_ascii_case(attr_name))?;
+                    let attr = elm.attributes.iter().find(|attr| &attr.name == attr_name)?;
+                    (
+                        StringLocation::Attribute(component_attr_name.to_string()),
+                        attr.children.len(),
+                    )
+                }
+            };
+
+            let string_node = self.document_model.get_source_string_node(
+                component_idx,
+                &location,
+                *child_idx,
+                num_source_children,
+            )?;
+            changes_to_make.set_tag(string_node, PropValue::String(value.clone().into()));
+
+            if location == StringLocation::Children {
+                for idx in self
+                    .document_model
+                    .get_components_with_string_child(string_node)
+                {
+                    if !components_with_changed_children.contains(&idx) {
+                        components_with_changed_children.push(idx);
+                    }
+                }
+            }
+        }
+
+        Some((changes_to_make, components_with_changed_children))
+    }
+
+    /// Set the text changed by `changes` in the normalized root.
+    ///
+    /// Each change must have been found by [`Core::get_string_changes`], so it is a text change of an element.
+    fn set_normalized_texts(&mut self, changes: &[DastChange]) {
+        let (Some(dast_root), Some(normalized_root)) = (&self.dast_root, &mut self.normalized_root)
+        else {
+            return;
+        };
+        for change in changes {
+            let DastChange::Text {
+                path,
+                attribute,
+                child_idx,
+                value,
+            } = change
+            else {
+                continue;
+            };
+            let Some(node_idx) = find_normalized_element(dast_root, normalized_root, path) else {
+                continue;
+            };
+            let NormalizedNode::Element(elm) = &mut normalized_root.nodes[node_idx] else {
+                continue;
+            };
+            let children = match attribute {
+                None => &mut elm.children,
+                Some(attr_name) => {
+                    let Some(attr) = elm
+                        .attributes
+                        .iter_mut()
+                        .find(|attr| &attr.name == attr_name)
+                    else {
+                        continue;
+                    };
+                    &mut attr.children
+                }
+            };
+            if let Some(child) = children.get_mut(*child_idx) {
+                *child = UntaggedContent::Text(value.clone());
+            }
+        }
+    }
+
+    /// Replace the children of an element as described by `replacement`, creating components for the children
+    /// in `replacement.new_range` of the element at `replacement.path` of `dast_root`, the edited source.
+    ///
+    /// Returns `None`, leaving `Core` unchanged, if the replacement could change what a ref outside the replaced children
+    /// resolves to, or if the replaced children were not created directly from the source.
+    fn replace_children(
+        &mut self,
+        replacement: &ChildrenReplacement,
+        dast_root: &DastRoot,
+    ) -> Result<Option<IncrementalUpdate>, CoreError> {
+        let (Some(old_dast_root), Some(normalized_root), Some(resolver)) =
+            (&self.dast_root, &self.normalized_root, &mut self.resolver)
+        else {
+            return Ok(None);
+        };
+        let Some(parent_idx) =
+            find_normalized_element(old_dast_root, normalized_root, &replacement.path)
+        else {
+            return Ok(None);
+        };
+        let Some(new_children) = get_dast_children(dast_root, &replacement.path)
+            .and_then(|children| children.get(replacement.new_range.clone()))
+        else {
+            return Ok(None);
+        };
+        let NormalizedNode::Element(parent_elm) = &normalized_root.nodes[parent_idx] else {
+            return Ok(None);
+        };
+        let num_source_children = parent_elm.children.len();
+
+        // The replaced children and their descendants
+        let mut is_replaced_child = vec![false; normalized_root.nodes.len()];
+        for child in parent_elm.children[replacement.old_range.clone()].iter() {
+            if let UntaggedContent::Ref(idx) = child {
+                is_replaced_child[*idx] = true;
+            }
+        }
+        let is_removed = (0..normalized_root.nodes.len())
+            .map(|idx| {
+                let mut node_idx = Some(idx);
+                while let Some(i) = node_idx {
+                    if is_replaced_child[i] {
+                        return true;
+                    }
+                    node_idx = normalized_root.nodes[i].parent();
+                }
+                false
+            })
+            .collect::<Vec<_>>();
+        let removed_nodes = normalized_root
+            .nodes
+            .iter()
+            .filter(|node| is_removed[node.idx()])
+            .collect::<Vec<_>>();
+
+        // A ref resolved through the parent to one of its children (e.g., `$parent[2]` or `$parent.child`)
+        // could resolve to a different child once the children are replaced.
+        let is_affected = |resolution: &RefResolution| {
+            let resolved_through_parent = resolution
+                .nodes_in_resolved_path
+                .split_last()
+                .is_some_and(|(_, path)| path.iter().skip(1).any(|idx| *idx == parent_idx));
+            resolved_through_parent
+                || std::iter::once(&resolution.node_idx)
+                    .chain(resolution.nodes_in_resolved_path.iter())
+                    .any(|idx| is_removed.get(*idx).copied().unwrap_or(false))
+        };
+        let outside_extends_are_affected = normalized_root.nodes.iter().any(|node| match node {
+            NormalizedNode::Element(elm) if !is_removed[elm.idx] => elm
+                .extending
+                .as_ref()
+                .is_some_and(|source| is_affected(source.get_resolution())),
+            _ => false,
+        });
+        let generated_children_are_affected =
+            self.generated_children.iter().any(|(idx, generated)| {
+                idx.as_usize() == parent_idx
+                    || is_removed.get(idx.as_usize()).copied().unwrap_or(false)
+                    || generated.any_extending(is_affected)
+            });
+        // The content of external documents is merged when the document is normalized.
+        let has_external_content = parent_elm.source_doc.is_some()
+            || removed_nodes.iter().any(|node| match node {
+                NormalizedNode::Element(elm) => elm.source_doc.is_some(),
+                NormalizedNode::Error(err) => err.source_doc.is_some(),
+            })
+            || (self.document_loader.is_some()
+                && new_children.iter().any(refers_to_external_document));
+        if outside_extends_are_affected || generated_children_are_affected || has_external_content {
+            return Ok(None);
+        }
+
+        let first_new_idx = self.document_model.get_num_components();
+        let mut fragment = FlatFragment::from_dast_with_id_shift(
+            &DastRoot {
+                children: new_children.to_vec(),
+                position: None,
+                sources: dast_root.sources.clone(),
+            },
+            first_new_idx,
+            Some(parent_idx),
+        );
+
+        // A ref that could not be resolved (or was ambiguous) could resolve differently once names are added or removed.
+        let mut old_names = removed_nodes
+            .iter()
+            .filter_map(|node| match node {
+                NormalizedNode::Element(elm) => get_name(&elm.attributes),
+                NormalizedNode::Error(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let mut new_names = fragment
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                FlatNode::Element(elm) => get_name(&elm.attributes),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        old_names.sort();
+        new_names.sort();
+        let has_outside_warnings = normalized_root.nodes.iter().any(|node| {
+            matches!(node, NormalizedNode::Error(err) if err.error_type == ErrorType::Warning && !is_removed[err.idx])
+        });
+        let parent_has_index_resolutions =
+            CHILDREN_ARE_IMPLICIT_INDEX_RESOLUTIONS.contains(&parent_elm.name.as_str());
+        if has_outside_warnings && (old_names != new_names || parent_has_index_resolutions) {
+            return Ok(None);
+        }
+
+        let old_flat_nodes = removed_nodes
+            .iter()
+            .map(|node| node.to_flat_node())
+            .collect::<Vec<_>>();
+        let element_name = |idx: Index| match normalized_root.nodes.get(idx) {
+            Some(NormalizedNode::Element(elm)) => Some(elm.name.as_str()),
+            _ => None,
+        };
+        if !resolver.replace_nodes(&old_flat_nodes, &fragment, element_name) {
+            return Ok(None);
+        }
+
+        // The nodes in `fragment` are resolved, so from here on `Core` is updated.
+        let mut parent_children = parent_elm.children.clone();
+        parent_children.splice(replacement.old_range.clone(), fragment.children.clone());
+        if parent_has_index_resolutions {
+            resolver.replace_index_resolutions(
+                &parent_children,
+                IndexResolution::ReplaceAll { parent: parent_idx },
+            );
+        }
+        let removed_indices = removed_nodes
+            .iter()
+            .map(|node| node.idx())
+            .collect::<Vec<_>>();
+
+        Expander::expand_fragment(&mut fragment, resolver, |idx| {
+            self.document_model
+                .get_component_type(ComponentIdx::new(idx))
+                .ok()
+        });
+        // Nodes that are no longer referenced (e.g., the ref consumed by an `extend` attribute) still have an index,
+        // so a placeholder component is created for them and then removed.
+        let unreferenced = fragment.find_unreferenced_nodes();
+        let nodes = fragment
+            .nodes
+            .iter()
+            .map(|node| {
+                if unreferenced.contains(&node.idx()) {
+                    let mut placeholder = NormalizedNode::default();
+                    placeholder.set_idx(node.idx());
+                    placeholder
+                } else {
+                    NormalizedNode::from_flat_node(node)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let normalized_root = self.normalized_root.as_mut().unwrap();
+        for idx in removed_indices.iter().copied() {
+            normalized_root.nodes[idx] = NormalizedNode::default();
+            normalized_root.nodes[idx].set_idx(idx);
+        }
+        while normalized_root.nodes.len() < first_new_idx {
+            let mut placeholder = NormalizedNode::default();
+            placeholder.set_idx(normalized_root.nodes.len());
+            normalized_root.nodes.push(placeholder);
+        }
+        normalized_root.nodes.extend(nodes);
+        if let NormalizedNode::Element(parent_elm) = &mut normalized_root.nodes[parent_idx] {
+            parent_elm.children = parent_children;
+        }
+
+        let removed_components = removed_indices
+            .into_iter()
+            .chain(unreferenced)
+            .map(ComponentIdx::new)
+            .collect::<Vec<_>>();
+        let changes = self.document_model.replace_source_children(
+            ComponentIdx::new(parent_idx),
+            num_source_children,
+            replacement.old_range.clone(),
+            &removed_components,
+            &fragment.children,
+            normalized_root,
+        )?;
+
+        self.drop_removed_components();
+
+        // The recorded actions may refer to the removed components.
+        self.action_history.clear();
+
+        let flat_dast_updates = self.render_component_changes(changes)?;
+
+        let new_elements = (first_new_idx..self.document_model.get_num_components())
+            .map(ComponentIdx::new)
+            .filter(|component_idx| !self.document_model.is_removed(*component_idx))
+            .map(|component_idx| {
+                self.document_renderer
+                    .component_to_flat_dast(component_idx, &self.document_model)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(IncrementalUpdate {
+            new_elements,
+            flat_dast_updates,
+        }))
+    }
+}
+
+/// Combine `changes` into a replacement of the children of the deepest element that contains all of them.
+///
+/// Returns `None` if there are no changes or if the changes are not all inside the same element
+/// (e.g., if the root's children changed).
+fn merge_changes(changes: &[DastChange]) -> Option<ChildrenReplacement> {
+    let replacements = changes
+        .iter()
+        .map(|change| match change {
+            DastChange::Text {
+                path,
+                attribute: None,
+                child_idx,
+                ..
+            } => Some(ChildrenReplacement {
+                path: path.clone(),
+                old_range: *child_idx..child_idx + 1,
+                new_range: *child_idx..child_idx + 1,
+            }),
+            // The text of an attribute is replaced by replacing the element.
+            DastChange::Text { path, .. } => {
+                let (last, parent_path) = path.split_last()?;
+                Some(ChildrenReplacement {
+                    path: parent_path.to_vec(),
+                    old_range: *last..last + 1,
+                    new_range: *last..last + 1,
+                })
+            }
+            DastChange::Children {
+                path,
+                old_range,
+                new_range,
+            } => Some(ChildrenReplacement {
+                path: path.clone(),
+                old_range: old_range.clone(),
+                new_range: new_range.clone(),
+            }),
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let first_path = &replacements.first()?.path;
+    let depth = replacements
+        .iter()
+        .map(|replacement| {
+            replacement
+                .path
+                .iter()
+                .zip(first_path)
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .min()?;
+    if depth == 0 {
+        return None;
+    }
+
+    // A change inside a child of the common element replaces that child.
+    let mut start = usize::MAX;
+    let mut old_end = 0;
+    let mut num_added: isize = 0;
+    for replacement in replacements.iter() {
+        let (old_range, new_range) = match replacement.path.get(depth) {
+            Some(&child_idx) => (child_idx..child_idx + 1, child_idx..child_idx + 1),
+            None => (replacement.old_range.clone(), replacement.new_range.clone()),
+        };
+        start = start.min(old_range.start);
+        old_end = old_end.max(old_range.end);
+        num_added += new_range.len() as isize - old_range.len() as isize;
+    }
+
+    Some(ChildrenReplacement {
+        path: first_path[..depth].to_vec(),
+        old_range: start..old_end,
+        new_range: start..old_end.checked_add_signed(num_added)?,
+    })
+}
+
+/// Find the index of the normalized element created from the element at `path` of `dast_root`.
+///
+/// Returns `None` if the children of the elements along `path` (including the element found)
+/// do not line up with the children of the corresponding normalized elements.
+fn find_normalized_element(
+    dast_root: &DastRoot,
+    normalized_root: &NormalizedRoot,
+    path: &[usize],
+) -> Option<Index> {
+    let mut dast_children = &dast_root.children;
+    let mut normalized_children = &normalized_root.children;
+    let mut node_idx = None;
+    for child_idx in path {
+        if !children_line_up(dast_children, normalized_children, normalized_root) {
+            return None;
+        }
+        let (DastElementContent::Element(elm), UntaggedContent::Ref(idx)) = (
+            dast_children.get(*child_idx)?,
+            &normalized_children[*child_idx],
+        ) else {
+            return None;
+        };
+        let NormalizedNode::Element(normalized_elm) = &normalized_root.nodes[*idx] else {
+            return None;
+        };
+        dast_children = &elm.children;
+        normalized_children = &normalized_elm.children;
+        node_idx = Some(*idx);
+    }
+    children_line_up(dast_children, normalized_children, normalized_root)
+        .then_some(node_idx)
+        .flatten()
+}
+
+/// Whether each of `dast_children` was normalized into the corresponding entry of `normalized_children`.
+fn children_line_up(
+    dast_children: &[DastElementContent],
+    normalized_children: &[UntaggedContent],
+    normalized_root: &NormalizedRoot,
+) -> bool {
+    dast_children.len() == normalized_children.len()
+        && dast_children.iter().zip(normalized_children).all(
+            |(dast_child, normalized_child)| match (dast_child, normalized_child) {
+                (DastElementContent::Text(_), UntaggedContent::Text(_)) => true,
+                (DastElementContent::Element(elm), UntaggedContent::Ref(idx)) => matches!(
+                    normalized_root.nodes.get(*idx),
+                    Some(NormalizedNode::Element(normalized_elm)) if normalized_elm.name == elm.name
+                ),
+                (DastElementContent::Text(_), _) | (_, UntaggedContent::Text(_)) => false,
+                (_, UntaggedContent::Ref(_)) => true,
+            },
+        )
+}
+
+/// The children of the element at `path` of `dast_root`, or the children of the root if `path` is empty.
+fn get_dast_children<'a>(
+    dast_root: &'a DastRoot,
+    path: &[usize],
+) -> Option<&'a Vec<DastElementContent>> {
+    let mut children = &dast_root.children;
+    for child_idx in path {
+        let DastElementContent::Element(elm) = children.get(*child_idx)? else {
+            return None;
+        };
+        children = &elm.children;
+    }
+    Some(children)
+}
+
+/// Whether `content` or one of its descendants extends or copies an external document (e.g., `extend="doenet:abc"`).
+fn refers_to_external_document(content: &DastElementContent) -> bool {
+    let DastElementContent::Element(elm) = content else {
+        return false;
+    };
+    is_external_reference(elm) || elm.children.iter().any(refers_to_external_document)
+}
+
+fn is_external_reference(elm: &DastElement) -> bool {
+    elm.attributes.iter().any(|(name, attr)| {
+        (name.eq_ignore_ascii_case("extend") || name.eq_ignore_ascii_case("copy"))
+            && attr.children.iter().any(|child| {
+                matches!(child, DastTextRefElementContent::Text(text) if text.value.trim_start().starts_with("doenet:"))
+            })
+    })
+}
+
+/// The value of the `name` attribute in `attributes`, if it is given by text.
+fn get_name(attributes: &[FlatAttribute]) -> Option<String> {
+    let name_attr = attributes
+        .iter()
+        .find(|attr| attr.name.eq_ignore_ascii_case("name"))?;
+    name_attr
+        .children
+        .iter()
+        .map(|child| match child {
+            UntaggedContent::Text(text) => Some(text.as_str()),
+            UntaggedContent::Ref(_) => None,
+        })
+        .collect()
+}
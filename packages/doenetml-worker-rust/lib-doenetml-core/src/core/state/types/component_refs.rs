@@ -4,8 +4,8 @@ use anyhow::anyhow;
 
 /// A vector of references to components
 ///
-/// TODO: when have array props, turn this into just `ComponentRef`, which is a reference to a single component
-/// and then use the general array prop mechanism to refer to multiple components
+/// A prop of this type can be marked as an array prop, in which case each element
+/// is a `ComponentRef` to a single component.
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "web", derive(tsify_next::Tsify))]
 pub struct ComponentRefs(pub Vec<ComponentIdx>);
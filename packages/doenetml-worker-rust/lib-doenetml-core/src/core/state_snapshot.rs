@@ -0,0 +1,372 @@
+//! # State snapshots
+//!
+//! A `StateSnapshot` records the interactive state of a document, i.e., the values of `State` nodes
+//! and of any `String` nodes that were changed by an action. A snapshot can be serialized, saved,
+//! and later re-applied to a `Core` that was initialized from the same source to restore the document
+//! to the state that the user last saw.
+//!
+//! Values are keyed by a [`ComponentAddress`] (a name or a path through the document) rather than by `GraphNode` indices,
+//! since `GraphNode` indices are not stable between initializations of `Core`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+#[cfg(feature = "web")]
+use tsify_next::Tsify;
+
+use crate::{
+    components::{prelude::FlatDastElementUpdate, types::ComponentIdx},
+    dast::flat_dast::{NormalizedNode, UntaggedContent},
+    error::CoreError,
+    graph::directed_graph::Taggable,
+    graph_node::GraphNodeLookup,
+    props::PropValue,
+    state::types::math_expr::MathExpr,
+};
+
+use super::{Core, action_recording::RecordedEvent};
+
+/// The version of the snapshot format produced by [`Core::get_state_snapshot`].
+/// Snapshots with a different version are rejected by [`Core::apply_state_snapshot`].
+pub const STATE_SNAPSHOT_VERSION: u32 = 1;
+
+/// The interactive state of a document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[cfg_attr(feature = "web", tsify(into_wasm_abi, from_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshot {
+    /// The version of the snapshot format.
+    pub version: u32,
+    /// The values of all `State` nodes that did not come from a default value.
+    pub states: Vec<StateSnapshotEntry>,
+    /// The values of all `String` nodes that have been changed since the document was initialized.
+    pub strings: Vec<StringSnapshotEntry>,
+}
+
+/// A stable way of referring to a component that survives re-initialization of `Core`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(tag = "type", content = "value")]
+pub enum ComponentAddress {
+    /// The root name of the component, as computed by `Core::calculate_root_names`.
+    Name(String),
+    /// A path from the root of the document to the component. Each step is the component type
+    /// along with the (zero-based) index of the component among its siblings of the same type.
+    Path(Vec<ComponentAddressStep>),
+}
+
+/// A single step of a [`ComponentAddress::Path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentAddressStep {
+    pub component_type: String,
+    pub index: usize,
+}
+
+/// The saved value of the state of a prop.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshotEntry {
+    pub component: ComponentAddress,
+    /// The type of the component. A value is only restored if the types match.
+    pub component_type: String,
+    pub prop_name: String,
+    pub value: SnapshotValue,
+}
+
+/// Where a `String` node is located in its component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(tag = "type", content = "name")]
+pub enum StringLocation {
+    /// The string is a child of the component.
+    Children,
+    /// The string is part of the content of the attribute with the given name.
+    Attribute(String),
+}
+
+/// The saved value of a `String` node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct StringSnapshotEntry {
+    pub component: ComponentAddress,
+    /// The type of the component. A value is only restored if the types match.
+    pub component_type: String,
+    pub location: StringLocation,
+    /// The index of the string among the (unexpanded) children of `location`.
+    pub child_idx: usize,
+    pub value: String,
+}
+
+/// A value that can be stored in a snapshot. Unlike `PropValue`, this value
+/// is tagged with its type so that it can be deserialized unambiguously.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "web", derive(Tsify))]
+#[serde(tag = "type", content = "value")]
+pub enum SnapshotValue {
+    String(String),
+    Number(f64),
+    Integer(i64),
+    Boolean(bool),
+    Math(MathExpr),
+}
+
+impl TryFrom<PropValue> for SnapshotValue {
+    type Error = PropValue;
+
+    /// Convert a `PropValue` to a `SnapshotValue`. If the type of the `PropValue`
+    /// cannot be stored in a snapshot, the original value is returned as the error.
+    fn try_from(value: PropValue) -> Result<Self, Self::Error> {
+        match value {
+            PropValue::String(s) => Ok(SnapshotValue::String((*s).clone())),
+            PropValue::Number(n) => Ok(SnapshotValue::Number(n)),
+            PropValue::Integer(i) => Ok(SnapshotValue::Integer(i)),
+            PropValue::Boolean(b) => Ok(SnapshotValue::Boolean(b)),
+            PropValue::Math(m) => Ok(SnapshotValue::Math((*m).clone())),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<SnapshotValue> for PropValue {
+    fn from(value: SnapshotValue) -> Self {
+        match value {
+            SnapshotValue::String(s) => PropValue::String(s.into()),
+            SnapshotValue::Number(n) => PropValue::Number(n),
+            SnapshotValue::Integer(i) => PropValue::Integer(i),
+            SnapshotValue::Boolean(b) => PropValue::Boolean(b),
+            SnapshotValue::Math(m) => PropValue::Math(m.into()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StateSnapshotError {
+    #[error("Unsupported state snapshot version {found}; expected version {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error(transparent)]
+    Core(#[from] CoreError),
+}
+
+impl Core {
+    /// Compute a `ComponentAddress` for every component, indexed by `ComponentIdx`.
+    /// Components that were removed (e.g., by an edit of the source) have no address.
+    ///
+    /// Components with a root name are addressed by that name. All other components
+    /// are addressed by their path from the root of the document.
+    pub fn get_component_addresses(&self) -> Result<Vec<Option<ComponentAddress>>, CoreError> {
+        let root_names = match self.resolver {
+            Some(_) => self.calculate_root_names()?,
+            None => Vec::new(),
+        };
+        let num_components = self.document_model.get_num_components();
+
+        // Components added by an edit of the source come after the existing components,
+        // so siblings are ordered by their position among the children of their parent in the source, if any,
+        // and then by index.
+        let mut source_positions = vec![usize::MAX; num_components];
+        for node in self
+            .normalized_root
+            .iter()
+            .flat_map(|root| root.nodes.iter())
+        {
+            let NormalizedNode::Element(elm) = node else {
+                continue;
+            };
+            for (position, child) in elm.children.iter().enumerate() {
+                if let UntaggedContent::Ref(idx) = child
+                    && let Some(source_position) = source_positions.get_mut(*idx)
+                {
+                    *source_position = position;
+                }
+            }
+        }
+
+        let mut parents = vec![None; num_components];
+        let mut siblings: HashMap<(Option<ComponentIdx>, String), Vec<ComponentIdx>> =
+            HashMap::new();
+        for component_idx in self.document_model.get_component_indices() {
+            let parent = self.document_model.get_true_component_parent(component_idx);
+            let component_type = self.document_model.get_component_type(component_idx)?;
+            parents[component_idx.as_usize()] = Some(parent);
+            siblings
+                .entry((parent, component_type))
+                .or_default()
+                .push(component_idx);
+        }
+
+        let mut steps: Vec<Option<ComponentAddressStep>> = vec![None; num_components];
+        for ((_, component_type), mut components) in siblings {
+            components.sort_by_key(|idx| (source_positions[idx.as_usize()], idx.as_usize()));
+            for (index, component_idx) in components.into_iter().enumerate() {
+                steps[component_idx.as_usize()] = Some(ComponentAddressStep {
+                    component_type: component_type.clone(),
+                    index,
+                });
+            }
+        }
+
+        // A parent always has a smaller index than its children,
+        // so the path of a parent is computed before the paths of its children.
+        let mut paths: Vec<Option<Vec<ComponentAddressStep>>> = vec![None; num_components];
+        for (idx, step) in steps.into_iter().enumerate() {
+            let (Some(parent), Some(step)) = (parents[idx], step) else {
+                continue;
+            };
+            let mut path = parent
+                .and_then(|p| paths[p.as_usize()].clone())
+                .unwrap_or_default();
+            path.push(step);
+            paths[idx] = Some(path);
+        }
+
+        Ok(paths
+            .into_iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let path = path?;
+                Some(match root_names.get(idx).cloned().flatten() {
+                    Some(name) => ComponentAddress::Name(name),
+                    None => ComponentAddress::Path(path),
+                })
+            })
+            .collect())
+    }
+
+    /// Create a snapshot of the interactive state of the document.
+    ///
+    /// Only state that has been changed from its default is recorded. State whose type
+    /// cannot be stored in a snapshot is skipped.
+    pub fn get_state_snapshot(&self) -> Result<StateSnapshot, CoreError> {
+        let addresses = self.get_component_addresses()?;
+
+        let mut states = Vec::new();
+        for (prop_pointer, value) in self.document_model.get_non_default_states()? {
+            let Ok(value) = SnapshotValue::try_from(value) else {
+                continue;
+            };
+            let component_idx = prop_pointer.component_idx;
+            let Some(component) = addresses[component_idx.as_usize()].clone() else {
+                continue;
+            };
+            states.push(StateSnapshotEntry {
+                component,
+                component_type: self.document_model.get_component_type(component_idx)?,
+                prop_name: self.document_model.get_prop_name(prop_pointer)?.to_string(),
+                value,
+            });
+        }
+
+        let mut strings = Vec::new();
+        for (component_idx, location, child_idx, value) in
+            self.document_model.get_modified_strings()?
+        {
+            let Some(component) = addresses[component_idx.as_usize()].clone() else {
+                continue;
+            };
+            strings.push(StringSnapshotEntry {
+                component,
+                component_type: self.document_model.get_component_type(component_idx)?,
+                location,
+                child_idx,
+                value,
+            });
+        }
+
+        Ok(StateSnapshot {
+            version: STATE_SNAPSHOT_VERSION,
+            states,
+            strings,
+        })
+    }
+
+    /// Apply a snapshot created by [`Core::get_state_snapshot`] and return any changes to the output flat dast.
+    ///
+    /// Entries that do not match the current document (e.g., an addressed component no longer exists,
+    /// has a different type, or has no state for the named prop) are ignored.
+    ///
+    /// A snapshot cannot be applied to a read-only document.
+    pub fn apply_state_snapshot(
+        &mut self,
+        snapshot: &StateSnapshot,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, StateSnapshotError> {
+        if self.document_model.get_flags().read_only {
+            return Err(CoreError::ReadOnly.into());
+        }
+        let flat_dast_updates = self.restore_state_snapshot(snapshot)?;
+
+        if self.is_recording_actions() {
+            self.record_event(RecordedEvent::ApplyStateSnapshot {
+                snapshot: snapshot.clone(),
+            })?;
+        }
+        Ok(flat_dast_updates)
+    }
+
+    /// Apply `snapshot` as in [`Core::apply_state_snapshot`], even if the document is read-only.
+    ///
+    /// This is used to keep the state of the document when core itself re-creates its components.
+    pub(super) fn restore_state_snapshot(
+        &mut self,
+        snapshot: &StateSnapshot,
+    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, StateSnapshotError> {
+        if snapshot.version != STATE_SNAPSHOT_VERSION {
+            return Err(StateSnapshotError::UnsupportedVersion {
+                found: snapshot.version,
+                expected: STATE_SNAPSHOT_VERSION,
+            });
+        }
+
+        let address_lookup: HashMap<ComponentAddress, ComponentIdx> = self
+            .get_component_addresses()?
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, address)| Some((address?, ComponentIdx::new(idx))))
+            .collect();
+        let find_component = |address: &ComponentAddress, component_type: &str| {
+            address_lookup.get(address).copied().filter(|&idx| {
+                self.document_model
+                    .get_component_type(idx)
+                    .is_ok_and(|t| t == component_type)
+            })
+        };
+
+        let mut changes_to_make = GraphNodeLookup::new();
+
+        for entry in &snapshot.states {
+            let Some(component_idx) = find_component(&entry.component, &entry.component_type)
+            else {
+                continue;
+            };
+            if let Some(state_node) = self
+                .document_model
+                .get_state_node_by_prop_name(component_idx, &entry.prop_name)?
+            {
+                changes_to_make.set_tag(state_node, entry.value.clone().into());
+            }
+        }
+
+        for entry in &snapshot.strings {
+            let Some(component_idx) = find_component(&entry.component, &entry.component_type)
+            else {
+                continue;
+            };
+            if let Some(string_node) =
+                self.document_model
+                    .get_string_node(component_idx, &entry.location, entry.child_idx)
+            {
+                changes_to_make.set_tag(string_node, PropValue::String(entry.value.clone().into()));
+            }
+        }
+
+        // The restored state is not the result of an action, so it cannot be undone.
+        self.action_history.clear();
+
+        Ok(self.apply_changes(changes_to_make)?)
+    }
+}
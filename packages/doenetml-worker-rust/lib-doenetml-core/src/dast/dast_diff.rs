@@ -0,0 +1,219 @@
+//! Compare two `DastRoot`s to find the parts of the source that an edit changed.
+
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{DastElement, DastElementContent, DastRoot};
+
+/// A change between two `DastRoot`s.
+///
+/// A `path` lists the index of a child of the root, followed by the index of a child of the element found so far, etc.
+/// The empty path refers to the root itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DastChange {
+    /// The value of the text child `child_idx` of the element at `path`
+    /// (or of its attribute `attribute`, if given) changed to `value`.
+    Text {
+        path: Vec<usize>,
+        attribute: Option<String>,
+        child_idx: usize,
+        value: String,
+    },
+    /// The children of the element at `path` in the range `old_range` were replaced
+    /// by the children in the range `new_range`.
+    Children {
+        path: Vec<usize>,
+        old_range: Range<usize>,
+        new_range: Range<usize>,
+    },
+}
+
+impl DastRoot {
+    /// Find the changes that turn `self` into `new_root`. Positions are ignored.
+    ///
+    /// Elements whose name and attribute names are unchanged are compared child by child, so a change is reported
+    /// at the deepest element it can be.
+    /// Text that changed is reported as a [`DastChange::Text`]; any other change is reported as a [`DastChange::Children`]
+    /// covering the children that differ.
+    pub fn changes_to(&self, new_root: &DastRoot) -> Vec<DastChange> {
+        let mut changes = Vec::new();
+        push_children_changes(
+            &mut changes,
+            &mut Vec::new(),
+            &self.children,
+            &new_root.children,
+        );
+        changes
+    }
+}
+
+/// Record the changes that turn `old_children`, the children of the element at `path`, into `new_children`.
+fn push_children_changes(
+    changes: &mut Vec<DastChange>,
+    path: &mut Vec<usize>,
+    old_children: &[DastElementContent],
+    new_children: &[DastElementContent],
+) {
+    if old_children.len() != new_children.len() {
+        // Report the children between the longest common prefix and the longest common suffix as replaced.
+        let prefix = old_children
+            .iter()
+            .zip(new_children)
+            .take_while(|(old, new)| content_eq(old, new))
+            .count();
+        let max_suffix = old_children.len().min(new_children.len()) - prefix;
+        let suffix = old_children
+            .iter()
+            .rev()
+            .zip(new_children.iter().rev())
+            .take(max_suffix)
+            .take_while(|(old, new)| content_eq(old, new))
+            .count();
+        changes.push(DastChange::Children {
+            path: path.clone(),
+            old_range: prefix..old_children.len() - suffix,
+            new_range: prefix..new_children.len() - suffix,
+        });
+        return;
+    }
+
+    for (child_idx, (old, new)) in old_children.iter().zip(new_children).enumerate() {
+        if content_eq(old, new) {
+            continue;
+        }
+        match (old, new) {
+            (DastElementContent::Text(_), DastElementContent::Text(new_text)) => {
+                changes.push(DastChange::Text {
+                    path: path.clone(),
+                    attribute: None,
+                    child_idx,
+                    value: new_text.value.clone(),
+                });
+            }
+            (DastElementContent::Element(old_elm), DastElementContent::Element(new_elm))
+                if have_same_shape(old_elm, new_elm) =>
+            {
+                path.push(child_idx);
+                push_attribute_changes(changes, path, old_elm, new_elm);
+                push_children_changes(changes, path, &old_elm.children, &new_elm.children);
+                path.pop();
+            }
+            _ => changes.push(DastChange::Children {
+                path: path.clone(),
+                old_range: child_idx..child_idx + 1,
+                new_range: child_idx..child_idx + 1,
+            }),
+        }
+    }
+}
+
+/// Record a [`DastChange::Text`] for each text child of an attribute of `new_elm`, the element at `path`,
+/// that differs from the corresponding text child of `old_elm`.
+///
+/// `old_elm` and `new_elm` must have the same shape (see [`have_same_shape`]).
+fn push_attribute_changes(
+    changes: &mut Vec<DastChange>,
+    path: &[usize],
+    old_elm: &DastElement,
+    new_elm: &DastElement,
+) {
+    let mut names = new_elm.attributes.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let old_children = to_values(&old_elm.attributes[name].children);
+        let new_children = to_values(&new_elm.attributes[name].children);
+        for (child_idx, (old, new)) in old_children.iter().zip(new_children.iter()).enumerate() {
+            if old != new {
+                changes.push(DastChange::Text {
+                    path: path.to_vec(),
+                    attribute: Some(name.clone()),
+                    child_idx,
+                    value: new["value"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Whether `old_elm` and `new_elm` can be compared child by child: they have the same name, come from the same document,
+/// and have the same attributes, whose children differ at most in the values of text children.
+fn have_same_shape(old_elm: &DastElement, new_elm: &DastElement) -> bool {
+    if old_elm.name != new_elm.name
+        || old_elm.source_doc != new_elm.source_doc
+        || old_elm.attributes.len() != new_elm.attributes.len()
+    {
+        return false;
+    }
+    old_elm.attributes.iter().all(|(name, old_attr)| {
+        let Some(new_attr) = new_elm.attributes.get(name) else {
+            return false;
+        };
+        let old_children = to_values(&old_attr.children);
+        let new_children = to_values(&new_attr.children);
+        old_children.len() == new_children.len()
+            && old_children
+                .iter()
+                .zip(new_children.iter())
+                .all(|(old, new)| old == new || (is_text(old) && is_text(new)))
+    })
+}
+
+/// Compare two nodes, ignoring positions.
+fn content_eq(old: &DastElementContent, new: &DastElementContent) -> bool {
+    match (old, new) {
+        (DastElementContent::Text(old_text), DastElementContent::Text(new_text)) => {
+            old_text.value == new_text.value && old_text.source_doc == new_text.source_doc
+        }
+        (DastElementContent::Element(old_elm), DastElementContent::Element(new_elm)) => {
+            old_elm.name == new_elm.name
+                && old_elm.source_doc == new_elm.source_doc
+                && old_elm.attributes.len() == new_elm.attributes.len()
+                && old_elm.attributes.iter().all(|(name, old_attr)| {
+                    new_elm.attributes.get(name).is_some_and(|new_attr| {
+                        to_values(&old_attr.children) == to_values(&new_attr.children)
+                    })
+                })
+                && old_elm.children.len() == new_elm.children.len()
+                && old_elm
+                    .children
+                    .iter()
+                    .zip(new_elm.children.iter())
+                    .all(|(old, new)| content_eq(old, new))
+        }
+        _ => to_value(old) == to_value(new),
+    }
+}
+
+/// Serialize `items` without their positions, so that they can be compared.
+fn to_values<T: Serialize>(items: &[T]) -> Vec<Value> {
+    items.iter().map(to_value).collect()
+}
+
+/// Serialize `item` without its positions, so that it can be compared.
+fn to_value<T: Serialize>(item: &T) -> Value {
+    let mut value = serde_json::to_value(item).unwrap_or_default();
+    strip_positions(&mut value);
+    value
+}
+
+fn strip_positions(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("position");
+            map.values_mut().for_each(strip_positions);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_positions),
+        _ => {}
+    }
+}
+
+/// Whether `value` is a serialized `DastText`.
+fn is_text(value: &Value) -> bool {
+    value["type"] == "text"
+}
+
+#[cfg(test)]
+#[path = "dast_diff.test.rs"]
+mod test;
@@ -0,0 +1,96 @@
+use super::*;
+use crate::test_utils::*;
+
+#[test]
+fn identical_roots_have_no_changes() {
+    let old_root = dast_root(r#"<p name="p">hello <text>there</text></p>$p"#);
+    let new_root = dast_root(r#"<p name="p">hello <text>there</text></p>$p"#);
+
+    assert_eq!(old_root.changes_to(&new_root), vec![]);
+}
+
+#[test]
+fn positions_are_ignored() {
+    let old_root = dast_root(r#"<p>hello</p> <text>there</text>"#);
+    let new_root = dast_root(r#"<p>hello</p>   <text>there</text>"#);
+
+    // Only the whitespace between the elements changed, even though the position of the `<text>` moved.
+    assert_eq!(
+        old_root.changes_to(&new_root),
+        vec![DastChange::Text {
+            path: vec![0],
+            attribute: None,
+            child_idx: 1,
+            value: "   ".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn text_changes_in_children_and_attributes_are_found() {
+    let old_root = dast_root(r#"<p>hello <math simplify="none">x</math></p>"#);
+    let new_root = dast_root(r#"<p>goodbye <math simplify="full">x</math></p>"#);
+
+    assert_eq!(
+        old_root.changes_to(&new_root),
+        vec![
+            DastChange::Text {
+                path: vec![0, 0],
+                attribute: None,
+                child_idx: 0,
+                value: "goodbye ".to_string(),
+            },
+            DastChange::Text {
+                path: vec![0, 0, 1],
+                attribute: Some("simplify".to_string()),
+                child_idx: 0,
+                value: "full".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn added_children_are_a_change_of_the_range_between_common_prefix_and_suffix() {
+    let old_root = dast_root(r#"<section><p>a</p><p>b</p></section>"#);
+    let new_root = dast_root(r#"<section><p>a</p><p>new</p><p>b</p></section>"#);
+
+    assert_eq!(
+        old_root.changes_to(&new_root),
+        vec![DastChange::Children {
+            path: vec![0, 0],
+            old_range: 1..1,
+            new_range: 1..2,
+        }]
+    );
+}
+
+#[test]
+fn changed_elements_are_replaced() {
+    let old_root = dast_root(r#"<section><p>a</p><p name="x">b</p></section>"#);
+    let new_root = dast_root(r#"<section><p>a</p><text>b</text></section>"#);
+
+    assert_eq!(
+        old_root.changes_to(&new_root),
+        vec![DastChange::Children {
+            path: vec![0, 0],
+            old_range: 1..2,
+            new_range: 1..2,
+        }]
+    );
+}
+
+#[test]
+fn changed_attributes_that_are_not_text_replace_the_element() {
+    let old_root = dast_root(r#"<section><math simplify="none">x</math></section>"#);
+    let new_root = dast_root(r#"<section><math simplify="$s">x</math></section>"#);
+
+    assert_eq!(
+        old_root.changes_to(&new_root),
+        vec![DastChange::Children {
+            path: vec![0, 0],
+            old_range: 0..1,
+            new_range: 0..1,
+        }]
+    );
+}
@@ -44,6 +44,30 @@ pub enum DastElementContent {
     Error(DastError),
 }
 
+impl DastRoot {
+    /// Replace the node at `path` with `content`, returning the node that was replaced.
+    ///
+    /// The first entry of `path` is the index of a child of the root, and each following entry
+    /// is the index of a child of the element found so far.
+    /// Returns `None`, leaving `self` unchanged, if `path` does not point to a node.
+    pub fn replace_content(
+        &mut self,
+        path: &[usize],
+        content: DastElementContent,
+    ) -> Option<DastElementContent> {
+        let (&last_idx, parent_path) = path.split_last()?;
+        let mut siblings = &mut self.children;
+        for &child_idx in parent_path {
+            match siblings.get_mut(child_idx)? {
+                DastElementContent::Element(elm) => siblings = &mut elm.children,
+                _ => return None,
+            }
+        }
+        let node = siblings.get_mut(last_idx)?;
+        Some(std::mem::replace(node, content))
+    }
+}
+
 impl DastElementContent {
     pub fn element_with_name(name: &str) -> Self {
         DastElementContent::Element(DastElement::with_name(name))
@@ -123,6 +147,10 @@ pub struct ElementData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 
+    /// For an error resolving a reference, the position of the part of the path that failed to resolve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_part_position: Option<Position>,
+
     #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
     pub props: Option<ForRenderProps>,
 }
@@ -137,6 +165,7 @@ impl PartialEq for ElementData {
         self.id == other.id
             && self.action_names == other.action_names
             && self.message == other.message
+            && self.path_part_position == other.path_part_position
     }
 }
 
@@ -514,8 +543,11 @@ impl Serialize for FlatDastElement {
         let have_source_doc = self.source_doc.is_some();
 
         if self.name == "_error" {
-            let n_fields =
-                2 + if have_position { 1 } else { 0 } + if have_source_doc { 1 } else { 0 };
+            let have_path_part_position = self.data.path_part_position.is_some();
+            let n_fields = 2
+                + if have_position { 1 } else { 0 }
+                + if have_path_part_position { 1 } else { 0 }
+                + if have_source_doc { 1 } else { 0 };
 
             let mut state = serializer.serialize_struct("error", n_fields)?;
             state.serialize_field("type", "error")?;
@@ -530,6 +562,9 @@ impl Serialize for FlatDastElement {
             if have_position {
                 state.serialize_field("position", &self.position)?;
             }
+            if have_path_part_position {
+                state.serialize_field("path_part_position", &self.data.path_part_position)?;
+            }
             if have_source_doc {
                 state.serialize_field("source_doc", &self.source_doc)?;
             }
@@ -573,7 +608,7 @@ pub struct DastWarning {
 
 /// An update to a single element in the Dast tree.
 /// It may contain changes to the element's attributes, children, or `data.state`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename = "elementUpdate")]
 #[serde(rename_all = "camelCase")]
@@ -582,6 +617,10 @@ pub struct FlatDastElementUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub new_children: Option<Vec<FlatDastElementContent>>,
 
+    /// The attributes whose values changed, keyed by name. Attributes that are not listed are unchanged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_attributes: Option<HashMap<String, DastAttribute>>,
+
     #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
     pub changed_state: Option<ForRenderProps>,
 }
@@ -0,0 +1,488 @@
+//! # External documents
+//!
+//! An element can extend a document that is not part of the source, e.g. `<section extend="doenet:abc" />`.
+//! The host supplies a [`DocumentLoader`] that looks up such documents, and
+//! [`FlatRoot::merge_external_documents`] merges each of them into the element that extends it.
+//! Every external document gets its own [`SourceDoc`], so that the names it contains
+//! are resolved within that document.
+
+use std::{cell::RefCell, collections::HashMap, fmt::Debug};
+
+use serde_json::json;
+use thiserror::Error;
+
+use super::{
+    DastElement, DastElementContent, DastError, DastRoot, DastTextRefElementContent,
+    DiagnosticArgs, Position,
+    flat_dast::{
+        ErrorType, FlatAttribute, FlatNode, FlatPathPart, FlatRoot, Index, SourceDoc,
+        UntaggedContent,
+    },
+    parser,
+};
+
+/// The prefix of the value of an `extend` or `copy` attribute that refers to an external document.
+const EXTERNAL_PREFIX: &str = "doenet:";
+
+/// Looks up the external documents referred to by `extend` or `copy` attributes beginning with `doenet:`.
+pub trait DocumentLoader: Debug {
+    /// Load the document identified by `uri`, e.g. `doenet:abc`.
+    fn load(&self, uri: &str) -> Result<DastRoot, DocumentLoadError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DocumentLoadError {
+    /// The loader has no document with the given URI.
+    #[error("No document found for `{0}`")]
+    NotFound(String),
+    /// The document exists but could not be retrieved, e.g. because of a network error.
+    #[error("Unable to load `{uri}`: {message}")]
+    Failed { uri: String, message: String },
+}
+
+/// A [`DocumentLoader`] that parses DoenetML sources kept in memory, e.g. for tests.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryDocumentLoader {
+    sources: HashMap<String, String>,
+}
+
+impl InMemoryDocumentLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the DoenetML `source` of the document identified by `uri`, replacing any previous source.
+    pub fn insert(&mut self, uri: impl Into<String>, source: impl Into<String>) {
+        self.sources.insert(uri.into(), source.into());
+    }
+
+    /// Add the DoenetML `source` of the document identified by `uri`. See [`InMemoryDocumentLoader::insert`].
+    pub fn with_document(mut self, uri: impl Into<String>, source: impl Into<String>) -> Self {
+        self.insert(uri, source);
+        self
+    }
+}
+
+impl DocumentLoader for InMemoryDocumentLoader {
+    fn load(&self, uri: &str) -> Result<DastRoot, DocumentLoadError> {
+        self.sources
+            .get(uri)
+            .map(|source| parser::parse(source))
+            .ok_or_else(|| DocumentLoadError::NotFound(uri.to_string()))
+    }
+}
+
+/// A [`DocumentLoader`] that keeps the documents loaded by `loader` in `cache`, by URI,
+/// so that each document is loaded only once. Documents that could not be loaded are not kept.
+#[derive(Debug)]
+pub struct CachingDocumentLoader<'a> {
+    pub loader: &'a dyn DocumentLoader,
+    pub cache: &'a RefCell<HashMap<String, DastRoot>>,
+}
+
+impl DocumentLoader for CachingDocumentLoader<'_> {
+    fn load(&self, uri: &str) -> Result<DastRoot, DocumentLoadError> {
+        if let Some(dast) = self.cache.borrow().get(uri) {
+            return Ok(dast.clone());
+        }
+        let dast = self.loader.load(uri)?;
+        self.cache
+            .borrow_mut()
+            .insert(uri.to_string(), dast.clone());
+        Ok(dast)
+    }
+}
+
+/// An element whose `extend` or `copy` attribute refers to an external document
+/// that has yet to be merged.
+struct PendingDocument {
+    idx: Index,
+    /// The external documents that were merged to reach the element, outermost first.
+    /// If the element refers to one of these, the documents include each other.
+    loading: Vec<String>,
+}
+
+/// An `extend` or `copy` attribute that refers to an external document.
+struct ExternalReference {
+    attribute_name: String,
+    uri: String,
+    position: Option<Position>,
+    source_doc: Option<SourceDoc>,
+}
+
+impl FlatRoot {
+    /// Merge the external documents referred to by `extend` or `copy` attributes beginning with `doenet:`,
+    /// looking them up with `loader`. This must be called before refs are expanded.
+    ///
+    /// The external document must consist of a single element of the same type as the element that extends it
+    /// (possibly wrapped in a `<document>`). For example, if `<section extend="doenet:abc" name="s" />`
+    /// extends `<section name="intro"><p name="p">Hi</p></section>`, then
+    /// - the children of the external `<section>` are prepended to the children of `s`,
+    /// - the attributes of the external `<section>` are added to `s`, unless `s` has an attribute of the same name,
+    ///   with `name` renamed to `source-n:name` as it only applies within the external document,
+    /// - `n` is appended to the `source:sequence` attribute of `s`, and
+    /// - the merged nodes are marked with `source_doc` `n`,
+    ///
+    /// where `n` is the index of the external document in `sources`. The `extend` attribute is removed.
+    /// External documents that in turn extend external documents are merged as well.
+    ///
+    /// If a document cannot be loaded, does not match the type of the element, or (indirectly) includes itself,
+    /// an error is prepended to the children of the element instead.
+    pub fn merge_external_documents(&mut self, loader: &dyn DocumentLoader) {
+        let mut pending = self.pending_documents(0, &[]);
+        pending.reverse();
+        while let Some(document) = pending.pop() {
+            let mut merged = self.merge_external_document(document, loader);
+            merged.reverse();
+            pending.extend(merged);
+        }
+    }
+
+    /// The elements with index `start_idx` or more that refer to external documents.
+    fn pending_documents(&self, start_idx: Index, loading: &[String]) -> Vec<PendingDocument> {
+        (start_idx..self.nodes.len())
+            .filter(|&idx| self.external_reference(idx).is_some())
+            .map(|idx| PendingDocument {
+                idx,
+                loading: loading.to_vec(),
+            })
+            .collect()
+    }
+
+    /// The `extend` or `copy` attribute of the element at `idx`, if it refers to an external document.
+    fn external_reference(&self, idx: Index) -> Option<ExternalReference> {
+        let FlatNode::Element(element) = &self.nodes[idx] else {
+            return None;
+        };
+        element.attributes.iter().find_map(|attr| {
+            if !is_extend_or_copy(attr) {
+                return None;
+            }
+            let mut uri = String::new();
+            for child in attr.children.iter() {
+                match child {
+                    UntaggedContent::Text(text) => uri.push_str(text),
+                    UntaggedContent::Ref(_) => return None,
+                }
+            }
+            let uri = uri.trim();
+            uri.starts_with(EXTERNAL_PREFIX).then(|| ExternalReference {
+                attribute_name: attr.name.clone(),
+                uri: uri.to_string(),
+                position: attr.position.clone(),
+                source_doc: attr.source_doc,
+            })
+        })
+    }
+
+    /// Merge the external document referred to by the element of `document` into the element.
+    /// Returns the elements of the merged content that refer to further external documents.
+    fn merge_external_document(
+        &mut self,
+        document: PendingDocument,
+        loader: &dyn DocumentLoader,
+    ) -> Vec<PendingDocument> {
+        let PendingDocument { idx, mut loading } = document;
+        let Some(reference) = self.external_reference(idx) else {
+            return Vec::new();
+        };
+
+        // The external document supersedes any other `extend` or `copy` attribute.
+        let FlatNode::Element(element) = &mut self.nodes[idx] else {
+            unreachable!("only elements refer to external documents");
+        };
+        element.attributes.retain(|attr| !is_extend_or_copy(attr));
+        let element_name = element.name.clone();
+
+        if loading.contains(&reference.uri) {
+            let cycle = loading
+                .iter()
+                .chain([&reference.uri])
+                .map(|uri| format!("`{uri}`"))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            self.prepend_error(
+                idx,
+                DastError {
+                    message: format!("Circular reference to external DoenetML: {cycle}"),
+                    error_type: Some(ErrorType::Error),
+                    code: Some("doenet-e0029".to_string()),
+                    args: None,
+                    position: reference.position,
+                    source_doc: reference.source_doc,
+                },
+            );
+            return Vec::new();
+        }
+
+        let mut args = DiagnosticArgs::new();
+        args.insert("attribute".to_string(), json!(reference.attribute_name));
+        args.insert("uri".to_string(), json!(reference.uri));
+
+        let dast = match loader.load(&reference.uri) {
+            Ok(dast) => dast,
+            Err(err) => {
+                self.prepend_error(
+                    idx,
+                    DastError {
+                        message: format!(
+                            "Unable to retrieve DoenetML from {}=\"{}\": {err}",
+                            reference.attribute_name, reference.uri
+                        ),
+                        error_type: Some(ErrorType::Error),
+                        code: Some("doenet-e0030".to_string()),
+                        args: Some(args),
+                        position: reference.position,
+                        source_doc: reference.source_doc,
+                    },
+                );
+                return Vec::new();
+            }
+        };
+
+        let Some(external_element) = matching_element(&dast, &element_name) else {
+            args.insert("componentType".to_string(), json!(element_name));
+            self.prepend_error(
+                idx,
+                DastError {
+                    message: format!(
+                        "Invalid DoenetML retrieved from {}=\"{}\": it did not match the component type \"{element_name}\"",
+                        reference.attribute_name, reference.uri
+                    ),
+                    error_type: Some(ErrorType::Error),
+                    code: Some("doenet-e0031".to_string()),
+                    args: Some(args),
+                    position: reference.position,
+                    source_doc: reference.source_doc,
+                },
+            );
+            return Vec::new();
+        };
+
+        if self.sources.is_empty() {
+            self.sources.push(String::new());
+        }
+        let source_doc = SourceDoc::from(self.sources.len() as u16);
+        self.sources
+            .push(dast.sources.first().cloned().unwrap_or_default());
+
+        let start_idx = self.nodes.len();
+        self.merge_external_element(idx, external_element, source_doc);
+        for node in self.nodes[start_idx..].iter_mut() {
+            set_source_doc(node, source_doc);
+        }
+
+        // The external element may itself have extended an external document,
+        // in which case its `extend` attribute was merged into the element.
+        loading.push(reference.uri);
+        let mut pending = Vec::new();
+        if self.external_reference(idx).is_some() {
+            pending.push(PendingDocument {
+                idx,
+                loading: loading.clone(),
+            });
+        }
+        pending.extend(self.pending_documents(start_idx, &loading));
+        pending
+    }
+
+    /// Merge the children and attributes of `external_element`, which comes from `source_doc`,
+    /// into the element at `idx`.
+    fn merge_external_element(
+        &mut self,
+        idx: Index,
+        external_element: &DastElement,
+        source_doc: SourceDoc,
+    ) {
+        // Both the element and the external element may end with a `<_dynamicChildren>`;
+        // the element keeps only its own.
+        let has_dynamic_children = self.element_children(idx).iter().any(|child| {
+            matches!(child, UntaggedContent::Ref(child_idx)
+                if matches!(&self.nodes[*child_idx], FlatNode::Element(child) if child.name == "_dynamicChildren"))
+        });
+        let external_children = external_element
+            .children
+            .iter()
+            .filter(|child| {
+                !(has_dynamic_children
+                    && matches!(child, DastElementContent::Element(child) if child.name == "_dynamicChildren"))
+            })
+            .cloned()
+            .collect();
+
+        // `merge_dast_root` adds the content as children of the root, so we move them to the element.
+        let num_root_children = self.children.len();
+        self.merge_dast_root(&DastRoot {
+            children: external_children,
+            position: None,
+            sources: Vec::new(),
+        });
+        let children = self.children.split_off(num_root_children);
+        for child in children.iter() {
+            if let UntaggedContent::Ref(child_idx) = child {
+                self.nodes[*child_idx].set_parent(Some(idx));
+            }
+        }
+
+        // The name of the external element only applies within the external document.
+        let mut external_attributes: Vec<_> = external_element
+            .attributes
+            .values()
+            .map(|attr| {
+                let name = if attr.name == "name" {
+                    format!("source-{source_doc}:name")
+                } else {
+                    attr.name.clone()
+                };
+                (name, attr)
+            })
+            // Attributes of the element take precedence over those of the external element.
+            .filter(|(name, _)| {
+                !self
+                    .element_attributes(idx)
+                    .iter()
+                    .any(|existing| existing.name.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        external_attributes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let attributes: Vec<_> = external_attributes
+            .into_iter()
+            .map(|(name, attr)| FlatAttribute {
+                name,
+                parent: Some(idx),
+                children: attr
+                    .children
+                    .iter()
+                    .map(|child| self.merge_content(&attribute_content(child), Some(idx)))
+                    .collect(),
+                position: attr.position.clone(),
+                source_doc: Some(source_doc),
+            })
+            .collect();
+
+        let FlatNode::Element(element) = &mut self.nodes[idx] else {
+            unreachable!("only elements refer to external documents");
+        };
+        element.children.splice(0..0, children);
+        element.attributes.extend(attributes);
+
+        // `source:sequence` lists the element's own source followed by the sources it extended,
+        // so that refs from the element's source can reach names in the external documents.
+        match element
+            .attributes
+            .iter_mut()
+            .find(|attr| attr.name == "source:sequence")
+        {
+            Some(source_sequence) => source_sequence
+                .children
+                .push(UntaggedContent::Text(source_doc.to_string())),
+            None => {
+                let element_source: SourceDoc = element.source_doc.into();
+                element.attributes.push(FlatAttribute {
+                    name: "source:sequence".to_string(),
+                    parent: Some(idx),
+                    children: vec![
+                        UntaggedContent::Text(element_source.to_string()),
+                        UntaggedContent::Text(source_doc.to_string()),
+                    ],
+                    position: None,
+                    source_doc: element.source_doc,
+                });
+            }
+        }
+    }
+
+    /// Prepend `error` to the children of the element at `idx`.
+    fn prepend_error(&mut self, idx: Index, error: DastError) {
+        let error = self.merge_content(&DastElementContent::Error(error), Some(idx));
+        if let FlatNode::Element(element) = &mut self.nodes[idx] {
+            element.children.insert(0, error);
+        }
+    }
+
+    fn element_children(&self, idx: Index) -> &[UntaggedContent] {
+        match &self.nodes[idx] {
+            FlatNode::Element(element) => &element.children,
+            _ => &[],
+        }
+    }
+
+    fn element_attributes(&self, idx: Index) -> &[FlatAttribute] {
+        match &self.nodes[idx] {
+            FlatNode::Element(element) => &element.attributes,
+            _ => &[],
+        }
+    }
+}
+
+fn is_extend_or_copy(attr: &FlatAttribute) -> bool {
+    attr.name.eq_ignore_ascii_case("extend") || attr.name.eq_ignore_ascii_case("copy")
+}
+
+/// The element of `dast` that can be extended by an element named `element_name`: either the only element of `dast`
+/// or the only element of a `<document>` that is the only element of `dast`. Blank text is ignored.
+fn matching_element<'a>(dast: &'a DastRoot, element_name: &str) -> Option<&'a DastElement> {
+    fn only_element(children: &[DastElementContent]) -> Option<&DastElement> {
+        let mut non_blank = children.iter().filter(
+            |child| !matches!(child, DastElementContent::Text(text) if text.value.trim().is_empty()),
+        );
+        match (non_blank.next(), non_blank.next()) {
+            (Some(DastElementContent::Element(element)), None) => Some(element),
+            _ => None,
+        }
+    }
+
+    let element = only_element(&dast.children)?;
+    if element.name == element_name {
+        return Some(element);
+    }
+    if element.name == "document" {
+        return only_element(&element.children).filter(|child| child.name == element_name);
+    }
+    None
+}
+
+fn attribute_content(child: &DastTextRefElementContent) -> DastElementContent {
+    match child {
+        DastTextRefElementContent::Text(txt) => DastElementContent::Text(txt.clone()),
+        DastTextRefElementContent::Ref(ref_) => DastElementContent::Ref(ref_.clone()),
+        DastTextRefElementContent::FunctionRef(function_ref) => {
+            DastElementContent::FunctionRef(function_ref.clone())
+        }
+        DastTextRefElementContent::Element(element) => DastElementContent::Element(element.clone()),
+    }
+}
+
+/// Mark `node`, along with its attributes and paths, as coming from `source_doc`.
+fn set_source_doc(node: &mut FlatNode, source_doc: SourceDoc) {
+    fn set_path_source_doc(path: &mut [FlatPathPart], source_doc: SourceDoc) {
+        for path_part in path.iter_mut() {
+            path_part.source_doc = Some(source_doc);
+            for index in path_part.index.iter_mut() {
+                index.source_doc = Some(source_doc);
+            }
+        }
+    }
+
+    match node {
+        FlatNode::Element(element) => {
+            element.source_doc = Some(source_doc);
+            for attr in element.attributes.iter_mut() {
+                attr.source_doc = Some(source_doc);
+            }
+        }
+        FlatNode::Error(error) => error.source_doc = Some(source_doc),
+        FlatNode::Ref(ref_) => {
+            ref_.source_doc = Some(source_doc);
+            set_path_source_doc(&mut ref_.path, source_doc);
+        }
+        FlatNode::FunctionRef(function_ref) => {
+            function_ref.source_doc = Some(source_doc);
+            set_path_source_doc(&mut function_ref.path, source_doc);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "document_loader.test.rs"]
+mod test;
@@ -0,0 +1,231 @@
+use super::*;
+use crate::test_utils::*;
+
+fn merged_flat_root(source: &str, loader: &InMemoryDocumentLoader) -> FlatRoot {
+    let mut flat_root = FlatRoot::from_dast(&dast_root_no_position(source));
+    flat_root.merge_external_documents(loader);
+    flat_root
+}
+
+fn element(flat_root: &FlatRoot, idx: Index) -> &crate::dast::flat_dast::FlatElement {
+    match &flat_root.nodes[idx] {
+        FlatNode::Element(element) => element,
+        node => panic!("expected an element, found {node:?}"),
+    }
+}
+
+fn attribute_texts(flat_root: &FlatRoot, idx: Index) -> Vec<(String, Vec<String>)> {
+    element(flat_root, idx)
+        .attributes
+        .iter()
+        .map(|attr| {
+            let texts = attr
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    UntaggedContent::Text(text) => Some(text.clone()),
+                    UntaggedContent::Ref(_) => None,
+                })
+                .collect();
+            (attr.name.clone(), texts)
+        })
+        .collect()
+}
+
+/// The messages of the errors among the children of the element at `idx`.
+fn error_messages(flat_root: &FlatRoot, idx: Index) -> Vec<String> {
+    element(flat_root, idx)
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            UntaggedContent::Ref(child_idx) => match &flat_root.nodes[*child_idx] {
+                FlatNode::Error(error) => Some(error.message.clone()),
+                _ => None,
+            },
+            UntaggedContent::Text(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn external_document_is_merged_into_extending_element() {
+    let loader = InMemoryDocumentLoader::new().with_document(
+        "doenet:abc",
+        r#"<p name="inner" hide="true"><text name="t">a</text></p>"#,
+    );
+    let flat_root = merged_flat_root(
+        r#"<p extend="doenet:abc" name="outer" hide="false"><text>b</text></p>"#,
+        &loader,
+    );
+    let p_idx = 1;
+
+    assert_eq!(flat_root.sources.len(), 2);
+    assert_eq!(
+        flat_root.sources[1],
+        r#"<p name="inner" hide="true"><text name="t">a</text></p>"#
+    );
+
+    let mut attributes = attribute_texts(&flat_root, p_idx);
+    attributes.sort();
+    assert_eq!(
+        attributes,
+        vec![
+            ("hide".to_string(), vec!["false".to_string()]),
+            ("name".to_string(), vec!["outer".to_string()]),
+            ("source-1:name".to_string(), vec!["inner".to_string()]),
+            (
+                "source:sequence".to_string(),
+                vec!["0".to_string(), "1".to_string()]
+            ),
+        ]
+    );
+
+    // The external children come first and are marked with the external source
+    let p = element(&flat_root, p_idx);
+    assert_eq!(p.source_doc, None);
+    let [
+        UntaggedContent::Ref(external_idx),
+        UntaggedContent::Ref(own_idx),
+    ] = p.children[..]
+    else {
+        panic!("unexpected children {:?}", p.children);
+    };
+    let external_text = element(&flat_root, external_idx);
+    assert_eq!(external_text.parent, Some(p_idx));
+    assert_eq!(external_text.source_doc, Some(SourceDoc::from(1)));
+    assert_eq!(
+        external_text.attributes[0].source_doc,
+        Some(SourceDoc::from(1))
+    );
+    assert_eq!(
+        external_text.children,
+        vec![UntaggedContent::Text("a".into())]
+    );
+    assert_eq!(element(&flat_root, own_idx).source_doc, None);
+
+    // Nothing was added to the root
+    assert_eq!(flat_root.children.len(), 1);
+}
+
+#[test]
+fn external_documents_can_extend_external_documents() {
+    let loader = InMemoryDocumentLoader::new()
+        .with_document("doenet:abc", r#"<p extend="doenet:def"><text>a</text></p>"#)
+        .with_document(
+            "doenet:def",
+            r#"<document><p><text>b</text><p extend="doenet:ghi" /></p></document>"#,
+        )
+        .with_document("doenet:ghi", r#"<p>c</p>"#);
+    let flat_root = merged_flat_root(r#"<p extend="doenet:abc" />"#, &loader);
+
+    assert_eq!(flat_root.sources.len(), 4);
+    assert_eq!(
+        attribute_texts(&flat_root, 1),
+        vec![(
+            "source:sequence".to_string(),
+            vec!["0".to_string(), "1".to_string(), "2".to_string()]
+        )]
+    );
+
+    // The nested `<p>` came from `doenet:def`, so its own `source:sequence` starts with that source
+    let nested_p = flat_root
+        .nodes
+        .iter()
+        .find_map(|node| match node {
+            FlatNode::Element(element)
+                if element.name == "p"
+                    && element.children == vec![UntaggedContent::Text("c".into())] =>
+            {
+                Some(element.idx)
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        attribute_texts(&flat_root, nested_p),
+        vec![(
+            "source:sequence".to_string(),
+            vec!["2".to_string(), "3".to_string()]
+        )]
+    );
+}
+
+#[test]
+fn cyclic_includes_are_errors() {
+    let loader = InMemoryDocumentLoader::new()
+        .with_document("doenet:abc", r#"<p extend="doenet:def">a</p>"#)
+        .with_document("doenet:def", r#"<p><p extend="doenet:abc" /></p>"#);
+    let flat_root = merged_flat_root(r#"<p extend="doenet:abc" />"#, &loader);
+
+    // `doenet:abc` and `doenet:def` were each merged once more inside `doenet:def` before the cycle was found
+    assert_eq!(flat_root.sources.len(), 3);
+    let messages: Vec<_> = (0..flat_root.nodes.len())
+        .filter(|&idx| matches!(flat_root.nodes[idx], FlatNode::Element(_)))
+        .flat_map(|idx| error_messages(&flat_root, idx))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Circular reference to external DoenetML: `doenet:abc` -> `doenet:def` -> `doenet:abc`"
+        ]
+    );
+    let codes: Vec<_> = flat_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            FlatNode::Error(error) => Some(error.code.as_deref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(codes, vec![Some("doenet-e0029")]);
+}
+
+#[test]
+fn unavailable_and_mismatched_documents_are_errors() {
+    let loader = InMemoryDocumentLoader::new().with_document("doenet:abc", r#"<text>a</text>"#);
+    let flat_root = merged_flat_root(
+        r#"<p extend="doenet:abc" /><p copy="doenet:missing" />"#,
+        &loader,
+    );
+
+    assert_eq!(flat_root.sources.len(), 1);
+    assert_eq!(
+        error_messages(&flat_root, 1),
+        vec![
+            r#"Invalid DoenetML retrieved from extend="doenet:abc": it did not match the component type "p""#
+        ]
+    );
+    assert_eq!(
+        error_messages(&flat_root, 2),
+        vec![
+            r#"Unable to retrieve DoenetML from copy="doenet:missing": No document found for `doenet:missing`"#
+        ]
+    );
+
+    // The attributes are replaced by the errors
+    assert!(attribute_texts(&flat_root, 1).is_empty());
+    assert!(attribute_texts(&flat_root, 2).is_empty());
+
+    let FlatNode::Error(error) = &flat_root.nodes[3] else {
+        panic!("expected an error");
+    };
+    assert_eq!(error.code.as_deref(), Some("doenet-e0031"));
+    assert_eq!(
+        error.args.as_ref().unwrap()["componentType"],
+        serde_json::json!("p")
+    );
+}
+
+#[test]
+fn other_extend_attributes_are_left_for_ref_expansion() {
+    let loader = InMemoryDocumentLoader::new();
+    let flat_root = merged_flat_root(r#"<p name="a" /><p extend="$a" />"#, &loader);
+    assert_eq!(flat_root.sources.len(), 1);
+    assert_eq!(
+        attribute_texts(&flat_root, 2)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["extend"]
+    );
+}
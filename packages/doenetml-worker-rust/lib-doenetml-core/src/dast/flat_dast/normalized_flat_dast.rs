@@ -26,6 +26,7 @@ impl Default for NormalizedNode {
             args: None,
             unresolved_path: None,
             position: None,
+            path_part_position: None,
             source_doc: None,
             idx: 0,
         })
@@ -45,6 +46,14 @@ impl NormalizedNode {
         }
     }
 
+    /// Convert the node back to a `FlatNode`.
+    pub fn to_flat_node(&self) -> FlatNode {
+        match self {
+            NormalizedNode::Element(e) => FlatNode::Element(e.clone()),
+            NormalizedNode::Error(e) => FlatNode::Error(e.clone()),
+        }
+    }
+
     /// Get the index of the node.
     pub fn idx(&self) -> Index {
         match self {
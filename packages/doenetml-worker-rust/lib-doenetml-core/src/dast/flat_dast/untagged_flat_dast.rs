@@ -226,6 +226,9 @@ pub struct FlatError {
     pub unresolved_path: Option<Vec<FlatPathPart>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<Position>,
+    /// For an error resolving a reference, the position of the part of the path that failed to resolve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_part_position: Option<Position>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_doc: Option<SourceDoc>,
     pub idx: Index,
@@ -241,6 +244,7 @@ impl FlatError {
             args: None,
             unresolved_path: None,
             position: None,
+            path_part_position: None,
             source_doc: None,
             idx,
         }
@@ -333,6 +337,7 @@ impl Default for FlatNode {
             args: None,
             unresolved_path: None,
             position: None,
+            path_part_position: None,
             source_doc: None,
             idx: 0,
         })
@@ -370,6 +375,16 @@ impl FlatNode {
         }
     }
 
+    /// Get the position of the node in its source.
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            FlatNode::Element(e) => e.position.as_ref(),
+            FlatNode::Error(e) => e.position.as_ref(),
+            FlatNode::FunctionRef(e) => e.position.as_ref(),
+            FlatNode::Ref(e) => e.position.as_ref(),
+        }
+    }
+
     /// Set the parent of the node to `parent`
     pub fn set_parent(&mut self, parent: Option<Index>) {
         match self {
@@ -460,8 +475,6 @@ impl FlatFragment {
         idx_to_id_shift: usize,
         parent_idx: Option<Index>,
     ) -> Self {
-        // TODO: this function is unused. If we use it, it needs tests.
-
         let mut flat_root = FlatRoot::from_dast(dast);
 
         // shift the indices of the children
@@ -474,7 +487,7 @@ impl FlatFragment {
         let mut idx_map = HashMap::new();
 
         // Shift the indices, parent indices, child indices, attribute child indices,
-        // extend indices, and input indices.
+        // extend indices, path index indices, and input indices.
         // If a parent is `None`, set it to `parent_idx`.
         for node in flat_root.nodes.iter_mut() {
             idx_map.insert(node.idx() + idx_to_id_shift, node.idx());
@@ -507,14 +520,18 @@ impl FlatFragment {
                         extend.set_idx(extend.idx() + idx_to_id_shift);
                     }
                 }
+                FlatNode::Ref(flat_ref) => {
+                    shift_path_indices(&mut flat_ref.path, idx_to_id_shift);
+                }
                 FlatNode::FunctionRef(flat_function_ref) => {
+                    shift_path_indices(&mut flat_function_ref.path, idx_to_id_shift);
                     for input in flat_function_ref.input.iter_mut().flatten().flatten() {
                         if let UntaggedContent::Ref(idx) = input {
                             *idx += idx_to_id_shift
                         }
                     }
                 }
-                _ => {}
+                FlatNode::Error(_) => {}
             }
         }
 
@@ -553,6 +570,16 @@ impl FlatFragment {
         &self.nodes[self.idx_map[&idx]]
     }
 
+    /// Replace the nodes of the fragment with `nodes`, e.g., after expanding the refs of the nodes.
+    pub fn set_nodes(&mut self, nodes: Vec<FlatNode>) {
+        self.idx_map = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.idx(), i))
+            .collect();
+        self.nodes = nodes;
+    }
+
     pub fn min_idx(&self) -> usize {
         self.idx_map
             .keys()
@@ -562,6 +589,19 @@ impl FlatFragment {
     }
 }
 
+/// Shift the indices of the nodes referenced in the index of each part of `path`, e.g., the `$n` in `$p[$n]`.
+fn shift_path_indices(path: &mut [FlatPathPart], idx_to_id_shift: usize) {
+    for value in path
+        .iter_mut()
+        .flat_map(|path_part| path_part.index.iter_mut())
+        .flat_map(|index| index.value.iter_mut())
+    {
+        if let UntaggedContent::Ref(idx) = value {
+            *idx += idx_to_id_shift
+        }
+    }
+}
+
 pub enum FlatRootOrFragment<'a> {
     Root(&'a FlatRoot),
     Fragment(&'a FlatFragment),
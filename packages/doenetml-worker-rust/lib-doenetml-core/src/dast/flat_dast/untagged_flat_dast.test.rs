@@ -198,3 +198,73 @@ fn flattening_carries_a_parser_diagnostic_code() {
     // the message catalogs still has something to show.
     assert!(error.message.starts_with("Invalid DoenetML: The tag `<p>`"));
 }
+
+#[test]
+fn can_flatten_dast_root_into_shifted_fragment() {
+    let dast_root = dast_root_no_position(r#"<p>a<text extend="$x" /></p>$x"#);
+    let fragment = FlatFragment::from_dast_with_id_shift(&dast_root, 10, Some(3));
+
+    assert_eq!(fragment.children, vec![UntaggedContent::Ref(10)]);
+    assert_eq!(fragment.parent_idx, Some(3));
+    assert_eq!(fragment.min_idx(), 10);
+    assert_eq!(fragment.len(), 15);
+    // Nodes without a parent in the fragment get the parent of the fragment
+    assert_json_eq!(
+        serde_json::to_value(&fragment.nodes).unwrap(),
+        json!([
+          {
+            "type": "element",
+            "name": "document",
+            "parent": 3,
+            "children": [11, 14],
+            "attributes": [],
+            "idx": 10
+          },
+          {
+            "type": "element",
+            "name": "p",
+            "parent": 10,
+            "children": ["a", 12],
+            "attributes": [],
+            "idx": 11
+          },
+          {
+            "type": "element",
+            "name": "text",
+            "parent": 11,
+            "children": [],
+            "attributes": [
+              { "type": "attribute", "name": "extend", "parent": 12, "children": [13] }
+            ],
+            "idx": 12
+          },
+          {
+            "type": "ref",
+            "parent": 12,
+            "path": [{ "type": "flatPathPart", "name": "x", "index": [] }],
+            "idx": 13
+          },
+          {
+            "type": "ref",
+            "parent": 10,
+            "path": [{ "type": "flatPathPart", "name": "x", "index": [] }],
+            "idx": 14
+          }
+        ])
+    );
+}
+
+#[test]
+fn shifted_fragment_shifts_refs_in_path_indices() {
+    let dast_root = dast_root_no_position(r#"$p[$n].x"#);
+    let fragment = FlatFragment::from_dast_with_id_shift(&dast_root, 10, Some(3));
+
+    let FlatNode::Ref(flat_ref) = fragment.get_node(11) else {
+        panic!("Expected a ref, found {:?}", fragment.get_node(11));
+    };
+    assert_eq!(
+        flat_ref.path[0].index[0].value,
+        vec![UntaggedContent::Ref(12)]
+    );
+    assert_eq!(fragment.get_node(12).parent(), Some(11));
+}
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use crate::dast::ref_resolve::Resolver;
 
-use super::{FlatNode, FlatRoot, Index, UntaggedContent};
+use super::{FlatFragment, FlatNode, FlatRoot, Index, UntaggedContent};
 
 impl FlatNode {
     /// Return every index that is referenced by this node.
@@ -231,6 +233,66 @@ impl FlatRoot {
     }
 }
 
+impl FlatFragment {
+    /// Find the nodes that are not referenced from the children of the fragment, e.g., the ref `$m`
+    /// in `<math extend="$m[$n]" />` once the `extend` attribute has been consumed.
+    ///
+    /// The indices of a fragment are fixed (e.g., they have been added to a resolver), so, unlike [`FlatRoot::compactify`],
+    /// the unreferenced nodes are left in place. Referenced nodes whose parent is unreferenced are given
+    /// the nearest referenced ancestor as their parent instead.
+    ///
+    /// Returns the indices of the unreferenced nodes.
+    pub fn find_unreferenced_nodes(&mut self) -> Vec<Index> {
+        let mut is_referenced: HashSet<Index> = HashSet::new();
+        let mut to_visit = self
+            .children
+            .iter()
+            .filter_map(|node| match node {
+                UntaggedContent::Ref(idx) => Some(*idx),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        while let Some(idx) = to_visit.pop() {
+            if !is_referenced.insert(idx) {
+                continue;
+            }
+            to_visit.extend(self.get_node(idx).all_references());
+        }
+
+        let unreferenced = self
+            .nodes
+            .iter()
+            .map(|node| node.idx())
+            .filter(|idx| !is_referenced.contains(idx))
+            .collect::<Vec<_>>();
+        if unreferenced.is_empty() {
+            return unreferenced;
+        }
+
+        let referenced_ancestor = |mut parent: Option<Index>| {
+            while let Some(idx) = parent {
+                if is_referenced.contains(&idx) || !unreferenced.contains(&idx) {
+                    break;
+                }
+                parent = self.get_node(idx).parent().or(self.parent_idx);
+            }
+            parent
+        };
+        let new_parents = self
+            .nodes
+            .iter()
+            .map(|node| referenced_ancestor(node.parent()))
+            .collect::<Vec<_>>();
+        for (node, parent) in self.nodes.iter_mut().zip(new_parents) {
+            if is_referenced.contains(&node.idx()) {
+                node.set_parent(parent);
+            }
+        }
+
+        unreferenced
+    }
+}
+
 #[cfg(test)]
 #[path = "untagged_flat_dast_compactify.test.rs"]
 mod test;
@@ -209,6 +209,7 @@ impl FlatRoot {
             code: node.code.clone(),
             args: node.args.clone(),
             position: node.position.clone(),
+            path_part_position: None,
             source_doc: node.source_doc,
             parent,
             idx,
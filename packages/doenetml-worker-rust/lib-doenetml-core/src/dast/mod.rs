@@ -6,14 +6,18 @@
 //!
 //! ## DAST vs. FlatDast
 //!
-//! `DAST` is what is produced by the JavaScript parser. It is a tree of nodes with each node having children,
+//! `DAST` is what is produced by the JavaScript parser (or by the native [`parser`] module). It is a tree of nodes with each node having children,
 //! which are themselves nodes. In contrast `FlatDast` stores all information in a flat structure. Instead of nodes
 //! having other nodes as children, a FlatDast node's children list contain pointers to the location in a flat array
 //! where the node is stored.
 
+pub mod dast_diff;
 pub mod dast_structure;
+pub mod document_loader;
 pub mod flat_dast;
+pub mod parser;
 pub mod ref_expand;
 pub mod ref_resolve;
+pub mod serialize;
 
 pub use dast_structure::*;
@@ -0,0 +1,53 @@
+//! A native parser that turns DoenetML source into a `DastRoot`, producing the same
+//! DAST as the JavaScript parser (`@doenet/parser`), so that no JavaScript runtime is needed.
+//!
+//! Parsing happens in two steps:
+//! 1. The markup is parsed into elements, attributes and text. Text and attribute values are then
+//!    parsed for refs (`$foo.bar[2]`) and function refs (`$$f(x)`). Malformed markup becomes `DastError`s.
+//! 2. The result is normalized so that it consists of a single `<document>` element (see [`parse`]).
+//!
+//! Positions use the conventions of the JavaScript parser: offsets count UTF-16 code units
+//! and lines and columns start at 1.
+//!
+//! Apart from `<_dynamicChildren>`, the component-specific sugar, deprecation rewrites and name checks
+//! applied by the JavaScript normalizer are not part of this parser.
+
+mod entities;
+mod gobble;
+mod macros;
+mod markup;
+mod normalize;
+mod position;
+
+use super::DastRoot;
+use position::PositionMap;
+
+/// Parse DoenetML `source` into a normalized `DastRoot`.
+///
+/// In addition to [`parse_without_normalizing`], this
+/// - wraps the content in a `<document>` element unless there already is one,
+///   trimming whitespace from the start and end of the document's content,
+/// - renames `xml:id` attributes to `name` and turns `<xref ref="foo" />` into a ref to `foo`,
+/// - expands aliased elements, e.g. `<section>` becomes `<division type="section">`, and
+/// - appends a `<_dynamicChildren>` element to components that support adding children dynamically.
+pub fn parse(source: &str) -> DastRoot {
+    let mut root = parse_without_normalizing(source);
+    normalize::normalize_document(&mut root);
+    root
+}
+
+/// Parse DoenetML `source` into a `DastRoot` that mirrors the source,
+/// except that comments, processing instructions and doctypes are dropped
+/// and CDATA sections become text.
+pub fn parse_without_normalizing(source: &str) -> DastRoot {
+    let position_map = PositionMap::new(source);
+    DastRoot {
+        children: markup::parse_markup(source, &position_map),
+        position: Some(position_map.position(0, source.len())),
+        sources: vec![source.to_string()],
+    }
+}
+
+#[cfg(test)]
+#[path = "parser.test.rs"]
+mod test;
@@ -0,0 +1,310 @@
+use assert_json_diff::assert_json_eq;
+
+use super::*;
+use crate::dast::{DastElement, DastElementContent, DastError, DastTextRefElementContent};
+use crate::test_utils::*;
+
+/// The children of the `<document>` element that `source` is normalized into.
+fn document_children(source: &str) -> Vec<DastElementContent> {
+    let root = parse(source);
+    assert_eq!(root.children.len(), 1);
+    match root.children.into_iter().next() {
+        Some(DastElementContent::Element(document)) if document.name == "document" => {
+            document.children
+        }
+        _ => panic!("expected a single <document> element"),
+    }
+}
+
+fn as_element(node: &DastElementContent) -> &DastElement {
+    match node {
+        DastElementContent::Element(element) => element,
+        _ => panic!("expected an element, found {node:?}"),
+    }
+}
+
+fn errors_in(nodes: &[DastElementContent]) -> Vec<&DastError> {
+    let mut errors = Vec::new();
+    for node in nodes {
+        match node {
+            DastElementContent::Error(error) => errors.push(error),
+            DastElementContent::Element(element) => errors.extend(errors_in(&element.children)),
+            _ => {}
+        }
+    }
+    errors
+}
+
+fn error_codes(source: &str) -> Vec<String> {
+    errors_in(&parse_without_normalizing(source).children)
+        .into_iter()
+        .map(|error| error.code.clone().unwrap_or_default())
+        .collect()
+}
+
+/// The concatenated values of the text nodes in `nodes`.
+fn text_of(nodes: &[DastElementContent]) -> String {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            DastElementContent::Text(text) => Some(text.value.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn parses_elements_attributes_and_text() {
+    let children = document_children(r#"<p name="a">hi <b>there</b></p>"#);
+    assert_json_eq!(
+        serde_json::to_value(as_element(&children[0]).position.as_ref()).unwrap(),
+        json!({ "start": { "line": 1, "column": 1, "offset": 0 }, "end": { "line": 1, "column": 32, "offset": 31 } })
+    );
+
+    let mut value = serde_json::to_value(&children).unwrap();
+    strip_position_fields(&mut value);
+    assert_json_eq!(
+        value,
+        json!([
+            {
+                "type": "element",
+                "name": "p",
+                "attributes": {
+                    "name": { "type": "attribute", "name": "name", "children": [{ "type": "text", "value": "a" }] }
+                },
+                "children": [
+                    { "type": "text", "value": "hi " },
+                    {
+                        "type": "element",
+                        "name": "b",
+                        "attributes": {},
+                        "children": [{ "type": "text", "value": "there" }],
+                    }
+                ],
+            }
+        ])
+    );
+}
+
+#[test]
+fn positions_count_utf16_code_units_and_start_at_one() {
+    let root = parse_without_normalizing("é😀\n<a/>");
+    let element = as_element(&root.children[1]);
+    let position = element.position.as_ref().unwrap();
+    assert_eq!(position.start.line, 2);
+    assert_eq!(position.start.column, 1);
+    // `é` is one UTF-16 code unit, `😀` is two, then the newline.
+    assert_eq!(position.start.offset, Some(4));
+    assert_eq!(position.end.offset, Some(8));
+    assert_eq!(root.position.as_ref().unwrap().end.offset, Some(8));
+}
+
+#[test]
+fn entities_are_decoded() {
+    let children = document_children("&lt;a&gt; &amp; &#x41;&#66; &notAnEntity;");
+    assert_eq!(text_of(&children), "<a> & AB &notAnEntity;");
+}
+
+#[test]
+fn refs_are_parsed_in_text_and_attributes() {
+    let root = parse(r#"<math simplify="$s">$a.b[2].c</math>"#);
+    let mut value = serde_json::to_value(&root).unwrap();
+    strip_position_fields(&mut value);
+    assert_json_eq!(
+        value["children"][0]["children"][0],
+        json!({
+            "type": "element",
+            "name": "math",
+            "attributes": {
+                "simplify": {
+                    "type": "attribute",
+                    "name": "simplify",
+                    "children": [
+                        { "type": "macro", "path": [{ "type": "pathPart", "name": "s", "index": [] }], "attributes": {} }
+                    ],
+                }
+            },
+            "children": [
+                {
+                    "type": "macro",
+                    "path": [
+                        { "type": "pathPart", "name": "a", "index": [] },
+                        {
+                            "type": "pathPart",
+                            "name": "b",
+                            "index": [{ "type": "index", "value": [{ "type": "text", "value": "2" }] }],
+                        },
+                        { "type": "pathPart", "name": "c", "index": [] },
+                    ],
+                    "attributes": {},
+                }
+            ],
+        })
+    );
+}
+
+#[test]
+fn refs_can_have_parenthesized_paths_and_attributes() {
+    let mut value = serde_json::to_value(document_children("$(a.b{x=\"1\"})x")).unwrap();
+    strip_position_fields(&mut value);
+    assert_json_eq!(
+        value,
+        json!([
+            {
+                "type": "macro",
+                "path": [
+                    { "type": "pathPart", "name": "a", "index": [] },
+                    { "type": "pathPart", "name": "b", "index": [] },
+                ],
+                "attributes": {
+                    "x": { "type": "attribute", "name": "x", "children": [{ "type": "text", "value": "1" }] }
+                },
+            },
+            { "type": "text", "value": "x" },
+        ])
+    );
+}
+
+#[test]
+fn text_that_is_not_a_ref_stays_text() {
+    let children = document_children("costs $5 or $ or $$");
+    assert!(
+        children
+            .iter()
+            .all(|node| matches!(node, DastElementContent::Text(_)))
+    );
+    assert_eq!(text_of(&children), "costs $5 or $ or $$");
+}
+
+#[test]
+fn function_refs_get_their_arguments() {
+    let mut value = serde_json::to_value(document_children("$$f(x, (y))")).unwrap();
+    strip_position_fields(&mut value);
+    assert_json_eq!(
+        value,
+        json!([
+            {
+                "type": "function",
+                "path": [{ "type": "pathPart", "name": "f", "index": [] }],
+                "input": [
+                    [{ "type": "text", "value": "x" }],
+                    [
+                        { "type": "text", "value": "(" },
+                        { "type": "text", "value": "y" },
+                        { "type": "text", "value": ")" },
+                    ],
+                ],
+            }
+        ])
+    );
+}
+
+#[test]
+fn function_refs_gobble_arguments_containing_elements() {
+    let children = document_children("$$f(<math>x</math>, 2) after");
+    let DastElementContent::FunctionRef(function_ref) = &children[0] else {
+        panic!("expected a function ref, found {:?}", children[0]);
+    };
+    let input = function_ref.input.as_ref().unwrap();
+    assert_eq!(input.len(), 2);
+    assert_eq!(as_element(&input[0][0]).name, "math");
+    assert!(matches!(&input[1][..], [DastElementContent::Text(text)] if text.value == "2"));
+    // The function ref extends to the closing paren.
+    assert_eq!(
+        function_ref.position.as_ref().unwrap().end.offset,
+        Some("$$f(<math>x</math>, 2)".len())
+    );
+    assert!(matches!(&children[1], DastElementContent::Text(text) if text.value == " after"));
+}
+
+#[test]
+fn missing_close_tag_is_an_error() {
+    assert_eq!(error_codes("<x>"), vec!["doenet-e0008"]);
+    assert_eq!(error_codes("<p>hello"), vec!["doenet-e0008"]);
+}
+
+#[test]
+fn unclosed_open_tag_is_an_error() {
+    assert_eq!(error_codes("<p"), vec!["doenet-e0015"]);
+}
+
+#[test]
+fn attributes_without_quotes_are_an_error() {
+    assert_eq!(error_codes("<p a=b></p>"), vec!["doenet-e0020"]);
+}
+
+#[test]
+fn stray_close_tags_are_errors() {
+    let codes = error_codes("</q>");
+    assert_eq!(codes.len(), 1);
+    assert!(
+        codes[0] == "doenet-e0021" || codes[0] == "doenet-e0022",
+        "unexpected code {codes:?}"
+    );
+}
+
+#[test]
+fn errors_carry_positions() {
+    let root = parse_without_normalizing("<p>hello");
+    let errors = errors_in(&root.children);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].position.is_some());
+}
+
+#[test]
+fn comments_are_dropped_and_cdata_becomes_text() {
+    let children = document_children("a<!-- hidden -->b<![CDATA[<c>]]>");
+    assert_eq!(text_of(&children), "ab<c>");
+    assert_eq!(children.len(), 3);
+}
+
+#[test]
+fn content_is_wrapped_in_a_trimmed_document() {
+    let root = parse("\n  <p/>\n  ");
+    let mut value = serde_json::to_value(&root).unwrap();
+    strip_position_fields(&mut value);
+    assert_json_eq!(
+        value["children"],
+        json!([
+            {
+                "type": "element",
+                "name": "document",
+                "attributes": {},
+                "children": [{ "type": "element", "name": "p", "attributes": {}, "children": [] }],
+            }
+        ])
+    );
+
+    // An existing `<document>` is kept.
+    let root = parse(r#"<document name="d"> <p/> </document>"#);
+    let document = as_element(&root.children[0]);
+    assert!(document.attributes.contains_key("name"));
+    assert_eq!(document.children.len(), 1);
+}
+
+#[test]
+fn aliased_elements_are_expanded() {
+    let children = document_children(r#"<section xml:id="s"><p/></section>"#);
+    let division = as_element(&children[0]);
+    assert_eq!(division.name, "division");
+    assert_eq!(
+        division.attributes["type"].get_string_value().unwrap(),
+        "section"
+    );
+    assert_eq!(division.attributes["name"].get_string_value().unwrap(), "s");
+    // Sections can have children added dynamically.
+    assert_eq!(
+        as_element(division.children.last().unwrap()).name,
+        "_dynamicChildren"
+    );
+}
+
+#[test]
+fn xref_ref_attributes_become_refs() {
+    let children = document_children(r#"<xref ref="foo" />"#);
+    let xref = as_element(&children[0]);
+    assert!(matches!(
+        &xref.attributes["ref"].children[..],
+        [DastTextRefElementContent::Ref(dast_ref)] if dast_ref.path[0].name == "foo"
+    ));
+}
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// The named XML/HTML entities (without the `&` and `;`), shared with the JavaScript parser.
+/// `entity-map.json` is a copy of the map generated by `static-assets/scripts/generate-entity-map.ts`.
+static ENTITY_MAP: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("entity-map.json")).expect("the entity map is valid JSON")
+});
+
+/// Convert an entity like `&amp;` or `&#x3C;` into the string it stands for.
+/// `entity` must start with `&` and end with `;`.
+/// Entities that cannot be converted are returned unchanged.
+pub(super) fn entity_to_string(entity: &str) -> String {
+    let trimmed = &entity[1..entity.len() - 1];
+
+    if let Some(numeric) = trimmed.strip_prefix('#') {
+        let code_point = match numeric.strip_prefix('x') {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => numeric.parse::<u32>(),
+        };
+        return code_point
+            .ok()
+            .and_then(char::from_u32)
+            .map(String::from)
+            .unwrap_or_else(|| entity.to_string());
+    }
+
+    ENTITY_MAP
+        .get(trimmed)
+        .cloned()
+        .unwrap_or_else(|| entity.to_string())
+}
+
+#[cfg(test)]
+#[path = "entities.test.rs"]
+mod test;
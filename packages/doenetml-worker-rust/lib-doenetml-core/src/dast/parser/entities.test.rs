@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn entity_map_matches_the_generated_map() {
+    // The generated map only exists when the crate is built inside the monorepo
+    let generated = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../static-assets/scripts/entity-map.json");
+    let Ok(generated) = std::fs::read_to_string(generated) else {
+        return;
+    };
+    assert_eq!(
+        include_str!("entity-map.json"),
+        generated,
+        "run `generate-entity-map.ts` in static-assets to update `entity-map.json`"
+    );
+}
@@ -0,0 +1,165 @@
+use crate::dast::{DastElementContent, DastText};
+
+use super::position::PositionMap;
+
+/// Attach arguments to function refs whose arguments contain elements, e.g. `$$f(<math>x</math>)`.
+///
+/// The macro parser only sees text, so such a function ref is parsed without input and is
+/// followed by sibling text nodes containing `(`, `,` and `)`. If there is a function ref
+/// without input, all text nodes are split so that each of these chars is a text node of its own,
+/// and the nodes between a function ref and its matching `)` become its arguments.
+pub(super) fn gobble_function_arguments(
+    nodes: Vec<DastElementContent>,
+    position_map: &PositionMap,
+) -> Vec<DastElementContent> {
+    if !nodes.iter().any(is_function_without_input) {
+        return nodes;
+    }
+
+    let nodes: Vec<_> = nodes
+        .into_iter()
+        .flat_map(|node| match node {
+            DastElementContent::Text(text) => split_text_at_special_chars(text, position_map)
+                .into_iter()
+                .map(DastElementContent::Text)
+                .collect(),
+            node => vec![node],
+        })
+        .collect();
+
+    // Whether a text node containing `)` appears at or after each index.
+    let mut closing_paren_from = vec![false; nodes.len() + 1];
+    for (idx, node) in nodes.iter().enumerate().rev() {
+        closing_paren_from[idx] = closing_paren_from[idx + 1]
+            || matches!(node, DastElementContent::Text(text) if text.value.contains(')'));
+    }
+
+    let mut result = Vec::with_capacity(nodes.len());
+    let mut function_idx = None;
+    let mut nodes = nodes.into_iter().enumerate();
+    while let Some((node_idx, node)) = nodes.next() {
+        if is_function_without_input(&node) {
+            function_idx = Some(result.len());
+            result.push(node);
+            continue;
+        }
+        let Some(idx) = function_idx.take() else {
+            result.push(node);
+            continue;
+        };
+        if !is_text(&node, "(") || !closing_paren_from[node_idx] {
+            result.push(node);
+            continue;
+        }
+
+        // Collect the arguments up to the matching `)`.
+        let mut input = Vec::new();
+        let mut argument = Vec::new();
+        let mut paren_depth = 1;
+        let mut end = None;
+        for (_, node) in nodes.by_ref() {
+            if is_text(&node, "(") {
+                paren_depth += 1;
+            } else if is_text(&node, ",") && paren_depth <= 1 {
+                input.push(trim_whitespace(std::mem::take(&mut argument)));
+                continue;
+            } else if is_text(&node, ")") {
+                paren_depth -= 1;
+                if paren_depth == 0 {
+                    let argument =
+                        gobble_function_arguments(std::mem::take(&mut argument), position_map);
+                    input.push(trim_whitespace(argument));
+                    end = node.position().map(|position| position.end.clone());
+                    break;
+                }
+            }
+            argument.push(node);
+        }
+        if end.is_none() && !argument.is_empty() {
+            // The parentheses were never balanced.
+            input.push(trim_whitespace(argument));
+        }
+
+        let DastElementContent::FunctionRef(function_ref) = &mut result[idx] else {
+            unreachable!("`function_idx` points to a function ref")
+        };
+        function_ref.input = Some(input);
+        if let (Some(position), Some(end)) = (&mut function_ref.position, end) {
+            position.end = end;
+        }
+    }
+
+    result
+}
+
+fn is_function_without_input(node: &DastElementContent) -> bool {
+    matches!(node, DastElementContent::FunctionRef(function_ref) if function_ref.input.is_none())
+}
+
+fn is_text(node: &DastElementContent, value: &str) -> bool {
+    matches!(node, DastElementContent::Text(text) if text.value == value)
+}
+
+/// Split `text` so that each `(`, `)` and `,` is in a text node of its own.
+fn split_text_at_special_chars(text: DastText, position_map: &PositionMap) -> Vec<DastText> {
+    if !text.value.contains(['(', ')', ',']) {
+        return vec![text];
+    }
+    let start_offset = text
+        .position
+        .as_ref()
+        .and_then(|position| position.start.offset);
+
+    let mut pieces = Vec::new();
+    let mut piece_start = 0;
+    // The UTF-16 offset of `piece_start` relative to the start of `text`.
+    let mut piece_utf16_start = 0;
+    let mut utf16_offset = 0;
+    let mut push_piece = |value: &str, utf16_start: usize, utf16_end: usize| {
+        if value.is_empty() {
+            return;
+        }
+        pieces.push(DastText {
+            value: value.to_string(),
+            data: None,
+            position: start_offset.map(|offset| {
+                position_map.position_utf16(offset + utf16_start, offset + utf16_end)
+            }),
+            source_doc: None,
+        });
+    };
+    for (idx, c) in text.value.char_indices() {
+        if matches!(c, '(' | ')' | ',') {
+            push_piece(
+                &text.value[piece_start..idx],
+                piece_utf16_start,
+                utf16_offset,
+            );
+            push_piece(&text.value[idx..idx + 1], utf16_offset, utf16_offset + 1);
+            piece_start = idx + 1;
+            piece_utf16_start = utf16_offset + 1;
+        }
+        utf16_offset += c.len_utf16();
+    }
+    push_piece(&text.value[piece_start..], piece_utf16_start, utf16_offset);
+
+    pieces
+}
+
+/// Remove leading whitespace from the first node and trailing whitespace from the last node,
+/// dropping them if they become empty.
+fn trim_whitespace(mut nodes: Vec<DastElementContent>) -> Vec<DastElementContent> {
+    if let Some(DastElementContent::Text(first)) = nodes.first_mut() {
+        first.value = first.value.trim_start().to_string();
+        if first.value.is_empty() {
+            nodes.remove(0);
+        }
+    }
+    if let Some(DastElementContent::Text(last)) = nodes.last_mut() {
+        last.value = last.value.trim_end().to_string();
+        if last.value.is_empty() {
+            nodes.pop();
+        }
+    }
+    nodes
+}
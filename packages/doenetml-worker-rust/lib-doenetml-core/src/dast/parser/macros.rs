@@ -0,0 +1,415 @@
+//! Parse the `$foo` and `$$f(x)` syntaxes inside of text.
+//!
+//! This is a port of the PEG grammar used by the JavaScript parser (`macros.peggy`):
+//! ```text
+//! top              = (Macro / FunctionMacro / Text)*
+//! Macro            = "$" "(" Path PropAttrs? ")" / "$" SimplePath PropAttrs?
+//! FunctionMacro    = "$$" "(" Path ")" FunctionInput? / "$$" SimplePath FunctionInput?
+//! SimplePath       = SimplePathPart ("." SimplePathPart)*     where names match [a-zA-Z_][a-zA-Z0-9_]*
+//! Path             = PathPart ("." PathPart)*                 where names match [a-zA-Z0-9_-]+
+//! PathPart         = name PropIndex*
+//! PropIndex        = "[" _? ((FunctionMacro / Macro / Text) _?)* "]"
+//! PropAttrs        = "{" _? (Attr _?)* "}"
+//! FunctionInput    = "(" _? Argument (_? "," _? Argument)* _? ")"
+//! ```
+//! Failed alternatives backtrack, so anything that is not a complete macro is left as text.
+
+use std::collections::HashMap;
+
+use crate::dast::{
+    DastAttribute, DastElementContent, DastFunctionRef, DastIndex, DastRef, DastText,
+    DastTextRefElementContent, PathPart,
+};
+
+use super::position::PositionMap;
+
+/// The nodes the macro parser can produce.
+pub(super) enum MacroContent {
+    Text(DastText),
+    Ref(DastRef),
+    FunctionRef(DastFunctionRef),
+}
+
+impl From<MacroContent> for DastElementContent {
+    fn from(content: MacroContent) -> Self {
+        match content {
+            MacroContent::Text(text) => DastElementContent::Text(text),
+            MacroContent::Ref(dast_ref) => DastElementContent::Ref(dast_ref),
+            MacroContent::FunctionRef(function_ref) => {
+                DastElementContent::FunctionRef(function_ref)
+            }
+        }
+    }
+}
+
+impl From<MacroContent> for DastTextRefElementContent {
+    fn from(content: MacroContent) -> Self {
+        match content {
+            MacroContent::Text(text) => DastTextRefElementContent::Text(text),
+            MacroContent::Ref(dast_ref) => DastTextRefElementContent::Ref(dast_ref),
+            MacroContent::FunctionRef(function_ref) => {
+                DastTextRefElementContent::FunctionRef(function_ref)
+            }
+        }
+    }
+}
+
+/// Split `value` into text, refs and function refs.
+///
+/// `value` is located at the UTF-16 offset `base_offset` of the source described by `position_map`,
+/// which is used to compute the positions of the resulting nodes.
+pub(super) fn parse_macros(
+    value: &str,
+    base_offset: usize,
+    position_map: &PositionMap,
+) -> Vec<MacroContent> {
+    let chars: Vec<char> = value.chars().collect();
+    let mut utf16_offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = base_offset;
+    for c in &chars {
+        utf16_offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    utf16_offsets.push(offset);
+
+    let mut parser = MacroParser {
+        chars: &chars,
+        utf16_offsets,
+        position_map,
+        pos: 0,
+    };
+    let mut nodes = Vec::new();
+    while !parser.at_end() {
+        if let Some(dast_ref) = parser.parse_macro() {
+            nodes.push(MacroContent::Ref(dast_ref));
+        } else if let Some(function_ref) = parser.parse_function_macro() {
+            nodes.push(MacroContent::FunctionRef(function_ref));
+        } else {
+            nodes.push(MacroContent::Text(parser.parse_text(&['$'])));
+        }
+    }
+    nodes
+}
+
+struct MacroParser<'a> {
+    chars: &'a [char],
+    /// The UTF-16 offset in the source of every char index (including the end).
+    utf16_offsets: Vec<usize>,
+    position_map: &'a PositionMap,
+    pos: usize,
+}
+
+impl MacroParser<'_> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Consume `c` if it is the next char.
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume any whitespace.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume the longest run of chars matching `predicate`.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&predicate) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn position(&self, start: usize) -> Option<crate::dast::Position> {
+        Some(
+            self.position_map
+                .position_utf16(self.utf16_offsets[start], self.utf16_offsets[self.pos]),
+        )
+    }
+
+    fn text_node(&self, value: String, start: usize) -> DastText {
+        DastText {
+            value,
+            data: None,
+            position: self.position(start),
+            source_doc: None,
+        }
+    }
+
+    /// Run `parse`, restoring the parser position if it fails.
+    fn attempt<T>(&mut self, parse: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let start = self.pos;
+        let result = parse(self);
+        if result.is_none() {
+            self.pos = start;
+        }
+        result
+    }
+
+    /// Parse the longest run of chars that are not in `excluded`. If the next char is `$`
+    /// (and `$` is excluded), a text node consisting of just `$` is returned.
+    /// Must not be called at the end of input or when the next char is excluded and not `$`.
+    fn parse_text(&mut self, excluded: &[char]) -> DastText {
+        let start = self.pos;
+        let mut value = self.take_while(|c| !excluded.contains(&c));
+        if value.is_empty() {
+            value.push(self.chars[self.pos]);
+            self.pos += 1;
+        }
+        self.text_node(value, start)
+    }
+
+    /// Like `parse_text`, but fails instead of consuming an excluded char other than `$`.
+    fn try_parse_text(&mut self, excluded: &[char]) -> Option<DastText> {
+        match self.peek() {
+            Some(c) if c == '$' || !excluded.contains(&c) => Some(self.parse_text(excluded)),
+            _ => None,
+        }
+    }
+
+    /// `Macro = "$" "(" Path PropAttrs? ")" / "$" SimplePath PropAttrs?`
+    fn parse_macro(&mut self) -> Option<DastRef> {
+        let start = self.pos;
+        let with_parens = self.attempt(|parser| {
+            parser.eat('$').then_some(())?;
+            parser.eat('(').then_some(())?;
+            let path = parser.parse_path(false)?;
+            let attributes = parser.attempt(Self::parse_prop_attrs).unwrap_or_default();
+            parser.eat(')').then_some((path, attributes))
+        });
+        let (path, attributes) = match with_parens {
+            Some(parsed) => parsed,
+            None => self.attempt(|parser| {
+                parser.eat('$').then_some(())?;
+                let path = parser.parse_path(true)?;
+                let attributes = parser.attempt(Self::parse_prop_attrs).unwrap_or_default();
+                Some((path, attributes))
+            })?,
+        };
+        Some(DastRef {
+            path,
+            attributes,
+            position: self.position(start),
+            source_doc: None,
+        })
+    }
+
+    /// `FunctionMacro = "$$" "(" Path ")" FunctionInput? / "$$" SimplePath FunctionInput?`
+    fn parse_function_macro(&mut self) -> Option<DastFunctionRef> {
+        let start = self.pos;
+        let with_parens = self.attempt(|parser| {
+            parser.eat('$').then_some(())?;
+            parser.eat('$').then_some(())?;
+            parser.eat('(').then_some(())?;
+            let path = parser.parse_path(false)?;
+            parser.eat(')').then_some(path)
+        });
+        let path = match with_parens {
+            Some(path) => path,
+            None => self.attempt(|parser| {
+                parser.eat('$').then_some(())?;
+                parser.eat('$').then_some(())?;
+                parser.parse_path(true)
+            })?,
+        };
+        let input = self.attempt(Self::parse_function_input);
+        Some(DastFunctionRef {
+            path,
+            input,
+            position: self.position(start),
+            source_doc: None,
+        })
+    }
+
+    /// `SimplePath` when `simple` is true, otherwise `Path`.
+    fn parse_path(&mut self, simple: bool) -> Option<Vec<PathPart>> {
+        let mut path = vec![self.parse_path_part(simple)?];
+        while let Some(part) = self.attempt(|parser| {
+            parser.eat('.').then_some(())?;
+            parser.parse_path_part(simple)
+        }) {
+            path.push(part);
+        }
+        Some(path)
+    }
+
+    /// A name followed by any number of `PropIndex`es.
+    fn parse_path_part(&mut self, simple: bool) -> Option<PathPart> {
+        let start = self.pos;
+        let name = if simple {
+            if !self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            {
+                return None;
+            }
+            self.take_while(|c| c.is_ascii_alphanumeric() || c == '_')
+        } else {
+            self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let mut index = Vec::new();
+        while let Some(prop_index) = self.attempt(Self::parse_prop_index) {
+            index.push(prop_index);
+        }
+        Some(PathPart {
+            name,
+            index,
+            position: self.position(start),
+            source_doc: None,
+        })
+    }
+
+    /// `PropIndex = "[" _? ((FunctionMacro / Macro / Text) _?)* "]"`
+    fn parse_prop_index(&mut self) -> Option<DastIndex> {
+        let start = self.pos;
+        self.eat('[').then_some(())?;
+        self.skip_whitespace();
+        let mut value = Vec::new();
+        loop {
+            if let Some(function_ref) = self.parse_function_macro() {
+                value.push(DastTextRefElementContent::FunctionRef(function_ref));
+            } else if let Some(dast_ref) = self.parse_macro() {
+                value.push(DastTextRefElementContent::Ref(dast_ref));
+            } else if let Some(text) = self.try_parse_text(&[']', '$']) {
+                value.push(DastTextRefElementContent::Text(text));
+            } else {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        self.eat(']').then_some(())?;
+        Some(DastIndex {
+            value,
+            position: self.position(start),
+            source_doc: None,
+        })
+    }
+
+    /// `PropAttrs = "{" _? (Attr _?)* "}"`
+    fn parse_prop_attrs(&mut self) -> Option<HashMap<String, DastAttribute>> {
+        self.eat('{').then_some(())?;
+        self.skip_whitespace();
+        let mut attributes = HashMap::new();
+        while let Some(attribute) = self.attempt(Self::parse_attr) {
+            attributes.insert(attribute.name.clone(), attribute);
+            self.skip_whitespace();
+        }
+        self.eat('}').then_some(attributes)
+    }
+
+    /// `Attr = AttrName _? "=" _? AttrValue / AttrName`
+    fn parse_attr(&mut self) -> Option<DastAttribute> {
+        let start = self.pos;
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '-'));
+        if name.is_empty() {
+            return None;
+        }
+        let children = self
+            .attempt(|parser| {
+                parser.skip_whitespace();
+                parser.eat('=').then_some(())?;
+                parser.skip_whitespace();
+                parser.parse_attr_value()
+            })
+            .unwrap_or_default();
+        Some(DastAttribute {
+            name,
+            children,
+            position: self.position(start),
+            source_doc: None,
+        })
+    }
+
+    /// A single- or double-quoted attribute value that may contain macros.
+    fn parse_attr_value(&mut self) -> Option<Vec<DastTextRefElementContent>> {
+        let quote = self.peek().filter(|&c| c == '"' || c == '\'')?;
+        self.pos += 1;
+        let mut children = Vec::new();
+        loop {
+            if let Some(dast_ref) = self.parse_macro() {
+                children.push(DastTextRefElementContent::Ref(dast_ref));
+            } else if let Some(function_ref) = self.parse_function_macro() {
+                children.push(DastTextRefElementContent::FunctionRef(function_ref));
+            } else if let Some(text) = self.try_parse_text(&[quote, '$']) {
+                children.push(DastTextRefElementContent::Text(text));
+            } else {
+                break;
+            }
+        }
+        self.eat(quote).then_some(children)
+    }
+
+    /// `FunctionInput = "(" _? Argument (_? "," _? Argument)* _? ")"`
+    fn parse_function_input(&mut self) -> Option<Vec<Vec<DastElementContent>>> {
+        self.eat('(').then_some(())?;
+        self.skip_whitespace();
+        let mut input = vec![self.parse_balanced_paren_text(true)];
+        while let Some(argument) = self.attempt(|parser| {
+            parser.skip_whitespace();
+            parser.eat(',').then_some(())?;
+            parser.skip_whitespace();
+            Some(parser.parse_balanced_paren_text(true))
+        }) {
+            input.push(argument);
+        }
+        self.skip_whitespace();
+        self.eat(')').then_some(input)
+    }
+
+    /// Parse text containing macros where all parentheses are balanced.
+    /// If `stop_at_comma` is true, a comma outside of parentheses ends the text.
+    fn parse_balanced_paren_text(&mut self, stop_at_comma: bool) -> Vec<DastElementContent> {
+        let excluded: &[char] = if stop_at_comma {
+            &['(', ')', ',', '$']
+        } else {
+            &['(', ')', '$']
+        };
+        let mut nodes = Vec::new();
+        loop {
+            if let Some(dast_ref) = self.parse_macro() {
+                nodes.push(DastElementContent::Ref(dast_ref));
+            } else if let Some(function_ref) = self.parse_function_macro() {
+                nodes.push(DastElementContent::FunctionRef(function_ref));
+            } else if let Some(text) = self.try_parse_text(excluded) {
+                nodes.push(DastElementContent::Text(text));
+            } else if let Some(group) = self.attempt(Self::parse_paren_group) {
+                nodes.extend(group);
+            } else {
+                break;
+            }
+        }
+        nodes
+    }
+
+    /// `"(" BalancedParenText ")"`, where the parentheses become text nodes of their own.
+    fn parse_paren_group(&mut self) -> Option<Vec<DastElementContent>> {
+        let start = self.pos;
+        self.eat('(').then_some(())?;
+        let open_paren = self.text_node("(".to_string(), start);
+        let mut nodes = vec![DastElementContent::Text(open_paren)];
+        nodes.extend(self.parse_balanced_paren_text(false));
+        let close_start = self.pos;
+        self.eat(')').then_some(())?;
+        nodes.push(DastElementContent::Text(
+            self.text_node(")".to_string(), close_start),
+        ));
+        Some(nodes)
+    }
+}
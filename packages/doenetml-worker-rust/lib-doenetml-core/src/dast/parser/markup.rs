@@ -0,0 +1,708 @@
+//! Parse the XML-like markup of DoenetML into DAST nodes.
+//!
+//! The markup is parsed leniently, like the lezer grammar used by the JavaScript parser:
+//! a malformed tag never stops the parse. Instead, a `DastError` describing the problem
+//! is inserted near the offending markup and parsing continues.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use crate::dast::{
+    DastAttribute, DastElement, DastElementContent, DastError, DastText, DiagnosticArgs, Position,
+};
+
+use super::entities::entity_to_string;
+use super::gobble::gobble_function_arguments;
+use super::macros::parse_macros;
+use super::position::PositionMap;
+
+/// Parse `source` into a list of DAST nodes. Comments, processing instructions and doctypes are dropped
+/// and CDATA sections become text.
+pub(super) fn parse_markup(source: &str, position_map: &PositionMap) -> Vec<DastElementContent> {
+    let mut parser = MarkupParser {
+        source,
+        position_map,
+        pos: 0,
+        open_elements: Vec::new(),
+    };
+    let (mut children, end) = parser.parse_content();
+    debug_assert!(matches!(end, ContentEnd::EndOfInput));
+    resolve_stray_close_tags(&mut children, None);
+
+    gobble_function_arguments(children, position_map)
+}
+
+/// Why the content of an element stopped.
+enum ContentEnd {
+    /// The end of the source was reached.
+    EndOfInput,
+    /// A close tag of an ancestor was found, so the current element is missing its close tag.
+    AncestorCloseTag,
+    /// The close tag of the current element was found.
+    CloseTag { error: Option<DastError> },
+}
+
+/// A placeholder for a close tag that does not match the element it appears in.
+/// Which error it becomes depends on whether that element turns out to have a close tag of its own.
+const STRAY_CLOSE_TAG_CODE: &str = "stray-close-tag";
+
+struct MarkupParser<'a> {
+    source: &'a str,
+    position_map: &'a PositionMap,
+    /// The current byte offset into `source`.
+    pos: usize,
+    /// The names of the open elements, as used to match close tags.
+    open_elements: Vec<String>,
+}
+
+impl MarkupParser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_space(&mut self) {
+        let trimmed = self.rest().trim_start_matches(is_space);
+        self.pos = self.source.len() - trimmed.len();
+    }
+
+    /// Advance past the first occurrence of `end`, or to the end of the source if there is none.
+    /// Returns the offset where `end` starts.
+    fn skip_past(&mut self, end: &str) -> usize {
+        match self.rest().find(end) {
+            Some(idx) => {
+                let end_start = self.pos + idx;
+                self.pos = end_start + end.len();
+                end_start
+            }
+            None => {
+                self.pos = self.source.len();
+                self.pos
+            }
+        }
+    }
+
+    /// Consume an XML identifier, e.g., a tag name or attribute name.
+    fn identifier(&mut self) -> Option<&str> {
+        let start = self.pos;
+        let mut chars = self.rest().char_indices();
+        match chars.next() {
+            Some((_, c)) if is_name_start(c) => {}
+            _ => return None,
+        }
+        let len = chars
+            .find(|&(_, c)| !is_name_char(c))
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        Some(&self.source[start..self.pos])
+    }
+
+    fn position(&self, start: usize, end: usize) -> Option<Position> {
+        Some(self.position_map.position(start, end))
+    }
+
+    fn parse_content(&mut self) -> (Vec<DastElementContent>, ContentEnd) {
+        let mut nodes = Vec::new();
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return (nodes, ContentEnd::EndOfInput);
+            }
+            let start = self.pos;
+            if rest.starts_with("<!--") {
+                self.skip_past("-->");
+            } else if rest.starts_with("<![CDATA[") || rest.starts_with("<![cdata[") {
+                self.pos += "<![CDATA[".len();
+                let content_start = self.pos;
+                let content_end = self.skip_past("]]>");
+                nodes.push(DastElementContent::Text(DastText {
+                    value: self.source[content_start..content_end].to_string(),
+                    data: None,
+                    position: self.position(start, self.pos),
+                    source_doc: None,
+                }));
+            } else if rest.starts_with("<!DOCTYPE") || rest.starts_with("<!doctype") {
+                self.skip_past(">");
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>");
+            } else if rest.starts_with("</") {
+                let Some(name) = self.tag_name_after(start + 2) else {
+                    // A `</` that isn't followed by a name is dropped.
+                    self.pos += 2;
+                    continue;
+                };
+                if self
+                    .open_elements
+                    .last()
+                    .is_some_and(|open| open.to_lowercase() == name.to_lowercase())
+                {
+                    let error = self.parse_close_tag();
+                    return (nodes, ContentEnd::CloseTag { error });
+                }
+                if self.open_elements.contains(&name) {
+                    return (nodes, ContentEnd::AncestorCloseTag);
+                }
+                nodes.push(DastElementContent::Error(self.parse_stray_close_tag()));
+            } else if rest.starts_with('<') && starts_tag(self.peek_nth(1)) {
+                nodes.extend(self.parse_element());
+            } else if rest.starts_with('&') {
+                nodes.push(DastElementContent::Text(self.parse_entity()));
+            } else if rest.starts_with("<<") || rest.starts_with("<!") {
+                // A `<` that doesn't start a tag, comment or similar is a less-than sign.
+                self.pos += 1;
+                nodes.extend(self.text_nodes(start, self.pos));
+            } else {
+                self.parse_text();
+                nodes.extend(self.text_nodes(start, self.pos));
+            }
+        }
+    }
+
+    /// Consume text up to the next tag or entity. A `<` followed by whitespace or `=` is part of the text.
+    fn parse_text(&mut self) {
+        loop {
+            let rest = self.rest();
+            match rest.chars().next() {
+                None | Some('&') => break,
+                Some('<') => {
+                    if rest.starts_with("<=") {
+                        self.pos += 2;
+                    } else if rest[1..].starts_with(is_space) {
+                        self.pos += 1;
+                        self.skip_space();
+                    } else {
+                        break;
+                    }
+                }
+                Some(c) => self.pos += c.len_utf8(),
+            }
+        }
+    }
+
+    /// The text nodes, refs and function refs of the text at `start..end`.
+    fn text_nodes(&self, start: usize, end: usize) -> Vec<DastElementContent> {
+        let value = &self.source[start..end];
+        if !value.contains('$') {
+            return vec![DastElementContent::Text(DastText {
+                value: value.to_string(),
+                data: None,
+                position: self.position(start, end),
+                source_doc: None,
+            })];
+        }
+        parse_macros(
+            value,
+            self.position_map.utf16_offset(start),
+            self.position_map,
+        )
+        .into_iter()
+        .map(DastElementContent::from)
+        .collect()
+    }
+
+    /// Parse a character reference (e.g., `&#x3C;`), an entity reference (e.g., `&lt;`) or a lone `&`.
+    fn parse_entity(&mut self) -> DastText {
+        let start = self.pos;
+        let value = match entity_len(self.rest()) {
+            Some(len) => {
+                self.pos += len;
+                self.entity_value(start)
+            }
+            None => {
+                self.pos += 1;
+                "&".to_string()
+            }
+        };
+        DastText {
+            value,
+            data: None,
+            position: self.position(start, self.pos),
+            source_doc: None,
+        }
+    }
+
+    /// The value of the entity at `start..self.pos`.
+    fn entity_value(&self, start: usize) -> String {
+        let mut value = entity_to_string(&self.source[start..self.pos]);
+        // An entity like `&dollar;` followed by a letter would otherwise create a ref
+        // that wasn't in the source, so a zero-width space is inserted after it.
+        if value == "$"
+            && self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        {
+            value.push('\u{200B}');
+        }
+        value
+    }
+
+    /// The name used to match an open tag with its close tag: the name chars (as understood
+    /// by the tag matcher of the JavaScript parser) following `offset`, skipping leading whitespace.
+    fn tag_name_after(&self, offset: usize) -> Option<String> {
+        let name: String = self.source[offset..]
+            .trim_start_matches(is_space)
+            .chars()
+            .take_while(|&c| {
+                matches!(c, '-' | '.' | ':' | '_') || c.is_ascii_alphabetic() || c as u32 >= 161
+            })
+            .collect();
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// Parse the close tag of the current element. Returns an error if the close tag is malformed.
+    fn parse_close_tag(&mut self) -> Option<DastError> {
+        let start = self.pos;
+        self.pos += 2;
+        self.skip_space();
+        if self.identifier().is_none() {
+            return Some(coded_error(
+                "doenet-e0019",
+                "Invalid DoenetML: Found a closing tag without a tag name, e.g. `</`".to_string(),
+                None,
+                self.position(self.pos, self.pos),
+            ));
+        }
+        let name_end = self.pos;
+        self.skip_space();
+        if self.eat(">") {
+            return None;
+        }
+        Some(unclosed_tag_error(
+            &self.source[start..name_end],
+            self.position(start, name_end),
+        ))
+    }
+
+    /// Parse a close tag that matches no open element. The returned error is a placeholder
+    /// that is resolved by [`resolve_stray_close_tags`] unless the close tag is itself malformed.
+    fn parse_stray_close_tag(&mut self) -> DastError {
+        let start = self.pos;
+        if let Some(error) = self.parse_close_tag() {
+            return error;
+        }
+        let tag = &self.source[start..self.pos];
+        coded_error(
+            STRAY_CLOSE_TAG_CODE,
+            tag.to_string(),
+            None,
+            self.position(start, self.pos),
+        )
+    }
+
+    /// Parse an element starting at the `<` of its open tag. If the open tag has no name,
+    /// an error is returned instead.
+    fn parse_element(&mut self) -> Vec<DastElementContent> {
+        let start = self.pos;
+        let match_name = self.tag_name_after(start + 1);
+        self.pos += 1;
+        self.skip_space();
+        let Some(name) = self.identifier().map(str::to_string) else {
+            return vec![DastElementContent::Error(coded_error(
+                "doenet-e0014",
+                "Invalid DoenetML: Found a tag without a tag name, e.g. `<`".to_string(),
+                None,
+                self.position(self.pos, self.pos),
+            ))];
+        };
+        let name_end = self.pos;
+        let mut open_tag_end = self.pos;
+
+        let mut attributes = Vec::new();
+        let mut open_tag_error = None;
+        let self_closing = loop {
+            self.skip_space();
+            if self.eat(">") {
+                open_tag_end = self.pos;
+                break false;
+            }
+            if self.eat("/>") {
+                open_tag_end = self.pos;
+                break true;
+            }
+            if let Some(attribute) = self.parse_attribute() {
+                open_tag_end = self.pos;
+                attributes.push(attribute);
+                continue;
+            }
+            // Anything else means the open tag is missing its `>`. The element stays open
+            // so that what follows becomes its content.
+            if attributes
+                .iter()
+                .all(|attribute: &ParsedAttribute| attribute.error.is_none())
+            {
+                open_tag_error = Some(unclosed_tag_error(
+                    &self.source[start..open_tag_end],
+                    self.position(start, name_end),
+                ));
+            }
+            break false;
+        };
+        let has_open_tag_error = open_tag_error.is_some()
+            || attributes.iter().any(|attribute| attribute.error.is_some());
+
+        let mut children = Vec::from_iter(open_tag_error.map(DastElementContent::Error));
+        let mut content = Vec::new();
+        let mut has_close_tag = false;
+        if !self_closing {
+            self.open_elements.push(match_name.unwrap_or_default());
+            let (element_content, end) = self.parse_content();
+            self.open_elements.pop();
+            content = element_content;
+            match end {
+                ContentEnd::CloseTag { error } => {
+                    has_close_tag = true;
+                    content.extend(error.map(DastElementContent::Error));
+                }
+                ContentEnd::EndOfInput | ContentEnd::AncestorCloseTag => {
+                    if !has_open_tag_error {
+                        let tag = &self.source[start..open_tag_end];
+                        let mut args = DiagnosticArgs::new();
+                        args.insert("tag".to_string(), json!(tag));
+                        args.insert("tagName".to_string(), json!(name));
+                        children.push(DastElementContent::Error(coded_error(
+                            "doenet-e0008",
+                            format!(
+                                "Invalid DoenetML: The tag `{tag}` has no closing tag. Expected a self-closing tag or a `</{name}>` tag."
+                            ),
+                            Some(args),
+                            self.position(start, open_tag_end),
+                        )));
+                    }
+                }
+            }
+        }
+        resolve_stray_close_tags(&mut content, (!has_close_tag).then_some(name.as_str()));
+
+        let attributes = self.resolve_attributes(attributes, &mut children);
+        children.extend(content);
+
+        vec![DastElementContent::Element(DastElement {
+            name,
+            attributes,
+            children: gobble_function_arguments(children, self.position_map),
+            data: None,
+            position: self.position(start, self.pos),
+            source_doc: None,
+        })]
+    }
+
+    /// Parse an attribute of an open tag, if there is one.
+    fn parse_attribute(&mut self) -> Option<ParsedAttribute> {
+        let start = self.pos;
+        let name = self.identifier()?.to_string();
+        let mut end = self.pos;
+        let mut children = Vec::new();
+        let mut error = None;
+
+        let before_is = self.pos;
+        self.skip_space();
+        if self.eat("=") {
+            end = self.pos;
+            self.skip_space();
+            match self.peek() {
+                Some(quote @ ('"' | '\'')) => {
+                    let (value_children, value_error) = self.parse_attribute_value(quote);
+                    end = self.pos;
+                    children = value_children;
+                    error = value_error;
+                }
+                _ => {
+                    let attribute = &self.source[start..end];
+                    let mut args = DiagnosticArgs::new();
+                    args.insert("attribute".to_string(), json!(attribute));
+                    error = Some(coded_error(
+                        "doenet-e0010",
+                        format!(
+                            "Invalid DoenetML: Invalid attribute `{attribute}` appears to be missing a value."
+                        ),
+                        Some(args),
+                        self.position(self.pos, self.pos),
+                    ));
+                }
+            }
+        } else {
+            self.pos = before_is;
+        }
+
+        Some(ParsedAttribute {
+            attribute: DastAttribute {
+                name,
+                children,
+                position: self.position(start, end),
+                source_doc: None,
+            },
+            source_end: end,
+            error,
+        })
+    }
+
+    /// Parse a quoted attribute value. Entity references are replaced by their values,
+    /// but character references are left as they are.
+    fn parse_attribute_value(
+        &mut self,
+        quote: char,
+    ) -> (
+        Vec<crate::dast::DastTextRefElementContent>,
+        Option<DastError>,
+    ) {
+        let start = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        let mut closed = false;
+        while let Some(c) = self.peek() {
+            if c == quote {
+                self.pos += 1;
+                closed = true;
+                break;
+            }
+            if c == '&' {
+                let entity_start = self.pos;
+                match entity_len(self.rest()) {
+                    Some(len) if self.rest().as_bytes()[1] != b'#' => {
+                        self.pos += len;
+                        value.push_str(&self.entity_value(entity_start));
+                    }
+                    Some(len) => {
+                        self.pos += len;
+                        value.push_str(&self.source[entity_start..self.pos]);
+                    }
+                    None => {
+                        self.pos += 1;
+                        value.push('&');
+                    }
+                }
+                continue;
+            }
+            value.push(c);
+            self.pos += c.len_utf8();
+        }
+
+        let error = (!closed).then(|| {
+            let raw = &self.source[start..self.pos];
+            let position = self.position(self.pos, self.pos);
+            if raw.ends_with(quote) {
+                let mut args = DiagnosticArgs::new();
+                args.insert("value".to_string(), json!(raw));
+                coded_error(
+                    "doenet-e0012",
+                    format!("Invalid DoenetML: Invalid attribute value `{raw}`"),
+                    Some(args),
+                    position,
+                )
+            } else {
+                let mut args = DiagnosticArgs::new();
+                args.insert("value".to_string(), json!(raw));
+                args.insert("quote".to_string(), json!(quote.to_string()));
+                coded_error(
+                    "doenet-e0013",
+                    format!(
+                        "Invalid DoenetML: Invalid attribute value `{raw}`. The quote marks do not match. You appear to be missing a `{quote}`"
+                    ),
+                    Some(args),
+                    position,
+                )
+            }
+        });
+
+        // The position of the value is inside of its quotes.
+        let value_start = self.position_map.utf16_offset(start) + 1;
+        let value_end = self.position_map.utf16_offset(self.pos).saturating_sub(1);
+        let children = if value.contains('$') {
+            parse_macros(&value, value_start, self.position_map)
+                .into_iter()
+                .map(Into::into)
+                .collect()
+        } else {
+            vec![crate::dast::DastTextRefElementContent::Text(DastText {
+                value,
+                data: None,
+                position: Some(self.position_map.position_utf16(value_start, value_end)),
+                source_doc: None,
+            })]
+        };
+
+        (children, error)
+    }
+
+    /// Turn the parsed attributes of an element into its attribute map, pushing any errors onto `children`.
+    ///
+    /// An unquoted value like `<point name=P />` is parsed as two attributes without values,
+    /// `name=` and `P`. Such pairs are replaced by a single error.
+    fn resolve_attributes(
+        &self,
+        attributes: Vec<ParsedAttribute>,
+        children: &mut Vec<DastElementContent>,
+    ) -> HashMap<String, DastAttribute> {
+        let is_assignment = |attribute: &ParsedAttribute| {
+            attribute.attribute.children.is_empty()
+                && self.source[..attribute.source_end].ends_with('=')
+        };
+
+        let mut result = HashMap::new();
+        let mut attributes = attributes.into_iter().peekable();
+        while let Some(attribute) = attributes.next() {
+            if is_assignment(&attribute)
+                && let Some(value) = attributes.next_if(|value| value.attribute.children.is_empty())
+            {
+                let assign_name = attribute.attribute.name;
+                let value_name = value.attribute.name;
+                let mut args = DiagnosticArgs::new();
+                args.insert("attribute".to_string(), json!(assign_name));
+                args.insert("value".to_string(), json!(value_name));
+                children.push(DastElementContent::Error(coded_error(
+                    "doenet-e0020",
+                    format!(
+                        "Attribute values must be enclosed in quotes: `{assign_name}=\"{value_name}\"`"
+                    ),
+                    Some(args),
+                    value.attribute.position,
+                )));
+                continue;
+            }
+            children.extend(attribute.error.map(DastElementContent::Error));
+            result.insert(attribute.attribute.name.clone(), attribute.attribute);
+        }
+        result
+    }
+}
+
+/// An attribute together with where its source ends and any error found while parsing it.
+struct ParsedAttribute {
+    attribute: DastAttribute,
+    source_end: usize,
+    error: Option<DastError>,
+}
+
+/// Replace the placeholders left by [`MarkupParser::parse_stray_close_tag`] with errors.
+/// `unclosed_element` is the name of the element containing `nodes`, if that element has no close tag.
+fn resolve_stray_close_tags(nodes: &mut [DastElementContent], unclosed_element: Option<&str>) {
+    for node in nodes.iter_mut() {
+        let DastElementContent::Error(error) = node else {
+            continue;
+        };
+        if error.code.as_deref() != Some(STRAY_CLOSE_TAG_CODE) {
+            continue;
+        }
+        let tag = std::mem::take(&mut error.message);
+        let position = error.position.take();
+        let mut args = DiagnosticArgs::new();
+        *error = match unclosed_element {
+            Some(expected) => {
+                args.insert("expected".to_string(), json!(expected));
+                args.insert("found".to_string(), json!(tag));
+                coded_error(
+                    "doenet-e0022",
+                    format!(
+                        "Invalid DoenetML: Mismatched closing tag. Expected `</{expected}>`. Found `{tag}`"
+                    ),
+                    Some(args),
+                    position,
+                )
+            }
+            None => {
+                args.insert("tag".to_string(), json!(tag));
+                coded_error(
+                    "doenet-e0021",
+                    format!(
+                        "Invalid DoenetML: Found closing tag `{tag}`, but no corresponding opening tag"
+                    ),
+                    Some(args),
+                    position,
+                )
+            }
+        };
+    }
+}
+
+/// The error for a tag like `<p` that is missing its `>`.
+fn unclosed_tag_error(tag: &str, position: Option<Position>) -> DastError {
+    let mut args = DiagnosticArgs::new();
+    args.insert("tag".to_string(), json!(tag));
+    coded_error(
+        "doenet-e0015",
+        format!("Invalid DoenetML: Tag `{tag}` was not closed (a `>` appears to be missing)."),
+        Some(args),
+        position,
+    )
+}
+
+fn coded_error(
+    code: &str,
+    message: String,
+    args: Option<DiagnosticArgs>,
+    position: Option<Position>,
+) -> DastError {
+    DastError {
+        message,
+        error_type: None,
+        code: Some(code.to_string()),
+        args,
+        position,
+        source_doc: None,
+    }
+}
+
+/// The length of the character reference (e.g., `&#60;` or `&#x3C;`)
+/// or entity reference (e.g., `&lt;`) at the start of `s`, if there is one.
+fn entity_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('&')?;
+    let body_len = if let Some(hex) = rest.strip_prefix("#x") {
+        2 + hex
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .filter(|&len| len > 0)?
+    } else if let Some(decimal) = rest.strip_prefix('#') {
+        1 + decimal
+            .find(|c: char| !c.is_ascii_digit())
+            .filter(|&len| len > 0)?
+    } else {
+        let mut chars = rest.char_indices();
+        if !chars.next().is_some_and(|(_, c)| is_name_start(c)) {
+            return None;
+        }
+        chars.find(|&(_, c)| !is_name_char(c)).map(|(idx, _)| idx)?
+    };
+    rest[body_len..]
+        .starts_with(';')
+        .then_some(1 + body_len + 1)
+}
+
+/// Whether `<` followed by `next` starts a tag rather than being a less-than sign.
+fn starts_tag(next: Option<char>) -> bool {
+    !matches!(next, Some('=' | '<' | '!' | '?')) && !next.is_some_and(is_space)
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+fn is_name_start(c: char) -> bool {
+    matches!(c,
+        ':' | 'a'..='z' | 'A'..='Z' | '_'
+        | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c)
+        || matches!(c, '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use crate::dast::{
+    DastAttribute, DastElement, DastElementContent, DastRef, DastRoot, DastText,
+    DastTextRefElementContent, PathPart,
+};
+
+/// Elements that are aliases for other elements, e.g. `<section>` is an alias for `<division type="section">`.
+/// Each entry is the alias, the element it expands to and the value of the `type` attribute it gets.
+const ELEMENT_EXPANSIONS: &[(&str, &str, &str)] = &[
+    ("chapter", "division", "chapter"),
+    ("part", "division", "part"),
+    ("section", "division", "section"),
+    ("subsection", "division", "subsection"),
+    ("subsubsection", "division", "subsubsection"),
+    ("paragraphs", "division", "paragraphs"),
+];
+
+/// Components that support adding children dynamically. They get a `<_dynamicChildren>` element
+/// appended to their children. For a `<division>`, the value of its `type` attribute is matched.
+const COMPONENTS_WITH_DYNAMIC_CHILDREN: &[&str] = &[
+    "graph",
+    "stickyGroup",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraphs",
+    "part",
+    "task",
+    "aside",
+    "objectives",
+    "problem",
+    "exercise",
+    "question",
+    "activity",
+    "example",
+    "definition",
+    "note",
+    "theorem",
+    "proof",
+    "problems",
+    "exercises",
+];
+
+/// Normalize `root` so that it consists of a single `<document>` element, and rewrite
+/// PreTeXt-style attributes and aliased elements into their DoenetML equivalents.
+pub(super) fn normalize_document(root: &mut DastRoot) {
+    ensure_document_element(root);
+    visit_elements_mut(&mut root.children, &mut |element| {
+        convert_pretext_attributes(element);
+        expand_aliased_element(element);
+        add_dynamic_children(element);
+    });
+}
+
+/// Make the root contain a single `<document>` element whose content is trimmed of whitespace.
+/// If there is no `<document>` element, one is created to hold the content of the root.
+fn ensure_document_element(root: &mut DastRoot) {
+    let existing_document = root.children.iter().position(
+        |node| matches!(node, DastElementContent::Element(element) if element.name == "document"),
+    );
+
+    match existing_document {
+        Some(idx) => {
+            if root.children.len() > 1 {
+                root.children = vec![root.children.swap_remove(idx)];
+            }
+            let DastElementContent::Element(document) = &mut root.children[0] else {
+                unreachable!("the root contains only the document element")
+            };
+            document.children = trim_whitespace_nodes(std::mem::take(&mut document.children));
+        }
+        None => {
+            let mut document = DastElement::with_name("document");
+            document.children = trim_whitespace_nodes(std::mem::take(&mut root.children));
+            root.children = vec![DastElementContent::Element(document)];
+        }
+    }
+}
+
+/// Remove whitespace from the start and end of `nodes`, including whitespace at the start of the first
+/// text node and the end of the last text node.
+fn trim_whitespace_nodes(nodes: Vec<DastElementContent>) -> Vec<DastElementContent> {
+    let is_content = |node: &DastElementContent| match node {
+        DastElementContent::Text(text) => !text.value.trim().is_empty(),
+        _ => true,
+    };
+    let (Some(first), Some(last)) = (
+        nodes.iter().position(is_content),
+        nodes.iter().rposition(is_content),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut trimmed: Vec<_> = nodes
+        .into_iter()
+        .skip(first)
+        .take(last + 1 - first)
+        .collect();
+    if let Some(DastElementContent::Text(text)) = trimmed.first_mut() {
+        text.value = text.value.trim_start().to_string();
+    }
+    if let Some(DastElementContent::Text(text)) = trimmed.last_mut() {
+        text.value = text.value.trim_end().to_string();
+    }
+    trimmed
+}
+
+/// Call `f` on every element in `nodes`, parents before their children. The arguments of function refs
+/// are visited as well.
+fn visit_elements_mut(nodes: &mut [DastElementContent], f: &mut impl FnMut(&mut DastElement)) {
+    for node in nodes {
+        match node {
+            DastElementContent::Element(element) => {
+                f(element);
+                visit_elements_mut(&mut element.children, f);
+            }
+            DastElementContent::FunctionRef(function_ref) => {
+                for argument in function_ref.input.iter_mut().flatten() {
+                    visit_elements_mut(argument, f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rename `xml:id` to `name` and turn `<xref ref="foo" />` into `<xref ref="$foo" />`.
+fn convert_pretext_attributes(element: &mut DastElement) {
+    if let Some(mut id) = element.attributes.remove("xml:id") {
+        id.name = "name".to_string();
+        element.attributes.insert(id.name.clone(), id);
+    }
+
+    if element.name != "xref" {
+        return;
+    }
+    let Some(ref_attribute) = element.attributes.get_mut("ref") else {
+        return;
+    };
+    let has_ref = ref_attribute
+        .children
+        .iter()
+        .any(|child| matches!(child, DastTextRefElementContent::Ref(_)));
+    let has_text = ref_attribute.children.iter().any(
+        |child| matches!(child, DastTextRefElementContent::Text(text) if !text.value.trim().is_empty()),
+    );
+    if has_ref || !has_text {
+        return;
+    }
+    let name = ref_attribute
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            DastTextRefElementContent::Text(DastText { value, .. }) => Some(value.as_str()),
+            _ => None,
+        })
+        .collect::<String>();
+    ref_attribute.children = vec![DastTextRefElementContent::Ref(DastRef {
+        path: vec![PathPart {
+            name,
+            index: Vec::new(),
+            position: None,
+            source_doc: None,
+        }],
+        attributes: HashMap::new(),
+        position: None,
+        source_doc: None,
+    })];
+}
+
+/// Expand an aliased element, e.g. `<section>` becomes `<division type="section">`.
+fn expand_aliased_element(element: &mut DastElement) {
+    let Some(&(_, expanded_name, type_value)) = ELEMENT_EXPANSIONS
+        .iter()
+        .find(|(alias, _, _)| *alias == element.name)
+    else {
+        return;
+    };
+    element.name = expanded_name.to_string();
+    element.attributes.insert(
+        "type".to_string(),
+        DastAttribute {
+            name: "type".to_string(),
+            children: vec![DastTextRefElementContent::Text(DastText {
+                value: type_value.to_string(),
+                data: None,
+                position: None,
+                source_doc: None,
+            })],
+            position: None,
+            source_doc: None,
+        },
+    );
+}
+
+/// Append a `<_dynamicChildren>` element to `element` if it supports adding children dynamically.
+fn add_dynamic_children(element: &mut DastElement) {
+    let component_type = match (element.name.as_str(), element.attributes.get("type")) {
+        ("division", Some(type_attribute)) => match type_attribute.children.first() {
+            Some(DastTextRefElementContent::Text(text)) => text.value.as_str(),
+            _ => "division",
+        },
+        (name, _) => name,
+    };
+    if !COMPONENTS_WITH_DYNAMIC_CHILDREN.contains(&component_type) {
+        return;
+    }
+
+    let defer_until_parent_rendered = element.children.iter().any(|child| {
+        matches!(child, DastElementContent::Element(child) if child.name == "_postponeRenderContainer")
+    });
+    let mut dynamic_children = DastElement::with_name("_dynamicChildren");
+    dynamic_children.source_doc = element.source_doc;
+    if defer_until_parent_rendered {
+        dynamic_children.attributes.insert(
+            "deferUntilParentRendered".to_string(),
+            DastAttribute {
+                name: "deferUntilParentRendered".to_string(),
+                children: vec![DastTextRefElementContent::Text(DastText {
+                    value: "true".to_string(),
+                    data: None,
+                    position: None,
+                    source_doc: None,
+                })],
+                position: None,
+                source_doc: element.source_doc,
+            },
+        );
+    }
+    element
+        .children
+        .push(DastElementContent::Element(dynamic_children));
+}
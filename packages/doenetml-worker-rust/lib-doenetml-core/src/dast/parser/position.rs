@@ -0,0 +1,78 @@
+use crate::dast::{Point, Position};
+
+/// Converts offsets in a source string into the `Point`s used by DAST positions.
+///
+/// DAST positions follow the JavaScript conventions: offsets count UTF-16 code units
+/// from the start of the source, and lines and columns start at 1.
+pub(super) struct PositionMap {
+    /// The UTF-16 offset of every byte offset of the source (including the end of the source).
+    utf16_offsets: Vec<usize>,
+    /// The zero-based line and column of every UTF-16 offset of the source (including the end of the source).
+    line_columns: Vec<(usize, usize)>,
+}
+
+impl PositionMap {
+    pub fn new(source: &str) -> Self {
+        let mut utf16_offsets = Vec::with_capacity(source.len() + 1);
+        let mut line_columns = Vec::with_capacity(source.len() + 1);
+        let (mut line, mut column) = (0, 0);
+        for c in source.chars() {
+            for _ in 0..c.len_utf8() {
+                utf16_offsets.push(line_columns.len());
+            }
+            for _ in 0..c.len_utf16() {
+                line_columns.push((line, column));
+                if c == '\n' {
+                    line += 1;
+                    column = 0;
+                } else {
+                    column += 1;
+                }
+            }
+        }
+        utf16_offsets.push(line_columns.len());
+        line_columns.push((line, column));
+
+        PositionMap {
+            utf16_offsets,
+            line_columns,
+        }
+    }
+
+    /// The UTF-16 offset of the byte offset `byte_offset`.
+    pub fn utf16_offset(&self, byte_offset: usize) -> usize {
+        self.utf16_offsets[byte_offset]
+    }
+
+    /// The `Point` at the UTF-16 offset `offset`. Offsets past the end of the source
+    /// are clamped to the end of the source.
+    pub fn point_at_utf16(&self, offset: usize) -> Point {
+        let (line, column) = self.line_columns[offset.min(self.line_columns.len() - 1)];
+        Point {
+            line: line + 1,
+            column: column + 1,
+            offset: Some(offset),
+        }
+    }
+
+    /// The `Point` at the byte offset `byte_offset`.
+    pub fn point(&self, byte_offset: usize) -> Point {
+        self.point_at_utf16(self.utf16_offset(byte_offset))
+    }
+
+    /// The `Position` spanning the byte offsets `start..end`.
+    pub fn position(&self, start: usize, end: usize) -> Position {
+        Position {
+            start: self.point(start),
+            end: self.point(end),
+        }
+    }
+
+    /// The `Position` spanning the UTF-16 offsets `start..end`.
+    pub fn position_utf16(&self, start: usize, end: usize) -> Position {
+        Position {
+            start: self.point_at_utf16(start),
+            end: self.point_at_utf16(end),
+        }
+    }
+}
@@ -2,10 +2,13 @@ use std::{collections::HashMap, mem};
 
 use anyhow::anyhow;
 
+use crate::components::_Fragment;
+
 use super::{
     DastElement, DastElementContent, DastError,
     flat_dast::{
-        ErrorType, FlatElement, FlatError, FlatNode, FlatRoot, Index, Source, UntaggedContent,
+        ErrorType, FlatElement, FlatError, FlatFragment, FlatNode, FlatRoot, Index, Source,
+        UntaggedContent,
     },
     ref_resolve::{RefResolution, ResolutionError, Resolver, format_error_message},
 };
@@ -30,14 +33,53 @@ impl Expander {
     /// other elements.
     pub fn expand(flat_root: &mut FlatRoot) -> Resolver {
         let resolver = Resolver::from_flat_root(flat_root);
-        Expander::expand_refs(flat_root, &resolver);
-        Expander::consume_extend_and_copy_attributes(flat_root);
+        Expander::expand_refs(flat_root, &resolver, 0, |_| None);
+        Expander::consume_extend_and_copy_attributes(flat_root, 0);
         resolver
     }
 
-    /// Expand all refs and function refs into their "xml" form.
-    fn expand_refs(flat_root: &mut FlatRoot, resolver: &Resolver) {
-        for idx in 0..flat_root.nodes.len() {
+    /// Expand all refs and function refs of `flat_fragment` into their "xml" form,
+    /// as in [`Expander::expand`].
+    ///
+    /// The nodes of `flat_fragment` must already have been added to `resolver`.
+    /// Refs may refer to nodes outside of `flat_fragment`; the tag name of such a referent is given by `referent_name`.
+    pub fn expand_fragment(
+        flat_fragment: &mut FlatFragment,
+        resolver: &Resolver,
+        referent_name: impl Fn(Index) -> Option<String>,
+    ) {
+        // The nodes of the fragment are placed in a `FlatRoot` at the positions given by their indices,
+        // so that new nodes created during expansion get the indices that follow.
+        let min_idx = flat_fragment.min_idx();
+        let mut flat_root = FlatRoot::new();
+        flat_root.nodes = std::iter::repeat_with(FlatNode::default)
+            .take(min_idx)
+            .chain(mem::take(&mut flat_fragment.nodes))
+            .collect();
+
+        Expander::expand_refs(&mut flat_root, resolver, min_idx, referent_name);
+        Expander::consume_extend_and_copy_attributes(&mut flat_root, min_idx);
+
+        flat_fragment.set_nodes(flat_root.nodes.split_off(min_idx));
+    }
+
+    /// Expand all refs and function refs with index `start_idx` or more into their "xml" form.
+    ///
+    /// The tag name of a referent that is not an element of `flat_root` is given by `referent_name`.
+    fn expand_refs(
+        flat_root: &mut FlatRoot,
+        resolver: &Resolver,
+        start_idx: Index,
+        referent_name: impl Fn(Index) -> Option<String>,
+    ) {
+        // Get the tag name of the referent
+        let referent_tag_name =
+            |flat_root: &FlatRoot, node_idx: Index| match &flat_root.nodes[node_idx] {
+                FlatNode::Element(e) => e.name.clone(),
+                _ => referent_name(node_idx).expect("Expected an element"),
+            };
+
+        for idx in start_idx..flat_root.nodes.len() {
             // The original `nodes[idx]` node is being completely replaced, so we are free to take its value,
             // which will prevent the borrow checker from complaining if we mutate `flat_root` during processing.
             flat_root.nodes[idx] = match mem::take(&mut flat_root.nodes[idx]) {
@@ -46,39 +88,72 @@ impl Expander {
                     // e.g. `<point name="p" />$p` becomes `<point name="p" /><point extend="$p" />`
                     // Expanding a ref is can be done with a single replacement.
 
-                    //flat_root.nodes[idx] = resolved
-                    match resolver.resolve(&ref_.path, ref_.idx, false) {
-                        Ok(ref_resolution) => {
-                            // Get the tag name of the referent
-                            let name = match &flat_root.nodes[ref_resolution.node_idx] {
-                                FlatNode::Element(e) => e.name.clone(),
-                                _ => panic!("Expected an element"),
-                            };
+                    // A ref to a range of items, e.g. `$g[2:3]`, has several referents. It is replaced by
+                    // a `<_fragment />` whose children extend the referents,
+                    // e.g. `<_fragment><point extend="$g[2]" /><point extend="$g[3]" /></_fragment>`.
+                    match resolver.resolve_all(&ref_.path, ref_.idx, false) {
+                        Ok(mut ref_resolutions) if ref_resolutions.len() == 1 => {
+                            let ref_resolution = ref_resolutions.pop().unwrap();
                             FlatNode::Element(FlatElement {
                                 idx: ref_.idx,
                                 parent: ref_.parent,
                                 attributes: Vec::new(),
                                 children: Vec::new(),
-                                name,
+                                name: referent_tag_name(flat_root, ref_resolution.node_idx),
                                 position: ref_.position.clone(),
                                 source_doc: ref_.source_doc,
                                 children_position: None,
                                 extending: Some(Source::Ref(ref_resolution)),
                             })
                         }
+                        Ok(ref_resolutions) => {
+                            let children = ref_resolutions
+                                .into_iter()
+                                .map(|ref_resolution| {
+                                    let name =
+                                        referent_tag_name(flat_root, ref_resolution.node_idx);
+                                    let child = flat_root.merge_content(
+                                        &DastElementContent::element_with_name(&name),
+                                        Some(idx),
+                                    );
+                                    if let FlatNode::Element(child_element) =
+                                        &mut flat_root.nodes[lookup_idx(&child).unwrap()]
+                                    {
+                                        child_element.position = ref_.position.clone();
+                                        child_element.source_doc = ref_.source_doc;
+                                        child_element.extending = Some(Source::Ref(ref_resolution));
+                                    }
+                                    child
+                                })
+                                .collect();
+                            FlatNode::Element(FlatElement {
+                                idx: ref_.idx,
+                                parent: ref_.parent,
+                                attributes: Vec::new(),
+                                children,
+                                name: _Fragment::NAME.to_string(),
+                                position: ref_.position.clone(),
+                                source_doc: ref_.source_doc,
+                                children_position: None,
+                                extending: None,
+                            })
+                        }
                         Err(err) => FlatNode::Error(FlatError {
                             idx: ref_.idx,
                             parent: ref_.parent,
-                            message: format_error_message(err, &ref_.path),
+                            message: format_error_message(&err, &ref_.path, |idx| {
+                                flat_root.nodes[idx].position().cloned()
+                            }),
                             error_type: ErrorType::Warning,
                             code: None,
                             args: None,
-                            unresolved_path: if let ResolutionError::NoReferent = err {
+                            unresolved_path: if let ResolutionError::NoReferent { .. } = err {
                                 Some(ref_.path.clone())
                             } else {
                                 None
                             },
                             position: ref_.position.clone(),
+                            path_part_position: err.position().cloned(),
                             source_doc: ref_.source_doc,
                         }),
                     }
@@ -154,16 +229,19 @@ impl Expander {
                             Err(err) => FlatNode::Error(FlatError {
                                 idx: function_ref.idx,
                                 parent: function_ref.parent,
-                                message: format!("Ref resolution error: {err}"),
+                                message: format_error_message(&err, &function_ref.path, |idx| {
+                                    flat_root.nodes[idx].position().cloned()
+                                }),
                                 error_type: ErrorType::Warning,
                                 code: None,
                                 args: None,
-                                unresolved_path: if let ResolutionError::NoReferent = err {
+                                unresolved_path: if let ResolutionError::NoReferent { .. } = err {
                                     Some(function_ref.path.clone())
                                 } else {
                                     None
                                 },
                                 position: function_ref.position.clone(),
+                                path_part_position: err.position().cloned(),
                                 source_doc: function_ref.source_doc,
                             }),
                         };
@@ -179,8 +257,10 @@ impl Expander {
     /// and instead set each node's `extending` to either a
     /// `Source::ExtendAttribute` or `Source::CopyAttribute` containing the extend's referent.
     /// This should be called _after_ all refs have been expanded into element form.
-    fn consume_extend_and_copy_attributes(flat_root: &mut FlatRoot) {
-        for i in 0..flat_root.nodes.len() {
+    ///
+    /// Only nodes with index `start_idx` or more are considered.
+    fn consume_extend_and_copy_attributes(flat_root: &mut FlatRoot, start_idx: Index) {
+        for i in start_idx..flat_root.nodes.len() {
             // Skip any cases we don't need to consider.
             if let FlatNode::Element(e) = &flat_root.nodes[i] {
                 if e.extending.is_some() {
@@ -537,7 +537,7 @@ fn initial_ref_skips_unqualified_option_children() {
                   },
                   {
                     "type": "error",
-                    "message": "No referent found for reference: `$n2`",
+                    "message": "No referent found for reference: `$n2`. Did you mean `$n1`?",
                     "errorType": "warning",
                     "parent": 0,
                     "idx": 3,
@@ -629,3 +629,130 @@ fn initial_ref_skips_unqualified_option_children() {
         )
     );
 }
+
+#[test]
+fn ref_errors_carry_the_position_of_the_failing_path_part() {
+    let dast_root = dast_root(r#"<number name="xMax" /> $xmx and $xMax[0]"#);
+    let mut flat_root = FlatRoot::from_dast(&dast_root);
+    Expander::expand(&mut flat_root);
+
+    let errors: Vec<_> = flat_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            FlatNode::Error(error) => Some(error),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(errors.len(), 2);
+
+    assert_eq!(
+        errors[0].message,
+        "No referent found for reference: `$xmx`. Did you mean `$xMax`?"
+    );
+    let position = errors[0].path_part_position.as_ref().unwrap();
+    assert_eq!((position.start.column, position.end.column), (25, 28));
+
+    // An invalid index is reported at the index.
+    assert_eq!(
+        errors[1].message,
+        "No referent found for reference: `$xMax`"
+    );
+    let position = errors[1].path_part_position.as_ref().unwrap();
+    assert_eq!((position.start.column, position.end.column), (38, 41));
+}
+
+#[test]
+fn ref_errors_list_the_positions_of_candidates() {
+    let dast_root = dast_root("<text name=\"t\" /><text name=\"t\" />\n$t $$t(x)");
+    let mut flat_root = FlatRoot::from_dast(&dast_root);
+    Expander::expand(&mut flat_root);
+
+    let messages: Vec<_> = flat_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            FlatNode::Error(error) => Some(error.message.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Multiple referents found for reference: `$t` (candidates at line 1, column 1 and line 1, column 18)";
+            2
+        ]
+    );
+}
+
+#[test]
+fn refs_to_ranges_expand_to_fragments() {
+    let dast_root = dast_root_no_position(
+        r#"<group name="g"><point name="p" />x<point name="q" /><text name="t" /></group>$g[1:3] $g[-1] $g[5]"#,
+    );
+    let mut flat_root = FlatRoot::from_dast(&dast_root);
+    Expander::expand(&mut flat_root);
+    assert_eq!(
+        flat_root.to_xml(),
+        r#"<document><group name="g"><point name="p" />x<point name="q" /><text name="t" /></group><_fragment><point /><point /></_fragment> <text /> <group /></document>"#
+    );
+
+    // The text item `x` is skipped, and `$g[5]` is left for the core to resolve.
+    let FlatNode::Element(fragment) = flat_root
+        .nodes
+        .iter()
+        .find(|node| matches!(node, FlatNode::Element(e) if e.name == "_fragment"))
+        .unwrap()
+    else {
+        unreachable!()
+    };
+    let referents: Vec<_> = fragment
+        .children
+        .iter()
+        .map(|child| match &flat_root.nodes[lookup_idx(child).unwrap()] {
+            FlatNode::Element(FlatElement {
+                extending: Some(Source::Ref(resolution)),
+                parent,
+                ..
+            }) => {
+                assert_eq!(*parent, Some(fragment.idx));
+                resolution.node_idx
+            }
+            node => panic!("expected an element extending a ref, found {node:?}"),
+        })
+        .collect();
+    let point_indices: Vec<_> = flat_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            FlatNode::Element(e) if e.name == "point" && e.extending.is_none() => Some(e.idx),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(referents, point_indices);
+}
+
+#[test]
+fn refs_to_out_of_range_indices_are_errors() {
+    let dast_root = dast_root_no_position(
+        r#"<group name="g"><point name="p" /><point name="q" /></group>$g[-3] $g[2:1]"#,
+    );
+    let mut flat_root = FlatRoot::from_dast(&dast_root);
+    Expander::expand(&mut flat_root);
+
+    let messages: Vec<_> = flat_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            FlatNode::Error(error) => Some(error.message.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Index `[-3]` is out of range for reference: `$g` has 2 items",
+            "Range `[2:1]` selects no items for reference: `$g`",
+        ]
+    );
+}
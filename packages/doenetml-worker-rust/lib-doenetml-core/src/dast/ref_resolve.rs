@@ -10,12 +10,14 @@ mod errors;
 mod index_resolutions;
 mod name_map;
 mod node_traversal;
+mod path_index;
 mod resolve;
 mod root_names;
 
 pub use errors::*;
 pub use index_resolutions::*;
 pub use name_map::*;
+pub use path_index::*;
 pub use resolve::*;
 
 #[cfg(test)]
@@ -1,9 +1,9 @@
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{iter, mem, ops::Range};
 
 use crate::dast::flat_dast::{
-    FlatAttribute, FlatElement, FlatFragment, FlatNode, FlatRoot, FlatRootOrFragment, SourceDoc,
-    UntaggedContent,
+    FlatAttribute, FlatElement, FlatFragment, FlatNode, FlatRoot, FlatRootOrFragment, Index,
+    SourceDoc, UntaggedContent,
 };
 
 use super::*;
@@ -214,6 +214,142 @@ impl Resolver {
         }
     }
 
+    /// Replace `old_nodes`, the nodes of some children of `flat_fragment.parent_idx` and of their descendants,
+    /// with the nodes of `flat_fragment`, e.g., after the source of those children was edited.
+    ///
+    /// Refs that resolved to one of the old nodes through the parent or its ancestors resolve to the
+    /// corresponding new node instead, where nodes correspond if they have the same name and the same position
+    /// among the nodes with that name. `element_name` gives the name of the element at an index,
+    /// so that names only found in the new nodes are made accessible to the same ancestors as in [`Resolver::from_flat_root`].
+    ///
+    /// Returns `false`, leaving the resolver unchanged, if the change could make a ref outside the fragment
+    /// resolve differently or become ambiguous, i.e., if the number of nodes with a name that is accessible
+    /// from the parent changed, or if a name found only in the new nodes was already accessible from the parent or its ancestors.
+    /// The index resolutions of the parent are not changed.
+    pub fn replace_nodes<'a>(
+        &mut self,
+        old_nodes: &[FlatNode],
+        flat_fragment: &FlatFragment,
+        element_name: impl Fn(Index) -> Option<&'a str>,
+    ) -> bool {
+        let Some(parent_idx) = flat_fragment.parent_idx else {
+            return false;
+        };
+        if parent_idx + 1 >= self.node_resolver_data.len() {
+            return false;
+        }
+        let old_indices: FxHashSet<Index> = old_nodes.iter().map(|node| node.idx()).collect();
+
+        // The names of the old nodes that are accessible from the parent
+        let old_refs: FxHashMap<NameWithSource, Vec<Index>> = self.node_resolver_data
+            [parent_idx + 1]
+            .name_map
+            .iter()
+            .filter_map(|(name_with_source, ref_)| {
+                let mut indices = ref_indices(ref_)
+                    .filter(|idx| old_indices.contains(idx))
+                    .collect::<Vec<_>>();
+                indices.sort_unstable();
+                (!indices.is_empty()).then(|| (name_with_source.clone(), indices))
+            })
+            .collect();
+
+        // The names of the new nodes that are accessible from the parent
+        let mut subtree_name_map =
+            Self::build_name_map(&FlatRootOrFragment::Fragment(flat_fragment));
+        let new_parent_map = mem::take(&mut subtree_name_map[parent_idx + 1]);
+
+        let ancestors = self.ancestors_plus_1(parent_idx);
+
+        let mut remapped_names = Vec::new();
+        let mut added_names = Vec::new();
+        for (name_with_source, ref_) in new_parent_map.iter() {
+            let mut new = ref_indices(ref_).collect::<Vec<_>>();
+            new.sort_unstable();
+            match old_refs.get(name_with_source) {
+                Some(old) if old.len() == new.len() => {
+                    remapped_names.push((name_with_source, old, new));
+                }
+                Some(_) => return false,
+                None => {
+                    if ancestors.iter().any(|idx_plus_1| {
+                        self.node_resolver_data[*idx_plus_1]
+                            .name_map
+                            .contains_key(name_with_source)
+                    }) {
+                        return false;
+                    }
+                    added_names.push((name_with_source, ref_));
+                }
+            }
+        }
+
+        // Point the name maps of the parent and its ancestors to the new nodes.
+        // The names of old nodes without a new counterpart are removed by `delete_nodes`.
+        for idx_plus_1 in ancestors.iter() {
+            let name_map = &mut self.node_resolver_data[*idx_plus_1].name_map;
+            for (name_with_source, old, new) in remapped_names.iter() {
+                let replace = |idx: &mut Index| {
+                    if let Some(i) = old.iter().position(|old_idx| old_idx == idx) {
+                        *idx = new[i];
+                    }
+                };
+                match name_map.get_mut(*name_with_source) {
+                    Some(Ref::Unique(idx)) => replace(idx),
+                    Some(Ref::Ambiguous(indices)) => indices.iter_mut().for_each(replace),
+                    None => {}
+                }
+            }
+        }
+
+        self.delete_nodes(old_nodes);
+        for node in old_nodes {
+            if let Some(node_resolver_data) = self.node_resolver_data.get_mut(node.idx() + 1) {
+                node_resolver_data.name_map.clear();
+                node_resolver_data.source_sequence = None;
+            }
+        }
+        self.add_nodes(flat_fragment, IndexResolution::None);
+
+        // `add_nodes` added the new names to the parent, so add them to the ancestors that can see them
+        for (name_with_source, ref_) in added_names {
+            for (child_idx_plus_1, idx_plus_1) in ancestors.iter().zip(ancestors.iter().skip(1)) {
+                let child_visibility = match child_idx_plus_1 {
+                    0 => Visibility::Visible,
+                    _ => element_name(child_idx_plus_1 - 1)
+                        .map(Visibility::lookup_by_name)
+                        .unwrap_or(Visibility::Invisible),
+                };
+                if matches!(
+                    child_visibility,
+                    Visibility::Invisible | Visibility::ChildrenInvisibleToTheirGrandparents
+                ) {
+                    break;
+                }
+                self.node_resolver_data[*idx_plus_1]
+                    .name_map
+                    .insert(name_with_source.clone(), ref_.clone());
+            }
+        }
+
+        true
+    }
+
+    /// The indices, shifted by one, of `idx` and its ancestors, ending with the flat root if `idx` is attached to it.
+    fn ancestors_plus_1(&self, idx: Index) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let mut idx_plus_1 = Some(idx + 1);
+        while let Some(current) = idx_plus_1 {
+            ancestors.push(current);
+            idx_plus_1 = match self.node_resolver_data[current].node_parent {
+                NodeParent::None => None,
+                NodeParent::FlatRoot => Some(0),
+                NodeParent::Node(idx) => Some(idx + 1),
+            };
+        }
+        ancestors
+    }
+
     /// Build a map of all the names that are accessible from a given node
     /// and the indices of the referents.
     fn build_name_map(
@@ -392,6 +528,15 @@ impl Resolver {
     }
 }
 
+/// The indices of the nodes referred to by `ref_`.
+fn ref_indices(ref_: &Ref) -> impl Iterator<Item = Index> + '_ {
+    let indices: &[Index] = match ref_ {
+        Ref::Unique(idx) => std::slice::from_ref(idx),
+        Ref::Ambiguous(indices) => indices,
+    };
+    indices.iter().copied()
+}
+
 /// Get a vector of the possible `NameWithSource` representations of the element.
 /// The `source_doc` for a `NameWithSource` is determined in two possible ways:
 /// - from the element's `name` attribute, in which case the `source_doc` is retrieved from the element itself, or
@@ -36,9 +36,9 @@ fn add_nodes() {
 
     // Since `z` and `w` were added to parent with name `y`, they cannot be found directly from `a`
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["w"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // Prefacing the added components with the fragment parent `y` allows them to be found as `y.z` and `y.w`.
     let referent = resolver.resolve(make_path(["y", "z"], None), a_idx, false);
@@ -159,9 +159,9 @@ fn add_nodes_with_no_parent() {
 
     // Since `z` and `w` were added without a parent, they cannot be found directly from `a`
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["w"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // Starting at `z`, one can find `w`
     let referent = resolver.resolve(make_path(["w"], None), c_idx, false);
@@ -177,10 +177,10 @@ fn add_nodes_with_no_parent() {
 
     // Starting at newly added nodes, one cannot search outward to find `y` or `q`
     let referent = resolver.resolve(make_path(["y"], None), c_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     let referent = resolver.resolve(make_path(["q"], None), g_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 }
 
 #[test]
@@ -282,6 +282,163 @@ fn delete_nodes() {
     );
 }
 
+#[test]
+fn replace_nodes() {
+    let dast_root = dast_root_no_position(
+        r#"<e><a name="x"><b name="y" /><c /></a></e>
+        <d name="q" />"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let a_idx = find(&flat_root, "a").unwrap();
+    let b_idx = find(&flat_root, "b").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+    let num_nodes = flat_root.nodes.len();
+
+    let mut resolver = Resolver::from_flat_root(&flat_root);
+
+    // The new `<b>` has a new child, but the names accessible from `a` are unchanged
+    let flat_fragment =
+        flat_fragment_from_str(r#"<b name="y"><g name="w" /></b>"#, num_nodes, Some(a_idx));
+    let new_b_idx = num_nodes;
+    let g_idx = num_nodes + 1;
+    assert!(
+        resolver.replace_nodes(&flat_root.nodes[b_idx..b_idx + 1], &flat_fragment, |idx| {
+            element_name(&flat_root, idx)
+        })
+    );
+
+    // Refs from outside `a` now find the new `<b>`
+    let referent = resolver.resolve(make_path(["y"], None), d_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: new_b_idx,
+            unresolved_path: None,
+            original_path: make_path(["y"], None),
+            nodes_in_resolved_path: vec![d_idx, new_b_idx]
+        })
+    );
+    let referent = resolver.resolve(make_path(["x", "y", "w"], None), d_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: g_idx,
+            unresolved_path: None,
+            original_path: make_path(["x", "y", "w"], None),
+            nodes_in_resolved_path: vec![d_idx, a_idx, new_b_idx, g_idx]
+        })
+    );
+
+    // The new nodes can search outward
+    let referent = resolver.resolve(make_path(["q"], None), g_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: d_idx,
+            unresolved_path: None,
+            original_path: make_path(["q"], None),
+            nodes_in_resolved_path: vec![g_idx, d_idx]
+        })
+    );
+}
+
+#[test]
+fn replace_nodes_adds_new_names_to_ancestors() {
+    let dast_root = dast_root_no_position(
+        r#"<e><a name="x"><b name="y" /></a><option name="o"><c /></option></e>
+        <d name="q" />"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let b_idx = find(&flat_root, "b").unwrap();
+    let c_idx = find(&flat_root, "c").unwrap();
+    let option_idx = find(&flat_root, "option").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+    let num_nodes = flat_root.nodes.len();
+
+    let mut resolver = Resolver::from_flat_root(&flat_root);
+
+    // The name `y` is removed and the name `z` is added
+    let flat_fragment = flat_fragment_from_str(r#"<b name="z" />"#, num_nodes, Some(b_idx - 1));
+    assert!(
+        resolver.replace_nodes(&flat_root.nodes[b_idx..b_idx + 1], &flat_fragment, |idx| {
+            element_name(&flat_root, idx)
+        })
+    );
+    let referent = resolver.resolve(make_path(["z"], None), d_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: num_nodes,
+            unresolved_path: None,
+            original_path: make_path(["z"], None),
+            nodes_in_resolved_path: vec![d_idx, num_nodes]
+        })
+    );
+    let referent = resolver.resolve(make_path(["y"], None), d_idx, false);
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
+
+    // Names added to the children of an `<option>` are accessible only through the option
+    let flat_fragment =
+        flat_fragment_from_str(r#"<c name="w" />"#, num_nodes + 1, Some(option_idx));
+    assert!(
+        resolver.replace_nodes(&flat_root.nodes[c_idx..c_idx + 1], &flat_fragment, |idx| {
+            element_name(&flat_root, idx)
+        })
+    );
+    let referent = resolver.resolve(make_path(["w"], None), d_idx, false);
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
+    let referent = resolver.resolve(make_path(["o", "w"], None), d_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: num_nodes + 1,
+            unresolved_path: None,
+            original_path: make_path(["o", "w"], None),
+            nodes_in_resolved_path: vec![d_idx, option_idx, num_nodes + 1]
+        })
+    );
+}
+
+#[test]
+fn replace_nodes_fails_if_other_refs_could_change() {
+    let dast_root = dast_root_no_position(
+        r#"<e><a name="x"><b name="y" /><c /></a></e>
+        <d name="q" />"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let a_idx = find(&flat_root, "a").unwrap();
+    let b_idx = find(&flat_root, "b").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+    let num_nodes = flat_root.nodes.len();
+
+    let mut resolver = Resolver::from_flat_root(&flat_root);
+
+    // `$y` would become ambiguous, and `$q` could resolve to the new `<f>` from inside `a`
+    for new_content in [
+        r#"<b name="y" /><f name="y" />"#,
+        r#"<b name="y" /><f name="q" />"#,
+    ] {
+        let flat_fragment = flat_fragment_from_str(new_content, num_nodes, Some(a_idx));
+        assert!(!resolver.replace_nodes(
+            &flat_root.nodes[b_idx..b_idx + 1],
+            &flat_fragment,
+            |idx| element_name(&flat_root, idx)
+        ));
+    }
+
+    // The resolver is unchanged
+    let referent = resolver.resolve(make_path(["y"], None), d_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: b_idx,
+            unresolved_path: None,
+            original_path: make_path(["y"], None),
+            nodes_in_resolved_path: vec![d_idx, b_idx]
+        })
+    );
+}
+
 #[test]
 fn add_and_delete_a_large_number_of_nodes_multiple_times() {
     // Note: this test should run in a fraction of a second.
@@ -402,3 +559,11 @@ fn add_and_delete_a_large_number_of_nodes_multiple_times() {
         })
     );
 }
+
+/// The name of the element of `flat_root` with index `idx`.
+fn element_name(flat_root: &FlatRoot, idx: Index) -> Option<&str> {
+    match &flat_root.nodes[idx] {
+        FlatNode::Element(element) => Some(element.name.as_str()),
+        _ => None,
+    }
+}
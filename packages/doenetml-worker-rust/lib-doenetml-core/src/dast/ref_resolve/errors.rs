@@ -2,21 +2,81 @@ use serde::Serialize;
 use thiserror::Error;
 use tsify_next::Tsify;
 
-use crate::dast::flat_dast::FlatPathPart;
+use crate::dast::{
+    Position,
+    flat_dast::{FlatPathPart, Index},
+};
 
-#[derive(Clone, Debug, Serialize, Error, PartialEq, Copy)]
+/// The maximum number of suggestions offered for a reference without a referent.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// An error resolving a reference.
+///
+/// Serializes as an object whose `kind` is the name of the variant, e.g. `{ kind: "NoReferent", ... }`,
+/// so that JavaScript can tell the errors apart.
+#[derive(Clone, Debug, Serialize, Error, PartialEq)]
 #[cfg_attr(feature = "web", derive(Tsify))]
 #[cfg_attr(feature = "web", tsify(into_wasm_abi))]
+#[serde(tag = "kind")]
 pub enum ResolutionError {
     #[error("No node identified by path")]
-    NoReferent,
+    NoReferent {
+        /// The position of the path part (or index) that could not be matched, if known.
+        position: Option<Position>,
+        /// Names accessible from the origin of the reference that are similar to the name that was not found,
+        /// most similar first.
+        suggestions: Vec<String>,
+    },
     #[error("Path referred to more than one node")]
-    NonUniqueReferent,
+    NonUniqueReferent {
+        /// The position of the path part that matched more than one node, if known.
+        position: Option<Position>,
+        /// The nodes that the path part matched.
+        candidates: Vec<Index>,
+    },
+    #[error("Index is out of range")]
+    #[cfg_attr(feature = "web", serde(rename_all = "camelCase"))]
+    IndexOutOfRange {
+        /// The position of the index that is out of range, if known.
+        position: Option<Position>,
+        /// The index as written, e.g. `[-5]`.
+        index: String,
+        /// The number of items that the index could have referred to.
+        num_items: usize,
+    },
+    #[error("Range of indices is empty")]
+    EmptyRange {
+        /// The position of the range, if known.
+        position: Option<Position>,
+        /// The range as written, e.g. `[4:2]`.
+        index: String,
+    },
+}
+
+impl ResolutionError {
+    /// A `NoReferent` error with no position or suggestions.
+    pub fn no_referent() -> Self {
+        ResolutionError::NoReferent {
+            position: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// The position of the path part that could not be resolved, if known.
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            ResolutionError::NoReferent { position, .. }
+            | ResolutionError::NonUniqueReferent { position, .. }
+            | ResolutionError::IndexOutOfRange { position, .. }
+            | ResolutionError::EmptyRange { position, .. } => position.as_ref(),
+        }
+    }
 }
 
 /// Format an error message given that `err` was produced when trying to resolve `path`.
 /// Since the resolve algorithm stops when any index is found,
 /// this message includes the path up to any index.
+/// The positions of the candidates of a `NonUniqueReferent` error are looked up with `node_position`.
 ///
 /// For example, given this DoenetML
 /// ```xml
@@ -26,11 +86,17 @@ pub enum ResolutionError {
 /// ```
 /// the error messages for the two references will be
 /// - `"No referent found for reference: $a.b.c"`, and
-/// - `"Multiple references found for reference: $t.a`.
-pub fn format_error_message(err: ResolutionError, path: &[FlatPathPart]) -> String {
-    // Note: this message could potentially be improved by recording
-    // the start and end location of the reference node
-    // and using the entire DoenetML string that created the reference
+/// - `"Multiple referents found for reference: $t.a (candidates at line 1, column 1 and line 1, column 18)"`.
+///
+/// If a name similar to the one that was not found is accessible, it is suggested,
+/// e.g. `"No referent found for reference: $xmx. Did you mean $xMax?"`.
+/// An index that is out of range, e.g. `$list[-5]` for a list of three items, gives
+/// `"Index [-5] is out of range for reference: $list has 3 items"`.
+pub fn format_error_message(
+    err: &ResolutionError,
+    path: &[FlatPathPart],
+    node_position: impl Fn(Index) -> Option<Position>,
+) -> String {
     let mut paths_until_first_index = vec![];
     for path_part in path.iter() {
         if !path_part.name.is_empty() {
@@ -43,11 +109,100 @@ pub fn format_error_message(err: ResolutionError, path: &[FlatPathPart]) -> Stri
     let paths_string = paths_until_first_index.join(".");
 
     match err {
-        ResolutionError::NoReferent => {
-            format!("No referent found for reference: `${paths_string}`")
+        ResolutionError::NoReferent { suggestions, .. } => {
+            let mut message = format!("No referent found for reference: `${paths_string}`");
+            if !suggestions.is_empty() {
+                let suggestions: Vec<_> = suggestions
+                    .iter()
+                    .map(|name| format!("`${name}`"))
+                    .collect();
+                message.push_str(&format!(
+                    ". Did you mean {}?",
+                    join_alternatives(&suggestions, "or")
+                ));
+            }
+            message
+        }
+        ResolutionError::NonUniqueReferent { candidates, .. } => {
+            let mut message = format!("Multiple referents found for reference: `${paths_string}`");
+            let positions: Vec<_> = candidates
+                .iter()
+                .filter_map(|&idx| node_position(idx))
+                .map(|position| {
+                    format!(
+                        "line {}, column {}",
+                        position.start.line, position.start.column
+                    )
+                })
+                .collect();
+            if !positions.is_empty() {
+                message.push_str(&format!(
+                    " (candidates at {})",
+                    join_alternatives(&positions, "and")
+                ));
+            }
+            message
         }
-        ResolutionError::NonUniqueReferent => {
-            format!("Multiple referents found for reference: `${paths_string}`")
+        ResolutionError::IndexOutOfRange {
+            index, num_items, ..
+        } => {
+            let items = if *num_items == 1 { "item" } else { "items" };
+            format!(
+                "Index `{index}` is out of range for reference: `${paths_string}` has {num_items} {items}"
+            )
+        }
+        ResolutionError::EmptyRange { index, .. } => {
+            format!("Range `{index}` selects no items for reference: `${paths_string}`")
         }
     }
 }
+
+/// The names in `names` that are similar to `name`, most similar first. A name is similar if it is
+/// a small number of edits away from `name`, ignoring case.
+pub(super) fn similar_names<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let max_distance = (name.chars().count() / 3).clamp(1, 2);
+    let lowercase_name = name.to_lowercase();
+    let mut similar: Vec<_> = names
+        .filter(|&candidate| candidate != name)
+        .filter_map(|candidate| {
+            let distance = edit_distance(&lowercase_name, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = Vec::with_capacity(b.len() + 1);
+        row.push(i + 1);
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != b_char);
+            row.push(substitution.min(previous_row[j + 1] + 1).min(row[j] + 1));
+        }
+        previous_row = row;
+    }
+    previous_row[b.len()]
+}
+
+/// Join `items` as a list, e.g. `a, b or c`.
+fn join_alternatives(items: &[String], conjunction: &str) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [init @ .., last] => format!("{} {conjunction} {last}", init.join(", ")),
+    }
+}
+
+#[cfg(test)]
+#[path = "errors.test.rs"]
+mod test;
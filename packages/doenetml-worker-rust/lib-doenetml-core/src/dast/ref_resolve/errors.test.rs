@@ -0,0 +1,136 @@
+use super::*;
+use crate::dast::{
+    Point,
+    ref_resolve::test_helpers::{TestPathPart, make_path, make_path_with_indices},
+};
+
+fn position_at(line: usize, column: usize) -> Position {
+    Position {
+        start: Point {
+            line,
+            column,
+            offset: None,
+        },
+        end: Point {
+            line,
+            column: column + 1,
+            offset: None,
+        },
+    }
+}
+
+#[test]
+fn edit_distance_counts_insertions_deletions_and_substitutions() {
+    assert_eq!(edit_distance("xmax", "xmax"), 0);
+    assert_eq!(edit_distance("xmx", "xmax"), 1);
+    assert_eq!(edit_distance("xmaxx", "xmax"), 1);
+    assert_eq!(edit_distance("ymax", "xmax"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn similar_names_are_close_ignoring_case_and_sorted_by_distance() {
+    let names = ["xMax", "xMin", "yMax", "t", "xmxa", "x"];
+    // Short names only allow a single edit
+    assert_eq!(
+        similar_names("xmx", names.into_iter()),
+        vec!["xMax", "xmxa"]
+    );
+    assert_eq!(
+        similar_names("XMAX", names.into_iter()),
+        vec!["xMax", "yMax"]
+    );
+    assert_eq!(similar_names("s", names.into_iter()), vec!["t", "x"]);
+    assert!(similar_names("abcdef", names.into_iter()).is_empty());
+
+    // Longer names allow two edits, and at most three names are suggested
+    let names = ["abcdgg", "abc", "abcdefgh", "abcxef", "abcdeg"];
+    assert_eq!(
+        similar_names("abcdef", names.into_iter()),
+        vec!["abcdeg", "abcxef", "abcdefgh"]
+    );
+}
+
+#[test]
+fn no_referent_message_includes_suggestions() {
+    let path = make_path(["xmx"], None);
+    let err = ResolutionError::NoReferent {
+        position: None,
+        suggestions: vec![],
+    };
+    assert_eq!(
+        format_error_message(&err, &path, |_| None),
+        "No referent found for reference: `$xmx`"
+    );
+
+    let err = ResolutionError::NoReferent {
+        position: None,
+        suggestions: vec!["xMax".to_string()],
+    };
+    assert_eq!(
+        format_error_message(&err, &path, |_| None),
+        "No referent found for reference: `$xmx`. Did you mean `$xMax`?"
+    );
+
+    let err = ResolutionError::NoReferent {
+        position: None,
+        suggestions: vec!["xMax".to_string(), "x".to_string(), "xMin".to_string()],
+    };
+    assert_eq!(
+        format_error_message(&err, &path, |_| None),
+        "No referent found for reference: `$xmx`. Did you mean `$xMax`, `$x` or `$xMin`?"
+    );
+}
+
+#[test]
+fn non_unique_referent_message_lists_candidate_positions() {
+    let path = make_path_with_indices(
+        &[
+            TestPathPart {
+                name: "t",
+                indices: vec![],
+            },
+            TestPathPart {
+                name: "a",
+                indices: vec!["1"],
+            },
+            TestPathPart {
+                name: "b",
+                indices: vec![],
+            },
+        ],
+        None,
+    );
+    let err = ResolutionError::NonUniqueReferent {
+        position: None,
+        candidates: vec![1, 2, 5],
+    };
+    let node_position = |idx| match idx {
+        1 => Some(position_at(1, 1)),
+        2 => Some(position_at(1, 18)),
+        _ => None,
+    };
+    assert_eq!(
+        format_error_message(&err, &path, node_position),
+        "Multiple referents found for reference: `$t.a` (candidates at line 1, column 1 and line 1, column 18)"
+    );
+    assert_eq!(
+        format_error_message(&err, &path, |_| None),
+        "Multiple referents found for reference: `$t.a`"
+    );
+}
+
+#[test]
+fn errors_serialize_with_their_kind() {
+    let value = serde_json::to_value(ResolutionError::no_referent()).unwrap();
+    assert_eq!(value["kind"], "NoReferent");
+
+    let value = serde_json::to_value(ResolutionError::NonUniqueReferent {
+        position: None,
+        candidates: vec![1, 2],
+    })
+    .unwrap();
+    assert_eq!(value["kind"], "NonUniqueReferent");
+    assert_eq!(value["candidates"], serde_json::json!([1, 2]));
+}
@@ -133,7 +133,10 @@ fn resolution_resolves_path_index_with_implicit_index_resolutions() {
         },
     ];
     let referent = resolver.resolve(path.clone(), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 
     let index1 = vec![FlatIndex {
         value: vec![UntaggedContent::Text("1".into())],
@@ -387,7 +390,7 @@ fn add_nodes_with_index_resolutions() {
 
     // Since the `z` was added later, it cannot be found directly from `a`
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // Prefacing the added components with the fragment parent `s` and an index allows them to be found as `s[1].z` and `s[2].z`.
     let path = make_path_with_indices(
@@ -1279,3 +1282,221 @@ fn replace_nodes_with_no_parent_but_index_resolutions() {
         })
     );
 }
+
+#[test]
+fn negative_indices_count_from_the_end() {
+    let dast_root = dast_root_no_position(
+        r#"<a name="x">
+            <group name="g">
+                <c><e name="z" /></c>
+                <d><f name="z" /></d>
+            </group>
+        </a>"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let a_idx = find(&flat_root, "a").unwrap();
+    let c_idx = find(&flat_root, "c").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+    let f_idx = find(&flat_root, "f").unwrap();
+    let g_idx = find(&flat_root, "group").unwrap();
+
+    let resolver = Resolver::from_flat_root(&flat_root);
+
+    // `$g[-1].z`
+    let path = make_path_with_indices(
+        &[
+            TestPathPart {
+                name: "g",
+                indices: vec!["-1"],
+            },
+            TestPathPart {
+                name: "z",
+                indices: vec![],
+            },
+        ],
+        None,
+    );
+    let referent = resolver.resolve(path.clone(), a_idx, false);
+    assert_eq!(
+        referent,
+        Ok(RefResolution {
+            node_idx: f_idx,
+            unresolved_path: None,
+            original_path: path,
+            nodes_in_resolved_path: vec![a_idx, g_idx, d_idx, f_idx]
+        })
+    );
+
+    // `$g[-2]`
+    let path = make_path_with_indices(
+        &[TestPathPart {
+            name: "g",
+            indices: vec!["-2"],
+        }],
+        None,
+    );
+    let referent = resolver.resolve(path, a_idx, false);
+    assert_eq!(referent.unwrap().node_idx, c_idx);
+
+    // `$g[-3]` and `$g[-1:3]` are out of range
+    for index in ["-3", "-1:3"] {
+        let path = make_path_with_indices(
+            &[TestPathPart {
+                name: "g",
+                indices: vec![index],
+            }],
+            None,
+        );
+        let referent = resolver.resolve(path, a_idx, false);
+        assert_eq!(
+            referent,
+            Err(ResolutionError::IndexOutOfRange {
+                position: None,
+                index: format!("[{index}]"),
+                num_items: 2,
+            })
+        );
+    }
+
+    // Without index resolutions, a negative index is left unresolved
+    let path = make_path_with_indices(
+        &[TestPathPart {
+            name: "x",
+            indices: vec!["-1"],
+        }],
+        None,
+    );
+    let referent = resolver.resolve(path, a_idx, false).unwrap();
+    assert_eq!(referent.node_idx, a_idx);
+    assert_eq!(referent.unresolved_path.unwrap()[0].name, "");
+}
+
+#[test]
+fn ranges_resolve_to_several_referents() {
+    let dast_root = dast_root_no_position(
+        r#"<a name="x">
+            <group name="g">
+                <c><e name="z" /></c>
+                text
+                <d><f name="z" /></d>
+                <h />
+            </group>
+        </a>"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let a_idx = find(&flat_root, "a").unwrap();
+    let c_idx = find(&flat_root, "c").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+    let e_idx = find(&flat_root, "e").unwrap();
+    let f_idx = find(&flat_root, "f").unwrap();
+    let g_idx = find(&flat_root, "group").unwrap();
+    let h_idx = find(&flat_root, "h").unwrap();
+
+    let resolver = Resolver::from_flat_root(&flat_root);
+
+    let g_path = |index: &'static str| {
+        make_path_with_indices(
+            &[TestPathPart {
+                name: "g",
+                indices: vec![index],
+            }],
+            None,
+        )
+    };
+    let node_indices = |resolutions: Vec<RefResolution>| {
+        resolutions
+            .into_iter()
+            .map(|resolution| resolution.node_idx)
+            .collect::<Vec<_>>()
+    };
+
+    // The text item is skipped
+    let referents = resolver.resolve_all(g_path("1:3"), a_idx, false).unwrap();
+    assert_eq!(
+        referents,
+        vec![
+            RefResolution {
+                node_idx: c_idx,
+                unresolved_path: None,
+                original_path: g_path("1:3"),
+                nodes_in_resolved_path: vec![a_idx, g_idx, c_idx]
+            },
+            RefResolution {
+                node_idx: d_idx,
+                unresolved_path: None,
+                original_path: g_path("1:3"),
+                nodes_in_resolved_path: vec![a_idx, g_idx, d_idx]
+            }
+        ]
+    );
+    assert_eq!(
+        node_indices(resolver.resolve_all(g_path("-2:"), a_idx, false).unwrap()),
+        vec![d_idx, h_idx]
+    );
+    assert_eq!(
+        node_indices(resolver.resolve_all(g_path(":"), a_idx, false).unwrap()),
+        vec![c_idx, d_idx, h_idx]
+    );
+
+    // A single index gives a single referent
+    assert_eq!(
+        node_indices(resolver.resolve_all(g_path("-1"), a_idx, false).unwrap()),
+        vec![h_idx]
+    );
+
+    // The rest of the path is resolved from each item in the range
+    let path = make_path_with_indices(
+        &[
+            TestPathPart {
+                name: "g",
+                indices: vec!["1:3"],
+            },
+            TestPathPart {
+                name: "z",
+                indices: vec![],
+            },
+        ],
+        None,
+    );
+    let referents = resolver.resolve_all(path.clone(), a_idx, false).unwrap();
+    assert_eq!(node_indices(referents.clone()), vec![e_idx, f_idx]);
+    assert_eq!(
+        referents[1].nodes_in_resolved_path,
+        vec![a_idx, g_idx, d_idx, f_idx]
+    );
+    assert!(
+        referents
+            .iter()
+            .all(|referent| referent.original_path == path)
+    );
+
+    // `resolve` leaves the range as the unresolved path
+    let referent = resolver.resolve(g_path("1:3"), a_idx, false).unwrap();
+    assert_eq!(referent.node_idx, g_idx);
+    assert_eq!(
+        referent.unresolved_path,
+        Some(make_path_with_indices(
+            &[TestPathPart {
+                name: "",
+                indices: vec!["1:3"],
+            }],
+            None,
+        ))
+    );
+
+    assert_eq!(
+        resolver.resolve_all(g_path("3:1"), a_idx, false),
+        Err(ResolutionError::EmptyRange {
+            position: None,
+            index: "[3:1]".into(),
+        })
+    );
+    assert_eq!(
+        resolver.resolve_all(g_path("2:5"), a_idx, false),
+        Err(ResolutionError::IndexOutOfRange {
+            position: None,
+            index: "[2:5]".into(),
+            num_items: 4,
+        })
+    );
+}
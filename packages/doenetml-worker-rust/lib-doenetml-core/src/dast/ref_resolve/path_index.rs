@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use super::ResolutionError;
+use crate::dast::flat_dast::{FlatIndex, UntaggedContent};
+
+/// The value of an index in a ref path, e.g. the `2` in `$list[2]`.
+///
+/// Indices count from `1` at the start of a list or from `-1` at the end of a list.
+/// For example, `$list[-1]` refers to the last item of `list`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathIndex {
+    /// A single item, e.g. `$list[2]` or `$list[-1]`.
+    Single(isize),
+    /// An inclusive range of items, e.g. `$list[2:4]` or `$list[-2:]`.
+    /// An omitted `start` or `end` is the start or end of the list, respectively.
+    Range {
+        start: Option<isize>,
+        end: Option<isize>,
+    },
+}
+
+impl PathIndex {
+    /// Parse the text of an index. Returns `None` if `text` is not an index or range of nonzero integers.
+    pub fn parse(text: &str) -> Option<Self> {
+        fn parse_bound(text: &str) -> Option<isize> {
+            text.trim()
+                .parse::<isize>()
+                .ok()
+                .filter(|bound| *bound != 0)
+        }
+
+        match text.split_once(':') {
+            None => parse_bound(text).map(PathIndex::Single),
+            Some((start, end)) => {
+                let start = if start.trim().is_empty() {
+                    None
+                } else {
+                    Some(parse_bound(start)?)
+                };
+                let end = if end.trim().is_empty() {
+                    None
+                } else {
+                    Some(parse_bound(end)?)
+                };
+                Some(PathIndex::Range { start, end })
+            }
+        }
+    }
+
+    /// Parse `index` if it consists of a single text node. See [`PathIndex::parse`].
+    ///
+    /// Returns `None` if the value of `index` is not plain text (e.g. `$list[$n]`),
+    /// and `Some(None)` if it is text that is not a valid index.
+    pub fn from_flat_index(index: &FlatIndex) -> Option<Option<Self>> {
+        match &index.value[..] {
+            [UntaggedContent::Text(text)] => Some(PathIndex::parse(text)),
+            _ => None,
+        }
+    }
+
+    /// Whether the index can be evaluated without knowing the number of items in the list.
+    pub fn counts_from_start(&self) -> bool {
+        matches!(self, PathIndex::Single(idx) if *idx > 0)
+    }
+
+    /// The zero-based positions of the items selected from a list of `num_items` items.
+    ///
+    /// `index` is the index from which `self` was parsed and is used for error reporting.
+    /// Returns a `ResolutionError::IndexOutOfRange` if any part of the index is not in the list,
+    /// and a `ResolutionError::EmptyRange` if a range selects no items.
+    pub fn select(
+        &self,
+        num_items: usize,
+        index: &FlatIndex,
+    ) -> Result<Range<usize>, ResolutionError> {
+        self.try_select(num_items).map_err(|err| match err {
+            SelectError::OutOfRange => ResolutionError::IndexOutOfRange {
+                position: index.position.clone(),
+                index: index_text(index),
+                num_items,
+            },
+            SelectError::EmptyRange => ResolutionError::EmptyRange {
+                position: index.position.clone(),
+                index: index_text(index),
+            },
+        })
+    }
+
+    /// The zero-based positions of the items selected from a list of `num_items` items,
+    /// or `None` if any part of the index is not in the list or a range selects no items.
+    pub fn positions(&self, num_items: usize) -> Option<Range<usize>> {
+        self.try_select(num_items).ok()
+    }
+
+    fn try_select(&self, num_items: usize) -> Result<Range<usize>, SelectError> {
+        let position = |bound: isize| {
+            let position = if bound > 0 {
+                bound - 1
+            } else {
+                num_items as isize + bound
+            };
+            if (0..num_items as isize).contains(&position) {
+                Ok(position as usize)
+            } else {
+                Err(SelectError::OutOfRange)
+            }
+        };
+
+        match *self {
+            PathIndex::Single(bound) => {
+                let position = position(bound)?;
+                Ok(position..position + 1)
+            }
+            PathIndex::Range { start, end } => {
+                let start = start.map_or(Ok(0), position)?;
+                let end = match end {
+                    Some(end) => position(end)? + 1,
+                    None => num_items,
+                };
+                if start < end {
+                    Ok(start..end)
+                } else {
+                    Err(SelectError::EmptyRange)
+                }
+            }
+        }
+    }
+}
+
+/// Why a `PathIndex` could not select any items from a list.
+enum SelectError {
+    OutOfRange,
+    EmptyRange,
+}
+
+/// The text of `index` as it was written, e.g. `[2:4]`.
+pub(crate) fn index_text(index: &FlatIndex) -> String {
+    let text: String = index
+        .value
+        .iter()
+        .filter_map(|content| match content {
+            UntaggedContent::Text(text) => Some(text.as_str()),
+            UntaggedContent::Ref(_) => None,
+        })
+        .collect();
+    format!("[{text}]")
+}
+
+#[cfg(test)]
+#[path = "path_index.test.rs"]
+mod test;
@@ -0,0 +1,90 @@
+use super::*;
+
+fn flat_index(text: &str) -> FlatIndex {
+    FlatIndex {
+        value: vec![UntaggedContent::Text(text.into())],
+        position: None,
+        source_doc: None,
+    }
+}
+
+fn select(text: &str, num_items: usize) -> Result<Range<usize>, ResolutionError> {
+    PathIndex::parse(text)
+        .unwrap()
+        .select(num_items, &flat_index(text))
+}
+
+#[test]
+fn parses_single_indices_and_ranges() {
+    assert_eq!(PathIndex::parse("2"), Some(PathIndex::Single(2)));
+    assert_eq!(PathIndex::parse("-1"), Some(PathIndex::Single(-1)));
+    assert_eq!(
+        PathIndex::parse("2:4"),
+        Some(PathIndex::Range {
+            start: Some(2),
+            end: Some(4)
+        })
+    );
+    assert_eq!(
+        PathIndex::parse("-2:"),
+        Some(PathIndex::Range {
+            start: Some(-2),
+            end: None
+        })
+    );
+    assert_eq!(
+        PathIndex::parse(" : -2 "),
+        Some(PathIndex::Range {
+            start: None,
+            end: Some(-2)
+        })
+    );
+
+    for invalid in ["0", "a", "1.5", "", "1:0", "1:2:3", "x:2"] {
+        assert_eq!(PathIndex::parse(invalid), None, "{invalid:?}");
+    }
+}
+
+#[test]
+fn selects_items_counting_from_either_end() {
+    assert_eq!(select("1", 3), Ok(0..1));
+    assert_eq!(select("3", 3), Ok(2..3));
+    assert_eq!(select("-1", 3), Ok(2..3));
+    assert_eq!(select("-3", 3), Ok(0..1));
+
+    assert_eq!(select("2:3", 3), Ok(1..3));
+    assert_eq!(select("2:2", 3), Ok(1..2));
+    assert_eq!(select("-2:", 3), Ok(1..3));
+    assert_eq!(select(":-2", 3), Ok(0..2));
+    assert_eq!(select(":", 3), Ok(0..3));
+    assert_eq!(select("1:-1", 3), Ok(0..3));
+}
+
+#[test]
+fn out_of_range_and_empty_selections_are_errors() {
+    for (text, num_items) in [("4", 3), ("-4", 3), ("2:4", 3), ("-5:", 3), ("1", 0)] {
+        assert_eq!(
+            select(text, num_items),
+            Err(ResolutionError::IndexOutOfRange {
+                position: None,
+                index: format!("[{text}]"),
+                num_items,
+            })
+        );
+    }
+
+    assert_eq!(
+        select("3:2", 3),
+        Err(ResolutionError::EmptyRange {
+            position: None,
+            index: "[3:2]".into(),
+        })
+    );
+    assert_eq!(
+        select("-1:-2", 3),
+        Err(ResolutionError::EmptyRange {
+            position: None,
+            index: "[-1:-2]".into(),
+        })
+    );
+}
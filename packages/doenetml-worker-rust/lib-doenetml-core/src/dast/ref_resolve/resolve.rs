@@ -2,9 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::iter;
 use tsify_next::Tsify;
 
-use super::{NameMap, ResolutionError};
+use super::{NameMap, PathIndex, ResolutionError, errors::similar_names};
 use crate::dast::{
-    flat_dast::{FlatElement, FlatNode, FlatPathPart, Index, SourceDoc, UntaggedContent},
+    flat_dast::{FlatElement, FlatNode, FlatPathPart, Index, SourceDoc},
     ref_resolve::NameWithSource,
 };
 
@@ -65,7 +65,7 @@ const CHILDREN_INVISIBLE_TO_THEIR_GRANDPARENTS: [&str; 5] =
     ["repeat", "repeatForSequence", "option", "case", "else"];
 
 impl Visibility {
-    fn lookup_by_name(name: &str) -> Self {
+    pub(super) fn lookup_by_name(name: &str) -> Self {
         if CHILDREN_INVISIBLE_TO_THEIR_GRANDPARENTS.contains(&name) {
             Visibility::ChildrenInvisibleToTheirGrandparents
         } else {
@@ -160,6 +160,11 @@ impl Resolver {
     /// E.g., matching `y.w[2]` from `<b />` returns the index of `<d />` along with `.w[2]` as the unresolved path
     /// and matching `y[2]` from `<b />` returns the index of `<d />` along with `.[2]` as the unresolved path.
     ///
+    /// An index of a node with index resolutions (e.g. a `<group>`) is resolved to the corresponding item.
+    /// A negative index counts from the end, so `g[-1]` is the last item of `g`. A range (e.g. `g[2:4]`)
+    /// is left in the unresolved path; use [`Resolver::resolve_all`] to expand it into its items.
+    /// A negative index or range that does not fit the number of items is an error.
+    ///
     /// If `skip_parent_search` is `true`, then modify the algorithm to only match children of `origin`.
     /// The result is equivalent to the full algorithm where the first part of the path matched `origin`,
     /// and the remaining path is `path`.
@@ -171,7 +176,7 @@ impl Resolver {
     ) -> Result<RefResolution, ResolutionError> {
         // If `origin` passed in is not a node in the resolver, then return no referent
         if origin + 1 >= self.node_resolver_data.len() {
-            return Err(ResolutionError::NoReferent);
+            return Err(ResolutionError::no_referent());
         }
 
         let path = path.as_ref();
@@ -195,14 +200,27 @@ impl Resolver {
         let remaining_path: Vec<FlatPathPart>;
 
         if !skip_parent_search {
-            let first_path_part = path.next().ok_or(ResolutionError::NoReferent)?;
-            current_idx = self.search_parents(
-                &NameWithSource {
-                    name: first_path_part.name.clone(),
-                    source_doc,
-                },
-                current_idx,
-            )?;
+            let first_path_part = path.next().ok_or(ResolutionError::no_referent())?;
+            let first_name = NameWithSource {
+                name: first_path_part.name.clone(),
+                source_doc,
+            };
+            current_idx =
+                self.search_parents(&first_name, current_idx)
+                    .map_err(|err| match err {
+                        ResolutionError::NoReferent { .. } => ResolutionError::NoReferent {
+                            position: first_path_part.position.clone(),
+                            suggestions: self.similar_accessible_names(&first_name, origin),
+                        },
+                        ResolutionError::NonUniqueReferent { candidates, .. } => {
+                            ResolutionError::NonUniqueReferent {
+                                position: first_path_part.position.clone(),
+                                candidates,
+                            }
+                        }
+                        // `search_parents` does not evaluate indices
+                        err => err,
+                    })?;
             if current_idx != origin {
                 nodes_in_resolved_path.push(current_idx);
             }
@@ -247,8 +265,11 @@ impl Resolver {
                             }
                             node_data = &self.node_resolver_data[current_idx + 1];
                         }
-                        Ref::Ambiguous(_) => {
-                            return Err(ResolutionError::NonUniqueReferent);
+                        Ref::Ambiguous(indices) => {
+                            return Err(ResolutionError::NonUniqueReferent {
+                                position: part.position.clone(),
+                                candidates: indices.clone(),
+                            });
                         }
                     }
                 }
@@ -290,8 +311,11 @@ impl Resolver {
                                             }
                                             node_data = &self.node_resolver_data[current_idx + 1];
                                         }
-                                        Ref::Ambiguous(_) => {
-                                            return Err(ResolutionError::NonUniqueReferent);
+                                        Ref::Ambiguous(indices) => {
+                                            return Err(ResolutionError::NonUniqueReferent {
+                                                position: part.position.clone(),
+                                                candidates: indices.clone(),
+                                            });
                                         }
                                     }
                                 } else {
@@ -328,8 +352,11 @@ impl Resolver {
                                                 node_data =
                                                     &self.node_resolver_data[current_idx + 1];
                                             }
-                                            Ref::Ambiguous(_) => {
-                                                return Err(ResolutionError::NonUniqueReferent);
+                                            Ref::Ambiguous(indices) => {
+                                                return Err(ResolutionError::NonUniqueReferent {
+                                                    position: part.position.clone(),
+                                                    candidates: indices.clone(),
+                                                });
                                             }
                                         }
                                     }
@@ -355,76 +382,78 @@ impl Resolver {
             }
 
             for (index_idx, index) in part.index.iter().enumerate() {
-                if index.value.len() == 1 {
-                    match &index.value[0] {
-                        UntaggedContent::Text(index_str) => match index_str.parse::<usize>() {
-                            Ok(index_num) => {
-                                if index_num == 0 {
-                                    // path indices begin at `1``, so we cannot match a `0`
-                                    return Err(ResolutionError::NoReferent);
+                // If the index is a reference to another component (or contains one),
+                // then we need information that is not in the resolver to continue.
+                if let Some(path_index) = PathIndex::from_flat_index(index) {
+                    let Some(path_index) = path_index else {
+                        // the string index did not correspond to a nonzero integer or range of nonzero integers
+                        return Err(ResolutionError::NoReferent {
+                            position: index.position.clone(),
+                            suggestions: Vec::new(),
+                        });
+                    };
+
+                    // Negative indices and ranges depend on the number of items, so they can be evaluated
+                    // only if the index resolutions of the current node are known.
+                    // A range that can be evaluated is left in the unresolved path for `resolve_all` to expand.
+                    let num_items = node_data.index_resolutions.len();
+                    let item = match path_index {
+                        PathIndex::Single(index_num) if path_index.counts_from_start() => {
+                            Some(index_num as usize - 1)
+                        }
+                        _ if num_items == 0 => None,
+                        PathIndex::Single(_) => Some(path_index.select(num_items, index)?.start),
+                        PathIndex::Range { .. } => {
+                            path_index.select(num_items, index)?;
+                            None
+                        }
+                    };
+
+                    if let Some(node_match) =
+                        item.and_then(|item| node_data.index_resolutions.get(item))
+                    {
+                        match node_match {
+                            Some(new_node_idx) => {
+                                current_idx = *new_node_idx;
+                                if !nodes_in_resolved_path.contains(&current_idx) {
+                                    nodes_in_resolved_path.push(current_idx);
                                 }
+                                node_data = &self.node_resolver_data[current_idx + 1];
 
-                                if let Some(node_match) =
-                                    node_data.index_resolutions.get(index_num - 1)
-                                {
-                                    match node_match {
-                                        Some(new_node_idx) => {
-                                            current_idx = *new_node_idx;
-                                            if !nodes_in_resolved_path.contains(&current_idx) {
-                                                nodes_in_resolved_path.push(current_idx);
-                                            }
-                                            node_data = &self.node_resolver_data[current_idx + 1];
-
-                                            // since we found a match, we continue to the next index, if it exists
-                                            continue;
-                                        }
-                                        None => {
-                                            // A value of `None` corresponds to an index matching a text node, which we cannot reference
-                                            // We add the remaining path as an unresolved path
-                                            // with name `"__invalid_index"` to make sure it won't resolve to anything.
-                                            // Note: we don't return `NoReferent`, as it is possible the index resolutions
-                                            // will later change so that this reference will begin to have a referent
-
-                                            let remaining_path: Vec<FlatPathPart> =
-                                                iter::once(FlatPathPart {
-                                                    name: "__invalid_index".into(),
-                                                    index: part
-                                                        .index
-                                                        .iter()
-                                                        .skip(index_idx)
-                                                        .cloned()
-                                                        .collect(),
-                                                    position: part.position.clone(),
-                                                    source_doc: part.source_doc,
-                                                })
-                                                .chain(path.cloned())
-                                                .collect();
-
-                                            return Ok(RefResolution {
-                                                node_idx: current_idx,
-                                                unresolved_path: Some(remaining_path),
-                                                original_path,
-                                                nodes_in_resolved_path,
-                                            });
-                                        }
-                                    }
-                                }
+                                // since we found a match, we continue to the next index, if it exists
+                                continue;
                             }
-                            Err(_) => {
-                                // the string index did not correspond to non-negative integer
-                                return Err(ResolutionError::NoReferent);
+                            None => {
+                                // A value of `None` corresponds to an index matching a text node, which we cannot reference
+                                // We add the remaining path as an unresolved path
+                                // with name `"__invalid_index"` to make sure it won't resolve to anything.
+                                // Note: we don't return `NoReferent`, as it is possible the index resolutions
+                                // will later change so that this reference will begin to have a referent
+
+                                let remaining_path: Vec<FlatPathPart> = iter::once(FlatPathPart {
+                                    name: "__invalid_index".into(),
+                                    index: part.index.iter().skip(index_idx).cloned().collect(),
+                                    position: part.position.clone(),
+                                    source_doc: part.source_doc,
+                                })
+                                .chain(path.cloned())
+                                .collect();
+
+                                return Ok(RefResolution {
+                                    node_idx: current_idx,
+                                    unresolved_path: Some(remaining_path),
+                                    original_path,
+                                    nodes_in_resolved_path,
+                                });
                             }
-                        },
-                        UntaggedContent::Ref(_) => {
-                            // if the index is a reference to another component,
-                            // then we need information that is not in the resolver to continue,
                         }
                     }
                 }
 
                 // If we make it here, then either
-                // - `index.value` was not a single text node, or
-                // - `index.value` did correspond to a valid index, but it wasn't in `self.index_resolutions` for the current node
+                // - `index.value` was not a single text node,
+                // - `index.value` did correspond to a valid index, but it wasn't in `self.index_resolutions` for the current node, or
+                // - `index.value` was a range, which `resolve_all` expands into its items
                 // We need information that is not in the resolver to continue, so we return the remaining path as unresolved.
                 let remaining_path: Vec<FlatPathPart> = iter::once(FlatPathPart {
                     name: "".into(),
@@ -453,6 +482,82 @@ impl Resolver {
         })
     }
 
+    /// Resolve `path` as in [`Resolver::resolve`], except that a range index (e.g. `$list[2:4]`)
+    /// is expanded into a separate resolution for each item in the range.
+    /// The remainder of the path after the range is resolved starting from each of the items.
+    ///
+    /// For example, given the DoenetML
+    /// ```xml
+    /// <group name="g"><point name="p"/><point name="q"/><point name="r"/></group>
+    /// ```
+    /// resolving `g[2:3]` gives resolutions to `<point name="q"/>` and `<point name="r"/>`,
+    /// and resolving `g[-2:].x` gives the same resolutions, each with `.x` as the unresolved path.
+    ///
+    /// Items of a range that are text nodes cannot be referenced, so they are skipped.
+    /// A path without a range gives a single resolution, the same as [`Resolver::resolve`].
+    pub fn resolve_all<T: AsRef<[FlatPathPart]>>(
+        &self,
+        path: T,
+        origin: Index,
+        skip_parent_search: bool,
+    ) -> Result<Vec<RefResolution>, ResolutionError> {
+        let resolution = self.resolve(path, origin, skip_parent_search)?;
+
+        // `resolve` stops at a range that it can evaluate, leaving it as the first index of the unresolved path
+        let Some((first_part, rest_of_path)) = resolution
+            .unresolved_path
+            .as_ref()
+            .and_then(|unresolved_path| unresolved_path.split_first())
+        else {
+            return Ok(vec![resolution]);
+        };
+        let Some((range_index, rest_of_indices)) = first_part.index.split_first() else {
+            return Ok(vec![resolution]);
+        };
+        let index_resolutions = &self.node_resolver_data[resolution.node_idx + 1].index_resolutions;
+        if !first_part.name.is_empty() || index_resolutions.is_empty() {
+            return Ok(vec![resolution]);
+        }
+        let Some(Some(path_index @ PathIndex::Range { .. })) =
+            PathIndex::from_flat_index(range_index)
+        else {
+            return Ok(vec![resolution]);
+        };
+        let items = path_index.select(index_resolutions.len(), range_index)?;
+
+        let remaining_path: Vec<FlatPathPart> = if rest_of_indices.is_empty() {
+            rest_of_path.to_vec()
+        } else {
+            iter::once(FlatPathPart {
+                name: "".into(),
+                index: rest_of_indices.to_vec(),
+                position: first_part.position.clone(),
+                source_doc: first_part.source_doc,
+            })
+            .chain(rest_of_path.iter().cloned())
+            .collect()
+        };
+
+        let mut resolutions = Vec::new();
+        for item_idx in index_resolutions[items].iter().flatten() {
+            for item_resolution in self.resolve_all(&remaining_path, *item_idx, true)? {
+                let mut nodes_in_resolved_path = resolution.nodes_in_resolved_path.clone();
+                for node_idx in item_resolution.nodes_in_resolved_path {
+                    if !nodes_in_resolved_path.contains(&node_idx) {
+                        nodes_in_resolved_path.push(node_idx);
+                    }
+                }
+                resolutions.push(RefResolution {
+                    node_idx: item_resolution.node_idx,
+                    nodes_in_resolved_path,
+                    unresolved_path: item_resolution.unresolved_path,
+                    original_path: resolution.original_path.clone(),
+                });
+            }
+        }
+        Ok(resolutions)
+    }
+
     /// Search up the chain of parents to find the first node that has `name` accessible.
     /// Return the referent of `name`.
     pub(super) fn search_parents(
@@ -470,13 +575,16 @@ impl Resolver {
                     Ref::Unique(idx) => {
                         return Ok(*idx);
                     }
-                    Ref::Ambiguous(_) => {
-                        return Err(ResolutionError::NonUniqueReferent);
+                    Ref::Ambiguous(indices) => {
+                        return Err(ResolutionError::NonUniqueReferent {
+                            position: None,
+                            candidates: indices.clone(),
+                        });
                     }
                 }
             }
 
-            return Err(ResolutionError::NoReferent);
+            return Err(ResolutionError::no_referent());
         }
 
         let mut child_idx = origin;
@@ -512,7 +620,10 @@ impl Resolver {
                             // ```
                             return Ok(child_idx);
                         } else {
-                            return Err(ResolutionError::NonUniqueReferent);
+                            return Err(ResolutionError::NonUniqueReferent {
+                                position: None,
+                                candidates: indices.clone(),
+                            });
                         }
                     }
                 }
@@ -524,7 +635,40 @@ impl Resolver {
                 child_idx = parent_plus_1 - 1;
             }
         }
-        Err(ResolutionError::NoReferent)
+        Err(ResolutionError::no_referent())
+    }
+
+    /// Find the names accessible from `origin` that are similar to `name_with_source_doc`,
+    /// for suggesting alternatives when `name_with_source_doc` has no referent.
+    ///
+    /// The accessible names are those in the `name_map` of `origin` and each of its ancestors
+    /// that come from the same source document.
+    fn similar_accessible_names(
+        &self,
+        name_with_source_doc: &NameWithSource,
+        origin: Index,
+    ) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut node_data_idx = Some(origin + 1);
+        while let Some(idx) = node_data_idx {
+            let node_data = &self.node_resolver_data[idx];
+            names.extend(
+                node_data
+                    .name_map
+                    .keys()
+                    .filter(|name| name.source_doc == name_with_source_doc.source_doc)
+                    .map(|name| name.name.as_str()),
+            );
+            node_data_idx = match node_data.node_parent {
+                NodeParent::None => None,
+                NodeParent::FlatRoot => Some(0),
+                NodeParent::Node(idx) => Some(idx + 1),
+            };
+            if idx == 0 {
+                break;
+            }
+        }
+        similar_names(&name_with_source_doc.name, names.into_iter())
     }
 }
 
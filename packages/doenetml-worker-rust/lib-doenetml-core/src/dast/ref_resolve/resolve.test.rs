@@ -1,6 +1,9 @@
 use super::*;
 use crate::{
-    dast::{flat_dast::FlatRoot, ref_resolve::test_helpers::*},
+    dast::{
+        flat_dast::{FlatRoot, UntaggedContent},
+        ref_resolve::test_helpers::*,
+    },
     test_utils::*,
 };
 
@@ -35,7 +38,10 @@ fn can_resolve_name_among_parents() {
 
     // Searching from `e` should fail because there are multiple `y`s that could be referred to.
     let referent = resolver.search_parents(&y_name, e_idx);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 }
 
 #[test]
@@ -75,7 +81,10 @@ fn resolve_to_parent_in_ambiguous_case() {
 
     // Searching from `e` should fail because there are multiple `x`s that could be referred to.
     let referent = resolver.search_parents(&x_name, e_idx);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 }
 
 #[test]
@@ -233,7 +242,10 @@ fn parent_name_supersedes_ambiguous_descendants() {
 
     // Searching for `y` from `f` is ambiguous
     let referent = resolver.resolve(make_path(["y"], None), f_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 
     // Searching for `y` from `b`  or `d` should find `b`
     let referent = resolver.resolve(make_path(["y"], None), b_idx, false);
@@ -259,9 +271,15 @@ fn parent_name_supersedes_ambiguous_descendants() {
 
     // Searching for `y.y` from `b`  or `d` is ambiguous
     let referent = resolver.resolve(make_path(["y", "y"], None), b_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
     let referent = resolver.resolve(make_path(["y", "y"], None), d_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 }
 
 #[test]
@@ -407,7 +425,10 @@ fn parent_name_supersedes_ambiguous_descendants_swapping_order() {
 
     // Searching for `y` from `f` is ambiguous
     let referent = resolver.resolve(make_path(["y"], None), f_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 
     // Searching for `y` from `e`  or `d` should find `e`
     let referent = resolver.resolve(make_path(["y"], None), e_idx, false);
@@ -433,9 +454,15 @@ fn parent_name_supersedes_ambiguous_descendants_swapping_order() {
 
     // Searching for `y.y` from `e`  or `d` is ambiguous
     let referent = resolver.resolve(make_path(["y", "y"], None), e_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
     let referent = resolver.resolve(make_path(["y", "y"], None), d_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 }
 
 #[test]
@@ -516,7 +543,10 @@ fn resolve_with_skip_parent_match() {
     // When `skip_parent_match` is not set,
     // searching from `x` for `y` should get an ambiguous result
     let referent = resolver.resolve(make_path(["y"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NonUniqueReferent));
+    assert!(matches!(
+        referent,
+        Err(ResolutionError::NonUniqueReferent { .. })
+    ));
 }
 
 #[test]
@@ -542,7 +572,7 @@ fn elements_with_children_invisible_to_their_grandparents() {
     // Since an `<option>` component has invisible children,
     // a search for the name `z` starting at `a_idx` fails.
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // Similarly, a search for `y.z` finds `<c>`
     let referent = resolver.resolve(make_path(["y", "z"], None), a_idx, false);
@@ -603,13 +633,13 @@ fn cannot_reference_elements_from_other_source_doc_without_parent_reference() {
 
     // Name of `<sourceSwitch>` from the second doc is not searchable starting from `a_idx`.
     let referent = resolver.resolve(make_path(["y2"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     // Since the children of `<sourceSwitch>` are from a different doc
     // a search for the names `z` or `u` starting at `a_idx` fails.
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["u"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // A search for `y.y2`,`y.y2.z`, `y.u`, and `y.z.u` succeeds as the children of `<sourceSwitch>` are visible from `s_idx`
     let referent = resolver.resolve(make_path(["y", "y2"], None), a_idx, false);
@@ -687,11 +717,11 @@ fn elements_from_other_source_doc_cannot_reference_outside_doc() {
 
     // starting at `c`, one cannot find "x", "y", or "q"
     let referent = resolver.resolve(make_path(["x"], Some(1.into())), c_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["y"], Some(1.into())), c_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["q"], Some(1.into())), c_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // Starting at `c`, one can still find "y2"
     let referent = resolver.resolve(make_path(["y2"], Some(1.into())), c_idx, false);
@@ -759,13 +789,13 @@ fn references_can_span_three_source_documents() {
 
     // Name of `<sourceSwitch>` from the second doc is not searchable starting from `a_idx`.
     let referent = resolver.resolve(make_path(["y2"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     // Since the children of `<sourceSwitch>` are from a different doc
     // a search for the names `z` or `u` starting at `a_idx` fails.
     let referent = resolver.resolve(make_path(["z"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
     let referent = resolver.resolve(make_path(["u"], None), a_idx, false);
-    assert_eq!(referent, Err(ResolutionError::NoReferent));
+    assert!(matches!(referent, Err(ResolutionError::NoReferent { .. })));
 
     // A search for `y.y2` succeeds as `y2` is the second name of `<sourceSwitch>`
     let referent = resolver.resolve(make_path(["y", "y2"], None), a_idx, false);
@@ -832,3 +862,67 @@ fn references_can_span_three_source_documents() {
         })
     );
 }
+
+#[test]
+fn no_referent_suggests_similar_accessible_names() {
+    let dast_root = dast_root_no_position(
+        r#"
+        <a name="xMax">
+            <b name="xMin" />
+        </a>
+        <option name="o">
+            <d name="xMa" />
+        </option>
+        <e />"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let e_idx = find(&flat_root, "e").unwrap();
+
+    let resolver = Resolver::from_flat_root(&flat_root);
+
+    // `xMa` is not accessible from `e`, so it is not suggested.
+    let referent = resolver.resolve(make_path(["xmx"], None), e_idx, false);
+    assert_eq!(
+        referent,
+        Err(ResolutionError::NoReferent {
+            position: None,
+            suggestions: vec!["xMax".to_string()]
+        })
+    );
+
+    let referent = resolver.resolve(make_path(["q"], None), e_idx, false);
+    assert_eq!(
+        referent,
+        Err(ResolutionError::NoReferent {
+            position: None,
+            suggestions: vec!["o".to_string()]
+        })
+    );
+}
+
+#[test]
+fn non_unique_referent_lists_candidates() {
+    let dast_root = dast_root_no_position(
+        r#"
+        <a name="x">
+            <b name="y" />
+            <c name="y" />
+        </a>
+        <d />"#,
+    );
+    let flat_root = FlatRoot::from_dast(&dast_root);
+    let b_idx = find(&flat_root, "b").unwrap();
+    let c_idx = find(&flat_root, "c").unwrap();
+    let d_idx = find(&flat_root, "d").unwrap();
+
+    let resolver = Resolver::from_flat_root(&flat_root);
+
+    for path in [make_path(["y"], None), make_path(["x", "y"], None)] {
+        let Err(ResolutionError::NonUniqueReferent { candidates, .. }) =
+            resolver.resolve(path, d_idx, false)
+        else {
+            panic!("expected a non-unique referent");
+        };
+        assert_eq!(candidates, vec![b_idx, c_idx]);
+    }
+}
@@ -0,0 +1,614 @@
+//! Serialize DAST back into DoenetML source.
+//!
+//! The output is canonical DoenetML: attributes are sorted by name, refs are written as `$a.b[2].c`
+//! (or `$(a-b.c)` when needed), function refs as `$$f(x, y)`, and text is escaped only where it would
+//! otherwise be read as markup or as a ref. Errors are not serialized, and neither are the `<_dynamicChildren>` elements
+//! that the parser adds, since parsing the output adds them again. Apart from that, parsing the output
+//! with [`parser::parse`](super::parser::parse) gives back the same DAST, up to positions
+//! and how text is split into text nodes.
+//!
+//! Whitespace is preserved unless [`SerializeOptions::pretty`] is set.
+
+use std::{collections::HashMap, fmt::Write};
+
+use super::{
+    DastAttribute, DastElement, DastElementContent, DastFunctionRef, DastIndex, DastRef, DastRoot,
+    DastText, DastTextRefElementContent, PathPart,
+    flat_dast::{
+        FlatElement, FlatPathPart, NormalizedNode, NormalizedRoot, Source, UntaggedContent,
+    },
+    ref_resolve::RefResolution,
+};
+use crate::components::_Fragment;
+
+/// The string used for one level of indentation when pretty-printing.
+const INDENT: &str = "    ";
+
+/// Options for serializing DAST into DoenetML.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Put each child of an element whose content consists only of elements on a line of its own,
+    /// indented by its depth. Whitespace between such children is replaced; all other whitespace is preserved.
+    pub pretty: bool,
+}
+
+/// Serialize `root` into DoenetML.
+pub fn serialize(root: &DastRoot, options: &SerializeOptions) -> String {
+    serialize_content(&root.children, options)
+}
+
+/// Serialize `nodes`, e.g. the children of an element, into DoenetML.
+pub fn serialize_content(nodes: &[DastElementContent], options: &SerializeOptions) -> String {
+    let mut serializer = Serializer {
+        options,
+        output: String::new(),
+    };
+    if options.pretty && is_block_content(nodes) {
+        for (idx, node) in non_whitespace_nodes(nodes).enumerate() {
+            if idx > 0 {
+                serializer.output.push('\n');
+            }
+            serializer.write_node(node, None, 0);
+        }
+    } else {
+        serializer.write_nodes(nodes, 0);
+    }
+    serializer.output
+}
+
+/// Serialize a `NormalizedRoot` into DoenetML.
+///
+/// Expanded refs are written as refs again: an element that extends a ref and has no attributes or children of
+/// its own becomes `$ref`, an `<evaluate>` created from a function ref becomes `$$f(x)`, and any other element
+/// that extends a referent gets an `extend` (or `copy`) attribute.
+pub fn serialize_normalized(root: &NormalizedRoot, options: &SerializeOptions) -> String {
+    let nodes: Vec<_> = root
+        .children
+        .iter()
+        .filter_map(|child| normalized_content_to_dast(root, child))
+        .collect();
+    serialize_content(&nodes, options)
+}
+
+struct Serializer<'a> {
+    options: &'a SerializeOptions,
+    output: String,
+}
+
+impl Serializer<'_> {
+    /// Write `nodes` as inline content, preserving whitespace. Adjacent text nodes are merged before
+    /// escaping so that escaping can take the text that follows into account.
+    fn write_nodes(&mut self, nodes: &[DastElementContent], depth: usize) {
+        let mut text = String::new();
+        for (idx, node) in nodes.iter().enumerate() {
+            match node {
+                DastElementContent::Text(DastText { value, .. }) => text.push_str(value),
+                node if is_skipped(node) => {}
+                node => {
+                    self.write_text(&text, Some(node), escape_content_text);
+                    text.clear();
+                    self.write_node(node, next_text(&nodes[idx + 1..]).as_deref(), depth);
+                }
+            }
+        }
+        self.write_text(&text, None, escape_content_text);
+    }
+
+    fn write_text(
+        &mut self,
+        text: &str,
+        next: Option<&DastElementContent>,
+        escape: fn(&str, bool) -> String,
+    ) {
+        let ref_follows = matches!(
+            next,
+            Some(DastElementContent::Ref(_) | DastElementContent::FunctionRef(_))
+        );
+        self.output.push_str(&escape(text, ref_follows));
+    }
+
+    /// Write a node that is not text. `next_text` is the text that follows the node, if any.
+    fn write_node(&mut self, node: &DastElementContent, next_text: Option<&str>, depth: usize) {
+        if is_skipped(node) {
+            return;
+        }
+        match node {
+            DastElementContent::Element(element) => self.write_element(element, depth),
+            DastElementContent::Ref(dast_ref) => self.write_ref(dast_ref, next_text),
+            DastElementContent::FunctionRef(function_ref) => {
+                self.write_function_ref(function_ref, next_text)
+            }
+            DastElementContent::Text(text) => self
+                .output
+                .push_str(&escape_content_text(&text.value, false)),
+            DastElementContent::Error(_) => {}
+        }
+    }
+
+    fn write_element(&mut self, element: &DastElement, depth: usize) {
+        write!(self.output, "<{}", element.name).unwrap();
+        self.write_attributes(&element.attributes, true);
+
+        let has_content = element.children.iter().any(|child| !is_skipped(child));
+        if !has_content {
+            self.output.push_str(" />");
+            return;
+        }
+        self.output.push('>');
+        if self.options.pretty && is_block_content(&element.children) {
+            for node in non_whitespace_nodes(&element.children) {
+                self.output.push('\n');
+                self.output.push_str(&INDENT.repeat(depth + 1));
+                self.write_node(node, None, depth + 1);
+            }
+            self.output.push('\n');
+            self.output.push_str(&INDENT.repeat(depth));
+        } else {
+            self.write_nodes(&element.children, depth);
+        }
+        write!(self.output, "</{}>", element.name).unwrap();
+    }
+
+    /// Write `attributes` sorted by name and separated by spaces. If `leading_space` is true,
+    /// the first attribute is preceded by a space as well.
+    fn write_attributes(
+        &mut self,
+        attributes: &HashMap<String, DastAttribute>,
+        leading_space: bool,
+    ) {
+        let mut attributes: Vec<_> = attributes.values().collect();
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+        for (idx, attribute) in attributes.into_iter().enumerate() {
+            if idx > 0 || leading_space {
+                self.output.push(' ');
+            }
+            self.write_attribute(attribute);
+        }
+    }
+
+    /// Write an attribute. An attribute without children is written without a value, e.g. `hide`.
+    fn write_attribute(&mut self, attribute: &DastAttribute) {
+        self.output.push_str(&attribute.name);
+        if attribute.children.is_empty() {
+            return;
+        }
+        let value = attribute_value(&attribute.children);
+        // Use the quote that needs less escaping, preferring `"`.
+        let quote = if value.matches('"').count() > value.matches('\'').count() {
+            '\''
+        } else {
+            '"'
+        };
+        let escaped_quote = if quote == '"' { "&quot;" } else { "&apos;" };
+        write!(
+            self.output,
+            "={quote}{}{quote}",
+            value.replace(quote, escaped_quote)
+        )
+        .unwrap();
+    }
+
+    fn write_ref(&mut self, dast_ref: &DastRef, next_text: Option<&str>) {
+        self.output.push('$');
+        if needs_parens(&dast_ref.path, next_text) {
+            self.output.push('(');
+            self.write_path(&dast_ref.path);
+            self.write_ref_attributes(dast_ref);
+            self.output.push(')');
+        } else {
+            self.write_path(&dast_ref.path);
+            self.write_ref_attributes(dast_ref);
+        }
+    }
+
+    fn write_ref_attributes(&mut self, dast_ref: &DastRef) {
+        if dast_ref.attributes.is_empty() {
+            return;
+        }
+        self.output.push('{');
+        self.write_attributes(&dast_ref.attributes, false);
+        self.output.push('}');
+    }
+
+    fn write_function_ref(&mut self, function_ref: &DastFunctionRef, next_text: Option<&str>) {
+        self.output.push_str("$$");
+        // The input ends the function ref, so only the text that follows a function ref without input matters.
+        let next_text = next_text.filter(|_| function_ref.input.is_none());
+        if needs_parens(&function_ref.path, next_text) {
+            self.output.push('(');
+            self.write_path(&function_ref.path);
+            self.output.push(')');
+        } else {
+            self.write_path(&function_ref.path);
+        }
+        if let Some(input) = &function_ref.input {
+            self.output.push('(');
+            for (idx, argument) in input.iter().enumerate() {
+                if idx > 0 {
+                    self.output.push_str(", ");
+                }
+                self.write_nodes(argument, 0);
+            }
+            self.output.push(')');
+        }
+    }
+
+    fn write_path(&mut self, path: &[PathPart]) {
+        for (idx, part) in path.iter().enumerate() {
+            if idx > 0 {
+                self.output.push('.');
+            }
+            self.output.push_str(&part.name);
+            for DastIndex { value, .. } in &part.index {
+                self.output.push('[');
+                let nodes: Vec<_> = value.iter().cloned().map(text_ref_to_content).collect();
+                self.write_nodes(&nodes, 0);
+                self.output.push(']');
+            }
+        }
+    }
+}
+
+/// The serialized value of an attribute, before quoting.
+fn attribute_value(children: &[DastTextRefElementContent]) -> String {
+    let nodes: Vec<_> = children.iter().cloned().map(text_ref_to_content).collect();
+    // Attribute values are never pretty-printed.
+    let options = SerializeOptions::default();
+    let mut serializer = Serializer {
+        options: &options,
+        output: String::new(),
+    };
+    let mut text = String::new();
+    for (idx, node) in nodes.iter().enumerate() {
+        match node {
+            DastElementContent::Text(DastText { value, .. }) => text.push_str(value),
+            DastElementContent::Error(_) => {}
+            node => {
+                serializer.write_text(&text, Some(node), escape_attribute_text);
+                text.clear();
+                match node {
+                    // Attribute values cannot contain markup, so elements end up as escaped text.
+                    DastElementContent::Element(element) => {
+                        let mut element_serializer = Serializer {
+                            options: serializer.options,
+                            output: String::new(),
+                        };
+                        element_serializer.write_element(element, 0);
+                        serializer
+                            .output
+                            .push_str(&escape_attribute_text(&element_serializer.output, false));
+                    }
+                    node => serializer.write_node(node, next_text(&nodes[idx + 1..]).as_deref(), 0),
+                }
+            }
+        }
+    }
+    serializer.write_text(&text, None, escape_attribute_text);
+    serializer.output
+}
+
+fn text_ref_to_content(node: DastTextRefElementContent) -> DastElementContent {
+    match node {
+        DastTextRefElementContent::Text(text) => DastElementContent::Text(text),
+        DastTextRefElementContent::Ref(dast_ref) => DastElementContent::Ref(dast_ref),
+        DastTextRefElementContent::FunctionRef(function_ref) => {
+            DastElementContent::FunctionRef(function_ref)
+        }
+        DastTextRefElementContent::Element(element) => DastElementContent::Element(element),
+    }
+}
+
+/// The text at the start of `nodes`, i.e., the merged value of the leading text nodes.
+fn next_text(nodes: &[DastElementContent]) -> Option<String> {
+    let text: String = nodes
+        .iter()
+        .map_while(|node| match node {
+            DastElementContent::Text(text) => Some(text.value.as_str()),
+            _ => None,
+        })
+        .collect();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Whether `node` is left out of the serialized DoenetML: errors and the `<_dynamicChildren>` added by the parser.
+fn is_skipped(node: &DastElementContent) -> bool {
+    match node {
+        DastElementContent::Error(_) => true,
+        DastElementContent::Element(element) => element.name == "_dynamicChildren",
+        _ => false,
+    }
+}
+
+/// Whether `nodes` consist only of elements, errors and whitespace.
+fn is_block_content(nodes: &[DastElementContent]) -> bool {
+    nodes
+        .iter()
+        .any(|node| matches!(node, DastElementContent::Element(_)) && !is_skipped(node))
+        && nodes.iter().all(|node| match node {
+            DastElementContent::Element(_) | DastElementContent::Error(_) => true,
+            DastElementContent::Text(text) => text.value.trim().is_empty(),
+            _ => false,
+        })
+}
+
+/// The nodes of block content that get a line of their own.
+fn non_whitespace_nodes(nodes: &[DastElementContent]) -> impl Iterator<Item = &DastElementContent> {
+    nodes
+        .iter()
+        .filter(|node| matches!(node, DastElementContent::Element(_)) && !is_skipped(node))
+}
+
+/// Whether a path must be wrapped in parentheses, either because one of its names cannot be written without them
+/// or because `next_text` would otherwise be read as part of the path.
+fn needs_parens(path: &[PathPart], next_text: Option<&str>) -> bool {
+    let is_simple_name = |name: &str| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if !path.iter().all(|part| is_simple_name(&part.name)) {
+        return true;
+    }
+    let mut next_chars = next_text.unwrap_or_default().chars();
+    match next_chars.next() {
+        Some(c) if c.is_ascii_alphanumeric() || matches!(c, '_' | '[' | '{') => true,
+        Some('.') => next_chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_'),
+        _ => false,
+    }
+}
+
+/// Escape text that appears as the content of an element.
+///
+/// A `<` is only escaped if it would start a tag, and a `&` is only escaped if it would start an entity.
+/// A `$` is escaped if it would start a ref; `ref_follows` tells whether a ref immediately follows the text.
+fn escape_content_text(text: &str, ref_follows: bool) -> String {
+    escape_text(text, ref_follows, |c, rest| {
+        c == '<' && !rest.starts_with(|c: char| c.is_whitespace() || c == '=')
+    })
+}
+
+/// Escape text that appears in an attribute value. Quotes are escaped when the value is quoted.
+fn escape_attribute_text(text: &str, ref_follows: bool) -> String {
+    escape_text(text, ref_follows, |c, _| c == '<')
+}
+
+fn escape_text(text: &str, ref_follows: bool, escape_lt: impl Fn(char, &str) -> bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (idx, c) in text.char_indices() {
+        let rest = &text[idx + c.len_utf8()..];
+        match c {
+            '<' if escape_lt(c, rest) => escaped.push_str("&lt;"),
+            '&' if starts_entity(rest) => escaped.push_str("&amp;"),
+            '$' if starts_ref(rest, ref_follows) => escaped.push_str("&#36;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether a `&` followed by `rest` would be read as an entity, i.e. whether a `;` follows before any whitespace.
+fn starts_entity(rest: &str) -> bool {
+    rest.split(char::is_whitespace)
+        .next()
+        .is_some_and(|word| word.contains(';'))
+}
+
+/// Whether a `$` followed by `rest` would be read as the start of a ref.
+fn starts_ref(rest: &str, ref_follows: bool) -> bool {
+    match rest.chars().next() {
+        Some(c) => c.is_ascii_alphabetic() || matches!(c, '_' | '(' | '$'),
+        None => ref_follows,
+    }
+}
+
+/// Convert a child of a `NormalizedRoot` back into DAST. Errors and `<_dynamicChildren>` elements are dropped.
+fn normalized_content_to_dast(
+    root: &NormalizedRoot,
+    content: &UntaggedContent,
+) -> Option<DastElementContent> {
+    let idx = match content {
+        UntaggedContent::Text(value) => {
+            return Some(DastElementContent::Text(DastText {
+                value: value.clone(),
+                data: None,
+                position: None,
+                source_doc: None,
+            }));
+        }
+        UntaggedContent::Ref(idx) => *idx,
+    };
+    let NormalizedNode::Element(element) = &root.nodes[idx] else {
+        return None;
+    };
+    if element.name == "_dynamicChildren" {
+        return None;
+    }
+    let children = |contents: &[UntaggedContent]| -> Vec<DastElementContent> {
+        contents
+            .iter()
+            .filter_map(|child| normalized_content_to_dast(root, child))
+            .collect()
+    };
+
+    let (attribute_name, resolution) = match &element.extending {
+        None => {
+            if let Some(resolution) = range_ref_resolution(root, element) {
+                return Some(DastElementContent::Ref(DastRef {
+                    path: flat_path_to_dast(root, &resolution.original_path),
+                    attributes: Default::default(),
+                    position: None,
+                    source_doc: None,
+                }));
+            }
+            return Some(DastElementContent::Element(normalized_element_to_dast(
+                root, element,
+            )));
+        }
+        Some(Source::Ref(resolution)) => {
+            let path = flat_path_to_dast(root, &resolution.original_path);
+            if element.attributes.is_empty() && element.children.is_empty() {
+                return Some(DastElementContent::Ref(DastRef {
+                    path,
+                    attributes: Default::default(),
+                    position: None,
+                    source_doc: None,
+                }));
+            }
+            if let Some(input) = function_ref_input(root, element) {
+                return Some(DastElementContent::FunctionRef(DastFunctionRef {
+                    path,
+                    input: Some(input.iter().map(|li| children(li)).collect()),
+                    position: None,
+                    source_doc: None,
+                }));
+            }
+            ("extend", resolution)
+        }
+        Some(Source::ExtendAttribute(resolution)) => ("extend", resolution),
+        Some(Source::CopyAttribute(resolution)) => ("copy", resolution),
+    };
+
+    let mut dast_element = normalized_element_to_dast(root, element);
+    dast_element.attributes.insert(
+        attribute_name.to_string(),
+        DastAttribute {
+            name: attribute_name.to_string(),
+            children: vec![DastTextRefElementContent::Ref(DastRef {
+                path: flat_path_to_dast(root, &resolution.original_path),
+                attributes: Default::default(),
+                position: None,
+                source_doc: None,
+            })],
+            position: None,
+            source_doc: None,
+        },
+    );
+    Some(DastElementContent::Element(dast_element))
+}
+
+/// If `element` is a `<_fragment>` created from a ref to a range of items (e.g. `$g[2:3]`),
+/// the resolution of one of the items, whose `original_path` is the path of the ref.
+fn range_ref_resolution<'a>(
+    root: &'a NormalizedRoot,
+    element: &FlatElement,
+) -> Option<&'a RefResolution> {
+    if element.name != _Fragment::NAME || !element.attributes.is_empty() {
+        return None;
+    }
+    let resolutions = element
+        .children
+        .iter()
+        .map(|child| match child {
+            UntaggedContent::Ref(idx) => match &root.nodes[*idx] {
+                NormalizedNode::Element(FlatElement {
+                    extending: Some(Source::Ref(resolution)),
+                    attributes,
+                    children,
+                    ..
+                }) if attributes.is_empty() && children.is_empty() => Some(resolution),
+                _ => None,
+            },
+            UntaggedContent::Text(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    // All of the items were created from the same ref, so they have the same `original_path`.
+    resolutions.first().copied()
+}
+
+/// If `element` is an `<evaluate>` created from a function ref, the children of each of its `<li>` elements,
+/// i.e., the arguments of the function ref.
+fn function_ref_input<'a>(
+    root: &'a NormalizedRoot,
+    element: &FlatElement,
+) -> Option<Vec<&'a [UntaggedContent]>> {
+    let as_element = |content: &UntaggedContent, name: &str| match content {
+        UntaggedContent::Ref(idx) => match &root.nodes[*idx] {
+            NormalizedNode::Element(element) if element.name == name => Some(element),
+            _ => None,
+        },
+        UntaggedContent::Text(_) => None,
+    };
+    if element.name != "evaluate" || !element.attributes.is_empty() {
+        return None;
+    }
+    let [ol] = element.children.as_slice() else {
+        return None;
+    };
+    as_element(ol, "ol")?
+        .children
+        .iter()
+        .map(|li| as_element(li, "li").map(|li| li.children.as_slice()))
+        .collect()
+}
+
+fn normalized_element_to_dast(root: &NormalizedRoot, element: &FlatElement) -> DastElement {
+    let mut dast_element = DastElement::with_name(&element.name);
+    dast_element.attributes = element
+        .attributes
+        .iter()
+        .map(|attribute| {
+            let children = attribute
+                .children
+                .iter()
+                .filter_map(|child| normalized_content_to_dast(root, child))
+                .filter_map(content_to_text_ref)
+                .collect();
+            (
+                attribute.name.clone(),
+                DastAttribute {
+                    name: attribute.name.clone(),
+                    children,
+                    position: None,
+                    source_doc: None,
+                },
+            )
+        })
+        .collect();
+    dast_element.children = element
+        .children
+        .iter()
+        .filter_map(|child| normalized_content_to_dast(root, child))
+        .collect();
+    dast_element
+}
+
+fn content_to_text_ref(content: DastElementContent) -> Option<DastTextRefElementContent> {
+    match content {
+        DastElementContent::Element(element) => Some(DastTextRefElementContent::Element(element)),
+        DastElementContent::Text(text) => Some(DastTextRefElementContent::Text(text)),
+        DastElementContent::Ref(dast_ref) => Some(DastTextRefElementContent::Ref(dast_ref)),
+        DastElementContent::FunctionRef(function_ref) => {
+            Some(DastTextRefElementContent::FunctionRef(function_ref))
+        }
+        DastElementContent::Error(_) => None,
+    }
+}
+
+fn flat_path_to_dast(root: &NormalizedRoot, path: &[FlatPathPart]) -> Vec<PathPart> {
+    path.iter()
+        .map(|part| PathPart {
+            name: part.name.clone(),
+            index: part
+                .index
+                .iter()
+                .map(|index| DastIndex {
+                    value: index
+                        .value
+                        .iter()
+                        .filter_map(|child| normalized_content_to_dast(root, child))
+                        .filter_map(content_to_text_ref)
+                        .collect(),
+                    position: None,
+                    source_doc: None,
+                })
+                .collect(),
+            position: None,
+            source_doc: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "serialize.test.rs"]
+mod test;
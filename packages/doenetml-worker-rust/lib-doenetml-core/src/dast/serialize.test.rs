@@ -0,0 +1,241 @@
+use super::*;
+use crate::{
+    dast::{
+        flat_dast::FlatRoot,
+        parser::{parse, parse_without_normalizing},
+        ref_expand::Expander,
+    },
+    test_utils::*,
+};
+
+/// Serialize the un-normalized parse of `source` without pretty-printing.
+fn reserialize(source: &str) -> String {
+    serialize(
+        &parse_without_normalizing(source),
+        &SerializeOptions::default(),
+    )
+}
+
+/// The DAST of `root` without positions and with adjacent text nodes merged, so that DAST
+/// that only differs in how text is split into nodes compares equal.
+fn comparable_dast(root: &DastRoot) -> Value {
+    fn merge_text(value: &mut Value) {
+        match value {
+            Value::Array(nodes) => {
+                let mut merged: Vec<Value> = Vec::with_capacity(nodes.len());
+                for mut node in nodes.drain(..) {
+                    merge_text(&mut node);
+                    match (merged.last_mut(), &node) {
+                        (Some(prev), node) if prev["type"] == "text" && node["type"] == "text" => {
+                            let value = prev["value"].as_str().unwrap().to_string()
+                                + node["value"].as_str().unwrap();
+                            prev["value"] = Value::String(value);
+                        }
+                        _ => merged.push(node),
+                    }
+                }
+                *nodes = merged;
+            }
+            Value::Object(map) => map.values_mut().for_each(merge_text),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(root).unwrap();
+    strip_position_fields(&mut value);
+    value.as_object_mut().unwrap().remove("sources");
+    merge_text(&mut value);
+    value
+}
+
+/// Assert that parse → serialize → parse gives back the same DAST, and that serializing again
+/// gives the same text, both with and without pretty-printing.
+fn assert_round_trips(source: &str) {
+    for options in [
+        SerializeOptions::default(),
+        SerializeOptions { pretty: true },
+    ] {
+        let parsed = parse(source);
+        let serialized = serialize(&parsed, &options);
+        let reparsed = parse(&serialized);
+        if !options.pretty {
+            assert_eq!(
+                comparable_dast(&parsed),
+                comparable_dast(&reparsed),
+                "round trip of {source:?} through {serialized:?} changed the DAST"
+            );
+        }
+        let reserialized = serialize(&reparsed, &options);
+        assert_eq!(
+            serialized, reserialized,
+            "serializing {source:?} is not idempotent"
+        );
+        assert_eq!(
+            comparable_dast(&reparsed),
+            comparable_dast(&parse(&reserialized))
+        );
+    }
+}
+
+#[test]
+fn elements_and_text_are_serialized_verbatim() {
+    let source = "<p>Hello <b>there</b>\n  <text/>  </p>";
+    assert_eq!(
+        reserialize(source),
+        "<p>Hello <b>there</b>\n  <text />  </p>"
+    );
+}
+
+#[test]
+fn attributes_are_sorted_and_quoted() {
+    assert_eq!(
+        reserialize(r#"<math simplify b='x' a="1" />"#),
+        r#"<math a="1" b="x" simplify />"#
+    );
+    assert_eq!(
+        reserialize(r#"<text a='say "hi"' b="it's" />"#),
+        r#"<text a='say "hi"' b="it's" />"#
+    );
+    assert_eq!(
+        reserialize(r#"<text a='"it&apos;s"' />"#),
+        r#"<text a='"it&apos;s"' />"#
+    );
+}
+
+#[test]
+fn refs_are_serialized() {
+    assert_eq!(reserialize("$a.b[2].c"), "$a.b[2].c");
+    assert_eq!(reserialize("$a[$n][1]"), "$a[$n][1]");
+    assert_eq!(
+        reserialize(r#"$p{simplify="full"}"#),
+        r#"$p{simplify="full"}"#
+    );
+    assert_eq!(
+        reserialize(r#"<math x="$a + $b" />"#),
+        r#"<math x="$a + $b" />"#
+    );
+}
+
+#[test]
+fn refs_get_parentheses_when_needed() {
+    assert_eq!(reserialize("$(a-b.c)"), "$(a-b.c)");
+    // Without the parentheses, the text that follows would become part of the ref.
+    assert_eq!(reserialize("$(a)bc"), "$(a)bc");
+    assert_eq!(reserialize("$(a).b"), "$(a).b");
+    assert_eq!(reserialize("$(a)[1]"), "$(a)[1]");
+    // The parentheses are not needed when the text that follows cannot continue the ref.
+    assert_eq!(reserialize("$(a). Done"), "$a. Done");
+    assert_eq!(reserialize("$(a) b"), "$a b");
+}
+
+#[test]
+fn function_refs_are_serialized() {
+    assert_eq!(reserialize("$$f(x,y)"), "$$f(x, y)");
+    assert_eq!(reserialize("$$f"), "$$f");
+    assert_eq!(reserialize("$$(f-g)((x+1))"), "$$(f-g)((x+1))");
+    assert_eq!(
+        reserialize("$$f(<math>x</math>, 2)"),
+        "$$f(<math>x</math>, 2)"
+    );
+}
+
+#[test]
+fn text_is_escaped_where_needed() {
+    assert_eq!(reserialize("a &lt; b"), "a < b");
+    assert_eq!(reserialize("a&lt;b"), "a&lt;b");
+    assert_eq!(reserialize("x <= y & z"), "x <= y & z");
+    assert_eq!(reserialize("&amp;lt;"), "&amp;lt;");
+    assert_eq!(reserialize("costs $5"), "costs $5");
+    assert_eq!(reserialize("&#36;(a)"), "&#36;(a)");
+}
+
+#[test]
+fn errors_are_not_serialized() {
+    assert_eq!(reserialize("<p>hello"), "<p>hello</p>");
+}
+
+#[test]
+fn pretty_printing_indents_block_content() {
+    let root = parse_without_normalizing(
+        "<section>  <title>Hi</title><p>Some <b>text</b></p>\n<ol><li>a</li> <li>b</li></ol></section>",
+    );
+    assert_eq!(
+        serialize(&root, &SerializeOptions { pretty: true }),
+        "<section>
+    <title>Hi</title>
+    <p>Some <b>text</b></p>
+    <ol>
+        <li>a</li>
+        <li>b</li>
+    </ol>
+</section>"
+    );
+}
+
+#[test]
+fn parse_serialize_parse_is_idempotent() {
+    let sources = [
+        "Hello there",
+        "<p>Hello <b>there</b></p>\n\n<p>Another   paragraph</p>",
+        r#"<point name="P" hide>(1, 2)</point> $P.x and $(P.coords)[1]"#,
+        r#"<section name="s"><title>Intro</title><p>$s.title is here</p></section>"#,
+        r#"<graph><point name="p" /><line through="$p (3,4)" /></graph>"#,
+        "$$f(x, <math>y^2</math>) and $$g((a, b), c)",
+        r#"$p{simplify="full" hide} $a[2][$n].b $(a-b)x"#,
+        "x &lt; y, x < y, &amp;, &copy; &#36;a $ and $$",
+        r#"<text a='He said "hi"' b="it's">'quotes' "here"</text>"#,
+        r#"<xref ref="intro" /><section xml:id="intro"><p/></section>"#,
+        "<document><p>  spaced  </p></document>",
+        "<ul><li>one</li><li>two <em>three</em></li></ul><![CDATA[<raw> & stuff]]>",
+    ];
+    for source in sources {
+        assert_round_trips(source);
+    }
+}
+
+#[test]
+fn normalized_roots_serialize_refs_as_refs() {
+    let source = r#"<point name="p" /><point extend="$p" /> $p $$p(x, 2) <text copy="$t" name="u"/><text name="t">hi</text>"#;
+    let mut flat_root = FlatRoot::from_dast(&dast_root_no_position(source));
+    Expander::expand(&mut flat_root);
+    flat_root.compactify(None);
+    let normalized_root = flat_root.into_normalized_root();
+
+    assert_eq!(
+        serialize_normalized(&normalized_root, &SerializeOptions::default()),
+        r#"<document><point name="p" /><point extend="$p" /> $p $$p(x, 2) <text copy="$t" name="u" /><text name="t">hi</text></document>"#
+    );
+}
+
+#[test]
+fn normalized_roots_serialize_range_refs_as_refs() {
+    let source = r#"<group name="g"><point name="p" /><point name="q" /></group> $g[1:2] $g[-1]"#;
+    let mut flat_root = FlatRoot::from_dast(&dast_root_no_position(source));
+    Expander::expand(&mut flat_root);
+    flat_root.compactify(None);
+    let normalized_root = flat_root.into_normalized_root();
+
+    assert_eq!(
+        serialize_normalized(&normalized_root, &SerializeOptions::default()),
+        format!("<document>{source}</document>")
+    );
+}
+
+#[test]
+fn dynamic_children_added_by_the_parser_are_not_serialized() {
+    let source = r#"<section name="s"><p>a</p></section>"#;
+    // Sections are divisions once parsed.
+    let expected = r#"<document><division name="s" type="section"><p>a</p></division></document>"#;
+    assert_eq!(
+        serialize(&parse(source), &SerializeOptions::default()),
+        expected
+    );
+
+    let mut flat_root = FlatRoot::from_dast(&dast_root_no_position(source));
+    Expander::expand(&mut flat_root);
+    flat_root.compactify(None);
+    let normalized_root = flat_root.into_normalized_root();
+    assert_eq!(
+        serialize_normalized(&normalized_root, &SerializeOptions::default()),
+        expected
+    );
+}
@@ -61,6 +61,11 @@ impl<Node: Clone + Debug, IndexLookup: Taggable<Node, usize>> DirectedGraph<Node
         &self.reverse_edges
     }
 
+    /// Returns whether `node` has been added to the graph.
+    pub fn contains_node<A: Borrow<Node>>(&self, node: A) -> bool {
+        self.index_lookup.get_tag(node.borrow()).is_some()
+    }
+
     /// Add a node to the graph.
     pub fn add_node<A: Borrow<Node>>(&mut self, node: A) -> usize {
         let node = node.borrow();
@@ -114,6 +119,89 @@ impl<Node: Clone + Debug, IndexLookup: Taggable<Node, usize>> DirectedGraph<Node
         self.reverse_edges[to_index].push(from_index);
     }
 
+    /// Remove one edge from `from` to `to`, keeping the order of the other edges of `from`.
+    /// Returns `false` if there is no such edge.
+    pub fn remove_edge<A: Borrow<Node>, B: Borrow<Node>>(&mut self, from: A, to: B) -> bool {
+        let (Some(&from_index), Some(&to_index)) = (
+            self.index_lookup.get_tag(from.borrow()),
+            self.index_lookup.get_tag(to.borrow()),
+        ) else {
+            return false;
+        };
+        let Some(position) = self.edges[from_index].iter().position(|&i| i == to_index) else {
+            return false;
+        };
+        self.edges[from_index].remove(position);
+        remove_first(&mut self.reverse_edges[to_index], from_index);
+        true
+    }
+
+    /// Remove every edge starting at `node`. The nodes themselves stay in the graph.
+    /// Does nothing if `node` is not in the graph.
+    pub fn remove_outgoing_edges<A: Borrow<Node>>(&mut self, node: A) {
+        let Some(&from_index) = self.index_lookup.get_tag(node.borrow()) else {
+            return;
+        };
+        for to_index in std::mem::take(&mut self.edges[from_index]) {
+            // There is one reverse edge for every edge, so remove only one of them
+            // in case there are several edges between the same nodes.
+            remove_first(&mut self.reverse_edges[to_index], from_index);
+        }
+    }
+
+    /// Remove every edge ending at `node`, keeping the order of the other edges of its parents.
+    /// The nodes themselves stay in the graph.
+    /// Does nothing if `node` is not in the graph.
+    pub fn remove_incoming_edges<A: Borrow<Node>>(&mut self, node: A) {
+        let Some(&to_index) = self.index_lookup.get_tag(node.borrow()) else {
+            return;
+        };
+        for from_index in std::mem::take(&mut self.reverse_edges[to_index]) {
+            remove_first(&mut self.edges[from_index], to_index);
+        }
+    }
+
+    /// Remove `node` and all edges to and from it. Returns `false` if `node` is not in the graph.
+    ///
+    /// The last node added to the graph takes the internal index of `node`,
+    /// so the order of the nodes returned by [`DirectedGraph::get_nodes`] changes.
+    /// The order of the edges of the remaining nodes is unchanged.
+    pub fn remove_node<A: Borrow<Node>>(&mut self, node: A) -> bool {
+        let node = node.borrow();
+        let Some(&index) = self.index_lookup.get_tag(node) else {
+            return false;
+        };
+        self.remove_outgoing_edges(node);
+        self.remove_incoming_edges(node);
+        self.index_lookup.remove_tag(node);
+
+        let last_index = self.nodes.len() - 1;
+        self.nodes.swap_remove(index);
+        self.edges.swap_remove(index);
+        self.reverse_edges.swap_remove(index);
+        if index == last_index {
+            return true;
+        }
+
+        // The node that was last now lives at `index`, so every reference to it needs to be updated.
+        // A self loop of the moved node shows up in both its edges and its reverse edges, so it is updated as well.
+        self.index_lookup.set_tag(self.nodes[index].clone(), index);
+        let move_index = |indices: &mut Vec<usize>| {
+            for i in indices.iter_mut().filter(|i| **i == last_index) {
+                *i = index;
+            }
+        };
+        move_index(&mut self.edges[index]);
+        move_index(&mut self.reverse_edges[index]);
+        for &parent in self.reverse_edges[index].iter() {
+            move_index(&mut self.edges[parent]);
+        }
+        for &child in self.edges[index].iter() {
+            move_index(&mut self.reverse_edges[child]);
+        }
+        true
+    }
+
     /// Returns the immediate children of `node`.
     pub fn get_children<A: Borrow<Node>>(&self, node: A) -> Vec<Node> {
         let node = node.borrow();
@@ -153,6 +241,16 @@ impl<Node: Clone + Debug, IndexLookup: Taggable<Node, usize>> DirectedGraph<Node
         }
     }
 
+    /// Returns the immediate parents of `node`.
+    pub fn get_parents<A: Borrow<Node>>(&self, node: A) -> Vec<Node> {
+        let node = node.borrow();
+        let &index = self.index_lookup.get_tag(node).unwrap();
+        self.reverse_edges[index]
+            .iter()
+            .map(|&i| self.nodes[i].clone())
+            .collect()
+    }
+
     /// Get the parent of `node` if it exists and is unique.
     pub fn get_unique_parent<A: Borrow<Node>>(&self, node: A) -> Option<Node> {
         let node = node.borrow();
@@ -189,6 +287,14 @@ impl<Node: Clone + Debug, IndexLookup: Taggable<Node, usize> + Default>
     }
 }
 
+/// Remove the first occurrence of `index` from `indices`, keeping the order of the rest.
+/// Edges are stored once per edge, so when there are several edges between the same nodes only one is removed.
+fn remove_first(indices: &mut Vec<usize>, index: usize) {
+    if let Some(position) = indices.iter().position(|&i| i == index) {
+        indices.remove(position);
+    }
+}
+
 /// `Taggable` items can have data attached to them. This provides hash-map-like functionality,
 /// but may be optimized for faster lookup.
 pub trait Taggable<Node, T> {
@@ -196,6 +302,8 @@ pub trait Taggable<Node, T> {
     fn get_tag(&self, node: &Node) -> Option<&T>;
     /// Set the index of `node` to `index`
     fn set_tag(&mut self, node: Node, tag: T);
+    /// Remove the tag of `node`, returning it if there was one
+    fn remove_tag(&mut self, node: &Node) -> Option<T>;
 }
 
 impl<Node: Clone + Eq + Hash, T> Taggable<Node, T> for HashMap<Node, T> {
@@ -206,6 +314,10 @@ impl<Node: Clone + Eq + Hash, T> Taggable<Node, T> for HashMap<Node, T> {
     fn set_tag(&mut self, node: Node, tag: T) {
         self.insert(node, tag);
     }
+
+    fn remove_tag(&mut self, node: &Node) -> Option<T> {
+        self.remove(node)
+    }
 }
 
 #[cfg(test)]
@@ -240,3 +240,162 @@ fn can_iterate_through_ancestors_topological_including_multiroot() {
     let nodes = graph.ancestors_topological_multiroot(&[d, b]).collect_vec();
     assert_eq!(nodes, vec![&d, &c, &b, &a]);
 }
+
+#[test]
+fn can_get_parents() {
+    // Set up the graph
+    // a -> c
+    // b -> c
+    let mut graph = DirectedGraph::<String, HashMap<_, _>>::new();
+    graph.add_edge("a".to_string(), "c".to_string());
+    graph.add_edge("b".to_string(), "c".to_string());
+
+    assert_eq!(graph.get_parents("c".to_string()), vec!["a", "b"]);
+    assert!(graph.get_parents("a".to_string()).is_empty());
+    assert_eq!(graph.get_unique_parent("c".to_string()), None);
+
+    assert!(graph.contains_node("a".to_string()));
+    assert!(!graph.contains_node("d".to_string()));
+}
+
+#[test]
+fn can_remove_outgoing_edges() {
+    // Set up the graph
+    // a -> b -> d
+    // a -> c
+    // c -> b
+    let (a, b, c, d) = ("a", "b", "c", "d");
+    let mut graph = DirectedGraph::<&str, HashMap<_, _>>::new();
+    graph.add_edge(a, b);
+    graph.add_edge(a, c);
+    graph.add_edge(b, d);
+    graph.add_edge(c, b);
+
+    graph.remove_outgoing_edges(a);
+    assert!(graph.get_children(a).is_empty());
+    assert_eq!(graph.get_parents(b), vec![c]);
+    assert!(graph.get_parents(c).is_empty());
+    assert_eq!(graph.get_children(b), vec![d]);
+    assert!(graph.contains_node(a));
+
+    let nodes = graph.descendants_topological_multiroot(&[c]).collect_vec();
+    assert_eq!(nodes, vec![&c, &b, &d]);
+
+    // New edges can be added after the old ones were removed
+    graph.add_edge(a, d);
+    assert_eq!(graph.get_children(a), vec![d]);
+    assert_eq!(graph.get_parents(d), vec![b, a]);
+}
+
+#[test]
+fn can_remove_edges() {
+    // Set up the graph
+    // a -> b
+    // a -> c -> d
+    // a -> d
+    // c -> d (twice)
+    let (a, b, c, d) = ("a", "b", "c", "d");
+    let mut graph = DirectedGraph::<&str, HashMap<_, _>>::new();
+    graph.add_edge(a, b);
+    graph.add_edge(a, c);
+    graph.add_edge(a, d);
+    graph.add_edge(c, d);
+    graph.add_edge(c, d);
+
+    assert!(graph.remove_edge(a, c));
+    assert_eq!(graph.get_children(a), vec![b, d]);
+    assert!(graph.get_parents(c).is_empty());
+    assert!(!graph.remove_edge(a, c));
+    assert!(!graph.remove_edge(a, "x"));
+
+    // Only one of several edges between the same nodes is removed
+    assert!(graph.remove_edge(c, d));
+    assert_eq!(graph.get_children(c), vec![d]);
+    assert_eq!(graph.get_parents(d), vec![a, c]);
+
+    graph.remove_incoming_edges(d);
+    assert_eq!(graph.get_children(a), vec![b]);
+    assert!(graph.get_children(c).is_empty());
+    assert!(graph.get_parents(d).is_empty());
+
+    let nodes = graph.descendants_topological_multiroot(&[a]).collect_vec();
+    assert_eq!(nodes, vec![&a, &b]);
+    let nodes = graph.ancestors_topological_multiroot(&[d]).collect_vec();
+    assert_eq!(nodes, vec![&d]);
+}
+
+#[test]
+fn can_remove_nodes() {
+    // Set up the graph
+    // a -> b
+    // a -> c -> e
+    // c -> d -> e
+    // b -> e
+    let (a, b, c, d, e) = ("a", "b", "c", "d", "e");
+    let mut graph = DirectedGraph::<&str, HashMap<_, _>>::new();
+    graph.add_edge(a, b);
+    graph.add_edge(a, c);
+    graph.add_edge(c, d);
+    graph.add_edge(c, e);
+    graph.add_edge(d, e);
+    graph.add_edge(b, e);
+
+    // `e` is the last node, so its index is given to `c`
+    assert!(graph.remove_node(c));
+    assert!(!graph.contains_node(c));
+    assert!(!graph.remove_node(c));
+    assert_eq!(graph.get_nodes(), &vec![a, b, e, d]);
+    assert_eq!(*graph._debug_get_index_lookup().get(&e).unwrap(), 2);
+
+    assert_eq!(graph.get_children(a), vec![b]);
+    assert!(graph.get_parents(d).is_empty());
+    assert_eq!(graph.get_parents(e), vec![d, b]);
+    assert_eq!(graph.get_children(b), vec![e]);
+    assert_eq!(graph.get_children(d), vec![e]);
+
+    let nodes = graph.descendants_topological_multiroot(&[a]).collect_vec();
+    assert_eq!(nodes, vec![&a, &b, &e]);
+    let nodes = graph
+        .descendants_reverse_topological_multiroot(&[a, d])
+        .collect_vec();
+    assert_eq!(nodes, vec![&e, &d, &b, &a]);
+    let nodes = graph.ancestors_topological_multiroot(&[e]).collect_vec();
+    assert_eq!(nodes, vec![&e, &d, &b, &a]);
+    let nodes = graph
+        .ancestors_reverse_topological_multiroot_with_skip(&[e], |&node| node == b)
+        .collect_vec();
+    assert_eq!(nodes, vec![&d, &e]);
+    let nodes = graph.descendants_quick(d).collect_vec();
+    assert_eq!(nodes, vec![&e, &d]);
+
+    // Nodes can be added again after being removed
+    graph.add_edge(a, c);
+    graph.add_edge(c, e);
+    let nodes = graph.descendants_topological_multiroot(&[a]).collect_vec();
+    assert_eq!(nodes, vec![&a, &b, &c, &e]);
+}
+
+#[test]
+fn can_remove_the_last_node_and_nodes_with_self_loops() {
+    // Set up the graph
+    // a -> b -> c
+    // c -> c
+    let (a, b, c) = ("a", "b", "c");
+    let mut graph = DirectedGraph::<&str, HashMap<_, _>>::new();
+    graph.add_edge(a, b);
+    graph.add_edge(b, c);
+    graph.add_edge(c, c);
+
+    // The self loop of `c` is kept when `c` is moved to the index of `a`
+    assert!(graph.remove_node(a));
+    assert_eq!(graph.get_nodes(), &vec![c, b]);
+    assert_eq!(graph.get_children(c), vec![c]);
+    assert_eq!(graph.get_parents(c), vec![b, c]);
+    assert_eq!(graph.get_children(b), vec![c]);
+
+    assert!(graph.remove_node(c));
+    assert_eq!(graph.get_nodes(), &vec![b]);
+    assert!(graph.get_children(b).is_empty());
+    let nodes = graph.descendants_topological_multiroot(&[b]).collect_vec();
+    assert_eq!(nodes, vec![&b]);
+}
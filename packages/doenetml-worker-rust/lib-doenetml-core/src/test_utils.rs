@@ -1,40 +1,60 @@
 //! This file contains utilities for testing DoenetMLCore. It is a duplicate of `tests/test_utils/mod.rs`, made available for internal testing purposes.
 use crate::dast::flat_dast::{FlatAttribute, FlatNode, FlatRoot, UntaggedContent};
-use crate::dast::{DastRoot, FlatDastRoot};
+use crate::dast::{DastRoot, FlatDastRoot, parser};
 use serde_json;
 pub use serde_json::{Value, json};
-use std::process::Command;
-use std::str;
 
-/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>`.
-/// This returns a JSON string of the parsed DAST. Node must be installed and properly configured.
+/// Parse the string containing DoenetML into a serde `Value` of the parsed DAST.
 ///
-/// If `strip_position` is true, the position field will be stripped from the output.
-pub fn evaluate_dast_via_node(dast: &str, strip_position: bool) -> std::io::Result<Value> {
-    evaluate_dast_via_node_to_json(dast, strip_position)
-        .map(|val| serde_json::from_str(&val).unwrap())
+/// If `strip_position` is true, the position fields will be stripped from the output.
+pub fn parse_dast_to_serde_value(source: &str, strip_position: bool) -> Value {
+    let mut value = serde_json::to_value(parser::parse(source)).unwrap();
+    if strip_position {
+        strip_position_fields(&mut value);
+    }
+    value
 }
 
-/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>`.
-/// This returns a JSON string of the parsed DAST. Node must be installed and properly configured.
+/// Remove the `position` field from every node of a serialized DAST.
+pub fn strip_position_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("position");
+            for (key, child) in map.iter_mut() {
+                match (key.as_str(), child) {
+                    // The keys of an attribute map are attribute names, which may well be `position`.
+                    ("attributes", Value::Object(attributes)) => {
+                        attributes.values_mut().for_each(strip_position_fields)
+                    }
+                    (_, child) => strip_position_fields(child),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_position_fields),
+        _ => {}
+    }
+}
+/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>` with the JavaScript parser.
+/// This returns the parsed DAST as a serde `Value`. Node must be installed and `npm run test:rust:before`
+/// must have been run to build `tests/dist/parse-dast.js`.
+///
+/// The Rust parser is used everywhere else; this is only used to check that both parsers agree.
 ///
 /// If `strip_position` is true, the position field will be stripped from the output.
-pub fn evaluate_dast_via_node_to_json(dast: &str, strip_position: bool) -> std::io::Result<String> {
-    let mut command = Command::new("node");
-    command
-        .arg("./tests/dist/parse-dast.js")
-        .arg("-i")
-        .arg(dast);
+#[allow(unused)]
+pub fn evaluate_dast_via_node(dast: &str, strip_position: bool) -> std::io::Result<Value> {
+    let script = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/dist/parse-dast.js");
+    let mut command = std::process::Command::new("node");
+    command.arg(script).arg("-i").arg(dast);
     if strip_position {
         command.arg("--strip-position");
     }
     let output = command.output()?;
 
     match output.status.success() {
-        true => Ok(str::from_utf8(&output.stdout).unwrap().to_string()),
-        false => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            str::from_utf8(&output.stderr).unwrap().to_string(),
+        true => Ok(serde_json::from_slice(&output.stdout)?),
+        false => Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
         )),
     }
 }
@@ -43,43 +63,39 @@ pub fn evaluate_dast_via_node_to_json(dast: &str, strip_position: bool) -> std::
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_no_position(str: &str) -> String {
-    evaluate_dast_via_node_to_json(str, true).unwrap()
+    parse_dast_to_serde_value(str, true).to_string()
 }
 
 /// Expand the string containing DoenetML into a JSON string of the parsed DAST
 #[allow(unused)]
 pub fn dast(str: &str) -> String {
-    evaluate_dast_via_node_to_json(str, false).unwrap()
+    parse_dast_to_serde_value(str, false).to_string()
 }
 
 /// Expand the string containing DoenetML into a serde `Value`
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_no_position_as_serde_value(str: &str) -> Value {
-    evaluate_dast_via_node(str, true).unwrap()
+    parse_dast_to_serde_value(str, true)
 }
 
 /// Expand the string containing DoenetML into a serde `Value`
 #[allow(unused)]
 pub fn dast_as_serde_value(str: &str) -> Value {
-    evaluate_dast_via_node(str, false).unwrap()
+    parse_dast_to_serde_value(str, false)
 }
 
 /// Expand the string containing DoenetML into `DastRoot`
 #[allow(unused)]
 pub fn dast_root(str: &str) -> DastRoot {
-    let root: DastRoot =
-        serde_json::from_str(&evaluate_dast_via_node_to_json(str, false).unwrap()).unwrap();
-    root
+    parser::parse(str)
 }
 
 /// Expand the string containing DoenetML into `DastRoot`
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_root_no_position(str: &str) -> DastRoot {
-    let root: DastRoot =
-        serde_json::from_str(&evaluate_dast_via_node_to_json(str, true).unwrap()).unwrap();
-    root
+    serde_json::from_value(parse_dast_to_serde_value(str, true)).unwrap()
 }
 
 pub fn to_serde_value(val: &FlatDastRoot) -> Value {
@@ -0,0 +1,136 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions, TextInputProps},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+};
+use test_utils::*;
+
+/// Return the action for typing `text` into the text input.
+fn type_text_action(component_idx: ComponentIdx, text: &str) -> Action {
+    Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    }
+}
+
+#[test]
+fn actions_can_be_undone_and_redone() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = ComponentIdx::new(1);
+
+    let initial = to_serde_value(&core.to_flat_dast().unwrap());
+
+    core.dispatch_action(type_text_action(text_input_idx, "h"))
+        .unwrap();
+    let after_first = to_serde_value(&core.to_flat_dast().unwrap());
+
+    core.dispatch_action(type_text_action(text_input_idx, "hi"))
+        .unwrap();
+    let after_second = to_serde_value(&core.to_flat_dast().unwrap());
+    assert_ne!(after_first, after_second);
+
+    // Undo returns flat dast updates for the changed text input
+    let updates = core.undo().unwrap();
+    assert!(updates.contains_key(&text_input_idx));
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), after_first);
+
+    core.undo().unwrap();
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), initial);
+
+    // Nothing left to undo
+    assert!(core.undo().unwrap().is_empty());
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), initial);
+
+    core.redo().unwrap();
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), after_first);
+    core.redo().unwrap();
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), after_second);
+    assert!(core.redo().unwrap().is_empty());
+}
+
+#[test]
+fn new_action_clears_redo() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = ComponentIdx::new(1);
+
+    core.dispatch_action(type_text_action(text_input_idx, "a"))
+        .unwrap();
+    core.undo().unwrap();
+    core.dispatch_action(type_text_action(text_input_idx, "b"))
+        .unwrap();
+    let after_b = to_serde_value(&core.to_flat_dast().unwrap());
+
+    assert!(core.redo().unwrap().is_empty());
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), after_b);
+}
+
+#[test]
+fn undo_restores_strings_changed_through_references() {
+    let dast_root =
+        dast_root_no_position(r#"<textInput>$t</textInput><text name="t">hello</text>"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = ComponentIdx::new(1);
+
+    let initial = to_serde_value(&core.to_flat_dast().unwrap());
+
+    core.dispatch_action(type_text_action(text_input_idx, "bye"))
+        .unwrap();
+    core.dispatch_action(Action {
+        component_idx: text_input_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    })
+    .unwrap();
+
+    core.undo().unwrap();
+    core.undo().unwrap();
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), initial);
+}
+
+#[test]
+fn undo_restores_default_values() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti" prefill="abc"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = core.get_component_index_by_name("ti");
+    let immediate_value_idx = TextInputProps::ImmediateValue.local_idx();
+    let immediate_value = |core: &mut TestCore| -> String {
+        core.get_prop_value_typed(text_input_idx, immediate_value_idx)
+    };
+    assert_eq!(immediate_value(&mut core), "abc");
+
+    core.core
+        .dispatch_action(type_text_action(ComponentIdx::new(text_input_idx), "x"))
+        .unwrap();
+    assert_eq!(immediate_value(&mut core), "x");
+    assert!(!core.core.get_state_snapshot().unwrap().states.is_empty());
+
+    // The state is back to its default, so it is no longer set by the user
+    core.core.undo().unwrap();
+    assert_eq!(immediate_value(&mut core), "abc");
+    assert!(core.core.get_state_snapshot().unwrap().states.is_empty());
+
+    core.core.redo().unwrap();
+    assert_eq!(immediate_value(&mut core), "x");
+    assert!(!core.core.get_state_snapshot().unwrap().states.is_empty());
+
+    core.core.undo().unwrap();
+    assert_eq!(immediate_value(&mut core), "abc");
+    assert!(core.core.get_state_snapshot().unwrap().states.is_empty());
+}
@@ -0,0 +1,230 @@
+mod test_utils;
+use doenetml_core::{
+    action_recording::{ActionRecording, RecordedEvent, ReplayError, hash_flat_dast},
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    dast::DastElementContent,
+    document_flags::DocumentFlags,
+    source_update::SourceUpdate,
+};
+use test_utils::*;
+
+const SOURCE: &str = r#"<textInput name="ti"/><p>$ti.value</p>"#;
+
+fn init_core(source: &str) -> Core {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    core.to_flat_dast().unwrap();
+    core
+}
+
+fn update_immediate_value_action(text_input_idx: usize, text: &str) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(text_input_idx),
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    }
+}
+
+fn update_value_action(text_input_idx: usize) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(text_input_idx),
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    }
+}
+
+/// Record typing `hello` into the text input of `SOURCE`, pressing enter, undoing, redoing,
+/// and then typing `bye` in a batch.
+fn record_session() -> (ActionRecording, Core) {
+    let mut core = init_core(SOURCE);
+    core.start_action_recording().unwrap();
+
+    core.dispatch_action(update_immediate_value_action(1, "hello"))
+        .unwrap();
+    core.dispatch_action(update_value_action(1)).unwrap();
+    core.undo().unwrap();
+    core.redo().unwrap();
+    core.dispatch_actions(vec![update_immediate_value_action(1, "bye")])
+        .unwrap();
+
+    (core.stop_action_recording().unwrap(), core)
+}
+
+#[test]
+fn actions_are_not_recorded_by_default() {
+    let mut core = init_core(SOURCE);
+    core.dispatch_action(update_immediate_value_action(1, "hello"))
+        .unwrap();
+
+    assert!(core.get_action_recording().is_none());
+    assert!(core.stop_action_recording().is_none());
+}
+
+#[test]
+fn recording_requires_an_initialized_core() {
+    let mut core = Core::new();
+    assert!(core.start_action_recording().is_err());
+}
+
+#[test]
+fn recording_captures_the_source_flags_and_events() {
+    let mut core = Core::new();
+    core.set_flags(DocumentFlags {
+        show_hints: false,
+        ..Default::default()
+    })
+    .unwrap();
+    core.init_from_dast_root(&dast_root_no_position(SOURCE));
+    core.start_action_recording().unwrap();
+    core.dispatch_action(update_immediate_value_action(1, "hello"))
+        .unwrap();
+    // Failed actions do not change the document, so they are not recorded.
+    assert!(
+        core.dispatch_action(update_immediate_value_action(2, "x"))
+            .is_err()
+    );
+    core.undo().unwrap();
+
+    let recording = core.get_action_recording().unwrap();
+    assert!(!recording.flags.show_hints);
+    assert_eq!(
+        serde_json::to_value(&recording.dast_root).unwrap(),
+        serde_json::to_value(dast_root_no_position(SOURCE)).unwrap()
+    );
+    assert_eq!(recording.entries.len(), 2);
+    assert_eq!(
+        recording.entries[0].event,
+        RecordedEvent::Action {
+            action: serde_json::to_value(update_immediate_value_action(1, "hello")).unwrap()
+        }
+    );
+    assert_eq!(recording.entries[1].event, RecordedEvent::Undo);
+    assert!(recording.entries[0].timestamp <= recording.entries[1].timestamp);
+
+    // The document is back to its initial state after undoing.
+    assert_eq!(recording.entries[1].flat_dast_hash, recording.initial_hash);
+    assert_ne!(recording.entries[0].flat_dast_hash, recording.initial_hash);
+}
+
+#[test]
+fn serialized_recording_replays_to_the_same_document() {
+    let (recording, mut recorded_core) = record_session();
+    assert_eq!(recording.entries.len(), 5);
+
+    let json = serde_json::to_string(&recording).unwrap();
+    let recording: ActionRecording = serde_json::from_str(&json).unwrap();
+
+    let mut core = Core::new();
+    core.replay_action_recording(&recording).unwrap();
+
+    let flat_dast = core.to_flat_dast().unwrap();
+    assert_eq!(
+        hash_flat_dast(&flat_dast),
+        hash_flat_dast(&recorded_core.to_flat_dast().unwrap())
+    );
+    assert_eq!(
+        serde_json::to_value(&flat_dast).unwrap()["elements"][1]["data"]["props"]["immediateValue"],
+        json!("bye")
+    );
+}
+
+#[test]
+fn replay_reports_the_first_step_that_differs() {
+    let (mut recording, _) = record_session();
+    recording.entries[2].flat_dast_hash = "0000000000000000".to_string();
+
+    let mut core = Core::new();
+    let err = core.replay_action_recording(&recording).unwrap_err();
+    assert!(matches!(err, ReplayError::HashMismatch { step: 3, .. }));
+}
+
+#[test]
+fn replay_reports_a_changed_source() {
+    let (mut recording, _) = record_session();
+    recording.dast_root =
+        dast_root_no_position(r#"<textInput name="ti"/><p>Changed $ti.value</p>"#);
+
+    let mut core = Core::new();
+    let err = core.replay_action_recording(&recording).unwrap_err();
+    assert!(matches!(err, ReplayError::HashMismatch { step: 0, .. }));
+}
+
+#[test]
+fn replay_rejects_unsupported_versions() {
+    let (mut recording, _) = record_session();
+    recording.version += 1;
+
+    let mut core = Core::new();
+    let err = core.replay_action_recording(&recording).unwrap_err();
+    assert!(matches!(err, ReplayError::UnsupportedVersion { .. }));
+}
+
+#[test]
+fn source_edits_generated_children_and_snapshots_are_replayed() {
+    let mut core = init_core(SOURCE);
+    core.start_action_recording().unwrap();
+
+    core.dispatch_action(update_immediate_value_action(1, "hello"))
+        .unwrap();
+    core.dispatch_action(update_value_action(1)).unwrap();
+    let snapshot = core.get_state_snapshot().unwrap();
+
+    // The first edit is applied incrementally. The second makes the unresolved `$y` resolve,
+    // so `Core` starts over from the edited source.
+    let update = core
+        .update_from_dast_root(dast_root_no_position(
+            r#"<textInput name="ti"/><p>Edited $ti.value</p><text extend="$y"/>"#,
+        ))
+        .unwrap();
+    assert!(matches!(update, SourceUpdate::Incremental(_)));
+    let update = core
+        .update_from_dast_root(dast_root_no_position(
+            r#"<textInput name="ti"/><p>Edited $ti.value</p><text extend="$y"/><text name="y">y</text>"#,
+        ))
+        .unwrap();
+    assert!(matches!(update, SourceUpdate::Full(_)));
+
+    let generated = match &dast_root_no_position("<text>generated</text>").children[0] {
+        DastElementContent::Element(document) => document.children.clone(),
+        _ => panic!("Expected a document element"),
+    };
+    core.replace_generated_children(ComponentIdx::new(0), &generated)
+        .unwrap();
+
+    core.dispatch_action(update_immediate_value_action(1, "bye"))
+        .unwrap();
+    core.apply_state_snapshot(&snapshot).unwrap();
+
+    let recording = core.stop_action_recording().unwrap();
+    assert_eq!(recording.entries.len(), 7);
+    assert!(matches!(
+        recording.entries[2].event,
+        RecordedEvent::UpdateSource { .. }
+    ));
+    assert!(matches!(
+        recording.entries[4].event,
+        RecordedEvent::ReplaceGeneratedChildren { .. }
+    ));
+    assert_eq!(
+        recording.entries[6].event,
+        RecordedEvent::ApplyStateSnapshot {
+            snapshot: snapshot.clone()
+        }
+    );
+
+    let json = serde_json::to_string(&recording).unwrap();
+    let recording: ActionRecording = serde_json::from_str(&json).unwrap();
+    let mut replayed_core = Core::new();
+    replayed_core.replay_action_recording(&recording).unwrap();
+    assert_eq!(
+        hash_flat_dast(&replayed_core.to_flat_dast().unwrap()),
+        hash_flat_dast(&core.to_flat_dast().unwrap())
+    );
+}
@@ -122,7 +122,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: VALUE_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         (value).clone().try_into().unwrap()
     }
@@ -133,7 +133,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: BOOLEAN_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         (value).clone().try_into().unwrap()
     }
@@ -144,7 +144,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: TEXT_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         let rc_value: Rc<String> = (value).clone().try_into().unwrap();
         (*rc_value).clone()
@@ -156,7 +156,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: HIDDEN_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         (value).clone().try_into().unwrap()
     }
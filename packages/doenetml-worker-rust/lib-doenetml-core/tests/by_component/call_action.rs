@@ -0,0 +1,82 @@
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::{call_action::CallActionActions, text_input::TextInputProps},
+        types::Action,
+    },
+    error::CoreError,
+};
+
+use super::*;
+
+fn call_action_action(component_idx: usize) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(component_idx),
+        action: ActionsEnum::CallAction(CallActionActions::CallAction),
+    }
+}
+
+#[test]
+fn call_action_calls_the_action_of_its_target() {
+    let dast_root = dast_root_no_position(
+        r#"<textInput name="ti"/><updateValue name="uv" target="$ti" type="text" newValue="hello"/><callAction name="ca" target="$uv" actionName="updateValue"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = core.get_component_index_by_name("ti");
+    let call_action_idx = core.get_component_index_by_name("ca");
+
+    core.dispatch_action(call_action_action(call_action_idx))
+        .unwrap();
+
+    let value: String =
+        core.get_prop_value_typed(text_input_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "hello");
+}
+
+#[test]
+fn call_action_errors_on_unknown_actions() {
+    let dast_root = dast_root_no_position(
+        r#"<textInput name="ti"/><callAction name="ca" target="$ti" actionName="explode"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let call_action_idx = core.get_component_index_by_name("ca");
+
+    assert!(matches!(
+        core.dispatch_action(call_action_action(call_action_idx)),
+        Err(CoreError::InvalidAction(_))
+    ));
+}
+
+#[test]
+fn actions_that_trigger_themselves_are_rejected() {
+    let dast_root = dast_root_no_position(
+        r#"<callAction name="ca1" target="$ca2" actionName="callAction"/><callAction name="ca2" target="$ca1" actionName="callAction"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let call_action_idx = core.get_component_index_by_name("ca1");
+
+    assert_eq!(
+        core.dispatch_action(call_action_action(call_action_idx))
+            .unwrap_err(),
+        CoreError::ActionLoop(call_action_idx)
+    );
+}
+
+#[test]
+fn actions_are_tagged_with_the_component_type_as_written_in_doenetml() {
+    let action = ActionsEnum::new_without_args("callAction", "callAction").unwrap();
+    assert!(matches!(
+        action,
+        ActionsEnum::CallAction(CallActionActions::CallAction)
+    ));
+    assert_eq!(
+        serde_json::to_value(&action).unwrap(),
+        json!({ "component": "callAction", "actionName": "callAction" })
+    );
+}
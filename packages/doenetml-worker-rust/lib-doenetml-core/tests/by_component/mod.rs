@@ -1,4 +1,5 @@
 mod boolean;
+mod call_action;
 mod division;
 mod graph;
 mod math;
@@ -8,7 +9,9 @@ mod point;
 mod section;
 mod text;
 mod text_input;
+mod trigger_set;
 mod ul;
+mod update_value;
 mod xref;
 
 use super::*;
@@ -27,7 +27,7 @@ fn p_rendered_children() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     let p_children = &flat_dast.elements[p_idx.as_usize()].children;
 
@@ -64,7 +64,7 @@ fn p_hidden_children_not_rendered() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     let p_children = &flat_dast.elements[p_idx.as_usize()].children;
 
@@ -94,7 +94,7 @@ fn ps_extending_ps_concatenate_children() {
     let p2_idx = 2;
     let p3_idx = 3;
 
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     // p1 has one string child "one"
     let p1 = &flat_dast.elements[p1_idx];
@@ -125,7 +125,7 @@ fn p_extending_text() {
     // indices start at 1, as the document tag will be index 0.
     let p_idx = 2;
 
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     // p has two text/string child "one" and "two"
     let p = &flat_dast.elements[p_idx];
@@ -147,7 +147,7 @@ fn p_extending_text_value() {
     // indices start at 1, as the document tag will be index 0.
     let p_idx = 2;
 
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     // p has two text/string child "one" and "two"
     let p = &flat_dast.elements[p_idx];
@@ -171,7 +171,7 @@ fn p_extending_section() {
     // the section's normalization-added `<_dynamicChildren>` is index 2
     let p_idx = 3;
 
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     // p has two text/string child "one" and "two"
     let p = &flat_dast.elements[p_idx];
@@ -208,7 +208,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: rendered_children_local_idx,
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::AnnotatedContentRefs> = prop.into_prop_view();
 
         (*prop_view.value)
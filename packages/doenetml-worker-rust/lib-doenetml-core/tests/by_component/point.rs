@@ -6,7 +6,9 @@ use doenetml_core::{
         doenet::point::{PointActions, PointMoveActionArgs, PointProps},
         types::{Action, ActionBody},
     },
-    dast::{ForRenderPropValue, ForRenderPropValueOrContent, ForRenderProps},
+    dast::{
+        FlatDastElementContent, ForRenderPropValue, ForRenderPropValueOrContent, ForRenderProps,
+    },
     state::types::math_expr::{JsMathExpr, MathExpr},
 };
 
@@ -391,3 +393,203 @@ fn rendered_props_with_references() {
         }
     ));
 }
+
+#[test]
+fn can_reference_elements_of_coords() {
+    use doenetml_core::components::doenet::math::MathProps;
+    let math_value_local_idx = MathProps::Value.local_idx();
+
+    let dast_root = dast_root_no_position(
+        r#"<number name="x">3</number><number name="y">-1</number><point name="P" x="$x" y="$y"/><math name="m1" extend="$P.coords[1]"/><math name="m2" extend="$P.coords[2]"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+
+    let point_idx = core.get_component_index_by_name("P");
+    let m1_idx = core.get_component_index_by_name("m1");
+    let m2_idx = core.get_component_index_by_name("m2");
+
+    let math_x: MathExpr = 3.0.into();
+    let math_y: MathExpr = (-1.0).into();
+    assert_eq!(
+        core.get_prop_value(m1_idx, math_value_local_idx),
+        math_x.into()
+    );
+    assert_eq!(
+        core.get_prop_value(m2_idx, math_value_local_idx),
+        math_y.clone().into()
+    );
+
+    // Each element tracks its coordinate of the point
+    let move_action = Action {
+        component_idx: point_idx.into(),
+        action: ActionsEnum::Point(PointActions::Move(ActionBody {
+            args: PointMoveActionArgs { x: 5.0, y: -1.0 },
+        })),
+    };
+    core.dispatch_action(move_action).unwrap();
+
+    let math_x: MathExpr = 5.0.into();
+    assert_eq!(
+        core.get_prop_value(m1_idx, math_value_local_idx),
+        math_x.into()
+    );
+    assert_eq!(
+        core.get_prop_value(m2_idx, math_value_local_idx),
+        math_y.into()
+    );
+}
+
+#[test]
+fn changing_a_reference_to_an_element_of_coords_changes_the_point() {
+    use doenetml_core::components::{
+        doenet::{math::MathProps, update_value::UpdateValueActions},
+        types::ComponentIdx,
+    };
+    let math_value_local_idx = MathProps::Value.local_idx();
+
+    let dast_root = dast_root_no_position(
+        r#"<number name="x">3</number><number name="y">-1</number><point name="P" x="$x" y="$y"/><math name="m1" extend="$P.coords[1]"/><number name="n">7</number><updateValue name="uv" target="$m1" type="math" newValue="$n"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+
+    let point_idx = core.get_component_index_by_name("P");
+    let m1_idx = core.get_component_index_by_name("m1");
+    let update_value_idx = core.get_component_index_by_name("uv");
+
+    // The first element of `coords` is an alias of `x`, so the change is inverted through `x` to `$x`
+    core.dispatch_action(Action {
+        component_idx: ComponentIdx::new(update_value_idx),
+        action: ActionsEnum::UpdateValue(UpdateValueActions::UpdateValue),
+    })
+    .unwrap();
+
+    let math_x: MathExpr = 7.0.into();
+    let math_y: MathExpr = (-1.0).into();
+    assert_eq!(
+        core.get_prop_value(m1_idx, math_value_local_idx),
+        math_x.clone().into()
+    );
+    assert_eq!(
+        core.get_prop_value(point_idx, X_LOCAL_IDX),
+        math_x.clone().into()
+    );
+    assert_eq!(
+        core.get_prop_value(point_idx, Y_LOCAL_IDX),
+        math_y.clone().into()
+    );
+    assert_eq!(
+        core.get_prop_value(point_idx, COORDS_LOCAL_IDX),
+        MathExpr::new_vector(&[math_x, math_y]).into()
+    );
+}
+
+#[test]
+fn can_reference_coords_from_the_end_and_ranges_of_coords() {
+    use doenetml_core::components::doenet::math::MathProps;
+    let math_value_local_idx = MathProps::Value.local_idx();
+
+    let dast_root = dast_root_no_position(
+        r#"<number name="x">3</number><number name="y">-1</number><point name="P" x="$x" y="$y"/><math name="m1" extend="$P.coords[-1]"/><math name="m2" extend="$P.coords[1:2]"/><p>$P.coords[-2]</p>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+
+    let point_idx = core.get_component_index_by_name("P");
+    let m1_idx = core.get_component_index_by_name("m1");
+    let m2_idx = core.get_component_index_by_name("m2");
+
+    let math_x: MathExpr = 3.0.into();
+    let math_y: MathExpr = (-1.0).into();
+    assert_eq!(
+        core.get_prop_value(m1_idx, math_value_local_idx),
+        math_y.clone().into()
+    );
+    assert_eq!(
+        core.get_prop_value(m2_idx, math_value_local_idx),
+        MathExpr::new_vector(&[math_x, math_y]).into()
+    );
+
+    // A direct ref to an element counted from the end becomes a math, too
+    let flat_dast = core.to_flat_dast();
+    let p_idx = flat_dast
+        .elements
+        .iter()
+        .position(|element| element.name == "p")
+        .unwrap();
+    let ref_idx = match flat_dast.elements[p_idx].children[..] {
+        [FlatDastElementContent::Element(idx)] => idx,
+        _ => panic!("Expected a single element child"),
+    };
+    assert_eq!(flat_dast.elements[ref_idx.id].name, "math");
+
+    let move_action = Action {
+        component_idx: point_idx.into(),
+        action: ActionsEnum::Point(PointActions::Move(ActionBody {
+            args: PointMoveActionArgs { x: 5.0, y: 2.0 },
+        })),
+    };
+    core.dispatch_action(move_action).unwrap();
+
+    let math_x: MathExpr = 5.0.into();
+    let math_y: MathExpr = 2.0.into();
+    assert_eq!(
+        core.get_prop_value(m1_idx, math_value_local_idx),
+        math_y.clone().into()
+    );
+    assert_eq!(
+        core.get_prop_value(m2_idx, math_value_local_idx),
+        MathExpr::new_vector(&[math_x, math_y]).into()
+    );
+}
+
+#[test]
+fn referencing_an_element_of_coords_directly_creates_a_math() {
+    let dast_root = dast_root_no_position(r#"<point name="P" x="3" y="-1"/><p>$P.coords[2]</p>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+
+    let flat_dast = core.to_flat_dast();
+    let p_idx = 2;
+    let ref_idx = match flat_dast.elements[p_idx].children[..] {
+        [FlatDastElementContent::Element(idx)] => idx,
+        _ => panic!("Expected a single element child"),
+    };
+    assert_eq!(flat_dast.elements[ref_idx.id].name, "math");
+}
+
+#[test]
+fn invalid_indices_of_coords_are_errors() {
+    let dast_root = dast_root_no_position(
+        r#"<point name="P"/><math extend="$P.coords[0]"/><math extend="$P.x[1]"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+
+    let flat_dast = core.to_flat_dast();
+    for idx in [2, 3] {
+        assert_eq!(flat_dast.elements[idx].name, "_error");
+    }
+    assert!(
+        flat_dast.elements[2]
+            .data
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("nonzero integers")
+    );
+    assert!(
+        flat_dast.elements[3]
+            .data
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("not an array")
+    );
+}
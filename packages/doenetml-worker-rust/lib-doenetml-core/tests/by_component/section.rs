@@ -25,7 +25,7 @@ fn section_content_excludes_title_tag() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let section_children = &flat_dast.elements[section_idx.as_usize()].children;
 
     assert_eq!(
@@ -56,7 +56,7 @@ fn section_handles_missing_title_tag() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let section_children = &flat_dast.elements[section_idx.as_usize()].children;
 
     assert_eq!(
@@ -101,7 +101,7 @@ fn section_finds_title_tag_in_middle() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let section_children = &flat_dast.elements[section_idx.as_usize()].children;
 
     assert_eq!(
@@ -155,7 +155,7 @@ fn section_with_multiple_title_tags_picks_last() {
     );
 
     // check the flat dast
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let section_children = &flat_dast.elements[section_idx.as_usize()].children;
 
     assert_eq!(
@@ -280,7 +280,7 @@ fn section_title_reference_works() {
     core.init_from_dast_root(&dast_root);
 
     // just to make sure this doesn't error
-    core.to_flat_dast();
+    core.to_flat_dast().unwrap();
 
     let fragment_idx = 5.into();
 
@@ -341,7 +341,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: TITLE_LOCAL_IDX,
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::ComponentRef> = prop.into_prop_view();
 
         prop_view.value.map(|v| v.0)
@@ -353,7 +353,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: RENDERED_CHILDREN_LOCAL_IDX,
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::AnnotatedContentRefs> = prop.into_prop_view();
 
         (*prop_view.value)
@@ -373,7 +373,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: DivisionProps::SerialNumber.local_idx(),
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::Integer> = prop.into_prop_view();
 
         prop_view.value
@@ -385,7 +385,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: DivisionProps::CodeNumber.local_idx(),
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::String> = prop.into_prop_view();
 
         prop_view.value
@@ -400,7 +400,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: DivisionProps::DivisionDepth.local_idx(),
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::Integer> = prop.into_prop_view();
 
         prop_view.value
@@ -415,7 +415,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: _FragmentProps::RenderedChildren.local_idx(),
         });
-        let prop = core.get_prop_for_render_untracked(prop_node);
+        let prop = core.get_prop_for_render_untracked(prop_node).unwrap();
         let prop_view: PropView<prop_type::AnnotatedContentRefs> = prop.into_prop_view();
 
         (*prop_view.value)
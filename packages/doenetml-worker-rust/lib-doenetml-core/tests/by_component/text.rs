@@ -21,7 +21,7 @@ fn value_prop_from_string_child() {
     assert_eq!(get_value_prop(text_idx, &mut core), "hello");
 
     // verify that value is the rendered prop
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let text_rendered_props = flat_dast.elements[text_idx.as_usize()]
         .data
         .props
@@ -37,7 +37,7 @@ fn value_prop_from_string_child() {
 
     // calling `to_flat_dast` a second time still includes the value prop
     // even though it didn't change since last to call to `to_flat_dast`
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let text_rendered_props = flat_dast.elements[text_idx.as_usize()]
         .data
         .props
@@ -62,7 +62,7 @@ fn text_has_no_rendered_children() {
     // the text will be index 1, as the document tag will be index 0.
     let text_idx = 1;
 
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
 
     let text_children = &flat_dast.elements[text_idx].children;
     assert_eq!(*text_children, vec![]);
@@ -84,7 +84,7 @@ fn hidden_or_unrendered_text_dont_calculate_render_props() {
     assert_eq!(get_value_prop(text1_idx, &mut core), "hello there");
 
     // verify that value is the rendered prop
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let text_rendered_props = flat_dast.elements[text1_idx.as_usize()]
         .data
         .props
@@ -101,14 +101,14 @@ fn hidden_or_unrendered_text_dont_calculate_render_props() {
     assert_eq!(get_value_prop(text2_idx, &mut core), "there");
 
     // verify that have no rendered prop
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let text_rendered_props = flat_dast.elements[text2_idx.as_usize()].data.props.as_ref();
     assert!(text_rendered_props.is_none());
 
     assert_eq!(get_value_prop(text3_idx, &mut core), "secret");
 
     // verify that have no rendered prop
-    let flat_dast = core.to_flat_dast();
+    let flat_dast = core.to_flat_dast().unwrap();
     let text_rendered_props = flat_dast.elements[text3_idx.as_usize()].data.props.as_ref();
     assert!(text_rendered_props.is_none());
 }
@@ -237,7 +237,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: VALUE_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         let rc_value: Rc<String> = value.try_into().unwrap();
         (*rc_value).clone()
@@ -249,7 +249,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: TEXT_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         let rc_value: Rc<String> = value.try_into().unwrap();
         (*rc_value).clone()
@@ -261,7 +261,7 @@ mod test_helpers {
             component_idx,
             local_prop_idx: HIDDEN_LOCAL_IDX,
         });
-        let value = core.get_prop_for_render_untracked(prop_node).value;
+        let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
         (value).clone().try_into().unwrap()
     }
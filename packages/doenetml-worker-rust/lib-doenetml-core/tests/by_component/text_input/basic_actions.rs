@@ -310,7 +310,7 @@ fn flat_dast_updates_from_actions() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let _ = core.to_flat_dast();
+    let _ = core.to_flat_dast().unwrap();
 
     // the text input will be index 1, as the document tag will be index 0.
     let text_input_idx = ComponentIdx::new(1);
@@ -325,7 +325,10 @@ fn flat_dast_updates_from_actions() {
     let _ = core.dispatch_action(type_word_action).unwrap();
 
     let type_word_action = update_immediate_value_action(String::from("hello"), text_input_idx);
-    let flat_dast_update = core.dispatch_action(type_word_action).unwrap();
+    let flat_dast_update = core
+        .dispatch_action(type_word_action)
+        .unwrap()
+        .flat_dast_updates;
 
     assert_eq!(flat_dast_update.keys().len(), 1);
 
@@ -348,7 +351,10 @@ fn flat_dast_updates_from_actions() {
     // should update value to match immediate value
     let press_enter_action = update_value_action(text_input_idx);
 
-    let flat_dast_update = core.dispatch_action(press_enter_action).unwrap();
+    let flat_dast_update = core
+        .dispatch_action(press_enter_action)
+        .unwrap()
+        .flat_dast_updates;
     println!("flat dast updates 2: {:?}", flat_dast_update);
 
     assert_eq!(flat_dast_update.keys().len(), 2);
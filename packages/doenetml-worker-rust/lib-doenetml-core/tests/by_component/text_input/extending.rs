@@ -18,7 +18,8 @@ fn text_input_extending_text_input() {
     // confirm that the extending component is still a text input
     assert_eq!(
         core.document_model
-            .get_component_type(GraphNode::Component(extending_idx.as_usize())),
+            .get_component_type(GraphNode::Component(extending_idx.as_usize()))
+            .unwrap(),
         "textInput"
     );
 
@@ -68,7 +69,8 @@ fn references_to_value_and_immediate_value_respond_to_actions() {
     // confirm that the extending component is still a text
     assert_eq!(
         core.document_model
-            .get_component_type(GraphNode::Component(extending_text_idx.as_usize())),
+            .get_component_type(GraphNode::Component(extending_text_idx.as_usize()))
+            .unwrap(),
         "text"
     );
 
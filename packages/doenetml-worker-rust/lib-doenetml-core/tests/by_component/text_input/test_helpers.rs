@@ -22,7 +22,7 @@ pub fn get_immediate_value(component_idx: ComponentIdx, core: &mut Core) -> Stri
         component_idx,
         local_prop_idx: IMMEDIATE_VALUE_IDX,
     });
-    let value = core.get_prop_for_render_untracked(prop_node).value;
+    let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
     (value).clone().try_into().unwrap()
 }
@@ -33,7 +33,7 @@ pub fn get_value(component_idx: ComponentIdx, core: &mut Core) -> String {
         component_idx,
         local_prop_idx: VALUE_IDX,
     });
-    let value = core.get_prop_for_render_untracked(prop_node).value;
+    let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
     (value).clone().try_into().unwrap()
 }
@@ -62,7 +62,7 @@ pub fn get_text_value(component_idx: ComponentIdx, core: &mut Core) -> String {
         component_idx,
         local_prop_idx: TEXT_VALUE_IDX,
     });
-    let value = core.get_prop_for_render_untracked(prop_node).value;
+    let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
     (value).clone().try_into().unwrap()
 }
@@ -73,7 +73,7 @@ pub fn get_boolean_value(component_idx: ComponentIdx, core: &mut Core) -> bool {
         component_idx,
         local_prop_idx: BOOLEAN_VALUE_IDX,
     });
-    let value = core.get_prop_for_render_untracked(prop_node).value;
+    let value = core.get_prop_for_render_untracked(prop_node).unwrap().value;
 
     (value).clone().try_into().unwrap()
 }
@@ -149,14 +149,17 @@ fn references_to_value_and_immediate_value_respond_to_actions() {
 
     // confirm that the references were expanded into texts
     assert_eq!(
-        core.document_model.get_component_type(GraphNode::Component(
-            immediate_value_reference_idx.as_usize()
-        )),
+        core.document_model
+            .get_component_type(GraphNode::Component(
+                immediate_value_reference_idx.as_usize()
+            ))
+            .unwrap(),
         "text"
     );
     assert_eq!(
         core.document_model
-            .get_component_type(GraphNode::Component(value_reference_idx.as_usize())),
+            .get_component_type(GraphNode::Component(value_reference_idx.as_usize()))
+            .unwrap(),
         "text"
     );
 
@@ -211,7 +214,8 @@ fn plain_reference_to_text_input_expands_into_text() {
     // confirm that the reference was expanded into text
     assert_eq!(
         core.document_model
-            .get_component_type(GraphNode::Component(reference_idx.as_usize())),
+            .get_component_type(GraphNode::Component(reference_idx.as_usize()))
+            .unwrap(),
         "text"
     );
 
@@ -0,0 +1,39 @@
+use doenetml_core::components::{
+    ActionsEnum,
+    doenet::{text::TextProps, trigger_set::TriggerSetActions},
+    types::Action,
+};
+
+use super::*;
+
+#[test]
+fn trigger_set_runs_the_actions_of_its_children_together() {
+    let dast_root = dast_root_no_position(
+        r#"<text name="a">1</text><text name="b">2</text>
+        <updateValue name="swap" target="$b" type="text" newValue="$a"/>
+        <triggerSet name="ts">
+            <updateValue target="$a" type="text" newValue="$b"/>
+            <callAction target="$swap" actionName="updateValue"/>
+        </triggerSet>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let trigger_set_idx = core.get_component_index_by_name("ts");
+
+    core.dispatch_action(Action {
+        component_idx: ComponentIdx::new(trigger_set_idx),
+        action: ActionsEnum::TriggerSet(TriggerSetActions::TriggerActions),
+    })
+    .unwrap();
+
+    // The new values are calculated before any of them are changed, so the values are swapped.
+    let get_value = |core: &mut TestCore, name: &str| -> String {
+        core.get_prop_value_typed(
+            core.get_component_index_by_name(name),
+            TextProps::Value.local_idx(),
+        )
+    };
+    assert_eq!(get_value(&mut core, "a"), "2");
+    assert_eq!(get_value(&mut core, "b"), "1");
+}
@@ -0,0 +1,101 @@
+use doenetml_core::components::{
+    ActionsEnum,
+    doenet::{
+        text::TextProps,
+        text_input::TextInputProps,
+        update_value::{UpdateValueActions, UpdateValueProps},
+    },
+    types::Action,
+};
+
+use super::*;
+
+fn update_value_action(component_idx: usize) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(component_idx),
+        action: ActionsEnum::UpdateValue(UpdateValueActions::UpdateValue),
+    }
+}
+
+#[test]
+fn update_value_changes_the_value_of_its_target() {
+    let dast_root = dast_root_no_position(
+        r#"<textInput name="ti"/><updateValue name="uv" target="$ti" type="text" newValue="hello">Say hello</updateValue>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = core.get_component_index_by_name("ti");
+    let update_value_idx = core.get_component_index_by_name("uv");
+
+    let label: String =
+        core.get_prop_value_typed(update_value_idx, UpdateValueProps::Label.local_idx());
+    assert_eq!(label, "Say hello");
+
+    core.dispatch_action(update_value_action(update_value_idx))
+        .unwrap();
+
+    let value: String =
+        core.get_prop_value_typed(text_input_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "hello");
+}
+
+#[test]
+fn update_value_can_change_a_referenced_text() {
+    let dast_root = dast_root_no_position(
+        r#"<text name="t">hi</text><text name="t2">$t</text><updateValue name="uv" target="$t" type="text" newValue="bye"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let update_value_idx = core.get_component_index_by_name("uv");
+
+    core.dispatch_action(update_value_action(update_value_idx))
+        .unwrap();
+
+    for name in ["t", "t2"] {
+        let value: String = core.get_prop_value_typed(
+            core.get_component_index_by_name(name),
+            TextProps::Value.local_idx(),
+        );
+        assert_eq!(value, "bye");
+    }
+}
+
+#[test]
+fn update_value_does_nothing_without_a_target_or_when_disabled() {
+    let dast_root = dast_root_no_position(
+        r#"<textInput name="ti"/><updateValue name="uv1" type="text" newValue="a"/><updateValue name="uv2" target="$ti" type="text" newValue="b" disabled/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let text_input_idx = core.get_component_index_by_name("ti");
+
+    for name in ["uv1", "uv2"] {
+        let result = core
+            .dispatch_action(update_value_action(core.get_component_index_by_name(name)))
+            .unwrap();
+        assert!(result.prop_outcomes.is_empty());
+    }
+
+    let value: String =
+        core.get_prop_value_typed(text_input_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "");
+}
+
+#[test]
+fn update_value_errors_when_the_target_has_no_value_of_its_type() {
+    let dast_root = dast_root_no_position(
+        r#"<p name="p">hi</p><updateValue name="uv" target="$p" type="text" newValue="bye"/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let update_value_idx = core.get_component_index_by_name("uv");
+
+    assert!(
+        core.dispatch_action(update_value_action(update_value_idx))
+            .is_err()
+    );
+}
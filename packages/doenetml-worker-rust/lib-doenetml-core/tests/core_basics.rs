@@ -1,6 +1,13 @@
 mod test_utils;
 use assert_json_diff::assert_json_eq;
-use doenetml_core::core::core::Core;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+};
 use test_utils::*;
 
 #[test]
@@ -10,7 +17,7 @@ fn basic_core_initialization() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let result = core.to_flat_dast();
+    let result = core.to_flat_dast().unwrap();
 
     let processed_string = to_serde_value(&result);
     assert_json_eq!(
@@ -88,3 +95,138 @@ fn test_can_change_source_on_an_initialized_core() {
     assert_eq!(core.get_component_index_by_name("t2"), 2);
     assert_eq!(core.get_component_index_by_name("t3"), 3);
 }
+
+#[test]
+fn unrecognized_attributes_are_passed_through_with_refs_evaluated() {
+    let dast_root = dast_root_no_position(
+        r#"<text name="t">wide</text><p class="box $t" data-id="p1">hi</p><_div style="$t.value" empty/>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let flat_dast = to_serde_value(&core.to_flat_dast());
+
+    let p_idx = core.get_component_index_by_name("t") + 1;
+    assert_json_eq!(
+        flat_dast["elements"][p_idx]["attributes"],
+        json!({
+            "class": {
+                "type": "attribute",
+                "name": "class",
+                "children": [{ "type": "text", "value": "box wide" }]
+            },
+            "data-id": {
+                "type": "attribute",
+                "name": "data-id",
+                "children": [{ "type": "text", "value": "p1" }]
+            }
+        })
+    );
+
+    let div = flat_dast["elements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|element| element["name"] == "_div")
+        .unwrap();
+    assert_json_eq!(
+        div["attributes"],
+        json!({
+            "style": {
+                "type": "attribute",
+                "name": "style",
+                "children": [{ "type": "text", "value": "wide" }]
+            },
+            "empty": {
+                "type": "attribute",
+                "name": "empty",
+                "children": []
+            }
+        })
+    );
+}
+
+#[test]
+fn refs_to_ranges_render_each_item() {
+    let dast_root = dast_root_no_position(
+        r#"<group name="g"><text>a</text><text>b</text><text>c</text></group><p>$g[2:3]</p><p>$g[-1]</p>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let flat_dast = to_serde_value(&core.to_flat_dast());
+    let elements = flat_dast["elements"].as_array().unwrap();
+
+    let fragment = elements
+        .iter()
+        .find(|element| element["name"] == "_fragment")
+        .unwrap();
+    let item_values: Vec<_> = fragment["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|child| {
+            let item = &elements[child["id"].as_u64().unwrap() as usize];
+            assert_eq!(item["name"], "text");
+            item["data"]["props"]["value"].clone()
+        })
+        .collect();
+    assert_eq!(item_values, vec![json!("b"), json!("c")]);
+
+    let last_p = elements
+        .iter()
+        .rfind(|element| element["name"] == "p")
+        .unwrap();
+    let last_item = &elements[last_p["children"][0]["id"].as_u64().unwrap() as usize];
+    assert_eq!(last_item["data"]["props"]["value"], json!("c"));
+}
+
+#[test]
+fn passed_through_attributes_are_updated_when_their_refs_change() {
+    let dast_root = dast_root_no_position(
+        r#"<textInput name="ti" prefill="wide"/><p class="box $ti.value">hi</p>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    let ti_idx = ComponentIdx::new(core.get_component_index_by_name("ti"));
+    let p_idx = ComponentIdx::new(ti_idx.as_usize() + 1);
+
+    core.dispatch_action(Action {
+        component_idx: ti_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: "narrow".to_string(),
+            },
+        })),
+    })
+    .unwrap();
+    let updates = core
+        .core
+        .dispatch_action(Action {
+            component_idx: ti_idx,
+            action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+        })
+        .unwrap();
+
+    assert_json_eq!(
+        serde_json::to_value(&updates.flat_dast_updates[&p_idx]).unwrap(),
+        json!({
+            "type": "elementUpdate",
+            "changedAttributes": {
+                "class": {
+                    "type": "attribute",
+                    "name": "class",
+                    "children": [{ "type": "text", "value": "box narrow" }]
+                }
+            }
+        })
+    );
+
+    let flat_dast = to_serde_value(&core.to_flat_dast());
+    assert_eq!(
+        flat_dast["elements"][p_idx.as_usize()]["attributes"]["class"]["children"][0]["value"],
+        "box narrow"
+    );
+}
@@ -4,7 +4,7 @@ use assert_json_diff::assert_json_eq;
 use test_utils::*;
 
 #[test]
-fn can_parse_dast_via_node() {
+fn can_parse_dast() {
     // Parse without any position information
     assert_json_eq!(
         json!({
@@ -19,7 +19,7 @@ fn can_parse_dast_via_node() {
             ],
             "sources": ["<document />"]
         }),
-        evaluate_dast_via_node("<document />", true).unwrap()
+        parse_dast_to_serde_value("<document />", true)
     );
 
     // Parse with position information
@@ -36,7 +36,7 @@ fn can_parse_dast_via_node() {
             "position":{"start":{"line":1,"column":1,"offset":0},"end":{"line":1,"column":13,"offset":12}},
             "sources": ["<document />"]
         }),
-        evaluate_dast_via_node("<document />", false).unwrap()
+        parse_dast_to_serde_value("<document />", false)
     );
 }
 
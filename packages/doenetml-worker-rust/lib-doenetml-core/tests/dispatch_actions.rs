@@ -0,0 +1,219 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::{
+            point::{PointActions, PointMoveActionArgs, PointProps},
+            text_input::{TextInputActionArgs, TextInputActions, TextInputProps},
+        },
+        types::{Action, ActionBody, ComponentIdx, LocalPropIdx},
+    },
+    state::types::math_expr::MathExpr,
+};
+use test_utils::*;
+
+const X_LOCAL_IDX: LocalPropIdx = PointProps::X.local_idx();
+const Y_LOCAL_IDX: LocalPropIdx = PointProps::Y.local_idx();
+
+fn move_point_action(component_idx: usize, x: f64, y: f64) -> Action {
+    Action {
+        component_idx: component_idx.into(),
+        action: ActionsEnum::Point(PointActions::Move(ActionBody {
+            args: PointMoveActionArgs { x, y },
+        })),
+    }
+}
+
+fn get_point_coords(core: &mut TestCore, point_idx: usize) -> (MathExpr, MathExpr) {
+    (
+        core.get_prop_value_typed(point_idx, X_LOCAL_IDX),
+        core.get_prop_value_typed(point_idx, Y_LOCAL_IDX),
+    )
+}
+
+#[test]
+fn batch_of_actions_is_applied_together() {
+    let dast_root = dast_root_no_position(r#"<point name="P"/><point name="Q"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+
+    let p_idx = core.get_component_index_by_name("P");
+    let q_idx = core.get_component_index_by_name("Q");
+
+    let updates = core
+        .core
+        .dispatch_actions(vec![
+            move_point_action(p_idx, 1.0, 2.0),
+            move_point_action(q_idx, -3.0, 4.0),
+        ])
+        .unwrap();
+
+    // A single set of updates is returned for both points
+    assert!(
+        updates
+            .flat_dast_updates
+            .contains_key(&ComponentIdx::new(p_idx))
+    );
+    assert!(
+        updates
+            .flat_dast_updates
+            .contains_key(&ComponentIdx::new(q_idx))
+    );
+
+    assert_eq!(get_point_coords(&mut core, p_idx), (1.0.into(), 2.0.into()));
+    assert_eq!(
+        get_point_coords(&mut core, q_idx),
+        ((-3.0).into(), 4.0.into())
+    );
+
+    // The batch is undone as a single step
+    core.core.undo().unwrap();
+    assert_eq!(get_point_coords(&mut core, p_idx), (0.0.into(), 0.0.into()));
+    assert_eq!(get_point_coords(&mut core, q_idx), (0.0.into(), 0.0.into()));
+}
+
+#[test]
+fn later_actions_in_batch_take_precedence() {
+    let dast_root = dast_root_no_position(r#"<point name="P"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let p_idx = core.get_component_index_by_name("P");
+
+    core.core
+        .dispatch_actions(vec![
+            move_point_action(p_idx, 1.0, 2.0),
+            move_point_action(p_idx, 5.0, 6.0),
+        ])
+        .unwrap();
+
+    assert_eq!(get_point_coords(&mut core, p_idx), (5.0.into(), 6.0.into()));
+}
+
+#[test]
+fn failed_action_rolls_back_batch() {
+    let dast_root = dast_root_no_position(r#"<point name="P"/><point name="Q"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let initial = to_serde_value(&core.to_flat_dast());
+
+    let p_idx = core.get_component_index_by_name("P");
+    let q_idx = core.get_component_index_by_name("Q");
+
+    // A point does not have a text input action, so the second action fails.
+    let result = core.core.dispatch_actions(vec![
+        move_point_action(p_idx, 1.0, 2.0),
+        Action {
+            component_idx: q_idx.into(),
+            action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+                args: TextInputActionArgs {
+                    text: "hello".to_string(),
+                },
+            })),
+        },
+    ]);
+    assert!(result.is_err());
+
+    // Neither point moved and there is nothing to undo
+    assert_eq!(get_point_coords(&mut core, p_idx), (0.0.into(), 0.0.into()));
+    assert_eq!(to_serde_value(&core.to_flat_dast()), initial);
+    assert!(core.core.undo().unwrap().is_empty());
+}
+
+fn text_input_action(component_idx: usize, action: TextInputActions) -> Action {
+    Action {
+        component_idx: component_idx.into(),
+        action: ActionsEnum::TextInput(action),
+    }
+}
+
+#[test]
+fn rejected_update_rolls_back_batch() {
+    let dast_root = dast_root_no_position(
+        r#"<point name="P"/><point name="Q" x="$m"/><math name="m" fixed>1</math>"#,
+    );
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let initial = to_serde_value(&core.to_flat_dast());
+
+    let p_idx = core.get_component_index_by_name("P");
+    let q_idx = core.get_component_index_by_name("Q");
+
+    // The x-coordinate of Q cannot be changed, so only part of the second action could be made.
+    let result = core.core.dispatch_actions(vec![
+        move_point_action(p_idx, 1.0, 2.0),
+        move_point_action(q_idx, 3.0, 4.0),
+    ]);
+    assert!(result.is_err());
+
+    assert_eq!(get_point_coords(&mut core, p_idx), (0.0.into(), 0.0.into()));
+    assert_eq!(get_point_coords(&mut core, q_idx), (1.0.into(), 0.0.into()));
+    assert_eq!(to_serde_value(&core.to_flat_dast()), initial);
+    assert!(core.core.undo().unwrap().is_empty());
+}
+
+#[test]
+fn actions_in_batch_see_earlier_changes() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    let ti_idx = core.get_component_index_by_name("ti");
+
+    core.core
+        .dispatch_actions(vec![
+            text_input_action(
+                ti_idx,
+                TextInputActions::UpdateImmediateValue(ActionBody {
+                    args: TextInputActionArgs {
+                        text: "hello".to_string(),
+                    },
+                }),
+            ),
+            text_input_action(ti_idx, TextInputActions::UpdateValue),
+        ])
+        .unwrap();
+
+    // The value is committed from the immediate value set by the first action
+    let value: String = core.get_prop_value_typed(ti_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "hello");
+
+    core.core.undo().unwrap();
+    let value: String = core.get_prop_value_typed(ti_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "");
+}
+
+#[test]
+fn failed_batch_restores_default_values() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti" prefill="abc"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    let ti_idx = core.get_component_index_by_name("ti");
+
+    // There is no component with index 100, so the second action fails.
+    let result = core.core.dispatch_actions(vec![
+        text_input_action(
+            ti_idx,
+            TextInputActions::UpdateImmediateValue(ActionBody {
+                args: TextInputActionArgs {
+                    text: "x".to_string(),
+                },
+            }),
+        ),
+        text_input_action(100, TextInputActions::UpdateValue),
+    ]);
+    assert!(result.is_err());
+
+    // The immediate value is back to its default, so it is not set by the user
+    let immediate_value: String =
+        core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "abc");
+    assert!(core.core.get_state_snapshot().unwrap().states.is_empty());
+}
@@ -0,0 +1,169 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions, TextInputProps},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    document_flags::{DocumentFlags, SolutionDisplayMode},
+    error::CoreError,
+    state_snapshot::StateSnapshotError,
+};
+use test_utils::*;
+
+fn update_immediate_value(component_idx: ComponentIdx, text: &str) -> Action {
+    Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    }
+}
+
+fn read_only_flags() -> DocumentFlags {
+    DocumentFlags {
+        read_only: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn flags_are_parsed_from_json() {
+    let flags = DocumentFlags::from_json(
+        r#"{"readOnly": true, "showFeedback": false, "solutionDisplayMode": "buttonRequirePermission"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        flags,
+        DocumentFlags {
+            read_only: true,
+            show_feedback: false,
+            solution_display_mode: SolutionDisplayMode::ButtonRequirePermission,
+            ..Default::default()
+        }
+    );
+
+    // Missing flags get their default values
+    assert_eq!(
+        DocumentFlags::from_json("{}").unwrap(),
+        DocumentFlags::default()
+    );
+
+    assert!(DocumentFlags::from_json(r#"{"solutionDisplayMode": "sometimes"}"#).is_err());
+}
+
+#[test]
+fn read_only_documents_reject_actions() {
+    let mut core = TestCore::new();
+    core.core.set_flags(read_only_flags()).unwrap();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput name="ti"/>"#));
+    core.to_flat_dast();
+    let ti_idx = ComponentIdx::new(1);
+
+    assert!(matches!(
+        core.dispatch_action(update_immediate_value(ti_idx, "hi")),
+        Err(CoreError::ReadOnly)
+    ));
+    assert!(matches!(
+        core.core
+            .dispatch_actions(vec![update_immediate_value(ti_idx, "hi")]),
+        Err(CoreError::ReadOnly)
+    ));
+
+    let immediate_value: String =
+        core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "");
+}
+
+#[test]
+fn flags_are_kept_when_reinitializing() {
+    let mut core = Core::new();
+    core.set_flags(read_only_flags()).unwrap();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput/>"#));
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput/>"#));
+
+    assert_eq!(core.get_flags(), &read_only_flags());
+    assert!(matches!(
+        core.dispatch_action(update_immediate_value(ComponentIdx::new(1), "hi")),
+        Err(CoreError::ReadOnly)
+    ));
+}
+
+#[test]
+fn text_inputs_are_disabled_in_read_only_documents() {
+    let source = r#"<textInput/><textInput disabled/>"#;
+    let disabled_idx = TextInputProps::Disabled.local_idx();
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    let disabled: bool = core.get_prop_value_typed(1, disabled_idx);
+    assert!(!disabled);
+    let disabled: bool = core.get_prop_value_typed(2, disabled_idx);
+    assert!(disabled);
+
+    let mut core = TestCore::new();
+    core.core.set_flags(read_only_flags()).unwrap();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    let disabled: bool = core.get_prop_value_typed(1, disabled_idx);
+    assert!(disabled);
+    let disabled: bool = core.get_prop_value_typed(2, disabled_idx);
+    assert!(disabled);
+}
+
+#[test]
+fn props_are_recalculated_when_flags_change_after_init() {
+    let disabled_idx = TextInputProps::Disabled.local_idx();
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput/>"#));
+    core.to_flat_dast();
+    let disabled: bool = core.get_prop_value_typed(1, disabled_idx);
+    assert!(!disabled);
+
+    core.core.set_flags(read_only_flags()).unwrap();
+    let disabled: bool = core.get_prop_value_typed(1, disabled_idx);
+    assert!(disabled);
+
+    // Setting the same flags again changes nothing
+    assert!(core.core.set_flags(read_only_flags()).unwrap().is_empty());
+
+    core.core.set_flags(DocumentFlags::default()).unwrap();
+    let disabled: bool = core.get_prop_value_typed(1, disabled_idx);
+    assert!(!disabled);
+}
+
+#[test]
+fn read_only_documents_reject_undo_redo_and_snapshots() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+    let ti_idx = ComponentIdx::new(1);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    core.dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+    let snapshot = core.core.get_state_snapshot().unwrap();
+
+    core.core.set_flags(read_only_flags()).unwrap();
+    assert!(matches!(core.core.undo(), Err(CoreError::ReadOnly)));
+    assert!(matches!(core.core.redo(), Err(CoreError::ReadOnly)));
+    assert!(matches!(
+        core.core.apply_state_snapshot(&snapshot),
+        Err(StateSnapshotError::Core(CoreError::ReadOnly))
+    ));
+
+    let immediate_value: String =
+        core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "hi");
+
+    // The state is still kept when the source of a read-only document is changed
+    core.core
+        .reinit_from_dast_root_preserving_state(&dast_root)
+        .unwrap();
+    let immediate_value: String =
+        core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "hi");
+}
@@ -0,0 +1,113 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    dast::FlatDastElementContent,
+    dispatch_action::ActionResult,
+};
+use test_utils::*;
+
+fn init_core(source: &str) -> Core {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    core.to_flat_dast().unwrap();
+    core
+}
+
+/// Type `text` into the text input and then press enter, returning the result of pressing enter.
+fn type_text_and_press_enter(core: &mut Core, text_input_idx: usize, text: &str) -> ActionResult {
+    let component_idx = ComponentIdx::new(text_input_idx);
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    })
+    .unwrap();
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    })
+    .unwrap()
+}
+
+#[test]
+fn showing_a_hidden_child_updates_the_children_of_its_parent() {
+    let mut core =
+        init_core(r#"<textInput name="i" prefill="true"/><p><text hide="$i">x</text>y</p>"#);
+    let p_idx = ComponentIdx::new(2);
+
+    let result = type_text_and_press_enter(&mut core, 1, "false");
+    assert_eq!(
+        result.flat_dast_updates[&p_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::new_original_element(3),
+            FlatDastElementContent::Text("y".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn hiding_a_child_updates_the_children_of_its_parent() {
+    let mut core =
+        init_core(r#"<textInput name="i" prefill="false"/><p><text hide="$i">x</text>y</p>"#);
+    let p_idx = ComponentIdx::new(2);
+
+    let result = type_text_and_press_enter(&mut core, 1, "true");
+    assert_eq!(
+        result.flat_dast_updates[&p_idx].new_children,
+        Some(vec![FlatDastElementContent::Text("y".to_string())])
+    );
+
+    let result = type_text_and_press_enter(&mut core, 1, "false");
+    assert_eq!(
+        result.flat_dast_updates[&p_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::new_original_element(3),
+            FlatDastElementContent::Text("y".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn children_are_not_updated_when_the_same_children_match() {
+    let mut core =
+        init_core(r#"<textInput name="i" prefill="false"/><p><text hide="$i">x</text>y</p>"#);
+    let p_idx = ComponentIdx::new(2);
+
+    // `hide` is still false, so the same children are rendered.
+    let result = type_text_and_press_enter(&mut core, 1, "FALSE");
+    assert!(
+        result
+            .flat_dast_updates
+            .get(&p_idx)
+            .is_none_or(|update| update.new_children.is_none())
+    );
+}
+
+#[test]
+fn shown_children_of_sections_are_rendered() {
+    let mut core = init_core(
+        r#"<textInput name="i" prefill="true"/><section><p hide="$i">a</p><p>b</p></section>"#,
+    );
+    let section_idx = ComponentIdx::new(2);
+
+    let result = type_text_and_press_enter(&mut core, 1, "false");
+    assert_eq!(
+        result.flat_dast_updates[&section_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::new_original_element(3),
+            FlatDastElementContent::new_original_element(5),
+        ])
+    );
+
+    // The newly shown paragraph is rendered with its contents.
+    let flat_dast = serde_json::to_value(core.to_flat_dast().unwrap()).unwrap();
+    assert_eq!(flat_dast["elements"][3]["children"], json!(["a"]));
+}
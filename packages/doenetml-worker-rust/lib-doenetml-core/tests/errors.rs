@@ -1,5 +1,15 @@
 mod test_utils;
-use doenetml_core::core::core::Core;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions},
+        types::{Action, ActionBody},
+    },
+    core::core::Core,
+    dast::{ForRenderPropValue, ForRenderProps},
+    error::CoreError,
+    props::PropValue,
+};
 use test_utils::*;
 
 #[test]
@@ -9,7 +19,7 @@ fn errors_from_dast_parsing_transmitted() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let elements = core.to_flat_dast().elements;
+    let elements = core.to_flat_dast().unwrap().elements;
 
     assert_eq!(elements[1].name, "a");
     assert_eq!(elements[2].name, "_error");
@@ -31,7 +41,7 @@ fn error_from_no_referent() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let elements = core.to_flat_dast().elements;
+    let elements = core.to_flat_dast().unwrap().elements;
 
     assert_eq!(elements[1].name, "_error");
     assert_eq!(elements[1].data.id, 1);
@@ -53,7 +63,7 @@ fn error_from_multiple_referents() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let elements = core.to_flat_dast().elements;
+    let elements = core.to_flat_dast().unwrap().elements;
 
     assert_eq!(elements[2].name, "_error");
     assert_eq!(elements[2].data.id, 2);
@@ -67,6 +77,32 @@ fn error_from_multiple_referents() {
     );
 }
 
+#[test]
+fn error_from_no_referent_suggests_similar_name_and_has_path_part_position() {
+    let dast_root = dast_root(r#"<number name="xMax" />$xMax.value $xmx"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+
+    let flat_dast = core.to_flat_dast().unwrap();
+    let error = flat_dast
+        .elements
+        .iter()
+        .find(|element| element.name == "_error")
+        .unwrap();
+
+    assert_eq!(
+        error.data.message.as_deref(),
+        Some("No referent found for reference: `$xmx`. Did you mean `$xMax`?")
+    );
+    let path_part_position = error.data.path_part_position.as_ref().unwrap();
+    assert_eq!(path_part_position.start.column, 36);
+
+    let error_json = serde_json::to_value(error).unwrap();
+    assert_eq!(error_json["type"], "error");
+    assert_eq!(error_json["path_part_position"]["start"]["column"], 36);
+}
+
 #[test]
 fn error_referencing_external_and_referencing_error() {
     let dast_root = dast_root_no_position(
@@ -76,7 +112,7 @@ fn error_referencing_external_and_referencing_error() {
     let mut core = Core::new();
     core.init_from_dast_root(&dast_root);
 
-    let elements = core.to_flat_dast().elements;
+    let elements = core.to_flat_dast().unwrap().elements;
 
     assert_eq!(elements[1].name, "_error");
     assert_eq!(elements[1].data.id, 1);
@@ -116,3 +152,107 @@ fn error_referencing_external_and_referencing_error() {
             .contains("from an error component")
     );
 }
+
+#[test]
+fn error_from_circular_references() {
+    let dast_root = dast_root_no_position(
+        "<document><text name='a'>$b</text><text name='b'>$a</text><text name='c'>hello</text></document>",
+    );
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+
+    let elements = core.to_flat_dast().unwrap().elements;
+
+    // The references `$b` and `$a` form the cycle, so they are replaced with errors.
+    for idx in [2, 4] {
+        assert_eq!(elements[idx].name, "_error");
+        assert_eq!(elements[idx].data.id, idx);
+        assert_eq!(
+            elements[idx].data.message.as_ref().unwrap(),
+            "Circular dependency: a -> b -> a"
+        );
+    }
+
+    // The rest of the document still renders
+    assert_eq!(elements[1].name, "text");
+    assert_eq!(elements[3].name, "text");
+    assert_eq!(elements[5].name, "text");
+    assert_eq!(
+        elements[5].data.props.as_ref().unwrap(),
+        &ForRenderProps(vec![ForRenderPropValue {
+            name: "value",
+            value: PropValue::from("hello").into()
+        }])
+    );
+}
+
+#[test]
+fn error_from_self_reference() {
+    let dast_root = dast_root_no_position("<document><text name='a'>x$a</text></document>");
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+
+    let elements = core.to_flat_dast().unwrap().elements;
+
+    assert_eq!(elements[1].name, "text");
+    assert_eq!(elements[2].name, "_error");
+    assert_eq!(
+        elements[2].data.message.as_ref().unwrap(),
+        "Circular dependency: a -> a"
+    );
+}
+
+#[test]
+fn error_from_circular_extend() {
+    let dast_root = dast_root_no_position(
+        "<document><text name='a' extend='$b' /><text name='b' extend='$a' /></document>",
+    );
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+
+    let elements = core.to_flat_dast().unwrap().elements;
+
+    for idx in [1, 2] {
+        assert_eq!(elements[idx].name, "_error");
+        assert_eq!(
+            elements[idx].data.message.as_ref().unwrap(),
+            "Circular dependency: a -> b -> a"
+        );
+    }
+}
+
+#[test]
+fn action_on_missing_component_is_an_error() {
+    let dast_root = dast_root_no_position("<document><textInput /></document>");
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast().unwrap();
+
+    let result = core.dispatch_action(Action {
+        component_idx: 100.into(),
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: "hello".to_string(),
+            },
+        })),
+    });
+    assert!(matches!(result, Err(CoreError::ComponentNotFound(100))));
+
+    // The document is still usable after the failed action
+    let elements = core.to_flat_dast().unwrap().elements;
+    assert_eq!(elements[1].name, "textInput");
+}
+
+#[test]
+fn resolver_queries_before_init_are_errors() {
+    let core = Core::new();
+
+    assert!(matches!(
+        core.calculate_root_names(),
+        Err(CoreError::NotInitialized(_))
+    ));
+}
@@ -0,0 +1,203 @@
+mod test_utils;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use doenetml_core::{
+    components::prelude::PropValue,
+    core::core::Core,
+    dast::{
+        DastRoot, FlatDastElementContent,
+        document_loader::{DocumentLoadError, DocumentLoader, InMemoryDocumentLoader},
+        flat_dast::NormalizedNode,
+    },
+};
+use test_utils::*;
+
+fn loader() -> InMemoryDocumentLoader {
+    InMemoryDocumentLoader::new()
+        .with_document(
+            "doenet:abc",
+            r#"<p name="intro"><text name="t">hello</text> <text extend="$t" /></p>"#,
+        )
+        .with_document("doenet:def", r#"<p extend="doenet:def" />"#)
+}
+
+#[test]
+fn refs_resolve_within_and_into_external_documents() {
+    let mut core = TestCore::new();
+    core.core.set_document_loader(loader());
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<p extend="doenet:abc" name="s" /><text name="u" extend="$s.t" />$t"#,
+    ));
+
+    let s_idx = core.get_component_index_by_name("s");
+    let u_idx = core.get_component_index_by_name("u");
+    let flat_dast = core.to_flat_dast();
+
+    // `$t` is resolved within the external document, and `$s.t` reaches into it
+    let FlatDastElementContent::Element(external_ref) = &flat_dast.elements[s_idx].children[2]
+    else {
+        panic!("expected an element");
+    };
+    let external_ref_idx = external_ref.id;
+    for idx in [external_ref_idx, u_idx] {
+        assert_eq!(
+            core.get_prop_value(idx, 0),
+            PropValue::String("hello".to_string().into())
+        );
+    }
+
+    // Names in the external document are not accessible from the main document
+    let message = flat_dast.elements[3].data.message.as_ref().unwrap();
+    assert!(message.starts_with("No referent found for reference: `$t`"));
+
+    // The attributes recording the sources don't produce warnings
+    assert!(
+        flat_dast
+            .warnings
+            .iter()
+            .all(|warning| !warning.message.starts_with("Invalid attribute")),
+        "{:?}",
+        flat_dast.warnings
+    );
+}
+
+#[test]
+fn document_loader_is_kept_when_reinitializing() {
+    let mut core = Core::new();
+    core.set_document_loader(loader());
+    for _ in 0..2 {
+        core.init_from_dast_root(&dast_root_no_position(
+            r#"<p extend="doenet:def" /><p extend="doenet:abc" />"#,
+        ));
+        let flat_dast = core.to_flat_dast().unwrap();
+        // `doenet:def` is merged once before it is found to extend itself
+        assert_eq!(
+            flat_dast.elements[3].data.message.as_deref(),
+            Some("Circular reference to external DoenetML: `doenet:def` -> `doenet:def`")
+        );
+        assert_eq!(flat_dast.elements[4].name, "text");
+    }
+}
+
+#[test]
+fn circular_references_have_an_error_code() {
+    let mut core = Core::new();
+    core.set_document_loader(loader());
+    let normalized_root =
+        core.normalized_root_from_dast_root(&dast_root_no_position(r#"<p extend="doenet:def" />"#));
+    let codes: Vec<_> = normalized_root
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            NormalizedNode::Error(error) => Some(error.code.as_deref()),
+            NormalizedNode::Element(_) => None,
+        })
+        .collect();
+    assert_eq!(codes, vec![Some("doenet-e0029")]);
+}
+
+/// Counts the documents loaded by an `InMemoryDocumentLoader`.
+#[derive(Debug)]
+struct CountingLoader {
+    loader: InMemoryDocumentLoader,
+    num_loads: Rc<Cell<usize>>,
+}
+
+impl DocumentLoader for CountingLoader {
+    fn load(&self, uri: &str) -> Result<DastRoot, DocumentLoadError> {
+        self.num_loads.set(self.num_loads.get() + 1);
+        self.loader.load(uri)
+    }
+}
+
+#[test]
+fn external_documents_are_loaded_once() {
+    let num_loads = Rc::new(Cell::new(0));
+    let mut core = Core::new();
+    core.set_document_loader(CountingLoader {
+        loader: loader(),
+        num_loads: num_loads.clone(),
+    });
+
+    let dast_root =
+        dast_root_no_position(r#"<p extend="doenet:abc" name="a" /><p extend="doenet:abc" />"#);
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(num_loads.get(), 1);
+
+    // The loaded documents are kept when core is re-initialized...
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(num_loads.get(), 1);
+
+    // ...but not when the loader is replaced
+    core.set_document_loader(CountingLoader {
+        loader: loader(),
+        num_loads: num_loads.clone(),
+    });
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(num_loads.get(), 2);
+}
+
+/// An `InMemoryDocumentLoader` whose documents can be changed after it is given to core.
+#[derive(Debug)]
+struct SharedLoader(Rc<RefCell<InMemoryDocumentLoader>>);
+
+impl DocumentLoader for SharedLoader {
+    fn load(&self, uri: &str) -> Result<DastRoot, DocumentLoadError> {
+        self.0.borrow().load(uri)
+    }
+}
+
+#[test]
+fn changed_external_documents_are_picked_up_after_clearing_them() {
+    let shared_loader = Rc::new(RefCell::new(loader()));
+    let mut core = TestCore::new();
+    core.core
+        .set_document_loader(SharedLoader(shared_loader.clone()));
+    let dast_root = dast_root_no_position(
+        r#"<p extend="doenet:abc" name="s" /><text extend="$s.t" name="u" />"#,
+    );
+    let u_text = |core: &mut TestCore| {
+        let u_idx = core.get_component_index_by_name("u");
+        core.get_prop_value(u_idx, 0)
+    };
+
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(
+        u_text(&mut core),
+        PropValue::String("hello".to_string().into())
+    );
+
+    shared_loader.borrow_mut().insert(
+        "doenet:abc",
+        r#"<p name="intro"><text name="t">goodbye</text></p>"#,
+    );
+
+    // The previously loaded document is reused...
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(
+        u_text(&mut core),
+        PropValue::String("hello".to_string().into())
+    );
+
+    // ...until the loaded documents are cleared
+    core.core.clear_external_documents();
+    core.init_from_dast_root(&dast_root);
+    assert_eq!(
+        u_text(&mut core),
+        PropValue::String("goodbye".to_string().into())
+    );
+}
+
+#[test]
+fn external_documents_are_invalid_without_a_loader() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<p extend="doenet:abc" />"#));
+    let flat_dast = core.to_flat_dast().unwrap();
+    assert_eq!(
+        flat_dast.elements[2].data.message.as_deref(),
+        Some("Invalid 'extend' attribute")
+    );
+}
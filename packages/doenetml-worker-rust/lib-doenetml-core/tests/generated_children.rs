@@ -0,0 +1,220 @@
+mod test_utils;
+use assert_json_diff::assert_json_eq;
+use doenetml_core::{
+    components::types::ComponentIdx,
+    core::core::Core,
+    dast::{
+        DastElementContent, FlatDastElementContent,
+        flat_dast::{FlatIndex, FlatPathPart, UntaggedContent},
+    },
+    error::CoreError,
+    generated_children::GeneratedChildrenUpdate,
+};
+use test_utils::*;
+
+/// Parse `source` and return the children of its document element.
+fn parse_content(source: &str) -> Vec<DastElementContent> {
+    let dast_root = dast_root_no_position(source);
+    match &dast_root.children[0] {
+        DastElementContent::Element(document) => document.children.clone(),
+        _ => panic!("Expected a document element"),
+    }
+}
+
+/// Create a path consisting of `name` and an optional index.
+fn make_path(name: &str, index: Option<&str>) -> Vec<FlatPathPart> {
+    vec![FlatPathPart {
+        name: name.to_string(),
+        index: index
+            .iter()
+            .map(|index| FlatIndex {
+                value: vec![UntaggedContent::Text(index.to_string())],
+                position: None,
+                source_doc: None,
+            })
+            .collect(),
+        position: None,
+        source_doc: None,
+    }]
+}
+
+fn init_core(source: &str) -> Core {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    core.to_flat_dast().unwrap();
+    core
+}
+
+/// The ids of the new elements of `update`.
+fn new_element_ids(update: &GeneratedChildrenUpdate) -> Vec<usize> {
+    update
+        .new_elements
+        .iter()
+        .map(|element| element.data.id)
+        .collect()
+}
+
+#[test]
+fn generated_children_follow_the_children_from_the_source() {
+    let mut core = init_core(r#"<text name="t">x</text><p name="p">hello</p>"#);
+    let p_idx = ComponentIdx::new(2);
+
+    let update = core
+        .replace_generated_children(p_idx, &parse_content(r#"<text name="g">a</text>$t"#))
+        .unwrap();
+
+    assert_eq!(new_element_ids(&update), vec![3, 4]);
+    assert_json_eq!(
+        serde_json::to_value(&update.new_elements[1]).unwrap(),
+        json!({
+            "type": "element",
+            "name": "text",
+            "attributes": {},
+            "children": [],
+            "data": { "id": 4, "action_names": [], "props": { "value": "x" } }
+        })
+    );
+    assert_eq!(update.flat_dast_updates.len(), 1);
+    assert_eq!(
+        update.flat_dast_updates[&p_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::Text("hello".to_string()),
+            FlatDastElementContent::new_original_element(3),
+            FlatDastElementContent::new_original_element(4),
+        ])
+    );
+}
+
+#[test]
+fn replacing_generated_children_removes_the_old_children() {
+    let mut core = init_core(r#"<p name="p">hello</p>"#);
+    let p_idx = ComponentIdx::new(1);
+
+    core.replace_generated_children(p_idx, &parse_content(r#"<text name="g">a</text>"#))
+        .unwrap();
+    let update = core
+        .replace_generated_children(p_idx, &parse_content(r#"<text>b</text>"#))
+        .unwrap();
+
+    assert_eq!(new_element_ids(&update), vec![3]);
+    assert!(core.document_model.is_removed(ComponentIdx::new(2)));
+    assert!(!core.document_model.is_removed(ComponentIdx::new(3)));
+    assert_eq!(
+        update.flat_dast_updates[&p_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::Text("hello".to_string()),
+            FlatDastElementContent::new_original_element(3),
+        ])
+    );
+
+    // The old child can no longer be found by name from the new one
+    assert!(core.resolve_path(make_path("g", None), 3, false).is_err());
+
+    // The flat dast matches the updates
+    let flat_dast = core.to_flat_dast().unwrap();
+    assert_eq!(
+        flat_dast.elements[p_idx.as_usize()].children,
+        vec![
+            FlatDastElementContent::Text("hello".to_string()),
+            FlatDastElementContent::new_original_element(3),
+        ]
+    );
+}
+
+#[test]
+fn generated_children_can_be_referenced() {
+    let mut core = init_core(r#"<p name="p" />"#);
+    let p_idx = ComponentIdx::new(1);
+
+    core.replace_generated_children(
+        p_idx,
+        &parse_content(r#"<text name="a">a</text><text name="b">b</text>"#),
+    )
+    .unwrap();
+
+    // By name, from another generated child
+    assert_eq!(
+        core.resolve_path(make_path("b", None), 2, false)
+            .unwrap()
+            .node_idx,
+        3
+    );
+    // By index into the parent
+    assert_eq!(
+        core.resolve_path(make_path("p", Some("2")), 0, false)
+            .unwrap()
+            .node_idx,
+        3
+    );
+
+    core.replace_generated_children(p_idx, &parse_content(r#"<text>c</text>"#))
+        .unwrap();
+    assert_eq!(
+        core.resolve_path(make_path("p", Some("1")), 0, false)
+            .unwrap()
+            .node_idx,
+        4
+    );
+}
+
+#[test]
+fn generated_children_update_props_that_depend_on_children() {
+    let mut core = init_core(r#"<text name="t">a</text>"#);
+    let t_idx = ComponentIdx::new(1);
+
+    let update = core
+        .replace_generated_children(t_idx, &parse_content(r#"<text>b</text>"#))
+        .unwrap();
+
+    assert!(update.flat_dast_updates[&t_idx].changed_state.is_some());
+    assert_json_eq!(
+        serde_json::to_value(&core.to_flat_dast().unwrap().elements[1].data).unwrap(),
+        json!({ "id": 1, "action_names": [], "props": { "value": "ab" } })
+    );
+
+    // Replacing the generated children again removes the old child from the value
+    let update = core
+        .replace_generated_children(t_idx, &parse_content(r#"<text>c</text>"#))
+        .unwrap();
+
+    assert!(update.flat_dast_updates[&t_idx].changed_state.is_some());
+    assert_json_eq!(
+        serde_json::to_value(&core.to_flat_dast().unwrap().elements[1].data).unwrap(),
+        json!({ "id": 1, "action_names": [], "props": { "value": "ac" } })
+    );
+}
+
+#[test]
+fn components_extending_the_parent_get_the_generated_children() {
+    let mut core = init_core(r#"<p name="p">hello</p><p extend="$p" />"#);
+    let p_idx = ComponentIdx::new(1);
+    let extending_idx = ComponentIdx::new(2);
+
+    let update = core
+        .replace_generated_children(p_idx, &parse_content(r#"<text>a</text>"#))
+        .unwrap();
+
+    assert_eq!(
+        update.flat_dast_updates[&extending_idx].new_children,
+        Some(vec![
+            FlatDastElementContent::Text("hello".to_string()),
+            FlatDastElementContent::new_duplicate_element(3),
+        ])
+    );
+}
+
+#[test]
+fn generated_children_require_an_initialized_core() {
+    let mut core = Core::new();
+    assert!(matches!(
+        core.replace_generated_children(ComponentIdx::new(0), &[]),
+        Err(CoreError::NotInitialized(_))
+    ));
+
+    let mut core = init_core(r#"<p />"#);
+    assert_eq!(
+        core.replace_generated_children(ComponentIdx::new(5), &[])
+            .err(),
+        Some(CoreError::ComponentNotFound(5))
+    );
+}
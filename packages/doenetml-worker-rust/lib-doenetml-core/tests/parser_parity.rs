@@ -0,0 +1,38 @@
+//! Check that the Rust parser produces the same DAST as the JavaScript parser.
+//!
+//! These tests need Node and the JavaScript parser, so they are ignored by default.
+//! Run them with `npm run test:rust:parser-parity`.
+mod test_utils;
+use assert_json_diff::assert_json_eq;
+use test_utils::*;
+
+/// DoenetML sources covering the syntax the parsers must agree on.
+const SOURCES: &[&str] = &[
+    "<document />",
+    r#"<p name="a">hello <b>there</b></p>"#,
+    "<p>line one\n  line two</p>\n\n<p/>",
+    r#"<document name="d"> <p/> </document>"#,
+    r#"<math simplify="$s">$a.b[2].c</math>"#,
+    "$x $(x.y) $x.y[1][$n] $$f(1, <m>2</m>)",
+    "$p.coords[-1] $l[1:2]",
+    "<text>a &amp; b &lt; c &#65;&#x42;</text>",
+    "<p>a <!-- a comment --> b</p>",
+    r#"<point name="P" coords="(1,2)" hide />"#,
+    "<p>an <unclosed> element</p>",
+    "<p>a stray </b> closing tag</p>",
+    r#"<p name="a" name="b">repeated attribute</p>"#,
+    "<p>a dangling $</p>",
+    "<?xml version=\"1.0\"?><document><p>with a declaration</p></document>",
+];
+
+#[test]
+#[ignore = "needs Node and the JavaScript parser, built with `npm run test:rust:before`"]
+fn rust_parser_matches_javascript_parser() {
+    for source in SOURCES {
+        for strip_position in [true, false] {
+            let expected = evaluate_dast_via_node(source, strip_position)
+                .unwrap_or_else(|err| panic!("Cannot parse {source:?} with Node: {err}"));
+            assert_json_eq!(parse_dast_to_serde_value(source, strip_position), expected);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    dast::flat_dast::{FlatIndex, FlatPathPart, UntaggedContent},
+    error::CoreError,
+    props::PropValue,
+    state::types::math_expr::MathExpr,
+};
+use test_utils::*;
+
+/// Create a path from `parts`, each of which is a name and an optional index.
+fn make_path(parts: &[(&str, Option<&str>)]) -> Vec<FlatPathPart> {
+    parts
+        .iter()
+        .map(|(name, index)| FlatPathPart {
+            name: name.to_string(),
+            index: index
+                .iter()
+                .map(|index| FlatIndex {
+                    value: vec![UntaggedContent::Text(index.to_string())],
+                    position: None,
+                    source_doc: None,
+                })
+                .collect(),
+            position: None,
+            source_doc: None,
+        })
+        .collect()
+}
+
+fn init_core(source: &str) -> Core {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    core
+}
+
+#[test]
+fn can_get_prop_by_path() {
+    let mut core = init_core(r#"<text name="t">hello</text><boolean name="b">true</boolean>"#);
+
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("t", None), ("value", None)]), 0),
+        Ok(PropValue::String("hello".to_string().into()))
+    );
+    // Prop names are case-insensitive
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("b", None), ("VALUE", None)]), 0),
+        Ok(PropValue::Boolean(true))
+    );
+    // Without a prop name, the default prop is used
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("t", None)]), 0),
+        Ok(PropValue::String("hello".to_string().into()))
+    );
+}
+
+#[test]
+fn prop_by_path_reflects_actions() {
+    let mut core = init_core(r#"<textInput name="ti"/>"#);
+    let value_path = make_path(&[("ti", None), ("value", None)]);
+
+    assert_eq!(
+        core.get_prop_by_path(&value_path, 0),
+        Ok(PropValue::String("".to_string().into()))
+    );
+
+    let component_idx = ComponentIdx::new(1);
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: "typed".to_string(),
+            },
+        })),
+    })
+    .unwrap();
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    })
+    .unwrap();
+
+    assert_eq!(
+        core.get_prop_by_path(&value_path, 0),
+        Ok(PropValue::String("typed".to_string().into()))
+    );
+}
+
+#[test]
+fn can_get_element_of_array_prop_by_path() {
+    let mut core = init_core(
+        r#"<number name="x">3</number><number name="y">-1</number><point name="P" x="$x" y="$y"/>"#,
+    );
+
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("P", None), ("coords", Some("2"))]), 0),
+        Ok(MathExpr::from(-1.0).into())
+    );
+    // Elements that are out of range have the default value
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("P", None), ("coords", Some("3"))]), 0),
+        Ok(MathExpr::default().into())
+    );
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("P", None), ("coords", Some("0"))]), 0),
+        Err(CoreError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("x", None), ("value", Some("1"))]), 0),
+        Err(CoreError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn invalid_paths_give_errors() {
+    let mut core = init_core(r#"<text name="t">hello</text><p name="p"/>"#);
+
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("t", None), ("bogus", None)]), 0),
+        Err(CoreError::PropNotFound { .. })
+    ));
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("nobody", None)]), 0),
+        Err(CoreError::Resolution(_))
+    ));
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("t", None), ("value", None), ("x", None)]), 0),
+        Err(CoreError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("p", None)]), 0),
+        Err(CoreError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn cannot_get_prop_by_path_before_initialization() {
+    let mut core = Core::new();
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("t", None)]), 0),
+        Err(CoreError::NotInitialized("resolve path"))
+    );
+}
+
+#[test]
+fn can_get_prop_by_path_with_negative_index() {
+    let mut core = init_core(
+        r#"<group name="g"><text>first</text> <text>second</text> <text>last</text></group>"#,
+    );
+
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("g", Some("-1"))]), 0),
+        Ok(PropValue::String("last".to_string().into()))
+    );
+    assert_eq!(
+        core.get_prop_by_path(make_path(&[("g", Some("-3")), ("value", None)]), 0),
+        Ok(PropValue::String("first".to_string().into()))
+    );
+    assert!(matches!(
+        core.get_prop_by_path(make_path(&[("g", Some("-4"))]), 0),
+        Err(CoreError::Resolution(_))
+    ));
+}
@@ -0,0 +1,298 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::{
+            point::{PointActions, PointMoveActionArgs},
+            text::TextProps,
+            text_input::{TextInputActionArgs, TextInputActions},
+        },
+        types::{Action, ActionBody, ComponentIdx, LocalPropIdx, PropPointer},
+    },
+    core::core::Core,
+    dast::flat_dast::{FlatIndex, FlatPathPart, UntaggedContent},
+    error::CoreError,
+    props::PropValue,
+    state::types::math_expr::MathExpr,
+};
+use test_utils::*;
+
+/// Create a path from `parts`, each of which is a name and an optional index.
+fn make_path(parts: &[(&str, Option<&str>)]) -> Vec<FlatPathPart> {
+    parts
+        .iter()
+        .map(|(name, index)| FlatPathPart {
+            name: name.to_string(),
+            index: index
+                .iter()
+                .map(|index| FlatIndex {
+                    value: vec![UntaggedContent::Text(index.to_string())],
+                    position: None,
+                    source_doc: None,
+                })
+                .collect(),
+            position: None,
+            source_doc: None,
+        })
+        .collect()
+}
+
+fn update_immediate_value(component_idx: ComponentIdx, text: &str) -> Action {
+    Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    }
+}
+
+fn update_value(component_idx: ComponentIdx) -> Action {
+    Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    }
+}
+
+#[test]
+fn actions_report_changes_of_subscribed_props() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<textInput name="ti"/><text name="t">$ti</text>"#,
+    ));
+    core.to_flat_dast().unwrap();
+    let ti_idx = ComponentIdx::new(1);
+    let t_idx = ComponentIdx::new(2);
+
+    let value_subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+    let text_subscription = core
+        .subscribe_to_prop(PropPointer {
+            component_idx: t_idx,
+            local_prop_idx: TextProps::Value.local_idx(),
+        })
+        .unwrap();
+
+    // Typing changes only the immediate value, so no subscribed prop changes
+    let result = core
+        .dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+    assert!(result.subscribed_prop_changes.is_empty());
+
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    let changes = result
+        .subscribed_prop_changes
+        .iter()
+        .map(|change| {
+            (
+                change.subscription_id,
+                change.component_idx,
+                change.prop_name.as_str(),
+                change.value.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let hi = PropValue::String("hi".to_string().into());
+    assert_eq!(
+        changes,
+        vec![
+            (value_subscription, ti_idx, "value", hi.clone()),
+            (text_subscription, t_idx, "value", hi)
+        ]
+    );
+
+    // Changes are reported only once
+    assert!(core.get_subscribed_prop_changes().unwrap().is_empty());
+
+    // Setting the same value again is not a change
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    assert!(result.subscribed_prop_changes.is_empty());
+}
+
+#[test]
+fn subscriptions_do_not_affect_rendering() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput name="ti"/>"#));
+    core.to_flat_dast();
+    let ti_idx = ComponentIdx::new(1);
+
+    core.core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+
+    core.dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    assert_eq!(result.subscribed_prop_changes.len(), 1);
+    assert!(result.flat_dast_updates.contains_key(&ti_idx));
+}
+
+#[test]
+fn unsubscribed_props_are_not_reported() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput name="ti"/>"#));
+    let ti_idx = ComponentIdx::new(1);
+
+    let subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+    assert!(core.unsubscribe_from_prop(subscription));
+    assert!(!core.unsubscribe_from_prop(subscription));
+
+    core.dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    assert!(result.subscribed_prop_changes.is_empty());
+}
+
+#[test]
+fn unsubscribing_removes_the_subscription_from_the_dependency_graph() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput name="ti"/>"#));
+    let ti_idx = ComponentIdx::new(1);
+    let num_graph_nodes =
+        |core: &Core| core.document_model.get_dependency_graph().get_nodes().len();
+
+    // The first subscription also resolves the prop and its dependencies
+    let kept_subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+    let num_nodes = num_graph_nodes(&core);
+
+    let subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+    assert_eq!(num_graph_nodes(&core), num_nodes + 1);
+    assert!(core.unsubscribe_from_prop(subscription));
+    assert_eq!(num_graph_nodes(&core), num_nodes);
+
+    // The remaining subscription still reports changes
+    core.dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    let changes = result.subscribed_prop_changes;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].subscription_id, kept_subscription);
+
+    assert!(core.unsubscribe_from_prop(kept_subscription));
+    assert_eq!(num_graph_nodes(&core), num_nodes - 1);
+}
+
+#[test]
+fn can_subscribe_to_element_of_array_prop() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<number name="x">3</number><number name="y">-1</number><point name="P" x="$x" y="$y"/>"#,
+    ));
+    let point_idx = ComponentIdx::new(3);
+
+    let x_subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("P", None), ("coords", Some("1"))]), 0)
+        .unwrap();
+    core.subscribe_to_prop_by_path(make_path(&[("P", None), ("coords", Some("2"))]), 0)
+        .unwrap();
+
+    // Only the first coordinate changes
+    let result = core
+        .dispatch_action(Action {
+            component_idx: point_idx,
+            action: ActionsEnum::Point(PointActions::Move(ActionBody {
+                args: PointMoveActionArgs { x: 5.0, y: -1.0 },
+            })),
+        })
+        .unwrap();
+    let changes = result.subscribed_prop_changes;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].subscription_id, x_subscription);
+    assert_eq!(changes[0].value, MathExpr::from(5.0).into());
+}
+
+#[test]
+fn cannot_subscribe_to_missing_props() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<text name="t">hello</text>"#));
+
+    assert_eq!(
+        core.subscribe_to_prop(PropPointer {
+            component_idx: ComponentIdx::new(1),
+            local_prop_idx: LocalPropIdx::new(1000),
+        }),
+        Err(CoreError::LocalPropNotFound {
+            component_idx: 1,
+            local_prop_idx: 1000
+        })
+    );
+    assert_eq!(
+        core.subscribe_to_prop(PropPointer {
+            component_idx: ComponentIdx::new(1000),
+            local_prop_idx: LocalPropIdx::new(0),
+        }),
+        Err(CoreError::ComponentNotFound(1000))
+    );
+    assert!(matches!(
+        core.subscribe_to_prop_by_path(make_path(&[("t", None), ("bogus", None)]), 0),
+        Err(CoreError::PropNotFound { .. })
+    ));
+}
+
+#[test]
+fn subscriptions_are_kept_when_a_cycle_is_replaced_with_errors() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<textInput name="ti"/><text name="a">$b</text><text name="b">$a</text>"#,
+    ));
+    let ti_idx = ComponentIdx::new(1);
+    let ref_b_idx = ComponentIdx::new(3);
+
+    let value_subscription = core
+        .subscribe_to_prop_by_path(make_path(&[("ti", None), ("value", None)]), 0)
+        .unwrap();
+    // The `<text>` created from `$b` is replaced with an error, so this subscription is removed.
+    core.subscribe_to_prop(PropPointer {
+        component_idx: ref_b_idx,
+        local_prop_idx: TextProps::Hidden.local_idx(),
+    })
+    .unwrap();
+    core.dispatch_action(update_immediate_value(ti_idx, "hi"))
+        .unwrap();
+
+    let flat_dast = core.to_flat_dast().unwrap();
+    assert_eq!(flat_dast.elements[ref_b_idx.as_usize()].name, "_error");
+    assert_eq!(
+        flat_dast
+            .warnings
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .filter(|message| message.starts_with("Replacing"))
+            .collect::<Vec<_>>(),
+        vec![
+            "Replacing a circular dependency with errors dropped 1 prop subscription(s), the undo history"
+        ]
+    );
+    assert!(!core.action_history.can_undo());
+
+    let result = core.dispatch_action(update_value(ti_idx)).unwrap();
+    let changes = result
+        .subscribed_prop_changes
+        .iter()
+        .map(|change| (change.subscription_id, change.value.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![(
+            value_subscription,
+            PropValue::String("hi".to_string().into())
+        )]
+    );
+
+    // The warning is only given when the cycle is replaced
+    assert!(
+        core.to_flat_dast()
+            .unwrap()
+            .warnings
+            .iter()
+            .all(|warning| !warning.message.starts_with("Replacing"))
+    );
+}
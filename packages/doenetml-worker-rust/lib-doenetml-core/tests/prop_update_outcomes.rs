@@ -0,0 +1,84 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::point::{PointActions, PointMoveActionArgs},
+        types::{Action, ActionBody},
+    },
+    dispatch_action::{PropUpdateOutcome, PropUpdateStatus},
+    props::PropValue,
+    state::types::math_expr::MathExpr,
+};
+use test_utils::*;
+
+fn move_point_action(component_idx: usize, x: f64, y: f64) -> Action {
+    Action {
+        component_idx: component_idx.into(),
+        action: ActionsEnum::Point(PointActions::Move(ActionBody {
+            args: PointMoveActionArgs { x, y },
+        })),
+    }
+}
+
+fn math_value(value: f64) -> PropValue {
+    PropValue::Math(MathExpr::from(value).into())
+}
+
+/// Move the point named `P` to (3,4) and return the outcomes of the requested updates to `x` and `y`.
+fn move_point_p(source: &str) -> (PropUpdateOutcome, PropUpdateOutcome) {
+    let dast_root = dast_root_no_position(source);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+
+    let p_idx = core.get_component_index_by_name("P");
+    let mut outcomes = core
+        .dispatch_action(move_point_action(p_idx, 3.0, 4.0))
+        .unwrap()
+        .prop_outcomes;
+
+    assert_eq!(outcomes.len(), 2);
+    let y_outcome = outcomes.pop().unwrap();
+    let x_outcome = outcomes.pop().unwrap();
+    assert_eq!(x_outcome.prop_name, "x");
+    assert_eq!(y_outcome.prop_name, "y");
+
+    (x_outcome, y_outcome)
+}
+
+#[test]
+fn accepted_updates_report_final_value() {
+    let (x_outcome, y_outcome) = move_point_p(r#"<point name="P"/>"#);
+
+    assert_eq!(x_outcome.status, PropUpdateStatus::Accepted);
+    assert_eq!(x_outcome.requested_value, math_value(3.0));
+    assert_eq!(x_outcome.final_value, x_outcome.requested_value);
+
+    assert_eq!(y_outcome.status, PropUpdateStatus::Accepted);
+    assert_eq!(y_outcome.final_value, math_value(4.0));
+}
+
+#[test]
+fn update_blocked_by_fixed_component_is_rejected() {
+    let (x_outcome, y_outcome) =
+        move_point_p(r#"<point name="P" x="$m"/><math name="m" fixed>1</math>"#);
+
+    assert_eq!(x_outcome.status, PropUpdateStatus::RejectedFixed);
+    assert_eq!(x_outcome.requested_value, math_value(3.0));
+    assert_eq!(x_outcome.final_value, math_value(1.0));
+
+    // The other coordinate is not affected
+    assert_eq!(y_outcome.status, PropUpdateStatus::Accepted);
+}
+
+#[test]
+fn update_that_cannot_be_inverted_is_rejected() {
+    let (x_outcome, y_outcome) =
+        move_point_p(r#"<point name="P" x="$n+1"/><number name="n">1</number>"#);
+
+    assert_eq!(x_outcome.status, PropUpdateStatus::RejectedByInvert);
+    assert_ne!(x_outcome.final_value, x_outcome.requested_value);
+
+    assert_eq!(y_outcome.status, PropUpdateStatus::Accepted);
+}
@@ -0,0 +1,291 @@
+mod test_utils;
+use doenetml_core::{
+    action_recording::RecordedEvent,
+    components::{
+        ActionsEnum,
+        doenet::{
+            text_input::{TextInputActionArgs, TextInputActions},
+            update_value::UpdateValueActions,
+        },
+        testing::_test_timer::{_TestTimerActions, _TestTimerProps},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    document_flags::DocumentFlags,
+    error::CoreError,
+    scheduler::MAX_ACTIONS_PER_ADVANCE,
+};
+use test_utils::*;
+
+const SOURCE: &str = r#"<textInput name="ti"/><p>$ti.value</p>"#;
+const TEXT_INPUT_IDX: usize = 1;
+
+fn init_core() -> Core {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(SOURCE));
+    core.to_flat_dast().unwrap();
+    core
+}
+
+fn type_text_action(text: &str) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(TEXT_INPUT_IDX),
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    }
+}
+
+fn immediate_value(core: &mut Core) -> Value {
+    to_serde_value(&core.to_flat_dast().unwrap())["elements"][TEXT_INPUT_IDX]["data"]["props"]
+        ["immediateValue"]
+        .clone()
+}
+
+#[test]
+fn scheduled_actions_are_sent_when_the_clock_reaches_them() {
+    let mut core = init_core();
+    core.schedule_action(type_text_action("later"), 100.0, None)
+        .unwrap();
+    assert_eq!(core.get_time(), 0.0);
+    assert_eq!(core.get_next_scheduled_time(), Some(100.0));
+
+    let result = core.advance_time(99.0).unwrap();
+    assert!(result.flat_dast_updates.is_empty());
+    assert_eq!(core.get_time(), 99.0);
+    assert_eq!(immediate_value(&mut core), json!(""));
+
+    let result = core.advance_time(1.0).unwrap();
+    assert!(
+        result
+            .flat_dast_updates
+            .contains_key(&ComponentIdx::new(TEXT_INPUT_IDX))
+    );
+    assert_eq!(result.prop_outcomes.len(), 1);
+    assert_eq!(immediate_value(&mut core), json!("later"));
+    assert_eq!(core.get_next_scheduled_time(), None);
+
+    // Changes made by scheduled actions cannot be undone.
+    assert!(!core.action_history.can_undo());
+}
+
+#[test]
+fn actions_due_in_one_advance_are_sent_in_order() {
+    let mut core = init_core();
+    core.schedule_action(type_text_action("second"), 20.0, None)
+        .unwrap();
+    core.schedule_action(type_text_action("first"), 10.0, None)
+        .unwrap();
+
+    let result = core.advance_time(50.0).unwrap();
+    let final_values = result
+        .prop_outcomes
+        .iter()
+        .map(|outcome| outcome.final_value.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(final_values, vec!["first".into(), "second".into()]);
+    assert_eq!(immediate_value(&mut core), json!("second"));
+    assert_eq!(core.get_time(), 50.0);
+}
+
+#[test]
+fn repeating_actions_are_sent_until_cancelled() {
+    let mut core = init_core();
+    let id = core
+        .schedule_action(type_text_action("tick"), 10.0, Some(10.0))
+        .unwrap();
+
+    let result = core.advance_time(35.0).unwrap();
+    assert_eq!(result.prop_outcomes.len(), 3);
+    assert_eq!(core.get_next_scheduled_time(), Some(40.0));
+
+    assert!(core.cancel_scheduled_action(id).unwrap());
+    assert!(!core.cancel_scheduled_action(id).unwrap());
+    assert!(core.advance_time(100.0).unwrap().prop_outcomes.is_empty());
+}
+
+#[test]
+fn invalid_times_are_rejected() {
+    let mut core = init_core();
+    assert_eq!(
+        core.advance_time(-1.0).unwrap_err(),
+        CoreError::InvalidTime(-1.0)
+    );
+    assert!(
+        core.schedule_action(type_text_action("x"), f64::NAN, None)
+            .is_err()
+    );
+    assert!(
+        core.schedule_action(type_text_action("x"), 0.0, Some(0.0))
+            .is_err()
+    );
+    assert!(matches!(
+        core.schedule_action(
+            Action {
+                component_idx: ComponentIdx::new(100),
+                ..type_text_action("x")
+            },
+            0.0,
+            None
+        ),
+        Err(CoreError::ComponentNotFound(100))
+    ));
+    assert_eq!(core.get_next_scheduled_time(), None);
+}
+
+#[test]
+fn clock_stops_at_a_failing_action() {
+    let mut core = Core::new();
+    core.set_flags(DocumentFlags {
+        read_only: true,
+        ..Default::default()
+    })
+    .unwrap();
+    core.init_from_dast_root(&dast_root_no_position(SOURCE));
+    core.schedule_action(type_text_action("x"), 10.0, None)
+        .unwrap();
+
+    assert_eq!(core.advance_time(20.0).unwrap_err(), CoreError::ReadOnly);
+    assert_eq!(core.get_time(), 10.0);
+    assert_eq!(core.get_next_scheduled_time(), None);
+}
+
+#[test]
+fn changes_before_a_failing_action_are_kept() {
+    let mut core = init_core();
+    core.schedule_action(type_text_action("first"), 5.0, None)
+        .unwrap();
+    // The `<p>` has no text input actions.
+    let mut failing_action = type_text_action("second");
+    failing_action.component_idx = ComponentIdx::new(TEXT_INPUT_IDX + 1);
+    core.schedule_action(failing_action, 10.0, None).unwrap();
+
+    assert!(core.advance_time(20.0).is_err());
+    assert_eq!(core.get_time(), 10.0);
+    assert_eq!(immediate_value(&mut core), json!("first"));
+}
+
+#[test]
+fn scheduled_actions_are_recorded_and_replayed() {
+    let mut core = init_core();
+    core.start_action_recording().unwrap();
+    core.schedule_action(type_text_action("a"), 10.0, None)
+        .unwrap();
+    core.advance_time(5.0).unwrap();
+    core.advance_time(5.0).unwrap();
+
+    let recording = core.stop_action_recording().unwrap();
+    assert_eq!(recording.entries.len(), 3);
+    assert_eq!(
+        recording.entries[2].event,
+        RecordedEvent::AdvanceTime { ms: 5.0 }
+    );
+    assert_ne!(
+        recording.entries[1].flat_dast_hash,
+        recording.entries[2].flat_dast_hash
+    );
+
+    let mut replayed = Core::new();
+    replayed.replay_action_recording(&recording).unwrap();
+    assert_eq!(immediate_value(&mut replayed), json!("a"));
+}
+
+fn timer_action(timer_idx: usize, action: _TestTimerActions) -> Action {
+    Action {
+        component_idx: ComponentIdx::new(timer_idx),
+        action: ActionsEnum::_TestTimer(action),
+    }
+}
+
+fn timer_count(core: &mut TestCore, timer_idx: usize) -> i64 {
+    core.get_prop_value_typed(timer_idx, _TestTimerProps::Count.local_idx())
+}
+
+#[test]
+fn components_can_schedule_their_own_actions() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<_testTimer name="timer" delay="5"/>"#,
+    ));
+    let timer_idx = core.get_component_index_by_name("timer");
+
+    core.dispatch_action(timer_action(timer_idx, _TestTimerActions::Start))
+        .unwrap();
+    assert_eq!(core.core.get_next_scheduled_time(), Some(5.0));
+
+    // Each tick schedules the next one
+    core.core.advance_time(12.0).unwrap();
+    assert_eq!(timer_count(&mut core, timer_idx), 2);
+    assert_eq!(core.core.get_next_scheduled_time(), Some(15.0));
+
+    core.dispatch_action(timer_action(timer_idx, _TestTimerActions::Stop))
+        .unwrap();
+    assert_eq!(core.core.get_next_scheduled_time(), None);
+    core.core.advance_time(100.0).unwrap();
+    assert_eq!(timer_count(&mut core, timer_idx), 2);
+}
+
+#[test]
+fn components_cannot_schedule_actions_without_a_delay() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<number name="d">5</number><_testTimer name="timer" delay="$d"/><updateValue name="uv" target="$d" type="number" newValue="0"/>"#,
+    ));
+    let timer_idx = core.get_component_index_by_name("timer");
+    let update_value_idx = core.get_component_index_by_name("uv");
+
+    core.dispatch_action(timer_action(timer_idx, _TestTimerActions::Start))
+        .unwrap();
+    core.core.advance_time(7.0).unwrap();
+    assert_eq!(timer_count(&mut core, timer_idx), 1);
+
+    // The next tick would schedule itself with no delay, so it fails rather than being sent forever
+    core.dispatch_action(Action {
+        component_idx: ComponentIdx::new(update_value_idx),
+        action: ActionsEnum::UpdateValue(UpdateValueActions::UpdateValue),
+    })
+    .unwrap();
+    assert_eq!(
+        core.core.advance_time(100.0).unwrap_err(),
+        CoreError::InvalidTime(0.0)
+    );
+    assert_eq!(core.core.get_time(), 10.0);
+    assert_eq!(timer_count(&mut core, timer_idx), 1);
+
+    assert_eq!(
+        core.dispatch_action(timer_action(timer_idx, _TestTimerActions::Start))
+            .unwrap_err(),
+        CoreError::InvalidTime(0.0)
+    );
+}
+
+#[test]
+fn advancing_the_clock_sends_a_limited_number_of_actions() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<_testTimer name="timer" delay="1"/>"#,
+    ));
+    let timer_idx = core.get_component_index_by_name("timer");
+
+    core.dispatch_action(timer_action(timer_idx, _TestTimerActions::Start))
+        .unwrap();
+    let max_actions = MAX_ACTIONS_PER_ADVANCE as f64;
+    assert_eq!(
+        core.core.advance_time(max_actions + 100.0).unwrap_err(),
+        CoreError::TooManyScheduledActions(MAX_ACTIONS_PER_ADVANCE)
+    );
+    assert_eq!(core.core.get_time(), max_actions);
+    assert_eq!(
+        timer_count(&mut core, timer_idx),
+        MAX_ACTIONS_PER_ADVANCE as i64
+    );
+
+    // The remaining actions are sent when the clock is advanced again
+    core.core.advance_time(100.0).unwrap();
+    assert_eq!(
+        timer_count(&mut core, timer_idx),
+        MAX_ACTIONS_PER_ADVANCE as i64 + 100
+    );
+}
@@ -0,0 +1,356 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions, TextInputProps},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    dast::{
+        DastElementContent, FlatDastElement, FlatDastElementContent, FlatDastRoot,
+        flat_dast::FlatPathPart,
+    },
+    source_update::{SourceUpdate, SourceUpdateError},
+};
+use test_utils::*;
+
+/// Parse `source`, which must consist of a single element, and return that element.
+fn parse_element(source: &str) -> DastElementContent {
+    let dast_root = dast_root_no_position(source);
+    match &dast_root.children[0] {
+        DastElementContent::Element(document) => document.children[0].clone(),
+        _ => panic!("Expected a document element"),
+    }
+}
+
+/// The flat dast of a core freshly initialized from `source`.
+fn fresh_flat_dast(source: &str) -> Value {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    to_serde_value(&core.to_flat_dast().unwrap())
+}
+
+/// The rendered tree of `flat_dast`, with each element inlined in place of the reference to it,
+/// so that documents whose components are numbered differently can be compared.
+fn rendered_tree(flat_dast: &FlatDastRoot) -> Value {
+    fn inline(content: &FlatDastElementContent, elements: &[FlatDastElement]) -> Value {
+        match content {
+            FlatDastElementContent::Text(text) => json!(text),
+            FlatDastElementContent::Element(element_ref) => {
+                let element = &elements[element_ref.id];
+                let mut value = serde_json::to_value(element).unwrap();
+                value["data"].as_object_mut().unwrap().remove("id");
+                value["children"] = element
+                    .children
+                    .iter()
+                    .map(|child| inline(child, elements))
+                    .collect();
+                value
+            }
+        }
+    }
+    flat_dast
+        .children
+        .iter()
+        .map(|child| inline(child, &flat_dast.elements))
+        .collect()
+}
+
+/// The rendered tree of a core freshly initialized from `source`.
+fn fresh_rendered_tree(source: &str) -> Value {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(source));
+    rendered_tree(&core.to_flat_dast().unwrap())
+}
+
+#[test]
+fn editing_text_updates_only_changed_elements() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<p>hello</p><p>unchanged</p><text name="t">hi</text>"#,
+    ));
+    core.to_flat_dast();
+
+    // Change the text of the first paragraph
+    let update = core
+        .core
+        .update_dast_subtree(&[0, 0], parse_element("<p>goodbye</p>"))
+        .unwrap();
+    let SourceUpdate::Incremental(update) = update else {
+        panic!("Expected an incremental update");
+    };
+    assert!(update.new_elements.is_empty());
+    let updates = update.flat_dast_updates;
+    assert_eq!(updates.len(), 1);
+    assert_eq!(
+        updates[&ComponentIdx::new(1)].new_children,
+        Some(vec![FlatDastElementContent::Text("goodbye".to_string())])
+    );
+
+    // Change the text of the `<text>`, which changes its rendered value
+    let update = core
+        .core
+        .update_dast_subtree(&[0, 2], parse_element(r#"<text name="t">bye</text>"#))
+        .unwrap();
+    let SourceUpdate::Incremental(update) = update else {
+        panic!("Expected an incremental update");
+    };
+    let updates = update.flat_dast_updates;
+    let t_idx = core.get_component_index_by_name("t");
+    assert_eq!(
+        updates.keys().collect::<Vec<_>>(),
+        vec![&ComponentIdx::new(t_idx)]
+    );
+    assert!(updates[&ComponentIdx::new(t_idx)].changed_state.is_some());
+
+    assert_eq!(
+        to_serde_value(&core.to_flat_dast()),
+        fresh_flat_dast(r#"<p>goodbye</p><p>unchanged</p><text name="t">bye</text>"#)
+    );
+}
+
+#[test]
+fn editing_text_updates_extending_components() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<text name="t">hi</text><p><text extend="$t"/></p>"#,
+    ));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<text name="t">bye</text>"#))
+        .unwrap();
+    let SourceUpdate::Incremental(update) = update else {
+        panic!("Expected an incremental update");
+    };
+    assert_eq!(update.flat_dast_updates.len(), 2);
+
+    assert_eq!(
+        to_serde_value(&core.to_flat_dast().unwrap()),
+        fresh_flat_dast(r#"<text name="t">bye</text><p><text extend="$t"/></p>"#)
+    );
+}
+
+#[test]
+fn editing_attribute_text_is_incremental() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<textInput prefill="hello"/>"#));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<textInput prefill="bye"/>"#))
+        .unwrap();
+    assert!(matches!(update, SourceUpdate::Incremental(_)));
+
+    assert_eq!(
+        to_serde_value(&core.to_flat_dast().unwrap()),
+        fresh_flat_dast(r#"<textInput prefill="bye"/>"#)
+    );
+}
+
+#[test]
+fn structural_edit_patches_children_and_preserves_state() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<textInput name="ti"/><p>hello</p>"#,
+    ));
+    core.to_flat_dast();
+
+    let ti_idx = core.get_component_index_by_name("ti");
+    let component_idx = ComponentIdx::new(ti_idx);
+    for action in [
+        TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: "typed".to_string(),
+            },
+        }),
+        TextInputActions::UpdateValue,
+    ] {
+        core.dispatch_action(Action {
+            component_idx,
+            action: ActionsEnum::TextInput(action),
+        })
+        .unwrap();
+    }
+
+    // Adding an element to the paragraph creates a component for it and leaves the other components in place
+    let update = core
+        .core
+        .update_dast_subtree(&[0, 1], parse_element("<p>hello <text>there</text></p>"))
+        .unwrap();
+    let SourceUpdate::Incremental(update) = update else {
+        panic!("Expected an incremental update");
+    };
+    assert_eq!(update.new_elements.len(), 1);
+    assert_eq!(update.new_elements[0].name, "text");
+    let p_update = &update.flat_dast_updates[&ComponentIdx::new(2)];
+    assert_eq!(
+        p_update.new_children,
+        Some(vec![
+            FlatDastElementContent::Text("hello ".to_string()),
+            FlatDastElementContent::new_original_element(update.new_elements[0].data.id),
+        ])
+    );
+
+    let value: String = core.get_prop_value_typed(ti_idx, TextInputProps::Value.local_idx());
+    assert_eq!(value, "typed");
+
+    assert_eq!(
+        rendered_tree(&core.core.to_flat_dast().unwrap()),
+        rendered_tree(&{
+            let mut core = Core::new();
+            core.init_from_dast_root(&dast_root_no_position(
+                r#"<textInput name="ti" prefill="typed"/><p>hello <text>there</text></p>"#,
+            ));
+            core.to_flat_dast().unwrap()
+        })
+    );
+}
+
+#[test]
+fn removing_children_removes_their_components() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<p><text name="a">a</text><text name="b">b</text></p><text extend="$b"/>"#,
+    ));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<p><text name="b">b</text></p>"#))
+        .unwrap();
+    let SourceUpdate::Incremental(update) = update else {
+        panic!("Expected an incremental update");
+    };
+    assert!(update.new_elements.is_empty());
+    assert!(
+        update.flat_dast_updates[&ComponentIdx::new(1)]
+            .new_children
+            .is_some()
+    );
+    let path = vec![FlatPathPart {
+        name: "a".to_string(),
+        index: Vec::new(),
+        position: None,
+        source_doc: None,
+    }];
+    assert!(core.resolve_path(path, 0, false).is_err());
+
+    assert_eq!(
+        rendered_tree(&core.to_flat_dast().unwrap()),
+        fresh_rendered_tree(r#"<p><text name="b">b</text></p><text extend="$b"/>"#)
+    );
+}
+
+#[test]
+fn new_children_can_refer_to_existing_components() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<text name="t">hi</text><p>a</p>"#,
+    ));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 1], parse_element(r#"<p>a <text extend="$t"/> $t</p>"#))
+        .unwrap();
+    assert!(matches!(update, SourceUpdate::Incremental(_)));
+
+    assert_eq!(
+        rendered_tree(&core.to_flat_dast().unwrap()),
+        fresh_rendered_tree(r#"<text name="t">hi</text><p>a <text extend="$t"/> $t</p>"#)
+    );
+
+    // Editing the referent updates the new components
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<text name="t">bye</text>"#))
+        .unwrap();
+    assert!(matches!(update, SourceUpdate::Incremental(_)));
+    assert_eq!(
+        rendered_tree(&core.to_flat_dast().unwrap()),
+        fresh_rendered_tree(r#"<text name="t">bye</text><p>a <text extend="$t"/> $t</p>"#)
+    );
+}
+
+#[test]
+fn edits_that_change_outside_refs_reinitialize() {
+    // The `<text>` extending `x` would have to extend the new `x`.
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<p><text name="x">a</text></p><text extend="$x"/>"#,
+    ));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(
+            &[0, 0],
+            parse_element(r#"<p><text name="x">b</text><math/></p>"#),
+        )
+        .unwrap();
+    let SourceUpdate::Full(flat_dast) = update else {
+        panic!("Expected a full update");
+    };
+    assert_eq!(
+        to_serde_value(&flat_dast),
+        fresh_flat_dast(r#"<p><text name="x">b</text><math/></p><text extend="$x"/>"#)
+    );
+
+    // The unresolved `$y` would resolve to the new `y`.
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(r#"<p/><text extend="$y"/>"#));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<p><text name="y">y</text></p>"#))
+        .unwrap();
+    let SourceUpdate::Full(flat_dast) = update else {
+        panic!("Expected a full update");
+    };
+    assert_eq!(
+        to_serde_value(&flat_dast),
+        fresh_flat_dast(r#"<p><text name="y">y</text></p><text extend="$y"/>"#)
+    );
+}
+
+#[test]
+fn edits_that_create_a_cycle_render_errors() {
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<text name="a">x</text><text name="b">hello</text>"#,
+    ));
+    core.to_flat_dast().unwrap();
+
+    let update = core
+        .update_dast_subtree(&[0, 0], parse_element(r#"<text name="a">x$a</text>"#))
+        .unwrap();
+    let SourceUpdate::Full(flat_dast) = update else {
+        panic!("Expected a full update");
+    };
+    assert_eq!(
+        to_serde_value(&flat_dast),
+        fresh_flat_dast(r#"<text name="a">x$a</text><text name="b">hello</text>"#)
+    );
+    assert!(
+        flat_dast.elements.iter().any(|element| {
+            element.data.message.as_deref() == Some("Circular dependency: a -> a")
+        })
+    );
+}
+
+#[test]
+fn invalid_updates_are_rejected() {
+    let mut core = Core::new();
+    assert!(matches!(
+        core.update_dast_subtree(&[0], parse_element("<p/>")),
+        Err(SourceUpdateError::NotInitialized)
+    ));
+
+    core.init_from_dast_root(&dast_root_no_position("<p>hello</p>"));
+    let expected = to_serde_value(&core.to_flat_dast().unwrap());
+
+    for path in [&[][..], &[1], &[0, 5], &[0, 0, 0, 0]] {
+        assert!(matches!(
+            core.update_dast_subtree(path, parse_element("<p/>")),
+            Err(SourceUpdateError::InvalidPath(_))
+        ));
+    }
+    assert_eq!(to_serde_value(&core.to_flat_dast().unwrap()), expected);
+}
@@ -0,0 +1,276 @@
+mod test_utils;
+use doenetml_core::{
+    components::{
+        ActionsEnum,
+        doenet::text_input::{TextInputActionArgs, TextInputActions, TextInputProps},
+        types::{Action, ActionBody, ComponentIdx},
+    },
+    core::core::Core,
+    state_snapshot::{ComponentAddress, STATE_SNAPSHOT_VERSION, StateSnapshot, StateSnapshotError},
+};
+use test_utils::*;
+
+/// Type `text` into the text input and then press enter.
+fn type_text_and_press_enter(core: &mut Core, text_input_idx: usize, text: &str) {
+    let component_idx = ComponentIdx::new(text_input_idx);
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateImmediateValue(ActionBody {
+            args: TextInputActionArgs {
+                text: text.to_string(),
+            },
+        })),
+    })
+    .unwrap();
+    core.dispatch_action(Action {
+        component_idx,
+        action: ActionsEnum::TextInput(TextInputActions::UpdateValue),
+    })
+    .unwrap();
+}
+
+#[test]
+fn snapshot_of_fresh_document_is_empty() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast().unwrap();
+
+    let snapshot = core.get_state_snapshot().unwrap();
+    assert_eq!(snapshot.version, STATE_SNAPSHOT_VERSION);
+    assert!(snapshot.states.is_empty());
+    assert!(snapshot.strings.is_empty());
+}
+
+#[test]
+fn snapshot_restores_state_after_reinitialization() {
+    let source = r#"<textInput name="ti"/><p><textInput/></p><text>$ti.value</text>"#;
+    let dast_root = dast_root_no_position(source);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+
+    let ti_idx = core.get_component_index_by_name("ti");
+    type_text_and_press_enter(&mut core.core, ti_idx, "hello");
+    // The second text input does not have a name, so it will be addressed by its path
+    type_text_and_press_enter(&mut core.core, 3, "bye");
+
+    let snapshot = core.core.get_state_snapshot().unwrap();
+    assert!(
+        snapshot
+            .states
+            .iter()
+            .any(|entry| entry.component == ComponentAddress::Name("ti".to_string()))
+    );
+    assert!(
+        snapshot
+            .states
+            .iter()
+            .any(|entry| matches!(entry.component, ComponentAddress::Path(_)))
+    );
+
+    // The snapshot survives a round trip through JSON
+    let serialized = serde_json::to_string(&snapshot).unwrap();
+    let snapshot: StateSnapshot = serde_json::from_str(&serialized).unwrap();
+
+    let expected = to_serde_value(&core.to_flat_dast());
+
+    let mut restored_core = Core::new();
+    restored_core.init_from_dast_root(&dast_root);
+    restored_core.apply_state_snapshot(&snapshot).unwrap();
+
+    assert_eq!(
+        to_serde_value(&restored_core.to_flat_dast().unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn snapshot_restores_changed_strings() {
+    let source = r#"<textInput>$t</textInput><text name="t">hello</text>"#;
+    let dast_root = dast_root_no_position(source);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast().unwrap();
+
+    // Changing the value of the text input changes the string child of the referenced text
+    type_text_and_press_enter(&mut core, 1, "bye");
+
+    let snapshot = core.get_state_snapshot().unwrap();
+    assert_eq!(snapshot.strings.len(), 1);
+    assert_eq!(snapshot.strings[0].value, "bye");
+
+    let expected = to_serde_value(&core.to_flat_dast().unwrap());
+
+    let mut restored_core = Core::new();
+    restored_core.init_from_dast_root(&dast_root);
+    restored_core.to_flat_dast().unwrap();
+    let updates = restored_core.apply_state_snapshot(&snapshot).unwrap();
+    assert!(!updates.is_empty());
+
+    assert_eq!(
+        to_serde_value(&restored_core.to_flat_dast().unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn snapshot_entries_for_missing_components_are_ignored() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    let ti_idx = core.get_component_index_by_name("ti");
+    type_text_and_press_enter(&mut core.core, ti_idx, "hello");
+    let snapshot = core.core.get_state_snapshot().unwrap();
+
+    // The text input has been renamed, so the state no longer applies
+    let dast_root = dast_root_no_position(r#"<textInput name="ti2"/>"#);
+    let mut restored_core = Core::new();
+    restored_core.init_from_dast_root(&dast_root);
+    let expected = to_serde_value(&restored_core.to_flat_dast().unwrap());
+
+    restored_core.apply_state_snapshot(&snapshot).unwrap();
+    assert_eq!(
+        to_serde_value(&restored_core.to_flat_dast().unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn snapshot_with_unknown_version_is_rejected() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti"/>"#);
+
+    let mut core = Core::new();
+    core.init_from_dast_root(&dast_root);
+
+    let mut snapshot = core.get_state_snapshot().unwrap();
+    snapshot.version = STATE_SNAPSHOT_VERSION + 1;
+
+    assert!(matches!(
+        core.apply_state_snapshot(&snapshot),
+        Err(StateSnapshotError::UnsupportedVersion { .. })
+    ));
+}
+
+/// Get the value of the text input with index `text_input_idx`.
+fn get_text_input_value(core: &mut TestCore, text_input_idx: usize) -> String {
+    core.get_prop_value_typed(text_input_idx, TextInputProps::Value.local_idx())
+}
+
+#[test]
+fn snapshot_after_undo_keeps_prefill() {
+    let dast_root = dast_root_no_position(r#"<textInput name="ti" prefill="abc"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    let ti_idx = core.get_component_index_by_name("ti");
+
+    type_text_and_press_enter(&mut core.core, ti_idx, "x");
+    core.core.undo().unwrap();
+    core.core.undo().unwrap();
+
+    // The undone states have their default values, so they are not part of the snapshot
+    let snapshot = core.core.get_state_snapshot().unwrap();
+    assert!(snapshot.states.is_empty());
+
+    let mut restored_core = TestCore::new();
+    restored_core.init_from_dast_root(&dast_root);
+    restored_core.core.apply_state_snapshot(&snapshot).unwrap();
+    restored_core.to_flat_dast();
+
+    assert_eq!(get_text_input_value(&mut restored_core, ti_idx), "abc");
+    let immediate_value: String =
+        restored_core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "abc");
+}
+
+#[test]
+fn reinitialization_preserves_state_of_matching_components() {
+    let dast_root =
+        dast_root_no_position(r#"<textInput name="ti"/><textInput name="ti2"/><textInput/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+
+    let ti_idx = core.get_component_index_by_name("ti");
+    let ti2_idx = core.get_component_index_by_name("ti2");
+    type_text_and_press_enter(&mut core.core, ti_idx, "hello");
+    type_text_and_press_enter(&mut core.core, ti2_idx, "bye");
+    type_text_and_press_enter(&mut core.core, 3, "unnamed");
+
+    // The author adds a paragraph before the inputs and renames `ti2`
+    let dast_root = dast_root_no_position(
+        r#"<p>Intro</p><textInput name="ti"/><textInput name="renamed"/><textInput/>"#,
+    );
+    core.core
+        .reinit_from_dast_root_preserving_state(&dast_root)
+        .unwrap();
+    core.to_flat_dast();
+
+    let ti_idx = core.get_component_index_by_name("ti");
+    assert_eq!(get_text_input_value(&mut core, ti_idx), "hello");
+
+    // The unnamed input is still the third text input of the document, so it keeps its state
+    assert_eq!(get_text_input_value(&mut core, 4), "unnamed");
+
+    // The renamed input no longer matches, so its state is lost
+    let renamed_idx = core.get_component_index_by_name("renamed");
+    assert_eq!(get_text_input_value(&mut core, renamed_idx), "");
+}
+
+#[test]
+fn reinitialization_after_undo_uses_new_prefill() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root_no_position(
+        r#"<textInput name="ti" prefill="abc"/>"#,
+    ));
+    core.to_flat_dast();
+    let ti_idx = core.get_component_index_by_name("ti");
+
+    type_text_and_press_enter(&mut core.core, ti_idx, "x");
+    core.core.undo().unwrap();
+    core.core.undo().unwrap();
+
+    // The author changes the prefill. The undone input has no state of its own, so it shows the new prefill.
+    core.core
+        .reinit_from_dast_root_preserving_state(&dast_root_no_position(
+            r#"<textInput name="ti" prefill="xyz"/>"#,
+        ))
+        .unwrap();
+    core.to_flat_dast();
+
+    let ti_idx = core.get_component_index_by_name("ti");
+    assert_eq!(get_text_input_value(&mut core, ti_idx), "xyz");
+    let immediate_value: String =
+        core.get_prop_value_typed(ti_idx, TextInputProps::ImmediateValue.local_idx());
+    assert_eq!(immediate_value, "xyz");
+}
+
+#[test]
+fn reinitialization_discards_state_when_type_changes() {
+    let dast_root = dast_root_no_position(r#"<textInput name="x"/>"#);
+
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root);
+    core.to_flat_dast();
+    type_text_and_press_enter(&mut core.core, 1, "hello");
+
+    let dast_root = dast_root_no_position(r#"<point name="x"/>"#);
+    core.core
+        .reinit_from_dast_root_preserving_state(&dast_root)
+        .unwrap();
+
+    let mut fresh_core = Core::new();
+    fresh_core.init_from_dast_root(&dast_root);
+
+    assert_eq!(
+        to_serde_value(&core.to_flat_dast()),
+        to_serde_value(&fresh_core.to_flat_dast().unwrap())
+    );
+}
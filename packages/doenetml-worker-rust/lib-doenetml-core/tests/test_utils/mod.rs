@@ -2,60 +2,67 @@
 use doenetml_core::Core;
 use doenetml_core::components::types::{Action, ComponentIdx, LocalPropIdx, PropPointer};
 use doenetml_core::dast::flat_dast::FlatPathPart;
-use doenetml_core::dast::{DastRoot, FlatDastElementUpdate, FlatDastRoot};
+use doenetml_core::dast::{DastRoot, FlatDastRoot, parser};
+use doenetml_core::dispatch_action::ActionResult;
+use doenetml_core::error::CoreError;
 use doenetml_core::props::cache::PropWithMeta;
 use doenetml_core::props::traits::IntoPropView;
 use doenetml_core::props::{PropValue, PropView};
 use serde_json;
 #[allow(unused)]
 pub use serde_json::{Value, json};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::Command;
-use std::str;
 
-const PARSE_DAST_JS_SOURCE: &str = include_str!("../dist/parse-dast.js");
-
-/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>`.
-/// This returns a JSON string of the parsed DAST. Node must be installed and properly configured.
+/// Parse the string containing DoenetML into a serde `Value` of the parsed DAST.
 ///
-/// If `strip_position` is true, the position field will be stripped from the output.
-pub fn evaluate_dast_via_node(dast: &str, strip_position: bool) -> std::io::Result<Value> {
-    evaluate_dast_via_node_to_json(dast, strip_position)
-        .map(|val| serde_json::from_str(&val).unwrap())
+/// If `strip_position` is true, the position fields will be stripped from the output.
+pub fn parse_dast_to_serde_value(source: &str, strip_position: bool) -> Value {
+    let mut value = serde_json::to_value(parser::parse(source)).unwrap();
+    if strip_position {
+        strip_position_fields(&mut value);
+    }
+    value
 }
 
-/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>`.
-/// This returns a JSON string of the parsed DAST. Node must be installed and properly configured.
+/// Remove the `position` field from every node of a serialized DAST.
+fn strip_position_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("position");
+            for (key, child) in map.iter_mut() {
+                match (key.as_str(), child) {
+                    // The keys of an attribute map are attribute names, which may well be `position`.
+                    ("attributes", Value::Object(attributes)) => {
+                        attributes.values_mut().for_each(strip_position_fields)
+                    }
+                    (_, child) => strip_position_fields(child),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_position_fields),
+        _ => {}
+    }
+}
+/// Execute the command `node ./tests/dist/parse-dast.js -i <dast>` with the JavaScript parser.
+/// This returns the parsed DAST as a serde `Value`. Node must be installed and `npm run test:rust:before`
+/// must have been run to build `tests/dist/parse-dast.js`.
+///
+/// The Rust parser is used everywhere else; this is only used to check that both parsers agree.
 ///
 /// If `strip_position` is true, the position field will be stripped from the output.
-pub fn evaluate_dast_via_node_to_json(dast: &str, strip_position: bool) -> std::io::Result<String> {
-    // Save the JS source to a temporary file so that the resulting binary becomes more portable
-    let dir = tempfile::tempdir()?;
-    let file_path = PathBuf::from(dir.path().join("tempfile.mjs"));
-    let mut file = File::create(&file_path)?;
-    writeln!(file, "{}", PARSE_DAST_JS_SOURCE)?;
-
-    let mut command = Command::new("node");
-    command
-        // Instead of `.arg("./tests/dist/parse-dast.js")`
-        // we use the tmp file that was created
-        .arg(file_path)
-        .arg("-i")
-        .arg(dast);
+#[allow(unused)]
+pub fn evaluate_dast_via_node(dast: &str, strip_position: bool) -> std::io::Result<Value> {
+    let script = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/dist/parse-dast.js");
+    let mut command = std::process::Command::new("node");
+    command.arg(script).arg("-i").arg(dast);
     if strip_position {
         command.arg("--strip-position");
     }
-
     let output = command.output()?;
 
     match output.status.success() {
-        true => Ok(str::from_utf8(&output.stdout).unwrap().to_string()),
-        false => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            str::from_utf8(&output.stderr).unwrap().to_string(),
+        true => Ok(serde_json::from_slice(&output.stdout)?),
+        false => Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
         )),
     }
 }
@@ -64,44 +71,39 @@ pub fn evaluate_dast_via_node_to_json(dast: &str, strip_position: bool) -> std::
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_no_position(str: &str) -> String {
-    evaluate_dast_via_node_to_json(str, true).unwrap()
+    parse_dast_to_serde_value(str, true).to_string()
 }
 
 /// Expand the string containing DoenetML into a JSON string of the parsed DAST
 #[allow(unused)]
 pub fn dast(str: &str) -> String {
-    evaluate_dast_via_node_to_json(str, false).unwrap()
+    parse_dast_to_serde_value(str, false).to_string()
 }
 
 /// Expand the string containing DoenetML into a serde `Value`
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_no_position_as_serde_value(str: &str) -> Value {
-    evaluate_dast_via_node(str, true).unwrap()
+    parse_dast_to_serde_value(str, true)
 }
 
 /// Expand the string containing DoenetML into a serde `Value`
 #[allow(unused)]
 pub fn dast_as_serde_value(str: &str) -> Value {
-    evaluate_dast_via_node(str, false).unwrap()
+    parse_dast_to_serde_value(str, false)
 }
 
 /// Expand the string containing DoenetML into `DastRoot`
 #[allow(unused)]
 pub fn dast_root(str: &str) -> DastRoot {
-    let root: DastRoot =
-        serde_json::from_str(&evaluate_dast_via_node_to_json(str, false).unwrap()).unwrap();
-    root
+    parser::parse(str)
 }
 
 /// Expand the string containing DoenetML into `DastRoot`
 /// omitting the position props.
 #[allow(unused)]
 pub fn dast_root_no_position(str: &str) -> DastRoot {
-    let root: DastRoot =
-        serde_json::from_str(&evaluate_dast_via_node_to_json(str, true).expect("Should unwrap 1"))
-            .expect("Should unwrap 2");
-    root
+    serde_json::from_value(parse_dast_to_serde_value(str, true)).unwrap()
 }
 
 #[allow(unused)]
@@ -159,8 +161,7 @@ impl TestCore {
                 component_idx,
                 local_prop_idx,
             });
-        let prop = self.core.get_prop_for_render_untracked(prop_node);
-        prop
+        self.core.get_prop_for_render_untracked(prop_node).unwrap()
     }
 
     /// Get the value of a prop from the core without tracking it. It will be resolved and calculated
@@ -216,7 +217,10 @@ impl TestCore {
         ComponentIdx: From<A>,
     {
         let component_idx = ComponentIdx::from(component_idx);
-        self.core.document_model.get_component(component_idx)
+        self.core
+            .document_model
+            .get_component(component_idx)
+            .unwrap()
     }
 
     /// Get the index of a component via its assigned name.
@@ -242,16 +246,13 @@ impl TestCore {
     }
 
     pub fn to_flat_dast(&mut self) -> FlatDastRoot {
-        self.core.to_flat_dast()
+        self.core.to_flat_dast().unwrap()
     }
     pub fn init_from_dast_root(&mut self, dast_root: &DastRoot) {
         self.core.init_from_dast_root(dast_root);
     }
 
-    pub fn dispatch_action(
-        &mut self,
-        action: Action,
-    ) -> Result<HashMap<ComponentIdx, FlatDastElementUpdate>, String> {
+    pub fn dispatch_action(&mut self, action: Action) -> Result<ActionResult, CoreError> {
         self.core.dispatch_action(action)
     }
 }
@@ -0,0 +1,117 @@
+mod test_utils;
+use doenetml_core::dast::DastWarning;
+use test_utils::*;
+
+/// The messages of `warnings` along with the (one-based) columns where they start.
+fn messages_and_columns(warnings: &[DastWarning]) -> Vec<(String, Option<usize>)> {
+    warnings
+        .iter()
+        .map(|warning| {
+            (
+                warning.message.clone(),
+                warning
+                    .position
+                    .as_ref()
+                    .map(|position| position.start.column),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn documents_without_problems_have_no_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(
+        r#"<text name="t">hi</text>$t<division type="chapter"/><ol start="3"><li>a</li></ol>"#,
+    ));
+    assert!(core.to_flat_dast().warnings.is_empty());
+}
+
+#[test]
+fn unrecognized_attributes_give_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(
+        r#"<text foo="x" hide="false" bar class="c" data-x="1">hi</text><_custom baz="1"/>"#,
+    ));
+
+    // Attributes of `_External` components are not checked,
+    // and `class` and `data-*` attributes are passed through to the renderer of any component
+    assert_eq!(
+        messages_and_columns(&core.to_flat_dast().warnings),
+        vec![
+            (
+                r#"Invalid attribute "foo" for a component of type <text>."#.to_string(),
+                Some(7)
+            ),
+            (
+                r#"Invalid attribute "bar" for a component of type <text>."#.to_string(),
+                Some(28)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn invalid_enum_values_give_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(r#"<division type="chaptr"/>"#));
+
+    assert_eq!(
+        messages_and_columns(&core.to_flat_dast().warnings),
+        vec![(
+            r#"Invalid value "chaptr" for attribute "type"; the default value is used instead."#
+                .to_string(),
+            Some(11)
+        )]
+    );
+}
+
+#[test]
+fn unparsable_numbers_give_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(r#"<ol start="three"><li>a</li></ol>"#));
+
+    assert_eq!(
+        messages_and_columns(&core.to_flat_dast().warnings),
+        vec![(
+            r#"Could not parse "three" as a number for attribute "start"; the default value 1 is used instead."#
+                .to_string(),
+            Some(5)
+        )]
+    );
+}
+
+#[test]
+fn names_that_are_never_referenced_give_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(
+        r#"<section name="s"><text name="t"/></section><text name="u"/>$s.t"#,
+    ));
+
+    // `s` is referenced through `$s.t`
+    assert_eq!(
+        messages_and_columns(&core.to_flat_dast().warnings),
+        vec![(r#"The name "u" is never referenced."#.to_string(), Some(51))]
+    );
+}
+
+#[test]
+fn empty_numbers_give_no_warnings() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(
+        r#"<ol start><li>a</li></ol><ol start=""><li>b</li></ol>"#,
+    ));
+
+    assert!(core.to_flat_dast().warnings.is_empty());
+}
+
+#[test]
+fn attribute_values_with_references_are_not_checked() {
+    let mut core = TestCore::new();
+    core.init_from_dast_root(&dast_root(
+        r#"<textInput name="i" prefill="chaptr"/><division type="$i"/>"#,
+    ));
+
+    // The value of `type` is only known once the document is calculated
+    assert!(core.to_flat_dast().warnings.is_empty());
+}
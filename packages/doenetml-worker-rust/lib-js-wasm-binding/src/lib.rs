@@ -9,13 +9,22 @@ use tsify_next::Tsify;
 use wasm_bindgen::prelude::*;
 
 use doenetml_core::{
+    action_recording::ActionRecording,
     components::{prelude::ComponentIdx, types::Action},
     core::core::Core,
     dast::{
-        DastRoot, FlatDastElementUpdate, FlatDastRoot,
+        DastElementContent, DastRoot, FlatDastElementUpdate, FlatDastRoot,
         flat_dast::{FlatFragment, FlatNode, FlatPathPart, Index, NormalizedRoot, UntaggedContent},
-        ref_resolve::{IndexResolution, RefResolution, ResolutionError},
+        ref_resolve::{IndexResolution, RefResolution},
     },
+    dispatch_action::{ActionResult, PropUpdateOutcome},
+    document_flags::DocumentFlags,
+    error::CoreError,
+    prop_subscriptions::{SubscribedPropChange, SubscriptionId},
+    props::PropValue,
+    scheduler::ScheduledActionId,
+    source_update::SourceUpdate,
+    state_snapshot::StateSnapshot,
 };
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -31,12 +40,56 @@ pub struct PublicDoenetMLCore {
     dast_root: Option<DastRoot>,
     flags_json: Option<String>,
     initialized: bool,
+    /// If true, the interactive state of components is kept when core is re-initialized after a source change.
+    preserve_state: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Tsify)]
 #[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
 pub struct ActionResponse {
     payload: HashMap<ComponentIdx, FlatDastElementUpdate>,
+    /// The outcome of each prop update requested by the action(s).
+    /// Empty if the changes did not come from actions (e.g., undo and redo).
+    prop_outcomes: Vec<PropUpdateOutcome>,
+    /// The subscribed props whose values changed.
+    /// Empty if the changes did not come from actions (e.g., undo and redo).
+    subscribed_prop_changes: Vec<SubscribedPropChange>,
+}
+
+impl From<ActionResult> for ActionResponse {
+    fn from(result: ActionResult) -> Self {
+        ActionResponse {
+            payload: result.flat_dast_updates,
+            prop_outcomes: result.prop_outcomes,
+            subscribed_prop_changes: result.subscribed_prop_changes,
+        }
+    }
+}
+
+impl From<HashMap<ComponentIdx, FlatDastElementUpdate>> for ActionResponse {
+    fn from(payload: HashMap<ComponentIdx, FlatDastElementUpdate>) -> Self {
+        ActionResponse {
+            payload,
+            prop_outcomes: Vec::new(),
+            subscribed_prop_changes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub struct ActionList {
+    actions: Vec<Action>,
+}
+
+/// A replacement for the node of the source DAST at `path`.
+/// See `Core::update_dast_subtree` for the meaning of `path`.
+#[derive(Debug, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub struct DastSubtree {
+    path: Vec<usize>,
+    content: DastElementContent,
 }
 
 #[derive(Debug, Serialize, Deserialize, Tsify)]
@@ -62,6 +115,18 @@ pub struct RootNames {
     names: Vec<Option<String>>,
 }
 
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct PropValueResponse {
+    value: PropValue,
+}
+
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct SubscribedPropChanges {
+    changes: Vec<SubscribedPropChange>,
+}
+
 // For some reason, wasm-bindgen won't always correctly see that a module is being used
 // in an exported interface. In that case, the Typescript types generated by that module will not
 // be picked up. As a workaround we can create a dummy struct in that module and use it here, forcing
@@ -83,6 +148,7 @@ impl PublicDoenetMLCore {
             dast_root: None,
             flags_json: None,
             initialized: false,
+            preserve_state: false,
         }
     }
 
@@ -103,6 +169,15 @@ impl PublicDoenetMLCore {
         self.dast_root = None;
     }
 
+    /// Set whether the interactive state of components (e.g., the position of a dragged point)
+    /// is kept when the source is changed with `set_source`. State is kept for components
+    /// whose name (or path, if unnamed) and type are unchanged.
+    pub fn set_preserve_state(&mut self, preserve_state: bool) {
+        self.preserve_state = preserve_state;
+    }
+
+    /// Set the flags of the document from JSON matching the `DoenetMLFlags` of the JavaScript worker.
+    /// The flags are parsed when core is created by `return_dast`.
     pub fn set_flags(&mut self, flags: &str) {
         self.flags_json = Some(flags.to_string());
         self.initialized = false;
@@ -125,44 +200,113 @@ impl PublicDoenetMLCore {
         &mut self,
         flat_fragment: FlatFragment,
         index_resolution: IndexResolution,
-    ) {
+    ) -> Result<(), String> {
         self.core
-            .add_nodes_to_resolver(&flat_fragment, index_resolution);
+            .add_nodes_to_resolver(&flat_fragment, index_resolution)
+            .map_err(|err| err.to_string())
     }
 
     pub fn replace_index_resolutions_in_resolver(
         &mut self,
         components: ContentVector,
         index_resolution: IndexResolution,
-    ) {
+    ) -> Result<(), String> {
         self.core
-            .replace_index_resolutions_in_resolver(&components.content, index_resolution);
+            .replace_index_resolutions_in_resolver(&components.content, index_resolution)
+            .map_err(|err| err.to_string())
     }
 
-    pub fn delete_nodes_from_resolver(&mut self, node_list: NodeList) {
-        self.core.delete_nodes_from_resolver(&node_list.nodes);
+    pub fn delete_nodes_from_resolver(&mut self, node_list: NodeList) -> Result<(), String> {
+        self.core
+            .delete_nodes_from_resolver(&node_list.nodes)
+            .map_err(|err| err.to_string())
     }
 
+    /// Resolve `path` relative to the node `origin`.
+    ///
+    /// If the path cannot be resolved, the `ResolutionError` is thrown.
+    /// Calling this before core is initialized throws an `Error` instead.
     pub fn resolve_path(
         &self,
         path: PathToCheck,
         origin: Index,
         skip_parent_search: bool,
-    ) -> Result<RefResolution, ResolutionError> {
-        self.core
+    ) -> Result<RefResolution, JsValue> {
+        match self
+            .core
             .resolve_path(&path.path, origin, skip_parent_search)
+        {
+            Ok(ref_resolution) => Ok(ref_resolution),
+            Err(CoreError::Resolution(err)) => Err(err.into()),
+            Err(err) => Err(js_sys::Error::new(&err.to_string()).into()),
+        }
+    }
+
+    /// Get the value of the public prop referred to by `path` (e.g., `answer1.creditAchieved` or `P.coords[2]`),
+    /// resolved relative to the node `origin`. See `Core::get_prop_by_path`.
+    pub fn get_prop_by_path(
+        &mut self,
+        path: PathToCheck,
+        origin: Index,
+    ) -> Result<PropValueResponse, String> {
+        if !self.initialized {
+            return Err("Cannot get a prop before core is initialized.".to_string());
+        }
+        Ok(PropValueResponse {
+            value: self
+                .core
+                .get_prop_by_path(&path.path, origin)
+                .map_err(|err| err.to_string())?,
+        })
     }
 
-    pub fn calculate_root_names(&self) -> RootNames {
-        RootNames {
-            names: self.core.calculate_root_names(),
+    /// Subscribe to changes in the value of the public prop referred to by `path`, resolved relative to the node `origin`.
+    /// Changes are reported in the response to each action. See `Core::subscribe_to_prop_by_path`.
+    pub fn subscribe_to_prop_by_path(
+        &mut self,
+        path: PathToCheck,
+        origin: Index,
+    ) -> Result<SubscriptionId, String> {
+        if !self.initialized {
+            return Err("Cannot subscribe to a prop before core is initialized.".to_string());
         }
+        self.core
+            .subscribe_to_prop_by_path(&path.path, origin)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Remove a subscription created by `subscribe_to_prop_by_path`.
+    /// Returns `false` if there was no such subscription.
+    pub fn unsubscribe_from_prop(&mut self, subscription_id: SubscriptionId) -> bool {
+        self.core.unsubscribe_from_prop(subscription_id)
+    }
+
+    /// Get the subscribed props whose values changed since they were last reported
+    /// (e.g., after an undo or redo, whose responses do not include subscribed prop changes).
+    pub fn get_subscribed_prop_changes(&mut self) -> Result<SubscribedPropChanges, String> {
+        Ok(SubscribedPropChanges {
+            changes: self
+                .core
+                .get_subscribed_prop_changes()
+                .map_err(|err| err.to_string())?,
+        })
+    }
+
+    pub fn calculate_root_names(&self) -> Result<RootNames, String> {
+        Ok(RootNames {
+            names: self
+                .core
+                .calculate_root_names()
+                .map_err(|err| err.to_string())?,
+        })
     }
 
     pub fn return_dast(&mut self) -> Result<FlatDastRoot, String> {
         if !self.initialized {
-            let _flags = match &self.flags_json {
-                Some(f) => f,
+            let flags = match &self.flags_json {
+                Some(f) => {
+                    DocumentFlags::from_json(f).map_err(|err| format!("Invalid flags: {err}"))?
+                }
                 None => return Err("Cannot create core before flags are set.".to_string()),
             };
             let dast_root = match &self.dast_root {
@@ -170,23 +314,190 @@ impl PublicDoenetMLCore {
                 None => return Err("Cannot create core before source is set.".to_string()),
             };
 
+            self.core.set_flags(flags).map_err(|err| err.to_string())?;
+
             // Create components from JSON tree and create all dependencies.
-            self.core.init_from_dast_root(dast_root);
+            if self.preserve_state {
+                self.core
+                    .reinit_from_dast_root_preserving_state(dast_root)
+                    .map_err(|err| err.to_string())?;
+            } else {
+                self.core.init_from_dast_root(dast_root);
+            }
             self.initialized = true;
         }
 
-        Ok(self.core.to_flat_dast())
+        self.core.to_flat_dast().map_err(|err| err.to_string())
+    }
+
+    /// Replace a subtree of the source with edited content.
+    /// If the edit changed only text, only the changed FlatDast elements are returned.
+    /// Otherwise, core is re-initialized (preserving the interactive state of matching components)
+    /// and the whole FlatDast is returned.
+    pub fn update_source_subtree(&mut self, subtree: DastSubtree) -> Result<SourceUpdate, String> {
+        if !self.initialized {
+            return Err("Cannot update the source before core is initialized.".to_string());
+        }
+        let update = self
+            .core
+            .update_dast_subtree(&subtree.path, subtree.content.clone())
+            .map_err(|err| err.to_string())?;
+
+        // Keep our copy of the source in sync in case core needs to be re-initialized from it.
+        if let Some(dast_root) = &mut self.dast_root {
+            dast_root.replace_content(&subtree.path, subtree.content);
+        }
+
+        Ok(update)
     }
 
     /// Send an action to DoenetMLCore. This is often in response to a user
     /// interaction with a component (and requesting a change to that component, like
     /// changing the value of a slider).
     ///
-    /// Returns updates to the FlatDast.
+    /// Returns updates to the FlatDast along with whether each prop update requested by the action
+    /// was accepted, partially applied, or rejected.
     pub fn dispatch_action(&mut self, action: Action) -> Result<ActionResponse, String> {
-        Ok(ActionResponse {
-            payload: self.core.dispatch_action(action)?,
-        })
+        Ok(self
+            .core
+            .dispatch_action(action)
+            .map_err(|err| err.to_string())?
+            .into())
+    }
+
+    /// Send several actions to DoenetMLCore that must succeed or fail together.
+    /// If any action fails, none of the actions are applied.
+    ///
+    /// Returns the merged updates to the FlatDast.
+    pub fn dispatch_actions(&mut self, action_list: ActionList) -> Result<ActionResponse, String> {
+        Ok(self
+            .core
+            .dispatch_actions(action_list.actions)
+            .map_err(|err| err.to_string())?
+            .into())
+    }
+
+    /// Undo the changes made by the most recent action.
+    ///
+    /// Returns updates to the FlatDast.
+    pub fn undo(&mut self) -> Result<ActionResponse, String> {
+        Ok(self.core.undo().map_err(|err| err.to_string())?.into())
+    }
+
+    /// Redo the changes of the most recently undone action.
+    ///
+    /// Returns updates to the FlatDast.
+    pub fn redo(&mut self) -> Result<ActionResponse, String> {
+        Ok(self.core.redo().map_err(|err| err.to_string())?.into())
+    }
+
+    /// Schedule an action to be sent to DoenetMLCore once its clock has advanced by `delay` milliseconds
+    /// and then, if `interval` is given, every `interval` milliseconds until it is cancelled.
+    pub fn schedule_action(
+        &mut self,
+        action: Action,
+        delay: f64,
+        interval: Option<f64>,
+    ) -> Result<ScheduledActionId, String> {
+        self.core
+            .schedule_action(action, delay, interval)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Cancel an action scheduled with `schedule_action`.
+    /// Returns `false` if the action was not scheduled or has already been sent.
+    pub fn cancel_scheduled_action(&mut self, id: ScheduledActionId) -> Result<bool, String> {
+        self.core
+            .cancel_scheduled_action(id)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Advance the clock of the document by `ms` milliseconds, sending the actions scheduled in that time.
+    ///
+    /// Returns updates to the FlatDast.
+    pub fn advance_time(&mut self, ms: f64) -> Result<ActionResponse, String> {
+        Ok(self
+            .core
+            .advance_time(ms)
+            .map_err(|err| err.to_string())?
+            .into())
+    }
+
+    /// Advance the clock of the document by the time that has passed since `tick` was last called,
+    /// sending the actions scheduled in that time. This can be called on every animation frame.
+    ///
+    /// Returns updates to the FlatDast.
+    pub fn tick(&mut self) -> Result<ActionResponse, String> {
+        Ok(self.core.tick().map_err(|err| err.to_string())?.into())
+    }
+
+    /// The time on the clock of the document when the next scheduled action will be sent, if any.
+    pub fn get_next_scheduled_time(&self) -> Option<f64> {
+        self.core.get_next_scheduled_time()
+    }
+
+    /// Create a snapshot of the interactive state of the document (e.g., what a student has typed or dragged)
+    /// that can be saved and later restored with `apply_state_snapshot`.
+    pub fn get_state_snapshot(&self) -> Result<StateSnapshot, String> {
+        self.core
+            .get_state_snapshot()
+            .map_err(|err| err.to_string())
+    }
+
+    /// Restore the interactive state of the document from a snapshot created by `get_state_snapshot`.
+    /// The core must have been initialized (i.e., `return_dast` must have been called) from the same source.
+    ///
+    /// Returns updates to the FlatDast.
+    pub fn apply_state_snapshot(
+        &mut self,
+        snapshot: StateSnapshot,
+    ) -> Result<ActionResponse, String> {
+        if !self.initialized {
+            return Err("Cannot apply a state snapshot before core is initialized.".to_string());
+        }
+        Ok(self
+            .core
+            .apply_state_snapshot(&snapshot)
+            .map_err(|err| err.to_string())?
+            .into())
+    }
+
+    /// Start recording the actions sent to the document, along with the source and flags it was created from.
+    /// The core must have been initialized (i.e., `return_dast` must have been called).
+    pub fn start_action_recording(&mut self) -> Result<(), String> {
+        if !self.initialized {
+            return Err("Cannot record actions before core is initialized.".to_string());
+        }
+        self.core
+            .start_action_recording()
+            .map_err(|err| err.to_string())
+    }
+
+    /// Stop recording actions and return the recording started by `start_action_recording`.
+    pub fn stop_action_recording(&mut self) -> Result<ActionRecording, String> {
+        self.core
+            .stop_action_recording()
+            .ok_or_else(|| "No actions are being recorded.".to_string())
+    }
+
+    /// Re-create core from the source and flags of `recording` and send its actions again,
+    /// checking that the document is the same as when it was recorded after each action.
+    ///
+    /// Returns the FlatDast of the document after the last action.
+    pub fn replay_action_recording(
+        &mut self,
+        recording: ActionRecording,
+    ) -> Result<FlatDastRoot, String> {
+        let flags_json = serde_json::to_string(&recording.flags).map_err(|err| err.to_string())?;
+        self.dast_root = Some(recording.dast_root.clone());
+        self.flags_json = Some(flags_json);
+        // Whether or not the replay succeeds, core has been re-created from the recorded source.
+        self.initialized = true;
+
+        self.core
+            .replay_action_recording(&recording)
+            .map_err(|err| err.to_string())?;
+        self.core.to_flat_dast().map_err(|err| err.to_string())
     }
 
     pub fn _run_test(&mut self, test_name: &str) {
@@ -143,3 +143,23 @@ mod component {
     //dbg!(syn::parse_str::<ItemMod>(input).unwrap());
     // dbg!(result.to_string());
 }
+
+#[test]
+fn test_array_props_are_recorded_and_checked() {
+    let input = r#"
+        #[component(name = Point)]
+        mod component {
+            enum Props {
+                #[prop(value_type = PropValueType::Math, is_public)]
+                X,
+                #[prop(value_type = PropValueType::Math, is_public, is_array)]
+                Coords,
+            }
+        }
+    "#;
+    let result = generate_component_module(syn::parse_str(input).unwrap());
+    let formatted = pretty_print_result(&result);
+
+    assert!(formatted.contains("const PROP_IS_ARRAYS: &'static [bool] = &[false, true];"));
+    assert!(formatted.contains("crate::props::array_element_type(PropValueType::Math)"));
+}
@@ -87,6 +87,7 @@ impl ComponentModule {
             })
             .collect::<Vec<_>>();
         let prop_is_publics = self.props.get_prop_is_publics();
+        let prop_is_arrays = self.props.get_prop_is_arrays();
         let prop_value_types = self.props.get_prop_value_types();
         let default_prop = match self.props.get_default_prop_local_index() {
             Some(idx) => quote! {Some(LocalPropIdx::new(#idx))},
@@ -132,6 +133,8 @@ impl ComponentModule {
 
                 const PROP_IS_PUBLICS: &'static [bool] = &[#(#prop_is_publics),*];
 
+                const PROP_IS_ARRAYS: &'static [bool] = &[#(#prop_is_arrays),*];
+
                 const PROP_VALUE_TYPES: &'static [PropValueType] = &[#(#prop_value_types),*];
 
                 const DEFAULT_PROP: Option<LocalPropIdx> = #default_prop;
@@ -187,6 +190,9 @@ impl ComponentModule {
                 fn get_prop_is_public(&self, local_prop_idx: LocalPropIdx) -> bool {
                     Component::PROP_IS_PUBLICS[local_prop_idx.as_usize()]
                 }
+                fn get_prop_is_array(&self, local_prop_idx: LocalPropIdx) -> bool {
+                    Component::PROP_IS_ARRAYS[local_prop_idx.as_usize()]
+                }
                 fn get_prop_value_type(&self, local_prop_idx: LocalPropIdx) -> PropValueType {
                     Component::PROP_VALUE_TYPES[local_prop_idx.as_usize()].clone()
                 }
@@ -23,6 +23,8 @@ pub struct PropsVariant {
     #[darling(default)]
     pub is_public: bool,
     #[darling(default)]
+    pub is_array: bool,
+    #[darling(default)]
     pub profile: Option<Path>,
     #[darling(default)]
     pub default: bool,
@@ -143,6 +145,11 @@ impl PropsEnum {
         self.get_variants().iter().map(|x| x.is_public).collect()
     }
 
+    /// The `is_array` property of all props defined on this component
+    pub fn get_prop_is_arrays(&self) -> Vec<bool> {
+        self.get_variants().iter().map(|x| x.is_array).collect()
+    }
+
     /// The `value_type` property of all props defined on this component
     pub fn get_prop_value_types(&self) -> Vec<Path> {
         self.get_variants()
@@ -199,6 +206,12 @@ impl PropsEnum {
                 descriptions.push("- Private: this prop can only be used internally.".to_string())
             }
         }
+        if variant.is_array {
+            descriptions.push(
+                "- Array: the elements of this prop can be referenced individually by index."
+                    .to_string(),
+            );
+        }
 
         match (variant.for_render().in_graph, variant.for_render().in_text) {
             (true, true) => {
@@ -457,10 +470,37 @@ impl PropsEnum {
                 }
             });
 
+        let array_value_types = self
+            .get_variants()
+            .iter()
+            .zip(prop_names.iter())
+            .filter(|(variant, _)| variant.is_array)
+            .map(|(variant, name)| {
+                // Create the compile-time assertions.
+                // They look like
+                // ```rust
+                // if crate::props::array_element_type(PropValueType::Foo).is_none() {
+                //     panic!("Prop `{name}` is an array but `PropValueType::Foo` cannot be an array");
+                // }
+                // ```
+                let value_type = &variant.value_type;
+                let value_type_str = quote! {#value_type}.to_string().replace(' ', "");
+                let panic_string = format!(
+                    "Prop `{name}` is marked `is_array` but `{value_type_str}` has no array element type"
+                );
+                quote! {
+                    if crate::props::array_element_type(#value_type).is_none() {
+                        panic!(#panic_string);
+                    }
+                }
+            });
+
         quote! {
-            // Compile-time checks that the prop_profile and the prop_value are compatible.
+            // Compile-time checks that the prop_profile and the prop_value are compatible
+            // and that array props have a type that can be an array.
             const _: () = {
                 #(#prop_profiles_and_value_types)*
+                #(#array_value_types)*
             };
         }
     }
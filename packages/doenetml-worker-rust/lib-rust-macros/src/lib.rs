@@ -115,6 +115,8 @@ mod try_from_ref;
 /// It has the following options:
 /// - `value_type = ...` - Required; the type of the prop. It should be specified as one of the `PropValueType::...` variants.
 /// - `is_public` - Optional; if set, the prop will be accessible by a ref in the document. E.g. with `$foo.prop`.
+/// - `is_array` - Optional; if set, the prop is an array prop whose elements can be referenced individually, e.g. with `$foo.prop[2]`.
+///   The `value_type` must have an array element type (see `array_element_type`).
 /// - `profile = ...` - Optional; the profile that the prop satisfies. It should be specified as one of the `PropProfile::...` variants.
 ///   If set, this prop will match [`DataQuery`]s for the specified profile.
 /// - `default` - Optional; if set, this prop will be the default prop for the component. Only **one** prop can be the default prop.
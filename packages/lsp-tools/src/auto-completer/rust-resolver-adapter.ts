@@ -40,9 +40,10 @@ export function getElementNameAttributeValue(el: DastElement): string | null {
 /**
  * Map a thrown `resolvePath` error to a bare-reference classification.
  *
- * The Rust core throws the bare variant name as a string (`"NoReferent"` /
- * `"NonUniqueReferent"`); across a worker boundary it may instead arrive
- * wrapped in an object, so check the stringified message/value defensively.
+ * The Rust core throws an object tagged with the variant name as its `kind`
+ * (`{ kind: "NoReferent", ... }` / `{ kind: "NonUniqueReferent", ... }`);
+ * across a worker boundary it may instead arrive wrapped in an error, so
+ * check the stringified message/value defensively.
  * Anything unrecognized stays `"indeterminate"` so callers never over-claim
  * a definite verdict the resolver didn't actually give.
  */
@@ -51,6 +52,7 @@ export function classifyResolutionError(
 ): "notFound" | "multiple" | "indeterminate" {
     const candidates = [
         typeof e === "string" ? e : undefined,
+        (e as { kind?: unknown } | null | undefined)?.kind,
         (e as { message?: unknown } | null | undefined)?.message,
         (e as { value?: unknown } | null | undefined)?.value,
         (e as { name?: unknown } | null | undefined)?.name,
@@ -15,6 +15,14 @@ import type { ResolverCore } from "../auto-completer/rust-resolver-adapter";
 import { DoenetSourceObject } from "../doenet-source-object";
 import { computeContextHelp } from "./computeContextHelp";
 
+// Resolution errors as thrown by the Rust core's `resolvePath`.
+const NO_REFERENT = { kind: "NoReferent", position: null, suggestions: [] };
+const NON_UNIQUE_REFERENT = {
+    kind: "NonUniqueReferent",
+    position: null,
+    candidates: [0, 1],
+};
+
 /**
  * Build a minimal `ResolverCore` from the JS DAST: assign pre-order ids to
  * elements and return `resolveResult` from `resolvePath`.  Matches the
@@ -30,8 +38,8 @@ function createMockCore(
         originalPath: Array<{ name: string }>;
     },
     // When set, `resolvePath` throws this value — mirrors the Rust core
-    // throwing the bare variant string (`"NoReferent"` / `"NonUniqueReferent"`)
-    // for unresolvable / ambiguous references.
+    // throwing an error tagged with its `kind` (`"NoReferent"` /
+    // `"NonUniqueReferent"`) for unresolvable / ambiguous references.
     resolveError?: unknown,
 ): ResolverCore {
     const elements: Array<{
@@ -441,7 +449,7 @@ describe("computeContextHelp — unresolved bare references (resolver-backed)",
     it("reports notFound when the resolver definitively finds no referent (NoReferent)", async () => {
         const source = `<math name="m">x</math>\n$bad`;
         const completer = await buildCompleterWithAdapter(source, {
-            resolveError: "NoReferent",
+            resolveError: NO_REFERENT,
         });
         const help = await computeContextHelp(completer, source.length);
         expect(help).toEqual({
@@ -456,7 +464,7 @@ describe("computeContextHelp — unresolved bare references (resolver-backed)",
         // resolver reports the ambiguity authoritatively.
         const source = `<math name="dup">x</math><math name="dup">y</math>\n$dup`;
         const completer = await buildCompleterWithAdapter(source, {
-            resolveError: "NonUniqueReferent",
+            resolveError: NON_UNIQUE_REFERENT,
         });
         const help = await computeContextHelp(completer, source.length);
         expect(help).toEqual({
@@ -486,7 +494,7 @@ describe("computeContextHelp — unresolved member chains (resolver-backed)", ()
         // the full reference `m.sub` rather than blanking the panel.
         const source = `<math name="other">x</math>\n$m.sub`;
         const completer = await buildCompleterWithAdapter(source, {
-            resolveError: "NoReferent",
+            resolveError: NO_REFERENT,
         });
         const help = await computeContextHelp(completer, source.length);
         expect(help).toEqual({
@@ -499,7 +507,7 @@ describe("computeContextHelp — unresolved member chains (resolver-backed)", ()
     it("reports the whole-chain multiple for $s2.m when the member is ambiguous", async () => {
         const source = `<section name="s2"><math name="m">a</math><math name="m">b</math></section>\n$s2.m`;
         const completer = await buildCompleterWithAdapter(source, {
-            resolveError: "NonUniqueReferent",
+            resolveError: NON_UNIQUE_REFERENT,
         });
         const help = await computeContextHelp(completer, source.length);
         expect(help).toEqual({
@@ -515,7 +523,7 @@ describe("computeContextHelp — unresolved member chains (resolver-backed)", ()
         // verdict is reported against `s2.m`, consistently with the bare form.
         const source = `<section name="s2"><math name="m">a</math><math name="m">b</math></section>\n<module copy="$s2.m" />`;
         const completer = await buildCompleterWithAdapter(source, {
-            resolveError: "NonUniqueReferent",
+            resolveError: NON_UNIQUE_REFERENT,
         });
         const onS2 = source.indexOf("$s2.m") + 2; // cursor on the s2 segment
         const onMember = source.indexOf("$s2.m") + 4; // cursor on the .m segment
@@ -2343,3 +2343,19 @@ const out = JSON.stringify(smallerEntittyMap, null, 4) + "\n";
 console.log("Writing", out.length / 1024, "KB to", file);
 
 fs.writeFile(path.join(__dirname, "entity-map.json"), out, "utf-8");
+// The Rust parser embeds its own copy so that the crate does not read outside its package
+fs.writeFile(
+    path.join(
+        __dirname,
+        "..",
+        "..",
+        "doenetml-worker-rust",
+        "lib-doenetml-core",
+        "src",
+        "dast",
+        "parser",
+        "entity-map.json",
+    ),
+    out,
+    "utf-8",
+);